@@ -8,7 +8,8 @@ use failure::Fail;
 use hmac_sha256::HMAC;
 use openssl::{
     bn::{BigNum, BigNumContext},
-    ec::{EcGroup, EcPoint, PointConversionForm},
+    ec::{EcGroup, EcKey, EcPoint, PointConversionForm},
+    ecdsa::EcdsaSig,
     error::ErrorStack,
     hash::{hash, MessageDigest},
     nid::Nid,
@@ -60,6 +61,15 @@ pub enum Error {
     #[fail(display = "The proof is invalid")]
     InvalidProof,
 
+    #[fail(display = "The signature length is invalid")]
+    InvalidSignatureLength,
+
+    #[fail(display = "The secret key is out of range")]
+    InvalidSecretKey,
+
+    #[fail(display = "The nonce produced an invalid signature")]
+    InvalidNonce,
+
     #[fail(display = "Unknown error")]
     Unknown,
 }
@@ -314,6 +324,91 @@ impl ECECDSA {
 
         self.gamma_to_hash(&gamma_point)
     }
+
+    /// Signs `message` with a deterministic (RFC 6979) ECDSA signature.
+    ///
+    /// The message is hashed with the suite hasher (SHA-256) before signing and
+    /// the signature is returned as the fixed-size `r || s` concatenation used
+    /// by Neo, with `s` normalized to the lower half of the curve order.
+    pub fn sign(&mut self, secret_key: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+        let d = BigNum::from_slice(secret_key)?;
+        if d <= BigNum::from_u32(0)? || d >= self.order {
+            return Err(Error::InvalidSecretKey);
+        }
+
+        let digest = hash(self.hasher, message)?;
+        let e = bits2int(&digest, self.qlen)?;
+
+        // Step 1: k = nonce(x, h1), R = k * G
+        let k = self.generate_nonce(&d, message)?;
+        let r_point = self.derive_public_key_point(&k)?;
+
+        let mut x = BigNum::new()?;
+        let mut y = BigNum::new()?;
+        r_point.affine_coordinates_gfp(&self.group, &mut x, &mut y, &mut self.bn_ctx)?;
+
+        // Step 2: r = x(R) mod n
+        let mut r = BigNum::new()?;
+        r.nnmod(&x, &self.order, &mut self.bn_ctx)?;
+
+        // Step 3: s = k^-1 * (e + r * d) mod n
+        let mut k_inv = BigNum::new()?;
+        k_inv.mod_inverse(&k, &self.order, &mut self.bn_ctx)?;
+        let mut rd = BigNum::new()?;
+        rd.mod_mul(&r, &d, &self.order, &mut self.bn_ctx)?;
+        let mut e_rd = BigNum::new()?;
+        e_rd.mod_add(&e, &rd, &self.order, &mut self.bn_ctx)?;
+        let mut s = BigNum::new()?;
+        s.mod_mul(&k_inv, &e_rd, &self.order, &mut self.bn_ctx)?;
+
+        let zero = BigNum::from_u32(0)?;
+        if r == zero || s == zero {
+            return Err(Error::InvalidNonce);
+        }
+
+        // Step 4: low-S normalization, s = n - s if s > n / 2
+        let mut half_order = BigNum::new()?;
+        half_order.rshift1(&self.order)?;
+        if s > half_order {
+            let mut low_s = BigNum::new()?;
+            low_s.checked_sub(&self.order, &s)?;
+            s = low_s;
+        }
+
+        let r_string = append_leading_zeros(&r.to_vec(), self.qlen);
+        let s_string = append_leading_zeros(&s.to_vec(), self.qlen);
+
+        Ok([&r_string[..], &s_string[..]].concat())
+    }
+
+    /// Verifies a `r || s` ECDSA signature of `message` against an encoded public key.
+    ///
+    /// Both low-S and high-S signatures are accepted, as they are by the Neo node.
+    pub fn verify_signature(
+        &mut self,
+        public_key: &[u8],
+        signature: &[u8],
+        message: &[u8],
+    ) -> Result<bool, Error> {
+        let len = (self.qlen + 7) / 8;
+        if signature.len() != len * 2 {
+            return Err(Error::InvalidSignatureLength);
+        }
+
+        let r = BigNum::from_slice(&signature[0..len])?;
+        let s = BigNum::from_slice(&signature[len..])?;
+        let zero = BigNum::from_u32(0)?;
+        if r <= zero || r >= self.order || s <= zero || s >= self.order {
+            return Ok(false);
+        }
+
+        let point = EcPoint::from_bytes(&self.group, public_key, &mut self.bn_ctx)?;
+        let key = EcKey::from_public_key(&self.group, &point)?;
+        let digest = hash(self.hasher, message)?;
+
+        let sig = EcdsaSig::from_private_components(r, s)?;
+        Ok(sig.verify(&digest, &key)?)
+    }
 }
 
 
@@ -402,6 +497,99 @@ impl Ecdsa<&[u8], &[u8]> for ECECDSA {
 mod test {
     use super::*;
 
+    /// Test vector for deterministic `P256` ECDSA with SHA-256
+    /// ASCII: "sample"
+    /// Source: [RFC 6979](https://tools.ietf.org/html/rfc6979) (section A.2.5), `s` normalized to low-S
+    #[test]
+    fn test_sign_p256_sha256_sample() {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let x = hex::decode("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")
+            .unwrap();
+        let message = b"sample".to_vec();
+
+        let signature = ecdsa.sign(&x, &message).unwrap();
+        let expected_signature = hex::decode("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf37160834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9").unwrap();
+        assert_eq!(signature, expected_signature);
+    }
+
+    /// Test vector for deterministic `P256` ECDSA with SHA-256
+    /// ASCII: "test"
+    /// Source: [RFC 6979](https://tools.ietf.org/html/rfc6979) (section A.2.5)
+    #[test]
+    fn test_sign_p256_sha256_test() {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let x = hex::decode("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")
+            .unwrap();
+        let message = b"test".to_vec();
+
+        let signature = ecdsa.sign(&x, &message).unwrap();
+        let expected_signature = hex::decode("f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083").unwrap();
+        assert_eq!(signature, expected_signature);
+    }
+
+    /// Both the low-S and the original high-S form of the RFC 6979 "sample" signature verify.
+    #[test]
+    fn test_verify_signature_p256_sha256() {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let y = hex::decode("0360fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6")
+            .unwrap();
+        let message = b"sample".to_vec();
+
+        let low_s = hex::decode("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf37160834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9").unwrap();
+        let high_s = hex::decode("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8").unwrap();
+        assert!(ecdsa.verify_signature(&y, &low_s, &message).unwrap());
+        assert!(ecdsa.verify_signature(&y, &high_s, &message).unwrap());
+
+        let message2 = b"notsample".to_vec();
+        assert!(!ecdsa.verify_signature(&y, &low_s, &message2).unwrap());
+        assert!(ecdsa.verify_signature(&y, &low_s[0..63], &message).is_err());
+    }
+
+    /// Key of the NEP-2 specification vector, N3 address NS5F1Mth64bgJW4LgmEMNdEk7pVeAp3jrF
+    const NEO_PRIVATE_KEY: &str = "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5";
+    const NEO_PUBLIC_KEY: &str = "026241e7e26b38bb7154b8ad49458b97fb1c4797443dc921c5ca5774f511a2bbfc";
+    const NEO_PUBLIC_KEY_UNCOMPRESSED: &str = "046241e7e26b38bb7154b8ad49458b97fb1c4797443dc921c5ca5774f511a2bbfc9714669225b6684976776230793f0950f22557ae25976a0081bfa58319574084";
+
+    /// Sign data of a Neo N3 transaction, as `Transaction.GetSignData` builds it on MainNet:
+    /// the network magic 860833102 in little-endian followed by the transaction hash
+    /// 0xbf0b513b321eaa149210220abf5fae5f881732f9f192b70df8511ea0a07df78e in its serialized order.
+    const NEO_SIGN_DATA: &str = "4e454f338ef77da0a01e51f80db792f1f93217885fae5fbf0a22109214aa1e323b510bbf";
+
+    /// Witness signature of a Neo N3 transaction, checked against an independent P-256 implementation
+    #[test]
+    fn test_sign_neo_transaction() {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let x = hex::decode(NEO_PRIVATE_KEY).unwrap();
+        assert_eq!(hex::encode(ecdsa.derive_public_key(&x).unwrap()), NEO_PUBLIC_KEY);
+
+        let message = hex::decode(NEO_SIGN_DATA).unwrap();
+        let signature = ecdsa.sign(&x, &message).unwrap();
+        assert_eq!(
+            hex::encode(&signature),
+            "caef29456fd36d591bca16788c62f3537b52c08d880a64ec23be72d54db0119516e6a56a074239811a48568901489dc4450294dcac73122d455b4e888e913b78"
+        );
+
+        // The same transaction signed for TestNet, magic 894710606, is another message
+        let mut testnet = message.clone();
+        testnet[0..4].copy_from_slice(&894710606u32.to_le_bytes());
+        let y = hex::decode(NEO_PUBLIC_KEY).unwrap();
+        assert!(!ecdsa.verify_signature(&y, &signature, &testnet).unwrap());
+    }
+
+    /// The C# node signs with a random nonce and no S normalization, its signatures must verify
+    /// against both encodings of the public key it accepts.
+    #[test]
+    fn test_verify_neo_random_nonce_signature() {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let message = hex::decode(NEO_SIGN_DATA).unwrap();
+        let high_s = hex::decode("6fbc06018337452892636bd6f730676fd14a2bec9d002cd779f9a95c1c45d21fb7eec162b46d4c84eedc2794ff4895f4928d4b5f002928f57da238533f7dea5c").unwrap();
+
+        for key in &[NEO_PUBLIC_KEY, NEO_PUBLIC_KEY_UNCOMPRESSED] {
+            let y = hex::decode(key).unwrap();
+            assert!(ecdsa.verify_signature(&y, &high_s, &message).unwrap());
+        }
+    }
+
     #[test]
     fn test_derive_public_key() {
         let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
@@ -0,0 +1,191 @@
+use std::io::Read;
+
+use crate::error::IoError;
+use crate::serializable::Serializable;
+
+/**
+ * Little-endian reader over any `std::io::Read`, including byte slices.
+ *
+ * Every variable-length read takes a maximum length, so a malicious payload
+ * can not make the reader allocate more than the caller allows.
+ */
+pub struct BinaryReader<R: Read> {
+    inner: R,
+    position: usize,
+}
+
+impl<'a> BinaryReader<&'a [u8]> {
+    /// Reader over a byte slice.
+    pub fn from_slice(data: &'a [u8]) -> Self {
+        BinaryReader::new(data)
+    }
+
+    /// Whether the whole slice has been consumed.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<R: Read> BinaryReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    /// Number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        self.inner.read_exact(buf)?;
+        self.position += buf.len();
+        Ok(())
+    }
+
+    pub fn read_fixed_bytes(&mut self, len: usize) -> Result<Vec<u8>, IoError> {
+        let mut buf = vec![0u8; len];
+        self.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], IoError> {
+        let mut buf = [0u8; N];
+        self.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, IoError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, IoError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(IoError::Format(format!("invalid boolean {}", b))),
+        }
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, IoError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, IoError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, IoError> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i8(&mut self) -> Result<i8, IoError> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    pub fn read_i16(&mut self) -> Result<i16, IoError> {
+        Ok(i16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, IoError> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, IoError> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a var-int, failing if it is larger than `max`.
+    pub fn read_var_int(&mut self, max: u64) -> Result<u64, IoError> {
+        let value = match self.read_u8()? {
+            0xfd => self.read_u16()? as u64,
+            0xfe => self.read_u32()? as u64,
+            0xff => self.read_u64()?,
+            b => b as u64,
+        };
+
+        if value > max {
+            return Err(IoError::LengthExceeded(value, max));
+        }
+        Ok(value)
+    }
+
+    /// Reads a var-int prefixed byte array of at most `max` bytes.
+    pub fn read_var_bytes(&mut self, max: usize) -> Result<Vec<u8>, IoError> {
+        let len = self.read_var_int(max as u64)? as usize;
+        self.read_fixed_bytes(len)
+    }
+
+    /// Reads a var-int prefixed utf-8 string of at most `max` bytes.
+    pub fn read_var_string(&mut self, max: usize) -> Result<String, IoError> {
+        Ok(String::from_utf8(self.read_var_bytes(max)?)?)
+    }
+
+    /// Reads a zero padded utf-8 string stored in exactly `len` bytes.
+    pub fn read_fixed_string(&mut self, len: usize) -> Result<String, IoError> {
+        let data = self.read_fixed_bytes(len)?;
+        let end = data.iter().position(|b| *b == 0).unwrap_or(len);
+        if data[end..].iter().any(|b| *b != 0) {
+            return Err(IoError::Format("fixed string is not zero padded".to_string()));
+        }
+
+        Ok(String::from_utf8(data[..end].to_vec())?)
+    }
+
+    pub fn read_serializable<T: Serializable>(&mut self) -> Result<T, IoError> {
+        T::deserialize(self)
+    }
+
+    /// Reads a var-int prefixed list of at most `max` items.
+    pub fn read_serializable_list<T: Serializable>(&mut self, max: usize) -> Result<Vec<T>, IoError> {
+        let count = self.read_var_int(max as u64)? as usize;
+        let mut list = Vec::with_capacity(count);
+        for _ in 0..count {
+            list.push(T::deserialize(self)?);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_var_int() {
+        let data = [0xfc, 0xfd, 0xfd, 0x00, 0xfe, 0x00, 0x00, 0x01, 0x00];
+        let mut reader = BinaryReader::from_slice(&data);
+
+        assert_eq!(reader.read_var_int(u64::MAX).unwrap(), 0xfc);
+        assert_eq!(reader.read_var_int(u64::MAX).unwrap(), 0xfd);
+        assert_eq!(reader.read_var_int(u64::MAX).unwrap(), 0x10000);
+        assert!(reader.is_empty());
+        assert_eq!(reader.position(), data.len());
+    }
+
+    #[test]
+    fn test_read_guards() {
+        let mut reader = BinaryReader::from_slice(&[0x03, 0x61, 0x62, 0x63]);
+        assert!(reader.read_var_bytes(2).is_err());
+
+        let mut reader = BinaryReader::from_slice(&[0x05, 0x61]);
+        match reader.read_var_bytes(16) {
+            Err(IoError::UnexpectedEof) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut reader = BinaryReader::from_slice(&[0x03, 0x61, 0x62, 0x63]);
+        assert_eq!(reader.read_var_string(16).unwrap(), "abc");
+
+        let mut reader = BinaryReader::from_slice(&[0x61, 0x00, 0x62]);
+        assert!(reader.read_fixed_string(3).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::Write;
+
+use crate::error::IoError;
+use crate::serializable::Serializable;
+
+/// Little-endian writer over any `std::io::Write`.
+pub struct BinaryWriter<W: Write> {
+    inner: W,
+}
+
+impl BinaryWriter<Vec<u8>> {
+    /// Writer into a new in-memory buffer.
+    pub fn new_vec() -> Self {
+        BinaryWriter::new(Vec::new())
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.inner.clone()
+    }
+}
+
+impl<W: Write> BinaryWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    pub fn flush(&mut self) -> Result<(), IoError> {
+        Ok(self.inner.flush()?)
+    }
+
+    pub fn write_bytes(&mut self, value: &[u8]) -> Result<(), IoError> {
+        Ok(self.inner.write_all(value)?)
+    }
+
+    pub fn write_u8(&mut self, value: u8) -> Result<(), IoError> {
+        self.write_bytes(&[value])
+    }
+
+    pub fn write_bool(&mut self, value: bool) -> Result<(), IoError> {
+        self.write_u8(value as u8)
+    }
+
+    pub fn write_u16(&mut self, value: u16) -> Result<(), IoError> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_u32(&mut self, value: u32) -> Result<(), IoError> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_u64(&mut self, value: u64) -> Result<(), IoError> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_i8(&mut self, value: i8) -> Result<(), IoError> {
+        self.write_u8(value as u8)
+    }
+
+    pub fn write_i16(&mut self, value: i16) -> Result<(), IoError> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_i32(&mut self, value: i32) -> Result<(), IoError> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_i64(&mut self, value: i64) -> Result<(), IoError> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_var_int(&mut self, value: u64) -> Result<(), IoError> {
+        if value < 0xfd {
+            self.write_u8(value as u8)
+        } else if value <= 0xffff {
+            self.write_u8(0xfd)?;
+            self.write_u16(value as u16)
+        } else if value <= 0xffff_ffff {
+            self.write_u8(0xfe)?;
+            self.write_u32(value as u32)
+        } else {
+            self.write_u8(0xff)?;
+            self.write_u64(value)
+        }
+    }
+
+    pub fn write_var_bytes(&mut self, value: &[u8]) -> Result<(), IoError> {
+        self.write_var_int(value.len() as u64)?;
+        self.write_bytes(value)
+    }
+
+    pub fn write_var_string(&mut self, value: &str) -> Result<(), IoError> {
+        self.write_var_bytes(value.as_bytes())
+    }
+
+    /// Writes `value` zero padded to exactly `len` bytes.
+    pub fn write_fixed_string(&mut self, value: &str, len: usize) -> Result<(), IoError> {
+        let bytes = value.as_bytes();
+        if bytes.len() > len {
+            return Err(IoError::LengthExceeded(bytes.len() as u64, len as u64));
+        }
+
+        self.write_bytes(bytes)?;
+        self.write_bytes(&vec![0u8; len - bytes.len()])
+    }
+
+    pub fn write_serializable<T: Serializable>(&mut self, value: &T) -> Result<(), IoError> {
+        value.serialize(self)
+    }
+
+    /// Writes a var-int prefixed list of serializable items.
+    pub fn write_serializable_list<T: Serializable>(&mut self, value: &[T]) -> Result<(), IoError> {
+        self.write_var_int(value.len() as u64)?;
+        value.iter().try_for_each(|item| item.serialize(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_var_int() {
+        let mut writer = BinaryWriter::new_vec();
+        writer.write_var_int(0xfc).unwrap();
+        writer.write_var_int(0xfd).unwrap();
+        writer.write_var_int(0x10000).unwrap();
+        writer.write_var_int(0x1_0000_0000).unwrap();
+
+        assert_eq!(
+            writer.to_vec(),
+            vec![
+                0xfc, 0xfd, 0xfd, 0x00, 0xfe, 0x00, 0x00, 0x01, 0x00, 0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+                0x00
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_strings() {
+        let mut writer = BinaryWriter::new_vec();
+        writer.write_var_string("abc").unwrap();
+        writer.write_fixed_string("ab", 4).unwrap();
+
+        assert_eq!(writer.to_vec(), vec![0x03, 0x61, 0x62, 0x63, 0x61, 0x62, 0x00, 0x00]);
+        assert!(writer.write_fixed_string("abcde", 4).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum IoError {
+    Io(std::io::Error),
+    Format(String),
+    LengthExceeded(u64, u64),
+    InvalidUtf8,
+    UnexpectedEof,
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IoError::Io(error) => write!(f, "{}", error),
+            IoError::Format(reason) => write!(f, "invalid format: {}", reason),
+            IoError::LengthExceeded(length, max) => write!(f, "length {} exceeds the maximum of {}", length, max),
+            IoError::InvalidUtf8 => write!(f, "invalid utf-8 string"),
+            IoError::UnexpectedEof => write!(f, "unexpected end of data"),
+        }
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IoError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for IoError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::UnexpectedEof => IoError::UnexpectedEof,
+            _ => IoError::Io(error),
+        }
+    }
+}
+
+impl From<std::string::FromUtf8Error> for IoError {
+    fn from(_: std::string::FromUtf8Error) -> Self {
+        IoError::InvalidUtf8
+    }
+}
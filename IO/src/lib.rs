@@ -1,7 +1,46 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {
-        assert_eq!(2 + 2, 4);
+//! # IO
+//!
+//! Binary serialization shared by the Neo crates: little-endian readers and
+//! writers, the variable-length encodings used on the wire, and the fixed
+//! size hash types.
+
+pub mod error;
+pub use self::error::IoError;
+
+pub mod binary_reader;
+pub use self::binary_reader::BinaryReader;
+
+pub mod binary_writer;
+pub use self::binary_writer::BinaryWriter;
+
+pub mod serializable;
+pub use self::serializable::Serializable;
+
+pub mod uint160;
+pub use self::uint160::UInt160;
+
+pub mod uint256;
+pub use self::uint256::UInt256;
+
+/// Returns the number of bytes `value` takes when written as a var-int.
+pub fn var_size(value: usize) -> usize {
+    if value < 0xfd {
+        1
+    } else if value <= 0xffff {
+        3
+    } else if value <= 0xffff_ffff {
+        5
+    } else {
+        9
     }
 }
+
+/// Returns the number of bytes `value` takes when written as var-bytes.
+pub fn var_bytes_size(value: &[u8]) -> usize {
+    var_size(value.len()) + value.len()
+}
+
+/// Returns the number of bytes a list of serializable items takes.
+pub fn var_list_size<T: Serializable>(value: &[T]) -> usize {
+    var_size(value.len()) + value.iter().map(|item| item.size()).sum::<usize>()
+}
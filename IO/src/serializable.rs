@@ -0,0 +1,43 @@
+use std::io::{Read, Write};
+
+use crate::binary_reader::BinaryReader;
+use crate::binary_writer::BinaryWriter;
+use crate::error::IoError;
+
+/**
+ * A type with a canonical binary form.
+ *
+ * `size` must return exactly the number of bytes `serialize` writes, it is
+ * used to check protocol size limits without serializing.
+ */
+pub trait Serializable {
+    fn size(&self) -> usize;
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError>;
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError>
+        where
+            Self: Sized;
+
+    /// Serializes into a new byte vector.
+    fn to_array(&self) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(Vec::with_capacity(self.size()));
+        self.serialize(&mut writer)
+            .expect("writing to a Vec<u8> can not fail");
+        writer.into_inner()
+    }
+
+    /// Deserializes from a byte slice, failing if any data is left over.
+    fn from_array(data: &[u8]) -> Result<Self, IoError>
+        where
+            Self: Sized,
+    {
+        let mut reader = BinaryReader::from_slice(data);
+        let value = Self::deserialize(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(IoError::Format(format!("{} trailing bytes", reader.remaining())));
+        }
+
+        Ok(value)
+    }
+}
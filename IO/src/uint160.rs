@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::binary_reader::BinaryReader;
+use crate::binary_writer::BinaryWriter;
+use crate::error::IoError;
+use crate::serializable::Serializable;
+
+/**
+ * A 160 bit unsigned integer, used for script hashes.
+ *
+ * Bytes are stored in little-endian order, as they are serialized. The
+ * string form is big-endian hex prefixed with `0x`, as shown by the node.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Default)]
+pub struct UInt160(pub [u8; 20]);
+
+impl UInt160 {
+    pub const LENGTH: usize = 20;
+
+    pub fn zero() -> Self {
+        UInt160([0u8; 20])
+    }
+
+    /// Creates the value from little-endian bytes.
+    pub fn from_slice(data: &[u8]) -> Result<Self, IoError> {
+        if data.len() != UInt160::LENGTH {
+            return Err(IoError::Format(format!("expected {} bytes, found {}", UInt160::LENGTH, data.len())));
+        }
+
+        let mut value = [0u8; 20];
+        value.copy_from_slice(data);
+        Ok(UInt160(value))
+    }
+
+    /// Little-endian bytes.
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Big-endian bytes, in the order they are displayed.
+    pub fn to_be_bytes(&self) -> [u8; 20] {
+        let mut value = self.0;
+        value.reverse();
+        value
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|b| *b == 0)
+    }
+}
+
+impl Ord for UInt160 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for UInt160 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serializable for UInt160 {
+    fn size(&self) -> usize {
+        UInt160::LENGTH
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_bytes(&self.0)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        Ok(UInt160(reader.read_array()?))
+    }
+}
+
+impl FromStr for UInt160 {
+    type Err = IoError;
+
+    /// Parses big-endian hex, with or without the `0x` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        if s.len() != UInt160::LENGTH * 2 {
+            return Err(IoError::Format(format!("invalid UInt160 length: {}", s.len())));
+        }
+
+        let mut value = [0u8; 20];
+        hex::decode_to_slice(s, &mut value).map_err(|e| IoError::Format(format!("{:?}", e)))?;
+        value.reverse();
+        Ok(UInt160(value))
+    }
+}
+
+impl fmt::Display for UInt160 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.to_be_bytes()))
+    }
+}
+
+impl From<[u8; 20]> for UInt160 {
+    fn from(value: [u8; 20]) -> Self {
+        UInt160(value)
+    }
+}
+
+impl Serialize for UInt160 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for UInt160 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        UInt160::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUE: &str = "0xd2a4cff31913016155e38e474a2c06d08be276cf";
+
+    #[test]
+    fn test_parse() {
+        let value = UInt160::from_str(VALUE).unwrap();
+        assert_eq!(value.to_string(), VALUE);
+        assert_eq!(value.0[0], 0xcf);
+        assert_eq!(UInt160::from_str(&VALUE[2..]).unwrap(), value);
+        assert!(UInt160::from_str("0x00").is_err());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let value = UInt160::from_str(VALUE).unwrap();
+        let bytes = value.to_array();
+
+        assert_eq!(bytes.len(), value.size());
+        assert_eq!(UInt160::from_array(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_order() {
+        let mut low = [0u8; 20];
+        let mut high = [0u8; 20];
+        low[0] = 0xff;
+        high[20 - 1] = 0x01;
+
+        assert!(UInt160(low) < UInt160(high));
+        assert!(UInt160::zero() < UInt160(low));
+    }
+}
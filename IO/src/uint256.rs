@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::binary_reader::BinaryReader;
+use crate::binary_writer::BinaryWriter;
+use crate::error::IoError;
+use crate::serializable::Serializable;
+
+/**
+ * A 256 bit unsigned integer, used for block and transaction hashes.
+ *
+ * Bytes are stored in little-endian order, as they are serialized. The
+ * string form is big-endian hex prefixed with `0x`, as shown by the node.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Default)]
+pub struct UInt256(pub [u8; 32]);
+
+impl UInt256 {
+    pub const LENGTH: usize = 32;
+
+    pub fn zero() -> Self {
+        UInt256([0u8; 32])
+    }
+
+    /// Creates the value from little-endian bytes.
+    pub fn from_slice(data: &[u8]) -> Result<Self, IoError> {
+        if data.len() != UInt256::LENGTH {
+            return Err(IoError::Format(format!("expected {} bytes, found {}", UInt256::LENGTH, data.len())));
+        }
+
+        let mut value = [0u8; 32];
+        value.copy_from_slice(data);
+        Ok(UInt256(value))
+    }
+
+    /// Little-endian bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Big-endian bytes, in the order they are displayed.
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut value = self.0;
+        value.reverse();
+        value
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|b| *b == 0)
+    }
+}
+
+impl Ord for UInt256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for UInt256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serializable for UInt256 {
+    fn size(&self) -> usize {
+        UInt256::LENGTH
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_bytes(&self.0)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        Ok(UInt256(reader.read_array()?))
+    }
+}
+
+impl FromStr for UInt256 {
+    type Err = IoError;
+
+    /// Parses big-endian hex, with or without the `0x` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        if s.len() != UInt256::LENGTH * 2 {
+            return Err(IoError::Format(format!("invalid UInt256 length: {}", s.len())));
+        }
+
+        let mut value = [0u8; 32];
+        hex::decode_to_slice(s, &mut value).map_err(|e| IoError::Format(format!("{:?}", e)))?;
+        value.reverse();
+        Ok(UInt256(value))
+    }
+}
+
+impl fmt::Display for UInt256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.to_be_bytes()))
+    }
+}
+
+impl From<[u8; 32]> for UInt256 {
+    fn from(value: [u8; 32]) -> Self {
+        UInt256(value)
+    }
+}
+
+impl Serialize for UInt256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for UInt256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        UInt256::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUE: &str = "0x1f4d1defa46faa5e7b9b8d3f79a06bec777d7c26c4aa5f6f5899a291daa87c15";
+
+    #[test]
+    fn test_parse() {
+        let value = UInt256::from_str(VALUE).unwrap();
+        assert_eq!(value.to_string(), VALUE);
+        assert_eq!(value.0[0], 0x15);
+        assert_eq!(UInt256::from_str(&VALUE[2..]).unwrap(), value);
+        assert!(UInt256::from_str("0x00").is_err());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let value = UInt256::from_str(VALUE).unwrap();
+        let bytes = value.to_array();
+
+        assert_eq!(bytes.len(), value.size());
+        assert_eq!(UInt256::from_array(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_order() {
+        let mut low = [0u8; 32];
+        let mut high = [0u8; 32];
+        low[0] = 0xff;
+        high[32 - 1] = 0x01;
+
+        assert!(UInt256(low) < UInt256(high));
+        assert!(UInt256::zero() < UInt256(low));
+    }
+}
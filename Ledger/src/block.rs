@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+use std::io::{Read, Write};
+
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt256};
+use neo_tx::n3::Transaction;
+
+use crate::header::Header;
+use crate::merkle_tree::MerkleTree;
+use crate::MAX_TRANSACTIONS_PER_BLOCK;
+
+/**
+ * Neo N3 block: a header and the transactions it commits to through its
+ * merkle root.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Default)]
+pub struct Block {
+    pub header: Header,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Block {
+    pub fn new(header: Header, transactions: Vec<Transaction>) -> Self {
+        Self { header, transactions }
+    }
+
+    pub fn hash(&self) -> UInt256 {
+        self.header.hash()
+    }
+
+    pub fn index(&self) -> u32 {
+        self.header.index
+    }
+
+    /// Merkle root of the transactions carried by the block.
+    pub fn compute_merkle_root(&self) -> UInt256 {
+        let hashes: Vec<UInt256> = self.transactions.iter().map(Transaction::hash).collect();
+        MerkleTree::compute_root(&hashes)
+    }
+}
+
+impl Serializable for Block {
+    fn size(&self) -> usize {
+        self.header.size() + neo_io::var_list_size(&self.transactions)
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_serializable(&self.header)?;
+        writer.write_serializable_list(&self.transactions)
+    }
+
+    /// Deserializes a block, rejecting duplicate transactions and a wrong merkle root.
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let header: Header = reader.read_serializable()?;
+        let transactions: Vec<Transaction> = reader.read_serializable_list(MAX_TRANSACTIONS_PER_BLOCK)?;
+
+        let hashes: Vec<UInt256> = transactions.iter().map(Transaction::hash).collect();
+        let mut unique = HashSet::with_capacity(hashes.len());
+        if !hashes.iter().all(|hash| unique.insert(*hash)) {
+            return Err(IoError::Format("duplicate transaction".to_string()));
+        }
+        if MerkleTree::compute_root(&hashes) != header.merkle_root {
+            return Err(IoError::Format("merkle root mismatch".to_string()));
+        }
+
+        Ok(Block { header, transactions })
+    }
+}
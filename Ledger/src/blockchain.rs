@@ -0,0 +1,395 @@
+use std::io::Read;
+
+use neo_io::{BinaryReader, IoError, Serializable, UInt256};
+use neo_persistence::{DataCache, Store};
+use neo_tx::n3::Transaction;
+
+use crate::block::Block;
+use crate::error::LedgerError;
+use crate::header::Header;
+use crate::trimmed_block::TrimmedBlock;
+use crate::verification::{parse_standard_contract, verify_witness};
+use crate::MAX_BLOCK_SIZE;
+
+/// Block hash to trimmed block.
+pub const PREFIX_BLOCK: u8 = 5;
+/// Big-endian block index to block hash.
+pub const PREFIX_BLOCK_HASH: u8 = 9;
+/// Transaction hash to block index and transaction.
+pub const PREFIX_TRANSACTION: u8 = 11;
+/// Hash and index of the current block.
+pub const PREFIX_CURRENT_BLOCK: u8 = 12;
+
+fn key(prefix: u8, data: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(1 + data.len());
+    key.push(prefix);
+    key.extend_from_slice(data);
+    key
+}
+
+/**
+ * Chain of blocks persisted in a store.
+ *
+ * Blocks are added one at a time on top of the current block and each one
+ * is validated against its predecessor: index, previous hash, timestamp and
+ * a witness from the consensus nodes named by the previous header, one of
+ * which is the primary of the block. The genesis block has no predecessor
+ * and is accepted as is.
+ *
+ * Transactions are stored as they are, their witnesses are not verified.
+ */
+pub struct Blockchain<S: Store> {
+    store: S,
+    network: u32,
+    current: Option<Header>,
+}
+
+impl<S: Store> Blockchain<S> {
+    /// Opens the chain kept in `store`, for the network with the given magic.
+    pub fn new(store: S, network: u32) -> Result<Self, LedgerError> {
+        let mut blockchain = Self {
+            store,
+            network,
+            current: None,
+        };
+
+        if let Some(value) = blockchain.store.try_get(&[PREFIX_CURRENT_BLOCK])? {
+            let hash = UInt256::from_slice(value.get(..UInt256::LENGTH).unwrap_or_default())?;
+            let header = blockchain
+                .get_header(&hash)?
+                .ok_or_else(|| IoError::Format(format!("missing current block {}", hash)))?;
+            blockchain.current = Some(header);
+        }
+        Ok(blockchain)
+    }
+
+    pub fn network(&self) -> u32 {
+        self.network
+    }
+
+    /// Index of the current block, `None` until the genesis block is added.
+    pub fn height(&self) -> Option<u32> {
+        self.current.as_ref().map(|header| header.index)
+    }
+
+    /// Hash of the current block.
+    pub fn current_hash(&self) -> Option<UInt256> {
+        self.current.as_ref().map(Header::hash)
+    }
+
+    pub fn current_header(&self) -> Option<&Header> {
+        self.current.as_ref()
+    }
+
+    pub fn get_block_hash(&self, index: u32) -> Result<Option<UInt256>, LedgerError> {
+        match self.store.try_get(&key(PREFIX_BLOCK_HASH, &index.to_be_bytes()))? {
+            Some(value) => Ok(Some(UInt256::from_array(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn contains_block(&self, hash: &UInt256) -> Result<bool, LedgerError> {
+        Ok(self.store.contains(&key(PREFIX_BLOCK, hash.as_bytes()))?)
+    }
+
+    pub fn get_trimmed_block(&self, hash: &UInt256) -> Result<Option<TrimmedBlock>, LedgerError> {
+        match self.store.try_get(&key(PREFIX_BLOCK, hash.as_bytes()))? {
+            Some(value) => Ok(Some(TrimmedBlock::from_array(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_header(&self, hash: &UInt256) -> Result<Option<Header>, LedgerError> {
+        Ok(self.get_trimmed_block(hash)?.map(|block| block.header))
+    }
+
+    pub fn get_block(&self, hash: &UInt256) -> Result<Option<Block>, LedgerError> {
+        let trimmed = match self.get_trimmed_block(hash)? {
+            Some(trimmed) => trimmed,
+            None => return Ok(None),
+        };
+
+        let mut transactions = Vec::with_capacity(trimmed.hashes.len());
+        for tx_hash in &trimmed.hashes {
+            let (_, tx) = self
+                .get_transaction(tx_hash)?
+                .ok_or_else(|| IoError::Format(format!("missing transaction {}", tx_hash)))?;
+            transactions.push(tx);
+        }
+        Ok(Some(Block::new(trimmed.header, transactions)))
+    }
+
+    pub fn get_block_by_index(&self, index: u32) -> Result<Option<Block>, LedgerError> {
+        match self.get_block_hash(index)? {
+            Some(hash) => self.get_block(&hash),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns a transaction together with the index of the block that holds it.
+    pub fn get_transaction(&self, hash: &UInt256) -> Result<Option<(u32, Transaction)>, LedgerError> {
+        let value = match self.store.try_get(&key(PREFIX_TRANSACTION, hash.as_bytes()))? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let mut reader = BinaryReader::from_slice(&value);
+        let index = reader.read_u32()?;
+        let tx: Transaction = reader.read_serializable()?;
+        Ok(Some((index, tx)))
+    }
+
+    /// Checks that `header` extends the current block.
+    pub fn verify_header(&self, header: &Header) -> Result<(), LedgerError> {
+        let prev = match &self.current {
+            Some(prev) => prev,
+            None if header.index != 0 => return Err(LedgerError::InvalidIndex(0, header.index)),
+            None if !header.prev_hash.is_zero() => return Err(LedgerError::InvalidPrevHash(header.prev_hash)),
+            None => return Ok(()),
+        };
+
+        if header.index != prev.index + 1 {
+            return Err(LedgerError::InvalidIndex(prev.index + 1, header.index));
+        }
+        if header.prev_hash != prev.hash() {
+            return Err(LedgerError::InvalidPrevHash(header.prev_hash));
+        }
+        if header.timestamp <= prev.timestamp {
+            return Err(LedgerError::InvalidTimestamp(header.timestamp, prev.timestamp));
+        }
+        if header.witness.script_hash() != prev.next_consensus {
+            return Err(LedgerError::InvalidWitness(format!(
+                "witness of {} is not from the next consensus nodes",
+                header.index
+            )));
+        }
+
+        // The witness is from the next consensus nodes, the primary is one of them.
+        let (_, validators) = parse_standard_contract(&header.witness.verification_script)
+            .ok_or_else(|| LedgerError::InvalidWitness("non-standard verification script".to_string()))?;
+        if header.primary_index as usize >= validators.len() {
+            return Err(LedgerError::InvalidPrimaryIndex(header.primary_index, validators.len()));
+        }
+
+        verify_witness(&header.witness, &header.get_sign_data(self.network))
+    }
+
+    /// Validates `block` and persists it as the new current block.
+    pub fn add_block(&mut self, block: &Block) -> Result<(), LedgerError> {
+        let hash = block.hash();
+        if self.contains_block(&hash)? {
+            return Err(LedgerError::BlockExists(hash));
+        }
+
+        self.verify_header(&block.header)?;
+        if block.compute_merkle_root() != block.header.merkle_root {
+            return Err(LedgerError::InvalidMerkleRoot(block.header.merkle_root));
+        }
+
+        let mut cache = DataCache::new(self.store.get_snapshot());
+        let index = block.index();
+        for tx in &block.transactions {
+            let mut value = index.to_le_bytes().to_vec();
+            value.extend_from_slice(&tx.to_array());
+            cache.put(&key(PREFIX_TRANSACTION, tx.hash().as_bytes()), value)?;
+        }
+        cache.add(&key(PREFIX_BLOCK, hash.as_bytes()), TrimmedBlock::from(block).to_array())?;
+        cache.add(&key(PREFIX_BLOCK_HASH, &index.to_be_bytes()), hash.to_array())?;
+
+        let mut current = hash.to_array();
+        current.extend_from_slice(&index.to_le_bytes());
+        cache.put(&[PREFIX_CURRENT_BLOCK], current)?;
+        cache.commit()?;
+
+        self.current = Some(block.header.clone());
+        Ok(())
+    }
+
+    /**
+     * Imports blocks from a chain dump in the neo-cli `chain.acc` format:
+     * an optional start index, a block count, then each block prefixed with
+     * its size. Blocks at or below the current height are skipped.
+     * Returns the number of blocks added.
+     * @param reader Chain dump.
+     * @param read_start Whether the dump starts with the index of its first block, as `chain.<start>.acc` files do.
+     */
+    pub fn import<R: Read>(&mut self, reader: R, read_start: bool) -> Result<u32, LedgerError> {
+        let mut reader = BinaryReader::new(reader);
+        let start = if read_start { reader.read_u32()? } else { 0 };
+        let count = reader.read_u32()?;
+
+        let mut imported = 0;
+        for index in (start as u64)..(start as u64 + count as u64) {
+            let size = reader.read_i32()?;
+            if size < 0 || size as usize > MAX_BLOCK_SIZE {
+                return Err(IoError::LengthExceeded(size as u64, MAX_BLOCK_SIZE as u64).into());
+            }
+            let data = reader.read_fixed_bytes(size as usize)?;
+
+            if matches!(self.height(), Some(height) if index <= height as u64) {
+                continue;
+            }
+            self.add_block(&Block::from_array(&data)?)?;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_core::protocol_version::ProtocolVersion;
+    use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
+    use neo_crypto::hex;
+    use neo_io::{BinaryWriter, UInt160};
+    use neo_persistence::MemoryStore;
+    use neo_tx::n3::{Signer, Witness};
+    use neo_vm::OpCode::OpCode;
+
+    use super::*;
+
+    const NETWORK: u32 = 860833102;
+    const PRIVATE_KEY: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+
+    fn verification_script() -> Vec<u8> {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let public_key = ecdsa.derive_public_key(&hex::decode(PRIVATE_KEY).unwrap()).unwrap();
+
+        ProtocolVersion::N3.verification_script(&public_key)
+    }
+
+    fn sign(header: &mut Header, network: u32) {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let signature = ecdsa
+            .sign(&hex::decode(PRIVATE_KEY).unwrap(), &header.get_sign_data(network))
+            .unwrap();
+
+        let mut invocation = vec![OpCode::PUSHDATA1.0, 0x40];
+        invocation.extend_from_slice(&signature);
+        header.witness = Witness::new(invocation, verification_script());
+    }
+
+    fn genesis() -> Block {
+        let header = Header {
+            timestamp: 1468595301000,
+            nonce: 2083236893,
+            next_consensus: Witness::new(vec![], verification_script()).script_hash(),
+            witness: Witness::new(vec![], vec![0x11]),
+            ..Header::default()
+        };
+        Block::new(header, vec![])
+    }
+
+    fn next(prev: &Header, transactions: Vec<Transaction>) -> Block {
+        let mut block = Block::new(
+            Header {
+                prev_hash: prev.hash(),
+                timestamp: prev.timestamp + 15000,
+                index: prev.index + 1,
+                next_consensus: prev.next_consensus,
+                ..Header::default()
+            },
+            transactions,
+        );
+        block.header.merkle_root = block.compute_merkle_root();
+        sign(&mut block.header, NETWORK);
+        block
+    }
+
+    fn transaction(nonce: u32) -> Transaction {
+        Transaction {
+            nonce,
+            valid_until_block: 100,
+            signers: vec![Signer::called_by_entry(UInt160([0x01; 20]))],
+            script: vec![0x11],
+            witnesses: vec![Witness::new(vec![], vec![0x11])],
+            ..Transaction::default()
+        }
+    }
+
+    #[test]
+    fn test_add_block() {
+        let store = MemoryStore::new();
+        let mut blockchain = Blockchain::new(store.clone(), NETWORK).unwrap();
+        assert_eq!(blockchain.height(), None);
+
+        let genesis = genesis();
+        blockchain.add_block(&genesis).unwrap();
+        let block = next(&genesis.header, vec![transaction(1), transaction(2)]);
+        blockchain.add_block(&block).unwrap();
+
+        assert_eq!(blockchain.height(), Some(1));
+        assert_eq!(blockchain.current_hash(), Some(block.hash()));
+        assert_eq!(blockchain.get_block_hash(0).unwrap(), Some(genesis.hash()));
+        assert_eq!(blockchain.get_block_by_index(1).unwrap(), Some(block.clone()));
+        assert_eq!(
+            blockchain.get_transaction(&block.transactions[1].hash()).unwrap(),
+            Some((1, block.transactions[1].clone()))
+        );
+        assert!(blockchain.add_block(&block).is_err());
+
+        // The current block is restored when the store is opened again.
+        let reopened = Blockchain::new(store, NETWORK).unwrap();
+        assert_eq!(reopened.height(), Some(1));
+        assert_eq!(reopened.current_hash(), Some(block.hash()));
+    }
+
+    #[test]
+    fn test_verify_header() {
+        let mut blockchain = Blockchain::new(MemoryStore::new(), NETWORK).unwrap();
+        let genesis = genesis();
+        assert!(blockchain.verify_header(&next(&genesis.header, vec![]).header).is_err());
+        blockchain.add_block(&genesis).unwrap();
+
+        let block = next(&genesis.header, vec![]);
+        assert!(blockchain.verify_header(&block.header).is_ok());
+
+        let mut header = block.header.clone();
+        header.index = 2;
+        sign(&mut header, NETWORK);
+        assert!(matches!(blockchain.verify_header(&header), Err(LedgerError::InvalidIndex(1, 2))));
+
+        let mut header = block.header.clone();
+        header.prev_hash = UInt256([0x01; 32]);
+        sign(&mut header, NETWORK);
+        assert!(matches!(blockchain.verify_header(&header), Err(LedgerError::InvalidPrevHash(_))));
+
+        let mut header = block.header.clone();
+        header.timestamp = genesis.header.timestamp;
+        sign(&mut header, NETWORK);
+        assert!(matches!(blockchain.verify_header(&header), Err(LedgerError::InvalidTimestamp(_, _))));
+
+        let mut header = block.header.clone();
+        header.primary_index = 1;
+        sign(&mut header, NETWORK);
+        assert!(matches!(blockchain.verify_header(&header), Err(LedgerError::InvalidPrimaryIndex(1, 1))));
+
+        let mut header = block.header.clone();
+        sign(&mut header, NETWORK + 1);
+        assert!(matches!(blockchain.verify_header(&header), Err(LedgerError::InvalidWitness(_))));
+
+        let mut block = next(&genesis.header, vec![transaction(1)]);
+        block.transactions.push(transaction(2));
+        assert!(matches!(blockchain.add_block(&block), Err(LedgerError::InvalidMerkleRoot(_))));
+    }
+
+    #[test]
+    fn test_import() {
+        let genesis = genesis();
+        let block = next(&genesis.header, vec![transaction(1)]);
+
+        let mut writer = BinaryWriter::new_vec();
+        writer.write_u32(2).unwrap();
+        for block in [&genesis, &block].iter() {
+            let data = block.to_array();
+            writer.write_i32(data.len() as i32).unwrap();
+            writer.write_bytes(&data).unwrap();
+        }
+        let dump = writer.into_inner();
+
+        let mut blockchain = Blockchain::new(MemoryStore::new(), NETWORK).unwrap();
+        assert_eq!(blockchain.import(&dump[..], false).unwrap(), 2);
+        assert_eq!(blockchain.current_hash(), Some(block.hash()));
+        assert_eq!(blockchain.import(&dump[..], false).unwrap(), 0);
+    }
+}
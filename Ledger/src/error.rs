@@ -0,0 +1,69 @@
+use std::fmt;
+
+use neo_io::{IoError, UInt256};
+use neo_persistence::StoreError;
+
+#[derive(Debug)]
+pub enum LedgerError {
+    Crate(&'static str, String),
+    Io(IoError),
+    Store(StoreError),
+    BlockExists(UInt256),
+    InvalidIndex(u32, u32),
+    InvalidPrevHash(UInt256),
+    InvalidTimestamp(u64, u64),
+    InvalidPrimaryIndex(u8, usize),
+    InvalidMerkleRoot(UInt256),
+    InvalidWitness(String),
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LedgerError::Crate(name, error) => write!(f, "{}: {}", name, error),
+            LedgerError::Io(error) => write!(f, "{}", error),
+            LedgerError::Store(error) => write!(f, "{}", error),
+            LedgerError::BlockExists(hash) => write!(f, "block already exists: {}", hash),
+            LedgerError::InvalidIndex(expected, found) => {
+                write!(f, "invalid block index, expected {} but found {}", expected, found)
+            }
+            LedgerError::InvalidPrevHash(hash) => write!(f, "invalid previous hash: {}", hash),
+            LedgerError::InvalidTimestamp(timestamp, prev) => {
+                write!(f, "block timestamp {} is not after {}", timestamp, prev)
+            }
+            LedgerError::InvalidPrimaryIndex(index, validators) => {
+                write!(f, "invalid primary index {} for {} validators", index, validators)
+            }
+            LedgerError::InvalidMerkleRoot(hash) => write!(f, "invalid merkle root: {}", hash),
+            LedgerError::InvalidWitness(reason) => write!(f, "invalid witness: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LedgerError::Io(error) => Some(error),
+            LedgerError::Store(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<IoError> for LedgerError {
+    fn from(error: IoError) -> Self {
+        LedgerError::Io(error)
+    }
+}
+
+impl From<StoreError> for LedgerError {
+    fn from(error: StoreError) -> Self {
+        LedgerError::Store(error)
+    }
+}
+
+impl From<neo_crypto::ecdsa::Error> for LedgerError {
+    fn from(error: neo_crypto::ecdsa::Error) -> Self {
+        LedgerError::Crate("ecdsa", format!("{:?}", error))
+    }
+}
@@ -0,0 +1,111 @@
+use std::io::{Read, Write};
+
+use neo_crypto::sha2::{Digest, Sha256};
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt160, UInt256};
+use neo_tx::n3::Witness;
+
+/// Size of the unsigned part of a header.
+pub const UNSIGNED_SIZE: usize = 4 + UInt256::LENGTH + UInt256::LENGTH + 8 + 8 + 4 + 1 + UInt160::LENGTH;
+
+/**
+ * Neo N3 block header.
+ *
+ * The hash of a header is the SHA-256 of its unsigned serialization, the
+ * witness signs the network magic followed by that hash and its script
+ * hash must match the `next_consensus` of the previous header.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Default)]
+pub struct Header {
+    pub version: u32,
+    pub prev_hash: UInt256,
+    pub merkle_root: UInt256,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub nonce: u64,
+    pub index: u32,
+    /// Index of the consensus node that proposed the block.
+    pub primary_index: u8,
+    /// Script hash of the consensus nodes that sign the next block.
+    pub next_consensus: UInt160,
+    pub witness: Witness,
+}
+
+impl Header {
+    fn serialize_unsigned_into<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_u32(self.version)?;
+        writer.write_serializable(&self.prev_hash)?;
+        writer.write_serializable(&self.merkle_root)?;
+        writer.write_u64(self.timestamp)?;
+        writer.write_u64(self.nonce)?;
+        writer.write_u32(self.index)?;
+        writer.write_u8(self.primary_index)?;
+        writer.write_serializable(&self.next_consensus)
+    }
+
+    /// Serializes everything but the witness, this is the hashed part.
+    pub fn serialize_unsigned(&self) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(Vec::with_capacity(UNSIGNED_SIZE));
+        self.serialize_unsigned_into(&mut writer)
+            .expect("writing to a Vec<u8> can not fail");
+        writer.into_inner()
+    }
+
+    /// SHA-256 of the unsigned header.
+    pub fn hash(&self) -> UInt256 {
+        UInt256::from_slice(&Sha256::digest(&self.serialize_unsigned())).unwrap()
+    }
+
+    /// Data the witness signs: the network magic followed by the header hash.
+    pub fn get_sign_data(&self, network: u32) -> Vec<u8> {
+        let mut data = Vec::with_capacity(4 + UInt256::LENGTH);
+        data.extend_from_slice(&network.to_le_bytes());
+        data.extend_from_slice(self.hash().as_bytes());
+        data
+    }
+}
+
+impl Serializable for Header {
+    fn size(&self) -> usize {
+        UNSIGNED_SIZE + neo_io::var_size(1) + self.witness.size()
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        self.serialize_unsigned_into(writer)?;
+        writer.write_var_int(1)?;
+        writer.write_serializable(&self.witness)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let version = reader.read_u32()?;
+        if version > 0 {
+            return Err(IoError::Format(format!("invalid version {}", version)));
+        }
+
+        let prev_hash = reader.read_serializable()?;
+        let merkle_root = reader.read_serializable()?;
+        let timestamp = reader.read_u64()?;
+        let nonce = reader.read_u64()?;
+        let index = reader.read_u32()?;
+        let primary_index = reader.read_u8()?;
+        let next_consensus = reader.read_serializable()?;
+
+        // Headers carry a list of witnesses on the wire, but always exactly one.
+        let count = reader.read_var_int(1)?;
+        if count != 1 {
+            return Err(IoError::Format(format!("expected 1 witness, found {}", count)));
+        }
+        let witness = reader.read_serializable()?;
+
+        Ok(Header {
+            version,
+            prev_hash,
+            merkle_root,
+            timestamp,
+            nonce,
+            index,
+            primary_index,
+            next_consensus,
+            witness,
+        })
+    }
+}
@@ -1,7 +1,30 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {
-        assert_eq!(2 + 2, 4);
-    }
-}
+//! # Ledger
+//!
+//! Neo N3 blocks and headers, the merkle tree over their transactions and
+//! the `Blockchain`, which validates the header chain and persists blocks
+//! through the Persistence crate.
+
+pub mod error;
+pub use self::error::LedgerError;
+
+pub mod header;
+pub use self::header::Header;
+
+pub mod block;
+pub use self::block::Block;
+
+pub mod trimmed_block;
+pub use self::trimmed_block::TrimmedBlock;
+
+pub mod merkle_tree;
+pub use self::merkle_tree::MerkleTree;
+
+pub mod verification;
+
+pub mod blockchain;
+pub use self::blockchain::Blockchain;
+
+/// Maximum number of transactions in a block.
+pub const MAX_TRANSACTIONS_PER_BLOCK: usize = u16::MAX as usize;
+/// Maximum size of a serialized block, the P2P payload limit.
+pub const MAX_BLOCK_SIZE: usize = 0x0200_0000;
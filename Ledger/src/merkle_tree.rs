@@ -0,0 +1,59 @@
+use neo_crypto::sha2::{Digest, Sha256};
+use neo_io::UInt256;
+
+/**
+ * Merkle tree over the transaction hashes of a block.
+ *
+ * Parents are the double SHA-256 of their two children, a level with an odd
+ * number of nodes pairs its last node with itself.
+ */
+pub struct MerkleTree {}
+
+impl MerkleTree {
+    /// Computes the root of `hashes`, the zero hash when there are none.
+    pub fn compute_root(hashes: &[UInt256]) -> UInt256 {
+        if hashes.is_empty() {
+            return UInt256::zero();
+        }
+
+        let mut level = hashes.to_vec();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| MerkleTree::parent(&pair[0], pair.last().unwrap()))
+                .collect();
+        }
+        level[0]
+    }
+
+    fn parent(left: &UInt256, right: &UInt256) -> UInt256 {
+        let mut data = [0u8; UInt256::LENGTH * 2];
+        data[..UInt256::LENGTH].copy_from_slice(left.as_bytes());
+        data[UInt256::LENGTH..].copy_from_slice(right.as_bytes());
+
+        UInt256::from_slice(&Sha256::digest(&Sha256::digest(&data))).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_compute_root() {
+        let hashes: Vec<UInt256> = (1..=3).map(|i| UInt256([i; 32])).collect();
+
+        assert_eq!(MerkleTree::compute_root(&[]), UInt256::zero());
+        assert_eq!(MerkleTree::compute_root(&hashes[..1]), hashes[0]);
+        assert_eq!(
+            MerkleTree::compute_root(&hashes[..2]),
+            UInt256::from_str("0xb4100303b9e99ada4b479b0bb93d9b549cb057a1c4be08896bc982debe20ce39").unwrap()
+        );
+        assert_eq!(
+            MerkleTree::compute_root(&hashes),
+            UInt256::from_str("0xacbd47d5022a6c5e954ad677df8ec221c893f871889826df53f0f1ad3f023e22").unwrap()
+        );
+    }
+}
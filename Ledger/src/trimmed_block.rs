@@ -0,0 +1,44 @@
+use std::io::{Read, Write};
+
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt256};
+
+use crate::block::Block;
+use crate::header::Header;
+use crate::MAX_TRANSACTIONS_PER_BLOCK;
+
+/**
+ * A block as it is kept in storage: the header and the hashes of its
+ * transactions, which are stored on their own.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Default)]
+pub struct TrimmedBlock {
+    pub header: Header,
+    pub hashes: Vec<UInt256>,
+}
+
+impl From<&Block> for TrimmedBlock {
+    fn from(block: &Block) -> Self {
+        Self {
+            header: block.header.clone(),
+            hashes: block.transactions.iter().map(|tx| tx.hash()).collect(),
+        }
+    }
+}
+
+impl Serializable for TrimmedBlock {
+    fn size(&self) -> usize {
+        self.header.size() + neo_io::var_list_size(&self.hashes)
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_serializable(&self.header)?;
+        writer.write_serializable_list(&self.hashes)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        Ok(TrimmedBlock {
+            header: reader.read_serializable()?,
+            hashes: reader.read_serializable_list(MAX_TRANSACTIONS_PER_BLOCK)?,
+        })
+    }
+}
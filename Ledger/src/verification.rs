@@ -0,0 +1,244 @@
+//! Verification of standard signature and multi-signature witnesses.
+//!
+//! Until witnesses can be run through the VM, only the two standard
+//! verification scripts are understood, any other script is rejected.
+
+use neo_core::neo_type::{PublicKeyBin, PUBLIC_KEY_BIN_LEN};
+use neo_core::protocol_settings::{ProtocolSettings, ProtocolVersion};
+use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
+use neo_tx::n3::Witness;
+use neo_vm::OpCode::OpCode;
+
+use crate::error::LedgerError;
+
+const SIGNATURE_LEN: usize = 64;
+
+/// Reads a count pushed by `PUSH1..PUSH16`, `PUSHINT8` or `PUSHINT16`.
+fn read_push_int(script: &[u8], pos: &mut usize) -> Option<usize> {
+    let value = match OpCode(*script.get(*pos)?) {
+        OpCode::PUSHINT8 => {
+            let value = *script.get(*pos + 1)? as i8;
+            *pos += 2;
+            value as i64
+        }
+        OpCode::PUSHINT16 => {
+            let bytes = script.get(*pos + 1..*pos + 3)?;
+            *pos += 3;
+            i16::from_le_bytes([bytes[0], bytes[1]]) as i64
+        }
+        opcode if opcode >= OpCode::PUSH1 && opcode <= OpCode::PUSH16 => {
+            *pos += 1;
+            (opcode.0 - OpCode::PUSH0.0) as i64
+        }
+        _ => return None,
+    };
+
+    if value > 0 {
+        Some(value as usize)
+    } else {
+        None
+    }
+}
+
+/// Reads a `PUSHDATA1` of exactly `len` bytes.
+fn read_push_data<'a>(script: &'a [u8], pos: &mut usize, len: usize) -> Option<&'a [u8]> {
+    if script.get(*pos) != Some(&OpCode::PUSHDATA1.0) || script.get(*pos + 1) != Some(&(len as u8)) {
+        return None;
+    }
+    let data = script.get(*pos + 2..*pos + 2 + len)?;
+    *pos += 2 + len;
+    Some(data)
+}
+
+/// Returns the public key of a single signature verification script.
+pub fn parse_signature_contract(script: &[u8]) -> Option<&[u8]> {
+    let public_key = read_push_data(script, &mut 0, PUBLIC_KEY_BIN_LEN)?;
+
+    if script == &ProtocolVersion::N3.verification_script(public_key)[..] {
+        Some(public_key)
+    } else {
+        None
+    }
+}
+
+/**
+ * Returns the threshold and public keys of a multi-signature verification script.
+ *
+ * The script must be the one `ProtocolSettings` builds for these keys, so the
+ * keys are sorted and the counts use the shortest push.
+ */
+pub fn parse_multisig_contract(script: &[u8]) -> Option<(usize, Vec<&[u8]>)> {
+    let mut pos = 0;
+    let m = read_push_int(script, &mut pos)?;
+
+    let mut public_keys = Vec::new();
+    while script.get(pos) == Some(&OpCode::PUSHDATA1.0) {
+        public_keys.push(read_push_data(script, &mut pos, PUBLIC_KEY_BIN_LEN)?);
+    }
+
+    let keys = public_keys
+        .iter()
+        .map(|key| {
+            let mut public_key: PublicKeyBin = [0u8; PUBLIC_KEY_BIN_LEN];
+            public_key.copy_from_slice(key);
+            public_key
+        })
+        .collect::<Vec<_>>();
+    match ProtocolSettings::N3_MAINNET.multisig_verification_script(m, &keys) {
+        Ok(expected) if expected == script => Some((m, public_keys)),
+        _ => None,
+    }
+}
+
+/**
+ * Returns the threshold and public keys of a standard verification script,
+ * either a single signature or a multi-signature one.
+ */
+pub fn parse_standard_contract(script: &[u8]) -> Option<(usize, Vec<&[u8]>)> {
+    match parse_signature_contract(script) {
+        Some(public_key) => Some((1, vec![public_key])),
+        None => parse_multisig_contract(script),
+    }
+}
+
+/// Returns the signatures pushed by an invocation script.
+fn parse_invocation_script(script: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut pos = 0;
+    let mut signatures = Vec::new();
+    while pos < script.len() {
+        signatures.push(read_push_data(script, &mut pos, SIGNATURE_LEN)?);
+    }
+    Some(signatures)
+}
+
+/**
+ * Verifies a witness over `sign_data`, the network magic followed by the
+ * hash of the signed item.
+ *
+ * Multi-signature witnesses must list their signatures in the same order as
+ * the public keys of the verification script, as `CheckMultisig` requires.
+ */
+pub fn verify_witness(witness: &Witness, sign_data: &[u8]) -> Result<(), LedgerError> {
+    let signatures = parse_invocation_script(&witness.invocation_script)
+        .ok_or_else(|| LedgerError::InvalidWitness("non-standard invocation script".to_string()))?;
+
+    let (m, public_keys) = parse_standard_contract(&witness.verification_script)
+        .ok_or_else(|| LedgerError::InvalidWitness("non-standard verification script".to_string()))?;
+    if signatures.len() != m {
+        return Err(LedgerError::InvalidWitness(format!(
+            "expected {} signatures, found {}",
+            m,
+            signatures.len()
+        )));
+    }
+
+    let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI)?;
+    let (mut i, mut j) = (0, 0);
+    while i < m && j < public_keys.len() {
+        if ecdsa
+            .verify_signature(public_keys[j], signatures[i], sign_data)
+            .unwrap_or(false)
+        {
+            i += 1;
+        }
+        j += 1;
+
+        if m - i > public_keys.len() - j {
+            break;
+        }
+    }
+
+    if i == m {
+        Ok(())
+    } else {
+        Err(LedgerError::InvalidWitness("signature verification failed".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_core::protocol_settings::syscall_hash;
+
+    use super::*;
+
+    const PRIVATE_KEYS: [&str; 2] = [
+        "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+        "1d9d6b11b9570e50a8511de539be9d125dda022b7d65452acc03de3aa3e87d6c",
+    ];
+
+    fn keys() -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        PRIVATE_KEYS
+            .iter()
+            .map(|key| {
+                let private_key = neo_crypto::hex::decode(key).unwrap();
+                let public_key = ecdsa.derive_public_key(&private_key).unwrap();
+                (private_key, public_key)
+            })
+            .collect()
+    }
+
+    fn push_signature(invocation: &mut Vec<u8>, private_key: &[u8], data: &[u8]) {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        invocation.extend_from_slice(&[OpCode::PUSHDATA1.0, SIGNATURE_LEN as u8]);
+        invocation.extend_from_slice(&ecdsa.sign(private_key, data).unwrap());
+    }
+
+    #[test]
+    fn test_signature_witness() {
+        let keys = keys();
+        let data = b"sign data";
+
+        let verification = ProtocolVersion::N3.verification_script(&keys[0].1);
+        assert_eq!(parse_signature_contract(&verification), Some(&keys[0].1[..]));
+        assert_eq!(verification[verification.len() - 4..], syscall_hash("System.Crypto.CheckSig"));
+
+        let mut invocation = Vec::new();
+        push_signature(&mut invocation, &keys[0].0, data);
+        let witness = Witness::new(invocation, verification);
+
+        assert!(verify_witness(&witness, data).is_ok());
+        assert!(verify_witness(&witness, b"other data").is_err());
+    }
+
+    #[test]
+    fn test_multisig_witness() {
+        let mut keys = keys();
+        let data = b"sign data";
+
+        let public_keys = keys
+            .iter()
+            .map(|(_, public_key)| {
+                let mut key: PublicKeyBin = [0u8; PUBLIC_KEY_BIN_LEN];
+                key.copy_from_slice(public_key);
+                key
+            })
+            .collect::<Vec<_>>();
+        let verification = ProtocolSettings::N3_MAINNET
+            .multisig_verification_script(2, &public_keys)
+            .unwrap();
+        let (m, parsed) = parse_multisig_contract(&verification).unwrap();
+        assert_eq!(m, 2);
+        assert_eq!(verification[verification.len() - 4..], syscall_hash("System.Crypto.CheckMultisig"));
+
+        // Sign in the order of the keys in the script.
+        keys.sort_by_key(|(_, public_key)| parsed.iter().position(|key| key == public_key));
+
+        // Keys out of order are not the standard script.
+        let mut unsorted = verification.clone();
+        unsorted[1..36].copy_from_slice(&verification[36..71]);
+        unsorted[36..71].copy_from_slice(&verification[1..36]);
+        assert!(parse_multisig_contract(&unsorted).is_none());
+
+        let mut invocation = Vec::new();
+        push_signature(&mut invocation, &keys[0].0, data);
+        push_signature(&mut invocation, &keys[1].0, data);
+        assert!(verify_witness(&Witness::new(invocation, verification.clone()), data).is_ok());
+
+        // Signatures in the wrong order do not verify.
+        let mut invocation = Vec::new();
+        push_signature(&mut invocation, &keys[1].0, data);
+        push_signature(&mut invocation, &keys[0].0, data);
+        assert!(verify_witness(&Witness::new(invocation, verification), data).is_err());
+    }
+}
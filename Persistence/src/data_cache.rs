@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use crate::error::StoreError;
+use crate::seek_direction::SeekDirection;
+use crate::store::{Entry, Snapshot};
+use crate::track_state::TrackState;
+
+/// An entry tracked by a `DataCache` together with its state.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Trackable {
+    /// Current value, `None` once deleted or when it was not found.
+    pub item: Option<Vec<u8>>,
+    pub state: TrackState,
+}
+
+impl Trackable {
+    fn new(item: Option<Vec<u8>>, state: TrackState) -> Self {
+        Self { item, state }
+    }
+}
+
+/**
+ * Write-tracking cache on top of a snapshot.
+ *
+ * Reads fall through to the snapshot, writes are kept in the cache with their
+ * `TrackState` and only reach the store when the cache is committed. Reads
+ * made through the cache see its own pending writes.
+ */
+pub struct DataCache {
+    store: Box<dyn Snapshot>,
+    dictionary: BTreeMap<Vec<u8>, Trackable>,
+}
+
+impl DataCache {
+    pub fn new(store: Box<dyn Snapshot>) -> Self {
+        Self {
+            store,
+            dictionary: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the value of `key`, looking at pending writes first.
+    pub fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        match self.dictionary.get(key) {
+            Some(trackable) => Ok(trackable.item.clone()),
+            None => self.store.try_get(key),
+        }
+    }
+
+    pub fn contains(&self, key: &[u8]) -> Result<bool, StoreError> {
+        Ok(self.try_get(key)?.is_some())
+    }
+
+    /// Adds a new entry, fails if the key already exists.
+    pub fn add(&mut self, key: &[u8], value: Vec<u8>) -> Result<(), StoreError> {
+        let state = match self.dictionary.get(key).map(|trackable| trackable.state) {
+            Some(TrackState::Deleted) => TrackState::Changed,
+            Some(TrackState::NotFound) => TrackState::Added,
+            Some(_) => return Err(StoreError::KeyExists(key.to_vec())),
+            None if self.store.contains(key)? => return Err(StoreError::KeyExists(key.to_vec())),
+            None => TrackState::Added,
+        };
+
+        self.dictionary.insert(key.to_vec(), Trackable::new(Some(value), state));
+        Ok(())
+    }
+
+    /// Sets the value of `key`, adding it if it does not exist.
+    pub fn put(&mut self, key: &[u8], value: Vec<u8>) -> Result<(), StoreError> {
+        match self.get_and_change(key)? {
+            Some(item) => *item = value,
+            None => self.add(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Deletes `key`, deleting a missing key is a no-op.
+    pub fn delete(&mut self, key: &[u8]) -> Result<(), StoreError> {
+        match self.dictionary.get_mut(key) {
+            Some(trackable) => match trackable.state {
+                TrackState::Added => {
+                    self.dictionary.remove(key);
+                }
+                TrackState::None | TrackState::Changed => {
+                    trackable.item = None;
+                    trackable.state = TrackState::Deleted;
+                }
+                TrackState::Deleted | TrackState::NotFound => {}
+            },
+            None => {
+                if self.store.contains(key)? {
+                    self.dictionary
+                        .insert(key.to_vec(), Trackable::new(None, TrackState::Deleted));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads `key` into the cache if it is not tracked yet.
+    fn track(&mut self, key: &[u8]) -> Result<&mut Trackable, StoreError> {
+        if !self.dictionary.contains_key(key) {
+            let trackable = match self.store.try_get(key)? {
+                Some(item) => Trackable::new(Some(item), TrackState::None),
+                None => Trackable::new(None, TrackState::NotFound),
+            };
+            self.dictionary.insert(key.to_vec(), trackable);
+        }
+        Ok(self.dictionary.get_mut(key).unwrap())
+    }
+
+    /// Returns the value of `key` for modification, marking it as changed.
+    pub fn get_and_change(&mut self, key: &[u8]) -> Result<Option<&mut Vec<u8>>, StoreError> {
+        let trackable = self.track(key)?;
+        if trackable.state == TrackState::None {
+            trackable.state = TrackState::Changed;
+        }
+        Ok(trackable.item.as_mut())
+    }
+
+    /// Returns the value of `key` for modification, adding `factory()` if it does not exist.
+    pub fn get_or_add<F: FnOnce() -> Vec<u8>>(&mut self, key: &[u8], factory: F) -> Result<&mut Vec<u8>, StoreError> {
+        let trackable = self.track(key)?;
+        match trackable.state {
+            TrackState::None => trackable.state = TrackState::Changed,
+            TrackState::Deleted => {
+                trackable.item = Some(factory());
+                trackable.state = TrackState::Changed;
+            }
+            TrackState::NotFound => {
+                trackable.item = Some(factory());
+                trackable.state = TrackState::Added;
+            }
+            TrackState::Added | TrackState::Changed => {}
+        }
+        Ok(trackable.item.as_mut().unwrap())
+    }
+
+    /// Returns the entries whose key starts with `prefix`, including pending writes.
+    pub fn find(&self, prefix: &[u8], direction: SeekDirection) -> Result<Vec<Entry>, StoreError> {
+        let mut entries = BTreeMap::new();
+        for item in self.store.find(prefix, SeekDirection::Forward) {
+            let (key, value) = item?;
+            if !self.dictionary.contains_key(&key) {
+                entries.insert(key, value);
+            }
+        }
+        for (key, trackable) in self.dictionary.range(prefix.to_vec()..) {
+            if !key.starts_with(prefix) {
+                break;
+            }
+            if let Some(item) = &trackable.item {
+                entries.insert(key.clone(), item.clone());
+            }
+        }
+
+        Ok(match direction {
+            SeekDirection::Forward => entries.into_iter().collect(),
+            SeekDirection::Backward => entries.into_iter().rev().collect(),
+        })
+    }
+
+    /// Entries that were added, changed or deleted since the last commit.
+    pub fn get_change_set(&self) -> impl Iterator<Item = (&Vec<u8>, &Trackable)> {
+        self.dictionary.iter().filter(|(_, trackable)| {
+            matches!(
+                trackable.state,
+                TrackState::Added | TrackState::Changed | TrackState::Deleted
+            )
+        })
+    }
+
+    /// Writes every pending change to the snapshot and commits it.
+    pub fn commit(&mut self) -> Result<(), StoreError> {
+        for (key, trackable) in self.dictionary.iter() {
+            match (trackable.state, &trackable.item) {
+                (TrackState::Added, Some(item)) | (TrackState::Changed, Some(item)) => self.store.put(key, item),
+                (TrackState::Deleted, _) => self.store.delete(key),
+                _ => {}
+            }
+        }
+        self.store.commit()?;
+        self.dictionary.clear();
+        Ok(())
+    }
+
+    /// Drops every pending change.
+    pub fn rollback(&mut self) {
+        self.dictionary.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_store::MemoryStore;
+    use crate::store::{ReadOnlyStore, Store};
+
+    fn store() -> MemoryStore {
+        let store = MemoryStore::new();
+        store.put(&[0x01, 0x01], b"a").unwrap();
+        store.put(&[0x01, 0x02], b"b").unwrap();
+        store.put(&[0x02], b"c").unwrap();
+        store
+    }
+
+    #[test]
+    fn test_track_state() {
+        let store = store();
+        let mut cache = DataCache::new(store.get_snapshot());
+
+        assert!(cache.add(&[0x01, 0x01], b"x".to_vec()).is_err());
+        cache.add(&[0x01, 0x03], b"d".to_vec()).unwrap();
+        cache.delete(&[0x01, 0x01]).unwrap();
+        cache.get_and_change(&[0x02]).unwrap().unwrap().push(b'!');
+        assert!(cache.get_and_change(&[0x03]).unwrap().is_none());
+        cache.get_or_add(&[0x04], || b"e".to_vec()).unwrap();
+
+        let changes: Vec<(Vec<u8>, TrackState)> = cache
+            .get_change_set()
+            .map(|(key, trackable)| (key.clone(), trackable.state))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (vec![0x01, 0x01], TrackState::Deleted),
+                (vec![0x01, 0x03], TrackState::Added),
+                (vec![0x02], TrackState::Changed),
+                (vec![0x04], TrackState::Added),
+            ]
+        );
+
+        // Deleting an added entry forgets it, re-adding a deleted one changes it.
+        cache.delete(&[0x04]).unwrap();
+        cache.add(&[0x01, 0x01], b"z".to_vec()).unwrap();
+        assert_eq!(cache.dictionary[&vec![0x01, 0x01]].state, TrackState::Changed);
+        assert!(!cache.dictionary.contains_key(&vec![0x04]));
+    }
+
+    #[test]
+    fn test_find() {
+        let store = store();
+        let mut cache = DataCache::new(store.get_snapshot());
+        cache.delete(&[0x01, 0x01]).unwrap();
+        cache.put(&[0x01, 0x00], b"z".to_vec()).unwrap();
+        cache.put(&[0x01, 0x02], b"y".to_vec()).unwrap();
+
+        assert_eq!(
+            cache.find(&[0x01], SeekDirection::Forward).unwrap(),
+            vec![(vec![0x01, 0x00], b"z".to_vec()), (vec![0x01, 0x02], b"y".to_vec())]
+        );
+        assert_eq!(
+            cache.find(&[0x01], SeekDirection::Backward).unwrap(),
+            vec![(vec![0x01, 0x02], b"y".to_vec()), (vec![0x01, 0x00], b"z".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_commit() {
+        let store = store();
+        let mut cache = DataCache::new(store.get_snapshot());
+        cache.delete(&[0x02]).unwrap();
+        cache.put(&[0x03], b"d".to_vec()).unwrap();
+        assert_eq!(cache.try_get(&[0x03]).unwrap(), Some(b"d".to_vec()));
+        assert!(store.contains(&[0x02]).unwrap());
+
+        cache.commit().unwrap();
+        assert!(!store.contains(&[0x02]).unwrap());
+        assert_eq!(store.try_get(&[0x03]).unwrap(), Some(b"d".to_vec()));
+        assert_eq!(cache.get_change_set().count(), 0);
+    }
+
+    /// A cache reused after a commit reads what it wrote, with either backend.
+    #[test]
+    fn test_reuse_after_commit() {
+        let memory = store();
+        let sled = crate::sled_store::SledStore::temporary().unwrap();
+        for snapshot in [memory.get_snapshot(), sled.get_snapshot()] {
+            let mut cache = DataCache::new(snapshot);
+            cache.put(&[0x03], b"d".to_vec()).unwrap();
+            cache.commit().unwrap();
+            assert_eq!(cache.try_get(&[0x03]).unwrap(), Some(b"d".to_vec()));
+
+            cache.delete(&[0x03]).unwrap();
+            cache.commit().unwrap();
+            assert_eq!(cache.try_get(&[0x03]).unwrap(), None);
+            assert!(cache.add(&[0x03], b"e".to_vec()).is_ok());
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum StoreError {
+    Crate(&'static str, String),
+    KeyExists(Vec<u8>),
+    KeyNotFound(Vec<u8>),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Crate(name, error) => write!(f, "{}: {}", name, error),
+            StoreError::KeyExists(key) => write!(f, "key already exists: {:?}", key),
+            StoreError::KeyNotFound(key) => write!(f, "key not found: {:?}", key),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<sled::Error> for StoreError {
+    fn from(error: sled::Error) -> Self {
+        StoreError::Crate("sled", format!("{:?}", error))
+    }
+}
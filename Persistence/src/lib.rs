@@ -1,7 +1,26 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {
-        assert_eq!(2 + 2, 4);
-    }
-}
+//! # Persistence
+//!
+//! Key-value storage for chain data: the `Store`/`Snapshot` abstraction,
+//! an in-memory and a sled backed implementation, and the `DataCache`
+//! that tracks changes on top of a snapshot until they are committed.
+
+pub mod error;
+pub use self::error::StoreError;
+
+pub mod seek_direction;
+pub use self::seek_direction::SeekDirection;
+
+pub mod store;
+pub use self::store::{Entry, ReadOnlyStore, SeekIterator, Snapshot, Store};
+
+pub mod memory_store;
+pub use self::memory_store::{MemorySnapshot, MemoryStore};
+
+pub mod sled_store;
+pub use self::sled_store::{SledSnapshot, SledStore};
+
+pub mod track_state;
+pub use self::track_state::TrackState;
+
+pub mod data_cache;
+pub use self::data_cache::{DataCache, Trackable};
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::{Arc, RwLock};
+
+use crate::error::StoreError;
+use crate::seek_direction::SeekDirection;
+use crate::store::{ReadOnlyStore, SeekIterator, Snapshot, Store};
+
+type Map = BTreeMap<Vec<u8>, Vec<u8>>;
+
+fn seek_map(map: &Map, key: &[u8], direction: SeekDirection) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let entries = |(k, v): (&Vec<u8>, &Vec<u8>)| (k.clone(), v.clone());
+    match direction {
+        SeekDirection::Forward => map.range(key.to_vec()..).map(entries).collect(),
+        SeekDirection::Backward if key.is_empty() => map.iter().rev().map(entries).collect(),
+        SeekDirection::Backward => map
+            .range((Bound::Unbounded, Bound::Included(key.to_vec())))
+            .rev()
+            .map(entries)
+            .collect(),
+    }
+}
+
+/**
+ * Store kept entirely in memory, mostly useful for tests and for replaying
+ * short chains.
+ *
+ * The map is copied on write: taking a snapshot only shares it, and the first
+ * write to the store while a snapshot still shares the map copies it once.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    inner: Arc<RwLock<Arc<Map>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ReadOnlyStore for MemoryStore {
+    fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.inner.read().unwrap().get(key).cloned())
+    }
+
+    fn seek<'a>(&'a self, key: &[u8], direction: SeekDirection) -> SeekIterator<'a> {
+        let entries = seek_map(&self.inner.read().unwrap(), key, direction);
+        Box::new(entries.into_iter().map(Ok))
+    }
+}
+
+impl Store for MemoryStore {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        Arc::make_mut(&mut self.inner.write().unwrap()).insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<(), StoreError> {
+        Arc::make_mut(&mut self.inner.write().unwrap()).remove(key);
+        Ok(())
+    }
+
+    fn get_snapshot(&self) -> Box<dyn Snapshot> {
+        Box::new(MemorySnapshot {
+            store: self.inner.clone(),
+            view: self.inner.read().unwrap().clone(),
+            batch: BTreeMap::new(),
+        })
+    }
+}
+
+/// Point-in-time view of a `MemoryStore` with a write batch, reading the store as of its last commit.
+#[derive(Debug)]
+pub struct MemorySnapshot {
+    store: Arc<RwLock<Arc<Map>>>,
+    view: Arc<Map>,
+    batch: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl ReadOnlyStore for MemorySnapshot {
+    fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.view.get(key).cloned())
+    }
+
+    fn seek<'a>(&'a self, key: &[u8], direction: SeekDirection) -> SeekIterator<'a> {
+        Box::new(seek_map(&self.view, key, direction).into_iter().map(Ok))
+    }
+}
+
+impl Snapshot for MemorySnapshot {
+    fn put(&mut self, key: &[u8], value: &[u8]) {
+        self.batch.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn delete(&mut self, key: &[u8]) {
+        self.batch.insert(key.to_vec(), None);
+    }
+
+    fn commit(&mut self) -> Result<(), StoreError> {
+        let mut store = self.store.write().unwrap();
+        // Drops the view first, so that the map isn't copied when only the snapshot shared it.
+        self.view = Arc::default();
+        let map = Arc::make_mut(&mut store);
+        for (key, value) in std::mem::take(&mut self.batch) {
+            match value {
+                Some(value) => map.insert(key, value),
+                None => map.remove(&key),
+            };
+        }
+        self.view = store.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(iter: SeekIterator) -> Vec<Vec<u8>> {
+        iter.map(|item| item.unwrap().0).collect()
+    }
+
+    #[test]
+    fn test_seek() {
+        let store = MemoryStore::new();
+        for key in [vec![0x00, 0x01], vec![0x00, 0x02], vec![0x01], vec![0x01, 0x00], vec![0x02]].iter() {
+            store.put(key, &[0xff]).unwrap();
+        }
+
+        assert_eq!(keys(store.seek(&[0x01], SeekDirection::Forward)), vec![vec![0x01], vec![0x01, 0x00], vec![0x02]]);
+        assert_eq!(keys(store.seek(&[0x01], SeekDirection::Backward)), vec![vec![0x01], vec![0x00, 0x02], vec![0x00, 0x01]]);
+        assert_eq!(keys(store.seek(&[], SeekDirection::Backward)).len(), 5);
+
+        assert_eq!(keys(store.find(&[0x00], SeekDirection::Forward)), vec![vec![0x00, 0x01], vec![0x00, 0x02]]);
+        assert_eq!(keys(store.find(&[0x00], SeekDirection::Backward)), vec![vec![0x00, 0x02], vec![0x00, 0x01]]);
+        assert_eq!(keys(store.find(&[0x01], SeekDirection::Backward)), vec![vec![0x01, 0x00], vec![0x01]]);
+    }
+
+    #[test]
+    fn test_snapshot_commit() {
+        let store = MemoryStore::new();
+        store.put(b"a", b"1").unwrap();
+
+        let mut snapshot = store.get_snapshot();
+        snapshot.put(b"b", b"2");
+        snapshot.delete(b"a");
+        store.put(b"c", b"3").unwrap();
+
+        assert_eq!(snapshot.try_get(b"a").unwrap(), Some(b"1".to_vec()));
+        assert_eq!(snapshot.try_get(b"c").unwrap(), None);
+        assert_eq!(store.try_get(b"b").unwrap(), None);
+
+        snapshot.commit().unwrap();
+        assert_eq!(store.try_get(b"a").unwrap(), None);
+        assert_eq!(store.try_get(b"b").unwrap(), Some(b"2".to_vec()));
+        assert_eq!(store.try_get(b"c").unwrap(), Some(b"3".to_vec()));
+
+        // Once committed, the snapshot reads the store, its own writes included.
+        assert_eq!(snapshot.try_get(b"a").unwrap(), None);
+        assert_eq!(snapshot.try_get(b"b").unwrap(), Some(b"2".to_vec()));
+        assert_eq!(snapshot.try_get(b"c").unwrap(), Some(b"3".to_vec()));
+    }
+}
@@ -0,0 +1,9 @@
+/// Direction of a store seek.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Default)]
+pub enum SeekDirection {
+    /// Keys greater than or equal to the seek key, in ascending order.
+    #[default]
+    Forward,
+    /// Keys less than or equal to the seek key, in descending order.
+    Backward,
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use crate::error::StoreError;
+use crate::seek_direction::SeekDirection;
+use crate::store::{Entry, ReadOnlyStore, SeekIterator, Snapshot, Store};
+
+fn entries(item: sled::Result<(sled::IVec, sled::IVec)>) -> Result<Entry, StoreError> {
+    let (key, value) = item?;
+    Ok((key.to_vec(), value.to_vec()))
+}
+
+fn seek_tree<'a>(tree: &'a sled::Tree, key: &[u8], direction: SeekDirection) -> SeekIterator<'a> {
+    match direction {
+        SeekDirection::Forward => Box::new(tree.range(key.to_vec()..).map(entries)),
+        SeekDirection::Backward if key.is_empty() => Box::new(tree.iter().rev().map(entries)),
+        SeekDirection::Backward => Box::new(tree.range(..=key.to_vec()).rev().map(entries)),
+    }
+}
+
+/**
+ * On-disk store backed by sled.
+ */
+#[derive(Debug, Clone)]
+pub struct SledStore {
+    db: sled::Db,
+}
+
+impl SledStore {
+    /// Opens or creates the store at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StoreError> {
+        Ok(Self { db: sled::open(path)? })
+    }
+
+    /// Opens a store that is removed when dropped.
+    pub fn temporary() -> Result<Self, StoreError> {
+        Ok(Self {
+            db: sled::Config::new().temporary(true).open()?,
+        })
+    }
+
+    /// Flushes every pending write to disk.
+    pub fn flush(&self) -> Result<(), StoreError> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+impl ReadOnlyStore for SledStore {
+    fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.db.get(key)?.map(|value| value.to_vec()))
+    }
+
+    fn seek<'a>(&'a self, key: &[u8], direction: SeekDirection) -> SeekIterator<'a> {
+        seek_tree(&self.db, key, direction)
+    }
+}
+
+impl Store for SledStore {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.db.insert(key, value)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<(), StoreError> {
+        self.db.remove(key)?;
+        Ok(())
+    }
+
+    fn get_snapshot(&self) -> Box<dyn Snapshot> {
+        Box::new(SledSnapshot {
+            db: self.db.clone(),
+            batch: sled::Batch::default(),
+        })
+    }
+}
+
+/**
+ * Write batch over a `SledStore`, applied atomically on commit.
+ *
+ * sled has no point-in-time reads, so reads through this snapshot see the
+ * store as it is at read time rather than when the snapshot was taken.
+ */
+pub struct SledSnapshot {
+    db: sled::Db,
+    batch: sled::Batch,
+}
+
+impl ReadOnlyStore for SledSnapshot {
+    fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.db.get(key)?.map(|value| value.to_vec()))
+    }
+
+    fn seek<'a>(&'a self, key: &[u8], direction: SeekDirection) -> SeekIterator<'a> {
+        seek_tree(&self.db, key, direction)
+    }
+}
+
+impl Snapshot for SledSnapshot {
+    fn put(&mut self, key: &[u8], value: &[u8]) {
+        self.batch.insert(key, value);
+    }
+
+    fn delete(&mut self, key: &[u8]) {
+        self.batch.remove(key);
+    }
+
+    fn commit(&mut self) -> Result<(), StoreError> {
+        self.db.apply_batch(std::mem::take(&mut self.batch))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sled_store() {
+        let store = SledStore::temporary().unwrap();
+        store.put(&[0x01, 0x01], b"a").unwrap();
+        store.put(&[0x01, 0x02], b"b").unwrap();
+        store.put(&[0x02], b"c").unwrap();
+
+        let found: Vec<Vec<u8>> = store
+            .find(&[0x01], SeekDirection::Backward)
+            .map(|item| item.unwrap().1)
+            .collect();
+        assert_eq!(found, vec![b"b".to_vec(), b"a".to_vec()]);
+
+        let mut snapshot = store.get_snapshot();
+        snapshot.delete(&[0x02]);
+        snapshot.put(&[0x03], b"d");
+        assert!(store.contains(&[0x02]).unwrap());
+
+        snapshot.commit().unwrap();
+        assert!(!store.contains(&[0x02]).unwrap());
+        assert_eq!(store.try_get(&[0x03]).unwrap(), Some(b"d".to_vec()));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::error::StoreError;
+use crate::seek_direction::SeekDirection;
+
+/// A `(key, value)` pair.
+pub type Entry = (Vec<u8>, Vec<u8>);
+
+/// Entries returned by a seek.
+pub type SeekIterator<'a> = Box<dyn Iterator<Item = Result<Entry, StoreError>> + 'a>;
+
+/**
+ * Read access to a key-value store.
+ */
+pub trait ReadOnlyStore {
+    fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError>;
+
+    fn contains(&self, key: &[u8]) -> Result<bool, StoreError> {
+        Ok(self.try_get(key)?.is_some())
+    }
+
+    /**
+     * Seeks from `key` in the given direction.
+     *
+     * Forward returns every key greater than or equal to `key`, backward every
+     * key less than or equal to it. A backward seek with an empty key starts
+     * from the last entry of the store.
+     */
+    fn seek<'a>(&'a self, key: &[u8], direction: SeekDirection) -> SeekIterator<'a>;
+
+    /// Returns the entries whose key starts with `prefix`, in the given direction.
+    fn find<'a>(&'a self, prefix: &[u8], direction: SeekDirection) -> SeekIterator<'a> {
+        let prefix = prefix.to_vec();
+        match direction {
+            SeekDirection::Forward => {
+                let iter = self.seek(&prefix, direction);
+                Box::new(iter.take_while(move |item| match item {
+                    Ok((key, _)) => key.starts_with(&prefix),
+                    Err(_) => true,
+                }))
+            }
+            SeekDirection::Backward => {
+                let start = next_prefix(&prefix).unwrap_or_default();
+                let iter = self.seek(&start, direction);
+                let skip = prefix.clone();
+                Box::new(
+                    iter.skip_while(move |item| matches!(item, Ok((key, _)) if !key.starts_with(&skip)))
+                        .take_while(move |item| match item {
+                            Ok((key, _)) => key.starts_with(&prefix),
+                            Err(_) => true,
+                        }),
+                )
+            }
+        }
+    }
+}
+
+/**
+ * A persistent store. Writes made directly on the store are applied
+ * immediately, writes made through a snapshot are applied on commit.
+ */
+pub trait Store: ReadOnlyStore + Send + Sync {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StoreError>;
+
+    fn delete(&self, key: &[u8]) -> Result<(), StoreError>;
+
+    fn get_snapshot(&self) -> Box<dyn Snapshot>;
+}
+
+/**
+ * A view of a store that buffers writes and applies them atomically on commit.
+ *
+ * Reads go to the view the snapshot was taken from, buffered writes are not
+ * visible until committed. Use a `DataCache` to read your own writes.
+ */
+pub trait Snapshot: ReadOnlyStore {
+    fn put(&mut self, key: &[u8], value: &[u8]);
+
+    fn delete(&mut self, key: &[u8]);
+
+    /// Atomically applies every buffered write to the store.
+    fn commit(&mut self) -> Result<(), StoreError>;
+}
+
+/// Smallest key greater than every key starting with `prefix`, if any.
+pub(crate) fn next_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut next = prefix.to_vec();
+    while let Some(last) = next.pop() {
+        if last < 0xff {
+            next.push(last + 1);
+            return Some(next);
+        }
+    }
+    None
+}
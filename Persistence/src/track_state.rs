@@ -0,0 +1,14 @@
+/// State of an entry tracked by a `DataCache`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum TrackState {
+    /// Read from the store and not modified.
+    None,
+    /// Not present in the store, added to the cache.
+    Added,
+    /// Present in the store, modified in the cache.
+    Changed,
+    /// Present in the store, deleted in the cache.
+    Deleted,
+    /// Looked up and not present in the store.
+    NotFound,
+}
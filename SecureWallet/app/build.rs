@@ -0,0 +1,21 @@
+use std::env;
+
+fn main() {
+    let sdk_dir = env::var("SGX_SDK").unwrap_or_else(|_| "/opt/sgxsdk".to_string());
+    let mode = env::var("SGX_MODE").unwrap_or_else(|_| "HW".to_string());
+    let lib_dir = env::var("SECURE_WALLET_LIB_DIR").unwrap_or_else(|_| "../build/lib".to_string());
+
+    println!("cargo:rerun-if-env-changed=SGX_SDK");
+    println!("cargo:rerun-if-env-changed=SGX_MODE");
+    println!("cargo:rerun-if-env-changed=SECURE_WALLET_LIB_DIR");
+
+    // The untrusted bridge generated from Enclave.edl.
+    println!("cargo:rustc-link-search=native={}", lib_dir);
+    println!("cargo:rustc-link-lib=static=Enclave_u");
+
+    println!("cargo:rustc-link-search=native={}/lib64", sdk_dir);
+    match mode.as_ref() {
+        "HW" => println!("cargo:rustc-link-lib=dylib=sgx_urts"),
+        _ => println!("cargo:rustc-link-lib=dylib=sgx_urts_sim"),
+    }
+}
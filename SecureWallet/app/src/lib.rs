@@ -0,0 +1,361 @@
+//! The untrusted side of the wallet enclave.
+//!
+//! [`SecureWallet`] creates the enclave and wraps its ECALLs. It implements
+//! `neo_wallet::signer::Enclave`, so an `EnclaveSigner` over it signs
+//! transactions with a key that never leaves the enclave.
+//!
+//! A loaded key can be sealed and the sealed wallet stored on disk; only the
+//! enclave can unseal it again, the host never sees the plain key.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use failure::Fail;
+use sgx_types::{sgx_attributes_t, sgx_enclave_id_t, sgx_launch_token_t, sgx_misc_attribute_t, sgx_status_t};
+use sgx_urts::SgxEnclave;
+
+use neo_core::neo_type::{PrivateKeyBin, PublicKeyBin, PUBLIC_KEY_BIN_LEN};
+use neo_core::ProtocolSettings;
+use neo_wallet::nep6::{Nep6Error, Nep6Wallet};
+use neo_wallet::signer::{Enclave, Signature};
+use neo_wallet::signing::{SigningError, SIGNATURE_LEN};
+
+/// Enclaves are built with `DisableDebug` off, see Enclave.config.xml.
+const DEBUG: i32 = 1;
+
+/// Longest address `ecall_get_address` may return.
+const MAX_ADDRESS_LEN: usize = 64;
+
+/// Room for a sealed wallet, the `sgx_sealed_data_t` header and a key with its version.
+const MAX_SEALED_LEN: usize = 1024;
+
+/// Who can unseal a sealed wallet.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SealPolicy {
+    /// Only the same enclave build, a new build of the enclave cannot read the wallet.
+    MrEnclave,
+    /// Any enclave signed with the same key, so the wallet survives enclave upgrades.
+    MrSigner,
+}
+
+impl SealPolicy {
+    /// The SGX key policy, `SGX_KEYPOLICY_MRENCLAVE` or `SGX_KEYPOLICY_MRSIGNER`.
+    fn key_policy(self) -> u16 {
+        match self {
+            SealPolicy::MrEnclave => 0x0001,
+            SealPolicy::MrSigner => 0x0002,
+        }
+    }
+}
+
+extern "C" {
+    fn ecall_load_from_nep6(
+        eid: sgx_enclave_id_t,
+        retval: *mut sgx_status_t,
+        wallet: *const u8,
+        wallet_len: usize,
+        address: *const u8,
+        address_len: usize,
+        passphrase: *const u8,
+        passphrase_len: usize,
+        protocol_version: u8,
+    ) -> sgx_status_t;
+
+    fn ecall_load_from_nep2(
+        eid: sgx_enclave_id_t,
+        retval: *mut sgx_status_t,
+        nep2: *const u8,
+        nep2_len: usize,
+        passphrase: *const u8,
+        passphrase_len: usize,
+        protocol_version: u8,
+        address_version: u8,
+    ) -> sgx_status_t;
+
+    fn ecall_load_from_wif(
+        eid: sgx_enclave_id_t,
+        retval: *mut sgx_status_t,
+        wif: *const u8,
+        wif_len: usize,
+    ) -> sgx_status_t;
+
+    fn ecall_load_from_prikey(eid: sgx_enclave_id_t, retval: *mut sgx_status_t, private_key: *const u8)
+        -> sgx_status_t;
+
+    fn ecall_sign_tx(
+        eid: sgx_enclave_id_t,
+        retval: *mut sgx_status_t,
+        message: *const u8,
+        message_len: usize,
+        signature: *mut u8,
+    ) -> sgx_status_t;
+
+    fn ecall_get_address(
+        eid: sgx_enclave_id_t,
+        retval: *mut sgx_status_t,
+        protocol_version: u8,
+        address_version: u8,
+        address: *mut u8,
+        address_cap: usize,
+        address_len: *mut usize,
+    ) -> sgx_status_t;
+
+    fn ecall_get_pubkey(eid: sgx_enclave_id_t, retval: *mut sgx_status_t, public_key: *mut u8) -> sgx_status_t;
+
+    fn ecall_seal_wallet(
+        eid: sgx_enclave_id_t,
+        retval: *mut sgx_status_t,
+        key_policy: u16,
+        sealed: *mut u8,
+        sealed_cap: usize,
+        sealed_len: *mut usize,
+    ) -> sgx_status_t;
+
+    fn ecall_unseal_wallet(
+        eid: sgx_enclave_id_t,
+        retval: *mut sgx_status_t,
+        sealed: *const u8,
+        sealed_len: usize,
+    ) -> sgx_status_t;
+}
+
+/**
+ * A wallet enclave holding at most one key.
+ *
+ * Loading a key replaces the previous one, and the key is destroyed with
+ * the enclave when this is dropped.
+ */
+pub struct SecureWallet {
+    enclave: SgxEnclave,
+}
+
+impl SecureWallet {
+    /// Creates the enclave from the signed enclave file at `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, SecureWalletError> {
+        let mut launch_token: sgx_launch_token_t = [0; 1024];
+        let mut launch_token_updated = 0;
+        let mut misc_attr = sgx_misc_attribute_t {
+            secs_attr: sgx_attributes_t { flags: 0, xfrm: 0 },
+            misc_select: 0,
+        };
+
+        let enclave = SgxEnclave::create(
+            path.as_ref(),
+            DEBUG,
+            &mut launch_token,
+            &mut launch_token_updated,
+            &mut misc_attr,
+        )
+        .map_err(SecureWalletError::Sgx)?;
+
+        Ok(Self { enclave })
+    }
+
+    /**
+     * Loads the key of the account `address` of `wallet`, it is decrypted inside the enclave.
+     * The protocol of the account is the one of its address version, see `ProtocolSettings::from_address`.
+     */
+    pub fn load_from_nep6(
+        &self,
+        wallet: &Nep6Wallet,
+        address: &str,
+        passphrase: &str,
+    ) -> Result<(), SecureWalletError> {
+        let settings = ProtocolSettings::from_address(address).ok_or(SecureWalletError::InvalidInput)?;
+        let json = wallet.to_json()?;
+        self.call(|eid, retval| unsafe {
+            ecall_load_from_nep6(
+                eid,
+                retval,
+                json.as_ptr(),
+                json.len(),
+                address.as_ptr(),
+                address.len(),
+                passphrase.as_ptr(),
+                passphrase.len(),
+                settings.version as u8,
+            )
+        })
+    }
+
+    /// Loads a NEP-2 key encrypted with the default scrypt parameters for an address of the network of `settings`.
+    pub fn load_from_nep2(
+        &self,
+        nep2: &str,
+        passphrase: &str,
+        settings: &ProtocolSettings,
+    ) -> Result<(), SecureWalletError> {
+        self.call(|eid, retval| unsafe {
+            ecall_load_from_nep2(
+                eid,
+                retval,
+                nep2.as_ptr(),
+                nep2.len(),
+                passphrase.as_ptr(),
+                passphrase.len(),
+                settings.version as u8,
+                settings.address_version,
+            )
+        })
+    }
+
+    pub fn load_from_wif(&self, wif: &str) -> Result<(), SecureWalletError> {
+        self.call(|eid, retval| unsafe { ecall_load_from_wif(eid, retval, wif.as_ptr(), wif.len()) })
+    }
+
+    pub fn load_from_private_key(&self, private_key: &PrivateKeyBin) -> Result<(), SecureWalletError> {
+        self.call(|eid, retval| unsafe { ecall_load_from_prikey(eid, retval, private_key.as_ptr()) })
+    }
+
+    pub fn public_key(&self) -> Result<PublicKeyBin, SecureWalletError> {
+        let mut public_key = [0u8; PUBLIC_KEY_BIN_LEN];
+        self.call(|eid, retval| unsafe { ecall_get_pubkey(eid, retval, public_key.as_mut_ptr()) })?;
+        Ok(public_key)
+    }
+
+    /// The address of the loaded key on the network of `settings`.
+    pub fn address(&self, settings: &ProtocolSettings) -> Result<String, SecureWalletError> {
+        let mut address = [0u8; MAX_ADDRESS_LEN];
+        let mut address_len = 0;
+        self.call(|eid, retval| unsafe {
+            ecall_get_address(
+                eid,
+                retval,
+                settings.version as u8,
+                settings.address_version,
+                address.as_mut_ptr(),
+                address.len(),
+                &mut address_len,
+            )
+        })?;
+
+        String::from_utf8(address[..address_len].to_vec())
+            .map_err(|error| SecureWalletError::Crate("std::string", format!("{:?}", error)))
+    }
+
+    /// Signs the SHA-256 of `message` with the loaded key.
+    pub fn sign(&self, message: &[u8]) -> Result<Signature, SecureWalletError> {
+        let mut signature = [0u8; SIGNATURE_LEN];
+        self.call(|eid, retval| unsafe {
+            ecall_sign_tx(eid, retval, message.as_ptr(), message.len(), signature.as_mut_ptr())
+        })?;
+        Ok(signature)
+    }
+
+    /// Seals the loaded key, the sealed wallet can only be unsealed by the enclaves `policy` allows.
+    pub fn seal_wallet(&self, policy: SealPolicy) -> Result<Vec<u8>, SecureWalletError> {
+        let mut sealed = vec![0u8; MAX_SEALED_LEN];
+        let mut sealed_len = 0;
+        self.call(|eid, retval| unsafe {
+            ecall_seal_wallet(
+                eid,
+                retval,
+                policy.key_policy(),
+                sealed.as_mut_ptr(),
+                sealed.len(),
+                &mut sealed_len,
+            )
+        })?;
+
+        sealed.truncate(sealed_len);
+        Ok(sealed)
+    }
+
+    /// Loads a key from a wallet sealed by [`SecureWallet::seal_wallet`].
+    pub fn unseal_wallet(&self, sealed: &[u8]) -> Result<(), SecureWalletError> {
+        let result =
+            self.call(|eid, retval| unsafe { ecall_unseal_wallet(eid, retval, sealed.as_ptr(), sealed.len()) });
+        match result {
+            // The MAC of a sealed wallet fails if it was tampered with or sealed by an enclave the policy excludes.
+            Err(SecureWalletError::WrongPassphrase) => Err(SecureWalletError::InvalidSealedWallet),
+            result => result,
+        }
+    }
+
+    /// Seals the loaded key into the file `path`.
+    pub fn save_sealed<P: AsRef<Path>>(&self, path: P, policy: SealPolicy) -> Result<(), SecureWalletError> {
+        Ok(fs::write(path, self.seal_wallet(policy)?)?)
+    }
+
+    /// Loads the key sealed in the file `path`.
+    pub fn load_sealed<P: AsRef<Path>>(&self, path: P) -> Result<(), SecureWalletError> {
+        self.unseal_wallet(&fs::read(path)?)
+    }
+
+    /// Makes an ECALL, failing with the status of the call if it did not reach the enclave, else with its result.
+    fn call<F>(&self, ecall: F) -> Result<(), SecureWalletError>
+    where
+        F: FnOnce(sgx_enclave_id_t, *mut sgx_status_t) -> sgx_status_t,
+    {
+        let mut retval = sgx_status_t::SGX_SUCCESS;
+        match ecall(self.enclave.geteid(), &mut retval) {
+            sgx_status_t::SGX_SUCCESS => {}
+            status => return Err(SecureWalletError::Sgx(status)),
+        }
+
+        match retval {
+            sgx_status_t::SGX_SUCCESS => Ok(()),
+            sgx_status_t::SGX_ERROR_INVALID_PARAMETER => Err(SecureWalletError::InvalidInput),
+            sgx_status_t::SGX_ERROR_MAC_MISMATCH => Err(SecureWalletError::WrongPassphrase),
+            sgx_status_t::SGX_ERROR_INVALID_STATE => Err(SecureWalletError::NoKey),
+            sgx_status_t::SGX_ERROR_INVALID_VERSION => Err(SecureWalletError::UnsupportedSealVersion),
+            status => Err(SecureWalletError::Sgx(status)),
+        }
+    }
+}
+
+impl Enclave for SecureWallet {
+    fn get_public_key(&self) -> Result<PublicKeyBin, SigningError> {
+        Ok(self.public_key()?)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        Ok(SecureWallet::sign(self, message)?)
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum SecureWalletError {
+    #[fail(display = "{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[fail(display = "enclave error: {:?}", _0)]
+    Sgx(sgx_status_t),
+
+    #[fail(display = "{}", _0)]
+    Nep6(Nep6Error),
+
+    #[fail(display = "invalid key, wallet or account")]
+    InvalidInput,
+
+    #[fail(display = "wrong passphrase")]
+    WrongPassphrase,
+
+    #[fail(display = "no key is loaded in the enclave")]
+    NoKey,
+
+    #[fail(display = "the sealed wallet is corrupted or was sealed by another enclave")]
+    InvalidSealedWallet,
+
+    #[fail(display = "unsupported sealed wallet version")]
+    UnsupportedSealVersion,
+}
+
+impl From<io::Error> for SecureWalletError {
+    fn from(error: io::Error) -> Self {
+        SecureWalletError::Crate("std::io", format!("{:?}", error))
+    }
+}
+
+impl From<Nep6Error> for SecureWalletError {
+    fn from(error: Nep6Error) -> Self {
+        SecureWalletError::Nep6(error)
+    }
+}
+
+impl From<SecureWalletError> for SigningError {
+    fn from(error: SecureWalletError) -> Self {
+        SigningError::Crate("sgx", error.to_string())
+    }
+}
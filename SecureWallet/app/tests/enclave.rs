@@ -0,0 +1,239 @@
+//! Runs the ECALLs against the signed enclave, `make test SGX_MODE=SIM` builds it
+//! and runs these on machines without SGX.
+
+use std::env;
+
+use neo_core::neo_type::PrivateKeyBin;
+use neo_core::ProtocolSettings;
+use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
+use neo_crypto::hex;
+use neo_secure_wallet::{SealPolicy, SecureWallet, SecureWalletError};
+use neo_wallet::account::AccountJSON;
+use neo_wallet::nep2::{Nep2, ScryptParams};
+use neo_wallet::nep6::Nep6Wallet;
+use neo_wallet::signer::{EnclaveSigner, Signer};
+use neo_wallet::signing::verify_message;
+
+/// The vectors `neo_wallet` is tested against, the enclave doesn't give out the private key.
+#[allow(dead_code)]
+#[path = "../../../Wallets/src/test_vectors.rs"]
+mod test_vectors;
+
+const PRIVATE_KEY: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+const PUBLIC_KEY: &str = "0360fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6";
+const WIF: &str = "L3ymBCnnfiRCFkMhNoY2fneJGEZH9BMLcmVnwVTbzocfBgrzkM28";
+
+/// Half the order of secp256r1, the largest low-S `s`.
+const HALF_ORDER: &str = "7fffffff800000007fffffffffffffffde737d56d38bcf4279dce5617e3192a8";
+
+fn create() -> SecureWallet {
+    let path = env::var("SECURE_WALLET_ENCLAVE").unwrap_or_else(|_| "../build/enclave.signed.so".to_string());
+    SecureWallet::create(path).unwrap()
+}
+
+fn private_key() -> PrivateKeyBin {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&hex::decode(PRIVATE_KEY).unwrap());
+    key
+}
+
+#[test]
+fn test_private_key() {
+    let wallet = create();
+    assert!(matches!(wallet.public_key(), Err(SecureWalletError::NoKey)));
+    assert!(matches!(wallet.sign(b"neo"), Err(SecureWalletError::NoKey)));
+    assert!(matches!(
+        wallet.load_from_private_key(&[0u8; 32]),
+        Err(SecureWalletError::InvalidInput)
+    ));
+
+    wallet.load_from_private_key(&private_key()).unwrap();
+    let public_key = wallet.public_key().unwrap();
+    assert_eq!(hex::encode(&public_key[..]), PUBLIC_KEY);
+    for settings in [ProtocolSettings::N2_MAINNET, ProtocolSettings::N3_MAINNET].iter() {
+        assert_eq!(
+            wallet.address(settings).unwrap(),
+            settings.address_from_public_key(&public_key)
+        );
+    }
+
+    let signature = wallet.sign(b"sample").unwrap();
+    assert!(verify_message(b"sample", &signature, &public_key));
+    assert!(!verify_message(b"neo", &signature, &public_key));
+}
+
+/// The enclave signs like the Cryptography crate: RFC 6979 nonces and low-S.
+#[test]
+fn test_sign_matches_cryptography() {
+    let wallet = create();
+    wallet.load_from_private_key(&private_key()).unwrap();
+    let public_key = wallet.public_key().unwrap();
+    let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+
+    for message in [&b"sample"[..], b"test", b"neo"].iter() {
+        let signature = wallet.sign(message).unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(ecdsa.verify_signature(&public_key, &signature, message).unwrap());
+        assert_eq!(&signature[..], &ecdsa.sign(&private_key(), message).unwrap()[..]);
+        assert!(signature[32..] <= hex::decode(HALF_ORDER).unwrap()[..]);
+        assert_eq!(wallet.sign(message).unwrap(), signature);
+    }
+    // RFC 6979 A.2.5, its s is high and comes back as n - s.
+    assert_eq!(
+        hex::encode(&wallet.sign(b"sample").unwrap()),
+        "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf37160834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9"
+    );
+}
+
+#[test]
+fn test_wif() {
+    let wallet = create();
+    wallet.load_from_wif(WIF).unwrap();
+    assert_eq!(hex::encode(&wallet.public_key().unwrap()[..]), PUBLIC_KEY);
+
+    let mut wif = WIF.to_string();
+    wif.replace_range(10..11, "2");
+    assert!(matches!(
+        wallet.load_from_wif(&wif),
+        Err(SecureWalletError::InvalidInput)
+    ));
+}
+
+#[test]
+fn test_nep2() {
+    let wallet = create();
+    let settings = ProtocolSettings::N3_MAINNET;
+    let nep2 = Nep2::encrypt_with(&private_key(), "neo", &ScryptParams::default(), &settings).unwrap();
+
+    assert!(matches!(
+        wallet.load_from_nep2(&nep2, "wrong", &settings),
+        Err(SecureWalletError::WrongPassphrase)
+    ));
+    wallet.load_from_nep2(&nep2, "neo", &settings).unwrap();
+    assert_eq!(hex::encode(&wallet.public_key().unwrap()[..]), PUBLIC_KEY);
+}
+
+#[test]
+fn test_nep6() {
+    let mut nep6 = Nep6Wallet::new("test");
+    nep6.scrypt = ScryptParams { n: 2, r: 1, p: 1 };
+    let settings = ProtocolSettings::N2_MAINNET;
+    let address = settings.address_from_public_key(&hex::decode(PUBLIC_KEY).unwrap());
+    let key = Nep2::encrypt_with(&private_key(), "neo", &nep6.scrypt, &settings).unwrap();
+    nep6.add_account(AccountJSON {
+        address: address.clone(),
+        label: None,
+        is_default: true,
+        lock: false,
+        key: Some(key),
+        contract: None,
+        extra: None,
+        unknown: Default::default(),
+    })
+    .unwrap();
+
+    let wallet = create();
+    let other = ProtocolSettings::N3_MAINNET.address_from_public_key(&hex::decode(PUBLIC_KEY).unwrap());
+    assert!(matches!(
+        wallet.load_from_nep6(&nep6, &other, "neo"),
+        Err(SecureWalletError::InvalidInput)
+    ));
+    assert!(matches!(
+        wallet.load_from_nep6(&nep6, &address, "wrong"),
+        Err(SecureWalletError::WrongPassphrase)
+    ));
+    wallet.load_from_nep6(&nep6, &address, "neo").unwrap();
+    assert_eq!(wallet.address(&settings).unwrap(), address);
+}
+
+/// The enclave decodes keys and derives addresses like `neo_core` and `neo_wallet` do.
+#[test]
+fn test_shared_vectors() {
+    let wallet = create();
+    wallet.load_from_wif(test_vectors::WIF).unwrap();
+    assert_eq!(hex::encode(&wallet.public_key().unwrap()[..]), test_vectors::PUBLIC_KEY);
+    assert_eq!(
+        wallet.address(&ProtocolSettings::N2_MAINNET).unwrap(),
+        test_vectors::N2_ADDRESS
+    );
+    assert_eq!(
+        wallet.address(&ProtocolSettings::N3_MAINNET).unwrap(),
+        test_vectors::N3_ADDRESS
+    );
+
+    for (passphrase, n, r, p, address, nep2) in test_vectors::NEP2.iter() {
+        let mut nep6 = Nep6Wallet::new("test");
+        nep6.scrypt = ScryptParams { n: *n, r: *r, p: *p };
+        nep6.add_account(AccountJSON {
+            address: address.to_string(),
+            label: None,
+            is_default: true,
+            lock: false,
+            key: Some(nep2.to_string()),
+            contract: None,
+            extra: None,
+            unknown: Default::default(),
+        })
+        .unwrap();
+
+        let wallet = create();
+        wallet.load_from_nep6(&nep6, address, passphrase).unwrap();
+        assert_eq!(hex::encode(&wallet.public_key().unwrap()[..]), test_vectors::PUBLIC_KEY);
+    }
+}
+
+#[test]
+fn test_enclave_signer() {
+    let wallet = create();
+    wallet.load_from_private_key(&private_key()).unwrap();
+    let signer = EnclaveSigner::new(wallet, &ProtocolSettings::N3_TESTNET).unwrap();
+    assert_eq!(hex::encode(&signer.public_key()[..]), PUBLIC_KEY);
+
+    let hash = [7u8; 32];
+    let network = ProtocolSettings::N3_TESTNET.network;
+    let signature = signer.sign_transaction(&hash, network).unwrap();
+    let data = [&network.to_le_bytes()[..], &hash[..]].concat();
+    assert!(verify_message(&data, &signature, &signer.public_key()));
+}
+
+#[test]
+fn test_seal() {
+    let wallet = create();
+    assert!(matches!(
+        wallet.seal_wallet(SealPolicy::MrEnclave),
+        Err(SecureWalletError::NoKey)
+    ));
+    wallet.load_from_private_key(&private_key()).unwrap();
+
+    for policy in [SealPolicy::MrEnclave, SealPolicy::MrSigner].iter() {
+        let sealed = wallet.seal_wallet(*policy).unwrap();
+        assert!(!sealed.windows(32).any(|window| window == &private_key()[..]));
+
+        // A new enclave, as after a restart of the host.
+        let restored = create();
+        restored.unseal_wallet(&sealed).unwrap();
+        assert_eq!(hex::encode(&restored.public_key().unwrap()[..]), PUBLIC_KEY);
+
+        let mut tampered = sealed.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(matches!(
+            restored.unseal_wallet(&tampered),
+            Err(SecureWalletError::InvalidSealedWallet)
+        ));
+        assert!(matches!(
+            restored.unseal_wallet(&sealed[..100]),
+            Err(SecureWalletError::InvalidSealedWallet)
+        ));
+    }
+
+    let path = env::temp_dir().join(format!("neo-sealed-wallet-{}", std::process::id()));
+    wallet.save_sealed(&path, SealPolicy::MrSigner).unwrap();
+    let restored = create();
+    restored.load_sealed(&path).unwrap();
+    assert_eq!(
+        restored.address(&ProtocolSettings::default()).unwrap(),
+        wallet.address(&ProtocolSettings::default()).unwrap()
+    );
+    std::fs::remove_file(&path).unwrap();
+}
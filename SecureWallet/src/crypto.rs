@@ -0,0 +1,91 @@
+//! The primitives the wallet needs inside the enclave.
+//!
+//! SHA-256 and public keys come from the SGX trusted crypto library, which
+//! works on secp256r1 like Neo does. Its keys are little-endian, they are
+//! converted to the big-endian encodings Neo uses here so nothing else in the
+//! enclave has to care. Signatures are made with the `p256` crate instead, the
+//! SGX library only signs with a random nonce and doesn't normalize `s`.
+
+use std::prelude::v1::*;
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, KeyInit};
+use aes::Aes256;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use ripemd::{Digest, Ripemd160};
+use sgx_tcrypto::{rsgx_ecc256_pub_from_priv, rsgx_sha256_slice};
+use sgx_types::{sgx_ec256_private_t, sgx_ec256_public_t, sgx_status_t, SgxResult};
+
+/// The base58 codec of the Cryptography crate, it only needs `alloc`.
+#[allow(clippy::all)]
+pub mod base58 {
+    use std::prelude::v1::*;
+
+    include!("../../Cryptography/src/base58/mod.rs");
+}
+
+pub const PRIVATE_KEY_LEN: usize = 32;
+pub const PUBLIC_KEY_LEN: usize = 33;
+pub const SIGNATURE_LEN: usize = 64;
+
+pub fn sha256(data: &[u8]) -> SgxResult<[u8; 32]> {
+    rsgx_sha256_slice(data)
+}
+
+/// Double SHA-256, the first 4 bytes are the base58check checksum.
+pub fn checksum(data: &[u8]) -> SgxResult<[u8; 32]> {
+    sha256(&sha256(data)?)
+}
+
+/// RIPEMD-160 of the SHA-256 of `data`, the script hash of a script.
+pub fn hash160(data: &[u8]) -> SgxResult<[u8; 20]> {
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&Ripemd160::digest(sha256(data)?));
+    Ok(hash)
+}
+
+/// Decrypts `data`, a whole number of blocks, with AES-256 in ECB mode.
+pub fn aes256_ecb_decrypt(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let cipher = Aes256::new(GenericArray::from_slice(key));
+    let mut out = data.to_vec();
+    for block in out.chunks_mut(16) {
+        cipher.decrypt_block(GenericArray::from_mut_slice(block));
+    }
+    out
+}
+
+/// The compressed public key of `private_key`.
+pub fn public_key(private_key: &[u8; PRIVATE_KEY_LEN]) -> SgxResult<[u8; PUBLIC_KEY_LEN]> {
+    let public = rsgx_ecc256_pub_from_priv(&to_sgx_private_key(private_key))?;
+    Ok(compress(&public))
+}
+
+/**
+ * Signs the SHA-256 of `message` with a deterministic (RFC 6979) nonce, `s` normalized to
+ * the lower half of the curve order. The signature is the one `neo_crypto` makes.
+ * @return The signature as `r || s`, both big-endian.
+ */
+pub fn sign(message: &[u8], private_key: &[u8; PRIVATE_KEY_LEN]) -> SgxResult<[u8; SIGNATURE_LEN]> {
+    let key = SigningKey::from_bytes(private_key).map_err(|_| sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+    let signature: Signature = key.sign(message);
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    let mut out = [0u8; SIGNATURE_LEN];
+    out.copy_from_slice(signature.as_ref());
+    Ok(out)
+}
+
+fn to_sgx_private_key(private_key: &[u8; PRIVATE_KEY_LEN]) -> sgx_ec256_private_t {
+    let mut r = *private_key;
+    r.reverse();
+    sgx_ec256_private_t { r }
+}
+
+fn compress(public: &sgx_ec256_public_t) -> [u8; PUBLIC_KEY_LEN] {
+    let mut key = [0u8; PUBLIC_KEY_LEN];
+    // gy is little-endian, its parity is in its first byte.
+    key[0] = 0x02 | (public.gy[0] & 1);
+    key[1..].copy_from_slice(&public.gx);
+    key[1..].reverse();
+    key
+}
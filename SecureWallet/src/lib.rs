@@ -15,50 +15,210 @@
 // specific language governing permissions and limitations
 // under the License..
 
-#![crate_name = "helloworldsampleenclave"]
-#![crate_type = "staticlib"]
+//! The wallet enclave.
+//!
+//! A key is loaded into the enclave once, from a NEP-6 wallet, a NEP-2 key,
+//! a WIF or a raw private key, and is then only used through the ECALLs
+//! below: the untrusted side gets the public key, the address and
+//! signatures, never the private key. The key can be sealed so the
+//! untrusted side can store it and load it into a later enclave. Every ECALL
+//! returns `SGX_SUCCESS` or the status of `wallet::WalletError` it failed with.
 
+#![crate_name = "secure_wallet"]
+#![crate_type = "staticlib"]
 #![cfg_attr(not(target_env = "sgx"), no_std)]
 #![cfg_attr(target_env = "sgx", feature(rustc_private))]
+// ECALLs are only entered through the edger8r bridge, which hands them
+// enclave copies of buffers of the sizes declared in Enclave.edl.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
 
+#[macro_use]
+extern crate lazy_static;
+extern crate sgx_tcrypto;
+extern crate sgx_tseal;
 extern crate sgx_types;
 #[cfg(not(target_env = "sgx"))]
 #[macro_use]
 extern crate sgx_tstd as std;
 
-use sgx_types::*;
-use std::string::String;
-use std::vec::Vec;
-use std::io::{self, Write};
+mod crypto;
+mod seal;
+mod wallet;
+
 use std::slice;
+use std::str;
+use std::sync::SgxMutex;
+
+use sgx_types::sgx_status_t;
+
+use crate::crypto::{PUBLIC_KEY_LEN, SIGNATURE_LEN};
+use crate::wallet::protocol_version::ProtocolVersion;
+use crate::wallet::{Key, ScryptParams, WalletError};
+
+lazy_static! {
+    /// The loaded key, loading another one replaces it.
+    static ref KEY: SgxMutex<Option<Key>> = SgxMutex::new(None);
+}
+
+/// Loads the key of the account `address` of a NEP-6 wallet, given as its JSON, on a network of `protocol_version`.
+#[no_mangle]
+pub extern "C" fn ecall_load_from_nep6(
+    wallet: *const u8,
+    wallet_len: usize,
+    address: *const u8,
+    address_len: usize,
+    passphrase: *const u8,
+    passphrase_len: usize,
+    protocol_version: u8,
+) -> sgx_status_t {
+    load(|| {
+        let wallet = unsafe { to_slice(wallet, wallet_len) };
+        let address = unsafe { to_str(address, address_len) }?;
+        let passphrase = unsafe { to_str(passphrase, passphrase_len) }?;
+        Key::from_nep6(wallet, address, passphrase, to_protocol_version(protocol_version)?)
+    })
+}
 
+/**
+ * Loads a NEP-2 key encrypted with the default scrypt parameters, for an address of the networks of
+ * `protocol_version` and `address_version`.
+ */
 #[no_mangle]
-pub extern "C" fn say_something(some_string: *const u8, some_len: usize) -> sgx_status_t {
+pub extern "C" fn ecall_load_from_nep2(
+    nep2: *const u8,
+    nep2_len: usize,
+    passphrase: *const u8,
+    passphrase_len: usize,
+    protocol_version: u8,
+    address_version: u8,
+) -> sgx_status_t {
+    load(|| {
+        let nep2 = unsafe { to_str(nep2, nep2_len) }?;
+        let passphrase = unsafe { to_str(passphrase, passphrase_len) }?;
+        let version = to_protocol_version(protocol_version)?;
+        Key::from_nep2(nep2, passphrase, &ScryptParams::default(), version, address_version)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ecall_load_from_wif(wif: *const u8, wif_len: usize) -> sgx_status_t {
+    load(|| Key::from_wif(unsafe { to_str(wif, wif_len) }?))
+}
 
-    let str_slice = unsafe { slice::from_raw_parts(some_string, some_len) };
-    let _ = io::stdout().write(str_slice);
+/// Loads a raw 32 byte private key.
+#[no_mangle]
+pub extern "C" fn ecall_load_from_prikey(private_key: *const u8) -> sgx_status_t {
+    load(|| Key::from_private_key(unsafe { slice::from_raw_parts(private_key, 32) }))
+}
 
-    // A sample &'static string
-    let rust_raw_string = "This is a in-Enclave ";
-    // An array
-    let word:[u8;4] = [82, 117, 115, 116];
-    // An vector
-    let word_vec:Vec<u8> = vec![32, 115, 116, 114, 105, 110, 103, 33];
+/// Signs the SHA-256 of `message`, for a transaction the network magic followed by its hash.
+#[no_mangle]
+pub extern "C" fn ecall_sign_tx(message: *const u8, message_len: usize, signature: *mut u8) -> sgx_status_t {
+    with_key(|key| {
+        let message = unsafe { to_slice(message, message_len) };
+        let out = unsafe { slice::from_raw_parts_mut(signature, SIGNATURE_LEN) };
+        out.copy_from_slice(&key.sign(message)?);
+        Ok(())
+    })
+}
 
-    // Construct a string from &'static string
-    let mut hello_string = String::from(rust_raw_string);
+/**
+ * Writes the address of the key on the networks of `protocol_version` and `address_version` to `address`.
+ * Fails without writing if it is longer than `address_cap`.
+ */
+#[no_mangle]
+pub extern "C" fn ecall_get_address(
+    protocol_version: u8,
+    address_version: u8,
+    address: *mut u8,
+    address_cap: usize,
+    address_len: *mut usize,
+) -> sgx_status_t {
+    with_key(|key| {
+        let value = key.address(to_protocol_version(protocol_version)?, address_version)?;
+        if value.len() > address_cap {
+            return Err(WalletError::InvalidFormat);
+        }
 
-    // Iterate on word array
-    for c in word.iter() {
-        hello_string.push(*c as char);
+        let out = unsafe { slice::from_raw_parts_mut(address, value.len()) };
+        out.copy_from_slice(value.as_bytes());
+        unsafe { *address_len = value.len() };
+        Ok(())
+    })
+}
+
+/// Writes the compressed public key of the key to `public_key`.
+#[no_mangle]
+pub extern "C" fn ecall_get_pubkey(public_key: *mut u8) -> sgx_status_t {
+    with_key(|key| {
+        let out = unsafe { slice::from_raw_parts_mut(public_key, PUBLIC_KEY_LEN) };
+        out.copy_from_slice(&key.public_key());
+        Ok(())
+    })
+}
+
+/**
+ * Seals the loaded key into `sealed` with the SGX key policy `key_policy`, MRENCLAVE or MRSIGNER,
+ * and writes the length of the sealed wallet to `sealed_len`.
+ */
+#[no_mangle]
+pub extern "C" fn ecall_seal_wallet(
+    key_policy: u16,
+    sealed: *mut u8,
+    sealed_cap: usize,
+    sealed_len: *mut usize,
+) -> sgx_status_t {
+    with_key(|key| {
+        let len = unsafe { seal::seal(key, key_policy, sealed, sealed_cap) }?;
+        unsafe { *sealed_len = len };
+        Ok(())
+    })
+}
+
+/// Loads a key sealed by `ecall_seal_wallet`.
+#[no_mangle]
+pub extern "C" fn ecall_unseal_wallet(sealed: *const u8, sealed_len: usize) -> sgx_status_t {
+    load(|| unsafe { seal::unseal(sealed, sealed_len) })
+}
+
+fn load<F: FnOnce() -> Result<Key, WalletError>>(f: F) -> sgx_status_t {
+    match f() {
+        Ok(key) => match KEY.lock() {
+            Ok(mut loaded) => {
+                *loaded = Some(key);
+                sgx_status_t::SGX_SUCCESS
+            }
+            Err(_) => sgx_status_t::SGX_ERROR_UNEXPECTED,
+        },
+        Err(error) => error.into(),
     }
+}
+
+fn with_key<F: FnOnce(&Key) -> Result<(), WalletError>>(f: F) -> sgx_status_t {
+    let loaded = match KEY.lock() {
+        Ok(loaded) => loaded,
+        Err(_) => return sgx_status_t::SGX_ERROR_UNEXPECTED,
+    };
 
-    // Rust style convertion
-    hello_string += String::from_utf8(word_vec).expect("Invalid UTF-8")
-                                               .as_str();
+    match loaded.as_ref().ok_or(WalletError::NoKey).and_then(f) {
+        Ok(()) => sgx_status_t::SGX_SUCCESS,
+        Err(error) => error.into(),
+    }
+}
 
-    // Ocall to normal world for output
-    println!("{}", &hello_string);
+/// The `[in]` buffer of an ecall, edger8r passes a null pointer for an empty one.
+unsafe fn to_slice<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+unsafe fn to_str<'a>(data: *const u8, len: usize) -> Result<&'a str, WalletError> {
+    str::from_utf8(to_slice(data, len)).map_err(|_| WalletError::InvalidFormat)
+}
 
-    sgx_status_t::SGX_SUCCESS
+fn to_protocol_version(value: u8) -> Result<ProtocolVersion, WalletError> {
+    ProtocolVersion::from_u8(value).ok_or(WalletError::InvalidFormat)
 }
@@ -0,0 +1,92 @@
+//! Sealing of the loaded key, so the untrusted side can keep it on disk.
+//!
+//! A sealed wallet is an `sgx_sealed_data_t` whose encrypted text is the
+//! private key and whose additional text, authenticated but readable, is
+//! [`SEAL_MAGIC`] followed by the [`SEAL_VERSION`] of the layout.
+
+use std::prelude::v1::*;
+
+use sgx_tseal::SgxSealedData;
+use sgx_types::{
+    sgx_attributes_t, sgx_sealed_data_t, sgx_status_t, SGX_KEYPOLICY_MRENCLAVE, SGX_KEYPOLICY_MRSIGNER,
+    TSEAL_DEFAULT_FLAGSMASK, TSEAL_DEFAULT_MISCMASK,
+};
+
+use crate::crypto::PRIVATE_KEY_LEN;
+use crate::wallet::{Key, WalletError};
+
+/// Marks a sealed wallet.
+pub const SEAL_MAGIC: [u8; 4] = *b"NEOW";
+/// Version of the sealed wallet layout, bumped when it changes.
+pub const SEAL_VERSION: u8 = 1;
+
+/**
+ * Seals `key` into `out`, which must hold `out_cap` bytes.
+ * @param key_policy `SGX_KEYPOLICY_MRENCLAVE` so that only this enclave build
+ *        can unseal, or `SGX_KEYPOLICY_MRSIGNER` so that any enclave of the same signer can.
+ * @return The length of the sealed wallet.
+ *
+ * # Safety
+ * `out` must be valid for writes of `out_cap` bytes and aligned for `sgx_sealed_data_t`,
+ * as the buffers of the edger8r bridge are.
+ */
+pub unsafe fn seal(key: &Key, key_policy: u16, out: *mut u8, out_cap: usize) -> Result<usize, WalletError> {
+    if key_policy != SGX_KEYPOLICY_MRENCLAVE && key_policy != SGX_KEYPOLICY_MRSIGNER {
+        return Err(WalletError::InvalidFormat);
+    }
+
+    let additional = header();
+    let len = SgxSealedData::<[u8]>::calc_raw_sealed_data_size(additional.len() as u32, PRIVATE_KEY_LEN as u32);
+    if len == u32::MAX || len as usize > out_cap {
+        return Err(WalletError::InvalidFormat);
+    }
+
+    let attribute_mask = sgx_attributes_t {
+        flags: TSEAL_DEFAULT_FLAGSMASK,
+        xfrm: 0,
+    };
+    let sealed = SgxSealedData::<[u8]>::seal_data_ex(
+        key_policy,
+        attribute_mask,
+        TSEAL_DEFAULT_MISCMASK,
+        &additional,
+        key.private_key(),
+    )?;
+    sealed
+        .to_raw_sealed_data_t(out as *mut sgx_sealed_data_t, len)
+        .ok_or(WalletError::Sgx(sgx_status_t::SGX_ERROR_UNEXPECTED))?;
+
+    Ok(len as usize)
+}
+
+/**
+ * Unseals a wallet sealed by [`seal`], failing if it was sealed by another enclave or tampered with.
+ *
+ * # Safety
+ * `sealed` must be valid for reads of `sealed_len` bytes and aligned for `sgx_sealed_data_t`.
+ */
+pub unsafe fn unseal(sealed: *const u8, sealed_len: usize) -> Result<Key, WalletError> {
+    if sealed_len == 0 || sealed_len > u32::MAX as usize {
+        return Err(WalletError::InvalidSealedData);
+    }
+
+    let sealed = SgxSealedData::<[u8]>::from_raw_sealed_data_t(sealed as *mut sgx_sealed_data_t, sealed_len as u32)
+        .ok_or(WalletError::InvalidSealedData)?;
+    let unsealed = sealed.unseal_data().map_err(|_| WalletError::InvalidSealedData)?;
+
+    let additional = unsealed.get_additional_txt();
+    if additional.len() != SEAL_MAGIC.len() + 1 || additional[..SEAL_MAGIC.len()] != SEAL_MAGIC {
+        return Err(WalletError::InvalidSealedData);
+    }
+    if additional[SEAL_MAGIC.len()] != SEAL_VERSION {
+        return Err(WalletError::UnsupportedSealVersion);
+    }
+
+    Key::from_private_key(unsealed.get_decrypt_txt())
+}
+
+fn header() -> Vec<u8> {
+    let mut header = SEAL_MAGIC.to_vec();
+    header.push(SEAL_VERSION);
+    header
+}
@@ -0,0 +1,257 @@
+//! The key held by the enclave, and the formats it can be loaded from.
+//!
+//! Decoding and decryption follow `neo_core::KeyPair` and `neo_wallet::nep2`,
+//! so the plain key only ever exists inside the enclave. The test vectors of
+//! `neo_wallet` pin both to the same results, see `app/tests/enclave.rs`.
+
+use std::prelude::v1::*;
+
+use scrypt::{scrypt, Params};
+use serde_json::Value;
+use sgx_types::sgx_status_t;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::crypto::base58::{FromBase58, ToBase58};
+use crate::crypto::{self, PRIVATE_KEY_LEN, PUBLIC_KEY_LEN, SIGNATURE_LEN};
+
+/// The protocol versions of `neo_core` and the verification scripts of their standard accounts.
+#[allow(clippy::all)]
+pub mod protocol_version {
+    use std::prelude::v1::*;
+
+    include!("../../neo_core/src/protocol_version.rs");
+}
+
+use self::protocol_version::ProtocolVersion;
+
+const WIF_VERSION: u8 = 0x80;
+const NEP2_HEADER: [u8; 3] = [0x01, 0x42, 0xe0];
+/// Length of a base58check decoded NEP-2 key.
+const NEP2_BIN_LEN: usize = 39;
+const SCRYPT_DK_LEN: usize = 64;
+
+/// Scrypt parameters used to derive the NEP-2 encryption key.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ScryptParams {
+    pub n: u64,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl ScryptParams {
+    fn to_params(self) -> Result<Params, WalletError> {
+        if self.n < 2 || !self.n.is_power_of_two() {
+            return Err(WalletError::InvalidScryptParams);
+        }
+
+        let log_n = self.n.trailing_zeros() as u8;
+        Params::new(log_n, self.r, self.p).map_err(|_| WalletError::InvalidScryptParams)
+    }
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        Self { n: 16384, r: 8, p: 8 }
+    }
+}
+
+/// A private key and its public key, the private key is wiped when it is dropped.
+pub struct Key {
+    private_key: [u8; PRIVATE_KEY_LEN],
+    public_key: [u8; PUBLIC_KEY_LEN],
+}
+
+impl Key {
+    pub fn from_private_key(private_key: &[u8]) -> Result<Self, WalletError> {
+        if private_key.len() != PRIVATE_KEY_LEN || private_key.iter().all(|byte| *byte == 0) {
+            return Err(WalletError::InvalidKey);
+        }
+
+        let mut key = [0u8; PRIVATE_KEY_LEN];
+        key.copy_from_slice(private_key);
+        let public_key = crypto::public_key(&key).map_err(|_| WalletError::InvalidKey)?;
+
+        Ok(Self {
+            private_key: key,
+            public_key,
+        })
+    }
+
+    /// Decodes a compressed-key WIF.
+    pub fn from_wif(wif: &str) -> Result<Self, WalletError> {
+        let data = from_base58_check(wif)?;
+        if data.len() != 34 || data[0] != WIF_VERSION || data[33] != 0x01 {
+            return Err(WalletError::InvalidFormat);
+        }
+
+        Key::from_private_key(&data[1..33])
+    }
+
+    /**
+     * Decrypts a NEP-2 key. Fails if the passphrase does not match the address hash.
+     * The passphrase is NFC normalized first, like `neo_wallet::nep2` does.
+     * @param version Protocol version of the network of the key.
+     * @param address_version Address version of the network of the key, the address hash depends on both.
+     */
+    pub fn from_nep2(
+        nep2: &str,
+        passphrase: &str,
+        params: &ScryptParams,
+        version: ProtocolVersion,
+        address_version: u8,
+    ) -> Result<Self, WalletError> {
+        let decoded = from_base58_check(nep2)?;
+        if decoded.len() != NEP2_BIN_LEN || decoded[0..3] != NEP2_HEADER {
+            return Err(WalletError::InvalidFormat);
+        }
+
+        let address_hash = &decoded[3..7];
+        let encrypted = &decoded[7..39];
+
+        let params = params.to_params()?;
+        let mut passphrase = passphrase.nfc().collect::<String>().into_bytes();
+        let mut derived = vec![0u8; SCRYPT_DK_LEN];
+        let result = scrypt(&passphrase, address_hash, &params, &mut derived);
+        wipe(&mut passphrase);
+        result.map_err(|_| WalletError::InvalidScryptParams)?;
+        let mut decrypted = crypto::aes256_ecb_decrypt(&derived[32..64], encrypted);
+        for (byte, mask) in decrypted.iter_mut().zip(derived.iter()) {
+            *byte ^= mask;
+        }
+
+        let key = Key::from_private_key(&decrypted);
+        wipe(&mut decrypted);
+        wipe(&mut derived);
+        let key = key?;
+
+        // Now check that the address hashes match. If they don't, the password was wrong.
+        let address = key.address(version, address_version)?;
+        if crypto::checksum(address.as_bytes())?[0..4] != *address_hash {
+            return Err(WalletError::WrongPassphrase);
+        }
+
+        Ok(key)
+    }
+
+    /**
+     * Decrypts the key of the account `address` of a NEP-6 wallet, with the scrypt parameters of the wallet.
+     * @param version Protocol version of the network of `address`.
+     */
+    pub fn from_nep6(
+        wallet: &[u8],
+        address: &str,
+        passphrase: &str,
+        version: ProtocolVersion,
+    ) -> Result<Self, WalletError> {
+        let wallet: Value = serde_json::from_slice(wallet).map_err(|_| WalletError::InvalidFormat)?;
+        let scrypt = &wallet["scrypt"];
+        let params = ScryptParams {
+            n: scrypt["n"].as_u64().ok_or(WalletError::InvalidFormat)?,
+            r: scrypt["r"].as_u64().ok_or(WalletError::InvalidFormat)? as u32,
+            p: scrypt["p"].as_u64().ok_or(WalletError::InvalidFormat)? as u32,
+        };
+
+        let account = wallet["accounts"]
+            .as_array()
+            .ok_or(WalletError::InvalidFormat)?
+            .iter()
+            .find(|account| account["address"].as_str() == Some(address))
+            .ok_or(WalletError::AccountNotFound)?;
+        let nep2 = account["key"].as_str().ok_or(WalletError::WatchOnlyAccount)?;
+
+        let address_version = *from_base58_check(address)?.first().ok_or(WalletError::InvalidFormat)?;
+        Key::from_nep2(nep2, passphrase, &params, version, address_version)
+    }
+
+    /// The private key, for sealing; it must not leave the enclave otherwise.
+    pub(crate) fn private_key(&self) -> &[u8; PRIVATE_KEY_LEN] {
+        &self.private_key
+    }
+
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] {
+        self.public_key
+    }
+
+    /// The address of the standard account of the key, on the networks of `version` and `address_version`.
+    pub fn address(&self, version: ProtocolVersion, address_version: u8) -> Result<String, WalletError> {
+        let script = version.verification_script(&self.public_key);
+
+        let mut data = Vec::with_capacity(25);
+        data.push(address_version);
+        data.extend_from_slice(&crypto::hash160(&script)?);
+        let sum = crypto::checksum(&data)?;
+        data.extend_from_slice(&sum[0..4]);
+        Ok(data.to_base58())
+    }
+
+    /// Signs the SHA-256 of `message`.
+    pub fn sign(&self, message: &[u8]) -> Result<[u8; SIGNATURE_LEN], WalletError> {
+        Ok(crypto::sign(message, &self.private_key)?)
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        wipe(&mut self.private_key);
+    }
+}
+
+/// Base58 decodes `data` and checks and strips its 4 byte checksum.
+fn from_base58_check(data: &str) -> Result<Vec<u8>, WalletError> {
+    let mut decoded = data.from_base58().map_err(|_| WalletError::InvalidFormat)?;
+    if decoded.len() < 4 {
+        return Err(WalletError::InvalidFormat);
+    }
+
+    let sum = decoded.split_off(decoded.len() - 4);
+    if crypto::checksum(&decoded)?[0..4] != *sum {
+        return Err(WalletError::InvalidFormat);
+    }
+    Ok(decoded)
+}
+
+fn wipe(data: &mut [u8]) {
+    for byte in data.iter_mut() {
+        // Volatile so that the wipe of a value about to be freed is not optimized away.
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WalletError {
+    Sgx(sgx_status_t),
+    InvalidFormat,
+    InvalidKey,
+    InvalidScryptParams,
+    AccountNotFound,
+    WatchOnlyAccount,
+    WrongPassphrase,
+    NoKey,
+    InvalidSealedData,
+    UnsupportedSealVersion,
+}
+
+impl From<sgx_status_t> for WalletError {
+    fn from(status: sgx_status_t) -> Self {
+        WalletError::Sgx(status)
+    }
+}
+
+/**
+ * The status an ECALL returns for an error, the untrusted side only sees these:
+ * a wrong passphrase or a sealed wallet that fails authentication is a MAC
+ * mismatch, a sealed wallet of another layout an invalid version, using the
+ * enclave before loading a key an invalid state and any malformed input an
+ * invalid parameter.
+ */
+impl From<WalletError> for sgx_status_t {
+    fn from(error: WalletError) -> Self {
+        match error {
+            WalletError::Sgx(status) => status,
+            WalletError::WrongPassphrase | WalletError::InvalidSealedData => sgx_status_t::SGX_ERROR_MAC_MISMATCH,
+            WalletError::UnsupportedSealVersion => sgx_status_t::SGX_ERROR_INVALID_VERSION,
+            WalletError::NoKey => sgx_status_t::SGX_ERROR_INVALID_STATE,
+            _ => sgx_status_t::SGX_ERROR_INVALID_PARAMETER,
+        }
+    }
+}
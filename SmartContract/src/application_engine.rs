@@ -0,0 +1,650 @@
+use std::cell::RefMut;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use neo_core::crypto::hash160;
+use neo_io::UInt160;
+use neo_ledger::Block;
+use neo_persistence::DataCache;
+use neo_tx::n3::Transaction;
+use neo_vm::ExecutionContext::ExecutionContext;
+use neo_vm::ExecutionEngine::ExecutionEngine;
+use neo_vm::ExecutionHost::ExecutionHost;
+use neo_vm::Instruction::Instruction;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::VmError::VmError;
+
+use crate::call_flags::CallFlags;
+use crate::contract_state::{ContractMethodDescriptor, ContractState};
+use crate::interop::InteropService;
+use crate::interop_descriptor::InteropDescriptor;
+use crate::opcode_prices::{opcode_price, DEFAULT_EXEC_FEE_FACTOR};
+use crate::trigger_type::TriggerType;
+
+/// A VM running in the blockchain environment described by an `ApplicationHost`.
+pub type ApplicationEngine = ExecutionEngine<ApplicationHost>;
+
+/// Price of one byte of storage, in datoshi.
+pub const STORAGE_PRICE: i64 = 100000;
+/// Gas limit of the scripts run to estimate their fee, as the `invokescript` RPC does: 20 GAS.
+pub const TEST_MODE_GAS: i64 = 20_00000000;
+/// Maximum size of an event name.
+pub const MAX_EVENT_NAME: usize = 32;
+/// Maximum size of a log message.
+pub const MAX_NOTIFICATION_SIZE: usize = 1024;
+
+/// State attached to the contexts loaded by an `ApplicationEngine`.
+#[derive(Debug, Clone)]
+pub struct ExecutionContextState {
+    /// Hash of the running script, computed from the script when not set.
+    pub script_hash: Option<UInt160>,
+    /// Hash of the script that loaded this one, `None` for the entry script.
+    pub calling_script_hash: Option<UInt160>,
+    pub call_flags: CallFlags,
+    /// The contract running in the context, if any.
+    pub contract: Option<Rc<ContractState>>,
+    /// Loaded by `System.Contract.Call`, which always leaves a value to its caller.
+    pub is_dynamic_call: bool,
+}
+
+impl Default for ExecutionContextState {
+    fn default() -> Self {
+        Self {
+            script_hash: None,
+            calling_script_hash: None,
+            call_flags: CallFlags::ALL,
+            contract: None,
+            is_dynamic_call: false,
+        }
+    }
+}
+
+/// Event sent by `System.Runtime.Notify`.
+#[derive(Debug, Clone)]
+pub struct NotifyEventArgs {
+    pub script_hash: UInt160,
+    pub event_name: String,
+    pub state: StackItem,
+}
+
+/// Message sent by `System.Runtime.Log`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LogEventArgs {
+    pub script_hash: UInt160,
+    pub message: String,
+}
+
+/**
+ * The blockchain environment of an `ApplicationEngine`.
+ *
+ * It resolves `SYSCALL`s through its `InteropService`, charges every
+ * instruction and interop against the gas limit, and keeps the storage
+ * snapshot, the known contracts and the notifications and logs sent by the
+ * scripts.
+ *
+ * Opcode and interop prices are multiplied by the execution fee factor,
+ * storage fees are not. Once the engine halts, `gas_consumed` is the system
+ * fee a transaction running the same script has to pay.
+ */
+pub struct ApplicationHost {
+    trigger: TriggerType,
+    script_container: Option<Rc<Transaction>>,
+    persisting_block: Option<Rc<Block>>,
+    snapshot: DataCache,
+    gas_limit: i64,
+    gas_consumed: i64,
+    exec_fee_factor: u32,
+    services: Rc<InteropService>,
+    contracts: HashMap<UInt160, Rc<ContractState>>,
+    notifications: Vec<NotifyEventArgs>,
+    logs: Vec<LogEventArgs>,
+}
+
+impl ApplicationHost {
+    pub fn new(
+        trigger: TriggerType,
+        script_container: Option<Transaction>,
+        snapshot: DataCache,
+        persisting_block: Option<Block>,
+        gas_limit: i64,
+    ) -> Self {
+        Self {
+            trigger,
+            script_container: script_container.map(Rc::new),
+            persisting_block: persisting_block.map(Rc::new),
+            snapshot,
+            gas_limit,
+            gas_consumed: 0,
+            exec_fee_factor: DEFAULT_EXEC_FEE_FACTOR,
+            services: Rc::new(InteropService::new()),
+            contracts: HashMap::new(),
+            notifications: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    pub fn trigger(&self) -> TriggerType {
+        self.trigger
+    }
+
+    /// The transaction whose script is executed.
+    pub fn script_container(&self) -> Option<&Rc<Transaction>> {
+        self.script_container.as_ref()
+    }
+
+    /// The block being persisted, if any.
+    pub fn persisting_block(&self) -> Option<&Rc<Block>> {
+        self.persisting_block.as_ref()
+    }
+
+    /// The storage written by the scripts, the caller decides whether to commit it.
+    pub fn snapshot(&self) -> &DataCache {
+        &self.snapshot
+    }
+
+    pub fn snapshot_mut(&mut self) -> &mut DataCache {
+        &mut self.snapshot
+    }
+
+    pub fn gas_limit(&self) -> i64 {
+        self.gas_limit
+    }
+
+    pub fn gas_consumed(&self) -> i64 {
+        self.gas_consumed
+    }
+
+    pub fn gas_left(&self) -> i64 {
+        self.gas_limit - self.gas_consumed
+    }
+
+    pub fn exec_fee_factor(&self) -> u32 {
+        self.exec_fee_factor
+    }
+
+    pub fn set_exec_fee_factor(&mut self, exec_fee_factor: u32) {
+        self.exec_fee_factor = exec_fee_factor;
+    }
+
+    /// Charges `fee` datoshi, fails and so faults the engine when the gas limit is exceeded.
+    pub fn add_gas(&mut self, fee: i64) -> Result<(), VmError> {
+        self.gas_consumed = self
+            .gas_consumed
+            .checked_add(fee)
+            .ok_or_else(|| VmError::invalid_operation("gas overflow"))?;
+        if self.gas_consumed > self.gas_limit {
+            return Err(VmError::invalid_operation("Insufficient GAS."));
+        }
+        Ok(())
+    }
+
+    pub fn services(&self) -> &Rc<InteropService> {
+        &self.services
+    }
+
+    /// Registers a host callback, replacing the descriptor with the same hash if any.
+    pub fn register(&mut self, descriptor: InteropDescriptor) -> Option<InteropDescriptor> {
+        Rc::make_mut(&mut self.services).register(descriptor)
+    }
+
+    /// Makes a contract callable and gives it a storage.
+    pub fn add_contract(&mut self, contract: ContractState) {
+        self.contracts.insert(contract.hash, Rc::new(contract));
+    }
+
+    pub fn contract(&self, hash: &UInt160) -> Option<Rc<ContractState>> {
+        self.contracts.get(hash).cloned()
+    }
+
+    pub fn notifications(&self) -> &[NotifyEventArgs] {
+        &self.notifications
+    }
+
+    pub(crate) fn notify(&mut self, event: NotifyEventArgs) {
+        self.notifications.push(event);
+    }
+
+    pub fn logs(&self) -> &[LogEventArgs] {
+        &self.logs
+    }
+
+    pub(crate) fn log(&mut self, event: LogEventArgs) {
+        self.logs.push(event);
+    }
+}
+
+impl ExecutionHost for ApplicationHost {
+    fn on_syscall(engine: &mut ApplicationEngine, method: u32) -> Result<(), VmError> {
+        let descriptor = engine
+            .host()
+            .services
+            .get(method)
+            .cloned()
+            .ok_or_else(|| VmError::invalid_operation(format!("Syscall not found: {}", method)))?;
+        validate_call_flags(engine, descriptor.required_call_flags)?;
+        let host = engine.host_mut();
+        host.add_gas(descriptor.fixed_price * host.exec_fee_factor as i64)?;
+        (descriptor.handler)(engine)
+    }
+
+    fn pre_execute_instruction(engine: &mut ApplicationEngine, instruction: &Instruction) -> Result<(), VmError> {
+        let host = engine.host_mut();
+        host.add_gas(opcode_price(instruction.opcode()) * host.exec_fee_factor as i64)
+    }
+
+    fn gas_consumed(&self) -> i64 {
+        self.gas_consumed
+    }
+
+    fn context_unloaded(engine: &mut ApplicationEngine, context: &ExecutionContext) {
+        if engine.uncaught_exception().is_some() {
+            return;
+        }
+        let returns_null = match engine.current_context() {
+            Some(current) => {
+                !current.shares_states_with(context)
+                    && context.rv_count() == 0
+                    && context_state(context).is_dynamic_call
+            }
+            None => false,
+        };
+        if returns_null {
+            engine.push(StackItem::Null);
+        }
+    }
+}
+
+/// The flags of the current context, capped by the trigger: verification scripts only read.
+fn validate_call_flags(engine: &ApplicationEngine, required: CallFlags) -> Result<(), VmError> {
+    let trigger_flags = if engine.host().trigger == TriggerType::VERIFICATION {
+        CallFlags::READ_ONLY
+    } else {
+        CallFlags::ALL
+    };
+    let flags = current_call_flags(engine) & trigger_flags;
+    if !flags.contains(required) {
+        return Err(VmError::invalid_operation(format!(
+            "Cannot call this SYSCALL with the flag {}.",
+            flags
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the `ExecutionContextState` of `context`, creating it if needed.
+pub fn context_state(context: &ExecutionContext) -> RefMut<'_, ExecutionContextState> {
+    context.state::<ExecutionContextState>()
+}
+
+/// Hash of the script running in `context`.
+pub fn script_hash(context: &ExecutionContext) -> UInt160 {
+    *context_state(context)
+        .script_hash
+        .get_or_insert_with(|| UInt160::from_slice(&hash160(context.script().as_bytes())).unwrap())
+}
+
+pub fn current_script_hash(engine: &ApplicationEngine) -> Option<UInt160> {
+    engine.current_context().map(script_hash)
+}
+
+pub fn calling_script_hash(engine: &ApplicationEngine) -> Option<UInt160> {
+    engine
+        .current_context()
+        .and_then(|context| context_state(context).calling_script_hash)
+}
+
+pub fn entry_script_hash(engine: &ApplicationEngine) -> Option<UInt160> {
+    engine.entry_context().map(script_hash)
+}
+
+pub fn current_call_flags(engine: &ApplicationEngine) -> CallFlags {
+    engine
+        .current_context()
+        .map_or(CallFlags::NONE, |context| context_state(context).call_flags)
+}
+
+/// Loads `method` of `contract`, the arguments are then pushed by the caller, last one first.
+pub fn load_contract<'a>(
+    engine: &'a mut ApplicationEngine,
+    contract: Rc<ContractState>,
+    method: &ContractMethodDescriptor,
+    call_flags: CallFlags,
+) -> Result<&'a mut ExecutionContext, VmError> {
+    let rv_count = if method.has_return_value { 1 } else { 0 };
+    let context = engine.create_context(contract.script.clone(), rv_count, method.offset)?;
+    {
+        let mut state = context_state(&context);
+        state.script_hash = Some(contract.hash);
+        state.calling_script_hash = current_script_hash(engine);
+        state.call_flags = call_flags;
+        state.contract = Some(contract);
+    }
+    Ok(engine.load_context(context))
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_persistence::{MemoryStore, Store};
+    use neo_tx::n3::Signer;
+    use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+    use neo_vm::OpCode::OpCode;
+    use neo_vm::ScriptBuilder::ScriptBuilder;
+    use neo_vm::VMState::VMState;
+
+    use neo_vm::Types::Struct::Struct;
+
+    use super::*;
+    use crate::binary_serializer;
+    use crate::find_options::FindOptions;
+    use crate::interop::storage::storage_key;
+    use crate::interop_descriptor::interop_hash;
+
+    const GAS: i64 = 20_00000000;
+
+    fn new_engine(trigger: TriggerType, script_container: Option<Transaction>, gas_limit: i64) -> ApplicationEngine {
+        let snapshot = DataCache::new(MemoryStore::new().get_snapshot());
+        let host = ApplicationHost::new(trigger, script_container, snapshot, None, gas_limit);
+        ApplicationEngine::with_host(ExecutionEngineLimits::default(), host)
+    }
+
+    fn syscall<'a>(sb: &'a mut ScriptBuilder, name: &str) -> &'a mut ScriptBuilder {
+        sb.emit_syscall(interop_hash(name))
+    }
+
+    fn push_small(sb: &mut ScriptBuilder, value: u8) -> &mut ScriptBuilder {
+        sb.emit(OpCode(OpCode::PUSH0.0 + value), &[])
+    }
+
+    fn emit_call(sb: &mut ScriptBuilder, hash: &UInt160, method: &str, call_flags: CallFlags) {
+        sb.emit(OpCode::NEWARRAY0, &[]);
+        push_small(sb, call_flags.0);
+        sb.emit_push_string(method).emit_push_data(hash.as_bytes());
+        syscall(sb, "System.Contract.Call");
+    }
+
+    /// A contract storing "value" under "key" with `put`, reading it with `get` and listing its keys with `keys`.
+    fn storage_contract(safe_put: bool) -> ContractState {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push_string("value").emit_push_string("key");
+        syscall(&mut sb, "System.Storage.GetContext");
+        syscall(&mut sb, "System.Storage.Put");
+        sb.emit(OpCode::RET, &[]);
+        let get = sb.length();
+        sb.emit_push_string("key");
+        syscall(&mut sb, "System.Storage.GetReadOnlyContext");
+        syscall(&mut sb, "System.Storage.Get");
+        sb.emit(OpCode::RET, &[]);
+        let keys = sb.length();
+        push_small(&mut sb, 0x03);
+        sb.emit_push_string("k");
+        syscall(&mut sb, "System.Storage.GetContext");
+        syscall(&mut sb, "System.Storage.Find");
+        sb.emit(OpCode::DUP, &[]);
+        syscall(&mut sb, "System.Iterator.Next");
+        sb.emit(OpCode::DROP, &[]);
+        syscall(&mut sb, "System.Iterator.Value");
+        sb.emit(OpCode::RET, &[]);
+
+        let script = sb.to_array();
+        let method = |name: &str, offset: usize, has_return_value: bool, safe: bool| ContractMethodDescriptor {
+            name: name.to_string(),
+            parameters_count: 0,
+            offset,
+            has_return_value,
+            safe,
+        };
+        ContractState::new(
+            7,
+            UInt160::from_slice(&hash160(&script)).unwrap(),
+            &script,
+            vec![
+                method("put", 0, false, safe_put),
+                method("get", get, true, true),
+                method("keys", keys, true, true),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_runtime() {
+        let mut sb = ScriptBuilder::new();
+        syscall(&mut sb, "System.Runtime.Platform");
+        syscall(&mut sb, "System.Runtime.GetTrigger");
+        sb.emit_push_string("hello");
+        syscall(&mut sb, "System.Runtime.Log");
+        sb.emit(OpCode::NEWARRAY0, &[]).emit_push_string("Event");
+        syscall(&mut sb, "System.Runtime.Notify");
+        syscall(&mut sb, "System.Runtime.GasLeft");
+        let script = sb.to_array();
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, GAS);
+        engine
+            .load_script(neo_vm::Script::Script::from(script.clone()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+
+        let script_hash = UInt160::from_slice(&hash160(&script)).unwrap();
+        let result = engine.result_stack();
+        assert_eq!(result.peek(2).unwrap().span().unwrap(), b"NEO".to_vec());
+        assert_eq!(result.peek(1).unwrap().integer().unwrap(), 0x40.into());
+        let fee = 30 * ((8 + 8 + (1 << 15) * 2 + 16) + (8 * 2 + 16));
+        assert_eq!(result.peek(0).unwrap().integer().unwrap(), (GAS - fee).into());
+        assert_eq!(engine.host().gas_consumed(), fee);
+        assert_eq!(
+            engine.host().logs(),
+            &[LogEventArgs {
+                script_hash,
+                message: "hello".to_string()
+            }]
+        );
+        let notifications = engine.host().notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].event_name, "Event");
+        assert_eq!(notifications[0].script_hash, script_hash);
+    }
+
+    #[test]
+    fn test_check_witness() {
+        let signer = UInt160::from([1; 20]);
+        let transaction = Transaction {
+            signers: vec![Signer::called_by_entry(signer)],
+            ..Default::default()
+        };
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push_data(signer.as_bytes());
+        syscall(&mut sb, "System.Runtime.CheckWitness");
+        sb.emit_push_data(&[2; 20]);
+        syscall(&mut sb, "System.Runtime.CheckWitness");
+
+        let mut engine = new_engine(TriggerType::APPLICATION, Some(transaction), GAS);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+        assert!(!engine.result_stack().peek(0).unwrap().boolean().unwrap());
+        assert!(engine.result_stack().peek(1).unwrap().boolean().unwrap());
+    }
+
+    #[test]
+    fn test_contract_call_and_storage() {
+        let contract = storage_contract(false);
+        let hash = contract.hash;
+        let mut sb = ScriptBuilder::new();
+        emit_call(&mut sb, &hash, "put", CallFlags::ALL);
+        emit_call(&mut sb, &hash, "get", CallFlags::ALL);
+        emit_call(&mut sb, &hash, "keys", CallFlags::ALL);
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, GAS);
+        engine.host_mut().add_contract(contract);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+
+        let result = engine.result_stack();
+        assert_eq!(result.count(), 3);
+        assert_eq!(result.peek(0).unwrap().span().unwrap(), b"ey".to_vec());
+        assert_eq!(result.peek(1).unwrap().span().unwrap(), b"value".to_vec());
+        assert!(result.peek(2).unwrap().is_null());
+        assert_eq!(
+            engine.host().snapshot().try_get(&storage_key(7, b"key")).unwrap(),
+            Some(b"value".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_find_deserialize_values() {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push(
+            &(FindOptions::VALUES_ONLY.0 | FindOptions::DESERIALIZE_VALUES.0 | FindOptions::PICK_FIELD1.0).into(),
+        )
+        .emit_push_string("k");
+        syscall(&mut sb, "System.Storage.GetReadOnlyContext");
+        syscall(&mut sb, "System.Storage.Find");
+        sb.emit(OpCode::DUP, &[]);
+        syscall(&mut sb, "System.Iterator.Next");
+        sb.emit(OpCode::DROP, &[]);
+        syscall(&mut sb, "System.Iterator.Value");
+        sb.emit(OpCode::RET, &[]);
+        let script = sb.to_array();
+        let method = ContractMethodDescriptor {
+            name: "pick".to_string(),
+            parameters_count: 0,
+            offset: 0,
+            has_return_value: true,
+            safe: true,
+        };
+        let contract = ContractState::new(
+            3,
+            UInt160::from_slice(&hash160(&script)).unwrap(),
+            &script,
+            vec![method],
+        );
+        let hash = contract.hash;
+
+        let value: StackItem = Struct::new(None, vec![StackItem::from(1), StackItem::from("field")]).into();
+        let value = binary_serializer::serialize(&value, &ExecutionEngineLimits::default()).unwrap();
+        let mut sb = ScriptBuilder::new();
+        emit_call(&mut sb, &hash, "pick", CallFlags::ALL);
+        let mut engine = new_engine(TriggerType::APPLICATION, None, GAS);
+        engine.host_mut().add_contract(contract);
+        engine
+            .host_mut()
+            .snapshot_mut()
+            .put(&storage_key(3, b"k1"), value)
+            .unwrap();
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+        assert_eq!(
+            engine.result_stack().peek(0).unwrap().span().unwrap(),
+            b"field".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_call_flags() {
+        let faults = |contract: ContractState, call_flags: CallFlags| {
+            let hash = contract.hash;
+            let mut sb = ScriptBuilder::new();
+            emit_call(&mut sb, &hash, "put", call_flags);
+            let mut engine = new_engine(TriggerType::APPLICATION, None, GAS);
+            engine.host_mut().add_contract(contract);
+            engine
+                .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+                .unwrap();
+            engine.execute() == VMState::FAULT
+        };
+        assert!(!faults(storage_contract(false), CallFlags::STATES));
+        assert!(faults(storage_contract(false), CallFlags::READ_ONLY));
+        assert!(faults(storage_contract(true), CallFlags::ALL));
+    }
+
+    #[test]
+    fn test_verification_trigger_is_read_only() {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push_string("hello");
+        syscall(&mut sb, "System.Runtime.Log");
+
+        let mut engine = new_engine(TriggerType::VERIFICATION, None, GAS);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::FAULT);
+        assert!(engine.host().logs().is_empty());
+    }
+
+    #[test]
+    fn test_gas_limit() {
+        let mut sb = ScriptBuilder::new();
+        syscall(&mut sb, "System.Runtime.Platform");
+        syscall(&mut sb, "System.Runtime.Platform");
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, 8 * 30);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::FAULT);
+        assert_eq!(engine.host().gas_consumed(), 16 * 30);
+        assert_eq!(engine.fault_exception().as_ref().unwrap().to_string(), "Insufficient GAS.");
+    }
+
+    #[test]
+    fn test_opcode_fees() {
+        let mut sb = ScriptBuilder::new();
+        push_small(&mut sb, 1);
+        push_small(&mut sb, 2);
+        sb.emit(OpCode::ADD, &[]).emit_push_string("data");
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, TEST_MODE_GAS);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+        assert_eq!(engine.host().gas_consumed(), (1 + 1 + 8 + 8) * 30);
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, TEST_MODE_GAS);
+        engine.host_mut().set_exec_fee_factor(1);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+        assert_eq!(engine.host().gas_consumed(), 1 + 1 + 8 + 8);
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, 10 * 30);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::FAULT);
+        assert_eq!(engine.result_stack().count(), 0);
+    }
+
+    #[test]
+    fn test_host_callback() {
+        let mut sb = ScriptBuilder::new();
+        syscall(&mut sb, "Host.Answer");
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, GAS);
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::FAULT);
+
+        let mut engine = new_engine(TriggerType::APPLICATION, None, GAS);
+        engine.host_mut().register(InteropDescriptor::new(
+            "Host.Answer",
+            |engine: &mut ApplicationEngine| {
+                engine.push_integer(42);
+                Ok(())
+            },
+            1,
+            CallFlags::NONE,
+        ));
+        engine
+            .load_script(neo_vm::Script::Script::from(sb.to_array()), -1, 0)
+            .unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+        assert_eq!(engine.result_stack().peek(0).unwrap().integer().unwrap(), 42.into());
+    }
+}
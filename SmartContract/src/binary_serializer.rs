@@ -0,0 +1,273 @@
+//! The binary format of stack items, used by `StdLib.serialize` and for contract storage values.
+//!
+//! An item is its type byte followed by its payload: nothing for `Any`, a
+//! byte for `Boolean`, var-bytes for `Integer`, `ByteString` and `Buffer`,
+//! and a var-int count followed by the items, or the keys and values, for
+//! `Array`, `Struct` and `Map`. `Pointer` and `InteropInterface` can't be
+//! serialized.
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use neo_io::{BinaryReader, BinaryWriter};
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+use neo_vm::ReferenceCounter::ReferenceCounter;
+use neo_vm::Types::Array::Array;
+use neo_vm::Types::Buffer::Buffer;
+use neo_vm::Types::Integer::Integer;
+use neo_vm::Types::Map::Map;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::Types::StackItemType::StackItemType;
+use neo_vm::Types::Struct::Struct;
+
+use crate::error::SerializationError;
+
+/// Serializes `item`, which must be at most `max_item_size` bytes and hold at most `max_stack_size` items.
+pub fn serialize(item: &StackItem, limits: &ExecutionEngineLimits) -> Result<Vec<u8>, SerializationError> {
+    serialize_with(item, limits.max_item_size() as usize, limits.max_stack_size() as usize)
+}
+
+/**
+ * Serializes `item` into at most `max_size` bytes and `max_items` items.
+ *
+ * Compound items are written once: an item referencing itself, or referenced
+ * twice, is rejected like the reference implementation does.
+ */
+pub fn serialize_with(item: &StackItem, max_size: usize, max_items: usize) -> Result<Vec<u8>, SerializationError> {
+    let mut buffer = Vec::new();
+    let mut serialized = HashSet::new();
+    let mut unserialized = vec![item.clone()];
+    let mut items = 0;
+    while let Some(item) = unserialized.pop() {
+        items += 1;
+        if items > max_items {
+            return Err(SerializationError::MaxItemsExceeded(max_items));
+        }
+        let typ = item.get_type();
+        let mut writer = BinaryWriter::new(&mut buffer);
+        writer.write_u8(typ as u8)?;
+        match &item {
+            StackItem::Null => {}
+            StackItem::Boolean(value) => writer.write_bool(*value)?,
+            StackItem::Integer(_) | StackItem::ByteString(_) | StackItem::Buffer(_) => {
+                writer.write_var_bytes(&item.span()?)?
+            }
+            StackItem::Array(_) | StackItem::Struct(_) | StackItem::Map(_) => {
+                if !serialized.insert(item.address()) {
+                    return Err(SerializationError::CircularReference);
+                }
+                if let Some(map) = item.as_map() {
+                    let dictionary = map.dictionary();
+                    writer.write_var_int(dictionary.len() as u64)?;
+                    for (key, value) in dictionary.iter().rev() {
+                        unserialized.push(value.clone());
+                        unserialized.push(key.item().clone());
+                    }
+                } else {
+                    let items = item.as_array().unwrap().items();
+                    writer.write_var_int(items.len() as u64)?;
+                    unserialized.extend(items.iter().rev().cloned());
+                }
+            }
+            StackItem::Pointer(_) | StackItem::InteropInterface(_) => {
+                return Err(SerializationError::NotSupported(typ))
+            }
+        }
+        if buffer.len() > max_size {
+            return Err(SerializationError::MaxSizeExceeded(max_size));
+        }
+    }
+    Ok(buffer)
+}
+
+/// Deserializes an item from `data`, creating compound items with `reference_counter`.
+pub fn deserialize(
+    data: &[u8],
+    limits: &ExecutionEngineLimits,
+    reference_counter: Option<Rc<ReferenceCounter>>,
+) -> Result<StackItem, SerializationError> {
+    let max_size = data.len().min(limits.max_item_size() as usize);
+    let mut reader = BinaryReader::from_slice(data);
+    let mut deserializer = Deserializer {
+        reader: &mut reader,
+        max_size,
+        max_items: limits.max_stack_size() as usize,
+        items: 0,
+        reference_counter,
+    };
+    deserializer.read_item()
+}
+
+struct Deserializer<'a, 'b> {
+    reader: &'a mut BinaryReader<&'b [u8]>,
+    max_size: usize,
+    max_items: usize,
+    /// Number of items read so far, nested ones included.
+    items: usize,
+    reference_counter: Option<Rc<ReferenceCounter>>,
+}
+
+impl Deserializer<'_, '_> {
+    fn read_item(&mut self) -> Result<StackItem, SerializationError> {
+        self.items += 1;
+        if self.items > self.max_items {
+            return Err(SerializationError::MaxItemsExceeded(self.max_items));
+        }
+        let typ = self.reader.read_u8()?;
+        let typ = StackItemType::from_u8(typ)
+            .ok_or_else(|| SerializationError::Format(format!("invalid stack item type {}", typ)))?;
+        let item = match typ {
+            StackItemType::Any => StackItem::Null,
+            StackItemType::Boolean => StackItem::Boolean(self.reader.read_bool()?),
+            StackItemType::Integer => {
+                let data = self.reader.read_var_bytes(Integer::MAX_SIZE)?;
+                StackItem::Integer(Integer::from_signed_bytes_le(&data)?)
+            }
+            StackItemType::ByteString => self.reader.read_var_bytes(self.max_size)?.into(),
+            StackItemType::Buffer => Buffer::from_slice(&self.reader.read_var_bytes(self.max_size)?).into(),
+            StackItemType::Array | StackItemType::Struct => {
+                let count = self.reader.read_var_int(self.max_items as u64)? as usize;
+                let items = (0..count).map(|_| self.read_item()).collect::<Result<Vec<_>, _>>()?;
+                if typ == StackItemType::Array {
+                    Array::new(self.reference_counter.clone(), items).into()
+                } else {
+                    Struct::new(self.reference_counter.clone(), items).into()
+                }
+            }
+            StackItemType::Map => {
+                let count = self.reader.read_var_int(self.max_items as u64)? as usize;
+                let map = Map::new(self.reference_counter.clone());
+                for _ in 0..count {
+                    let key = self.read_item()?;
+                    if Map::check_key(&key).is_err() {
+                        return Err(SerializationError::Format(format!("invalid map key {:?}", key)));
+                    }
+                    let value = self.read_item()?;
+                    map.set(key, value);
+                }
+                map.into()
+            }
+            StackItemType::Pointer | StackItemType::InteropInterface => {
+                return Err(SerializationError::Format(format!("{:?} can't be deserialized", typ)))
+            }
+        };
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ExecutionEngineLimits {
+        ExecutionEngineLimits::default()
+    }
+
+    fn bytes(data: &[u8]) -> StackItem {
+        StackItem::from(data)
+    }
+
+    fn integer(value: i64) -> StackItem {
+        StackItem::from(value)
+    }
+
+    #[test]
+    fn test_primitives() {
+        let cases: Vec<(StackItem, &str)> = vec![
+            (StackItem::Null, "00"),
+            (StackItem::Boolean(true), "2001"),
+            (StackItem::Boolean(false), "2000"),
+            (integer(0), "2100"),
+            (integer(-1), "2101ff"),
+            (integer(128), "21028000"),
+            (integer(-129), "21027fff"),
+            (bytes(b"neo"), "28036e656f"),
+            (Buffer::from_slice(&[1, 2]).into(), "30020102"),
+        ];
+        for (item, expected) in cases {
+            let data = serialize(&item, &limits()).unwrap();
+            assert_eq!(hex::encode(&data), expected);
+            let back = deserialize(&data, &limits(), None).unwrap();
+            assert_eq!(back.get_type(), item.get_type());
+            assert_eq!(serialize(&back, &limits()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_compound() {
+        let map = Map::new(None);
+        map.set(bytes(b"a"), integer(1));
+        map.set(integer(2), Struct::new(None, vec![StackItem::Boolean(true)]).into());
+        let item: StackItem = Array::new(None, vec![map.into(), Array::new(None, vec![]).into()]).into();
+        let data = serialize(&item, &limits()).unwrap();
+        assert_eq!(hex::encode(&data), "40024802280161210101210102410120014000");
+
+        let back = deserialize(&data, &limits(), None).unwrap();
+        let items = back.as_array().unwrap().items().clone();
+        let map = items[0].as_map().unwrap();
+        assert_eq!(map.get(&bytes(b"a")).unwrap().integer().unwrap(), 1.into());
+        let fields = map.get(&integer(2)).unwrap();
+        assert_eq!(fields.get_type(), StackItemType::Struct);
+        assert!(fields.as_array().unwrap().get(0).boolean().unwrap());
+        assert_eq!(serialize(&back, &limits()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_references() {
+        let array = Array::new(None, vec![]);
+        let item: StackItem = array.into();
+        item.as_array().unwrap().add(item.clone());
+        assert!(matches!(
+            serialize(&item, &limits()),
+            Err(SerializationError::CircularReference)
+        ));
+
+        let inner: StackItem = Array::new(None, vec![]).into();
+        let item: StackItem = Array::new(None, vec![inner.clone(), inner]).into();
+        assert!(matches!(
+            serialize(&item, &limits()),
+            Err(SerializationError::CircularReference)
+        ));
+    }
+
+    #[test]
+    fn test_not_supported() {
+        let item: StackItem = neo_vm::Types::InteropInterface::InteropInterface::new(Rc::new(())).into();
+        assert!(matches!(
+            serialize(&item, &limits()),
+            Err(SerializationError::NotSupported(StackItemType::InteropInterface))
+        ));
+        assert!(deserialize(&[0x10, 0x00], &limits(), None).is_err());
+        assert!(deserialize(&[0x99], &limits(), None).is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let item = bytes(&[0u8; 100]);
+        assert!(matches!(
+            serialize_with(&item, 100, 10),
+            Err(SerializationError::MaxSizeExceeded(100))
+        ));
+        assert_eq!(serialize_with(&item, 102, 10).unwrap().len(), 102);
+
+        let item: StackItem = Array::new(None, vec![integer(1), integer(2)]).into();
+        assert!(matches!(
+            serialize_with(&item, 100, 2),
+            Err(SerializationError::MaxItemsExceeded(2))
+        ));
+
+        let mut limits = limits();
+        limits.set_max_stack_size(2);
+        let data = serialize(&item, &ExecutionEngineLimits::default()).unwrap();
+        assert!(deserialize(&data, &limits, None).is_err());
+
+        // The length of a byte string is bounded by the size of the data.
+        assert!(deserialize(&[0x28, 0x02, 0x01], &ExecutionEngineLimits::default(), None).is_err());
+        // Integers are at most 32 bytes.
+        let mut data = vec![0x21, 33];
+        data.extend_from_slice(&[1u8; 33]);
+        assert!(deserialize(&data, &ExecutionEngineLimits::default(), None).is_err());
+        // Compound items can't be map keys.
+        assert!(deserialize(&[0x48, 0x01, 0x40, 0x00, 0x00], &ExecutionEngineLimits::default(), None).is_err());
+    }
+}
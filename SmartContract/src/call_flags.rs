@@ -1,16 +1,85 @@
-mod SmartContract {
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Not};
 
-    #[derive(Debug)]
-    enum CallFlags {
-        None = 0,
+/**
+ * What a contract is allowed to do while it runs, stored as a bit set.
+ *
+ * A called contract never gets more flags than its caller has.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Default)]
+pub struct CallFlags(pub u8);
 
-        ReadStates = 0b00000001,
-        WriteStates = 0b00000010,
-        AllowCall = 0b00000100,
-        AllowNotify = 0b00001000,
+impl CallFlags {
+    /// No flag is set.
+    pub const NONE: CallFlags = CallFlags(0b0000_0000);
+    /// Reading the blockchain state.
+    pub const READ_STATES: CallFlags = CallFlags(0b0000_0001);
+    /// Writing the blockchain state.
+    pub const WRITE_STATES: CallFlags = CallFlags(0b0000_0010);
+    /// Calling another contract.
+    pub const ALLOW_CALL: CallFlags = CallFlags(0b0000_0100);
+    /// Sending notifications and logs.
+    pub const ALLOW_NOTIFY: CallFlags = CallFlags(0b0000_1000);
 
-        States = CallFlags::ReadStates | CallFlags::WriteStates,
-        ReadOnly = CallFlags::ReadStates | CallFlags::AllowCall,
-        All = CallFlags::States | CallFlags::AllowCall | CallFlags::AllowNotify,
+    pub const STATES: CallFlags = CallFlags(Self::READ_STATES.0 | Self::WRITE_STATES.0);
+    pub const READ_ONLY: CallFlags = CallFlags(Self::READ_STATES.0 | Self::ALLOW_CALL.0);
+    pub const ALL: CallFlags = CallFlags(Self::STATES.0 | Self::ALLOW_CALL.0 | Self::ALLOW_NOTIFY.0);
+
+    /// Returns the flags if no unknown bit is set.
+    pub fn from_byte(b: u8) -> Option<Self> {
+        if b & !CallFlags::ALL.0 != 0 {
+            return None;
+        }
+
+        Some(CallFlags(b))
+    }
+
+    pub fn contains(&self, other: CallFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for CallFlags {
+    type Output = CallFlags;
+
+    fn bitor(self, rhs: CallFlags) -> CallFlags {
+        CallFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for CallFlags {
+    type Output = CallFlags;
+
+    fn bitand(self, rhs: CallFlags) -> CallFlags {
+        CallFlags(self.0 & rhs.0)
+    }
+}
+
+impl Not for CallFlags {
+    type Output = CallFlags;
+
+    fn not(self) -> CallFlags {
+        CallFlags(!self.0 & CallFlags::ALL.0)
+    }
+}
+
+impl fmt::Display for CallFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "None");
+        }
+
+        let names = [
+            (CallFlags::READ_STATES, "ReadStates"),
+            (CallFlags::WRITE_STATES, "WriteStates"),
+            (CallFlags::ALLOW_CALL, "AllowCall"),
+            (CallFlags::ALLOW_NOTIFY, "AllowNotify"),
+        ];
+        let parts: Vec<&str> = names
+            .iter()
+            .filter(|(flags, _)| self.contains(*flags))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", parts.join(", "))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,253 @@
+//! Scripts calling contract methods through `System.Contract.Call`, and reading what they leave on the result stack.
+
+use neo_io::UInt160;
+use neo_vm::EvaluationStack::EvaluationStack;
+use neo_vm::OpCode::OpCode;
+use neo_vm::ScriptBuilder::ScriptBuilder;
+use neo_vm::Types::Integer::Integer;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::Types::StackItemType::StackItemType;
+use num::BigInt;
+
+use crate::call_flags::CallFlags;
+use crate::error::ResultError;
+use crate::interop_descriptor::interop_hash;
+
+/// An argument of a contract call, pushed as the VM item of the same kind.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ContractArg {
+    Null,
+    Boolean(bool),
+    Integer(BigInt),
+    /// Pushed as its 20 little-endian bytes.
+    Hash160(UInt160),
+    ByteArray(Vec<u8>),
+    String(String),
+    Array(Vec<ContractArg>),
+}
+
+impl From<bool> for ContractArg {
+    fn from(value: bool) -> Self {
+        ContractArg::Boolean(value)
+    }
+}
+
+impl From<i64> for ContractArg {
+    fn from(value: i64) -> Self {
+        ContractArg::Integer(value.into())
+    }
+}
+
+impl From<BigInt> for ContractArg {
+    fn from(value: BigInt) -> Self {
+        ContractArg::Integer(value)
+    }
+}
+
+impl From<UInt160> for ContractArg {
+    fn from(value: UInt160) -> Self {
+        ContractArg::Hash160(value)
+    }
+}
+
+impl From<&str> for ContractArg {
+    fn from(value: &str) -> Self {
+        ContractArg::String(value.to_string())
+    }
+}
+
+impl From<Vec<u8>> for ContractArg {
+    fn from(value: Vec<u8>) -> Self {
+        ContractArg::ByteArray(value)
+    }
+}
+
+/// Emits a push of `arg`, arrays are packed from their items.
+pub fn emit_arg<'a>(sb: &'a mut ScriptBuilder, arg: &ContractArg) -> &'a mut ScriptBuilder {
+    match arg {
+        ContractArg::Null => sb.emit(OpCode::PUSHNULL, &[]),
+        ContractArg::Boolean(value) => sb.emit_push_bool(*value),
+        ContractArg::Integer(value) => sb.emit_push(value),
+        ContractArg::Hash160(value) => sb.emit_push_data(value.as_bytes()),
+        ContractArg::ByteArray(value) => sb.emit_push_data(value),
+        ContractArg::String(value) => sb.emit_push_string(value),
+        ContractArg::Array(items) => emit_pack(sb, items),
+    }
+}
+
+/**
+ * Emits a call of `method` of the contract `hash` with `args`.
+ *
+ * The script leaves the return value of the method on the stack, `Null`
+ * for the methods that return nothing.
+ */
+pub fn emit_dynamic_call<'a>(
+    sb: &'a mut ScriptBuilder,
+    hash: &UInt160,
+    method: &str,
+    call_flags: CallFlags,
+    args: &[ContractArg],
+) -> &'a mut ScriptBuilder {
+    emit_pack(sb, args);
+    sb.emit_push(&call_flags.0.into())
+        .emit_push_string(method)
+        .emit_push_data(hash.as_bytes())
+        .emit_syscall(interop_hash("System.Contract.Call"))
+}
+
+/// The first item is on top of the stack once unpacked.
+fn emit_pack<'a>(sb: &'a mut ScriptBuilder, items: &[ContractArg]) -> &'a mut ScriptBuilder {
+    if items.is_empty() {
+        return sb.emit(OpCode::NEWARRAY0, &[]);
+    }
+    for item in items.iter().rev() {
+        emit_arg(sb, item);
+    }
+    sb.emit_push(&items.len().into()).emit(OpCode::PACK, &[])
+}
+
+/**
+ * Emits a loop moving the values of the iterator on top of the stack, as
+ * returned by `System.Storage.Find`, into an array replacing it. It stops
+ * after `max_items` values, so the script stays within the stack limits.
+ */
+pub fn emit_iterator_unwrap(sb: &mut ScriptBuilder, max_items: usize) -> &mut ScriptBuilder {
+    let mut body = ScriptBuilder::new();
+    body.emit(OpCode::DUP, &[])
+        .emit(OpCode::SIZE, &[])
+        .emit_push(&max_items.into())
+        .emit(OpCode::GE, &[]);
+    let mut next = ScriptBuilder::new();
+    next.emit(OpCode::OVER, &[])
+        .emit_syscall(interop_hash("System.Iterator.Next"));
+    let mut append = ScriptBuilder::new();
+    append
+        .emit(OpCode::DUP, &[])
+        .emit(OpCode::PUSH2, &[])
+        .emit(OpCode::PICK, &[])
+        .emit_syscall(interop_hash("System.Iterator.Value"))
+        .emit(OpCode::APPEND, &[]);
+
+    // Jumps are short, their offsets are from the jump instruction itself.
+    let loop_length = body.length() + 2 + next.length() + 2 + append.length() + 2;
+    let end = |position: usize| (loop_length - position) as i32;
+    sb.emit(OpCode::NEWARRAY0, &[]).emit_raw(&body.to_array());
+    sb.emit_jump(OpCode::JMPIF, end(body.length()));
+    sb.emit_raw(&next.to_array());
+    sb.emit_jump(OpCode::JMPIFNOT, end(body.length() + 2 + next.length()));
+    sb.emit_raw(&append.to_array());
+    sb.emit_jump(OpCode::JMP, -((loop_length - 2) as i32));
+    sb.emit(OpCode::NIP, &[])
+}
+
+/**
+ * The items left by a script emitting `count` calls, in the order of the calls.
+ * Fails if the script did not leave exactly `count` items.
+ */
+pub fn results(stack: &EvaluationStack, count: usize) -> Result<Vec<StackItem>, ResultError> {
+    if stack.count() != count {
+        return Err(ResultError::Count(count, stack.count()));
+    }
+
+    let items = (0..count).rev().map(|i| stack.peek(i)).collect::<Result<_, _>>()?;
+    Ok(items)
+}
+
+/// Reads an `Integer`, or a `Boolean` or `ByteString` that converts to one.
+pub fn result_integer(item: &StackItem) -> Result<BigInt, ResultError> {
+    match item.get_type() {
+        StackItemType::Integer | StackItemType::Boolean => Ok(item.integer()?),
+        StackItemType::ByteString if item.span()?.len() <= Integer::MAX_SIZE => Ok(item.integer()?),
+        StackItemType::ByteString => Err(ResultError::Format("integer too large".to_string())),
+        other => Err(ResultError::Type(other)),
+    }
+}
+
+pub fn result_boolean(item: &StackItem) -> Result<bool, ResultError> {
+    match item.get_type() {
+        StackItemType::Boolean | StackItemType::Integer | StackItemType::ByteString => Ok(item.boolean()?),
+        other => Err(ResultError::Type(other)),
+    }
+}
+
+/// Reads the items of an `Array` or a `Struct`, such as an unwrapped iterator.
+pub fn result_array(item: &StackItem) -> Result<Vec<StackItem>, ResultError> {
+    match item.as_array() {
+        Some(array) => Ok(array.items().clone()),
+        None => Err(ResultError::Type(item.get_type())),
+    }
+}
+
+/// Reads the memory of a `ByteString` or a `Buffer`.
+pub fn result_bytes(item: &StackItem) -> Result<Vec<u8>, ResultError> {
+    match item.get_type() {
+        StackItemType::ByteString | StackItemType::Buffer => Ok(item.span()?),
+        other => Err(ResultError::Type(other)),
+    }
+}
+
+/// Reads a strict UTF-8 string.
+pub fn result_string(item: &StackItem) -> Result<String, ResultError> {
+    String::from_utf8(result_bytes(item)?).map_err(|e| ResultError::Format(format!("{:?}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_vm::Types::Array::Array;
+
+    use super::*;
+
+    #[test]
+    fn test_emit_dynamic_call() {
+        let hash = UInt160::from([0x11; 20]);
+        let mut sb = ScriptBuilder::new();
+        emit_dynamic_call(&mut sb, &hash, "m", CallFlags::READ_ONLY, &[]);
+        let mut expected = vec![OpCode::NEWARRAY0.0, OpCode::PUSH5.0, OpCode::PUSHDATA1.0, 1, b'm'];
+        expected.extend_from_slice(&[OpCode::PUSHDATA1.0, 20]);
+        expected.extend_from_slice(&[0x11; 20]);
+        expected.extend_from_slice(&[OpCode::SYSCALL.0, 0x62, 0x7d, 0x5b, 0x52]);
+        assert_eq!(sb.to_array(), expected);
+
+        let mut sb = ScriptBuilder::new();
+        let args = [
+            ContractArg::from(7),
+            ContractArg::Array(vec![ContractArg::Null, true.into()]),
+        ];
+        emit_dynamic_call(&mut sb, &hash, "m", CallFlags::ALL, &args);
+        assert_eq!(
+            sb.to_array()[..8].to_vec(),
+            vec![
+                OpCode::PUSH1.0,
+                OpCode::PUSHNULL.0,
+                OpCode::PUSH2.0,
+                OpCode::PACK.0,
+                OpCode::PUSH7.0,
+                OpCode::PUSH2.0,
+                OpCode::PACK.0,
+                OpCode::PUSH15.0
+            ]
+        );
+    }
+
+    #[test]
+    fn test_results() {
+        let integer = StackItem::from(10);
+        assert_eq!(result_integer(&integer).unwrap(), 10.into());
+        assert!(matches!(
+            result_bytes(&integer),
+            Err(ResultError::Type(StackItemType::Integer))
+        ));
+
+        let data = StackItem::from(vec![0xff; 33]);
+        assert!(matches!(result_integer(&data), Err(ResultError::Format(_))));
+        assert!(matches!(result_string(&data), Err(ResultError::Format(_))));
+        assert_eq!(result_string(&StackItem::from("NEO")).unwrap(), "NEO");
+        assert!(result_boolean(&StackItem::Boolean(true)).unwrap());
+
+        let array: StackItem = Array::new(None, vec![]).into();
+        assert!(matches!(
+            result_integer(&array),
+            Err(ResultError::Type(StackItemType::Array))
+        ));
+    }
+}
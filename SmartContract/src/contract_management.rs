@@ -0,0 +1,288 @@
+//! Deployment and update of contracts through the native `ContractManagement` contract.
+
+use neo_core::crypto::hash160;
+use neo_io::{Serializable, UInt160};
+use neo_tx::n3::Signer;
+use neo_vm::Instruction::Instruction;
+use neo_vm::OpCode::OpCode;
+use neo_vm::ScriptBuilder::ScriptBuilder;
+
+use crate::application_engine::STORAGE_PRICE;
+use crate::call_flags::CallFlags;
+use crate::contract_call::{emit_dynamic_call, ContractArg};
+use crate::contract_state::{ContractMethodDescriptor, ContractState};
+use crate::interop::InteropService;
+use crate::manifest::{ContractManifest, ContractParameterType};
+use crate::nef_file::NefFile;
+use crate::opcode_prices::opcode_price;
+use crate::transaction_builder::TransactionBuilder;
+
+/// Hash of the native `ContractManagement` contract of N3, 0xfffdc93764dbaddd97c48f252a53ea4643faa3fd.
+pub const CONTRACT_MANAGEMENT_HASH: UInt160 = UInt160([
+    0xfd, 0xa3, 0xfa, 0x43, 0x46, 0xea, 0x53, 0x2a, 0x25, 0xf8, 0xc4, 0x97, 0xdd, 0xad, 0xdb, 0x64, 0x37, 0xc9, 0xfd,
+    0xff,
+]);
+/// Default minimum fee of a deployment, 10 GAS, set by the committee.
+pub const DEFAULT_MINIMUM_DEPLOYMENT_FEE: i64 = 10_00000000;
+
+/**
+ * The hash of the contract `name` deployed by `sender` from a NEF file
+ * with `nef_checksum`, the hash of `ABORT, sender, nef_checksum, name`.
+ *
+ * It doesn't depend on the script, so updates keep the hash.
+ */
+pub fn get_contract_hash(sender: &UInt160, nef_checksum: u32, name: &str) -> UInt160 {
+    let mut sb = ScriptBuilder::new();
+    sb.emit(OpCode::ABORT, &[])
+        .emit_push_data(sender.as_bytes())
+        .emit_push(&nef_checksum.into())
+        .emit_push_string(name);
+    UInt160::from_slice(&hash160(&sb.to_array())).unwrap()
+}
+
+/// Emits a call of `ContractManagement.deploy`, the contract gets `data` in its `_deploy` method.
+pub fn emit_deploy<'a>(
+    sb: &'a mut ScriptBuilder,
+    nef: &NefFile,
+    manifest: &ContractManifest,
+    data: &ContractArg,
+) -> &'a mut ScriptBuilder {
+    emit_dynamic_call(
+        sb,
+        &CONTRACT_MANAGEMENT_HASH,
+        "deploy",
+        CallFlags::ALL,
+        &[
+            ContractArg::ByteArray(nef.to_array()),
+            ContractArg::String(manifest.to_string()),
+            data.clone(),
+        ],
+    )
+}
+
+/**
+ * Emits a call of the `update` method of the contract `hash`.
+ *
+ * `ContractManagement.update` updates the contract calling it, so the
+ * contract exposes an `update(nef, manifest, data)` method forwarding its
+ * arguments once it checked the witness of its owner.
+ */
+pub fn emit_update<'a>(
+    sb: &'a mut ScriptBuilder,
+    hash: &UInt160,
+    nef: &NefFile,
+    manifest: &ContractManifest,
+    data: &ContractArg,
+) -> &'a mut ScriptBuilder {
+    emit_dynamic_call(
+        sb,
+        hash,
+        "update",
+        CallFlags::ALL,
+        &[
+            ContractArg::ByteArray(nef.to_array()),
+            ContractArg::String(manifest.to_string()),
+            data.clone(),
+        ],
+    )
+}
+
+/// The fee `ContractManagement.deploy` charges: the storage of the NEF file and the manifest, or the minimum.
+pub fn deployment_fee(nef: &NefFile, manifest: &ContractManifest, minimum_deployment_fee: i64) -> i64 {
+    let size = nef.size() + manifest.to_string().len();
+    (STORAGE_PRICE * size as i64).max(minimum_deployment_fee)
+}
+
+/**
+ * A transaction deploying a contract, paid by `sender`.
+ *
+ * The system fee is what the node charges the deployment script with the
+ * default fees and exec fee factor. It doesn't cover the `_deploy` method
+ * of the contract, a contract with one needs `system_fee` to be raised.
+ */
+pub fn deploy_transaction(
+    sender: Signer,
+    nef: &NefFile,
+    manifest: &ContractManifest,
+    data: &ContractArg,
+    valid_until_block: u32,
+) -> TransactionBuilder {
+    let mut sb = ScriptBuilder::new();
+    emit_deploy(&mut sb, nef, manifest, data);
+    let mut builder = TransactionBuilder::new(sb.to_array(), vec![sender], valid_until_block);
+    builder.system_fee = script_cost(&builder.script) * builder.exec_fee_factor as i64
+        + deployment_fee(nef, manifest, DEFAULT_MINIMUM_DEPLOYMENT_FEE);
+    builder
+}
+
+/**
+ * A transaction updating the contract `hash`, signed by `signer`, the
+ * owner the `update` method of the contract checks.
+ *
+ * The system fee depends on the `update` method, it is left to the
+ * caller, estimated against a node or set from a previous update.
+ */
+pub fn update_transaction(
+    signer: Signer,
+    hash: &UInt160,
+    nef: &NefFile,
+    manifest: &ContractManifest,
+    data: &ContractArg,
+    valid_until_block: u32,
+) -> TransactionBuilder {
+    let mut sb = ScriptBuilder::new();
+    emit_update(&mut sb, hash, nef, manifest, data);
+    TransactionBuilder::new(sb.to_array(), vec![signer], valid_until_block)
+}
+
+/// The contract deployed by `sender`, as an `ApplicationHost` runs it.
+pub fn contract_state(id: i32, sender: &UInt160, nef: &NefFile, manifest: &ContractManifest) -> ContractState {
+    let methods = manifest
+        .abi
+        .methods
+        .iter()
+        .map(|method| ContractMethodDescriptor {
+            name: method.name.clone(),
+            parameters_count: method.parameters.len(),
+            offset: method.offset,
+            has_return_value: method.return_type != ContractParameterType::Void,
+            safe: method.safe,
+        })
+        .collect();
+    ContractState::new(
+        id,
+        get_contract_hash(sender, nef.checksum, &manifest.name),
+        &nef.script,
+        methods,
+    )
+}
+
+/// The price of the instructions and interops of a script without jumps, before the exec fee factor.
+fn script_cost(script: &[u8]) -> i64 {
+    let services = InteropService::new();
+    let mut cost = 0;
+    let mut ip = 0;
+    while ip < script.len() {
+        let instruction = Instruction::from_script(script, ip).expect("the builder emits valid instructions");
+        cost += opcode_price(instruction.opcode());
+        if instruction.opcode() == OpCode::SYSCALL {
+            cost += services
+                .get(instruction.token_u32())
+                .map_or(0, |descriptor| descriptor.fixed_price);
+        }
+        ip += instruction.size();
+    }
+    cost
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use neo_persistence::{DataCache, MemoryStore, Store};
+    use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+    use neo_vm::Script::Script;
+    use neo_vm::VMState::VMState;
+
+    use crate::application_engine::{ApplicationEngine, ApplicationHost, TEST_MODE_GAS};
+    use crate::contract_call::result_integer;
+    use crate::manifest::MethodDescriptor;
+    use crate::trigger_type::TriggerType;
+
+    use super::*;
+
+    fn manifest() -> ContractManifest {
+        let mut manifest = ContractManifest::new("Test");
+        manifest.abi.methods.push(MethodDescriptor {
+            name: "main".to_string(),
+            parameters: Vec::new(),
+            return_type: ContractParameterType::Integer,
+            offset: 0,
+            safe: true,
+        });
+        manifest
+    }
+
+    fn snapshot() -> DataCache {
+        DataCache::new(MemoryStore::new().get_snapshot())
+    }
+
+    #[test]
+    fn test_get_contract_hash() {
+        let nef = NefFile::new("test", "", vec![], vec![1, 2, 3]);
+        assert_eq!(
+            get_contract_hash(&UInt160::zero(), nef.checksum, "").to_string(),
+            "0x9b9628e4f1611af90e761eea8cc21372380c74b6"
+        );
+        let sender = UInt160::from_str("0xa400ff00ff00ff00ff00ff00ff00ff00ff00ff01").unwrap();
+        assert_eq!(
+            get_contract_hash(&sender, nef.checksum, "").to_string(),
+            "0x66eec404d86b918d084e62a29ac9990e3b6f4286"
+        );
+    }
+
+    #[test]
+    fn test_deploy_transaction() {
+        let nef = NefFile::new("test", "", vec![], vec![OpCode::PUSH1.0, OpCode::RET.0]);
+        let sender = Signer::called_by_entry(UInt160([7; 20]));
+        let mut builder = deploy_transaction(sender, &nef, &manifest(), &ContractArg::Null, 1000);
+        let system_fee = builder.system_fee;
+        assert!(system_fee > DEFAULT_MINIMUM_DEPLOYMENT_FEE);
+
+        // A stand-in for ContractManagement that drops its arguments: the script is charged as on the node.
+        let drop = [OpCode::DROP.0, OpCode::DROP.0, OpCode::DROP.0, OpCode::RET.0];
+        let method = ContractMethodDescriptor {
+            name: "deploy".to_string(),
+            parameters_count: 3,
+            offset: 0,
+            has_return_value: false,
+            safe: false,
+        };
+        let management = ContractState::new(-1, CONTRACT_MANAGEMENT_HASH, &drop, vec![method]);
+        let executed = builder.estimate_system_fee(snapshot(), &[management]).unwrap();
+        assert_eq!(
+            system_fee,
+            executed - 3 * opcode_price(OpCode::DROP) * builder.exec_fee_factor as i64 + DEFAULT_MINIMUM_DEPLOYMENT_FEE
+        );
+
+        let large = NefFile::new("test", "", vec![], vec![OpCode::NOP.0; 20000]);
+        assert_eq!(
+            deployment_fee(&large, &manifest(), DEFAULT_MINIMUM_DEPLOYMENT_FEE),
+            STORAGE_PRICE * (large.size() + manifest().to_string().len()) as i64
+        );
+    }
+
+    #[test]
+    fn test_contract_state() {
+        let nef = NefFile::new("test", "", vec![], vec![OpCode::PUSH5.0, OpCode::RET.0]);
+        let sender = UInt160([7; 20]);
+        let contract = contract_state(1, &sender, &nef, &manifest());
+        assert_eq!(contract.hash, get_contract_hash(&sender, nef.checksum, "Test"));
+        assert!(contract.method("main", 0).unwrap().has_return_value);
+
+        let mut sb = ScriptBuilder::new();
+        emit_dynamic_call(&mut sb, &contract.hash, "main", CallFlags::ALL, &[]);
+        let mut host = ApplicationHost::new(TriggerType::APPLICATION, None, snapshot(), None, TEST_MODE_GAS);
+        host.add_contract(contract);
+        let mut engine = ApplicationEngine::with_host(ExecutionEngineLimits::default(), host);
+        engine.load_script(Script::from(sb.to_array()), -1, 0).unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+        assert_eq!(
+            result_integer(&engine.result_stack().peek(0).unwrap()).unwrap(),
+            5.into()
+        );
+
+        let mut sb = ScriptBuilder::new();
+        emit_update(&mut sb, &UInt160([9; 20]), &nef, &manifest(), &ContractArg::Null);
+        let builder = update_transaction(
+            Signer::called_by_entry(sender),
+            &UInt160([9; 20]),
+            &nef,
+            &manifest(),
+            &ContractArg::Null,
+            1000,
+        );
+        assert_eq!(builder.script, sb.to_array());
+        assert_eq!(builder.system_fee, 0);
+    }
+}
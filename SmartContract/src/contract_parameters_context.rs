@@ -0,0 +1,547 @@
+//! Signatures collected for a transaction by several parties, in the JSON format of neo-cli.
+//!
+//! A context holds the unsigned transaction and, for each signer account, the
+//! verification script and the signatures added so far. Co-signers exchange
+//! it, add their signature with `add_signature`, and the last one turns it
+//! into the witnesses of the transaction once it is `completed`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use neo_core::crypto::hash160;
+use neo_core::neo_type::{PublicKeyBin, PUBLIC_KEY_BIN_LEN};
+use neo_core::protocol_settings::MAX_MULTISIG_KEYS;
+use neo_crypto::hex;
+use neo_io::UInt160;
+use neo_tx::n3::{Transaction, Witness};
+use neo_vm::OpCode::OpCode;
+use neo_vm::ScriptBuilder::ScriptBuilder;
+use serde_json::{json, Map, Value};
+
+use crate::error::ContextError;
+use crate::interop_descriptor::interop_hash;
+
+/// Value of the `type` field for transactions, the name of the class in the reference implementation.
+pub const TRANSACTION_TYPE: &str = "Neo.Network.P2P.Payloads.Transaction";
+
+/// Length of a signature, `r` and `s` of secp256r1.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// The verification script of an account and the parameters its invocation script pushes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContextItem {
+    /// `None` for deployed contracts, which are verified by their `verify` method.
+    pub script: Option<Vec<u8>>,
+    /// Signature parameters of the script, `None` until signed.
+    pub parameters: Vec<Option<Vec<u8>>>,
+    /// Signatures added so far, in the order they were added.
+    pub signatures: Vec<(PublicKeyBin, Vec<u8>)>,
+}
+
+impl ContextItem {
+    fn new(script: &[u8], parameter_count: usize) -> Self {
+        Self {
+            script: Some(script.to_vec()),
+            parameters: vec![None; parameter_count],
+            signatures: Vec::new(),
+        }
+    }
+
+    fn completed(&self) -> bool {
+        self.parameters.iter().all(Option::is_some)
+    }
+
+    /// Pushes the parameters in reverse order, the first one ends on top of the stack.
+    fn invocation_script(&self) -> Vec<u8> {
+        let mut sb = ScriptBuilder::new();
+        for parameter in self.parameters.iter().rev().flatten() {
+            sb.emit_push_data(parameter);
+        }
+        sb.to_array()
+    }
+
+    fn to_json(&self) -> Value {
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|parameter| match parameter {
+                Some(signature) => json!({ "type": "Signature", "value": base64::encode(signature) }),
+                None => json!({ "type": "Signature" }),
+            })
+            .collect::<Vec<_>>();
+        let signatures = self
+            .signatures
+            .iter()
+            .map(|(public_key, signature)| (hex::encode(&public_key[..]), Value::from(base64::encode(signature))))
+            .collect::<Map<_, _>>();
+
+        json!({
+            "script": self.script.as_ref().map(base64::encode),
+            "parameters": parameters,
+            "signatures": signatures,
+        })
+    }
+
+    fn from_json(json: &Value) -> Result<Self, ContextError> {
+        let script = match &json["script"] {
+            Value::Null => None,
+            script => Some(decode_base64(script)?),
+        };
+
+        let parameters = json["parameters"]
+            .as_array()
+            .ok_or_else(|| format_error("parameters must be an array"))?
+            .iter()
+            .map(|parameter| match parameter["type"].as_str() {
+                Some("Signature") => match &parameter["value"] {
+                    Value::Null => Ok(None),
+                    value => Ok(Some(decode_base64(value)?)),
+                },
+                Some(typ) => Err(ContextError::NotSupported(typ.to_string())),
+                None => Err(format_error("parameter without type")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let signatures = match &json["signatures"] {
+            Value::Null => Vec::new(),
+            Value::Object(signatures) => signatures
+                .iter()
+                .map(|(public_key, signature)| Ok((decode_public_key(public_key)?, decode_base64(signature)?)))
+                .collect::<Result<Vec<_>, ContextError>>()?,
+            _ => return Err(format_error("signatures must be an object")),
+        };
+
+        Ok(Self {
+            script,
+            parameters,
+            signatures,
+        })
+    }
+}
+
+/**
+ * The context in which the signers of a transaction add their signatures.
+ *
+ * Items are kept in the order of the signers, which is the order of the
+ * witnesses the node expects.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContractParametersContext {
+    pub transaction: Transaction,
+    /// Magic number of the network the signatures are for.
+    pub network: u32,
+    items: Vec<(UInt160, ContextItem)>,
+}
+
+impl ContractParametersContext {
+    pub fn new(transaction: Transaction, network: u32) -> Self {
+        Self {
+            transaction,
+            network,
+            items: Vec::new(),
+        }
+    }
+
+    /// The accounts that have to sign, the signers of the transaction.
+    pub fn script_hashes(&self) -> Vec<UInt160> {
+        self.transaction.signers.iter().map(|signer| signer.account).collect()
+    }
+
+    /// The data to sign, the network magic followed by the transaction hash.
+    pub fn sign_data(&self) -> Vec<u8> {
+        self.transaction.get_sign_data(self.network)
+    }
+
+    pub fn get_item(&self, script_hash: &UInt160) -> Option<&ContextItem> {
+        self.items
+            .iter()
+            .find(|(hash, _)| hash == script_hash)
+            .map(|(_, item)| item)
+    }
+
+    /**
+     * Adds the signature of `public_key` for the account of `verification_script`.
+     *
+     * The script must be a signature or multi-signature contract of a signer.
+     * Once a multi-signature account has its threshold of signatures, they
+     * are set as parameters in the order of the keys in the script.
+     */
+    pub fn add_signature(
+        &mut self,
+        verification_script: &[u8],
+        public_key: &PublicKeyBin,
+        signature: &[u8],
+    ) -> Result<(), ContextError> {
+        if signature.len() != SIGNATURE_LEN {
+            return Err(ContextError::InvalidSignatureLength(signature.len()));
+        }
+
+        if let Some((m, public_keys)) = parse_multisig_contract(verification_script) {
+            if !public_keys.contains(public_key) {
+                return Err(ContextError::KeyNotInContract(hex::encode(&public_key[..])));
+            }
+            let item = self.create_item(verification_script, m)?;
+            if item.completed() {
+                return Err(ContextError::AlreadyCompleted);
+            }
+            add_unique(item, public_key, signature)?;
+
+            if item.signatures.len() == m {
+                // Parameters are pushed in reverse, the signatures end up on the stack in the order of their keys.
+                let mut indexed = item
+                    .signatures
+                    .iter()
+                    .filter_map(|(key, signature)| Some((public_keys.iter().position(|k| k == key)?, signature)))
+                    .collect::<Vec<_>>();
+                indexed.sort_by(|a, b| b.0.cmp(&a.0));
+                item.parameters = indexed
+                    .into_iter()
+                    .map(|(_, signature)| Some(signature.clone()))
+                    .collect();
+            }
+            Ok(())
+        } else if let Some(key) = parse_signature_contract(verification_script) {
+            if &key != public_key {
+                return Err(ContextError::KeyNotInContract(hex::encode(&public_key[..])));
+            }
+            let item = self.create_item(verification_script, 1)?;
+            add_unique(item, public_key, signature)?;
+            item.parameters[0] = Some(signature.to_vec());
+            Ok(())
+        } else {
+            Err(ContextError::NotStandardContract)
+        }
+    }
+
+    /// Whether every signer has all the parameters of its verification script.
+    pub fn completed(&self) -> bool {
+        self.script_hashes()
+            .iter()
+            .all(|hash| self.get_item(hash).map_or(false, ContextItem::completed))
+    }
+
+    /// The witnesses of the transaction, in the order of its signers.
+    pub fn get_witnesses(&self) -> Result<Vec<Witness>, ContextError> {
+        if !self.completed() {
+            return Err(ContextError::NotCompleted);
+        }
+
+        Ok(self
+            .script_hashes()
+            .iter()
+            .map(|hash| {
+                let item = self.get_item(hash).unwrap();
+                Witness::new(item.invocation_script(), item.script.clone().unwrap_or_default())
+            })
+            .collect())
+    }
+
+    /// The transaction with the witnesses of the context.
+    pub fn to_signed_transaction(&self) -> Result<Transaction, ContextError> {
+        let mut transaction = self.transaction.clone();
+        transaction.witnesses = self.get_witnesses()?;
+        Ok(transaction)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let items = self
+            .items
+            .iter()
+            .map(|(hash, item)| (hash.to_string(), item.to_json()))
+            .collect::<Map<_, _>>();
+
+        json!({
+            "type": TRANSACTION_TYPE,
+            "hash": self.transaction.hash_hex(),
+            "data": base64::encode(self.transaction.serialize_unsigned()),
+            "items": items,
+            "network": self.network,
+        })
+    }
+
+    pub fn from_json(json: &Value) -> Result<Self, ContextError> {
+        match json["type"].as_str() {
+            Some(TRANSACTION_TYPE) => {}
+            Some(typ) => return Err(ContextError::NotSupported(typ.to_string())),
+            None => return Err(format_error("missing type")),
+        }
+
+        let transaction = Transaction::deserialize_unsigned(&decode_base64(&json["data"])?)
+            .map_err(|error| format_error(&error.to_string()))?;
+        if let Some(hash) = json["hash"].as_str() {
+            if hash != transaction.hash_hex() {
+                return Err(format_error("hash does not match data"));
+            }
+        }
+        let network = json["network"]
+            .as_u64()
+            .filter(|network| *network <= u32::MAX as u64)
+            .ok_or_else(|| format_error("invalid network"))? as u32;
+
+        let mut context = ContractParametersContext::new(transaction, network);
+        let script_hashes = context.script_hashes();
+        if let Some(items) = json["items"].as_object() {
+            for (hash, item) in items {
+                let hash =
+                    UInt160::from_str(hash).map_err(|_| format_error(&format!("invalid script hash {}", hash)))?;
+                if !script_hashes.contains(&hash) {
+                    return Err(ContextError::NotSigner(hash.to_string()));
+                }
+                context.items.push((hash, ContextItem::from_json(item)?));
+            }
+        }
+        Ok(context)
+    }
+
+    /// Returns the item of the account of `verification_script`, creating it if the account is a signer.
+    fn create_item(
+        &mut self,
+        verification_script: &[u8],
+        parameter_count: usize,
+    ) -> Result<&mut ContextItem, ContextError> {
+        let hash = UInt160::from_slice(&hash160(verification_script)).unwrap();
+        match self.items.iter().position(|(h, _)| *h == hash) {
+            Some(index) => Ok(&mut self.items[index].1),
+            None => {
+                if !self.script_hashes().contains(&hash) {
+                    return Err(ContextError::NotSigner(hash.to_string()));
+                }
+                self.items
+                    .push((hash, ContextItem::new(verification_script, parameter_count)));
+                Ok(&mut self.items.last_mut().unwrap().1)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ContractParametersContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl FromStr for ContractParametersContext {
+    type Err = ContextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let json = serde_json::from_str(s).map_err(|error| format_error(&error.to_string()))?;
+        ContractParametersContext::from_json(&json)
+    }
+}
+
+/// The public key of a signature contract, `PUSHDATA1 <key> SYSCALL System.Crypto.CheckSig`.
+pub fn parse_signature_contract(script: &[u8]) -> Option<PublicKeyBin> {
+    if script.len() != 40
+        || script[0] != OpCode::PUSHDATA1.0
+        || script[1] as usize != PUBLIC_KEY_BIN_LEN
+        || script[35] != OpCode::SYSCALL.0
+        || script[36..40] != interop_hash("System.Crypto.CheckSig").to_le_bytes()
+    {
+        return None;
+    }
+    Some(to_public_key(&script[2..35]))
+}
+
+/**
+ * The threshold and the public keys of a multi-signature contract,
+ * `PUSH m, PUSHDATA1 <key>..., PUSH n, SYSCALL System.Crypto.CheckMultisig`.
+ */
+pub fn parse_multisig_contract(script: &[u8]) -> Option<(usize, Vec<PublicKeyBin>)> {
+    let (m, mut i) = read_count(script, 0)?;
+    let mut public_keys = Vec::new();
+    while script.get(i) == Some(&OpCode::PUSHDATA1.0) && script.get(i + 1) == Some(&(PUBLIC_KEY_BIN_LEN as u8)) {
+        public_keys.push(to_public_key(script.get(i + 2..i + 2 + PUBLIC_KEY_BIN_LEN)?));
+        i += 2 + PUBLIC_KEY_BIN_LEN;
+    }
+    let (n, i) = read_count(script, i)?;
+    if n != public_keys.len() || m < 1 || m > n || n > MAX_MULTISIG_KEYS {
+        return None;
+    }
+
+    let mut syscall = vec![OpCode::SYSCALL.0];
+    syscall.extend_from_slice(&interop_hash("System.Crypto.CheckMultisig").to_le_bytes());
+    if script[i..] != syscall[..] {
+        return None;
+    }
+    Some((m, public_keys))
+}
+
+/// Reads a count pushed with `PUSH1` to `PUSH16`, `PUSHINT8` or `PUSHINT16`, returning it and the next offset.
+fn read_count(script: &[u8], i: usize) -> Option<(usize, usize)> {
+    match OpCode(*script.get(i)?) {
+        OpCode::PUSHINT8 => Some((*script.get(i + 1)? as usize, i + 2)),
+        OpCode::PUSHINT16 => {
+            let operand = script.get(i + 1..i + 3)?;
+            Some((u16::from_le_bytes([operand[0], operand[1]]) as usize, i + 3))
+        }
+        opcode if opcode.0 >= OpCode::PUSH1.0 && opcode.0 <= OpCode::PUSH16.0 => {
+            Some(((opcode.0 - OpCode::PUSH0.0) as usize, i + 1))
+        }
+        _ => None,
+    }
+}
+
+fn add_unique(item: &mut ContextItem, public_key: &PublicKeyBin, signature: &[u8]) -> Result<(), ContextError> {
+    if item.signatures.iter().any(|(key, _)| key == public_key) {
+        return Err(ContextError::DuplicateSignature(hex::encode(&public_key[..])));
+    }
+    item.signatures.push((*public_key, signature.to_vec()));
+    Ok(())
+}
+
+fn to_public_key(data: &[u8]) -> PublicKeyBin {
+    let mut public_key = [0u8; PUBLIC_KEY_BIN_LEN];
+    public_key.copy_from_slice(data);
+    public_key
+}
+
+fn decode_public_key(s: &str) -> Result<PublicKeyBin, ContextError> {
+    match hex::decode(s) {
+        Ok(data) if data.len() == PUBLIC_KEY_BIN_LEN => Ok(to_public_key(&data)),
+        _ => Err(format_error(&format!("invalid public key {}", s))),
+    }
+}
+
+fn decode_base64(value: &Value) -> Result<Vec<u8>, ContextError> {
+    value
+        .as_str()
+        .and_then(|s| base64::decode(s).ok())
+        .ok_or_else(|| format_error(&format!("invalid base64 string {}", value)))
+}
+
+fn format_error(message: &str) -> ContextError {
+    ContextError::Format(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_core::ProtocolSettings;
+    use neo_tx::n3::Signer;
+
+    use super::*;
+
+    fn public_key(i: u8) -> PublicKeyBin {
+        let mut key = [0x02; PUBLIC_KEY_BIN_LEN];
+        key[1] = i;
+        key
+    }
+
+    fn script_hash(script: &[u8]) -> UInt160 {
+        UInt160::from_slice(&hash160(script)).unwrap()
+    }
+
+    /// A transaction signed by a 2 out of 3 multi-signature account, then by a single key.
+    fn context() -> (ContractParametersContext, Vec<u8>, Vec<u8>) {
+        let settings = ProtocolSettings::N3_MAINNET;
+        let multisig = settings
+            .multisig_verification_script(2, &[public_key(3), public_key(1), public_key(2)])
+            .unwrap();
+        let single = settings.verification_script(&public_key(4));
+        let transaction = Transaction {
+            signers: vec![
+                Signer::called_by_entry(script_hash(&multisig)),
+                Signer::called_by_entry(script_hash(&single)),
+            ],
+            script: vec![0x11],
+            ..Default::default()
+        };
+        (
+            ContractParametersContext::new(transaction, settings.network),
+            multisig,
+            single,
+        )
+    }
+
+    #[test]
+    fn test_parse_contracts() {
+        let (_, multisig, single) = context();
+        assert_eq!(
+            parse_multisig_contract(&multisig),
+            Some((2, vec![public_key(1), public_key(2), public_key(3)]))
+        );
+        assert_eq!(parse_multisig_contract(&single), None);
+        assert_eq!(parse_signature_contract(&single), Some(public_key(4)));
+        assert_eq!(parse_signature_contract(&multisig), None);
+    }
+
+    #[test]
+    fn test_add_signatures() {
+        let (mut context, multisig, single) = context();
+        context.add_signature(&multisig, &public_key(3), &[3; 64]).unwrap();
+        assert!(matches!(
+            context.add_signature(&multisig, &public_key(3), &[3; 64]),
+            Err(ContextError::DuplicateSignature(_))
+        ));
+        assert!(matches!(
+            context.add_signature(&multisig, &public_key(4), &[4; 64]),
+            Err(ContextError::KeyNotInContract(_))
+        ));
+        assert!(!context.completed());
+
+        context.add_signature(&multisig, &public_key(1), &[1; 64]).unwrap();
+        assert!(matches!(
+            context.add_signature(&multisig, &public_key(2), &[2; 64]),
+            Err(ContextError::AlreadyCompleted)
+        ));
+        assert!(!context.completed());
+        assert!(matches!(context.get_witnesses(), Err(ContextError::NotCompleted)));
+
+        context.add_signature(&single, &public_key(4), &[4; 64]).unwrap();
+        assert!(context.completed());
+
+        let witnesses = context.get_witnesses().unwrap();
+        let mut invocation = vec![0x0c, 0x40];
+        invocation.extend_from_slice(&[1; 64]);
+        invocation.extend_from_slice(&[0x0c, 0x40]);
+        invocation.extend_from_slice(&[3; 64]);
+        assert_eq!(witnesses[0], Witness::new(invocation, multisig));
+        assert_eq!(witnesses[1].verification_script, single);
+        assert_eq!(context.to_signed_transaction().unwrap().witnesses, witnesses);
+    }
+
+    #[test]
+    fn test_not_signer() {
+        let (mut context, ..) = context();
+        let other = ProtocolSettings::N3_MAINNET.verification_script(&public_key(5));
+        assert!(matches!(
+            context.add_signature(&other, &public_key(5), &[5; 64]),
+            Err(ContextError::NotSigner(_))
+        ));
+        assert!(matches!(
+            context.add_signature(&[0x40], &public_key(5), &[5; 64]),
+            Err(ContextError::NotStandardContract)
+        ));
+        assert!(matches!(
+            context.add_signature(&other, &public_key(5), &[5; 63]),
+            Err(ContextError::InvalidSignatureLength(63))
+        ));
+    }
+
+    #[test]
+    fn test_json() {
+        let (mut context, multisig, _) = context();
+        context.add_signature(&multisig, &public_key(2), &[2; 64]).unwrap();
+
+        let json = context.to_json();
+        assert_eq!(json["type"], TRANSACTION_TYPE);
+        assert_eq!(json["hash"], context.transaction.hash_hex());
+        assert_eq!(json["network"], 860833102);
+        let item = &json["items"][script_hash(&multisig).to_string()];
+        assert_eq!(item["script"], base64::encode(&multisig));
+        assert_eq!(
+            item["parameters"],
+            json!([{ "type": "Signature" }, { "type": "Signature" }])
+        );
+        assert_eq!(
+            item["signatures"][hex::encode(&public_key(2)[..])],
+            base64::encode(&[2; 64][..])
+        );
+
+        let parsed = context.to_string().parse::<ContractParametersContext>().unwrap();
+        assert_eq!(parsed, context);
+
+        let mut json = json;
+        json["hash"] = Value::from("0x00");
+        assert!(ContractParametersContext::from_json(&json).is_err());
+    }
+}
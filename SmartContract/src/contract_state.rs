@@ -0,0 +1,50 @@
+use std::rc::Rc;
+
+use neo_io::UInt160;
+use neo_vm::Script::Script;
+
+/// A method a contract exposes to `System.Contract.Call`.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct ContractMethodDescriptor {
+    pub name: String,
+    pub parameters_count: usize,
+    /// Position of the method in the contract script.
+    pub offset: usize,
+    /// Whether the method leaves a value on the stack, `Void` methods return `Null` to dynamic calls.
+    pub has_return_value: bool,
+    /// Safe methods can neither write states nor notify.
+    pub safe: bool,
+}
+
+/**
+ * A deployed contract as seen by the `ApplicationEngine`.
+ *
+ * The `id` prefixes every storage key of the contract, so that contracts
+ * can't read or write each other's storage.
+ */
+#[derive(Debug, Clone)]
+pub struct ContractState {
+    pub id: i32,
+    pub hash: UInt160,
+    pub script: Rc<Script>,
+    pub methods: Vec<ContractMethodDescriptor>,
+}
+
+impl ContractState {
+    /// The script is checked in strict mode, it panics if the script is invalid.
+    pub fn new(id: i32, hash: UInt160, script: &[u8], methods: Vec<ContractMethodDescriptor>) -> Self {
+        Self {
+            id,
+            hash,
+            script: Rc::new(Script::from_bytes(script, true).unwrap_or_else(|e| panic!("{}", e))),
+            methods,
+        }
+    }
+
+    /// Returns the method with the given name and number of parameters.
+    pub fn method(&self, name: &str, parameters_count: usize) -> Option<&ContractMethodDescriptor> {
+        self.methods
+            .iter()
+            .find(|method| method.name == name && method.parameters_count == parameters_count)
+    }
+}
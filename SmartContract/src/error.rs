@@ -0,0 +1,131 @@
+use failure::Fail;
+use neo_io::IoError;
+use neo_vm::Types::StackItemType::StackItemType;
+use neo_vm::VmError::VmError;
+use neo_wallet::signing::SigningError;
+
+#[derive(Debug, Fail)]
+pub enum SerializationError {
+    #[fail(display = "{}", _0)]
+    Io(#[cause] IoError),
+
+    #[fail(display = "invalid format: {}", _0)]
+    Format(String),
+
+    #[fail(display = "{:?} can't be serialized", _0)]
+    NotSupported(StackItemType),
+
+    #[fail(display = "the item references itself or is referenced twice")]
+    CircularReference,
+
+    #[fail(display = "size exceeds the maximum of {}", _0)]
+    MaxSizeExceeded(usize),
+
+    #[fail(display = "item count exceeds the maximum of {}", _0)]
+    MaxItemsExceeded(usize),
+
+    #[fail(display = "depth exceeds the maximum of {}", _0)]
+    MaxDepthExceeded(usize),
+}
+
+#[derive(Debug, Fail)]
+pub enum ContextError {
+    #[fail(display = "invalid context format: {}", _0)]
+    Format(String),
+
+    #[fail(display = "{} is not supported", _0)]
+    NotSupported(String),
+
+    #[fail(display = "{} is not a signer of the transaction", _0)]
+    NotSigner(String),
+
+    #[fail(display = "the script is neither a signature nor a multi-signature contract")]
+    NotStandardContract,
+
+    #[fail(display = "public key {} is not in the contract", _0)]
+    KeyNotInContract(String),
+
+    #[fail(display = "public key {} already signed", _0)]
+    DuplicateSignature(String),
+
+    #[fail(display = "invalid signature length: {}", _0)]
+    InvalidSignatureLength(usize),
+
+    #[fail(display = "the account already has all its signatures")]
+    AlreadyCompleted,
+
+    #[fail(display = "the context is not completed")]
+    NotCompleted,
+}
+
+#[derive(Debug, Fail)]
+pub enum ManifestError {
+    #[fail(display = "invalid manifest format: {}", _0)]
+    Format(String),
+
+    #[fail(display = "duplicate {}", _0)]
+    Duplicate(String),
+
+    #[fail(display = "manifest size {} exceeds the maximum of {}", _0, _1)]
+    TooLarge(usize, usize),
+
+    #[fail(display = "the signature of group {} is invalid", _0)]
+    InvalidGroup(String),
+}
+
+#[derive(Debug, Fail)]
+pub enum ResultError {
+    #[fail(display = "expected {} result(s), found {}", _0, _1)]
+    Count(usize, usize),
+
+    #[fail(display = "unexpected result type {:?}", _0)]
+    Type(StackItemType),
+
+    #[fail(display = "invalid result: {}", _0)]
+    Format(String),
+}
+
+#[derive(Debug, Fail)]
+pub enum AmountError {
+    #[fail(display = "invalid amount: {}", _0)]
+    Format(String),
+
+    #[fail(display = "the amount has more than {} decimals", _0)]
+    Precision(u8),
+}
+
+#[derive(Debug, Fail)]
+pub enum BuildError {
+    #[fail(display = "the script faulted after consuming {} datoshi", _0)]
+    Fault(i64),
+
+    #[fail(display = "the verification script of {} is neither a signature nor a multi-signature contract", _0)]
+    UnsupportedAccount(String),
+
+    #[fail(display = "{}", _0)]
+    Signing(SigningError),
+}
+
+impl From<IoError> for SerializationError {
+    fn from(error: IoError) -> Self {
+        SerializationError::Io(error)
+    }
+}
+
+impl From<VmError> for SerializationError {
+    fn from(error: VmError) -> Self {
+        SerializationError::Format(error.to_string())
+    }
+}
+
+impl From<VmError> for ResultError {
+    fn from(error: VmError) -> Self {
+        ResultError::Format(error.to_string())
+    }
+}
+
+impl From<SigningError> for BuildError {
+    fn from(error: SigningError) -> Self {
+        BuildError::Signing(error)
+    }
+}
@@ -1,20 +1,28 @@
-mod SmartContract {
-    #[derive(Debug)]
-    pub(crate) enum FindOptions {
-        None = 0,
+/**
+ * Options of `System.Storage.Find`, stored as a bit set.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Default)]
+pub struct FindOptions(pub u8);
 
-        KeysOnly = 1 << 0,
-        RemovePrefix = 1 << 1,
-        ValuesOnly = 1 << 2,
-        DeserializeValues = 1 << 3,
-        PickField0 = 1 << 4,
-        PickField1 = 1 << 5,
+impl FindOptions {
+    /// The iterator returns `Struct`s of the key and the value.
+    pub const NONE: FindOptions = FindOptions(0);
+    /// The iterator only returns the keys.
+    pub const KEYS_ONLY: FindOptions = FindOptions(1 << 0);
+    /// The prefix is removed from the returned keys.
+    pub const REMOVE_PREFIX: FindOptions = FindOptions(1 << 1);
+    /// The iterator only returns the values.
+    pub const VALUES_ONLY: FindOptions = FindOptions(1 << 2);
+    /// The values are deserialized as stack items.
+    pub const DESERIALIZE_VALUES: FindOptions = FindOptions(1 << 3);
+    /// Only the field 0 of the deserialized values is returned.
+    pub const PICK_FIELD0: FindOptions = FindOptions(1 << 4);
+    /// Only the field 1 of the deserialized values is returned.
+    pub const PICK_FIELD1: FindOptions = FindOptions(1 << 5);
 
-        All = FindOptions::KeysOnly
-            | FindOptions::RemovePrefix
-            | FindOptions::ValuesOnly
-            | FindOptions::DeserializeValues
-            | FindOptions::PickField0
-            | FindOptions::PickField1,
+    pub const ALL: FindOptions = FindOptions(0b0011_1111);
+
+    pub fn contains(&self, other: FindOptions) -> bool {
+        self.0 & other.0 == other.0
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,100 @@
+//! `System.Contract.*`: calling other contracts.
+
+use std::convert::TryFrom;
+
+use neo_io::UInt160;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::VmError::VmError;
+
+use crate::application_engine::{context_state, current_call_flags, load_contract, ApplicationEngine};
+use crate::call_flags::CallFlags;
+use crate::interop::{pop_uint160, InteropService};
+use crate::interop_descriptor::InteropDescriptor;
+
+pub(crate) fn register(service: &mut InteropService) {
+    service.register(InteropDescriptor::new(
+        "System.Contract.Call",
+        call,
+        1 << 15,
+        CallFlags::READ_STATES | CallFlags::ALLOW_CALL,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Contract.GetCallFlags",
+        get_call_flags,
+        1 << 10,
+        CallFlags::NONE,
+    ));
+}
+
+/// Takes the contract hash, the method name, the `CallFlags` and the array of arguments.
+fn call(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let hash = pop_uint160(engine)?;
+    let method = engine.pop()?.string()?;
+    let call_flags = engine.pop()?.integer()?;
+    let call_flags = u8::try_from(&call_flags)
+        .ok()
+        .and_then(CallFlags::from_byte)
+        .ok_or_else(|| VmError::invalid_operation(format!("Invalid call flags: {}", call_flags)))?;
+    let args = engine.pop()?;
+    let args = match args.as_array() {
+        Some(args) => args.items().clone(),
+        None => {
+            return Err(VmError::invalid_operation(format!(
+                "Invalid cast from {:?} to Array",
+                args.get_type()
+            )))
+        }
+    };
+    call_contract(engine, &hash, &method, call_flags, args)
+}
+
+/**
+ * Loads `method` of the contract `hash` with `args`, the first argument on top.
+ *
+ * The callee gets the requested flags that its caller has, without
+ * `WriteStates` and `AllowNotify` for safe methods. It always leaves one
+ * value to its caller, `Null` for the methods that return nothing.
+ */
+pub fn call_contract(
+    engine: &mut ApplicationEngine,
+    hash: &UInt160,
+    method: &str,
+    call_flags: CallFlags,
+    args: Vec<StackItem>,
+) -> Result<(), VmError> {
+    if method.starts_with('_') {
+        return Err(VmError::invalid_operation(format!("Invalid method name: {}", method)));
+    }
+    let contract = engine
+        .host()
+        .contract(hash)
+        .ok_or_else(|| VmError::invalid_operation(format!("Called contract does not exist: {}", hash)))?;
+    let descriptor = contract
+        .method(method, args.len())
+        .ok_or_else(|| {
+            VmError::invalid_operation(format!(
+                "Method \"{}\" with {} parameter(s) doesn't exist in the contract {}.",
+                method,
+                args.len(),
+                hash
+            ))
+        })?
+        .clone();
+
+    let mut call_flags = call_flags & current_call_flags(engine);
+    if descriptor.safe {
+        call_flags = call_flags & !(CallFlags::WRITE_STATES | CallFlags::ALLOW_NOTIFY);
+    }
+    let context = load_contract(engine, contract, &descriptor, call_flags)?;
+    context_state(context).is_dynamic_call = true;
+    for arg in args.into_iter().rev() {
+        engine.push(arg);
+    }
+    Ok(())
+}
+
+fn get_call_flags(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let call_flags = current_call_flags(engine);
+    engine.push_integer(call_flags.0);
+    Ok(())
+}
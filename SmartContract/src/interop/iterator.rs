@@ -0,0 +1,123 @@
+//! `System.Iterator.*`: walking the results of `System.Storage.Find`.
+
+use std::cell::Cell;
+
+use neo_persistence::Entry;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::Types::Struct::Struct;
+use neo_vm::VmError::VmError;
+
+use crate::application_engine::ApplicationEngine;
+use crate::binary_serializer;
+use crate::call_flags::CallFlags;
+use crate::find_options::FindOptions;
+use crate::interop::{pop_interface, InteropService};
+use crate::interop_descriptor::InteropDescriptor;
+
+/**
+ * Iterator over storage entries, given to scripts as an interop interface.
+ *
+ * It starts before the first entry, `System.Iterator.Next` must be called
+ * before reading a value.
+ */
+#[derive(Debug)]
+pub struct StorageIterator {
+    entries: Vec<Entry>,
+    /// Length of the prefix, contract id included, removed with `FindOptions::REMOVE_PREFIX`.
+    prefix_length: usize,
+    options: FindOptions,
+    /// Number of `next` calls, the current entry is the previous one.
+    position: Cell<usize>,
+}
+
+impl StorageIterator {
+    pub fn new(entries: Vec<Entry>, prefix_length: usize, options: FindOptions) -> Self {
+        Self {
+            entries,
+            prefix_length,
+            options,
+            position: Cell::new(0),
+        }
+    }
+
+    /// Advances to the next entry, returns whether there is one.
+    pub fn next(&self) -> bool {
+        if self.position.get() <= self.entries.len() {
+            self.position.set(self.position.get() + 1);
+        }
+        self.position.get() <= self.entries.len()
+    }
+
+    /// The current entry as a stack item, shaped by the `FindOptions`.
+    pub fn value(&self, engine: &ApplicationEngine) -> Result<StackItem, VmError> {
+        let position = self.position.get();
+        if position == 0 || position > self.entries.len() {
+            return Err(VmError::invalid_operation("The iterator has no current value."));
+        }
+        let (key, value) = &self.entries[position - 1];
+        let key = if self.options.contains(FindOptions::REMOVE_PREFIX) {
+            key[self.prefix_length..].to_vec()
+        } else {
+            key[4..].to_vec()
+        };
+        let key = StackItem::from(key);
+        let mut value: StackItem = if self.options.contains(FindOptions::DESERIALIZE_VALUES) {
+            let reference_counter = Some(engine.reference_counter().clone());
+            binary_serializer::deserialize(value, engine.limits(), reference_counter)
+                .map_err(|e| VmError::invalid_operation(e.to_string()))?
+        } else {
+            StackItem::from(value.clone())
+        };
+        if self.options.contains(FindOptions::PICK_FIELD0) {
+            value = field(&value, 0)?;
+        } else if self.options.contains(FindOptions::PICK_FIELD1) {
+            value = field(&value, 1)?;
+        }
+        if self.options.contains(FindOptions::KEYS_ONLY) {
+            Ok(key)
+        } else if self.options.contains(FindOptions::VALUES_ONLY) {
+            Ok(value)
+        } else {
+            Ok(Struct::new(Some(engine.reference_counter().clone()), vec![key, value]).into())
+        }
+    }
+}
+
+fn field(item: &StackItem, index: usize) -> Result<StackItem, VmError> {
+    match item.as_array() {
+        Some(array) if index < array.items().len() => Ok(array.get(index)),
+        Some(_) => Err(VmError::invalid_operation(format!("The value has no field {}.", index))),
+        None => Err(VmError::invalid_operation(format!(
+            "Invalid cast from {:?} to Array",
+            item.get_type()
+        ))),
+    }
+}
+
+pub(crate) fn register(service: &mut InteropService) {
+    service.register(InteropDescriptor::new(
+        "System.Iterator.Next",
+        next,
+        1 << 15,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Iterator.Value",
+        value,
+        1 << 4,
+        CallFlags::NONE,
+    ));
+}
+
+fn next(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let iterator = pop_interface::<StorageIterator>(engine)?;
+    engine.push_boolean(iterator.next());
+    Ok(())
+}
+
+fn value(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let iterator = pop_interface::<StorageIterator>(engine)?;
+    let value = iterator.value(engine)?;
+    engine.push(value);
+    Ok(())
+}
@@ -0,0 +1,109 @@
+//! The interoperable services a script reaches through `SYSCALL`.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use neo_io::UInt160;
+use neo_vm::Types::InteropInterface::InteropInterface;
+use neo_vm::VmError::VmError;
+
+use crate::application_engine::ApplicationEngine;
+use crate::interop_descriptor::InteropDescriptor;
+
+pub mod contract;
+pub mod iterator;
+pub mod runtime;
+pub mod storage;
+
+/**
+ * Registry of the interop descriptors, indexed by the hash of their name.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct InteropService {
+    descriptors: HashMap<u32, InteropDescriptor>,
+}
+
+impl InteropService {
+    /// The core services: `System.Runtime.*`, `System.Storage.*`, `System.Iterator.*` and `System.Contract.*`.
+    /// `InteropService::default()` is empty.
+    pub fn new() -> Self {
+        let mut service = Self::default();
+        runtime::register(&mut service);
+        storage::register(&mut service);
+        iterator::register(&mut service);
+        contract::register(&mut service);
+        service
+    }
+
+    /// Adds a descriptor, returning the one it replaces.
+    pub fn register(&mut self, descriptor: InteropDescriptor) -> Option<InteropDescriptor> {
+        self.descriptors.insert(descriptor.hash, descriptor)
+    }
+
+    pub fn get(&self, hash: u32) -> Option<&InteropDescriptor> {
+        self.descriptors.get(&hash)
+    }
+
+    pub fn descriptors(&self) -> impl Iterator<Item = &InteropDescriptor> {
+        self.descriptors.values()
+    }
+
+    /// The names of the services by hash, with which `neo_vm::Disassembler` resolves `SYSCALL`s.
+    pub fn names(&self) -> HashMap<u32, String> {
+        self.descriptors()
+            .map(|descriptor| (descriptor.hash, descriptor.name.clone()))
+            .collect()
+    }
+}
+
+pub(crate) fn pop_bytes(engine: &mut ApplicationEngine) -> Result<Vec<u8>, VmError> {
+    engine.pop()?.span()
+}
+
+pub(crate) fn pop_uint160(engine: &mut ApplicationEngine) -> Result<UInt160, VmError> {
+    let data = pop_bytes(engine)?;
+    UInt160::from_slice(&data)
+        .map_err(|_| VmError::invalid_operation(format!("Invalid UInt160 of {} bytes", data.len())))
+}
+
+pub(crate) fn pop_interface<T: 'static>(engine: &mut ApplicationEngine) -> Result<Rc<T>, VmError> {
+    let item = engine.pop()?;
+    item.as_interop_interface()
+        .and_then(|interface| interface.interface::<T>())
+        .ok_or_else(|| {
+            VmError::invalid_operation(format!(
+                "Invalid cast from {:?} to {}",
+                item.get_type(),
+                std::any::type_name::<T>()
+            ))
+        })
+}
+
+pub(crate) fn push_bytes(engine: &mut ApplicationEngine, data: Vec<u8>) {
+    engine.push(data.into());
+}
+
+pub(crate) fn push_interface<T: Any>(engine: &mut ApplicationEngine, value: T) {
+    engine.push(InteropInterface::new(Rc::new(value)).into());
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_vm::Assembler::assemble;
+    use neo_vm::Disassembler::disassemble;
+
+    use super::*;
+    use crate::interop_descriptor::interop_hash;
+
+    #[test]
+    fn test_names() {
+        let names = InteropService::new().names();
+        let script = assemble("SYSCALL System.Runtime.Log", &names).unwrap();
+        assert_eq!(script[1..], interop_hash("System.Runtime.Log").to_le_bytes());
+        assert_eq!(
+            disassemble(&script, &names).unwrap(),
+            "    0  SYSCALL    System.Runtime.Log\n"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! `System.Runtime.*`: information about the execution and the events a script sends.
+
+use neo_core::crypto::hash160;
+use neo_io::UInt160;
+use neo_tx::n3::{WitnessCondition, WitnessRule, WitnessRuleAction, WitnessScope};
+use neo_vm::ScriptBuilder::ScriptBuilder;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::VmError::VmError;
+
+use crate::application_engine::{
+    calling_script_hash, current_script_hash, entry_script_hash, ApplicationEngine, LogEventArgs, NotifyEventArgs,
+    MAX_EVENT_NAME, MAX_NOTIFICATION_SIZE,
+};
+use crate::call_flags::CallFlags;
+use crate::interop::{pop_bytes, push_bytes, InteropService};
+use crate::interop_descriptor::{interop_hash, InteropDescriptor};
+
+/// Name of the platform returned by `System.Runtime.Platform`.
+pub const PLATFORM: &str = "NEO";
+
+pub(crate) fn register(service: &mut InteropService) {
+    service.register(InteropDescriptor::new(
+        "System.Runtime.Platform",
+        platform,
+        1 << 3,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.GetTrigger",
+        get_trigger,
+        1 << 3,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.GetTime",
+        get_time,
+        1 << 3,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.GetExecutingScriptHash",
+        get_executing_script_hash,
+        1 << 4,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.GetCallingScriptHash",
+        get_calling_script_hash,
+        1 << 4,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.GetEntryScriptHash",
+        get_entry_script_hash,
+        1 << 4,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.CheckWitness",
+        check_witness_interop,
+        1 << 10,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.GasLeft",
+        gas_left,
+        1 << 4,
+        CallFlags::NONE,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.Notify",
+        notify,
+        1 << 15,
+        CallFlags::ALLOW_NOTIFY,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Runtime.Log",
+        log,
+        1 << 15,
+        CallFlags::ALLOW_NOTIFY,
+    ));
+}
+
+fn platform(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    push_bytes(engine, PLATFORM.as_bytes().to_vec());
+    Ok(())
+}
+
+fn get_trigger(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let trigger = engine.host().trigger();
+    engine.push_integer(trigger.0);
+    Ok(())
+}
+
+/// Timestamp of the persisting block, in milliseconds.
+fn get_time(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let timestamp = match engine.host().persisting_block() {
+        Some(block) => block.header.timestamp,
+        None => {
+            return Err(VmError::invalid_operation(
+                "GetTime can only be called while persisting a block.",
+            ))
+        }
+    };
+    engine.push_integer(timestamp);
+    Ok(())
+}
+
+fn push_hash(engine: &mut ApplicationEngine, hash: Option<UInt160>) {
+    match hash {
+        Some(hash) => push_bytes(engine, hash.as_bytes().to_vec()),
+        None => engine.push(StackItem::Null),
+    }
+}
+
+fn get_executing_script_hash(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let hash = current_script_hash(engine);
+    push_hash(engine, hash);
+    Ok(())
+}
+
+fn get_calling_script_hash(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let hash = calling_script_hash(engine);
+    push_hash(engine, hash);
+    Ok(())
+}
+
+fn get_entry_script_hash(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let hash = entry_script_hash(engine);
+    push_hash(engine, hash);
+    Ok(())
+}
+
+/// Takes a script hash or a public key, whose hash is the one of its signature contract.
+fn check_witness_interop(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let data = pop_bytes(engine)?;
+    let hash = match data.len() {
+        20 => UInt160::from_slice(&data).unwrap(),
+        33 => {
+            let mut sb = ScriptBuilder::new();
+            sb.emit_push_data(&data)
+                .emit_syscall(interop_hash("System.Crypto.CheckSig"));
+            UInt160::from_slice(&hash160(&sb.to_array())).unwrap()
+        }
+        length => {
+            return Err(VmError::invalid_operation(format!(
+                "Invalid hashOrPubkey of {} bytes.",
+                length
+            )))
+        }
+    };
+    let result = check_witness(engine, &hash);
+    engine.push_boolean(result);
+    Ok(())
+}
+
+/**
+ * Whether `hash` witnessed the script container in the current context.
+ *
+ * The calling script always witnesses, otherwise `hash` must be a signer of
+ * the transaction whose scopes allow the current context. Group conditions
+ * never match, contracts have no groups yet.
+ */
+pub fn check_witness(engine: &ApplicationEngine, hash: &UInt160) -> bool {
+    if calling_script_hash(engine).as_ref() == Some(hash) {
+        return true;
+    }
+    let signer = match engine.host().script_container() {
+        Some(transaction) => match transaction.signers.iter().find(|signer| &signer.account == hash) {
+            Some(signer) => signer.clone(),
+            None => return false,
+        },
+        None => return false,
+    };
+
+    let mut rules = Vec::new();
+    if signer.scopes.contains(WitnessScope::GLOBAL) {
+        rules.push(allow(WitnessCondition::Boolean(true)));
+    } else {
+        if signer.scopes.contains(WitnessScope::CALLED_BY_ENTRY) {
+            rules.push(allow(WitnessCondition::CalledByEntry));
+        }
+        if signer.scopes.contains(WitnessScope::CUSTOM_CONTRACTS) {
+            rules.extend(
+                signer
+                    .allowed_contracts
+                    .iter()
+                    .map(|contract| allow(WitnessCondition::ScriptHash(*contract))),
+            );
+        }
+        if signer.scopes.contains(WitnessScope::CUSTOM_GROUPS) {
+            rules.extend(
+                signer
+                    .allowed_groups
+                    .iter()
+                    .map(|group| allow(WitnessCondition::Group(*group))),
+            );
+        }
+        if signer.scopes.contains(WitnessScope::WITNESS_RULES) {
+            rules.extend(signer.rules.iter().cloned());
+        }
+    }
+    rules
+        .iter()
+        .find(|rule| matches(engine, &rule.condition))
+        .is_some_and(|rule| rule.action == WitnessRuleAction::Allow)
+}
+
+fn allow(condition: WitnessCondition) -> WitnessRule {
+    WitnessRule {
+        action: WitnessRuleAction::Allow,
+        condition,
+    }
+}
+
+fn matches(engine: &ApplicationEngine, condition: &WitnessCondition) -> bool {
+    match condition {
+        WitnessCondition::Boolean(value) => *value,
+        WitnessCondition::Not(condition) => !matches(engine, condition),
+        WitnessCondition::And(conditions) => conditions.iter().all(|condition| matches(engine, condition)),
+        WitnessCondition::Or(conditions) => conditions.iter().any(|condition| matches(engine, condition)),
+        WitnessCondition::ScriptHash(hash) => current_script_hash(engine).as_ref() == Some(hash),
+        WitnessCondition::CalledByEntry => {
+            let calling = calling_script_hash(engine);
+            calling.is_none() || calling == entry_script_hash(engine)
+        }
+        WitnessCondition::CalledByContract(hash) => calling_script_hash(engine).as_ref() == Some(hash),
+        WitnessCondition::Group(_) | WitnessCondition::CalledByGroup(_) => false,
+    }
+}
+
+fn gas_left(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let gas_left = engine.host().gas_left();
+    engine.push_integer(gas_left);
+    Ok(())
+}
+
+/// Takes the event name and its state, an array.
+fn notify(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let event_name = engine.pop()?.string()?;
+    if event_name.len() > MAX_EVENT_NAME {
+        return Err(VmError::invalid_operation(format!(
+            "The event name is longer than {} bytes.",
+            MAX_EVENT_NAME
+        )));
+    }
+    let state = engine.pop()?;
+    if state.as_array().is_none() {
+        return Err(VmError::invalid_operation(format!(
+            "Invalid cast from {:?} to Array",
+            state.get_type()
+        )));
+    }
+    let script_hash = current_script_hash(engine).unwrap();
+    engine.host_mut().notify(NotifyEventArgs {
+        script_hash,
+        event_name,
+        state,
+    });
+    Ok(())
+}
+
+fn log(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let message = pop_bytes(engine)?;
+    if message.len() > MAX_NOTIFICATION_SIZE {
+        return Err(VmError::invalid_operation(format!(
+            "The message is longer than {} bytes.",
+            MAX_NOTIFICATION_SIZE
+        )));
+    }
+    let message = String::from_utf8(message).map_err(|_| VmError::invalid_operation("invalid UTF-8 string"))?;
+    let script_hash = current_script_hash(engine).unwrap();
+    engine.host_mut().log(LogEventArgs { script_hash, message });
+    Ok(())
+}
@@ -0,0 +1,241 @@
+//! `System.Storage.*`: the key-value storage of the running contract.
+
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use neo_persistence::SeekDirection;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::VmError::VmError;
+
+use crate::application_engine::{context_state, current_script_hash, ApplicationEngine, STORAGE_PRICE};
+use crate::call_flags::CallFlags;
+use crate::contract_state::ContractState;
+use crate::find_options::FindOptions;
+use crate::interop::iterator::StorageIterator;
+use crate::interop::{pop_bytes, pop_interface, push_bytes, push_interface, InteropService};
+use crate::interop_descriptor::InteropDescriptor;
+
+/// Maximum size of a storage key, without the contract id.
+pub const MAX_KEY_SIZE: usize = 64;
+/// Maximum size of a storage value.
+pub const MAX_VALUE_SIZE: usize = u16::MAX as usize;
+
+/// Handle on the storage of a contract, given to scripts as an interop interface.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct StorageContext {
+    /// Id of the contract owning the storage.
+    pub id: i32,
+    pub is_read_only: bool,
+}
+
+/// Key of `key` in the storage of the contract `id`.
+pub fn storage_key(id: i32, key: &[u8]) -> Vec<u8> {
+    let mut storage_key = Vec::with_capacity(4 + key.len());
+    storage_key.extend_from_slice(&id.to_le_bytes());
+    storage_key.extend_from_slice(key);
+    storage_key
+}
+
+pub(crate) fn register(service: &mut InteropService) {
+    service.register(InteropDescriptor::new(
+        "System.Storage.GetContext",
+        get_context,
+        1 << 4,
+        CallFlags::READ_STATES,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Storage.GetReadOnlyContext",
+        get_read_only_context,
+        1 << 4,
+        CallFlags::READ_STATES,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Storage.AsReadOnly",
+        as_read_only,
+        1 << 4,
+        CallFlags::READ_STATES,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Storage.Get",
+        get,
+        1 << 15,
+        CallFlags::READ_STATES,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Storage.Find",
+        find,
+        1 << 15,
+        CallFlags::READ_STATES,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Storage.Put",
+        put,
+        1 << 15,
+        CallFlags::WRITE_STATES,
+    ));
+    service.register(InteropDescriptor::new(
+        "System.Storage.Delete",
+        delete,
+        1 << 15,
+        CallFlags::WRITE_STATES,
+    ));
+}
+
+/// The contract running in the current context, only contracts have a storage.
+fn current_contract(engine: &ApplicationEngine) -> Result<Rc<ContractState>, VmError> {
+    let contract = engine
+        .current_context()
+        .and_then(|context| context_state(context).contract.clone());
+    if let Some(contract) = contract {
+        return Ok(contract);
+    }
+    let hash = current_script_hash(engine).unwrap();
+    engine
+        .host()
+        .contract(&hash)
+        .ok_or_else(|| VmError::invalid_operation(format!("Contract not found: {}", hash)))
+}
+
+/// Errors of the store fault the engine like the script ones.
+fn storage_error(error: impl ToString) -> VmError {
+    VmError::invalid_operation(error.to_string())
+}
+
+fn get_context(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let id = current_contract(engine)?.id;
+    push_interface(
+        engine,
+        StorageContext {
+            id,
+            is_read_only: false,
+        },
+    );
+    Ok(())
+}
+
+fn get_read_only_context(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let id = current_contract(engine)?.id;
+    push_interface(engine, StorageContext { id, is_read_only: true });
+    Ok(())
+}
+
+fn as_read_only(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let context = pop_interface::<StorageContext>(engine)?;
+    push_interface(
+        engine,
+        StorageContext {
+            is_read_only: true,
+            ..*context
+        },
+    );
+    Ok(())
+}
+
+fn get(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let context = pop_interface::<StorageContext>(engine)?;
+    let key = pop_bytes(engine)?;
+    let value = engine
+        .host()
+        .snapshot()
+        .try_get(&storage_key(context.id, &key))
+        .map_err(storage_error)?;
+    match value {
+        Some(value) => push_bytes(engine, value),
+        None => engine.push(StackItem::Null),
+    }
+    Ok(())
+}
+
+/// Takes the context, the prefix and the `FindOptions`, returns an iterator over the matching entries.
+fn find(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let context = pop_interface::<StorageContext>(engine)?;
+    let prefix = pop_bytes(engine)?;
+    let options = engine.pop()?.integer()?;
+    let options = match u8::try_from(&options) {
+        Ok(options) if options & !FindOptions::ALL.0 == 0 => FindOptions(options),
+        _ => return Err(VmError::invalid_operation(format!("Invalid FindOptions: {}", options))),
+    };
+    let keys_only = options.contains(FindOptions::KEYS_ONLY);
+    let values_only = options.contains(FindOptions::VALUES_ONLY);
+    let deserialize = options.contains(FindOptions::DESERIALIZE_VALUES);
+    let pick_field0 = options.contains(FindOptions::PICK_FIELD0);
+    let pick_field1 = options.contains(FindOptions::PICK_FIELD1);
+    if keys_only && (values_only || deserialize || pick_field0 || pick_field1) {
+        return Err(VmError::invalid_operation(
+            "KeysOnly can't be used with ValuesOnly, DeserializeValues, PickField0, or PickField1",
+        ));
+    }
+    if values_only && (keys_only || options.contains(FindOptions::REMOVE_PREFIX)) {
+        return Err(VmError::invalid_operation(
+            "ValuesOnly can't be used with KeysOnly or RemovePrefix",
+        ));
+    }
+    if pick_field0 && pick_field1 {
+        return Err(VmError::invalid_operation(
+            "PickField0 and PickField1 can't be used together",
+        ));
+    }
+    if (pick_field0 || pick_field1) && !deserialize {
+        return Err(VmError::invalid_operation(
+            "PickField0 or PickField1 requires DeserializeValues",
+        ));
+    }
+
+    let prefix = storage_key(context.id, &prefix);
+    let entries = engine
+        .host()
+        .snapshot()
+        .find(&prefix, SeekDirection::Forward)
+        .map_err(storage_error)?;
+    push_interface(engine, StorageIterator::new(entries, prefix.len(), options));
+    Ok(())
+}
+
+/// Takes the context, the key and the value. Besides the fixed price, every new byte costs `STORAGE_PRICE`
+/// and rewriting existing bytes costs a quarter of it.
+fn put(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let context = pop_interface::<StorageContext>(engine)?;
+    let key = pop_bytes(engine)?;
+    let value = pop_bytes(engine)?;
+    if key.len() > MAX_KEY_SIZE {
+        return Err(VmError::invalid_operation(format!(
+            "The key is longer than {} bytes.",
+            MAX_KEY_SIZE
+        )));
+    }
+    if value.len() > MAX_VALUE_SIZE {
+        return Err(VmError::invalid_operation(format!(
+            "The value is longer than {} bytes.",
+            MAX_VALUE_SIZE
+        )));
+    }
+    if context.is_read_only {
+        return Err(VmError::invalid_operation("The storage context is read-only."));
+    }
+
+    let key = storage_key(context.id, &key);
+    let snapshot = engine.host().snapshot();
+    let new_data_size = match snapshot.try_get(&key).map_err(storage_error)? {
+        None => key.len() - 4 + value.len(),
+        Some(_) if value.is_empty() => 0,
+        Some(old) if value.len() <= old.len() => (value.len() - 1) / 4 + 1,
+        Some(old) if old.is_empty() => value.len(),
+        Some(old) => (old.len() - 1) / 4 + 1 + value.len() - old.len(),
+    };
+    let host = engine.host_mut();
+    host.add_gas(new_data_size as i64 * STORAGE_PRICE)?;
+    host.snapshot_mut().put(&key, value).map_err(storage_error)
+}
+
+fn delete(engine: &mut ApplicationEngine) -> Result<(), VmError> {
+    let context = pop_interface::<StorageContext>(engine)?;
+    let key = pop_bytes(engine)?;
+    if context.is_read_only {
+        return Err(VmError::invalid_operation("The storage context is read-only."));
+    }
+    engine
+        .host_mut()
+        .snapshot_mut()
+        .delete(&storage_key(context.id, &key))
+        .map_err(storage_error)
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+use std::rc::Rc;
+
+use neo_crypto::sha2::{Digest, Sha256};
+use neo_vm::VmError::VmError;
+
+use crate::application_engine::ApplicationEngine;
+use crate::call_flags::CallFlags;
+
+/// Host callback run when a `SYSCALL` resolves to its descriptor. The
+/// arguments are on the evaluation stack, an error faults the engine.
+pub type InteropHandler = Rc<dyn Fn(&mut ApplicationEngine) -> Result<(), VmError>>;
+
+/// Hash of an interop name: the first 4 bytes of its SHA-256, little-endian.
+pub fn interop_hash(name: &str) -> u32 {
+    let digest = Sha256::digest(name.as_bytes());
+    u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+/**
+ * An interoperable service, the operand of `SYSCALL` is the hash of its name.
+ */
+#[derive(Clone)]
+pub struct InteropDescriptor {
+    pub name: String,
+    pub hash: u32,
+    pub handler: InteropHandler,
+    /// Price charged before the handler runs, storage writes charge more inside the handler.
+    pub fixed_price: i64,
+    /// Flags the calling context must have.
+    pub required_call_flags: CallFlags,
+}
+
+impl InteropDescriptor {
+    pub fn new<F>(name: &str, handler: F, fixed_price: i64, required_call_flags: CallFlags) -> Self
+    where
+        F: Fn(&mut ApplicationEngine) -> Result<(), VmError> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            hash: interop_hash(name),
+            handler: Rc::new(handler),
+            fixed_price,
+            required_call_flags,
+        }
+    }
+}
+
+impl fmt::Debug for InteropDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InteropDescriptor")
+            .field("name", &self.name)
+            .field("hash", &format_args!("0x{:08x}", self.hash))
+            .field("fixed_price", &self.fixed_price)
+            .field("required_call_flags", &self.required_call_flags)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interop_hash() {
+        assert_eq!(interop_hash("System.Crypto.CheckSig"), 0x27b3e756);
+        assert_eq!(interop_hash("System.Contract.Call"), 0x525b7d62);
+        assert_eq!(interop_hash("System.Runtime.CheckWitness"), 0x8cec27f8);
+    }
+}
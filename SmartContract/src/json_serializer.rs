@@ -0,0 +1,519 @@
+//! The JSON formats of stack items.
+//!
+//! `serialize` and `deserialize` are the plain format of `StdLib.jsonSerialize`
+//! and `StdLib.jsonDeserialize`: `null`, booleans, safe integers, UTF-8
+//! strings, arrays and objects. `to_json` and `from_json` are the typed
+//! format of RPC results, `{"type": "Integer", "value": "1"}`, which every
+//! item type has.
+
+use std::rc::Rc;
+
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+use neo_vm::ReferenceCounter::ReferenceCounter;
+use neo_vm::Script::Script;
+use neo_vm::Types::Array::Array;
+use neo_vm::Types::Buffer::Buffer;
+use neo_vm::Types::Integer::Integer;
+use neo_vm::Types::InteropInterface::InteropInterface;
+use neo_vm::Types::Map::Map;
+use neo_vm::Types::Pointer::Pointer;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::Types::StackItemType::StackItemType;
+use neo_vm::Types::Struct::Struct;
+use num::{BigInt, FromPrimitive};
+use serde_json::{json, Map as JsonMap, Value};
+
+use crate::error::SerializationError;
+
+/// Largest integer a JSON number holds exactly, 2^53 - 1.
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+/// Smallest integer a JSON number holds exactly, -(2^53 - 1).
+pub const MIN_SAFE_INTEGER: i64 = -MAX_SAFE_INTEGER;
+/// Nesting depth `StdLib.jsonDeserialize` accepts.
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// Serializes `item` to the plain format, in at most `max_item_size` bytes.
+pub fn serialize(item: &StackItem, limits: &ExecutionEngineLimits) -> Result<Vec<u8>, SerializationError> {
+    let mut writer = JsonWriter {
+        json: String::new(),
+        max_size: limits.max_item_size() as usize,
+        ancestors: Vec::new(),
+    };
+    writer.write(item)?;
+    Ok(writer.json.into_bytes())
+}
+
+struct JsonWriter {
+    json: String,
+    max_size: usize,
+    /// Compound items being written, an item may appear twice but not inside itself.
+    ancestors: Vec<*const u8>,
+}
+
+impl JsonWriter {
+    fn push(&mut self, json: &str) -> Result<(), SerializationError> {
+        self.json.push_str(json);
+        if self.json.len() > self.max_size {
+            return Err(SerializationError::MaxSizeExceeded(self.max_size));
+        }
+        Ok(())
+    }
+
+    fn push_string(&mut self, data: Vec<u8>) -> Result<(), SerializationError> {
+        let string = String::from_utf8(data).map_err(|_| SerializationError::Format("invalid UTF-8 string".into()))?;
+        self.push(&Value::String(string).to_string())
+    }
+
+    fn write(&mut self, item: &StackItem) -> Result<(), SerializationError> {
+        let typ = item.get_type();
+        match typ {
+            StackItemType::Any => self.push("null"),
+            StackItemType::Boolean => self.push(if item.boolean()? { "true" } else { "false" }),
+            StackItemType::Integer => {
+                let integer = item.integer()?;
+                if integer > BigInt::from(MAX_SAFE_INTEGER) || integer < BigInt::from(MIN_SAFE_INTEGER) {
+                    return Err(SerializationError::Format(format!("{} is not a safe integer", integer)));
+                }
+                self.push(&integer.to_string())
+            }
+            StackItemType::ByteString | StackItemType::Buffer => self.push_string(item.span()?),
+            StackItemType::Array | StackItemType::Struct | StackItemType::Map => {
+                let address = item.address();
+                if self.ancestors.contains(&address) {
+                    return Err(SerializationError::CircularReference);
+                }
+                self.ancestors.push(address);
+                if let Some(array) = item.as_array() {
+                    self.push("[")?;
+                    for (i, item) in array.items().iter().enumerate() {
+                        if i > 0 {
+                            self.push(",")?;
+                        }
+                        self.write(item)?;
+                    }
+                    self.push("]")?;
+                } else {
+                    let map = item.as_map().unwrap();
+                    self.push("{")?;
+                    for (i, (key, value)) in map.dictionary().iter().enumerate() {
+                        if key.item().get_type() != StackItemType::ByteString {
+                            return Err(SerializationError::Format(format!(
+                                "{:?} can't be an object key",
+                                key.item().get_type()
+                            )));
+                        }
+                        if i > 0 {
+                            self.push(",")?;
+                        }
+                        self.push_string(key.item().span()?)?;
+                        self.push(":")?;
+                        self.write(value)?;
+                    }
+                    self.push("}")?;
+                }
+                self.ancestors.pop();
+                Ok(())
+            }
+            StackItemType::Pointer | StackItemType::InteropInterface => Err(SerializationError::NotSupported(typ)),
+        }
+    }
+}
+
+/// Deserializes the plain format, nested at most `DEFAULT_MAX_DEPTH` deep and holding at most `max_stack_size` items.
+pub fn deserialize(
+    json: &[u8],
+    limits: &ExecutionEngineLimits,
+    reference_counter: Option<Rc<ReferenceCounter>>,
+) -> Result<StackItem, SerializationError> {
+    let json: Value = serde_json::from_slice(json).map_err(|e| SerializationError::Format(e.to_string()))?;
+    deserialize_value(
+        &json,
+        DEFAULT_MAX_DEPTH,
+        limits.max_stack_size() as usize,
+        reference_counter,
+    )
+}
+
+/// Converts a parsed plain format value, object keys count as items.
+pub fn deserialize_value(
+    json: &Value,
+    max_depth: usize,
+    max_items: usize,
+    reference_counter: Option<Rc<ReferenceCounter>>,
+) -> Result<StackItem, SerializationError> {
+    let mut reader = JsonReader {
+        max_depth,
+        max_items,
+        items: 0,
+        reference_counter,
+    };
+    reader.read(json, 1)
+}
+
+struct JsonReader {
+    max_depth: usize,
+    max_items: usize,
+    items: usize,
+    reference_counter: Option<Rc<ReferenceCounter>>,
+}
+
+impl JsonReader {
+    fn count_item(&mut self) -> Result<(), SerializationError> {
+        self.items += 1;
+        if self.items > self.max_items {
+            return Err(SerializationError::MaxItemsExceeded(self.max_items));
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, json: &Value, depth: usize) -> Result<StackItem, SerializationError> {
+        self.count_item()?;
+        let item: StackItem = match json {
+            Value::Null => StackItem::Null,
+            Value::Bool(value) => StackItem::Boolean(*value),
+            Value::Number(number) => integer(number_to_bigint(number)?)?,
+            Value::String(string) => string.as_str().into(),
+            Value::Array(values) => {
+                self.check_depth(depth)?;
+                let items = values
+                    .iter()
+                    .map(|value| self.read(value, depth + 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Array::new(self.reference_counter.clone(), items).into()
+            }
+            Value::Object(entries) => {
+                self.check_depth(depth)?;
+                let map = Map::new(self.reference_counter.clone());
+                for (key, value) in entries {
+                    self.count_item()?;
+                    if key.len() > Map::MAX_KEY_SIZE {
+                        return Err(SerializationError::Format(format!("the key {} is too long", key)));
+                    }
+                    let value = self.read(value, depth + 1)?;
+                    map.set(key.as_str().into(), value);
+                }
+                map.into()
+            }
+        };
+        Ok(item)
+    }
+
+    fn check_depth(&self, depth: usize) -> Result<(), SerializationError> {
+        if depth > self.max_depth {
+            return Err(SerializationError::MaxDepthExceeded(self.max_depth));
+        }
+        Ok(())
+    }
+}
+
+fn number_to_bigint(number: &serde_json::Number) -> Result<BigInt, SerializationError> {
+    if let Some(value) = number.as_i64() {
+        return Ok(BigInt::from(value));
+    }
+    if let Some(value) = number.as_u64() {
+        return Ok(BigInt::from(value));
+    }
+    let value = number.as_f64().unwrap_or(f64::NAN);
+    if value.fract() != 0.0 {
+        return Err(SerializationError::Format("Decimal value is not allowed".into()));
+    }
+    BigInt::from_f64(value).ok_or_else(|| SerializationError::Format(format!("invalid number {}", number)))
+}
+
+fn integer(value: BigInt) -> Result<StackItem, SerializationError> {
+    let error = || SerializationError::Format(format!("{} is too large", value));
+    Ok(StackItem::Integer(Integer::new(value.clone()).map_err(|_| error())?))
+}
+
+/**
+ * Converts `item` to the typed format of RPC results.
+ *
+ * Byte strings and buffers are base64, integers are decimal strings and
+ * maps are arrays of `{"key", "value"}` objects. Compound items are written
+ * once, like `serialize` in the binary format, and the JSON text must fit
+ * in `max_size` bytes.
+ */
+pub fn to_json(item: &StackItem, max_size: usize) -> Result<Value, SerializationError> {
+    let json = to_json_value(item, &mut Vec::new())?;
+    if json.to_string().len() > max_size {
+        return Err(SerializationError::MaxSizeExceeded(max_size));
+    }
+    Ok(json)
+}
+
+fn to_json_value(item: &StackItem, context: &mut Vec<*const u8>) -> Result<Value, SerializationError> {
+    let typ = item.get_type();
+    let value = match typ {
+        StackItemType::Any | StackItemType::InteropInterface => None,
+        StackItemType::Boolean => Some(Value::Bool(item.boolean()?)),
+        StackItemType::Integer => Some(Value::String(item.integer()?.to_string())),
+        StackItemType::ByteString | StackItemType::Buffer => Some(Value::String(base64::encode(item.span()?))),
+        StackItemType::Pointer => Some(json!(item.as_pointer().unwrap().position())),
+        StackItemType::Array | StackItemType::Struct | StackItemType::Map => {
+            let address = item.address();
+            if context.contains(&address) {
+                return Err(SerializationError::CircularReference);
+            }
+            context.push(address);
+            let values = match item.as_array() {
+                Some(array) => array
+                    .items()
+                    .iter()
+                    .map(|item| to_json_value(item, context))
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => item
+                    .as_map()
+                    .unwrap()
+                    .dictionary()
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok(json!({
+                            "key": to_json_value(key.item(), context)?,
+                            "value": to_json_value(value, context)?,
+                        }))
+                    })
+                    .collect::<Result<Vec<_>, SerializationError>>()?,
+            };
+            Some(Value::Array(values))
+        }
+    };
+
+    let mut json = JsonMap::new();
+    json.insert("type".into(), Value::String(format!("{:?}", typ)));
+    if let Some(value) = value {
+        json.insert("value".into(), value);
+    }
+    Ok(Value::Object(json))
+}
+
+/**
+ * Converts the typed format back to an item.
+ *
+ * Pointers are detached from their script and interop interfaces wrap
+ * nothing, the JSON doesn't carry them.
+ */
+pub fn from_json(
+    json: &Value,
+    limits: &ExecutionEngineLimits,
+    reference_counter: Option<Rc<ReferenceCounter>>,
+) -> Result<StackItem, SerializationError> {
+    let mut items = 0;
+    from_json_value(json, limits, &reference_counter, &mut items)
+}
+
+fn from_json_value(
+    json: &Value,
+    limits: &ExecutionEngineLimits,
+    reference_counter: &Option<Rc<ReferenceCounter>>,
+    items: &mut usize,
+) -> Result<StackItem, SerializationError> {
+    *items += 1;
+    if *items > limits.max_stack_size() as usize {
+        return Err(SerializationError::MaxItemsExceeded(limits.max_stack_size() as usize));
+    }
+    let format = |message: &str| SerializationError::Format(format!("{}: {}", message, json));
+    let typ = json
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| format("missing type"))?;
+    let value = json.get("value");
+    let string = || value.and_then(Value::as_str).ok_or_else(|| format("invalid value"));
+    let base64 = || base64::decode(string()?).map_err(|_| format("invalid base64"));
+    let array = || value.and_then(Value::as_array).ok_or_else(|| format("invalid value"));
+
+    let item: StackItem = match typ {
+        "Any" => StackItem::Null,
+        "Boolean" => StackItem::Boolean(value.and_then(Value::as_bool).ok_or_else(|| format("invalid value"))?),
+        "Integer" => integer(string()?.parse::<BigInt>().map_err(|_| format("invalid integer"))?)?,
+        "ByteString" => {
+            let data = base64()?;
+            if data.len() > limits.max_item_size() as usize {
+                return Err(SerializationError::MaxSizeExceeded(limits.max_item_size() as usize));
+            }
+            StackItem::from(data)
+        }
+        "Buffer" => {
+            let data = base64()?;
+            if data.len() > limits.max_item_size() as usize {
+                return Err(SerializationError::MaxSizeExceeded(limits.max_item_size() as usize));
+            }
+            StackItem::from(Buffer::from_slice(&data))
+        }
+        "Array" | "Struct" => {
+            let items = array()?
+                .iter()
+                .map(|item| from_json_value(item, limits, reference_counter, items))
+                .collect::<Result<Vec<_>, _>>()?;
+            if typ == "Array" {
+                Array::new(reference_counter.clone(), items).into()
+            } else {
+                Struct::new(reference_counter.clone(), items).into()
+            }
+        }
+        "Map" => {
+            let map = Map::new(reference_counter.clone());
+            for entry in array()? {
+                let key = entry.get("key").ok_or_else(|| format("missing key"))?;
+                let key = from_json_value(key, limits, reference_counter, items)?;
+                if Map::check_key(&key).is_err() {
+                    return Err(format("invalid map key"));
+                }
+                let value = entry.get("value").ok_or_else(|| format("missing value"))?;
+                map.set(key, from_json_value(value, limits, reference_counter, items)?);
+            }
+            map.into()
+        }
+        "Pointer" => {
+            let position = value.and_then(Value::as_u64).ok_or_else(|| format("invalid value"))?;
+            StackItem::from(Pointer::new(Rc::new(Script::default()), position as usize))
+        }
+        "InteropInterface" => StackItem::from(InteropInterface::new(Rc::new(()))),
+        _ => return Err(format("invalid type")),
+    };
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ExecutionEngineLimits {
+        ExecutionEngineLimits::default()
+    }
+
+    fn bytes(data: &[u8]) -> StackItem {
+        StackItem::from(data)
+    }
+
+    fn integer(value: i64) -> StackItem {
+        StackItem::from(value)
+    }
+
+    fn serialize_str(item: &StackItem) -> String {
+        String::from_utf8(serialize(item, &limits()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_serialize() {
+        let map = Map::new(None);
+        map.set(bytes(b"z"), integer(-5));
+        map.set(bytes(b"a\"b"), StackItem::Null);
+        let item: StackItem = Array::new(
+            None,
+            vec![
+                StackItem::Boolean(true),
+                bytes("héllo".as_bytes()),
+                StackItem::from(Buffer::from_slice(b"buf")),
+                Struct::new(None, vec![integer(MAX_SAFE_INTEGER)]).into(),
+                map.into(),
+            ],
+        )
+        .into();
+        assert_eq!(
+            serialize_str(&item),
+            r#"[true,"héllo","buf",[9007199254740991],{"z":-5,"a\"b":null}]"#
+        );
+
+        let shared: StackItem = Array::new(None, vec![]).into();
+        let item: StackItem = Array::new(None, vec![shared.clone(), shared]).into();
+        assert_eq!(serialize_str(&item), "[[],[]]");
+    }
+
+    #[test]
+    fn test_serialize_errors() {
+        let item = integer(MAX_SAFE_INTEGER + 1);
+        assert!(serialize(&item, &limits()).is_err());
+        assert!(serialize(&bytes(&[0xff]), &limits()).is_err());
+
+        let map = Map::new(None);
+        map.set(integer(1), integer(1));
+        let item: StackItem = map.into();
+        assert!(serialize(&item, &limits()).is_err());
+
+        let item: StackItem = Array::new(None, vec![]).into();
+        item.as_array().unwrap().add(item.clone());
+        assert!(matches!(
+            serialize(&item, &limits()),
+            Err(SerializationError::CircularReference)
+        ));
+
+        let mut small = limits();
+        small.set_max_item_size(4);
+        assert!(serialize(&bytes(b"abc"), &small).is_err());
+        assert!(serialize(&bytes(b"ab"), &small).is_ok());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let item = deserialize(br#"{"b":[1,-2.0,"x",null,false],"a":{}}"#, &limits(), None).unwrap();
+        let map = item.as_map().unwrap();
+        let keys: Vec<String> = map.keys().iter().map(|key| key.string().unwrap()).collect();
+        assert_eq!(keys, vec!["b", "a"]);
+        let array = map.get(&bytes(b"b")).unwrap();
+        let items = array.as_array().unwrap().items().clone();
+        assert_eq!(items[0].integer().unwrap(), BigInt::from(1));
+        assert_eq!(items[1].integer().unwrap(), BigInt::from(-2));
+        assert_eq!(items[2].get_type(), StackItemType::ByteString);
+        assert!(items[3].is_null());
+        assert!(!items[4].boolean().unwrap());
+        assert_eq!(serialize_str(&item), r#"{"b":[1,-2,"x",null,false],"a":{}}"#);
+
+        assert!(deserialize(b"1.5", &limits(), None).is_err());
+        assert!(deserialize(b"[", &limits(), None).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_limits() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(deserialize(nested(DEFAULT_MAX_DEPTH).as_bytes(), &limits(), None).is_ok());
+        assert!(matches!(
+            deserialize(nested(DEFAULT_MAX_DEPTH + 1).as_bytes(), &limits(), None),
+            Err(SerializationError::MaxDepthExceeded(DEFAULT_MAX_DEPTH))
+        ));
+
+        let mut small = limits();
+        small.set_max_stack_size(3);
+        assert!(deserialize(b"[1,2]", &small, None).is_ok());
+        assert!(deserialize(b"[1,2,3]", &small, None).is_err());
+        // Object keys count as items.
+        assert!(deserialize(br#"{"a":1,"b":2}"#, &small, None).is_err());
+    }
+
+    #[test]
+    fn test_typed_json() {
+        let map = Map::new(None);
+        map.set(integer(1), bytes(b"neo"));
+        let item: StackItem = Array::new(
+            None,
+            vec![
+                StackItem::Null,
+                StackItem::Boolean(false),
+                integer(-100),
+                StackItem::from(Buffer::from_slice(&[1, 2, 3])),
+                Struct::new(None, vec![]).into(),
+                map.into(),
+                StackItem::from(Pointer::new(Rc::new(Script::default()), 7)),
+                StackItem::from(InteropInterface::new(Rc::new(()))),
+            ],
+        )
+        .into();
+        let json = to_json(&item, usize::MAX).unwrap();
+        assert_eq!(
+            json.to_string(),
+            concat!(
+                r#"{"type":"Array","value":[{"type":"Any"},{"type":"Boolean","value":false},"#,
+                r#"{"type":"Integer","value":"-100"},{"type":"Buffer","value":"AQID"},"#,
+                r#"{"type":"Struct","value":[]},{"type":"Map","value":[{"key":{"type":"Integer","value":"1"},"#,
+                r#""value":{"type":"ByteString","value":"bmVv"}}]},{"type":"Pointer","value":7},"#,
+                r#"{"type":"InteropInterface"}]}"#
+            )
+        );
+
+        let back = from_json(&json, &limits(), None).unwrap();
+        assert_eq!(to_json(&back, usize::MAX).unwrap(), json);
+
+        assert!(to_json(&item, 100).is_err());
+        assert!(from_json(&json!({"type": "Integer", "value": "x"}), &limits(), None).is_err());
+        assert!(from_json(&json!({"type": "Unknown"}), &limits(), None).is_err());
+    }
+}
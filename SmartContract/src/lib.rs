@@ -1,12 +1,24 @@
-#![feature(arbitrary_enum_discriminant)]
-
-pub mod contract_parameter;
 pub mod find_options;
 pub mod trigger_type;
 pub mod call_flags;
-pub mod op_code;
-pub mod stack_item;
-pub mod script_builder;
+
+pub mod error;
+pub mod binary_serializer;
+pub mod json_serializer;
+pub mod contract_parameters_context;
+
+pub mod contract_state;
+pub mod interop_descriptor;
+pub mod interop;
+pub mod opcode_prices;
+pub mod application_engine;
+pub mod contract_call;
+pub mod nep17;
+pub mod nep11;
+pub mod nef_file;
+pub mod manifest;
+pub mod transaction_builder;
+pub mod contract_management;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,304 @@
+//! The ABI of a contract: the methods it exposes and the events it sends.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+use crate::error::ManifestError;
+use crate::manifest::{check_unique, format_error, get_array, get_str};
+
+/// Type of a parameter or a return value, as named in manifests and RPC results.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[repr(u8)]
+pub enum ContractParameterType {
+    Any = 0x00,
+    Boolean = 0x10,
+    Integer = 0x11,
+    ByteArray = 0x12,
+    String = 0x13,
+    Hash160 = 0x14,
+    Hash256 = 0x15,
+    PublicKey = 0x16,
+    Signature = 0x17,
+    Array = 0x20,
+    Map = 0x22,
+    InteropInterface = 0x30,
+    /// Only for return values.
+    Void = 0xff,
+}
+
+impl ContractParameterType {
+    const ALL: [ContractParameterType; 13] = [
+        ContractParameterType::Any,
+        ContractParameterType::Boolean,
+        ContractParameterType::Integer,
+        ContractParameterType::ByteArray,
+        ContractParameterType::String,
+        ContractParameterType::Hash160,
+        ContractParameterType::Hash256,
+        ContractParameterType::PublicKey,
+        ContractParameterType::Signature,
+        ContractParameterType::Array,
+        ContractParameterType::Map,
+        ContractParameterType::InteropInterface,
+        ContractParameterType::Void,
+    ];
+
+    pub fn from_byte(b: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|typ| *typ as u8 == b)
+    }
+}
+
+impl fmt::Display for ContractParameterType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for ContractParameterType {
+    type Err = ManifestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|typ| typ.to_string() == s)
+            .ok_or_else(|| format_error(&format!("unknown parameter type {}", s)))
+    }
+}
+
+/// A named parameter of a method or an event.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct ParameterDefinition {
+    pub name: String,
+    pub parameter_type: ContractParameterType,
+}
+
+impl ParameterDefinition {
+    pub fn new(name: &str, parameter_type: ContractParameterType) -> Self {
+        Self {
+            name: name.to_string(),
+            parameter_type,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({"name": self.name, "type": self.parameter_type.to_string()})
+    }
+
+    /// The name must not be empty and the type can't be `Void`.
+    pub fn from_json(json: &Value) -> Result<Self, ManifestError> {
+        let name = get_str(json, "name")?;
+        let parameter_type = get_str(json, "type")?.parse()?;
+        if name.is_empty() {
+            return Err(format_error("empty parameter name"));
+        }
+        if parameter_type == ContractParameterType::Void {
+            return Err(format_error(&format!("parameter {} is Void", name)));
+        }
+        Ok(Self::new(name, parameter_type))
+    }
+}
+
+/// A method of the contract and where it starts in the script.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct MethodDescriptor {
+    pub name: String,
+    pub parameters: Vec<ParameterDefinition>,
+    pub return_type: ContractParameterType,
+    pub offset: usize,
+    /// Safe methods can neither write states nor notify, whatever the flags they are called with.
+    pub safe: bool,
+}
+
+impl MethodDescriptor {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "parameters": self.parameters.iter().map(ParameterDefinition::to_json).collect::<Vec<_>>(),
+            "returntype": self.return_type.to_string(),
+            "offset": self.offset,
+            "safe": self.safe,
+        })
+    }
+
+    pub fn from_json(json: &Value) -> Result<Self, ManifestError> {
+        let name = get_str(json, "name")?;
+        if name.is_empty() {
+            return Err(format_error("empty method name"));
+        }
+        let parameters = parse_parameters(json)?;
+        let return_type = get_str(json, "returntype")?.parse()?;
+        let offset = json["offset"]
+            .as_u64()
+            .ok_or_else(|| format_error(&format!("invalid offset of method {}", name)))? as usize;
+        let safe = json["safe"]
+            .as_bool()
+            .ok_or_else(|| format_error(&format!("invalid safe flag of method {}", name)))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            parameters,
+            return_type,
+            offset,
+            safe,
+        })
+    }
+}
+
+/// An event the contract sends with `System.Runtime.Notify`.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct EventDescriptor {
+    pub name: String,
+    pub parameters: Vec<ParameterDefinition>,
+}
+
+impl EventDescriptor {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "parameters": self.parameters.iter().map(ParameterDefinition::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(json: &Value) -> Result<Self, ManifestError> {
+        let name = get_str(json, "name")?;
+        if name.is_empty() {
+            return Err(format_error("empty event name"));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            parameters: parse_parameters(json)?,
+        })
+    }
+}
+
+/**
+ * The methods and events of a contract.
+ *
+ * Methods are overloaded by their number of parameters, a name and a
+ * number of parameters identify a single method. Event names are unique.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct ContractAbi {
+    pub methods: Vec<MethodDescriptor>,
+    pub events: Vec<EventDescriptor>,
+}
+
+impl ContractAbi {
+    /// The method `name` taking `parameters_count` parameters, or any number of them if `None`.
+    pub fn get_method(&self, name: &str, parameters_count: Option<usize>) -> Option<&MethodDescriptor> {
+        self.methods.iter().find(|method| {
+            method.name == name && (parameters_count.is_none() || parameters_count == Some(method.parameters.len()))
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "methods": self.methods.iter().map(MethodDescriptor::to_json).collect::<Vec<_>>(),
+            "events": self.events.iter().map(EventDescriptor::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// There must be at least one method.
+    pub fn from_json(json: &Value) -> Result<Self, ManifestError> {
+        let methods = get_array(json, "methods")?
+            .iter()
+            .map(MethodDescriptor::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        let events = get_array(json, "events")?
+            .iter()
+            .map(EventDescriptor::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        if methods.is_empty() {
+            return Err(format_error("the ABI has no method"));
+        }
+        check_unique(
+            methods
+                .iter()
+                .map(|method| format!("method {}/{}", method.name, method.parameters.len())),
+        )?;
+        check_unique(events.iter().map(|event| format!("event {}", event.name)))?;
+
+        Ok(Self { methods, events })
+    }
+}
+
+/// The parameters of a method or an event, whose names must be unique.
+fn parse_parameters(json: &Value) -> Result<Vec<ParameterDefinition>, ManifestError> {
+    let parameters = get_array(json, "parameters")?
+        .iter()
+        .map(ParameterDefinition::from_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    check_unique(
+        parameters
+            .iter()
+            .map(|parameter| format!("parameter {}", parameter.name)),
+    )?;
+    Ok(parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameter_types() {
+        for typ in ContractParameterType::ALL.iter() {
+            assert_eq!(typ.to_string().parse::<ContractParameterType>().unwrap(), *typ);
+            assert_eq!(ContractParameterType::from_byte(*typ as u8), Some(*typ));
+        }
+        assert_eq!(ContractParameterType::Hash160.to_string(), "Hash160");
+        assert!("hash160".parse::<ContractParameterType>().is_err());
+        assert_eq!(ContractParameterType::from_byte(0x21), None);
+    }
+
+    #[test]
+    fn test_abi() {
+        let json = json!({
+            "methods": [
+                {"name": "balanceOf", "parameters": [{"name": "account", "type": "Hash160"}],
+                 "returntype": "Integer", "offset": 0, "safe": true},
+                {"name": "balanceOf", "parameters": [], "returntype": "Integer", "offset": 7, "safe": true},
+            ],
+            "events": [{"name": "Transfer", "parameters": [{"name": "amount", "type": "Integer"}]}],
+        });
+        let abi = ContractAbi::from_json(&json).unwrap();
+        assert_eq!(abi.to_json(), json);
+        assert_eq!(abi.get_method("balanceOf", Some(0)).unwrap().offset, 7);
+        assert_eq!(abi.get_method("balanceOf", None).unwrap().offset, 0);
+        assert!(abi.get_method("balanceOf", Some(2)).is_none());
+
+        let invalid = |f: &dyn Fn(&mut Value)| {
+            let mut json = json.clone();
+            f(&mut json);
+            ContractAbi::from_json(&json).unwrap_err()
+        };
+        assert!(matches!(
+            invalid(&|json| json["methods"][1]["parameters"] = json!([{"name": "a", "type": "Any"}])),
+            ManifestError::Duplicate(_)
+        ));
+        assert!(matches!(
+            invalid(&|json| json["events"][0]["parameters"][0]["type"] = json!("Void")),
+            ManifestError::Format(_)
+        ));
+        assert!(matches!(
+            invalid(&|json| json["events"][0]["parameters"] =
+                json!([{"name": "a", "type": "Any"}, {"name": "a", "type": "Map"}])),
+            ManifestError::Duplicate(_)
+        ));
+        assert!(matches!(
+            invalid(&|json| json["methods"][0]["offset"] = json!(-1)),
+            ManifestError::Format(_)
+        ));
+        assert!(matches!(
+            invalid(&|json| json["methods"] = json!([])),
+            ManifestError::Format(_)
+        ));
+        assert!(matches!(
+            invalid(&|json| json["methods"][0]["name"] = json!("")),
+            ManifestError::Format(_)
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+//! Groups of contracts, identified by a key that signed each of them.
+
+use neo_core::neo_type::PublicKeyBin;
+use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
+use neo_crypto::hex;
+use neo_io::UInt160;
+use serde_json::{json, Value};
+
+use crate::error::ManifestError;
+use crate::manifest::permission::parse_public_key;
+use crate::manifest::{format_error, get_str};
+
+/// Length of a signature, `r` and `s` of secp256r1.
+pub const SIGNATURE_LEN: usize = 64;
+
+/**
+ * A contract is in the group of `public_key` if `signature` is a signature
+ * of its hash by that key. Contracts of a group can be trusted or allowed
+ * together, and witness scopes can name the group.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct ContractGroup {
+    pub public_key: PublicKeyBin,
+    pub signature: Vec<u8>,
+}
+
+impl ContractGroup {
+    /// Whether `signature` is a signature of the contract `hash`, its little-endian bytes.
+    pub fn is_valid(&self, hash: &UInt160) -> bool {
+        match ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI) {
+            Ok(mut ecdsa) => ecdsa
+                .verify_signature(&self.public_key, &self.signature, hash.as_bytes())
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pubkey": hex::encode(&self.public_key[..]),
+            "signature": base64::encode(&self.signature),
+        })
+    }
+
+    pub fn from_json(json: &Value) -> Result<Self, ManifestError> {
+        let public_key = get_str(json, "pubkey")?;
+        let public_key =
+            parse_public_key(public_key).ok_or_else(|| format_error(&format!("invalid public key {}", public_key)))?;
+        let signature = base64::decode(get_str(json, "signature")?)
+            .ok()
+            .filter(|signature| signature.len() == SIGNATURE_LEN)
+            .ok_or_else(|| format_error("invalid group signature"))?;
+
+        Ok(Self { public_key, signature })
+    }
+}
@@ -0,0 +1,334 @@
+//! The manifest of a contract, deployed with its NEF file.
+//!
+//! It describes what the contract exposes, its ABI and the standards it
+//! supports, and what it may do: the contracts and methods it may call. The
+//! JSON format and its checks are those of the reference node, a manifest
+//! read with `from_json` would be accepted by `ContractManagement.deploy`.
+
+pub mod abi;
+pub mod group;
+pub mod permission;
+
+pub use self::abi::{ContractAbi, ContractParameterType, EventDescriptor, MethodDescriptor, ParameterDefinition};
+pub use self::group::ContractGroup;
+pub use self::permission::{ContractPermission, ContractPermissionDescriptor, WildcardContainer};
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use neo_crypto::hex;
+use neo_io::UInt160;
+use serde_json::{json, Map, Value};
+
+use crate::error::ManifestError;
+
+/// Maximum size of the JSON of a manifest.
+pub const MAX_MANIFEST_LENGTH: usize = u16::MAX as usize;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ContractManifest {
+    pub name: String,
+    pub groups: Vec<ContractGroup>,
+    /// Such as "NEP-17".
+    pub supported_standards: Vec<String>,
+    pub abi: ContractAbi,
+    pub permissions: Vec<ContractPermission>,
+    /// Contracts whose calls to this one a wallet may sign without asking.
+    pub trusts: WildcardContainer<ContractPermissionDescriptor>,
+    /// Anything the author wants to add, a JSON object.
+    pub extra: Option<Map<String, Value>>,
+}
+
+impl ContractManifest {
+    /// A manifest with no method, allowed to call any contract and trusting none.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            groups: Vec::new(),
+            supported_standards: Vec::new(),
+            abi: ContractAbi {
+                methods: Vec::new(),
+                events: Vec::new(),
+            },
+            permissions: vec![ContractPermission::default_permission()],
+            trusts: WildcardContainer::List(Vec::new()),
+            extra: None,
+        }
+    }
+
+    /// Whether a permission allows this contract to call `method` of the contract `hash` with `manifest`.
+    pub fn can_call(&self, hash: &UInt160, manifest: &ContractManifest, method: &str) -> bool {
+        self.permissions
+            .iter()
+            .any(|permission| permission.is_allowed(hash, manifest, method))
+    }
+
+    /// Checks that every group signed `hash`, the hash of the contract.
+    pub fn validate(&self, hash: &UInt160) -> Result<(), ManifestError> {
+        match self.groups.iter().find(|group| !group.is_valid(hash)) {
+            Some(group) => Err(ManifestError::InvalidGroup(hex::encode(&group.public_key[..]))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "groups": self.groups.iter().map(ContractGroup::to_json).collect::<Vec<_>>(),
+            "features": {},
+            "supportedstandards": self.supported_standards,
+            "abi": self.abi.to_json(),
+            "permissions": self.permissions.iter().map(ContractPermission::to_json).collect::<Vec<_>>(),
+            "trusts": self.trusts.to_json(|trust| json!(trust.to_string())),
+            "extra": self.extra,
+        })
+    }
+
+    /**
+     * Reads a manifest, failing as the reference node does if the name is
+     * empty, `features` is not an empty object, or a group, a standard, a
+     * permission or a trust is given twice.
+     */
+    pub fn from_json(json: &Value) -> Result<Self, ManifestError> {
+        let size = json.to_string().len();
+        if size > MAX_MANIFEST_LENGTH {
+            return Err(ManifestError::TooLarge(size, MAX_MANIFEST_LENGTH));
+        }
+
+        let name = get_str(json, "name")?;
+        if name.is_empty() {
+            return Err(format_error("empty name"));
+        }
+        let groups = get_array(json, "groups")?
+            .iter()
+            .map(ContractGroup::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        check_unique(
+            groups
+                .iter()
+                .map(|group| format!("group {}", hex::encode(&group.public_key[..]))),
+        )?;
+        match json["features"].as_object() {
+            Some(features) if features.is_empty() => {}
+            _ => return Err(format_error("features must be an empty object")),
+        }
+        let supported_standards = get_array(json, "supportedstandards")?
+            .iter()
+            .map(|standard| match standard.as_str() {
+                Some(standard) if !standard.is_empty() => Ok(standard.to_string()),
+                _ => Err(format_error(&format!("invalid standard {}", standard))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        check_unique(
+            supported_standards
+                .iter()
+                .map(|standard| format!("standard {}", standard)),
+        )?;
+        let abi = ContractAbi::from_json(&json["abi"])?;
+        let permissions = get_array(json, "permissions")?
+            .iter()
+            .map(ContractPermission::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        check_unique(
+            permissions
+                .iter()
+                .map(|permission| format!("permission {}", permission.contract)),
+        )?;
+        let trusts = WildcardContainer::from_json(&json["trusts"], |trust| match trust.as_str() {
+            Some(trust) => trust.parse(),
+            None => Err(format_error(&format!("invalid trust {}", trust))),
+        })?;
+        if let WildcardContainer::List(trusts) = &trusts {
+            check_unique(trusts.iter().map(|trust| format!("trust {}", trust)))?;
+        }
+        let extra = match &json["extra"] {
+            Value::Null => None,
+            Value::Object(extra) => Some(extra.clone()),
+            _ => return Err(format_error("extra must be an object")),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            groups,
+            supported_standards,
+            abi,
+            permissions,
+            trusts,
+            extra,
+        })
+    }
+}
+
+impl fmt::Display for ContractManifest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl FromStr for ContractManifest {
+    type Err = ManifestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let json = serde_json::from_str(s).map_err(|error| format_error(&error.to_string()))?;
+        ContractManifest::from_json(&json)
+    }
+}
+
+fn format_error(message: &str) -> ManifestError {
+    ManifestError::Format(message.to_string())
+}
+
+fn get_str<'a>(json: &'a Value, field: &str) -> Result<&'a str, ManifestError> {
+    json[field]
+        .as_str()
+        .ok_or_else(|| format_error(&format!("{} must be a string", field)))
+}
+
+fn get_array<'a>(json: &'a Value, field: &str) -> Result<&'a Vec<Value>, ManifestError> {
+    json[field]
+        .as_array()
+        .ok_or_else(|| format_error(&format!("{} must be an array", field)))
+}
+
+/// Fails with the first item, a description of it, that is given twice.
+fn check_unique<I: Iterator<Item = String>>(items: I) -> Result<(), ManifestError> {
+    let mut seen = HashSet::new();
+    for item in items {
+        if seen.contains(&item) {
+            return Err(ManifestError::Duplicate(item));
+        }
+        seen.insert(item);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
+
+    use super::*;
+
+    const PRIVATE_KEY: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+
+    fn manifest_json() -> Value {
+        json!({
+            "name": "Token",
+            "groups": [],
+            "features": {},
+            "supportedstandards": ["NEP-17"],
+            "abi": {
+                "methods": [{"name": "symbol", "parameters": [], "returntype": "String", "offset": 0, "safe": true}],
+                "events": [],
+            },
+            "permissions": [{"contract": "*", "methods": ["onNEP17Payment"]}],
+            "trusts": "*",
+            "extra": {"Author": "Neo"},
+        })
+    }
+
+    fn group(hash: &UInt160) -> ContractGroup {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
+        let private_key = hex::decode(PRIVATE_KEY).unwrap();
+        let mut public_key = [0u8; 33];
+        public_key.copy_from_slice(&ecdsa.derive_public_key(&private_key).unwrap());
+        ContractGroup {
+            public_key,
+            signature: ecdsa.sign(&private_key, hash.as_bytes()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let manifest = ContractManifest::from_json(&manifest_json()).unwrap();
+        assert_eq!(manifest.to_json(), manifest_json());
+        assert_eq!(manifest.to_string().parse::<ContractManifest>().unwrap(), manifest);
+        assert!(manifest.trusts.is_wildcard());
+        assert_eq!(
+            manifest.abi.get_method("symbol", Some(0)).unwrap().return_type,
+            ContractParameterType::String
+        );
+
+        let default = ContractManifest::new("Empty");
+        assert_eq!(
+            default.to_json()["permissions"],
+            json!([{"contract": "*", "methods": "*"}])
+        );
+        assert_eq!(default.to_json()["trusts"], json!([]));
+        assert_eq!(default.to_json()["extra"], Value::Null);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let invalid = |field: &str, value: Value| {
+            let mut json = manifest_json();
+            json[field] = value;
+            ContractManifest::from_json(&json).unwrap_err()
+        };
+        assert!(matches!(invalid("name", json!("")), ManifestError::Format(_)));
+        assert!(matches!(
+            invalid("features", json!({"storage": true})),
+            ManifestError::Format(_)
+        ));
+        assert!(matches!(
+            invalid("supportedstandards", json!([""])),
+            ManifestError::Format(_)
+        ));
+        assert!(matches!(
+            invalid("supportedstandards", json!(["NEP-17", "NEP-17"])),
+            ManifestError::Duplicate(_)
+        ));
+        assert!(matches!(
+            invalid(
+                "permissions",
+                json!([{"contract": "*", "methods": "*"}, {"contract": "*", "methods": []}])
+            ),
+            ManifestError::Duplicate(_)
+        ));
+        assert!(matches!(
+            invalid("trusts", json!(["*", "*"])),
+            ManifestError::Duplicate(_)
+        ));
+        assert!(matches!(invalid("trusts", json!("all")), ManifestError::Format(_)));
+        assert!(matches!(invalid("extra", json!("x")), ManifestError::Format(_)));
+        assert!(matches!(
+            invalid("abi", json!({"methods": [], "events": []})),
+            ManifestError::Format(_)
+        ));
+        assert!(matches!(
+            invalid("extra", json!({"data": "x".repeat(MAX_MANIFEST_LENGTH)})),
+            ManifestError::TooLarge(_, MAX_MANIFEST_LENGTH)
+        ));
+
+        let group = group(&UInt160::zero());
+        assert!(matches!(
+            invalid("groups", json!([group.to_json(), group.to_json()])),
+            ManifestError::Duplicate(_)
+        ));
+        let mut short = group.to_json();
+        short["signature"] = json!(base64::encode([1; 63]));
+        assert!(matches!(invalid("groups", json!([short])), ManifestError::Format(_)));
+    }
+
+    #[test]
+    fn test_groups() {
+        let hash = UInt160::from([7; 20]);
+        let mut manifest = ContractManifest::from_json(&manifest_json()).unwrap();
+        manifest.groups.push(group(&hash));
+        manifest.validate(&hash).unwrap();
+        assert!(matches!(
+            manifest.validate(&UInt160::zero()),
+            Err(ManifestError::InvalidGroup(_))
+        ));
+        assert_eq!(ContractManifest::from_json(&manifest.to_json()).unwrap(), manifest);
+
+        let mut caller = ContractManifest::new("Caller");
+        caller.permissions = vec![ContractPermission {
+            contract: ContractPermissionDescriptor::Group(manifest.groups[0].public_key),
+            methods: WildcardContainer::List(vec!["symbol".to_string()]),
+        }];
+        assert!(caller.can_call(&hash, &manifest, "symbol"));
+        assert!(!caller.can_call(&hash, &manifest, "transfer"));
+        assert!(!caller.can_call(&hash, &ContractManifest::new("Other"), "symbol"));
+    }
+}
@@ -0,0 +1,197 @@
+//! Which contracts and methods a contract may call, and which contracts it trusts.
+
+use std::fmt;
+use std::str::FromStr;
+
+use neo_core::neo_type::{PublicKeyBin, PUBLIC_KEY_BIN_LEN};
+use neo_crypto::hex;
+use neo_io::UInt160;
+use serde_json::{json, Value};
+
+use crate::error::ManifestError;
+use crate::manifest::{check_unique, format_error, get_str, ContractManifest};
+
+/// Either every value, written `"*"`, or the listed ones.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum WildcardContainer<T> {
+    Wildcard,
+    List(Vec<T>),
+}
+
+impl<T: PartialEq> WildcardContainer<T> {
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, WildcardContainer::Wildcard)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        match self {
+            WildcardContainer::Wildcard => true,
+            WildcardContainer::List(values) => values.contains(value),
+        }
+    }
+
+    pub fn to_json<F: Fn(&T) -> Value>(&self, f: F) -> Value {
+        match self {
+            WildcardContainer::Wildcard => json!("*"),
+            WildcardContainer::List(values) => Value::Array(values.iter().map(f).collect()),
+        }
+    }
+
+    pub fn from_json<F>(json: &Value, f: F) -> Result<Self, ManifestError>
+    where
+        F: Fn(&Value) -> Result<T, ManifestError>,
+    {
+        match json {
+            Value::String(s) if s == "*" => Ok(WildcardContainer::Wildcard),
+            Value::Array(values) => Ok(WildcardContainer::List(values.iter().map(f).collect::<Result<_, _>>()?)),
+            _ => Err(format_error(&format!("invalid wildcard container {}", json))),
+        }
+    }
+}
+
+/// A contract given by its hash, the contracts of a group, or any contract.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum ContractPermissionDescriptor {
+    Wildcard,
+    Hash(UInt160),
+    Group(PublicKeyBin),
+}
+
+impl ContractPermissionDescriptor {
+    /// Whether the contract `hash` with `manifest` is described.
+    pub fn matches(&self, hash: &UInt160, manifest: &ContractManifest) -> bool {
+        match self {
+            ContractPermissionDescriptor::Wildcard => true,
+            ContractPermissionDescriptor::Hash(expected) => expected == hash,
+            ContractPermissionDescriptor::Group(key) => manifest.groups.iter().any(|group| group.public_key == *key),
+        }
+    }
+}
+
+impl fmt::Display for ContractPermissionDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContractPermissionDescriptor::Wildcard => write!(f, "*"),
+            ContractPermissionDescriptor::Hash(hash) => write!(f, "{}", hash),
+            ContractPermissionDescriptor::Group(key) => write!(f, "{}", hex::encode(&key[..])),
+        }
+    }
+}
+
+impl FromStr for ContractPermissionDescriptor {
+    type Err = ManifestError;
+
+    /// `"*"`, a `0x` prefixed script hash or a compressed public key.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format_error(&format!("invalid contract {}", s));
+        match s.len() {
+            1 if s == "*" => Ok(ContractPermissionDescriptor::Wildcard),
+            42 if s.starts_with("0x") => Ok(ContractPermissionDescriptor::Hash(
+                UInt160::from_str(s).map_err(|_| invalid())?,
+            )),
+            66 => Ok(ContractPermissionDescriptor::Group(
+                parse_public_key(s).ok_or_else(invalid)?,
+            )),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A compressed public key in hex.
+pub(crate) fn parse_public_key(s: &str) -> Option<PublicKeyBin> {
+    let data = hex::decode(s).ok()?;
+    if data.len() != PUBLIC_KEY_BIN_LEN || (data[0] != 0x02 && data[0] != 0x03) {
+        return None;
+    }
+    let mut key = [0u8; PUBLIC_KEY_BIN_LEN];
+    key.copy_from_slice(&data);
+    Some(key)
+}
+
+/// The methods a contract may call on the contracts matching `contract`.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct ContractPermission {
+    pub contract: ContractPermissionDescriptor,
+    pub methods: WildcardContainer<String>,
+}
+
+impl ContractPermission {
+    /// Any method of any contract, the permission of contracts that don't restrict their calls.
+    pub fn default_permission() -> Self {
+        Self {
+            contract: ContractPermissionDescriptor::Wildcard,
+            methods: WildcardContainer::Wildcard,
+        }
+    }
+
+    /// Whether the permission allows calling `method` of the contract `hash` with `manifest`.
+    pub fn is_allowed(&self, hash: &UInt160, manifest: &ContractManifest, method: &str) -> bool {
+        self.contract.matches(hash, manifest) && self.methods.contains(&method.to_string())
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "contract": self.contract.to_string(),
+            "methods": self.methods.to_json(|method| json!(method)),
+        })
+    }
+
+    /// Method names must not be empty and must be unique.
+    pub fn from_json(json: &Value) -> Result<Self, ManifestError> {
+        let contract = get_str(json, "contract")?.parse()?;
+        let methods = WildcardContainer::from_json(&json["methods"], |method| match method.as_str() {
+            Some(method) if !method.is_empty() => Ok(method.to_string()),
+            _ => Err(format_error(&format!("invalid method {}", method))),
+        })?;
+        if let WildcardContainer::List(methods) = &methods {
+            check_unique(methods.iter().map(|method| format!("method {}", method)))?;
+        }
+
+        Ok(Self { contract, methods })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "03b209fd4f53a7170ea4444e0cb0a6bb6a53c2bd016926989cf85f9b0fba17a70c";
+
+    #[test]
+    fn test_descriptor() {
+        for s in ["*", "0xd2a4cff31913016155e38e474a2c06d08be276cf", KEY].iter() {
+            assert_eq!(s.parse::<ContractPermissionDescriptor>().unwrap().to_string(), *s);
+        }
+        for s in [
+            "",
+            "**",
+            "d2a4cff31913016155e38e474a2c06d08be276cf",
+            "0xd2a4cff31913016155e38e474a2c06d08be276cg",
+            &KEY.replacen("03", "04", 1),
+        ]
+        .iter()
+        {
+            assert!(s.parse::<ContractPermissionDescriptor>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_permission() {
+        let json = json!({"contract": "0xd2a4cff31913016155e38e474a2c06d08be276cf", "methods": ["transfer"]});
+        let permission = ContractPermission::from_json(&json).unwrap();
+        assert_eq!(permission.to_json(), json);
+        assert!(!permission.methods.is_wildcard());
+
+        let manifest = ContractManifest::new("Token");
+        let gas = UInt160::from_str("0xd2a4cff31913016155e38e474a2c06d08be276cf").unwrap();
+        assert!(permission.is_allowed(&gas, &manifest, "transfer"));
+        assert!(!permission.is_allowed(&gas, &manifest, "balanceOf"));
+        assert!(!permission.is_allowed(&UInt160::zero(), &manifest, "transfer"));
+        assert!(ContractPermission::default_permission().is_allowed(&UInt160::zero(), &manifest, "any"));
+
+        for methods in [json!(["a", "a"]), json!([""]), json!("a"), json!([1])].iter() {
+            let json = json!({"contract": "*", "methods": methods});
+            assert!(ContractPermission::from_json(&json).is_err(), "{}", methods);
+        }
+    }
+}
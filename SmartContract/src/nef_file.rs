@@ -0,0 +1,242 @@
+//! The NEF file of a contract: its script and what the compiler knew about it.
+//!
+//! ```text
+//! magic "NEF3" | compiler, 64 bytes | source | 0 | tokens | 0, 2 bytes | script | checksum
+//! ```
+//!
+//! The checksum is the first 4 bytes of the double SHA-256 of everything before it.
+
+use std::io::{Read, Write};
+
+use neo_crypto::sha2::{Digest, Sha256};
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt160};
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+
+use crate::call_flags::CallFlags;
+
+/// "NEF3", little-endian.
+pub const NEF_MAGIC: u32 = 0x3346_454e;
+/// Size of the zero padded compiler field.
+pub const MAX_COMPILER_LENGTH: usize = 64;
+pub const MAX_SOURCE_LENGTH: usize = 256;
+pub const MAX_TOKENS: usize = 128;
+/// Maximum length of the method name of a token.
+pub const MAX_METHOD_LENGTH: usize = 32;
+
+/**
+ * A static call of a method of another contract, made by the `CALLT`
+ * instruction whose operand is the index of the token.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct MethodToken {
+    pub hash: UInt160,
+    pub method: String,
+    pub parameters_count: u16,
+    pub has_return_value: bool,
+    pub call_flags: CallFlags,
+}
+
+impl Serializable for MethodToken {
+    fn size(&self) -> usize {
+        UInt160::LENGTH + neo_io::var_bytes_size(self.method.as_bytes()) + 2 + 1 + 1
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        self.hash.serialize(writer)?;
+        writer.write_var_string(&self.method)?;
+        writer.write_u16(self.parameters_count)?;
+        writer.write_bool(self.has_return_value)?;
+        writer.write_u8(self.call_flags.0)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let hash = reader.read_serializable()?;
+        let method = reader.read_var_string(MAX_METHOD_LENGTH)?;
+        if method.starts_with('_') {
+            return Err(IoError::Format(format!("the method {} can't be called", method)));
+        }
+        let parameters_count = reader.read_u16()?;
+        let has_return_value = reader.read_bool()?;
+        let call_flags = reader.read_u8()?;
+        let call_flags = CallFlags::from_byte(call_flags)
+            .ok_or_else(|| IoError::Format(format!("invalid call flags {}", call_flags)))?;
+
+        Ok(Self {
+            hash,
+            method,
+            parameters_count,
+            has_return_value,
+            call_flags,
+        })
+    }
+}
+
+/**
+ * A compiled contract, as deployed by `ContractManagement.deploy`.
+ *
+ * `checksum` is kept as read, `Serializable::deserialize` fails if it is
+ * not the checksum of the other fields. `new` and `update_checksum` compute it.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct NefFile {
+    /// Name and version of the compiler.
+    pub compiler: String,
+    /// Where the source code can be found, usually a URL.
+    pub source: String,
+    pub tokens: Vec<MethodToken>,
+    pub script: Vec<u8>,
+    pub checksum: u32,
+}
+
+impl NefFile {
+    /// Panics if `compiler` is longer than `MAX_COMPILER_LENGTH` bytes.
+    pub fn new(compiler: &str, source: &str, tokens: Vec<MethodToken>, script: Vec<u8>) -> Self {
+        let mut nef = Self {
+            compiler: compiler.to_string(),
+            source: source.to_string(),
+            tokens,
+            script,
+            checksum: 0,
+        };
+        nef.update_checksum();
+        nef
+    }
+
+    /// The checksum of the fields other than `checksum`.
+    pub fn compute_checksum(&self) -> u32 {
+        let data = self.to_array();
+        let hash = Sha256::digest(&Sha256::digest(&data[..data.len() - 4]));
+        u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
+    }
+
+    /// Sets `checksum` after the other fields have been changed.
+    pub fn update_checksum(&mut self) {
+        self.checksum = self.compute_checksum();
+    }
+}
+
+impl Serializable for NefFile {
+    fn size(&self) -> usize {
+        4 + MAX_COMPILER_LENGTH
+            + neo_io::var_bytes_size(self.source.as_bytes())
+            + 1
+            + neo_io::var_list_size(&self.tokens)
+            + 2
+            + neo_io::var_bytes_size(&self.script)
+            + 4
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_u32(NEF_MAGIC)?;
+        writer.write_fixed_string(&self.compiler, MAX_COMPILER_LENGTH)?;
+        writer.write_var_string(&self.source)?;
+        writer.write_u8(0)?;
+        writer.write_serializable_list(&self.tokens)?;
+        writer.write_u16(0)?;
+        writer.write_var_bytes(&self.script)?;
+        writer.write_u32(self.checksum)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let start = reader.position();
+        let max_size = ExecutionEngineLimits::default().max_item_size() as usize;
+        if reader.read_u32()? != NEF_MAGIC {
+            return Err(IoError::Format("wrong magic".to_string()));
+        }
+        let compiler = reader.read_fixed_string(MAX_COMPILER_LENGTH)?;
+        let source = reader.read_var_string(MAX_SOURCE_LENGTH)?;
+        if reader.read_u8()? != 0 {
+            return Err(IoError::Format("reserved byte must be 0".to_string()));
+        }
+        let tokens = reader.read_serializable_list(MAX_TOKENS)?;
+        if reader.read_u16()? != 0 {
+            return Err(IoError::Format("reserved bytes must be 0".to_string()));
+        }
+        let script = reader.read_var_bytes(max_size)?;
+        if script.is_empty() {
+            return Err(IoError::Format("the script is empty".to_string()));
+        }
+        let nef = Self {
+            compiler,
+            source,
+            tokens,
+            script,
+            checksum: reader.read_u32()?,
+        };
+
+        if nef.checksum != nef.compute_checksum() {
+            return Err(IoError::Format("checksum mismatch".to_string()));
+        }
+        let size = reader.position() - start;
+        if size > max_size {
+            return Err(IoError::LengthExceeded(size as u64, max_size as u64));
+        }
+        Ok(nef)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nef() -> NefFile {
+        let token = MethodToken {
+            hash: UInt160::from([0xcf; 20]),
+            method: "transfer".to_string(),
+            parameters_count: 4,
+            has_return_value: true,
+            call_flags: CallFlags::ALL,
+        };
+        NefFile::new("neon 3.0", "https://example.org", vec![token], vec![0x11, 0x40])
+    }
+
+    #[test]
+    fn test_serialize() {
+        let nef = NefFile::new("neon", "", vec![], vec![0x40]);
+        let mut expected = b"NEF3neon".to_vec();
+        expected.extend_from_slice(&[0; 60]);
+        expected.extend_from_slice(&[0, 0, 0, 0, 0, 1, 0x40]);
+        expected.extend_from_slice(&nef.checksum.to_le_bytes());
+        assert_eq!(nef.to_array(), expected);
+        assert_eq!(nef.checksum, 0x4c13_c2cd);
+
+        let nef = self::nef();
+        let data = nef.to_array();
+        assert_eq!(data.len(), nef.size());
+        assert_eq!(NefFile::from_array(&data).unwrap(), nef);
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        let data = nef().to_array();
+        let mut corrupted = data.clone();
+        corrupted[data.len() - 5] = 0x12;
+        assert!(matches!(NefFile::from_array(&corrupted), Err(IoError::Format(_))));
+
+        let mut wrong_magic = data.clone();
+        wrong_magic[0] = b'X';
+        assert!(matches!(NefFile::from_array(&wrong_magic), Err(IoError::Format(_))));
+
+        let mut empty = nef();
+        empty.script.clear();
+        empty.update_checksum();
+        assert!(matches!(
+            NefFile::from_array(&empty.to_array()),
+            Err(IoError::Format(_))
+        ));
+
+        let mut private = nef();
+        private.tokens[0].method = "_deploy".to_string();
+        private.update_checksum();
+        assert!(matches!(
+            NefFile::from_array(&private.to_array()),
+            Err(IoError::Format(_))
+        ));
+
+        let mut flags = data;
+        // The call flags of the token, right before the reserved bytes, the script and the checksum.
+        let index = flags.len() - 4 - 3 - 2 - 1;
+        flags[index] = 0x10;
+        assert!(NefFile::from_array(&flags).is_err());
+    }
+}
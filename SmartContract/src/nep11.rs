@@ -0,0 +1,358 @@
+//! Client side of NEP-11 non-fungible tokens, divisible or not.
+//!
+//! A non-divisible token has a single owner and is moved whole, a divisible
+//! one is shared by several owners holding amounts of it. The methods that
+//! return an iterator, `tokensOf` and the `ownerOf` of divisible tokens, are
+//! followed by a loop unwrapping it into an array, so their results can be
+//! read from the result stack like any other.
+
+use neo_io::UInt160;
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+use neo_vm::OpCode::OpCode;
+use neo_vm::ScriptBuilder::ScriptBuilder;
+use neo_vm::Types::StackItem::StackItem;
+use num::BigInt;
+use serde_json::{Map, Value};
+
+use crate::call_flags::CallFlags;
+use crate::contract_call::{emit_dynamic_call, emit_iterator_unwrap, result_array, result_bytes, ContractArg};
+use crate::error::ResultError;
+use crate::json_serializer;
+use crate::nep17::Nep17Token;
+
+/// Maximum length of a token id.
+pub const MAX_TOKEN_ID_LENGTH: usize = 64;
+
+/**
+ * A NEP-11 token contract.
+ *
+ * Like `Nep17Token`, each method emits one call into a `ScriptBuilder`
+ * and the read calls leave one result each, for the `decode_*` functions.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct Nep11Token {
+    pub hash: UInt160,
+}
+
+impl Nep11Token {
+    pub fn new(hash: UInt160) -> Self {
+        Self { hash }
+    }
+
+    pub fn symbol<'a>(&self, sb: &'a mut ScriptBuilder) -> &'a mut ScriptBuilder {
+        Nep17Token::new(self.hash).symbol(sb)
+    }
+
+    /// 0 for non-divisible tokens.
+    pub fn decimals<'a>(&self, sb: &'a mut ScriptBuilder) -> &'a mut ScriptBuilder {
+        Nep17Token::new(self.hash).decimals(sb)
+    }
+
+    pub fn total_supply<'a>(&self, sb: &'a mut ScriptBuilder) -> &'a mut ScriptBuilder {
+        Nep17Token::new(self.hash).total_supply(sb)
+    }
+
+    /// The number of tokens `owner` has, the sum of its amounts of each for divisible tokens.
+    pub fn balance_of<'a>(&self, sb: &'a mut ScriptBuilder, owner: &UInt160) -> &'a mut ScriptBuilder {
+        self.call(sb, "balanceOf", &[ContractArg::Hash160(*owner)])
+    }
+
+    /// The amount of the divisible token `token_id` that `owner` has.
+    pub fn balance_of_token<'a>(
+        &self,
+        sb: &'a mut ScriptBuilder,
+        owner: &UInt160,
+        token_id: &[u8],
+    ) -> &'a mut ScriptBuilder {
+        let args = [ContractArg::Hash160(*owner), ContractArg::ByteArray(token_id.to_vec())];
+        self.call(sb, "balanceOf", &args)
+    }
+
+    /// The ids of the first `max_items` tokens of `owner`.
+    pub fn tokens_of<'a>(&self, sb: &'a mut ScriptBuilder, owner: &UInt160, max_items: usize) -> &'a mut ScriptBuilder {
+        self.call(sb, "tokensOf", &[ContractArg::Hash160(*owner)]);
+        emit_iterator_unwrap(sb, max_items)
+    }
+
+    /// The owner of the non-divisible token `token_id`.
+    pub fn owner_of<'a>(&self, sb: &'a mut ScriptBuilder, token_id: &[u8]) -> &'a mut ScriptBuilder {
+        self.call(sb, "ownerOf", &[ContractArg::ByteArray(token_id.to_vec())])
+    }
+
+    /// The first `max_items` owners of the divisible token `token_id`.
+    pub fn owners_of<'a>(&self, sb: &'a mut ScriptBuilder, token_id: &[u8], max_items: usize) -> &'a mut ScriptBuilder {
+        self.call(sb, "ownerOf", &[ContractArg::ByteArray(token_id.to_vec())]);
+        emit_iterator_unwrap(sb, max_items)
+    }
+
+    pub fn properties<'a>(&self, sb: &'a mut ScriptBuilder, token_id: &[u8]) -> &'a mut ScriptBuilder {
+        self.call(sb, "properties", &[ContractArg::ByteArray(token_id.to_vec())])
+    }
+
+    /**
+     * Emits a transfer of the non-divisible token `token_id` to `to`, signed by its owner.
+     * `data` is passed to `onNEP11Payment` when `to` is a contract.
+     *
+     * Like the transfers of `Nep17Token`, it is followed by an `ASSERT` and leaves nothing on the stack.
+     */
+    pub fn transfer<'a>(
+        &self,
+        sb: &'a mut ScriptBuilder,
+        to: &UInt160,
+        token_id: &[u8],
+        data: &ContractArg,
+    ) -> &'a mut ScriptBuilder {
+        let args = [
+            ContractArg::Hash160(*to),
+            ContractArg::ByteArray(token_id.to_vec()),
+            data.clone(),
+        ];
+        self.call(sb, "transfer", &args).emit(OpCode::ASSERT, &[])
+    }
+
+    /// Emits a transfer of `amount` of the divisible token `token_id` from `from` to `to`.
+    pub fn transfer_divisible<'a>(
+        &self,
+        sb: &'a mut ScriptBuilder,
+        from: &UInt160,
+        to: &UInt160,
+        amount: &BigInt,
+        token_id: &[u8],
+        data: &ContractArg,
+    ) -> &'a mut ScriptBuilder {
+        let args = [
+            ContractArg::Hash160(*from),
+            ContractArg::Hash160(*to),
+            ContractArg::Integer(amount.clone()),
+            ContractArg::ByteArray(token_id.to_vec()),
+            data.clone(),
+        ];
+        self.call(sb, "transfer", &args).emit(OpCode::ASSERT, &[])
+    }
+
+    fn call<'a>(&self, sb: &'a mut ScriptBuilder, method: &str, args: &[ContractArg]) -> &'a mut ScriptBuilder {
+        emit_dynamic_call(sb, &self.hash, method, CallFlags::ALL, args)
+    }
+}
+
+/// Decodes the result of `tokensOf`.
+pub fn decode_token_ids(item: &StackItem) -> Result<Vec<Vec<u8>>, ResultError> {
+    result_array(item)?
+        .iter()
+        .map(|id| {
+            let id = result_bytes(id)?;
+            if id.len() > MAX_TOKEN_ID_LENGTH {
+                return Err(ResultError::Format(format!("token id of {} bytes", id.len())));
+            }
+            Ok(id)
+        })
+        .collect()
+}
+
+/// Decodes the result of `ownerOf` of a non-divisible token.
+pub fn decode_owner(item: &StackItem) -> Result<UInt160, ResultError> {
+    UInt160::from_slice(&result_bytes(item)?).map_err(|e| ResultError::Format(format!("{}", e)))
+}
+
+/// Decodes the result of `ownerOf` of a divisible token.
+pub fn decode_owners(item: &StackItem) -> Result<Vec<UInt160>, ResultError> {
+    result_array(item)?.iter().map(|owner| decode_owner(owner)).collect()
+}
+
+/**
+ * Decodes the result of `properties`, a `Map` with string keys such as
+ * "name" and "image", into the JSON object `StdLib.jsonSerialize` makes of it.
+ */
+pub fn decode_properties(item: &StackItem) -> Result<Map<String, Value>, ResultError> {
+    let json = json_serializer::serialize(item, &ExecutionEngineLimits::default())
+        .map_err(|e| ResultError::Format(format!("{}", e)))?;
+    match serde_json::from_slice(&json) {
+        Ok(Value::Object(properties)) => Ok(properties),
+        _ => Err(ResultError::Type(item.get_type())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_persistence::{DataCache, MemoryStore, Store};
+    use neo_vm::Script::Script;
+    use neo_vm::Types::Map::Map as VmMap;
+    use neo_vm::VMState::VMState;
+
+    use super::*;
+    use crate::application_engine::{ApplicationEngine, ApplicationHost, TEST_MODE_GAS};
+    use crate::contract_call::results;
+    use crate::contract_state::{ContractMethodDescriptor, ContractState};
+    use crate::find_options::FindOptions;
+    use crate::interop::storage::storage_key;
+    use crate::interop_descriptor::interop_hash;
+    use crate::nep17::decode_amount;
+    use crate::trigger_type::TriggerType;
+
+    const ID: i32 = 11;
+    const OWNER: UInt160 = UInt160([1; 20]);
+
+    /**
+     * A token whose `tokensOf` and `ownerOf` find the keys and values stored
+     * under "t" and "o", and whose `transfer` accepts the token "a".
+     */
+    fn token_contract() -> ContractState {
+        let find = |sb: &mut ScriptBuilder, prefix: &str, options: FindOptions| {
+            sb.emit(OpCode::DROP, &[])
+                .emit_push(&options.0.into())
+                .emit_push_string(prefix)
+                .emit_syscall(interop_hash("System.Storage.GetReadOnlyContext"))
+                .emit_syscall(interop_hash("System.Storage.Find"))
+                .emit(OpCode::RET, &[]);
+        };
+        let mut sb = ScriptBuilder::new();
+        find(
+            &mut sb,
+            "t",
+            FindOptions(FindOptions::KEYS_ONLY.0 | FindOptions::REMOVE_PREFIX.0),
+        );
+        let owner_of = sb.length();
+        find(&mut sb, "o", FindOptions::VALUES_ONLY);
+        let properties = sb.length();
+        sb.emit(OpCode::DROP, &[])
+            .emit(OpCode::NEWMAP, &[])
+            .emit(OpCode::DUP, &[])
+            .emit_push_string("name")
+            .emit_push_string("Token A")
+            .emit(OpCode::SETITEM, &[])
+            .emit(OpCode::RET, &[]);
+        let transfer = sb.length();
+        sb.emit(OpCode::DROP, &[])
+            .emit(OpCode::NIP, &[])
+            .emit_push_string("a")
+            .emit(OpCode::EQUAL, &[])
+            .emit(OpCode::RET, &[]);
+
+        let method = |name: &str, offset: usize, parameters_count: usize| ContractMethodDescriptor {
+            name: name.to_string(),
+            parameters_count,
+            offset,
+            has_return_value: true,
+            safe: name != "transfer",
+        };
+        ContractState::new(
+            ID,
+            UInt160::from([0x0b; 20]),
+            &sb.to_array(),
+            vec![
+                method("tokensOf", 0, 1),
+                method("ownerOf", owner_of, 1),
+                method("properties", properties, 1),
+                method("transfer", transfer, 3),
+            ],
+        )
+    }
+
+    fn run(script: Vec<u8>) -> ApplicationEngine {
+        let snapshot = DataCache::new(MemoryStore::new().get_snapshot());
+        let host = ApplicationHost::new(TriggerType::APPLICATION, None, snapshot, None, TEST_MODE_GAS);
+        let mut engine = ApplicationEngine::with_host(ExecutionEngineLimits::default(), host);
+        engine.host_mut().add_contract(token_contract());
+        for (key, value) in [
+            ("ta", vec![]),
+            ("tb", vec![]),
+            ("tc", vec![]),
+            ("o1", vec![1; 20]),
+            ("o2", vec![2; 20]),
+        ]
+        .iter()
+        {
+            engine
+                .host_mut()
+                .snapshot_mut()
+                .put(&storage_key(ID, key.as_bytes()), value.clone())
+                .unwrap();
+        }
+        engine.load_script(Script::from(script), -1, 0).unwrap();
+        engine.execute();
+        engine
+    }
+
+    #[test]
+    fn test_iterators() {
+        let token = Nep11Token::new(UInt160::from([0x0b; 20]));
+        let mut sb = ScriptBuilder::new();
+        token.tokens_of(&mut sb, &OWNER, 10);
+        token.tokens_of(&mut sb, &OWNER, 2);
+        token.owners_of(&mut sb, b"a", 10);
+
+        let engine = run(sb.to_array());
+        assert_eq!(engine.state(), VMState::HALT);
+        let items = results(engine.result_stack(), 3).unwrap();
+        assert_eq!(
+            decode_token_ids(&items[0]).unwrap(),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]
+        );
+        assert_eq!(decode_token_ids(&items[1]).unwrap().len(), 2);
+        assert_eq!(
+            decode_owners(&items[2]).unwrap(),
+            vec![UInt160::from([1; 20]), UInt160::from([2; 20])]
+        );
+        assert!(matches!(decode_owner(&items[2]), Err(ResultError::Type(_))));
+    }
+
+    #[test]
+    fn test_properties() {
+        let token = Nep11Token::new(UInt160::from([0x0b; 20]));
+        let mut sb = ScriptBuilder::new();
+        token.properties(&mut sb, b"a");
+
+        let engine = run(sb.to_array());
+        let items = results(engine.result_stack(), 1).unwrap();
+        let properties = decode_properties(&items[0]).unwrap();
+        assert_eq!(properties["name"], "Token A");
+
+        let map = VmMap::new(None);
+        map.set(StackItem::from(1), StackItem::from("x"));
+        let map: StackItem = map.into();
+        assert!(matches!(decode_properties(&map), Err(ResultError::Format(_))));
+        let amount = StackItem::from(3);
+        assert!(matches!(decode_properties(&amount), Err(ResultError::Type(_))));
+        assert_eq!(decode_amount(&amount, 0).unwrap().to_string(), "3");
+    }
+
+    #[test]
+    fn test_transfer() {
+        let token = Nep11Token::new(UInt160::from([0x0b; 20]));
+        let transfer = |token_id: &[u8]| {
+            let mut sb = ScriptBuilder::new();
+            token.transfer(&mut sb, &OWNER, token_id, &ContractArg::Null);
+            run(sb.to_array()).state()
+        };
+        assert_eq!(transfer(b"a"), VMState::HALT);
+        assert_eq!(transfer(b"b"), VMState::FAULT);
+    }
+
+    #[test]
+    fn test_divisible_scripts() {
+        let token = Nep11Token::new(UInt160::from([0x0b; 20]));
+        let mut sb = ScriptBuilder::new();
+        token.transfer_divisible(&mut sb, &OWNER, &UInt160::zero(), &5.into(), b"a", &"memo".into());
+        let mut expected = ScriptBuilder::new();
+        expected
+            .emit_push_string("memo")
+            .emit_push_data(b"a")
+            .emit(OpCode::PUSH5, &[])
+            .emit_push_data(&[0; 20])
+            .emit_push_data(&[1; 20])
+            .emit(OpCode::PUSH5, &[])
+            .emit(OpCode::PACK, &[]);
+        assert!(sb.to_array().starts_with(&expected.to_array()));
+        assert_eq!(sb.to_array().last(), Some(&OpCode::ASSERT.0));
+
+        let mut sb = ScriptBuilder::new();
+        token.balance_of_token(&mut sb, &OWNER, b"a");
+        let mut expected = ScriptBuilder::new();
+        expected
+            .emit_push_data(b"a")
+            .emit_push_data(&[1; 20])
+            .emit(OpCode::PUSH2, &[])
+            .emit(OpCode::PACK, &[]);
+        assert!(sb.to_array().starts_with(&expected.to_array()));
+    }
+}
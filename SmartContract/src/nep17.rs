@@ -0,0 +1,312 @@
+//! Client side of NEP-17 fungible tokens: scripts calling a token contract and the decoding of what they return.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use neo_io::UInt160;
+use neo_vm::OpCode::OpCode;
+use neo_vm::ScriptBuilder::ScriptBuilder;
+use neo_vm::Types::StackItem::StackItem;
+use num::{BigInt, Signed, Zero};
+
+use crate::call_flags::CallFlags;
+use crate::contract_call::{emit_dynamic_call, result_integer, result_string, ContractArg};
+use crate::error::{AmountError, ResultError};
+
+/// Hash of the native NEO token of N3, 0xef4073a0f2b305a38ec4050e4d3d28bc40ea63f5.
+pub const NEO_TOKEN_HASH: UInt160 = UInt160([
+    0xf5, 0x63, 0xea, 0x40, 0xbc, 0x28, 0x3d, 0x4d, 0x0e, 0x05, 0xc4, 0x8e, 0xa3, 0x05, 0xb3, 0xf2, 0xa0, 0x73, 0x40,
+    0xef,
+]);
+/// Hash of the native GAS token of N3, 0xd2a4cff31913016155e38e474a2c06d08be276cf.
+pub const GAS_TOKEN_HASH: UInt160 = UInt160([
+    0xcf, 0x76, 0xe2, 0x8b, 0xd0, 0x06, 0x2c, 0x4a, 0x47, 0x8e, 0xe3, 0x55, 0x61, 0x01, 0x13, 0x19, 0xf3, 0xcf, 0xa4,
+    0xd2,
+]);
+
+/**
+ * A NEP-17 token contract.
+ *
+ * Each method emits one call into a `ScriptBuilder`, so several calls can
+ * be run by a single script. The read calls leave their result on the
+ * stack, in the order they were emitted, for the `decode_*` functions.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct Nep17Token {
+    pub hash: UInt160,
+}
+
+impl Nep17Token {
+    /// The native NEO token, it has no decimals.
+    pub const NEO: Nep17Token = Nep17Token { hash: NEO_TOKEN_HASH };
+    /// The native GAS token, with 8 decimals.
+    pub const GAS: Nep17Token = Nep17Token { hash: GAS_TOKEN_HASH };
+
+    pub fn new(hash: UInt160) -> Self {
+        Self { hash }
+    }
+
+    pub fn symbol<'a>(&self, sb: &'a mut ScriptBuilder) -> &'a mut ScriptBuilder {
+        self.call(sb, "symbol", &[])
+    }
+
+    pub fn decimals<'a>(&self, sb: &'a mut ScriptBuilder) -> &'a mut ScriptBuilder {
+        self.call(sb, "decimals", &[])
+    }
+
+    pub fn total_supply<'a>(&self, sb: &'a mut ScriptBuilder) -> &'a mut ScriptBuilder {
+        self.call(sb, "totalSupply", &[])
+    }
+
+    pub fn balance_of<'a>(&self, sb: &'a mut ScriptBuilder, account: &UInt160) -> &'a mut ScriptBuilder {
+        self.call(sb, "balanceOf", &[ContractArg::Hash160(*account)])
+    }
+
+    /**
+     * Emits a transfer of `amount`, in the smallest unit of the token, from `from` to `to`.
+     * `data` is passed to `onNEP17Payment` when `to` is a contract, usually `ContractArg::Null`.
+     *
+     * The transfer is followed by an `ASSERT`, so the script faults instead of
+     * going on when the token refuses it. It leaves nothing on the stack.
+     */
+    pub fn transfer<'a>(
+        &self,
+        sb: &'a mut ScriptBuilder,
+        from: &UInt160,
+        to: &UInt160,
+        amount: &BigInt,
+        data: &ContractArg,
+    ) -> &'a mut ScriptBuilder {
+        let args = [
+            ContractArg::Hash160(*from),
+            ContractArg::Hash160(*to),
+            ContractArg::Integer(amount.clone()),
+            data.clone(),
+        ];
+        self.call(sb, "transfer", &args).emit(OpCode::ASSERT, &[])
+    }
+
+    fn call<'a>(&self, sb: &'a mut ScriptBuilder, method: &str, args: &[ContractArg]) -> &'a mut ScriptBuilder {
+        emit_dynamic_call(sb, &self.hash, method, CallFlags::ALL, args)
+    }
+}
+
+/// Decodes the result of `symbol`.
+pub fn decode_symbol(item: &StackItem) -> Result<String, ResultError> {
+    result_string(item)
+}
+
+/// Decodes the result of `decimals`.
+pub fn decode_decimals(item: &StackItem) -> Result<u8, ResultError> {
+    let decimals = result_integer(item)?;
+    u8::try_from(&decimals).map_err(|_| ResultError::Format(format!("invalid decimals: {}", decimals)))
+}
+
+/// Decodes the result of `totalSupply` or `balanceOf` of a token with `decimals`.
+pub fn decode_amount(item: &StackItem, decimals: u8) -> Result<TokenAmount, ResultError> {
+    Ok(TokenAmount::new(result_integer(item)?, decimals))
+}
+
+/**
+ * An amount of a token, `value` being in its smallest unit.
+ *
+ * It is displayed and parsed as a decimal number, with at most `decimals`
+ * digits after the point: 150000000 with 8 decimals is "1.5".
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TokenAmount {
+    pub value: BigInt,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(value: BigInt, decimals: u8) -> Self {
+        Self { value, decimals }
+    }
+
+    /// Parses a decimal number such as "-12.05", failing if it has more than `decimals` significant decimals.
+    pub fn parse(s: &str, decimals: u8) -> Result<Self, AmountError> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (integer, fraction) = match digits.find('.') {
+            Some(point) => (&digits[..point], &digits[point + 1..]),
+            None => (digits, ""),
+        };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+            return Err(AmountError::Format(s.to_string()));
+        }
+
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > decimals as usize {
+            return Err(AmountError::Precision(decimals));
+        }
+        let mut value = integer.to_string();
+        value.push_str(fraction);
+        value.push_str(&"0".repeat(decimals as usize - fraction.len()));
+        let value: BigInt = value.parse().map_err(|_| AmountError::Format(s.to_string()))?;
+
+        Ok(Self::new(if negative { -value } else { value }, decimals))
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    /// Trailing zeros of the decimals are dropped, "1.5" rather than "1.50000000".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let divisor = num::pow(BigInt::from(10), self.decimals as usize);
+        let abs = self.value.abs();
+        let sign = if self.value.is_negative() { "-" } else { "" };
+        let fraction = &abs % &divisor;
+        if fraction.is_zero() {
+            return write!(f, "{}{}", sign, abs / divisor);
+        }
+
+        let fraction = format!("{:0width$}", fraction, width = self.decimals as usize);
+        write!(f, "{}{}.{}", sign, abs / divisor, fraction.trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_core::crypto::hash160;
+    use neo_persistence::{DataCache, MemoryStore, Store};
+    use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+    use neo_vm::Script::Script;
+    use neo_vm::VMState::VMState;
+
+    use super::*;
+    use crate::application_engine::{ApplicationEngine, ApplicationHost, TEST_MODE_GAS};
+    use crate::contract_call::results;
+    use crate::contract_state::{ContractMethodDescriptor, ContractState};
+    use crate::trigger_type::TriggerType;
+
+    /// A token at the hash of GAS whose `transfer` accepts positive amounts.
+    fn token_contract() -> ContractState {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push_string("GAS").emit(OpCode::RET, &[]);
+        let decimals = sb.length();
+        sb.emit_push(&8.into()).emit(OpCode::RET, &[]);
+        let total_supply = sb.length();
+        sb.emit_push(&5_200_000_000_000_000i64.into()).emit(OpCode::RET, &[]);
+        let balance_of = sb.length();
+        sb.emit(OpCode::DROP, &[])
+            .emit_push(&150_000_000.into())
+            .emit(OpCode::RET, &[]);
+        let transfer = sb.length();
+        sb.emit(OpCode::DROP, &[])
+            .emit(OpCode::DROP, &[])
+            .emit_push(&0.into())
+            .emit(OpCode::GT, &[])
+            .emit(OpCode::NIP, &[])
+            .emit(OpCode::RET, &[]);
+
+        let method = |name: &str, offset: usize, parameters_count: usize| ContractMethodDescriptor {
+            name: name.to_string(),
+            parameters_count,
+            offset,
+            has_return_value: true,
+            safe: name != "transfer",
+        };
+        ContractState::new(
+            -6,
+            GAS_TOKEN_HASH,
+            &sb.to_array(),
+            vec![
+                method("symbol", 0, 0),
+                method("decimals", decimals, 0),
+                method("totalSupply", total_supply, 0),
+                method("balanceOf", balance_of, 1),
+                method("transfer", transfer, 4),
+            ],
+        )
+    }
+
+    fn run(script: Vec<u8>) -> ApplicationEngine {
+        let snapshot = DataCache::new(MemoryStore::new().get_snapshot());
+        let host = ApplicationHost::new(TriggerType::APPLICATION, None, snapshot, None, TEST_MODE_GAS);
+        let mut engine = ApplicationEngine::with_host(ExecutionEngineLimits::default(), host);
+        engine.host_mut().add_contract(token_contract());
+        engine.load_script(Script::from(script), -1, 0).unwrap();
+        engine.execute();
+        engine
+    }
+
+    #[test]
+    fn test_native_scripts() {
+        let mut sb = ScriptBuilder::new();
+        Nep17Token::GAS.decimals(&mut sb);
+        assert_eq!(
+            base64::encode(sb.to_array()),
+            "wh8MCGRlY2ltYWxzDBTPduKL0AYsSkeO41VhARMZ88+k0kFifVtS"
+        );
+        assert_eq!(NEO_TOKEN_HASH.to_string(), "0xef4073a0f2b305a38ec4050e4d3d28bc40ea63f5");
+        assert_eq!(GAS_TOKEN_HASH.to_string(), "0xd2a4cff31913016155e38e474a2c06d08be276cf");
+    }
+
+    #[test]
+    fn test_read_calls() {
+        let account = UInt160::from([1; 20]);
+        let mut sb = ScriptBuilder::new();
+        Nep17Token::GAS.symbol(&mut sb);
+        Nep17Token::GAS.decimals(&mut sb);
+        Nep17Token::GAS.total_supply(&mut sb);
+        Nep17Token::GAS.balance_of(&mut sb, &account);
+
+        let engine = run(sb.to_array());
+        assert_eq!(engine.state(), VMState::HALT);
+        let items = results(engine.result_stack(), 4).unwrap();
+        assert_eq!(decode_symbol(&items[0]).unwrap(), "GAS");
+        let decimals = decode_decimals(&items[1]).unwrap();
+        assert_eq!(decimals, 8);
+        assert_eq!(decode_amount(&items[2], decimals).unwrap().to_string(), "52000000");
+        let balance = decode_amount(&items[3], decimals).unwrap();
+        assert_eq!(balance, TokenAmount::new(150_000_000.into(), 8));
+        assert_eq!(balance.to_string(), "1.5");
+
+        assert!(matches!(
+            results(engine.result_stack(), 3),
+            Err(ResultError::Count(3, 4))
+        ));
+        assert!(matches!(decode_decimals(&items[0]), Err(ResultError::Format(_))));
+    }
+
+    #[test]
+    fn test_transfer() {
+        let from = UInt160::from_slice(&hash160(b"from")).unwrap();
+        let to = UInt160::from([2; 20]);
+        let transfer = |amount: i64| {
+            let mut sb = ScriptBuilder::new();
+            Nep17Token::GAS.transfer(&mut sb, &from, &to, &amount.into(), &ContractArg::Null);
+            run(sb.to_array())
+        };
+
+        let engine = transfer(100);
+        assert_eq!(engine.state(), VMState::HALT);
+        assert_eq!(engine.result_stack().count(), 0);
+        assert_eq!(transfer(0).state(), VMState::FAULT);
+    }
+
+    #[test]
+    fn test_token_amount() {
+        for (s, value, decimals, display) in [
+            ("1.5", 150_000_000, 8, "1.5"),
+            ("-0.00000001", -1, 8, "-0.00000001"),
+            ("12", 12, 0, "12"),
+            ("3.10", 31, 1, "3.1"),
+            ("20.000", 2_000_000_000, 8, "20"),
+        ]
+        .iter()
+        {
+            let amount = TokenAmount::parse(s, *decimals).unwrap();
+            assert_eq!(amount, TokenAmount::new((*value).into(), *decimals));
+            assert_eq!(amount.to_string(), *display);
+        }
+
+        assert!(matches!(TokenAmount::parse("0.123", 2), Err(AmountError::Precision(2))));
+        for s in ["", ".5", "1.2.3", "1e8", "+1", "--1"].iter() {
+            assert!(matches!(TokenAmount::parse(s, 8), Err(AmountError::Format(_))), "{}", s);
+        }
+    }
+}
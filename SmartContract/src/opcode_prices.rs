@@ -0,0 +1,74 @@
+use neo_vm::OpCode::OpCode;
+
+/// Default factor applied to the opcode and interop prices, set by the policy contract.
+pub const DEFAULT_EXEC_FEE_FACTOR: u32 = 30;
+
+/**
+ * Price of an instruction before the execution fee factor is applied.
+ *
+ * The table follows the N3 protocol: stack and slot operations are cheap,
+ * instructions that copy or allocate items cost more. `SYSCALL` is free,
+ * the interop it calls has its own price.
+ */
+pub fn opcode_price(opcode: OpCode) -> i64 {
+    match opcode {
+        OpCode::PUSHINT8 | OpCode::PUSHINT16 | OpCode::PUSHINT32 | OpCode::PUSHINT64 => 1 << 0,
+        OpCode::PUSHINT128 | OpCode::PUSHINT256 => 1 << 2,
+        OpCode::PUSHA => 1 << 2,
+        OpCode::PUSHNULL => 1 << 0,
+        OpCode::PUSHDATA1 => 1 << 3,
+        OpCode::PUSHDATA2 => 1 << 9,
+        OpCode::PUSHDATA4 => 1 << 12,
+        OpCode::PUSHM1 => 1 << 0,
+        OpCode(op) if (OpCode::PUSH0.0..=OpCode::PUSH16.0).contains(&op) => 1 << 0,
+        OpCode::NOP => 1 << 0,
+        OpCode(op) if (OpCode::JMP.0..=OpCode::JMPLE_L.0).contains(&op) => 1 << 1,
+        OpCode::CALL | OpCode::CALL_L | OpCode::CALLA => 1 << 9,
+        OpCode::CALLT => 1 << 15,
+        OpCode::ABORT => 0,
+        OpCode::ASSERT => 1 << 0,
+        OpCode::THROW => 1 << 9,
+        OpCode::TRY | OpCode::TRY_L | OpCode::ENDTRY | OpCode::ENDTRY_L | OpCode::ENDFINALLY => 1 << 2,
+        OpCode::RET => 0,
+        OpCode::SYSCALL => 0,
+        OpCode::DEPTH | OpCode::DROP | OpCode::NIP => 1 << 1,
+        OpCode::XDROP | OpCode::CLEAR => 1 << 4,
+        OpCode::DUP | OpCode::OVER | OpCode::PICK | OpCode::TUCK | OpCode::SWAP | OpCode::ROT => 1 << 1,
+        OpCode::ROLL => 1 << 4,
+        OpCode::REVERSE3 | OpCode::REVERSE4 => 1 << 1,
+        OpCode::REVERSEN => 1 << 4,
+        OpCode::INITSSLOT => 1 << 4,
+        OpCode::INITSLOT => 1 << 6,
+        OpCode(op) if (OpCode::LDSFLD0.0..=OpCode::STARG.0).contains(&op) => 1 << 1,
+        OpCode::NEWBUFFER => 1 << 8,
+        OpCode::MEMCPY | OpCode::CAT | OpCode::SUBSTR | OpCode::LEFT | OpCode::RIGHT => 1 << 11,
+        OpCode::INVERT => 1 << 2,
+        OpCode::AND | OpCode::OR | OpCode::XOR => 1 << 3,
+        OpCode::EQUAL | OpCode::NOTEQUAL => 1 << 5,
+        OpCode::SIGN | OpCode::ABS | OpCode::NEGATE | OpCode::INC | OpCode::DEC => 1 << 2,
+        OpCode::ADD | OpCode::SUB | OpCode::MUL | OpCode::DIV | OpCode::MOD => 1 << 3,
+        OpCode::POW | OpCode::SQRT => 1 << 6,
+        OpCode::SHL | OpCode::SHR => 1 << 3,
+        OpCode::NOT => 1 << 2,
+        OpCode::BOOLAND | OpCode::BOOLOR => 1 << 3,
+        OpCode::NZ => 1 << 2,
+        OpCode::NUMEQUAL | OpCode::NUMNOTEQUAL => 1 << 3,
+        OpCode::LT | OpCode::LE | OpCode::GT | OpCode::GE | OpCode::MIN | OpCode::MAX | OpCode::WITHIN => 1 << 3,
+        OpCode::PACK | OpCode::UNPACK => 1 << 11,
+        OpCode::NEWARRAY0 => 1 << 4,
+        OpCode::NEWARRAY | OpCode::NEWARRAY_T => 1 << 9,
+        OpCode::NEWSTRUCT0 => 1 << 4,
+        OpCode::NEWSTRUCT => 1 << 9,
+        OpCode::NEWMAP => 1 << 3,
+        OpCode::SIZE => 1 << 2,
+        OpCode::HASKEY => 1 << 6,
+        OpCode::KEYS => 1 << 4,
+        OpCode::VALUES => 1 << 13,
+        OpCode::PICKITEM => 1 << 6,
+        OpCode::APPEND | OpCode::SETITEM | OpCode::REVERSEITEMS => 1 << 13,
+        OpCode::REMOVE | OpCode::CLEARITEMS | OpCode::POPITEM => 1 << 4,
+        OpCode::ISNULL | OpCode::ISTYPE => 1 << 1,
+        OpCode::CONVERT => 1 << 13,
+        _ => panic!("Invalid opcode: 0x{:02x}", opcode.0),
+    }
+}
@@ -0,0 +1,265 @@
+//! Transactions running a script: their signers, their fees and their witnesses.
+
+use neo_io::UInt160;
+use neo_persistence::DataCache;
+use neo_tx::n3::{Signer, Transaction, TransactionAttribute};
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+use neo_vm::OpCode::OpCode;
+use neo_vm::Script::Script;
+use neo_vm::ScriptBuilder::ScriptBuilder;
+use neo_vm::VMState::VMState;
+use neo_wallet::signer::Signer as AccountSigner;
+use neo_wallet::signing::SigningError;
+
+use crate::application_engine::{ApplicationEngine, ApplicationHost, TEST_MODE_GAS};
+use crate::contract_parameters_context::{parse_multisig_contract, parse_signature_contract, SIGNATURE_LEN};
+use crate::contract_state::ContractState;
+use crate::error::BuildError;
+use crate::opcode_prices::{opcode_price, DEFAULT_EXEC_FEE_FACTOR};
+use crate::trigger_type::TriggerType;
+
+/// Default network fee per byte of a transaction, in datoshi, set by the policy contract.
+pub const DEFAULT_FEE_PER_BYTE: i64 = 1000;
+/// Price of `System.Crypto.CheckSig`, each key checked by `System.Crypto.CheckMultisig` costs as much.
+pub const CHECK_SIG_PRICE: i64 = 1 << 15;
+
+/// Size of the invocation script of a signature, `PUSHDATA1 <signature>`.
+const INVOCATION_SIZE: usize = 2 + SIGNATURE_LEN;
+
+/**
+ * Builds a transaction running `script`, the first signer paying the fees.
+ *
+ * The system fee is the GAS the script may consume, it is set as is or
+ * estimated by running the script locally. The network fee is computed from
+ * the verification scripts of the signers as the node does, it pays for the
+ * size of the signed transaction and the verification of its witnesses.
+ */
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TransactionBuilder {
+    pub script: Vec<u8>,
+    pub signers: Vec<Signer>,
+    pub attributes: Vec<TransactionAttribute>,
+    /// Makes transactions with the same script and signers distinct.
+    pub nonce: u32,
+    pub valid_until_block: u32,
+    pub system_fee: i64,
+    pub fee_per_byte: i64,
+    pub exec_fee_factor: u32,
+}
+
+impl TransactionBuilder {
+    pub fn new(script: Vec<u8>, signers: Vec<Signer>, valid_until_block: u32) -> Self {
+        Self {
+            script,
+            signers,
+            attributes: Vec::new(),
+            nonce: 0,
+            valid_until_block,
+            system_fee: 0,
+            fee_per_byte: DEFAULT_FEE_PER_BYTE,
+            exec_fee_factor: DEFAULT_EXEC_FEE_FACTOR,
+        }
+    }
+
+    /// The transaction without network fee nor witnesses.
+    pub fn unsigned_transaction(&self) -> Transaction {
+        Transaction {
+            version: 0,
+            nonce: self.nonce,
+            system_fee: self.system_fee,
+            network_fee: 0,
+            valid_until_block: self.valid_until_block,
+            signers: self.signers.clone(),
+            attributes: self.attributes.clone(),
+            script: self.script.clone(),
+            witnesses: Vec::new(),
+        }
+    }
+
+    /**
+     * Sets the system fee to the GAS consumed by running the script on
+     * `snapshot`, as the `invokescript` RPC does. Only `contracts` can be
+     * called, the script is limited to `TEST_MODE_GAS`.
+     */
+    pub fn estimate_system_fee(&mut self, snapshot: DataCache, contracts: &[ContractState]) -> Result<i64, BuildError> {
+        let mut host = ApplicationHost::new(
+            TriggerType::APPLICATION,
+            Some(self.unsigned_transaction()),
+            snapshot,
+            None,
+            TEST_MODE_GAS,
+        );
+        host.set_exec_fee_factor(self.exec_fee_factor);
+        for contract in contracts {
+            host.add_contract(contract.clone());
+        }
+
+        let mut engine = ApplicationEngine::with_host(ExecutionEngineLimits::default(), host);
+        engine
+            .load_script(Script::from(self.script.clone()), -1, 0)
+            .expect("a script starts at 0");
+        let gas_consumed = match engine.execute() {
+            VMState::HALT => engine.host().gas_consumed(),
+            _ => return Err(BuildError::Fault(engine.host().gas_consumed())),
+        };
+        self.system_fee = gas_consumed;
+        Ok(gas_consumed)
+    }
+
+    /**
+     * The network fee of the transaction signed by accounts with
+     * `verification_scripts`, one per signer in order. Only signature and
+     * multi-signature accounts are supported.
+     */
+    pub fn network_fee(&self, verification_scripts: &[Vec<u8>]) -> Result<i64, BuildError> {
+        let tx = self.unsigned_transaction();
+        let mut size = tx.serialize_unsigned().len() + neo_io::var_size(verification_scripts.len());
+        let mut exec_fee = 0;
+        for (signer, script) in tx.signers.iter().zip(verification_scripts) {
+            let invocation_size = if parse_signature_contract(script).is_some() {
+                exec_fee += signature_contract_cost();
+                INVOCATION_SIZE
+            } else if let Some((m, public_keys)) = parse_multisig_contract(script) {
+                exec_fee += multisig_contract_cost(m, public_keys.len());
+                INVOCATION_SIZE * m
+            } else {
+                return Err(BuildError::UnsupportedAccount(signer.account.to_string()));
+            };
+            size += neo_io::var_size(invocation_size) + invocation_size + neo_io::var_bytes_size(script);
+        }
+        Ok(exec_fee * self.exec_fee_factor as i64 + size as i64 * self.fee_per_byte)
+    }
+
+    /// Sets the network fee and signs the transaction with an `AccountSigner` per signer.
+    pub fn sign(&self, account_signers: &[&dyn AccountSigner], network: u32) -> Result<Transaction, BuildError> {
+        let verification_scripts = self
+            .signers
+            .iter()
+            .map(|signer| {
+                account_signers
+                    .iter()
+                    .find(|account_signer| UInt160(account_signer.script_hash()) == signer.account)
+                    .map(|account_signer| account_signer.verification_script())
+                    .ok_or_else(|| SigningError::MissingSigner(signer.account.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut tx = self.unsigned_transaction();
+        tx.network_fee = self.network_fee(&verification_scripts)?;
+        tx.sign(account_signers, network)?;
+        Ok(tx)
+    }
+}
+
+/// Cost of running a signature contract, before the execution fee factor.
+pub fn signature_contract_cost() -> i64 {
+    opcode_price(OpCode::PUSHDATA1) * 2 + opcode_price(OpCode::SYSCALL) + CHECK_SIG_PRICE
+}
+
+/// Cost of running an `m` out of `n` multi-signature contract, before the execution fee factor.
+pub fn multisig_contract_cost(m: usize, n: usize) -> i64 {
+    let push_price = |count: usize| {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push(&count.into());
+        opcode_price(OpCode(sb.to_array()[0]))
+    };
+    opcode_price(OpCode::PUSHDATA1) * (m + n) as i64
+        + push_price(m)
+        + push_price(n)
+        + opcode_price(OpCode::SYSCALL)
+        + CHECK_SIG_PRICE * n as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_core::ProtocolSettings;
+    use neo_io::Serializable;
+    use neo_persistence::{MemoryStore, Store};
+    use neo_tx::n3::Witness;
+    use neo_wallet::signer::KeySigner;
+    use neo_wallet::signing::verify_message;
+
+    use crate::interop_descriptor::interop_hash;
+
+    use super::*;
+
+    const NETWORK: u32 = 860833102;
+
+    fn builder(key: &KeySigner, script: Vec<u8>) -> TransactionBuilder {
+        TransactionBuilder::new(script, vec![Signer::called_by_entry(UInt160(key.script_hash()))], 1000)
+    }
+
+    #[test]
+    fn test_sign() {
+        let key = KeySigner::new(&[1u8; 32]).unwrap();
+        let mut builder = builder(&key, vec![OpCode::PUSH1.0]);
+        builder.system_fee = 100;
+        builder.nonce = 7;
+        let tx = builder.sign(&[&key], NETWORK).unwrap();
+
+        assert_eq!(signature_contract_cost(), 32784);
+        assert_eq!(
+            tx.network_fee,
+            tx.size() as i64 * DEFAULT_FEE_PER_BYTE + 32784 * DEFAULT_EXEC_FEE_FACTOR as i64
+        );
+        assert_eq!((tx.system_fee, tx.nonce, tx.valid_until_block), (100, 7, 1000));
+        assert!(verify_message(
+            &tx.get_sign_data(NETWORK),
+            &tx.witnesses[0].invocation_script[2..],
+            &key.public_key()
+        ));
+
+        let other = KeySigner::new(&[2u8; 32]).unwrap();
+        assert!(matches!(
+            builder.sign(&[&other], NETWORK),
+            Err(BuildError::Signing(SigningError::MissingSigner(_)))
+        ));
+    }
+
+    #[test]
+    fn test_multisig_network_fee() {
+        let public_keys = [[1u8; 32], [2u8; 32], [3u8; 32]]
+            .iter()
+            .map(|key| KeySigner::new(key).unwrap().public_key())
+            .collect::<Vec<_>>();
+        let script = ProtocolSettings::default()
+            .multisig_verification_script(2, &public_keys)
+            .unwrap();
+        let mut builder = builder(&KeySigner::new(&[1u8; 32]).unwrap(), vec![OpCode::PUSH1.0]);
+        builder.signers[0].account = Witness::new(vec![], script.clone()).script_hash();
+
+        assert_eq!(multisig_contract_cost(2, 3), 8 * 5 + 1 + 1 + 32768 * 3);
+        let mut tx = builder.unsigned_transaction();
+        tx.witnesses
+            .push(Witness::new(vec![0; 2 * INVOCATION_SIZE], script.clone()));
+        assert_eq!(
+            builder.network_fee(&[script]).unwrap(),
+            tx.size() as i64 * DEFAULT_FEE_PER_BYTE + multisig_contract_cost(2, 3) * DEFAULT_EXEC_FEE_FACTOR as i64
+        );
+        assert!(matches!(
+            builder.network_fee(&[vec![OpCode::PUSH1.0]]),
+            Err(BuildError::UnsupportedAccount(_))
+        ));
+    }
+
+    #[test]
+    fn test_estimate_system_fee() {
+        let key = KeySigner::new(&[1u8; 32]).unwrap();
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push_data(&key.script_hash())
+            .emit_syscall(interop_hash("System.Runtime.CheckWitness"))
+            .emit(OpCode::ASSERT, &[]);
+        let mut builder = builder(&key, sb.to_array());
+        let snapshot = || DataCache::new(MemoryStore::new().get_snapshot());
+
+        let fee = builder.estimate_system_fee(snapshot(), &[]).unwrap();
+        assert_eq!(fee, (8 + (1 << 10) + 1) * DEFAULT_EXEC_FEE_FACTOR as i64);
+        assert_eq!(builder.sign(&[&key], NETWORK).unwrap().system_fee, fee);
+
+        builder.signers[0].account = UInt160::zero();
+        assert!(matches!(
+            builder.estimate_system_fee(snapshot(), &[]),
+            Err(BuildError::Fault(_))
+        ));
+    }
+}
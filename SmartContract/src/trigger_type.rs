@@ -1,26 +1,55 @@
-mod SmartContract {
-    #[derive(Debug)]
-    enum TriggerType {
-        OnPersist = 0x01,
-        PostPersist = 0x02,
-        /// <summary>
-        /// The verification trigger indicates that the contract is being invoked as a verification function.
-        /// The verification function can accept multiple parameters, and should return a boolean value that indicates the validity of the Transaction or block.
-        /// The entry point of the contract will be invoked if the contract is triggered by Verification:
-        ///     main(...);
-        /// The entry point of the contract must be able to handle this type of invocation.
-        /// </summary>
-        Verification = 0x20,
-        /// <summary>
-        /// The application trigger indicates that the contract is being invoked as an application function.
-        /// The application function can accept multiple parameters, change the states of the blockchain, and return any type of value.
-        /// The contract can have any form of entry point, but we recommend that all contracts should have the following entry point:
-        ///     public byte[] main(string operation, params object[] args)
-        /// The functions can be invoked by creating an InvocationTransaction.
-        /// </summary>
-        Application = 0x40,
+use std::fmt;
 
-        System = TriggerType::OnPersist | TriggerType::PostPersist,
-        All = TriggerType::OnPersist | TriggerType::PostPersist | TriggerType::Verification | TriggerType::Application,
+/**
+ * Why a contract is invoked, stored as a bit set so that an interop can
+ * accept several triggers.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct TriggerType(pub u8);
+
+impl TriggerType {
+    /// Invoked by the system before the transactions of a block are persisted.
+    pub const ON_PERSIST: TriggerType = TriggerType(0x01);
+    /// Invoked by the system after the transactions of a block are persisted.
+    pub const POST_PERSIST: TriggerType = TriggerType(0x02);
+    /// The verification trigger indicates that the contract is being invoked as a verification function.
+    /// The verification function can accept multiple parameters, and should return a boolean value
+    /// that indicates the validity of the transaction or block.
+    pub const VERIFICATION: TriggerType = TriggerType(0x20);
+    /// The application trigger indicates that the contract is being invoked as an application function.
+    /// The application function can accept multiple parameters, change the states of the blockchain,
+    /// and return any type of value.
+    pub const APPLICATION: TriggerType = TriggerType(0x40);
+
+    pub const SYSTEM: TriggerType = TriggerType(Self::ON_PERSIST.0 | Self::POST_PERSIST.0);
+    pub const ALL: TriggerType = TriggerType(Self::SYSTEM.0 | Self::VERIFICATION.0 | Self::APPLICATION.0);
+
+    pub fn contains(&self, other: TriggerType) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for TriggerType {
+    type Output = TriggerType;
+
+    fn bitor(self, rhs: TriggerType) -> TriggerType {
+        TriggerType(self.0 | rhs.0)
+    }
+}
+
+impl fmt::Display for TriggerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = [
+            (TriggerType::ON_PERSIST, "OnPersist"),
+            (TriggerType::POST_PERSIST, "PostPersist"),
+            (TriggerType::VERIFICATION, "Verification"),
+            (TriggerType::APPLICATION, "Application"),
+        ];
+        let parts: Vec<&str> = names
+            .iter()
+            .filter(|(trigger, _)| self.contains(*trigger))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", parts.join(", "))
     }
-}
\ No newline at end of file
+}
@@ -1,13 +1,27 @@
 
+pub mod n3;
+
+// The Neo2 transaction model, only half ported from neon-js. It does not
+// build yet and is kept behind the `neo2` feature for Network's RPC queries.
+#[cfg(feature = "neo2")]
 pub mod usage;
+#[cfg(feature = "neo2")]
 pub mod txtype;
+#[cfg(feature = "neo2")]
 pub mod witness;
+#[cfg(feature = "neo2")]
 pub mod transaction_output;
+#[cfg(feature = "neo2")]
 pub mod transaction_input;
+#[cfg(feature = "neo2")]
 pub mod transaction_attribute;
+#[cfg(feature = "neo2")]
 pub mod state_descriptor;
+#[cfg(feature = "neo2")]
 pub mod utils;
+#[cfg(feature = "neo2")]
 pub mod txmodel;
+#[cfg(feature = "neo2")]
 pub mod transaction_base;
 
 
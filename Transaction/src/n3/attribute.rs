@@ -0,0 +1,122 @@
+use std::io::{Read, Write};
+
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt256};
+
+/// Maximum size of an oracle response result.
+pub const MAX_ORACLE_RESULT_SIZE: usize = 0xffff;
+
+/// Response code of an oracle request.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[repr(u8)]
+pub enum OracleResponseCode {
+    Success = 0x00,
+    ProtocolNotSupported = 0x10,
+    ConsensusUnreachable = 0x12,
+    NotFound = 0x14,
+    Timeout = 0x16,
+    Forbidden = 0x18,
+    ResponseTooLarge = 0x1a,
+    InsufficientFunds = 0x1c,
+    ContentTypeNotSupported = 0x1f,
+    Error = 0xff,
+}
+
+impl OracleResponseCode {
+    pub fn from_byte(b: u8) -> Result<Self, IoError> {
+        let code = match b {
+            0x00 => OracleResponseCode::Success,
+            0x10 => OracleResponseCode::ProtocolNotSupported,
+            0x12 => OracleResponseCode::ConsensusUnreachable,
+            0x14 => OracleResponseCode::NotFound,
+            0x16 => OracleResponseCode::Timeout,
+            0x18 => OracleResponseCode::Forbidden,
+            0x1a => OracleResponseCode::ResponseTooLarge,
+            0x1c => OracleResponseCode::InsufficientFunds,
+            0x1f => OracleResponseCode::ContentTypeNotSupported,
+            0xff => OracleResponseCode::Error,
+            _ => return Err(IoError::Format(format!("invalid oracle response code 0x{:02x}", b))),
+        };
+
+        Ok(code)
+    }
+}
+
+/**
+ * An attribute that is used to decorate the N3 Transaction.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum TransactionAttribute {
+    HighPriority,
+    OracleResponse {
+        id: u64,
+        code: OracleResponseCode,
+        result: Vec<u8>,
+    },
+    NotValidBefore {
+        height: u32,
+    },
+    Conflicts {
+        hash: UInt256,
+    },
+}
+
+impl TransactionAttribute {
+    pub fn attribute_type(&self) -> u8 {
+        match self {
+            TransactionAttribute::HighPriority => 0x01,
+            TransactionAttribute::OracleResponse { .. } => 0x11,
+            TransactionAttribute::NotValidBefore { .. } => 0x20,
+            TransactionAttribute::Conflicts { .. } => 0x21,
+        }
+    }
+
+    /// Whether the attribute may appear more than once in a transaction.
+    pub fn allow_multiple(&self) -> bool {
+        matches!(self, TransactionAttribute::Conflicts { .. })
+    }
+}
+
+impl Serializable for TransactionAttribute {
+    fn size(&self) -> usize {
+        1 + match self {
+            TransactionAttribute::HighPriority => 0,
+            TransactionAttribute::OracleResponse { result, .. } => 8 + 1 + neo_io::var_bytes_size(result),
+            TransactionAttribute::NotValidBefore { .. } => 4,
+            TransactionAttribute::Conflicts { .. } => UInt256::LENGTH,
+        }
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_u8(self.attribute_type())?;
+        match self {
+            TransactionAttribute::HighPriority => Ok(()),
+            TransactionAttribute::OracleResponse { id, code, result } => {
+                writer.write_u64(*id)?;
+                writer.write_u8(*code as u8)?;
+                writer.write_var_bytes(result)
+            }
+            TransactionAttribute::NotValidBefore { height } => writer.write_u32(*height),
+            TransactionAttribute::Conflicts { hash } => hash.serialize(writer),
+        }
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let attribute = match reader.read_u8()? {
+            0x01 => TransactionAttribute::HighPriority,
+            0x11 => {
+                let id = reader.read_u64()?;
+                let code = OracleResponseCode::from_byte(reader.read_u8()?)?;
+                let result = reader.read_var_bytes(MAX_ORACLE_RESULT_SIZE)?;
+                if code != OracleResponseCode::Success && !result.is_empty() {
+                    return Err(IoError::Format("oracle error response with a result".to_string()));
+                }
+                TransactionAttribute::OracleResponse { id, code, result }
+            }
+            0x20 => TransactionAttribute::NotValidBefore { height: reader.read_u32()? },
+            0x21 => TransactionAttribute::Conflicts { hash: reader.read_serializable()? },
+            t => return Err(IoError::Format(format!("invalid attribute type 0x{:02x}", t))),
+        };
+
+        Ok(attribute)
+    }
+}
@@ -0,0 +1,123 @@
+//! Neo N3 transaction model.
+//!
+//! The legacy Neo2 UTXO types live at the root of this crate; the types in
+//! this module follow the N3 binary format used by the VM crate and the node.
+
+pub mod attribute;
+pub mod signer;
+pub mod transaction;
+pub mod witness;
+pub mod witness_rule;
+pub mod witness_scope;
+
+pub use self::attribute::{OracleResponseCode, TransactionAttribute};
+pub use self::signer::Signer;
+pub use self::transaction::Transaction;
+pub use self::witness::Witness;
+pub use self::witness_rule::{WitnessCondition, WitnessRule, WitnessRuleAction};
+pub use self::witness_scope::WitnessScope;
+
+/// Maximum size of a serialized transaction.
+pub const MAX_TRANSACTION_SIZE: usize = 102400;
+/// Maximum number of signers plus attributes.
+pub const MAX_TRANSACTION_ATTRIBUTES: usize = 16;
+/// Maximum number of entries in the lists carried by signers and conditions.
+pub const MAX_SUBITEMS: usize = 16;
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use neo_crypto::hex;
+    use neo_io::{Serializable, UInt160};
+    use neo_wallet::signer::{KeySigner, Signer as AccountSigner};
+    use neo_wallet::signing::{verify_message, SigningError};
+
+    use super::*;
+
+    fn sample() -> Transaction {
+        let account = UInt160::from_str("0x4e3da2f51c7c6db6ede3a0a9b6041da3b4a1c5f7").unwrap();
+
+        let mut signer = Signer::new(account, WitnessScope::CALLED_BY_ENTRY | WitnessScope::CUSTOM_CONTRACTS);
+        signer.allowed_contracts.push(UInt160([0x11; 20]));
+
+        Transaction {
+            version: 0,
+            nonce: 0x01020304,
+            system_fee: 100_000_000,
+            network_fee: 1,
+            valid_until_block: 0x01020304,
+            signers: vec![signer],
+            attributes: vec![TransactionAttribute::HighPriority],
+            script: vec![0x11],
+            witnesses: vec![Witness::new(vec![0x0c, 0x00], vec![0x11])],
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        let tx = sample();
+        let bytes = tx.to_array();
+
+        assert_eq!(
+            hex::encode(&bytes),
+            "000403020100e1f50500000000010000000000000004030201\
+             01f7c5a1b4a31d04b6a9a0e3edb66d7c1cf5a23d4e1101\
+             1111111111111111111111111111111111111111\
+             0101011101020c000111"
+        );
+        assert_eq!(bytes.len(), tx.size());
+        assert_eq!(Transaction::from_array(&bytes).unwrap(), tx);
+    }
+
+    #[test]
+    fn test_hash() {
+        let tx = sample();
+
+        assert_eq!(
+            tx.hash_hex(),
+            "0xbf0b513b321eaa149210220abf5fae5f881732f9f192b70df8511ea0a07df78e"
+        );
+        assert_eq!(hex::encode(&tx.get_sign_data(860833102)[0..4]), "4e454f33");
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        let mut tx = sample();
+        tx.witnesses.clear();
+        assert!(Transaction::from_array(&tx.to_array()).is_err());
+        assert!(Transaction::deserialize_unsigned(&tx.serialize_unsigned()).is_ok());
+
+        tx.attributes.push(TransactionAttribute::HighPriority);
+        assert!(Transaction::deserialize_unsigned(&tx.serialize_unsigned()).is_err());
+
+        let mut tx = sample();
+        tx.signers[0].scopes = WitnessScope::GLOBAL | WitnessScope::CALLED_BY_ENTRY;
+        assert!(Transaction::from_array(&tx.to_array()).is_err());
+    }
+
+    #[test]
+    fn test_sign() {
+        let key = KeySigner::new(&[1u8; 32]).unwrap();
+        let mut tx = sample();
+        tx.signers[0].account = UInt160(key.script_hash());
+        tx.sign(&[&key], 860833102).unwrap();
+
+        assert_eq!(tx.witnesses.len(), 1);
+        let witness = &tx.witnesses[0];
+        assert_eq!(witness.verification_script, key.verification_script());
+        assert_eq!(witness.script_hash(), tx.signers[0].account);
+        assert_eq!(&witness.invocation_script[0..2], &[0x0c, 0x40]);
+        assert!(verify_message(
+            &tx.get_sign_data(860833102),
+            &witness.invocation_script[2..],
+            &key.public_key()
+        ));
+
+        tx.signers.push(Signer::called_by_entry(UInt160([0x22; 20])));
+        assert!(matches!(
+            tx.sign(&[&key], 860833102),
+            Err(SigningError::MissingSigner(_))
+        ));
+    }
+}
@@ -0,0 +1,91 @@
+use std::io::{Read, Write};
+
+use neo_core::neo_type::PublicKeyBin;
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt160};
+
+use crate::n3::witness_rule::WitnessRule;
+use crate::n3::witness_scope::WitnessScope;
+use crate::n3::MAX_SUBITEMS;
+
+/**
+ * An account that has to witness a transaction, together with the scope
+ * in which its signature may be used.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Signer {
+    pub account: UInt160,
+    pub scopes: WitnessScope,
+    pub allowed_contracts: Vec<UInt160>,
+    pub allowed_groups: Vec<PublicKeyBin>,
+    pub rules: Vec<WitnessRule>,
+}
+
+impl Signer {
+    /// Signer with the default `CalledByEntry` scope.
+    pub fn called_by_entry(account: UInt160) -> Self {
+        Self::new(account, WitnessScope::CALLED_BY_ENTRY)
+    }
+
+    pub fn new(account: UInt160, scopes: WitnessScope) -> Self {
+        Self {
+            account,
+            scopes,
+            allowed_contracts: Vec::new(),
+            allowed_groups: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Serializable for Signer {
+    fn size(&self) -> usize {
+        let mut size = UInt160::LENGTH + 1;
+        if self.scopes.contains(WitnessScope::CUSTOM_CONTRACTS) {
+            size += neo_io::var_list_size(&self.allowed_contracts);
+        }
+        if self.scopes.contains(WitnessScope::CUSTOM_GROUPS) {
+            size += neo_io::var_size(self.allowed_groups.len()) + self.allowed_groups.len() * 33;
+        }
+        if self.scopes.contains(WitnessScope::WITNESS_RULES) {
+            size += neo_io::var_list_size(&self.rules);
+        }
+        size
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        self.account.serialize(writer)?;
+        writer.write_u8(self.scopes.0)?;
+        if self.scopes.contains(WitnessScope::CUSTOM_CONTRACTS) {
+            writer.write_serializable_list(&self.allowed_contracts)?;
+        }
+        if self.scopes.contains(WitnessScope::CUSTOM_GROUPS) {
+            writer.write_var_int(self.allowed_groups.len() as u64)?;
+            self.allowed_groups.iter().try_for_each(|key| writer.write_bytes(key))?;
+        }
+        if self.scopes.contains(WitnessScope::WITNESS_RULES) {
+            writer.write_serializable_list(&self.rules)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let account = reader.read_serializable()?;
+        let scopes = WitnessScope::from_byte(reader.read_u8()?)?;
+        let mut signer = Signer::new(account, scopes);
+
+        if scopes.contains(WitnessScope::CUSTOM_CONTRACTS) {
+            signer.allowed_contracts = reader.read_serializable_list(MAX_SUBITEMS)?;
+        }
+        if scopes.contains(WitnessScope::CUSTOM_GROUPS) {
+            let count = reader.read_var_int(MAX_SUBITEMS as u64)?;
+            for _ in 0..count {
+                signer.allowed_groups.push(reader.read_array()?);
+            }
+        }
+        if scopes.contains(WitnessScope::WITNESS_RULES) {
+            signer.rules = reader.read_serializable_list(MAX_SUBITEMS)?;
+        }
+
+        Ok(signer)
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+use std::io::{Read, Write};
+
+use neo_crypto::sha2::{Digest, Sha256};
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt160, UInt256};
+use neo_wallet::signer::Signer as AccountSigner;
+use neo_wallet::signing::SigningError;
+
+use crate::n3::attribute::TransactionAttribute;
+use crate::n3::signer::Signer;
+use crate::n3::witness::Witness;
+use crate::n3::{MAX_TRANSACTION_ATTRIBUTES, MAX_TRANSACTION_SIZE};
+
+/// Size of the fixed part of the header: version, nonce, fees and valid-until-block.
+pub const HEADER_SIZE: usize = 1 + 4 + 8 + 8 + 4;
+
+/**
+ * Neo N3 transaction.
+ *
+ * The hash of a transaction is the SHA-256 of its unsigned serialization,
+ * and the data signed by each witness is the network magic followed by that hash.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Default)]
+pub struct Transaction {
+    pub version: u8,
+    pub nonce: u32,
+    pub system_fee: i64,
+    pub network_fee: i64,
+    pub valid_until_block: u32,
+    pub signers: Vec<Signer>,
+    pub attributes: Vec<TransactionAttribute>,
+    pub script: Vec<u8>,
+    pub witnesses: Vec<Witness>,
+}
+
+impl Transaction {
+    /// The first signer pays the fees.
+    pub fn sender(&self) -> Option<&UInt160> {
+        self.signers.first().map(|signer| &signer.account)
+    }
+
+    fn unsigned_size(&self) -> usize {
+        HEADER_SIZE
+            + neo_io::var_list_size(&self.signers)
+            + neo_io::var_list_size(&self.attributes)
+            + neo_io::var_bytes_size(&self.script)
+    }
+
+    fn serialize_unsigned_into<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_u8(self.version)?;
+        writer.write_u32(self.nonce)?;
+        writer.write_i64(self.system_fee)?;
+        writer.write_i64(self.network_fee)?;
+        writer.write_u32(self.valid_until_block)?;
+        writer.write_serializable_list(&self.signers)?;
+        writer.write_serializable_list(&self.attributes)?;
+        writer.write_var_bytes(&self.script)
+    }
+
+    /// Serializes everything but the witnesses, this is the hashed part.
+    pub fn serialize_unsigned(&self) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(Vec::with_capacity(self.unsigned_size()));
+        self.serialize_unsigned_into(&mut writer)
+            .expect("writing to a Vec<u8> can not fail");
+        writer.into_inner()
+    }
+
+    /// Deserializes the unsigned part of a transaction.
+    pub fn deserialize_unsigned(data: &[u8]) -> Result<Self, IoError> {
+        let mut reader = BinaryReader::from_slice(data);
+        let tx = Transaction::deserialize_unsigned_from(&mut reader)?;
+
+        if !reader.is_empty() {
+            return Err(IoError::Format("trailing data after transaction".to_string()));
+        }
+        Ok(tx)
+    }
+
+    fn deserialize_unsigned_from<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let version = reader.read_u8()?;
+        if version > 0 {
+            return Err(IoError::Format(format!("invalid version {}", version)));
+        }
+
+        let nonce = reader.read_u32()?;
+        let system_fee = reader.read_i64()?;
+        if system_fee < 0 {
+            return Err(IoError::Format("negative system fee".to_string()));
+        }
+        let network_fee = reader.read_i64()?;
+        if network_fee < 0 {
+            return Err(IoError::Format("negative network fee".to_string()));
+        }
+        if system_fee.checked_add(network_fee).is_none() {
+            return Err(IoError::Format("fee overflow".to_string()));
+        }
+        let valid_until_block = reader.read_u32()?;
+
+        let signers = Transaction::deserialize_signers(reader)?;
+        let attributes = Transaction::deserialize_attributes(reader, MAX_TRANSACTION_ATTRIBUTES - signers.len())?;
+
+        let script = reader.read_var_bytes(u16::MAX as usize)?;
+        if script.is_empty() {
+            return Err(IoError::Format("empty script".to_string()));
+        }
+
+        Ok(Transaction {
+            version,
+            nonce,
+            system_fee,
+            network_fee,
+            valid_until_block,
+            signers,
+            attributes,
+            script,
+            witnesses: Vec::new(),
+        })
+    }
+
+    fn deserialize_signers<R: Read>(reader: &mut BinaryReader<R>) -> Result<Vec<Signer>, IoError> {
+        let signers: Vec<Signer> = reader.read_serializable_list(MAX_TRANSACTION_ATTRIBUTES)?;
+        if signers.is_empty() {
+            return Err(IoError::Format("transaction without signers".to_string()));
+        }
+
+        let mut accounts = HashSet::with_capacity(signers.len());
+        if !signers.iter().all(|signer| accounts.insert(signer.account)) {
+            return Err(IoError::Format("duplicate signer".to_string()));
+        }
+        Ok(signers)
+    }
+
+    fn deserialize_attributes<R: Read>(
+        reader: &mut BinaryReader<R>,
+        max: usize,
+    ) -> Result<Vec<TransactionAttribute>, IoError> {
+        let attributes: Vec<TransactionAttribute> = reader.read_serializable_list(max)?;
+
+        let mut types = HashSet::with_capacity(attributes.len());
+        if !attributes
+            .iter()
+            .all(|attribute| attribute.allow_multiple() || types.insert(attribute.attribute_type()))
+        {
+            return Err(IoError::Format("duplicate attribute".to_string()));
+        }
+        Ok(attributes)
+    }
+
+    /// SHA-256 of the unsigned transaction.
+    pub fn hash(&self) -> UInt256 {
+        UInt256::from_slice(&Sha256::digest(&self.serialize_unsigned())).unwrap()
+    }
+
+    /// Transaction hash as displayed by the node and explorers, eg. `0x...`.
+    pub fn hash_hex(&self) -> String {
+        self.hash().to_string()
+    }
+
+    /// Data each witness signs: the network magic followed by the transaction hash.
+    pub fn get_sign_data(&self, network: u32) -> Vec<u8> {
+        let mut data = Vec::with_capacity(4 + UInt256::LENGTH);
+        data.extend_from_slice(&network.to_le_bytes());
+        data.extend_from_slice(self.hash().as_bytes());
+        data
+    }
+
+    /**
+     * Signs the transaction for `network`, replacing its witnesses with one per signer.
+     * Each signer account needs an `AccountSigner` whose script hash is the account.
+     */
+    pub fn sign(&mut self, account_signers: &[&dyn AccountSigner], network: u32) -> Result<(), SigningError> {
+        let hash = self.hash();
+        self.witnesses = self
+            .signers
+            .iter()
+            .map(|signer| {
+                let account_signer = account_signers
+                    .iter()
+                    .find(|account_signer| UInt160(account_signer.script_hash()) == signer.account)
+                    .ok_or_else(|| SigningError::MissingSigner(signer.account.to_string()))?;
+                let signature = account_signer.sign_transaction(hash.as_bytes(), network)?;
+                Ok(Witness::from_signature(
+                    &signature,
+                    account_signer.verification_script(),
+                ))
+            })
+            .collect::<Result<_, SigningError>>()?;
+        Ok(())
+    }
+}
+
+impl Serializable for Transaction {
+    fn size(&self) -> usize {
+        self.unsigned_size() + neo_io::var_list_size(&self.witnesses)
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        self.serialize_unsigned_into(writer)?;
+        writer.write_serializable_list(&self.witnesses)
+    }
+
+    /// Deserializes a signed transaction, it must carry one witness per signer.
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let start = reader.position();
+        let mut tx = Transaction::deserialize_unsigned_from(reader)?;
+
+        tx.witnesses = reader.read_serializable_list(tx.signers.len())?;
+        if tx.witnesses.len() != tx.signers.len() {
+            return Err(IoError::Format(format!(
+                "expected {} witnesses, found {}",
+                tx.signers.len(),
+                tx.witnesses.len()
+            )));
+        }
+
+        let size = reader.position() - start;
+        if size > MAX_TRANSACTION_SIZE {
+            return Err(IoError::LengthExceeded(size as u64, MAX_TRANSACTION_SIZE as u64));
+        }
+        Ok(tx)
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::{Read, Write};
+
+use neo_core::crypto::hash160;
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt160};
+
+/// Maximum size of an invocation script.
+pub const MAX_INVOCATION_SCRIPT: usize = 1024;
+/// Maximum size of a verification script.
+pub const MAX_VERIFICATION_SCRIPT: usize = 1024;
+
+/**
+ * A Witness is a section of VM code that is ran during the verification of the Transaction.
+ *
+ * The invocation script pushes the signatures and the verification script checks them.
+ */
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Default)]
+pub struct Witness {
+    pub invocation_script: Vec<u8>,
+    pub verification_script: Vec<u8>,
+}
+
+impl Witness {
+    pub fn new(invocation_script: Vec<u8>, verification_script: Vec<u8>) -> Self {
+        Self {
+            invocation_script,
+            verification_script,
+        }
+    }
+
+    /// Witness of a signature contract, the invocation script pushes `signature`.
+    pub fn from_signature(signature: &[u8], verification_script: Vec<u8>) -> Self {
+        let mut invocation_script = Vec::with_capacity(2 + signature.len());
+        // PUSHDATA1
+        invocation_script.push(0x0c);
+        invocation_script.push(signature.len() as u8);
+        invocation_script.extend_from_slice(signature);
+        Self::new(invocation_script, verification_script)
+    }
+
+    /// Script hash of the verification script.
+    pub fn script_hash(&self) -> UInt160 {
+        UInt160::from_slice(&hash160(&self.verification_script)).unwrap()
+    }
+}
+
+impl Serializable for Witness {
+    fn size(&self) -> usize {
+        neo_io::var_bytes_size(&self.invocation_script) + neo_io::var_bytes_size(&self.verification_script)
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_var_bytes(&self.invocation_script)?;
+        writer.write_var_bytes(&self.verification_script)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let invocation_script = reader.read_var_bytes(MAX_INVOCATION_SCRIPT)?;
+        let verification_script = reader.read_var_bytes(MAX_VERIFICATION_SCRIPT)?;
+
+        Ok(Witness {
+            invocation_script,
+            verification_script,
+        })
+    }
+}
@@ -0,0 +1,141 @@
+use std::io::{Read, Write};
+
+use neo_core::neo_type::PublicKeyBin;
+use neo_io::{BinaryReader, BinaryWriter, IoError, Serializable, UInt160};
+
+use crate::n3::MAX_SUBITEMS;
+
+/// Maximum nesting depth of `Not`, `And` and `Or` conditions.
+pub const MAX_NESTING_DEPTH: usize = 2;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[repr(u8)]
+pub enum WitnessRuleAction {
+    Deny = 0,
+    Allow = 1,
+}
+
+/// Condition of a witness rule, evaluated by the node at `CheckWitness` time.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum WitnessCondition {
+    Boolean(bool),
+    Not(Box<WitnessCondition>),
+    And(Vec<WitnessCondition>),
+    Or(Vec<WitnessCondition>),
+    ScriptHash(UInt160),
+    Group(PublicKeyBin),
+    CalledByEntry,
+    CalledByContract(UInt160),
+    CalledByGroup(PublicKeyBin),
+}
+
+impl WitnessCondition {
+    pub fn condition_type(&self) -> u8 {
+        match self {
+            WitnessCondition::Boolean(_) => 0x00,
+            WitnessCondition::Not(_) => 0x01,
+            WitnessCondition::And(_) => 0x02,
+            WitnessCondition::Or(_) => 0x03,
+            WitnessCondition::ScriptHash(_) => 0x18,
+            WitnessCondition::Group(_) => 0x19,
+            WitnessCondition::CalledByEntry => 0x20,
+            WitnessCondition::CalledByContract(_) => 0x28,
+            WitnessCondition::CalledByGroup(_) => 0x29,
+        }
+    }
+
+    fn deserialize_with_depth<R: Read>(reader: &mut BinaryReader<R>, max_depth: usize) -> Result<Self, IoError> {
+        let condition_type = reader.read_u8()?;
+        if matches!(condition_type, 0x01..=0x03) && max_depth == 0 {
+            return Err(IoError::Format("witness condition nested too deeply".to_string()));
+        }
+
+        let condition = match condition_type {
+            0x00 => WitnessCondition::Boolean(reader.read_bool()?),
+            0x01 => WitnessCondition::Not(Box::new(WitnessCondition::deserialize_with_depth(reader, max_depth - 1)?)),
+            0x02 | 0x03 => {
+                let count = reader.read_var_int(MAX_SUBITEMS as u64)? as usize;
+                if count == 0 {
+                    return Err(IoError::Format("empty witness condition list".to_string()));
+                }
+                let mut conditions = Vec::with_capacity(count);
+                for _ in 0..count {
+                    conditions.push(WitnessCondition::deserialize_with_depth(reader, max_depth - 1)?);
+                }
+                if condition_type == 0x02 {
+                    WitnessCondition::And(conditions)
+                } else {
+                    WitnessCondition::Or(conditions)
+                }
+            }
+            0x18 => WitnessCondition::ScriptHash(reader.read_serializable()?),
+            0x19 => WitnessCondition::Group(reader.read_array()?),
+            0x20 => WitnessCondition::CalledByEntry,
+            0x28 => WitnessCondition::CalledByContract(reader.read_serializable()?),
+            0x29 => WitnessCondition::CalledByGroup(reader.read_array()?),
+            t => return Err(IoError::Format(format!("invalid witness condition type 0x{:02x}", t))),
+        };
+
+        Ok(condition)
+    }
+}
+
+impl Serializable for WitnessCondition {
+    fn size(&self) -> usize {
+        1 + match self {
+            WitnessCondition::Boolean(_) => 1,
+            WitnessCondition::Not(condition) => condition.size(),
+            WitnessCondition::And(conditions) | WitnessCondition::Or(conditions) => neo_io::var_list_size(conditions),
+            WitnessCondition::ScriptHash(_) | WitnessCondition::CalledByContract(_) => UInt160::LENGTH,
+            WitnessCondition::Group(key) | WitnessCondition::CalledByGroup(key) => key.len(),
+            WitnessCondition::CalledByEntry => 0,
+        }
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_u8(self.condition_type())?;
+        match self {
+            WitnessCondition::Boolean(b) => writer.write_bool(*b),
+            WitnessCondition::Not(condition) => condition.serialize(writer),
+            WitnessCondition::And(conditions) | WitnessCondition::Or(conditions) => {
+                writer.write_serializable_list(conditions)
+            }
+            WitnessCondition::ScriptHash(hash) | WitnessCondition::CalledByContract(hash) => hash.serialize(writer),
+            WitnessCondition::Group(key) | WitnessCondition::CalledByGroup(key) => writer.write_bytes(key),
+            WitnessCondition::CalledByEntry => Ok(()),
+        }
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        WitnessCondition::deserialize_with_depth(reader, MAX_NESTING_DEPTH)
+    }
+}
+
+/// Rule attached to a signer with the `WitnessRules` scope.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct WitnessRule {
+    pub action: WitnessRuleAction,
+    pub condition: WitnessCondition,
+}
+
+impl Serializable for WitnessRule {
+    fn size(&self) -> usize {
+        1 + self.condition.size()
+    }
+
+    fn serialize<W: Write>(&self, writer: &mut BinaryWriter<W>) -> Result<(), IoError> {
+        writer.write_u8(self.action as u8)?;
+        self.condition.serialize(writer)
+    }
+
+    fn deserialize<R: Read>(reader: &mut BinaryReader<R>) -> Result<Self, IoError> {
+        let action = match reader.read_u8()? {
+            0 => WitnessRuleAction::Deny,
+            1 => WitnessRuleAction::Allow,
+            a => return Err(IoError::Format(format!("invalid witness rule action {}", a))),
+        };
+        let condition = reader.read_serializable()?;
+
+        Ok(WitnessRule { action, condition })
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use neo_io::IoError;
+
+/**
+ * Scopes of a transaction signer, stored as a bit set.
+ *
+ * `Global` can not be combined with any other scope.
+ */
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct WitnessScope(pub u8);
+
+impl WitnessScope {
+    /// No contract can use the signature, only valid for the fee payer.
+    pub const NONE: WitnessScope = WitnessScope(0x00);
+    /// Only the entry script and the contracts it calls directly.
+    pub const CALLED_BY_ENTRY: WitnessScope = WitnessScope(0x01);
+    /// Only the contracts listed in `allowed_contracts`.
+    pub const CUSTOM_CONTRACTS: WitnessScope = WitnessScope(0x10);
+    /// Only the contracts in the groups listed in `allowed_groups`.
+    pub const CUSTOM_GROUPS: WitnessScope = WitnessScope(0x20);
+    /// Evaluated against the signer `rules`.
+    pub const WITNESS_RULES: WitnessScope = WitnessScope(0x40);
+    /// The signature is valid everywhere.
+    pub const GLOBAL: WitnessScope = WitnessScope(0x80);
+
+    const ALL: u8 = 0x01 | 0x10 | 0x20 | 0x40 | 0x80;
+
+    pub fn from_byte(b: u8) -> Result<Self, IoError> {
+        let scope = WitnessScope(b);
+        if b & !WitnessScope::ALL != 0 || (scope.contains(WitnessScope::GLOBAL) && b != 0x80) {
+            return Err(IoError::Format(format!("invalid witness scope 0x{:02x}", b)));
+        }
+
+        Ok(scope)
+    }
+
+    pub fn contains(&self, other: WitnessScope) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for WitnessScope {
+    type Output = WitnessScope;
+
+    fn bitor(self, rhs: WitnessScope) -> WitnessScope {
+        WitnessScope(self.0 | rhs.0)
+    }
+}
+
+impl fmt::Display for WitnessScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "None");
+        }
+
+        let names = [
+            (WitnessScope::CALLED_BY_ENTRY, "CalledByEntry"),
+            (WitnessScope::CUSTOM_CONTRACTS, "CustomContracts"),
+            (WitnessScope::CUSTOM_GROUPS, "CustomGroups"),
+            (WitnessScope::WITNESS_RULES, "WitnessRules"),
+            (WitnessScope::GLOBAL, "Global"),
+        ];
+        let parts: Vec<&str> = names
+            .iter()
+            .filter(|(scope, _)| self.contains(*scope))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
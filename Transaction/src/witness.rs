@@ -4,7 +4,6 @@ use neo_core::misc::reverse_hex;
 use neo_core::no_std::io::Error;
 use neo_core::stringstream::StringStream;
 use neo_crypto::hex;
-use neo_wallet::verify::verify;
 
 use crate::txmodel::Transaction;
 
@@ -73,89 +72,6 @@ impl Witness {
     }
 
 
-    /**
-     * Builds a multi-sig Witness object.
-     * @param tx Hexstring to be signed.
-     * @param sigs Unordered list of signatures.
-     * @param acct_or_verification_script Account or verification script. Account needs to be the multi-sig account and not one of the public keys.
-     */
-    pub fn build_multi_sig(
-        &self,
-        tx: &str,
-        sigs: &[Witness],
-        acct_or_verification_script: &str,
-    ) -> Witness {
-
-        let verification_script = acct_or_verification_script;
-
-
-        let public_keys =  getPublicKeysFromVerificationScript(verification_script);
-
-        let orderedSigs = public_keys.len().fill("");
-
-        sigs.forEach((element) => {
-            if (typeof element == = "string") {
-                let position = public_keys.findIndex((key) =>
-                verify(tx, element, key)
-                );
-
-                if position == -1 {
-                    panic!("Invalid signature given: $ { element }");
-                }
-
-                orderedSigs[position] = element;
-            } else if (element
-            instanceof
-            Witness) {
-                let keys = getPublicKeysFromVerificationScript(
-                    element.verificationScript
-                );
-
-                if (keys.len() != 1) {
-                    throw
-                    new
-                    Error("Given witness contains more than 1 public key!");
-                }
-
-                let position = public_keys.indexOf(keys[0]);
-
-                orderedSigs[position] = getSignaturesFromInvocationScript(
-                    element.invocationScript
-                )[0];
-            } else {
-                panic!("Unable to process given signature");
-            }
-        });
-
-        let signingThreshold = getSigningThresholdFromVerificationScript(
-            verification_script
-        );
-
-        let validSigs = orderedSigs.filter((s) => s != "");
-
-        if (validSigs.len() < signingThreshold) {
-            throw
-            new
-            Error(
-            `Insufficient
-            signatures: expected $ { signingThreshold }
-            but
-            got $ { validSigs.len() }
-            instead`
-            );
-        }
-
-        Witness {
-            invocation_script: validSigs
-                .slice(0, signingThreshold)
-                .map((s) => "40" + s)
-            .join(""),
-            verification_script,
-            _script_hash: None,
-        }
-    }
-
-
     pub fn get_script_hash(&mut self) -> String {
         if self._script_hash {
             return self._script_hash.unwrap().to_string();
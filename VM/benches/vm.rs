@@ -0,0 +1,95 @@
+//! Throughput of the VM on the neo-vm test corpus and on scripts churning through stack items.
+//!
+//! Run with `cargo bench -p neo_vm`. The corpus benchmark executes every
+//! script of `tests/neo-vm.Tests/Tests` to its end, faulting ones included.
+//! It fails until `tests/fetch-neo-vm-tests.sh` copied them there.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use neo_vm::ExecutionEngine::ExecutionEngine;
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+use neo_vm::OpCode::OpCode;
+use neo_vm::Script::Script;
+use neo_vm::VMState::VMState;
+
+use common::{load_tests, parse_script, NEO_VM_TESTS};
+
+/// The scripts of the neo-vm tests.
+fn corpus() -> Vec<Vec<u8>> {
+    let mut scripts = Vec::new();
+    for (_, content) in load_tests(NEO_VM_TESTS) {
+        for test in content["tests"].as_array().unwrap() {
+            scripts.push(parse_script(&test["script"]));
+        }
+    }
+    scripts
+}
+
+fn run(script: &[u8]) -> VMState {
+    let mut engine = ExecutionEngine::new(ExecutionEngineLimits::default());
+    engine.load_script(Script::from(script.to_vec()), -1, 0).unwrap();
+    engine.execute()
+}
+
+/// Counts down from 1000: `PUSHINT16 1000, loop: DEC, DUP, JMPIF loop`.
+fn integer_loop() -> Vec<u8> {
+    vec![
+        OpCode::PUSHINT16.0,
+        0xe8,
+        0x03,
+        OpCode::DEC.0,
+        OpCode::DUP.0,
+        OpCode::JMPIF.0,
+        -2i8 as u8,
+    ]
+}
+
+/// Appends 1000 integers to an array: `NEWARRAY0, PUSHINT16 1000, loop: OVER, PUSH1, APPEND, DEC, DUP, JMPIF loop`.
+fn array_loop() -> Vec<u8> {
+    vec![
+        OpCode::NEWARRAY0.0,
+        OpCode::PUSHINT16.0,
+        0xe8,
+        0x03,
+        OpCode::OVER.0,
+        OpCode::PUSH1.0,
+        OpCode::APPEND.0,
+        OpCode::DEC.0,
+        OpCode::DUP.0,
+        OpCode::JMPIF.0,
+        -5i8 as u8,
+    ]
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let scripts = corpus();
+    assert!(
+        !scripts.is_empty(),
+        "no test files under {}, run tests/fetch-neo-vm-tests.sh",
+        NEO_VM_TESTS
+    );
+
+    let mut group = c.benchmark_group("vm");
+    group.throughput(Throughput::Elements(scripts.len() as u64));
+    group.bench_function("corpus", |b| {
+        b.iter(|| {
+            for script in scripts.iter() {
+                black_box(run(script));
+            }
+        })
+    });
+
+    group.throughput(Throughput::Elements(1000));
+    let script = integer_loop();
+    assert_eq!(run(&script), VMState::HALT);
+    group.bench_function("integer_loop", |b| b.iter(|| run(&script)));
+    let script = array_loop();
+    assert_eq!(run(&script), VMState::HALT);
+    group.bench_function("array_loop", |b| b.iter(|| run(&script)));
+    group.finish();
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
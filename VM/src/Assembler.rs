@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use num::BigInt;
+
+use crate::OpCode::OpCode;
+use crate::Types::StackItemType::StackItemType;
+
+/// <summary>
+/// The error of <see cref="assemble"/>, with the line of the text it was found at, from 1.
+/// </summary>
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AssembleError {}
+
+/// <summary>
+/// An instruction read by the first pass, once its offset is known.
+/// </summary>
+struct Line<'a> {
+    number: usize,
+    offset: usize,
+    opcode: OpCode,
+    operand: &'a str,
+}
+
+/// <summary>
+/// Converts text in the format of <see cref="Disassembler.disassemble"/> back to a script.
+/// </summary>
+/// <remarks>
+/// Each line holds an optional offset, ignored, an opcode and its operand. Comments start with <c>;</c>.
+/// A line may start with labels, <c>name:</c>, which jump, call and try targets use in place of offsets.
+/// The short and long forms of the instructions are kept, so a disassembled script assembles to the same bytes.
+/// </remarks>
+/// <param name="text">The text to assemble.</param>
+/// <param name="syscalls">The names of the interop services by hash, a <c>SYSCALL</c> takes a name or a hash.</param>
+/// <returns>The script.</returns>
+pub fn assemble(text: &str, syscalls: &HashMap<u32, String>) -> Result<Vec<u8>, AssembleError> {
+    let mut lines = Vec::new();
+    let mut labels = HashMap::new();
+    let mut offset = 0;
+    for (index, text) in text.lines().enumerate() {
+        let number = index + 1;
+        let error = |message: String| AssembleError { line: number, message };
+        let mut text = text.split(';').next().unwrap().trim();
+        while let Some((label, rest)) = split_label(text) {
+            if !is_label(label) {
+                return Err(error(format!("invalid label {}", label)));
+            }
+            if labels.insert(label, offset).is_some() {
+                return Err(error(format!("duplicate label {}", label)));
+            }
+            text = rest;
+        }
+        let mut tokens = text.splitn(2, char::is_whitespace);
+        let mut name = tokens.next().unwrap();
+        let mut operand = tokens.next().unwrap_or("").trim();
+        if name.chars().all(|c| c.is_ascii_digit()) && !name.is_empty() {
+            let mut tokens = operand.splitn(2, char::is_whitespace);
+            name = tokens.next().unwrap();
+            operand = tokens.next().unwrap_or("").trim();
+        }
+        if name.is_empty() {
+            continue;
+        }
+        let opcode =
+            OpCode::from_name(&name.to_ascii_uppercase()).ok_or_else(|| error(format!("unknown opcode {}", name)))?;
+        let size = match opcode.operand_size_prefix() {
+            0 => 1 + opcode.operand_size(),
+            prefix => 1 + prefix + parse_hex(operand).map_err(error)?.len(),
+        };
+        lines.push(Line {
+            number,
+            offset,
+            opcode,
+            operand,
+        });
+        offset += size;
+    }
+
+    let mut script = Vec::with_capacity(offset);
+    for line in lines {
+        script.push(line.opcode.0);
+        encode_operand(&line, &labels, syscalls, &mut script).map_err(|message| AssembleError {
+            line: line.number,
+            message,
+        })?;
+    }
+    Ok(script)
+}
+
+fn split_label(text: &str) -> Option<(&str, &str)> {
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    let label = text[..end].strip_suffix(':')?;
+    Some((label, text[end..].trim_start()))
+}
+
+fn is_label(label: &str) -> bool {
+    let mut chars = label.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+fn encode_operand(
+    line: &Line,
+    labels: &HashMap<&str, usize>,
+    syscalls: &HashMap<u32, String>,
+    script: &mut Vec<u8>,
+) -> Result<(), String> {
+    let opcode = line.opcode;
+    let operand = line.operand;
+    let prefix = opcode.operand_size_prefix();
+    if prefix > 0 {
+        let data = parse_hex(operand)?;
+        let max = match prefix {
+            1 => u8::MAX as usize,
+            2 => u16::MAX as usize,
+            _ => i32::MAX as usize,
+        };
+        if data.len() > max {
+            return Err(format!("{} bytes don't fit in {}", data.len(), opcode.name().unwrap()));
+        }
+        script.extend_from_slice(&(data.len() as u32).to_le_bytes()[..prefix]);
+        script.extend_from_slice(&data);
+        return Ok(());
+    }
+
+    let size = opcode.operand_size();
+    if opcode >= OpCode::PUSHINT8 && opcode <= OpCode::PUSHINT256 {
+        let value: BigInt = operand.parse().map_err(|_| format!("invalid integer {}", operand))?;
+        let mut data = value.to_signed_bytes_le();
+        if data.len() > size {
+            return Err(format!("{} doesn't fit in {}", value, opcode.name().unwrap()));
+        }
+        let padding = if value.sign() == num::bigint::Sign::Minus {
+            0xff
+        } else {
+            0
+        };
+        data.resize(size, padding);
+        script.extend_from_slice(&data);
+        return Ok(());
+    }
+
+    let offset_count = opcode.offset_count();
+    if offset_count > 0 {
+        let targets = split_operands(operand, offset_count)?;
+        for target in targets {
+            let target = match labels.get(target) {
+                Some(&offset) => offset as i64,
+                None => target.parse().map_err(|_| format!("unknown label {}", target))?,
+            };
+            let relative = target - line.offset as i64;
+            match size / offset_count {
+                1 => match i8::try_from(relative) {
+                    Ok(relative) => script.push(relative as u8),
+                    Err(_) => {
+                        return Err(format!(
+                            "target {} is too far, use {}_L",
+                            target,
+                            opcode.name().unwrap()
+                        ))
+                    }
+                },
+                _ => match i32::try_from(relative) {
+                    Ok(relative) => script.extend_from_slice(&relative.to_le_bytes()),
+                    Err(_) => return Err(format!("target {} is out of range", target)),
+                },
+            }
+        }
+        return Ok(());
+    }
+
+    match opcode {
+        OpCode::SYSCALL => {
+            let hash = match operand.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16).map_err(|_| format!("invalid hash {}", operand))?,
+                None => {
+                    *syscalls
+                        .iter()
+                        .find(|(_, name)| name.as_str() == operand)
+                        .ok_or_else(|| format!("unknown syscall {}", operand))?
+                        .0
+                }
+            };
+            script.extend_from_slice(&hash.to_le_bytes());
+        }
+        OpCode::NEWARRAY_T | OpCode::ISTYPE | OpCode::CONVERT => {
+            let typ = match operand.strip_prefix("0x") {
+                Some(hex) => u8::from_str_radix(hex, 16).ok(),
+                None => (0..=u8::MAX)
+                    .filter_map(StackItemType::from_u8)
+                    .find(|typ| format!("{:?}", typ) == operand)
+                    .map(|typ| typ as u8),
+            };
+            script.push(typ.ok_or_else(|| format!("invalid type {}", operand))?);
+        }
+        OpCode::INITSLOT => {
+            for count in split_operands(operand, 2)? {
+                script.push(count.parse().map_err(|_| format!("invalid count {}", count))?);
+            }
+        }
+        OpCode::CALLT => {
+            let token: u16 = operand.parse().map_err(|_| format!("invalid token {}", operand))?;
+            script.extend_from_slice(&token.to_le_bytes());
+        }
+        _ if size == 0 => {
+            if !operand.is_empty() {
+                return Err(format!("{} has no operand", opcode.name().unwrap()));
+            }
+        }
+        _ => script.push(operand.parse().map_err(|_| format!("invalid index {}", operand))?),
+    }
+    Ok(())
+}
+
+fn split_operands(operand: &str, count: usize) -> Result<Vec<&str>, String> {
+    let operands: Vec<&str> = operand.split(',').map(str::trim).collect();
+    if operands.len() != count || operands.iter().any(|operand| operand.is_empty()) {
+        return Err(format!("expected {} operand(s), found {}", count, operand));
+    }
+    Ok(operands)
+}
+
+fn parse_hex(operand: &str) -> Result<Vec<u8>, String> {
+    let hex = operand
+        .strip_prefix("0x")
+        .filter(|hex| hex.len() % 2 == 0)
+        .ok_or_else(|| format!("invalid data {}", operand))?;
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid data {}", operand)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Disassembler::disassemble;
+
+    #[test]
+    fn test_assemble() {
+        let mut syscalls = HashMap::new();
+        syscalls.insert(0xcfe74796, "System.Runtime.Log".to_string());
+        let text = "
+            ; Logs \"hi\" twice.
+            INITSLOT 1, 0
+            PUSH2
+            STLOC0
+            loop:
+                PUSHDATA1 0x6869
+                SYSCALL System.Runtime.Log
+                LDLOC0
+                DEC
+                DUP
+                STLOC0
+                JMPIF loop ; back to the log
+            end: RET
+        ";
+        let script = assemble(text, &syscalls).unwrap();
+        assert_eq!(
+            script,
+            vec![
+                OpCode::INITSLOT.0,
+                1,
+                0,
+                OpCode::PUSH2.0,
+                OpCode::STLOC0.0,
+                OpCode::PUSHDATA1.0,
+                2,
+                b'h',
+                b'i',
+                OpCode::SYSCALL.0,
+                0x96,
+                0x47,
+                0xe7,
+                0xcf,
+                OpCode::LDLOC0.0,
+                OpCode::DEC.0,
+                OpCode::DUP.0,
+                OpCode::STLOC0.0,
+                OpCode::JMPIF.0,
+                (-13i8) as u8,
+                OpCode::RET.0,
+            ]
+        );
+        assert_eq!(
+            assemble(&disassemble(&script, &syscalls).unwrap(), &syscalls).unwrap(),
+            script
+        );
+        assert_eq!(
+            assemble(&disassemble(&script, &HashMap::new()).unwrap(), &HashMap::new()).unwrap(),
+            script
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let syscalls = HashMap::new();
+        let line = |text: &str| assemble(text, &syscalls).unwrap_err().line;
+        assert_eq!(line("NOP\nFOO"), 2);
+        assert_eq!(line("JMP missing"), 1);
+        assert_eq!(line("a: NOP\na: NOP"), 2);
+        assert_eq!(line("PUSHINT8 128"), 1);
+        assert_eq!(line("NOP\nNOP\nJMP far"), 3);
+        assert_eq!(line("PUSHDATA1 0x123"), 1);
+        assert_eq!(line("SYSCALL System.Runtime.Log"), 1);
+        assert_eq!(line("RET 1"), 1);
+        assert!(assemble(&format!("JMP far\n{}far: RET", "NOP\n".repeat(128)), &syscalls).is_err());
+        assert!(assemble(&format!("JMP_L far\n{}far: RET", "NOP\n".repeat(128)), &syscalls).is_ok());
+    }
+}
@@ -1,123 +1,320 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 
+use crate::ExecutionContext::ExecutionContext;
+use crate::ExecutionEngine::ExecutionEngine;
+use crate::ExecutionHost::ExecutionHost;
+use crate::Slot::Slot;
+use crate::Types::StackItem::StackItem;
+use crate::VMState::VMState;
+
+/// <summary>
+/// A simple debugger for <see cref="ExecutionEngine"/>.
+/// </summary>
+///
+/// Breakpoints are set per script hash <typeparamref name="K"/> and offset. The VM has no hash function, the
+/// debugger gets the hash of a context from the function given to <see cref="Debugger.with_script_hash"/>.
+/// <see cref="Debugger.new"/> uses <c>()</c>, a breakpoint then applies to every script.
+pub struct Debugger<'a, H: ExecutionHost = (), K = ()> {
+    engine: &'a mut ExecutionEngine<H>,
+    script_hash: fn(&ExecutionContext) -> K,
+    break_points: HashMap<K, HashSet<usize>>,
+}
+
+impl<'a, H: ExecutionHost> Debugger<'a, H> {
     /// <summary>
-    /// A simple debugger for <see cref="ExecutionEngine"/>.
+    /// Create a debugger on the specified <see cref="ExecutionEngine"/>, whose breakpoints apply to every script.
     /// </summary>
-    public class Debugger
-    {
-        private readonly ExecutionEngine engine;
-        private readonly Dictionary<Script, HashSet<uint>> break_points = new();
+    /// <param name="engine">The <see cref="ExecutionEngine"/> to attach the debugger.</param>
+    pub fn new(engine: &'a mut ExecutionEngine<H>) -> Self {
+        Self::with_script_hash(engine, |_| ())
+    }
+}
 
-        /// <summary>
-        /// Create a debugger on the specified <see cref="ExecutionEngine"/>.
-        /// </summary>
-        /// <param name="engine">The <see cref="ExecutionEngine"/> to attach the debugger.</param>
-        public Debugger(ExecutionEngine engine)
-        {
-            this.engine = engine;
+impl<'a, H: ExecutionHost, K: Eq + Hash> Debugger<'a, H, K> {
+    /// <summary>
+    /// Create a debugger on the specified <see cref="ExecutionEngine"/>.
+    /// </summary>
+    /// <param name="engine">The <see cref="ExecutionEngine"/> to attach the debugger.</param>
+    /// <param name="script_hash">Gives the hash of the script running in a context.</param>
+    pub fn with_script_hash(engine: &'a mut ExecutionEngine<H>, script_hash: fn(&ExecutionContext) -> K) -> Self {
+        Self {
+            engine,
+            script_hash,
+            break_points: HashMap::new(),
         }
+    }
 
-        /// <summary>
-        /// Add a breakpoint at the specified position of the specified script. The VM will break the execution when it reaches the breakpoint.
-        /// </summary>
-        /// <param name="script">The script to add the breakpoint.</param>
-        /// <param name="position">The position of the breakpoint in the script.</param>
-        public void AddBreakPoint(Script script, uint position)
-        {
-            if (!break_points.TryGetValue(script, out HashSet<uint>? hashset))
-            {
-                hashset = new HashSet<uint>();
-                break_points.Add(script, hashset);
-            }
-            hashset.Add(position);
+    /// <summary>
+    /// The engine being debugged.
+    /// </summary>
+    pub fn engine(&self) -> &ExecutionEngine<H> {
+        self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut ExecutionEngine<H> {
+        self.engine
+    }
+
+    /// <summary>
+    /// The breakpoints, as offsets by script hash.
+    /// </summary>
+    pub fn break_points(&self) -> &HashMap<K, HashSet<usize>> {
+        &self.break_points
+    }
+
+    /// <summary>
+    /// Add a breakpoint at the specified position of the specified script. The VM will break the execution when it reaches the breakpoint.
+    /// </summary>
+    /// <param name="script_hash">The hash of the script to add the breakpoint.</param>
+    /// <param name="position">The position of the breakpoint in the script.</param>
+    pub fn add_break_point(&mut self, script_hash: K, position: usize) {
+        self.break_points.entry(script_hash).or_default().insert(position);
+    }
+
+    /// <summary>
+    /// Removes the breakpoint at the specified position in the specified script.
+    /// </summary>
+    /// <param name="script_hash">The hash of the script to remove the breakpoint.</param>
+    /// <param name="position">The position of the breakpoint in the script.</param>
+    /// <returns>
+    /// <see langword="true"/> if the breakpoint is successfully found and removed;
+    /// otherwise, <see langword="false"/>.
+    /// </returns>
+    pub fn remove_break_point(&mut self, script_hash: &K, position: usize) -> bool {
+        let positions = match self.break_points.get_mut(script_hash) {
+            Some(positions) => positions,
+            None => return false,
+        };
+        if !positions.remove(&position) {
+            return false;
+        }
+        if positions.is_empty() {
+            self.break_points.remove(script_hash);
         }
+        true
+    }
 
-        /// <summary>
-        /// Start or continue execution of the VM.
-        /// </summary>
-        /// <returns>Returns the state of the VM after the execution.</returns>
-        public VMState Execute()
-        {
-            if (engine.State == VMState.BREAK)
-                engine.State = VMState.NONE;
-            while (engine.State == VMState.NONE)
-                ExecuteAndCheckBreakPoints();
-            return engine.State;
+    /// <summary>
+    /// Start or continue execution of the VM.
+    /// </summary>
+    /// <returns>Returns the state of the VM after the execution.</returns>
+    pub fn execute(&mut self) -> VMState {
+        if self.engine.state() == VMState::BREAK {
+            self.engine.set_state(VMState::NONE);
+        }
+        while self.engine.state() == VMState::NONE {
+            self.execute_and_check_break_points();
         }
+        self.engine.state()
+    }
 
-        private void ExecuteAndCheckBreakPoints()
-        {
-            engine.ExecuteNext();
-            if (engine.State == VMState.NONE && engine.InvocationStack.Count > 0 && break_points.Count > 0)
-            {
-                if (break_points.TryGetValue(engine.CurrentContext!.Script, out HashSet<uint>? hashset) && hashset.Contains((uint)engine.CurrentContext.InstructionPointer))
-                    engine.State = VMState.BREAK;
+    fn execute_and_check_break_points(&mut self) {
+        self.engine.execute_next();
+        if self.engine.state() != VMState::NONE || self.break_points.is_empty() {
+            return;
+        }
+        if let Some(context) = self.engine.current_context() {
+            let hit = self
+                .break_points
+                .get(&(self.script_hash)(context))
+                .is_some_and(|positions| positions.contains(&context.instruction_pointer()));
+            if hit {
+                self.engine.set_state(VMState::BREAK);
             }
         }
+    }
 
-        /// <summary>
-        /// Removes the breakpoint at the specified position in the specified script.
-        /// </summary>
-        /// <param name="script">The script to remove the breakpoint.</param>
-        /// <param name="position">The position of the breakpoint in the script.</param>
-        /// <returns>
-        /// <see langword="true"/> if the breakpoint is successfully found and removed;
-        /// otherwise, <see langword="false"/>.
-        /// </returns>
-        public bool RemoveBreakPoint(Script script, uint position)
-        {
-            if (!break_points.TryGetValue(script, out HashSet<uint>? hashset)) return false;
-            if (!hashset.Remove(position)) return false;
-            if (hashset.Count == 0) break_points.Remove(script);
-            return true;
+    /// <summary>
+    /// Execute the next instruction. If the instruction involves a call to a method, it steps into the method and breaks the execution on the first instruction of that method.
+    /// </summary>
+    /// <returns>The VM state after the instruction is executed.</returns>
+    pub fn step_into(&mut self) -> VMState {
+        self.engine.step()
+    }
+
+    /// <summary>
+    /// Execute until the currently executed method is returned.
+    /// </summary>
+    /// <returns>The VM state after the currently executed method is returned.</returns>
+    pub fn step_out(&mut self) -> VMState {
+        if self.engine.state() == VMState::BREAK {
+            self.engine.set_state(VMState::NONE);
+        }
+        let depth = self.engine.invocation_stack().len();
+        while self.engine.state() == VMState::NONE && self.engine.invocation_stack().len() >= depth {
+            self.execute_and_check_break_points();
         }
+        if self.engine.state() == VMState::NONE {
+            self.engine.set_state(VMState::BREAK);
+        }
+        self.engine.state()
+    }
 
-        /// <summary>
-        /// Execute the next instruction. If the instruction involves a call to a method, it steps into the method and breaks the execution on the first instruction of that method.
-        /// </summary>
-        /// <returns>The VM state after the instruction is executed.</returns>
-        public VMState StepInto()
-        {
-            if (engine.State == VMState.HALT || engine.State == VMState.FAULT)
-                return engine.State;
-            engine.ExecuteNext();
-            if (engine.State == VMState.NONE)
-                engine.State = VMState.BREAK;
-            return engine.State;
+    /// <summary>
+    /// Execute the next instruction. If the instruction involves a call to a method, it does not step into the method (it steps over it instead).
+    /// </summary>
+    /// <returns>The VM state after the instruction is executed.</returns>
+    pub fn step_over(&mut self) -> VMState {
+        if self.engine.state() == VMState::HALT || self.engine.state() == VMState::FAULT {
+            return self.engine.state();
         }
+        self.engine.set_state(VMState::NONE);
+        let depth = self.engine.invocation_stack().len();
+        loop {
+            self.execute_and_check_break_points();
+            if self.engine.state() != VMState::NONE || self.engine.invocation_stack().len() <= depth {
+                break;
+            }
+        }
+        if self.engine.state() == VMState::NONE {
+            self.engine.set_state(VMState::BREAK);
+        }
+        self.engine.state()
+    }
+
+    /// <summary>
+    /// The context at <paramref name="depth"/> in the invocation stack, 0 is the current context.
+    /// </summary>
+    pub fn context(&self, depth: usize) -> Option<&ExecutionContext> {
+        let invocation_stack = self.engine.invocation_stack();
+        invocation_stack.iter().rev().nth(depth)
+    }
+
+    /// <summary>
+    /// The hash of the script running in the context at <paramref name="depth"/>.
+    /// </summary>
+    pub fn script_hash(&self, depth: usize) -> Option<K> {
+        self.context(depth).map(self.script_hash)
+    }
+
+    /// <summary>
+    /// The items of the evaluation stack of the context at <paramref name="depth"/>, from the top.
+    /// </summary>
+    pub fn evaluation_stack(&self, depth: usize) -> Option<Vec<StackItem>> {
+        self.context(depth)
+            .map(|context| context.evaluation_stack().iter().rev().cloned().collect())
+    }
+
+    /// <summary>
+    /// The local variables of the context at <paramref name="depth"/>, <c>None</c> when it has no slot.
+    /// </summary>
+    pub fn local_variables(&self, depth: usize) -> Option<Vec<StackItem>> {
+        self.context(depth)?.local_variables().as_ref().map(slot_items)
+    }
+
+    /// <summary>
+    /// The arguments of the context at <paramref name="depth"/>, <c>None</c> when it has no slot.
+    /// </summary>
+    pub fn arguments(&self, depth: usize) -> Option<Vec<StackItem>> {
+        self.context(depth)?.arguments().as_ref().map(slot_items)
+    }
+
+    /// <summary>
+    /// The static fields of the script of the context at <paramref name="depth"/>, <c>None</c> when it has no slot.
+    /// </summary>
+    pub fn static_fields(&self, depth: usize) -> Option<Vec<StackItem>> {
+        self.context(depth)?.static_fields().as_ref().map(slot_items)
+    }
+}
 
-        /// <summary>
-        /// Execute until the currently executed method is returned.
-        /// </summary>
-        /// <returns>The VM state after the currently executed method is returned.</returns>
-        public VMState StepOut()
-        {
-            if (engine.State == VMState.BREAK)
-                engine.State = VMState.NONE;
-            int c = engine.InvocationStack.Count;
-            while (engine.State == VMState.NONE && engine.InvocationStack.Count >= c)
-                ExecuteAndCheckBreakPoints();
-            if (engine.State == VMState.NONE)
-                engine.State = VMState.BREAK;
-            return engine.State;
+fn slot_items(slot: &Slot) -> Vec<StackItem> {
+    slot.iter().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExecutionEngineLimits::ExecutionEngineLimits;
+    use crate::OpCode::OpCode;
+    use crate::Script::Script;
+    use crate::ScriptBuilder::ScriptBuilder;
+
+    /// Stores 1 in a static field, calls with 2 a method storing its argument in a local, then pushes 3.
+    fn script() -> Vec<u8> {
+        let mut sb = ScriptBuilder::new();
+        sb.emit(OpCode::INITSSLOT, &[1])
+            .emit(OpCode::PUSH1, &[])
+            .emit(OpCode::STSFLD0, &[])
+            .emit(OpCode::PUSH2, &[])
+            .emit_call(4)
+            .emit(OpCode::PUSH3, &[])
+            .emit(OpCode::RET, &[])
+            .emit(OpCode::INITSLOT, &[1, 1])
+            .emit(OpCode::LDARG0, &[])
+            .emit(OpCode::STLOC0, &[])
+            .emit(OpCode::LDLOC0, &[])
+            .emit(OpCode::RET, &[]);
+        sb.to_array()
+    }
+
+    fn engine() -> ExecutionEngine {
+        let mut engine = ExecutionEngine::new(ExecutionEngineLimits::default());
+        engine.load_script(Script::from(script()), -1, 0).unwrap();
+        engine
+    }
+
+    #[test]
+    fn test_step() {
+        let mut engine = engine();
+        let mut debugger = Debugger::new(&mut engine);
+        for _ in 0..4 {
+            assert_eq!(debugger.step_into(), VMState::BREAK);
         }
+        assert_eq!(debugger.context(0).unwrap().instruction_pointer(), 5);
+        assert_eq!(debugger.step_into(), VMState::BREAK);
+        assert_eq!(debugger.engine().invocation_stack().len(), 2);
+        assert_eq!(debugger.context(0).unwrap().instruction_pointer(), 9);
+        assert_eq!(debugger.static_fields(0).unwrap()[0].integer().unwrap(), 1.into());
 
-        /// <summary>
-        /// Execute the next instruction. If the instruction involves a call to a method, it does not step into the method (it steps over it instead).
-        /// </summary>
-        /// <returns>The VM state after the instruction is executed.</returns>
-        public VMState StepOver()
-        {
-            if (engine.State == VMState.HALT || engine.State == VMState.FAULT)
-                return engine.State;
-            engine.State = VMState.NONE;
-            int c = engine.InvocationStack.Count;
-            do
-            {
-                ExecuteAndCheckBreakPoints();
-            }
-            while (engine.State == VMState.NONE && engine.InvocationStack.Count > c);
-            if (engine.State == VMState.NONE)
-                engine.State = VMState.BREAK;
-            return engine.State;
+        debugger.step_into();
+        debugger.step_into();
+        debugger.step_into();
+        assert_eq!(debugger.arguments(0).unwrap()[0].integer().unwrap(), 2.into());
+        assert_eq!(debugger.local_variables(0).unwrap()[0].integer().unwrap(), 2.into());
+        assert!(debugger.local_variables(1).is_none());
+
+        assert_eq!(debugger.step_out(), VMState::BREAK);
+        assert_eq!(debugger.engine().invocation_stack().len(), 1);
+        assert_eq!(debugger.context(0).unwrap().instruction_pointer(), 7);
+        assert_eq!(debugger.evaluation_stack(0).unwrap()[0].integer().unwrap(), 2.into());
+
+        assert_eq!(debugger.execute(), VMState::HALT);
+        assert_eq!(debugger.step_over(), VMState::HALT);
+        assert_eq!(engine.result_stack().count(), 2);
+    }
+
+    #[test]
+    fn test_step_over() {
+        let mut engine = engine();
+        let mut debugger = Debugger::new(&mut engine);
+        for _ in 0..4 {
+            debugger.step_over();
         }
+        assert_eq!(debugger.step_over(), VMState::BREAK);
+        assert_eq!(debugger.engine().invocation_stack().len(), 1);
+        assert_eq!(debugger.context(0).unwrap().instruction_pointer(), 7);
+    }
+
+    #[test]
+    fn test_break_points() {
+        let mut engine = engine();
+        let mut debugger = Debugger::with_script_hash(&mut engine, |context| context.script().length());
+        let length = script().len();
+        debugger.add_break_point(length, 13);
+        debugger.add_break_point(length, 8);
+        debugger.add_break_point(length + 1, 9);
+
+        assert_eq!(debugger.execute(), VMState::BREAK);
+        assert_eq!(debugger.context(0).unwrap().instruction_pointer(), 13);
+        assert_eq!(debugger.script_hash(1), Some(length));
+        assert_eq!(debugger.execute(), VMState::BREAK);
+        assert_eq!(debugger.context(0).unwrap().instruction_pointer(), 8);
+
+        assert!(debugger.remove_break_point(&length, 13));
+        assert!(!debugger.remove_break_point(&length, 13));
+        assert!(!debugger.remove_break_point(&(length + 2), 9));
+        assert_eq!(debugger.break_points().len(), 2);
+        assert_eq!(debugger.execute(), VMState::HALT);
     }
+}
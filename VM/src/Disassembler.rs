@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use num::BigInt;
+
+use crate::Instruction::Instruction;
+use crate::OpCode::OpCode;
+use crate::Types::StackItemType::StackItemType;
+use crate::VmError::VmError;
+
+/// <summary>
+/// Converts a script to text, one instruction per line, in the format read by <see cref="Assembler.assemble"/>.
+/// </summary>
+/// <param name="script">The script to disassemble.</param>
+/// <param name="syscalls">The names of the interop services by hash, unknown ones are shown as their hash.</param>
+/// <returns>The text of the script.</returns>
+/// <exception cref="VmError::BadScript">An instruction of the script can't be decoded.</exception>
+pub fn disassemble(script: &[u8], syscalls: &HashMap<u32, String>) -> Result<String, VmError> {
+    let mut text = String::new();
+    let mut ip = 0;
+    while ip < script.len() {
+        let instruction = Instruction::from_script(script, ip)?;
+        text.push_str(&format_instruction(&instruction, ip, syscalls));
+        text.push('\n');
+        ip += instruction.size();
+    }
+    Ok(text)
+}
+
+/// <summary>
+/// Formats the instruction at <paramref name="offset"/>: the offset, the opcode and the decoded operand.
+/// </summary>
+/// <remarks>
+/// Jump, call and try targets are absolute offsets, <c>SYSCALL</c> shows the interop name and
+/// <c>PUSHDATA</c> the hex of the data, followed by a comment with the string when it is printable UTF-8.
+/// </remarks>
+pub fn format_instruction(instruction: &Instruction, offset: usize, syscalls: &HashMap<u32, String>) -> String {
+    let name = instruction.opcode().name().unwrap_or("?");
+    let operand = format_operand(instruction, offset, syscalls);
+    if operand.is_empty() {
+        format!("{:>5}  {}", offset, name)
+    } else {
+        format!("{:>5}  {:<10} {}", offset, name, operand)
+    }
+}
+
+fn format_operand(instruction: &Instruction, offset: usize, syscalls: &HashMap<u32, String>) -> String {
+    let opcode = instruction.opcode();
+    let operand = instruction.operand();
+    if opcode.operand_size_prefix() > 0 {
+        return format_data(operand);
+    }
+    if opcode >= OpCode::PUSHINT8 && opcode <= OpCode::PUSHINT256 {
+        return BigInt::from_signed_bytes_le(operand).to_string();
+    }
+    if let Some(width) = opcode.operand_size().checked_div(opcode.offset_count()) {
+        return operand
+            .chunks(width)
+            .map(|relative| {
+                let relative = match *relative {
+                    [relative] => relative as i8 as i64,
+                    [b0, b1, b2, b3] => i32::from_le_bytes([b0, b1, b2, b3]) as i64,
+                    _ => unreachable!(),
+                };
+                (offset as i64 + relative).to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+    }
+    match opcode {
+        OpCode::SYSCALL => {
+            let hash = instruction.token_u32();
+            match syscalls.get(&hash) {
+                Some(name) => name.clone(),
+                None => format!("0x{:08x}", hash),
+            }
+        }
+        OpCode::NEWARRAY_T | OpCode::ISTYPE | OpCode::CONVERT => match StackItemType::from_u8(operand[0]) {
+            Some(typ) => format!("{:?}", typ),
+            None => format!("0x{:02x}", operand[0]),
+        },
+        OpCode::INITSLOT => format!("{}, {}", operand[0], operand[1]),
+        OpCode::CALLT => instruction.token_u16().to_string(),
+        _ if operand.is_empty() => String::new(),
+        _ => instruction.token_u8().to_string(),
+    }
+}
+
+fn format_data(data: &[u8]) -> String {
+    let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+    match std::str::from_utf8(data) {
+        Ok(text) if !text.is_empty() && !text.chars().any(char::is_control) => format!("0x{} ; {:?}", hex, text),
+        _ => format!("0x{}", hex),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptBuilder::ScriptBuilder;
+
+    #[test]
+    fn test_disassemble() {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push_data(b"hello")
+            .emit_syscall(0xcfe74796)
+            .emit_syscall(0x01020304)
+            .emit_push(&BigInt::from(-1000))
+            .emit_jump(OpCode::JMPIF, -11)
+            .emit(OpCode::TRY, &[4, 0])
+            .emit(OpCode::INITSLOT, &[2, 1])
+            .emit(OpCode::CONVERT, &[StackItemType::Integer as u8])
+            .emit_push_data(&[0, 1])
+            .emit(OpCode::RET, &[]);
+        let mut syscalls = HashMap::new();
+        syscalls.insert(0xcfe74796, "System.Runtime.Log".to_string());
+
+        let expected = [
+            "    0  PUSHDATA1  0x68656c6c6f ; \"hello\"",
+            "    7  SYSCALL    System.Runtime.Log",
+            "   12  SYSCALL    0x01020304",
+            "   17  PUSHINT16  -1000",
+            "   20  JMPIF      9",
+            "   22  TRY        26, 22",
+            "   25  INITSLOT   2, 1",
+            "   28  CONVERT    Integer",
+            "   30  PUSHDATA1  0x0001",
+            "   34  RET",
+        ];
+        assert_eq!(
+            disassemble(&sb.to_array(), &syscalls).unwrap(),
+            expected.join("\n") + "\n"
+        );
+        assert!(matches!(
+            disassemble(&[OpCode::PUSHDATA1.0, 0x02], &syscalls),
+            Err(VmError::BadScript { ip: 0, .. })
+        ));
+    }
+}
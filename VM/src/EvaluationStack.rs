@@ -1,176 +1,123 @@
-use crate::reference_counter::reference_counter;
-use std::option::Iter;
-use crate::StackItem::StackItem;
-use crate::StackItemType::StackItemType;
-use alloc::vec::Vec;
-use std::any::TypeId;
-use primitive_types::H256;
+use std::rc::Rc;
 
-#[derive(Clone, Debug)]
+use crate::ReferenceCounter::ReferenceCounter;
+use crate::Types::StackItem::StackItem;
+use crate::VmError::VmError;
+
+/// <summary>
+/// Represents the evaluation stack in the VM.
+/// </summary>
+#[derive(Debug)]
 pub struct EvaluationStack {
     inner_list: Vec<StackItem>,
-    reference_counter: reference_counter,
-
+    reference_counter: Rc<ReferenceCounter>,
 }
 
-impl StackItem for EvaluationStack {
-    fn Type() -> StackItemType {
-        todo!()
-    }
-
-    fn ConvertTo(&self, typ: StackItemType) -> Self {
-        todo!()
+impl EvaluationStack {
+    pub fn new(reference_counter: Rc<ReferenceCounter>) -> Self {
+        Self {
+            inner_list: Vec::new(),
+            reference_counter,
+        }
     }
-}
-
-//     /// <summary>
-/// Represents the evaluation stack in the VM.
-/// </summary>
-impl EvaluationStack
-{
-
-// internal EvaluationStack(reference_counter reference_counter)
-// {
-// this.reference_counter = reference_counter;
-// }
 
     /// <summary>
     /// Gets the number of items on the stack.
     /// </summary>
-    pub fn count(&self) -> usize { self.inner_list.len() }
+    pub fn count(&self) -> usize {
+        self.inner_list.len()
+    }
 
-    pub fn clear(&mut self)
-    {
-        for item in self.inner_list {
-            self.reference_counter.RemoveStackReference(item);
+    pub fn clear(&mut self) {
+        for item in self.inner_list.iter() {
+            self.reference_counter.remove_stack_reference(item);
         }
-
         self.inner_list.clear();
     }
 
-    pub fn copy_to(&mut self, stack: &EvaluationStack, mut count: Option<i32>)
-    {
-        if count.is_none() { count = Some(-1); }
-        if count.unwrap() < -1 || count.unwrap() > self.inner_list.len() as i32 { panic!(); }
-        if count.unwrap() == 0 { return; }
-        if count.unwrap() == -1 || count.unwrap() == self.inner_list.len()
-        { stack.inner_list.AddRange(&self.inner_list); } else { stack.inner_list.AddRange(&self.inner_list.Skip(self.inner_list.len() - count)); }
+    /// <summary>
+    /// Moves the top <paramref name="count"/> items, all of them when <see langword="None"/>, onto another stack.
+    /// Both stacks share the reference counter so the count is unchanged.
+    /// </summary>
+    pub fn move_to(&mut self, stack: &mut EvaluationStack, count: Option<usize>) -> Result<(), VmError> {
+        let count = count.unwrap_or(self.inner_list.len());
+        self.check_count(count)?;
+        let start = self.inner_list.len() - count;
+        stack.inner_list.extend(self.inner_list.drain(start..));
+        Ok(())
     }
 
-    pub fn enumerator(&self) -> std::slice::Iter<'_, _>
-    {
+    /// <summary>
+    /// Iterates from the bottom to the top of the stack.
+    /// </summary>
+    pub fn iter(&self) -> std::slice::Iter<'_, StackItem> {
         self.inner_list.iter()
     }
 
-    // IEnumerator IEnumerable.GetEnumerator()
-    // {
-    // return inner_list.GetEnumerator();
-    // }
-
-    #[inline]
-    pub fn insert(&mut self, index: i32, item: &StackItem)
-    {
-        if index > self.inner_list.len() as i32 { panic!(); }
-
+    /// <summary>
+    /// Inserts an item at the specified index from the top of the stack.
+    /// </summary>
+    pub fn insert(&mut self, index: usize, item: StackItem) -> Result<(), VmError> {
+        self.check_count(index)?;
+        self.reference_counter.add_stack_reference(&item, 1);
         self.inner_list.insert(self.inner_list.len() - index, item);
-        self.reference_counter.add_stack_reference(item);
-    }
-
-    pub fn moveTo(&self, stack: &EvaluationStack, mut count: Option<i32>)
-    {
-        if count.is_none() {
-            count = Some(-1);
-        }
-        if count.unwrap() == 0 { return; }
-
-        CopyTo(stack, count);
-
-        if count.unwrap() == -1 || count.unwrap() == self.inner_list.len() as i32
-        {
-            inner_list.Clear();
-        } else {
-            self.inner_list.remove_range(inner_list.Count - count, count);
-        }
+        Ok(())
     }
 
     /// <summary>
     /// Returns the item at the specified index from the top of the stack without removing it.
     /// </summary>
     /// <param name="index">The index of the object from the top of the stack.</param>
-    /// <returns>The item at the specified index.</returns>
-    pub fn peek(&mut self, mut index: Option<i32>) -> &StackItem
-    {
-        if index.is_none() {
-            index = Some(0);
-        }
-        let mut id = index.unwrap();
-
-        if id >= self.inner_list.len() { panic!(); }
-        if id < 0
-        {
-            id += self.inner_list.len();
-            if id < 0 { panic!(); }
-        }
-        self.inner_list[self.inner_list.len() - id - 1]
+    pub fn peek(&self, index: usize) -> Result<StackItem, VmError> {
+        self.check_count(index + 1)?;
+        Ok(self.inner_list[self.inner_list.len() - index - 1].clone())
     }
 
-    // StackItem IReadOnlyList < StackItem >.this[int index] => Peek(index);
-
     /// <summary>
     /// Pushes an item onto the top of the stack.
     /// </summary>
-    /// <param name="item">The item to be pushed.</param>
-    pub fn push(&mut self, item: Box<StackItem>)
-    {
+    pub fn push(&mut self, item: StackItem) {
+        self.reference_counter.add_stack_reference(&item, 1);
         self.inner_list.push(item);
-        self.reference_counter.add_stack_reference(item);
     }
 
-    pub fn reverse(&mut self, n: i32)
-    {
-        if n < 0 || n > self.inner_list.len() as i32 { panic!(); }
-        if n <= 1 {
-            return;
-        }
-        self.inner_list.reverse(self.inner_list.len() - n, n);
+    /// <summary>
+    /// Reverses the order of the top <paramref name="n"/> items.
+    /// </summary>
+    pub fn reverse(&mut self, n: usize) -> Result<(), VmError> {
+        self.check_count(n)?;
+        let start = self.inner_list.len() - n;
+        self.inner_list[start..].reverse();
+        Ok(())
     }
 
     /// <summary>
     /// Removes and returns the item at the top of the stack.
     /// </summary>
-    /// <returns>The item removed from the top of the stack.</returns>
-    // [MethodImpl(MethodImplOptions.AggressiveInlining)]
-    // pub fn Pop() -> &StackItem
-    // {
-    //     Remove < StackItem > (0)
-    // }
+    pub fn pop(&mut self) -> Result<StackItem, VmError> {
+        self.remove(0)
+    }
 
     /// <summary>
-    /// Removes and returns the item at the top of the stack and convert it to the specified type.
+    /// Removes and returns the item at the specified index from the top of the stack.
     /// </summary>
-    /// <typeparam name="T">The type to convert to.</typeparam>
-    /// <returns>The item removed from the top of the stack.</returns>
-    pub fn pop<T>(&mut self) -> T
-        where T: StackItem
-    {
-        self.remove::<T>(0)
+    pub fn remove(&mut self, index: usize) -> Result<StackItem, VmError> {
+        self.check_count(index + 1)?;
+        let item = self.inner_list.remove(self.inner_list.len() - index - 1);
+        self.reference_counter.remove_stack_reference(&item);
+        Ok(item)
     }
 
-    pub fn remove<T>(&mut self, mut index: i32) -> T
-        where T: StackItem
-    {
-        if index >= self.inner_list.len() as i32 { panic!(); }
-        if index < 0
-        {
-            index += self.inner_list.len();
-            if index < 0 { panic!(); }
+    /// <summary>
+    /// Fails with <see cref="VmError::StackUnderflow"/> when the stack has less than <paramref name="count"/> items.
+    /// </summary>
+    fn check_count(&self, count: usize) -> Result<(), VmError> {
+        if count > self.inner_list.len() {
+            return Err(VmError::StackUnderflow {
+                index: count - 1,
+                count: self.inner_list.len(),
+            });
         }
-        index = self.inner_list.len() as i32 - index - 1;
-        // if (inner_list[index] is not T item)
-        // throw new InvalidCastException( $ "The item can't be casted to type {typeof(T)}");
-        // TypeId::of::<T>() == TypeId::of::<String>()
-        self.inner_list.remove_at(index);
-        self.reference_counter.remove_stack_reference(item);
-        item
+        Ok(())
     }
 }
@@ -1,59 +1,58 @@
+use getset::{CopyGetters, Setters};
+
 use crate::ExceptionHandlingState::ExceptionHandlingState;
-use getset::{CopyGetters, Getters, MutGetters, Setters};
 
-#[derive(Getters, Setters, MutGetters, CopyGetters,Debug, Clone, Default, Eq, PartialEq)]
+/// <summary>
+/// Represents the context used for exception handling.
+/// </summary>
+#[derive(CopyGetters, Setters, Debug, Clone, Eq, PartialEq)]
 pub struct ExceptionHandlingContext {
     /// <summary>
     /// The position of the <see langword="catch"/> block.
     /// </summary>
-    #[getset(get = "pub", set)]
-    CatchPointer: i32,// { get; }
+    #[getset(get_copy = "pub")]
+    catch_pointer: Option<usize>,
 
     /// <summary>
     /// The position of the <see langword="finally"/> block.
     /// </summary>
-    #[getset(get = "pub", set, get_mut = "pub")]
-    FinallyPointer: i32,//  { get; }
+    #[getset(get_copy = "pub")]
+    finally_pointer: Option<usize>,
 
     /// <summary>
     /// The end position of the <see langword="try"/>-<see langword="catch"/>-<see langword="finally"/> block.
     /// </summary>
-    #[getset(get = "pub", set, get_mut = "pub")]
-    EndPointer: i32,//  { get; internal set; } = -1;
+    #[getset(get_copy = "pub", set = "pub(crate)")]
+    end_pointer: Option<usize>,
 
     /// <summary>
-    /// Indicates whether the <see langword="catch"/> block is included in the context.
+    /// Indicates the state of the context.
     /// </summary>
-    #[getset(get = "pub", set, get_mut = "pub")]
-    HasCatch: bool,// => CatchPointer >= 0;
+    #[getset(get_copy = "pub", set = "pub(crate)")]
+    state: ExceptionHandlingState,
+}
+
+impl ExceptionHandlingContext {
+    pub fn new(catch_pointer: Option<usize>, finally_pointer: Option<usize>) -> Self {
+        Self {
+            catch_pointer,
+            finally_pointer,
+            end_pointer: None,
+            state: ExceptionHandlingState::Try,
+        }
+    }
 
     /// <summary>
-    /// Indicates whether the <see langword="finally"/> block is included in the context.
+    /// Indicates whether the <see langword="catch"/> block is included in the context.
     /// </summary>
-    #[getset(get = "pub", set, get_mut = "pub")]
-    HasFinally: bool,// => FinallyPointer >= 0;
+    pub fn has_catch(&self) -> bool {
+        self.catch_pointer.is_some()
+    }
 
     /// <summary>
-    /// Indicates the state of the context.
+    /// Indicates whether the <see langword="finally"/> block is included in the context.
     /// </summary>
-    #[getset(get = "pub", set, get_mut = "pub")]
-    State: ExceptionHandlingState,// { get; internal set; } = ExceptionHandlingState.Try;
-}
-
-
-/// <summary>
-/// Represents the context used for exception handling.
-/// </summary>
-impl Default for ExceptionHandlingContext
-{
-    fn default() -> Self {
-        Self {
-            CatchPointer: 0,
-            FinallyPointer: 0,
-            EndPointer: -1,
-            HasCatch: CatchPointer >= 0,
-            HasFinally: FinallyPointer >= 0,
-            State: ExceptionHandlingState.Try,
-        }
+    pub fn has_finally(&self) -> bool {
+        self.finally_pointer.is_some()
     }
 }
@@ -1,8 +1,8 @@
 /// <summary>
 /// Indicates the state of the <see cref="ExceptionHandlingContext"/>.
 /// </summary>
-pub enum ExceptionHandlingState
-{
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ExceptionHandlingState {
     /// <summary>
     /// Indicates that the <see langword="try"/> block is being executed.
     /// </summary>
@@ -17,4 +17,4 @@ pub enum ExceptionHandlingState
     /// Indicates that the <see langword="finally"/> block is being executed.
     /// </summary>
     Finally,
-}
\ No newline at end of file
+}
@@ -1,5 +1,9 @@
-use std::any::Any;
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
 use std::collections::HashMap;
+use std::rc::Rc;
+
+use getset::{CopyGetters, Getters, MutGetters};
 
 use crate::EvaluationStack::EvaluationStack;
 use crate::ExceptionHandlingContext::ExceptionHandlingContext;
@@ -7,189 +11,176 @@ use crate::Instruction::Instruction;
 use crate::ReferenceCounter::ReferenceCounter;
 use crate::Script::Script;
 use crate::Slot::Slot;
-use getset::{CopyGetters, Getters, MutGetters, Setters};
+use crate::VmError::VmError;
 
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
+/// The parts of a context shared with the contexts cloned from it by <see cref="OpCode.CALL"/>.
+#[derive(Debug)]
 struct SharedStates {
-    script: Script,
-    evaluation_stack: EvaluationStack,
-    static_fields: Option<Slot>,
-    states: HashMap<Type, dyn Any>,
+    script: Rc<Script>,
+    evaluation_stack: RefCell<EvaluationStack>,
+    static_fields: RefCell<Option<Slot>>,
+    states: RefCell<HashMap<TypeId, Box<dyn Any>>>,
 }
 
-impl SharedStates
-{
-    pub fn new( script:&Script,  referenceCounter:&ReferenceCounter)->Self
-    {
-        Self{
-            script: script.clone(),
-            evaluation_stack,
-            static_fields: None,
-            states: Dictionary<Type,object>()
+impl SharedStates {
+    fn new(script: Rc<Script>, reference_counter: Rc<ReferenceCounter>) -> Self {
+        Self {
+            script,
+            evaluation_stack: RefCell::new(EvaluationStack::new(reference_counter)),
+            static_fields: RefCell::new(None),
+            states: RefCell::new(HashMap::new()),
         }
     }
 }
 
-#[derive(Getters, Setters, MutGetters, CopyGetters, Default)]
+/// <summary>
+/// Represents a frame in the VM execution stack.
+/// </summary>
+#[derive(Getters, MutGetters, CopyGetters, Debug)]
 pub struct ExecutionContext {
-    shared_states: SharedStates,
+    shared_states: Rc<SharedStates>,
 
-    #[getset(get = "pub", set)]
-    instruction_pointer: i32,
+    /// <summary>
+    /// The pointer indicating the current instruction.
+    /// </summary>
+    #[getset(get_copy = "pub")]
+    instruction_pointer: usize,
 
     /// <summary>
     /// Indicates the number of values that the context should return when it is unloaded.
     /// </summary>
-    #[getset(get_copy = "pub", set)]
-    rv_count:i32,
+    #[getset(get_copy = "pub")]
+    rv_count: i32,
 
     /// <summary>
     /// The slot used to store the local variables of the current method.
     /// </summary>
-    #[getset(get_mut = "pub", set)]
+    #[getset(get = "pub", get_mut = "pub(crate)")]
     local_variables: Option<Slot>,
 
     /// <summary>
     /// The slot used to store the arguments of the current method.
     /// </summary>
-    #[getset(get_copy = "pub", set, get_mut = "pub")]
+    #[getset(get = "pub", get_mut = "pub(crate)")]
     arguments: Option<Slot>,
 
     /// <summary>
     /// The stack containing nested <see cref="ExceptionHandlingContext"/>.
     /// </summary>
-    // #[getset(get_copy = "pub", set = "pub", get_mut = "pub")]
-    #[getset(get = "pub", set)]
-    try_stack: Option<Stack<ExceptionHandlingContext>>,
+    #[getset(get = "pub", get_mut = "pub(crate)")]
+    try_stack: Option<Vec<ExceptionHandlingContext>>,
 }
 
 impl ExecutionContext {
-
-    pub fn new(script: &Script, rvcount: i32, referenceCounter: &ReferenceCounter) ->Self{
-        Self{
-            shared_states: SharedStates::new(script, referenceCounter),
+    pub(crate) fn new(script: Rc<Script>, rv_count: i32, reference_counter: Rc<ReferenceCounter>) -> Self {
+        Self {
+            shared_states: Rc::new(SharedStates::new(script, reference_counter)),
             instruction_pointer: 0,
-            rv_count: rvcount,
+            rv_count,
             local_variables: None,
             arguments: None,
             try_stack: None,
         }
     }
-    fn from_shared_states( shared_states:&SharedStates, rvcount:i32, initialPosition:i32) ->Self{
-        Self{
-            shared_states: shared_states.clone(),
-            instruction_pointer: initialPosition,
-            rv_count: rvcount,
-            local_variables: None,
-            arguments: None,
-            try_stack: None,
-        }
-    }
-
 
     /// <summary>
     /// The script to run in this context.
     /// </summary>
-    pub fn script(&self) ->&Script{
+    pub fn script(&self) -> &Rc<Script> {
         &self.shared_states.script
     }
 
-    pub fn script_mut(&mut self) ->&mut Script{
-        &mut self.shared_states.script
-    }
-
     /// <summary>
     /// The evaluation stack for this context.
     /// </summary>
-    pub fn evaluation_stack(&mut self)->&EvaluationStack{
-        &mut self.shared_states.EvaluationStack
+    pub fn evaluation_stack(&self) -> Ref<'_, EvaluationStack> {
+        self.shared_states.evaluation_stack.borrow()
+    }
+
+    pub fn evaluation_stack_mut(&self) -> RefMut<'_, EvaluationStack> {
+        self.shared_states.evaluation_stack.borrow_mut()
     }
 
     /// <summary>
     /// The slot used to store the static fields.
     /// </summary>
-    pub fn static_fields(&self) -> &Option<Slot> {
-        &self.shared_states.static_fields
+    pub fn static_fields(&self) -> Ref<'_, Option<Slot>> {
+        self.shared_states.static_fields.borrow()
     }
 
-    pub fn static_fields_mut(&mut self)->&mut Option<Slot>{
-        &mut self.shared_states.static_fields
+    pub(crate) fn static_fields_mut(&self) -> RefMut<'_, Option<Slot>> {
+        self.shared_states.static_fields.borrow_mut()
     }
 
-    pub fn set_static_fields(&mut self, slots:Option<Slot>) {
-        self.shared_states.static_fields = slots;
+    pub(crate) fn set_instruction_pointer(&mut self, value: usize) -> Result<(), VmError> {
+        if value > self.script().length() {
+            return Err(VmError::out_of_range(value));
+        }
+        self.instruction_pointer = value;
+        Ok(())
     }
 
-    // pub fn set_instructionPointer(&mut self, instructionPointer: i32) -> &mut ExecutionContext {
-    //     if value < 0 || value > self.Script.Length() { panic!() }
-    //     // throw new ArgumentOutOfRangeException(nameof(value));
-    //     self.instructionPointer = value;
-    //     self
-    // }
+    pub(crate) fn set_local_variables(&mut self, slot: Slot) {
+        self.local_variables = Some(slot);
+    }
+
+    pub(crate) fn set_arguments(&mut self, slot: Slot) {
+        self.arguments = Some(slot);
+    }
 
     /// <summary>
     /// Returns the current <see cref="Instruction"/>.
     /// </summary>
-    pub fn current_instruction(&self) -> Instruction
-    {
-        self.instruction(self.instructionPointer)
+    pub fn current_instruction(&self) -> Result<Rc<Instruction>, VmError> {
+        self.script().instruction(self.instruction_pointer)
     }
 
     /// <summary>
     /// Returns the next <see cref="Instruction"/>.
     /// </summary>
-    pub fn next_instruction(&self) -> Instruction
-    {
-        self.instruction(self.instructionPointer + &self.CurrentInstruction().Size)
+    pub fn next_instruction(&self) -> Result<Rc<Instruction>, VmError> {
+        self.script()
+            .instruction(self.instruction_pointer + self.current_instruction()?.size())
     }
 
-    /// <summary>
-    /// Clones the context so that they share the same script, stack, and static fields.
-    /// </summary>
-    /// <returns>The cloned context.</returns>
-    // pub fn Clone(&self) -> Self
-    // {
-    //     Clone(InstructionPointer);
-    // }
-
     /// <summary>
     /// Clones the context so that they share the same script, stack, and static fields.
     /// </summary>
     /// <param name="initialPosition">The instruction pointer of the new context.</param>
     /// <returns>The cloned context.</returns>
-    pub fn clone(&self, initialPosition: i32) -> Self
-    {
-        Self { shared_states,
-            instruction_pointer: 0,
+    pub fn clone_at(&self, initial_position: usize) -> Self {
+        Self {
+            shared_states: self.shared_states.clone(),
+            instruction_pointer: initial_position,
             rv_count: 0,
             local_variables: None,
             arguments: None,
-            try_stack: None }
+            try_stack: None,
+        }
     }
 
-    fn instruction(&self, ip: i32) -> Instruction { &self.script().instruction(ip) }
+    /// Whether both contexts share the same script, stack, and static fields.
+    pub fn shares_states_with(&self, other: &ExecutionContext) -> bool {
+        Rc::ptr_eq(&self.shared_states, &other.shared_states)
+    }
 
     /// <summary>
     /// Gets custom data of the specified type. If the data does not exist, create a new one.
     /// </summary>
     /// <typeparam name="T">The type of data to be obtained.</typeparam>
     /// <returns>The custom data of the specified type.</returns>
-    pub fn state<T>() -> T
-// where T : class, new()
-    {
-        if !shared_states.States.TryGetValue(typeof(T), out object? value)
-        {
-            value = new T();
-            shared_states.States[typeof(T)] = value;
-        }
-        return (T);
-        value;
+    pub fn state<T: Default + 'static>(&self) -> RefMut<'_, T> {
+        RefMut::map(self.shared_states.states.borrow_mut(), |states| {
+            states
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Box::new(T::default()))
+                .downcast_mut::<T>()
+                .unwrap()
+        })
     }
 
-    fn move_next(&mut self) -> bool
-    {
-        self.instruction_pointer += self.current_instruction.Size;
-         self.instruction_pointer < self.script().Length
+    pub(crate) fn move_next(&mut self) -> Result<(), VmError> {
+        self.instruction_pointer += self.current_instruction()?.size();
+        Ok(())
     }
 }
-
@@ -1,1494 +1,1071 @@
-use core::panicking::panic;
-use std::ptr::null;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use getset::{CopyGetters, Getters, MutGetters};
+use num::Signed;
 
 use crate::EvaluationStack::EvaluationStack;
+use crate::ExceptionHandlingContext::ExceptionHandlingContext;
+use crate::ExceptionHandlingState::ExceptionHandlingState;
 use crate::ExecutionContext::ExecutionContext;
 use crate::ExecutionEngineLimits::ExecutionEngineLimits;
+use crate::ExecutionHost::ExecutionHost;
+use crate::ExecutionObserver::ExecutionObserver;
+use crate::Instruction::Instruction;
 use crate::OpCode::OpCode;
 use crate::ReferenceCounter::ReferenceCounter;
 use crate::Script::Script;
-use crate::VMState::VMState;
-use crate::ExceptionHandlingContext::ExceptionHandlingContext;
-use std::collections::VecDeque;
-use num::BigInt;
-use crate::Pointer::Pointer;
-use crate::StackItem::StackItem;
-use getset::{CopyGetters, Getters, MutGetters, Setters};
 use crate::Slot::Slot;
+use crate::Types::Array::Array;
+use crate::Types::Buffer::Buffer;
+use crate::Types::ByteString::ByteString;
+use crate::Types::Integer::Integer;
+use crate::Types::Map::Map;
+use crate::Types::Pointer::Pointer;
+use crate::Types::StackItem::StackItem;
+use crate::Types::StackItemType::StackItemType;
+use crate::Types::Struct::Struct;
+use crate::VMState::VMState;
+use crate::VmError::{Limit, VmError};
+
+/// The slots an instruction can load from or store to.
+#[derive(Clone, Copy)]
+enum SlotKind {
+    Static,
+    Local,
+    Argument,
+}
 
-#[derive(Getters, Setters, MutGetters, CopyGetters, Default)]
-pub struct ExecutionEngine {
+/// <summary>
+/// Represents the VM used to execute the script.
+/// </summary>
+///
+/// The host <typeparamref name="H"/> handles what the VM leaves to its environment,
+/// such as <see cref="OpCode.SYSCALL"/> and <see cref="OpCode.CALLT"/>.
+#[derive(Getters, CopyGetters, MutGetters)]
+pub struct ExecutionEngine<H: ExecutionHost = ()> {
+    /// <summary>
+    /// Indicates the state of the VM.
+    /// </summary>
+    #[getset(get_copy = "pub")]
     state: VMState,
-    // = VMState.BREAK;
-    #[getset(get="pub", set)]
-    is_jumping: bool,// = false;
+
+    is_jumping: bool,
 
     /// <summary>
     /// Restrictions on the VM.
     /// </summary>
-    #[getset(get_mut = "pub", get="pub", set)]
-    limits: ExecutionEngineLimits, //{get; }
+    #[getset(get = "pub")]
+    limits: ExecutionEngineLimits,
 
     /// <summary>
     /// Used for reference counting of objects in the VM.
     /// </summary>
-    #[getset(get_mut = "pub", get="pub", set)]
-    reference_counter: ReferenceCounter,// { get; }
+    #[getset(get = "pub")]
+    reference_counter: Rc<ReferenceCounter>,
 
     /// <summary>
-    /// The invocation stack of the VM.
+    /// The invocation stack of the VM, the current context is the last one.
     /// </summary>
-    #[getset(get_mut = "pub", get="pub", set)]
-    invocation_stack: VecDeque<ExecutionContext>,
+    #[getset(get = "pub")]
+    invocation_stack: Vec<ExecutionContext>,
 
     /// <summary>
-    /// The top frame of the invocation stack.
+    /// The stack to store the return values.
     /// </summary>
-    #[getset(get_mut = "pub", get="pub", set)]
-    current_context: Option<ExecutionContext>,
+    #[getset(get = "pub")]
+    result_stack: EvaluationStack,
+
     /// <summary>
-    /// The bottom frame of the invocation stack.
+    /// The VM object representing the uncaught exception.
     /// </summary>
-    #[getset(get_mut = "pub", get="pub", set)]
-    entry_context: Option<ExecutionContext>,
+    #[getset(get = "pub")]
+    uncaught_exception: Option<StackItem>,
+
     /// <summary>
-    /// The stack to store the return values.
+    /// The error that faulted the VM, set with <see cref="VMState.FAULT"/>.
     /// </summary>
-    #[getset(get_mut = "pub", get="pub", set)]
-    result_stack: EvaluationStack,
+    #[getset(get = "pub")]
+    fault_exception: Option<VmError>,
 
     /// <summary>
-    /// The VM object representing the uncaught exception.
+    /// The environment the VM runs in.
     /// </summary>
-    #[getset(get_mut = "pub", get="pub", set)]
-    uncaught_exception: Option<StackItem>,// { get; private set; }
+    #[getset(get = "pub", get_mut = "pub")]
+    host: H,
+
+    observer: Option<Box<dyn ExecutionObserver<H>>>,
+}
+
+impl Default for ExecutionEngine {
+    fn default() -> Self {
+        Self::new(ExecutionEngineLimits::default())
+    }
 }
 
 impl ExecutionEngine {
+    /// <summary>
+    /// Initializes a new instance of the <see cref="ExecutionEngine"/> class with the specified <see cref="ExecutionEngineLimits"/>.
+    /// </summary>
+    /// <param name="limits">Restrictions on the VM.</param>
+    pub fn new(limits: ExecutionEngineLimits) -> Self {
+        Self::with_host(limits, ())
+    }
+}
+
+impl<H: ExecutionHost> ExecutionEngine<H> {
+    /// <summary>
+    /// Initializes a new instance of the <see cref="ExecutionEngine"/> class running in the specified host.
+    /// </summary>
+    /// <param name="limits">Restrictions on the VM.</param>
+    /// <param name="host">The environment the VM runs in.</param>
+    pub fn with_host(limits: ExecutionEngineLimits, host: H) -> Self {
+        let reference_counter = Rc::new(ReferenceCounter::new());
+        Self {
+            state: VMState::BREAK,
+            is_jumping: false,
+            limits,
+            result_stack: EvaluationStack::new(reference_counter.clone()),
+            reference_counter,
+            invocation_stack: Vec::new(),
+            uncaught_exception: None,
+            fault_exception: None,
+            host,
+            observer: None,
+        }
+    }
+
     pub fn set_state(&mut self, value: VMState) {
-        if self.state != value
-        {
+        if self.state != value {
+            let previous = self.state;
             self.state = value;
-            OnStateChanged();
+            self.on_state_changed(previous);
         }
     }
 
     /// <summary>
-    /// Initializes a new instance of the <see cref="ExecutionEngine"/> class.
+    /// Sets the <see cref="ExecutionObserver"/> watching the VM, <see langword="null"/> to remove it.
     /// </summary>
-    // fn ExecutionEngine(): this(new ReferenceCounter(), ExecutionEngineLimits.Default)
-    // {}
+    /// <returns>The previous observer.</returns>
+    pub fn set_observer(
+        &mut self,
+        observer: Option<Box<dyn ExecutionObserver<H>>>,
+    ) -> Option<Box<dyn ExecutionObserver<H>>> {
+        std::mem::replace(&mut self.observer, observer)
+    }
+
+    /// Calls the observer, if any, which is taken out of the engine while it reads it.
+    #[inline]
+    fn notify(&mut self, f: impl FnOnce(&mut dyn ExecutionObserver<H>, &Self)) {
+        if let Some(mut observer) = self.observer.take() {
+            f(observer.as_mut(), self);
+            self.observer = Some(observer);
+        }
+    }
 
     /// <summary>
-    /// Initializes a new instance of the <see cref="ExecutionEngine"/> class with the specified <see cref="VM.ReferenceCounter"/> and <see cref="ExecutionEngineLimits"/>.
+    /// The top frame of the invocation stack.
     /// </summary>
-    /// <param name="referenceCounter">The reference counter to be used.</param>
-    /// <param name="limits">Restrictions on the VM.</param>
+    pub fn current_context(&self) -> Option<&ExecutionContext> {
+        self.invocation_stack.last()
+    }
+
+    /// <summary>
+    /// The bottom frame of the invocation stack.
+    /// </summary>
+    pub fn entry_context(&self) -> Option<&ExecutionContext> {
+        self.invocation_stack.first()
+    }
+
+    fn context(&self) -> &ExecutionContext {
+        self.current_context().expect("no context is loaded")
+    }
 
-    // fn ExecutionEngine(ReferenceCounter referenceCounter, ExecutionEngineLimits limits)
-    // {
-    // this.Limits = limits;
-    // this.ReferenceCounter = referenceCounter;
-    // this.ResultStack = new EvaluationStack(referenceCounter);
-    // }
+    fn context_mut(&mut self) -> &mut ExecutionContext {
+        self.invocation_stack.last_mut().expect("no context is loaded")
+    }
 
     /// <summary>
     /// Called when a context is unloaded.
     /// </summary>
     /// <param name="context">The context being unloaded.</param>
-    pub fn context_unloaded(&mut self, context: &mut ExecutionContext)
-    {
-        if self.invocation_stack().Count == 0
-        {
-            self.current_context = None;
-            self.entry_context = None;
-        } else {
-            self.current_context = self.invocation_stack_mut().Peek();
+    fn context_unloaded(&mut self, context: ExecutionContext) {
+        let shares_static_fields = self
+            .current_context()
+            .is_some_and(|current| current.shares_states_with(&context));
+        if !shares_static_fields {
+            if let Some(static_fields) = context.static_fields().as_ref() {
+                static_fields.clear_references();
+            }
         }
-        if context.static_fields().is_some() && context.static_fields() != self.current_context?.static_fields()
-        {
-            context.static_fields_mut().ClearReferences();
+        if let Some(local_variables) = context.local_variables() {
+            local_variables.clear_references();
         }
-        context.local_variables()?.ClearReferences();
-        context.Arguments?.ClearReferences();
-    }
-
-    pub fn dispose(&mut self)
-    {
-        self.invocation_stack.clear();
+        if let Some(arguments) = context.arguments() {
+            arguments.clear_references();
+        }
+        H::context_unloaded(self, &context);
+        self.notify(|observer, engine| observer.context_unloaded(engine, &context));
     }
 
     /// <summary>
     /// Start execution of the VM.
     /// </summary>
     /// <returns></returns>
-    pub fn execute(&mut self) -> VMState
-    {
-        if self.state == VMState::BREAK
-        { self.state = VMState::NONE; }
-
+    pub fn execute(&mut self) -> VMState {
+        if self.state == VMState::BREAK {
+            self.set_state(VMState::NONE);
+        }
         while self.state != VMState::HALT && self.state != VMState::FAULT {
             self.execute_next();
         }
-        self.state.clone()
+        self.state
     }
 
-    // [MethodImpl(MethodImplOptions.AggressiveInlining)]
-    fn ExecuteCall(&mut self, position: usize)
-    {
-        self::LoadContext(self.current_context.unwrap().Clone(position));
+    /// <summary>
+    /// Execute the next instruction and break, unless the execution has ended.
+    /// </summary>
+    pub fn step(&mut self) -> VMState {
+        if self.state == VMState::HALT || self.state == VMState::FAULT {
+            return self.state;
+        }
+        self.execute_next();
+        if self.state == VMState::NONE {
+            self.set_state(VMState::BREAK);
+        }
+        self.state
     }
 
-    fn ExecuteInstruction(&mut self)
-    {
-        let mut instruction = self.current_context.unwrap().CurrentInstruction();
+    #[inline]
+    fn execute_call(&mut self, position: i64) -> Result<(), VmError> {
+        let length = self.context().script().length();
+        if position < 0 || position as usize > length {
+            return Err(VmError::out_of_range(position));
+        }
+        let context = self.context().clone_at(position as usize);
+        self.load_context(context);
+        Ok(())
+    }
 
-        match instruction.OpCode {
-            OpCode::PUSHINT8 |
-            OpCode::PUSHINT16 |
-            OpCode::PUSHINT32 |
-            OpCode::PUSHINT64 |
-            OpCode::PUSHINT128 |
-            OpCode::PUSHINT256 => {
-                self.Push(BigInt(instruction.Operand.Span));
+    fn execute_instruction(&mut self, instruction: &Instruction) -> Result<(), VmError> {
+        let opcode = instruction.opcode();
+        match opcode {
+            //Push
+            OpCode::PUSHINT8
+            | OpCode::PUSHINT16
+            | OpCode::PUSHINT32
+            | OpCode::PUSHINT64
+            | OpCode::PUSHINT128
+            | OpCode::PUSHINT256 => {
+                self.push_integer(Integer::from_signed_bytes_le(instruction.operand())?);
             }
             OpCode::PUSHA => {
-                let mut position: usize = checked(self.current_context?.instruction_pointer() + instruction.token_i32());
-                if position < 0 || position > self.current_context.unwrap().Script.Length() { panic!(); }
-                Push(Pointer(CurrentContext.Script, position));
-            }
-            OpCode::PUSHNULL =>
-                {
-                    Push(StackItem.Null);
-                }
-            OpCode::PUSHDATA1 |
-            OpCode::PUSHDATA2 |
-            OpCode::PUSHDATA4 => {
-                Limits.AssertMaxItemSize(instruction.Operand.Length);
-                Push(instruction.Operand);
-            }
-            OpCode::PUSHM1 |
-            OpCode::PUSH0 |
-            OpCode::PUSH1 |
-            OpCode::PUSH2 |
-            OpCode::PUSH3 |
-            OpCode::PUSH4 |
-            OpCode::PUSH5 |
-            OpCode::PUSH6 |
-            OpCode::PUSH7 |
-            OpCode::PUSH8 |
-            OpCode::PUSH9 |
-            OpCode::PUSH10 |
-            OpCode::PUSH11 |
-            OpCode::PUSH12 |
-            OpCode::PUSH13 |
-            OpCode::PUSH14 |
-            OpCode::PUSH15 |
-            OpCode::PUSH16 => {
-                Push((int)instruction.opcode - OpCode::PUSH0.0);
-            }
-            OpCode::NOP => break,
-            OpCode::JMP =>
-                {
-                    ExecuteJumpOffset(instruction.token_i8());
-                }
-            OpCode::JMP_L =>
-                {
-                    ExecuteJumpOffset(instruction.token_i32());
-                }
-            OpCode::JMPIF =>
-                {
-                    if Pop().boolean()
-                    { ExecuteJumpOffset(instruction.token_i8()); }
-                }
-            OpCode::JMPIF_L =>
-                {
-                    if Pop().boolean()
-                    { ExecuteJumpOffset(instruction.token_i32()); }
-                }
-            OpCode::JMPIFNOT =>
-                {
-                    if (!Pop().boolean()) {
-                        ExecuteJumpOffset(instruction.token_i8());
-                    }
-                }
-            OpCode::JMPIFNOT_L =>
-                {
-                    if (!Pop().boolean()) {
-                        ExecuteJumpOffset(instruction.token_i32());
-                    }
-                }
-            OpCode::JMPEQ =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 == x2 {
-                        ExecuteJumpOffset(instruction.token_i8());
-                    }
-                }
-            OpCode::JMPEQ_L =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 == x2 {
-                        ExecuteJumpOffset(instruction.token_i32());
-                    }
-                }
-            OpCode::JMPNE =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 != x2 {
-                        ExecuteJumpOffset(instruction.token_i8());
-                    }
-                }
-            OpCode::JMPNE_L =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 != x2 { ExecuteJumpOffset(instruction.token_i32()); }
-                }
-            OpCode::JMPGT =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if (x1 > x2) { ExecuteJumpOffset(instruction.token_i8()); }
-                }
-            OpCode::JMPGT_L =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 > x2 { ExecuteJumpOffset(instruction.token_i32()); }
-                }
-            OpCode::JMPGE =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 >= x2 { ExecuteJumpOffset(instruction.token_i8()); }
-                }
-            OpCode::JMPGE_L =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 >= x2 { ExecuteJumpOffset(instruction.token_i32()); }
-                }
-            OpCode::JMPLT =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 < x2 { ExecuteJumpOffset(instruction.token_i8()); }
-                }
-            OpCode::JMPLT_L =>
-                {
-                    let x2 = Pop().integer();
-                    let x1 = Pop().integer();
-                    if x1 < x2 { ExecuteJumpOffset(instruction.token_i32()); }
-                }
-            OpCode::JMPLE =>
-                {
-                    let x2 = Pop().integer() as i32;
-                    let x1 = Pop().integer() as i32;
-                    if x1 <= x2 {
-                        ExecuteJumpOffset(instruction.token_i8());
-                    }
-                }
-            OpCode::JMPLE_L =>
-                {
-                    let x2 = Pop().integer() as i32;
-                    let x1 = Pop().integer() as i32;
-                    if x1 <= x2 { ExecuteJumpOffset(instruction.token_i32()); }
-                }
-            OpCode::CALL =>
-                {
-                    ExecuteCall(checked(CurrentContext.InstructionPointer + instruction.token_i8()));
-                }
-            OpCode::CALL_L =>
-                {
-                    ExecuteCall(checked(CurrentContext.InstructionPointer + instruction.token_i32()));
-                }
-            OpCode::CALLA =>
-                {
-                    let x = Pop < Pointer > ();
-                    if (x.Script != CurrentContext.Script) { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException("Pointers can't be shared between scripts");
-                    ExecuteCall(x.Position);
-                }
-            OpCode::CALLT =>
-                {
-                    LoadToken(instruction.TokenU16);
-                }
-            OpCode::ABORT =>
-                {
-                    panic!();
+                let position = self.context().instruction_pointer() as i64 + instruction.token_i32() as i64;
+                let script = self.context().script().clone();
+                if position < 0 || position as usize > script.length() {
+                    return Err(VmError::invalid_operation(format!("Bad pointer address: {}", position)));
                 }
-            OpCode::ASSERT =>
-                {
-                    let x = Pop().boolean() as bool;
-                    if !x { panic!(); }
-                }
-            OpCode::THROW =>
-                {
-                    self.execute_throw(Pop());
-                }
-            OpCode::TRY =>
-                {
-                    let catchOffset = instruction.token_i8() as i32;
-                    let finallyOffset = instruction.token_i8_1 as i32;
-                    ExecuteTry(catchOffset, finallyOffset);
-                }
-            OpCode::TRY_L =>
-                {
-                    let catchOffset = instruction.token_i32() as i32;
-                    let finallyOffset = instruction.token_i32_1() as i32;
-                    ExecuteTry(catchOffset, finallyOffset);
+                self.push(Pointer::new(script, position as usize).into());
+            }
+            OpCode::PUSHNULL => {
+                self.push(StackItem::Null);
+            }
+            OpCode::PUSHDATA1 | OpCode::PUSHDATA2 | OpCode::PUSHDATA4 => {
+                self.limits.assert_max_item_size(instruction.operand().len())?;
+                self.push(ByteString::from(instruction.operand().as_slice()).into());
+            }
+            OpCode::PUSHM1
+            | OpCode::PUSH0
+            | OpCode::PUSH1
+            | OpCode::PUSH2
+            | OpCode::PUSH3
+            | OpCode::PUSH4
+            | OpCode::PUSH5
+            | OpCode::PUSH6
+            | OpCode::PUSH7
+            | OpCode::PUSH8
+            | OpCode::PUSH9
+            | OpCode::PUSH10
+            | OpCode::PUSH11
+            | OpCode::PUSH12
+            | OpCode::PUSH13
+            | OpCode::PUSH14
+            | OpCode::PUSH15
+            | OpCode::PUSH16 => {
+                self.push_integer(opcode.0 as i32 - OpCode::PUSH0.0 as i32);
+            }
+
+            // Control
+            OpCode::NOP => {}
+            OpCode::JMP => self.execute_jump_offset(instruction.token_i8() as i32)?,
+            OpCode::JMP_L => self.execute_jump_offset(instruction.token_i32())?,
+            OpCode::JMPIF | OpCode::JMPIF_L | OpCode::JMPIFNOT | OpCode::JMPIFNOT_L => {
+                let condition = self.pop()?.boolean()?;
+                if condition == (opcode == OpCode::JMPIF || opcode == OpCode::JMPIF_L) {
+                    self.execute_jump_offset(Self::jump_offset(instruction))?;
                 }
-            OpCode::ENDTRY =>
-                {
-                    let endOffset = instruction.token_i8() as i32;
-                    ExecuteEndTry(endOffset);
+            }
+            OpCode::JMPEQ
+            | OpCode::JMPEQ_L
+            | OpCode::JMPNE
+            | OpCode::JMPNE_L
+            | OpCode::JMPGT
+            | OpCode::JMPGT_L
+            | OpCode::JMPGE
+            | OpCode::JMPGE_L
+            | OpCode::JMPLT
+            | OpCode::JMPLT_L
+            | OpCode::JMPLE
+            | OpCode::JMPLE_L => {
+                let x2 = self.pop_integer()?;
+                let x1 = self.pop_integer()?;
+                let condition = match opcode {
+                    OpCode::JMPEQ | OpCode::JMPEQ_L => x1 == x2,
+                    OpCode::JMPNE | OpCode::JMPNE_L => x1 != x2,
+                    OpCode::JMPGT | OpCode::JMPGT_L => x1 > x2,
+                    OpCode::JMPGE | OpCode::JMPGE_L => x1 >= x2,
+                    OpCode::JMPLT | OpCode::JMPLT_L => x1 < x2,
+                    _ => x1 <= x2,
+                };
+                if condition {
+                    self.execute_jump_offset(Self::jump_offset(instruction))?;
                 }
-            OpCode::ENDTRY_L =>
-                {
-                    let endOffset = instruction.token_i32() as i32;
-                    ExecuteEndTry(endOffset);
+            }
+            OpCode::CALL | OpCode::CALL_L => {
+                let position = self.context().instruction_pointer() as i64 + Self::jump_offset(instruction) as i64;
+                self.execute_call(position)?;
+            }
+            OpCode::CALLA => {
+                let x = self.pop()?;
+                let pointer = match x.as_pointer() {
+                    Some(pointer) => pointer,
+                    None => return Err(VmError::invalid_type(opcode, &x)),
+                };
+                if !Rc::ptr_eq(pointer.script(), self.context().script()) {
+                    return Err(VmError::invalid_operation("Pointers can't be shared between scripts"));
+                }
+                self.execute_call(pointer.position() as i64)?;
+            }
+            OpCode::CALLT => {
+                let context = H::load_token(self, instruction.token_u16())?;
+                self.load_context(context);
+            }
+            OpCode::ABORT => {
+                return Err(VmError::invalid_operation("ABORT is executed."));
+            }
+            OpCode::ASSERT => {
+                let x = self.pop()?.boolean()?;
+                if !x {
+                    return Err(VmError::invalid_operation("ASSERT is executed with false result."));
                 }
-            OpCode::ENDFINALLY =>
-                {
-                    if self.current_context.unwrap().try_stack().is_none() { panic!(); }
-
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The corresponding TRY block cannot be found.");
-                    if (!self.current_context.try_stack.TryPop(out ExceptionHandlingContext? currentTry)) { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The corresponding TRY block cannot be found.");
-
-                    if self.uncaught_exception.is_none() {
-                        CurrentContext.InstructionPointer = currentTry.EndPointer;
-                    } else {
-                        HandleException();
+            }
+            OpCode::THROW => {
+                let ex = self.pop()?;
+                self.execute_throw(ex)?;
+            }
+            OpCode::TRY => {
+                self.execute_try(instruction.token_i8() as i32, instruction.token_i8_1() as i32)?;
+            }
+            OpCode::TRY_L => {
+                self.execute_try(instruction.token_i32(), instruction.token_i32_1())?;
+            }
+            OpCode::ENDTRY | OpCode::ENDTRY_L => {
+                self.execute_end_try(Self::jump_offset(instruction))?;
+            }
+            OpCode::ENDFINALLY => {
+                let current_try = match self
+                    .context_mut()
+                    .try_stack_mut()
+                    .as_mut()
+                    .and_then(|stack| stack.pop())
+                {
+                    Some(current_try) => current_try,
+                    None => {
+                        return Err(VmError::invalid_operation(
+                            "The corresponding TRY block cannot be found.",
+                        ))
                     }
-
-                    self.is_jumping = true;
-                }
-            OpCode::RET =>
-                {
-                    ExecutionContext
-                    context_pop = InvocationStack.Pop();
-                    EvaluationStack
-                    stack_eval = InvocationStack.Count == 0?
-                    ResultStack => InvocationStack.Peek().EvaluationStack;
-                    if (context_pop.EvaluationStack != stack_eval)
-                    {
-                        if context_pop.RVCount > = 0 & &context_pop.EvaluationStack.Count != context_pop.RVCount
-                        {
-                            panic!();
+                };
+                if self.uncaught_exception.is_none() {
+                    let end_pointer = current_try
+                        .end_pointer()
+                        .ok_or_else(|| VmError::invalid_operation("The TRY block has not ended."))?;
+                    self.context_mut().set_instruction_pointer(end_pointer)?;
+                } else {
+                    self.handle_exception()?;
+                }
+                self.is_jumping = true;
+            }
+            OpCode::RET => {
+                let context_pop = self.invocation_stack.pop().unwrap();
+                match self.invocation_stack.last() {
+                    Some(context) if context.shares_states_with(&context_pop) => {}
+                    next => {
+                        let rv_count = context_pop.rv_count();
+                        let mut evaluation_stack = context_pop.evaluation_stack_mut();
+                        if rv_count >= 0 && evaluation_stack.count() != rv_count as usize {
+                            return Err(VmError::invalid_operation("RVCount doesn't match with EvaluationStack"));
+                        }
+                        match next {
+                            Some(context) => evaluation_stack.move_to(&mut context.evaluation_stack_mut(), None)?,
+                            None => evaluation_stack.move_to(&mut self.result_stack, None)?,
                         }
-                        context_pop.EvaluationStack.CopyTo(stack_eval);
                     }
-                    if (InvocationStack.Count == 0)
-                    State = VMState.HALT;
-                    ContextUnloaded(context_pop);
-                    self.is_jumping = true;
                 }
-            OpCode::SYSCALL =>
-                {
-                    OnSysCall(instruction.TokenU32);
+                if self.invocation_stack.is_empty() {
+                    self.set_state(VMState::HALT);
                 }
+                self.context_unloaded(context_pop);
+                self.is_jumping = true;
+            }
+            OpCode::SYSCALL => {
+                H::on_syscall(self, instruction.token_u32())?;
+            }
 
             // Stack ops
-            OpCode::DEPTH =>
-                {
-                    Push(CurrentContext.EvaluationStack.Count);
-                }
-            OpCode::DROP =>
-                {
-                    Pop();
-                }
-            OpCode::NIP =>
-                {
-                    CurrentContext.EvaluationStack.Remove < StackItem > (1);
-                }
-            OpCode::XDROP =>
-                {
-                    int
-                    n = (int)
-                    Pop().integer();
-                    if (n < 0)
-                    throw
-                    new
-                    InvalidOperationException($ "The negative value {n} is invalid for OpCode.{instruction.OpCode}.");
-                    CurrentContext.EvaluationStack.Remove < StackItem > (n);
-                }
-            OpCode::CLEAR =>
-                {
-                    CurrentContext.EvaluationStack.Clear();
-                }
-            OpCode::DUP =>
-                {
-                    Push(Peek());
-                }
-            OpCode::OVER =>
-                {
-                    Push(Peek(1));
-                }
-            OpCode::PICK =>
-                {
-                    int
-                    n = (int)
-                    Pop().integer();
-                    if (n < 0)
-                    throw
-                    new
-                    InvalidOperationException($ "The negative value {n} is invalid for OpCode.{instruction.OpCode}.");
-                    Push(Peek(n));
-                }
-            OpCode::TUCK =>
-                {
-                    CurrentContext.EvaluationStack.Insert(2, Peek());
-                }
-            OpCode::SWAP =>
-                {
-                    let x = CurrentContext.EvaluationStack.Remove < StackItem > (1);
-                    Push(x);
-                }
-            OpCode::ROT =>
-                {
-                    let x = CurrentContext.EvaluationStack.Remove < StackItem > (2);
-                    Push(x);
-                }
-            OpCode::ROLL =>
-                {
-                    int
-                    n = (int)
-                    Pop().integer();
-                    if (n < 0)
-                    throw
-                    new
-                    InvalidOperationException($ "The negative value {n} is invalid for OpCode.{instruction.OpCode}.");
-                    if (n == 0)
-
-                    let x = CurrentContext.EvaluationStack.Remove < StackItem > (n);
-                    Push(x);
-                }
-            OpCode::REVERSE3 =>
-                {
-                    CurrentContext.EvaluationStack.Reverse(3);
-                }
-            OpCode::REVERSE4 =>
-                {
-                    CurrentContext.EvaluationStack.Reverse(4);
-                }
-            OpCode::REVERSEN =>
-                {
-                    int
-                    n = (int)
-                    Pop().integer();
-                    CurrentContext.EvaluationStack.Reverse(n);
+            OpCode::DEPTH => {
+                let depth = self.context().evaluation_stack().count();
+                self.push_integer(depth);
+            }
+            OpCode::DROP => {
+                self.pop()?;
+            }
+            OpCode::NIP => {
+                self.context().evaluation_stack_mut().remove(1)?;
+            }
+            OpCode::XDROP => {
+                let n = self.pop_index()?;
+                self.context().evaluation_stack_mut().remove(n)?;
+            }
+            OpCode::CLEAR => {
+                self.context().evaluation_stack_mut().clear();
+            }
+            OpCode::DUP => {
+                let x = self.peek(0)?;
+                self.push(x);
+            }
+            OpCode::OVER => {
+                let x = self.peek(1)?;
+                self.push(x);
+            }
+            OpCode::PICK => {
+                let n = self.pop_index()?;
+                let x = self.peek(n)?;
+                self.push(x);
+            }
+            OpCode::TUCK => {
+                let x = self.peek(0)?;
+                self.context().evaluation_stack_mut().insert(2, x)?;
+            }
+            OpCode::SWAP => {
+                let x = self.context().evaluation_stack_mut().remove(1)?;
+                self.push(x);
+            }
+            OpCode::ROT => {
+                let x = self.context().evaluation_stack_mut().remove(2)?;
+                self.push(x);
+            }
+            OpCode::ROLL => {
+                let n = self.pop_index()?;
+                if n != 0 {
+                    let x = self.context().evaluation_stack_mut().remove(n)?;
+                    self.push(x);
                 }
+            }
+            OpCode::REVERSE3 => self.context().evaluation_stack_mut().reverse(3)?,
+            OpCode::REVERSE4 => self.context().evaluation_stack_mut().reverse(4)?,
+            OpCode::REVERSEN => {
+                let n = self.pop_index()?;
+                self.context().evaluation_stack_mut().reverse(n)?;
+            }
 
             //Slot
-            OpCode::INITSSLOT =>
-                {
-                    if (CurrentContext.StaticFields != null)
-                    throw
-                    new
-                    InvalidOperationException($ "{instruction.OpCode} cannot be executed twice.");
-                    if (instruction.TokenU8 == 0)
-                    throw
-                    new
-                    InvalidOperationException($ "The operand {instruction.TokenU8} is invalid for OpCode.{instruction.OpCode}.");
-                    CurrentContext.StaticFields = new
-                    Slot(instruction.TokenU8, ReferenceCounter);
-                }
-            OpCode::INITSLOT =>
-                {
-                    if (CurrentContext.LocalVariables != null | | CurrentContext.Arguments != null)
-                    throw
-                    new
-                    InvalidOperationException($ "{instruction.OpCode} cannot be executed twice.");
-                    if (instruction.TokenU16 == 0)
-                    throw
-                    new
-                    InvalidOperationException($ "The operand {instruction.TokenU16} is invalid for OpCode.{instruction.OpCode}.");
-                    if (instruction.TokenU8 > 0)
-                    {
-                        CurrentContext.LocalVariables = new
-                        Slot(instruction.TokenU8, ReferenceCounter);
-                    }
-                    if (instruction.TokenU8_1 > 0)
-                    {
-                        StackItem
-                        []
-                        items = new
-                        StackItem[instruction.TokenU8_1];
-                        for (int i = 0; i < instruction.TokenU8_1; i + +)
-                        {
-                            items[i] = Pop();
-                        }
-                        CurrentContext.Arguments = new
-                        Slot(items, ReferenceCounter);
-                    }
-                }
-            OpCode::LDSFLD0 =>
-                OpCode::LDSFLD1:
-                    OpCode::LDSFLD2:
-                    OpCode::LDSFLD3:
-                    OpCode::LDSFLD4:
-                    OpCode::LDSFLD5:
-                    OpCode::LDSFLD6 =>
-            {
-            ExecuteLoadFromSlot(CurrentContext.StaticFields, instruction.OpCode - OpCode.LDSFLD0);
-
-            }
-            OpCode::LDSFLD =>
-                {
-                    ExecuteLoadFromSlot(CurrentContext.StaticFields, instruction.TokenU8);
-                }
-            OpCode::STSFLD0 |
-            OpCode::STSFLD1 |
-            OpCode::STSFLD2 |
-            OpCode::STSFLD3 |
-            OpCode::STSFLD4 |
-            OpCode::STSFLD5 |
-            OpCode::STSFLD6 =>
-                {
-                    ExecuteStoreToSlot(CurrentContext.StaticFields, instruction.OpCode - OpCode.STSFLD0);
-                }
-            OpCode::STSFLD =>
-                {
-                    ExecuteStoreToSlot(CurrentContext.StaticFields, instruction.TokenU8);
-                }
-            OpCode::LDLOC0 |
-            OpCode::LDLOC1 |
-            OpCode::LDLOC2 |
-            OpCode::LDLOC3 |
-            OpCode::LDLOC4 |
-            OpCode::LDLOC5 |
-            OpCode::LDLOC6 =>
-                {
-                    ExecuteLoadFromSlot(CurrentContext.LocalVariables, instruction.OpCode - OpCode.LDLOC0);
-                }
-            OpCode::LDLOC =>
-                {
-                    ExecuteLoadFromSlot(CurrentContext.LocalVariables, instruction.TokenU8);
-                }
-            OpCode::STLOC0 |
-            OpCode::STLOC1 |
-            OpCode::STLOC2 |
-            OpCode::STLOC3 |
-            OpCode::STLOC4 |
-            OpCode::STLOC5 |
-            OpCode::STLOC6 =>
-                {
-                    ExecuteStoreToSlot(CurrentContext.LocalVariables, instruction.OpCode - OpCode.STLOC0);
-                }
-            OpCode::STLOC =>
-                {
-                    ExecuteStoreToSlot(CurrentContext.LocalVariables, instruction.TokenU8);
-                }
-            OpCode::LDARG0 |
-            OpCode::LDARG1 |
-            OpCode::LDARG2 |
-            OpCode::LDARG3 |
-            OpCode::LDARG4 |
-            OpCode::LDARG5 |
-            OpCode::LDARG6 =>
-                {
-                    ExecuteLoadFromSlot(CurrentContext.Arguments, instruction.OpCode - OpCode.LDARG0);
-                }
-            OpCode::LDARG =>
-                {
-                    ExecuteLoadFromSlot(CurrentContext.Arguments, instruction.TokenU8);
-                }
-            OpCode::STARG0 |
-            OpCode::STARG1 |
-            OpCode::STARG2 |
-            OpCode::STARG3 |
-            OpCode::STARG4 |
-            OpCode::STARG5 |
-            OpCode::STARG6 =>
-                {
-                    ExecuteStoreToSlot(CurrentContext.Arguments, instruction.OpCode - OpCode.STARG0);
-                }
-            OpCode::STARG =>
-                {
-                    ExecuteStoreToSlot(CurrentContext.Arguments, instruction.TokenU8);
+            OpCode::INITSSLOT => {
+                if self.context().static_fields().is_some() {
+                    return Err(VmError::invalid_operation("INITSSLOT cannot be executed twice."));
+                }
+                if instruction.token_u8() == 0 {
+                    return Err(VmError::invalid_operation(format!(
+                        "The operand {} is invalid for OpCode.INITSSLOT.",
+                        instruction.token_u8()
+                    )));
+                }
+                let slot = Slot::new(instruction.token_u8() as usize, self.reference_counter.clone());
+                *self.context().static_fields_mut() = Some(slot);
+            }
+            OpCode::INITSLOT => {
+                if self.context().local_variables().is_some() || self.context().arguments().is_some() {
+                    return Err(VmError::invalid_operation("INITSLOT cannot be executed twice."));
+                }
+                if instruction.token_u16() == 0 {
+                    return Err(VmError::invalid_operation(format!(
+                        "The operand {} is invalid for OpCode.INITSLOT.",
+                        instruction.token_u16()
+                    )));
+                }
+                if instruction.token_u8() > 0 {
+                    let slot = Slot::new(instruction.token_u8() as usize, self.reference_counter.clone());
+                    self.context_mut().set_local_variables(slot);
+                }
+                if instruction.token_u8_1() > 0 {
+                    let items = (0..instruction.token_u8_1())
+                        .map(|_| self.pop())
+                        .collect::<Result<_, _>>()?;
+                    let slot = Slot::from_items(items, self.reference_counter.clone());
+                    self.context_mut().set_arguments(slot);
                 }
+            }
+            OpCode::LDSFLD0
+            | OpCode::LDSFLD1
+            | OpCode::LDSFLD2
+            | OpCode::LDSFLD3
+            | OpCode::LDSFLD4
+            | OpCode::LDSFLD5
+            | OpCode::LDSFLD6 => {
+                self.execute_load_from_slot(SlotKind::Static, (opcode.0 - OpCode::LDSFLD0.0) as usize)?;
+            }
+            OpCode::LDSFLD => self.execute_load_from_slot(SlotKind::Static, instruction.token_u8() as usize)?,
+            OpCode::STSFLD0
+            | OpCode::STSFLD1
+            | OpCode::STSFLD2
+            | OpCode::STSFLD3
+            | OpCode::STSFLD4
+            | OpCode::STSFLD5
+            | OpCode::STSFLD6 => {
+                self.execute_store_to_slot(SlotKind::Static, (opcode.0 - OpCode::STSFLD0.0) as usize)?;
+            }
+            OpCode::STSFLD => self.execute_store_to_slot(SlotKind::Static, instruction.token_u8() as usize)?,
+            OpCode::LDLOC0
+            | OpCode::LDLOC1
+            | OpCode::LDLOC2
+            | OpCode::LDLOC3
+            | OpCode::LDLOC4
+            | OpCode::LDLOC5
+            | OpCode::LDLOC6 => {
+                self.execute_load_from_slot(SlotKind::Local, (opcode.0 - OpCode::LDLOC0.0) as usize)?;
+            }
+            OpCode::LDLOC => self.execute_load_from_slot(SlotKind::Local, instruction.token_u8() as usize)?,
+            OpCode::STLOC0
+            | OpCode::STLOC1
+            | OpCode::STLOC2
+            | OpCode::STLOC3
+            | OpCode::STLOC4
+            | OpCode::STLOC5
+            | OpCode::STLOC6 => {
+                self.execute_store_to_slot(SlotKind::Local, (opcode.0 - OpCode::STLOC0.0) as usize)?;
+            }
+            OpCode::STLOC => self.execute_store_to_slot(SlotKind::Local, instruction.token_u8() as usize)?,
+            OpCode::LDARG0
+            | OpCode::LDARG1
+            | OpCode::LDARG2
+            | OpCode::LDARG3
+            | OpCode::LDARG4
+            | OpCode::LDARG5
+            | OpCode::LDARG6 => {
+                self.execute_load_from_slot(SlotKind::Argument, (opcode.0 - OpCode::LDARG0.0) as usize)?;
+            }
+            OpCode::LDARG => self.execute_load_from_slot(SlotKind::Argument, instruction.token_u8() as usize)?,
+            OpCode::STARG0
+            | OpCode::STARG1
+            | OpCode::STARG2
+            | OpCode::STARG3
+            | OpCode::STARG4
+            | OpCode::STARG5
+            | OpCode::STARG6 => {
+                self.execute_store_to_slot(SlotKind::Argument, (opcode.0 - OpCode::STARG0.0) as usize)?;
+            }
+            OpCode::STARG => self.execute_store_to_slot(SlotKind::Argument, instruction.token_u8() as usize)?,
 
             // Splice
-            OpCode::NEWBUFFER =>
-                {
-                    int
-                    length = (int)
-                    Pop().integer();
-                    Limits.AssertMaxItemSize(length);
-                    Push(new Buffer(length));
-                }
-            OpCode::MEMCPY =>
-                {
-                    let count = Pop().integer() as i32;
-                    if count < 0 { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    let si = Pop().integer() as i32;
-                    if si < 0 { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {si} is out of range.");
-                    ReadOnlySpan < u8 > src = Pop().GetSpan();
-                    if (checked(si + count) > src.Length) { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    let di = Pop().integer() as i32;
-                    if di < 0 { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {di} is out of range.");
-                    Buffer
-                    dst = Pop < Buffer > ();
-                    if checked(di + count) > dst.Size { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    src.Slice(si, count).CopyTo(dst.InnerBuffer.AsSpan(di));
-                }
-            OpCode::CAT =>
-                {
-                    let x2 = Pop().GetSpan();
-                    let x1 = Pop().GetSpan();
-                    let length = x1.Length + x2.Length;
-                    Limits.AssertMaxItemSize(length);
-                    Buffer
-                    result = new(length);
-                    x1.CopyTo(result.InnerBuffer);
-                    x2.CopyTo(result.InnerBuffer.AsSpan(x1.Length));
-                    Push(result);
-                }
-            OpCode::SUBSTR =>
-                {
-                    let count = Pop().integer() as i32;
-                    if count < 0 { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    let index = Pop().integer() as i32;
-                    if index < 0 { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {index} is out of range.");
-                    let x = Pop().GetSpan();
-                    if index + count > x.Length { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    Buffer
-                    result = new(count);
-                    x.Slice(index, count).CopyTo(result.InnerBuffer);
-                    Push(result);
-                }
-            OpCode::LEFT =>
-                {
-                    let count = Pop().integer() as usize;
-                    if count < 0 { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    let x = Pop().GetSpan();
-                    if count > x.Length { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    Buffer
-                    result = new(count);
-                    x[..count].CopyTo(result.InnerBuffer);
-                    Push(result);
+            OpCode::NEWBUFFER => {
+                let length = self.pop_index()?;
+                self.limits.assert_max_item_size(length)?;
+                self.push(Buffer::new(length).into());
+            }
+            OpCode::MEMCPY => {
+                let count = self.pop_index()?;
+                let si = self.pop_index()?;
+                let src = self.pop()?.span()?;
+                if si + count > src.len() {
+                    return Err(VmError::out_of_range(count));
+                }
+                let di = self.pop_index()?;
+                let x = self.pop()?;
+                let dst = match x.as_buffer() {
+                    Some(dst) => dst,
+                    None => return Err(VmError::invalid_type(opcode, &x)),
+                };
+                if di + count > dst.size() {
+                    return Err(VmError::out_of_range(count));
+                }
+                dst.inner_buffer_mut()[di..di + count].copy_from_slice(&src[si..si + count]);
+            }
+            OpCode::CAT => {
+                let x2 = self.pop()?.span()?;
+                let x1 = self.pop()?.span()?;
+                let length = x1.len() + x2.len();
+                self.limits.assert_max_item_size(length)?;
+                let mut result = Vec::with_capacity(length);
+                result.extend_from_slice(&x1);
+                result.extend_from_slice(&x2);
+                self.push(Buffer::from_slice(&result).into());
+            }
+            OpCode::SUBSTR => {
+                let count = self.pop_index()?;
+                let index = self.pop_index()?;
+                let x = self.pop()?.span()?;
+                if index + count > x.len() {
+                    return Err(VmError::out_of_range(count));
+                }
+                self.push(Buffer::from_slice(&x[index..index + count]).into());
+            }
+            OpCode::LEFT => {
+                let count = self.pop_index()?;
+                let x = self.pop()?.span()?;
+                if count > x.len() {
+                    return Err(VmError::out_of_range(count));
                 }
-            OpCode::RIGHT =>
-                {
-                    let count = Pop().integer() as i32;
-                    if count < 0 { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    let x = Pop().GetSpan();
-                    if count > x.Length { panic!(); }
-                    // throw
-                    // new
-                    // InvalidOperationException($ "The value {count} is out of range.");
-                    Buffer
-                    result = new(count);
-                    x
-                    [ ^ count.. ^ 0].CopyTo(result.InnerBuffer);
-                    Push(result);
+                self.push(Buffer::from_slice(&x[..count]).into());
+            }
+            OpCode::RIGHT => {
+                let count = self.pop_index()?;
+                let x = self.pop()?.span()?;
+                if count > x.len() {
+                    return Err(VmError::out_of_range(count));
                 }
+                self.push(Buffer::from_slice(&x[x.len() - count..]).into());
+            }
+
             // Bitwise logic
-            OpCode::INVERT =>
-                {
-                    let x = Pop().integer();
-                    Push(~x);
-                }
-            OpCode::AND =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 & x2);
-                }
-            OpCode::OR =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 | x2);
-                }
-            OpCode::XOR =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 ^ x2);
-                }
-            OpCode::EQUAL =>
-                {
-                    StackItem
-                    x2 = Pop();
-                    StackItem
-                    x1 = Pop();
-                    Push(x1.Equals(x2));
-                }
-            OpCode::NOTEQUAL =>
-                {
-                    StackItem
-                    x2 = Pop();
-                    StackItem
-                    x1 = Pop();
-                    Push(!x1.Equals(x2));
-                }
+            OpCode::INVERT => {
+                let x = self.pop_integer()?;
+                self.push_integer(x.not()?);
+            }
+            OpCode::AND | OpCode::OR | OpCode::XOR => {
+                let x2 = self.pop_integer()?;
+                let x1 = self.pop_integer()?;
+                self.push_integer(match opcode {
+                    OpCode::AND => x1.bitand(&x2),
+                    OpCode::OR => x1.bitor(&x2),
+                    _ => x1.bitxor(&x2),
+                }?);
+            }
+            OpCode::EQUAL | OpCode::NOTEQUAL => {
+                let x2 = self.pop()?;
+                let x1 = self.pop()?;
+                self.push_boolean(x1.equals(&x2)? == (opcode == OpCode::EQUAL));
+            }
 
             // Numeric
-            OpCode::SIGN =>
-                {
-                    let
-                        x = Pop().integer();
-                    Push(x.Sign);
-                }
-            OpCode::ABS =>
-                {
-                    let
-                        x = Pop().integer();
-                    Push(BigInt.Abs(x));
-                }
-            OpCode::NEGATE =>
-                {
-                    let
-                        x = Pop().integer();
-                    Push(-x);
-                }
-            OpCode::INC =>
-                {
-                    let
-                        x = Pop().integer();
-                    Push(x + 1);
-                }
-            OpCode::DEC =>
-                {
-                    let
-                        x = Pop().integer();
-                    Push(x - 1);
-                }
-            OpCode::ADD =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 + x2);
-                }
-            OpCode::SUB =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 - x2);
-                }
-            OpCode::MUL =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 * x2);
-                }
-            OpCode::DIV =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 / x2);
-                }
-            OpCode::MOD =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 % x2);
-                }
-            OpCode::POW =>
-                {
-                    let
-                        exponent = (int)
-                    Pop().integer();
-                    let
-                        value = Pop().integer();
-                    Push(BigInt.Pow(value, exponent));
-                }
-            OpCode::SQRT =>
-                {
-                    Push(Pop().integer().Sqrt());
-                }
-            OpCode::SHL =>
-                {
-                    int
-                    shift = (int)
-                    Pop().integer();
-                    Limits.AssertShift(shift);
-                    if (shift == 0)
-
-                    let
-                        x = Pop().integer();
-                    Push(x < < shift);
-                }
-            OpCode::SHR =>
-                {
-                    int
-                    shift = (int)
-                    Pop().integer();
-                    Limits.AssertShift(shift);
-                    if (shift == 0)
-
-                    let
-                        x = Pop().integer();
-                    Push(x > > shift);
-                }
-            OpCode::NOT =>
-                {
-                    let
-                        x = Pop().boolean();
-                    Push(!x);
-                }
-            OpCode::BOOLAND =>
-                {
-                    let
-                        x2 = Pop().boolean();
-                    let
-                        x1 = Pop().boolean();
-                    Push(x1 & &x2);
-                }
-            OpCode::BOOLOR =>
-                {
-                    let
-                        x2 = Pop().boolean();
-                    let
-                        x1 = Pop().boolean();
-                    Push(x1 | | x2);
-                }
-            OpCode::NZ =>
-                {
-                    let
-                        x = Pop().integer();
-                    Push(!x.IsZero);
-                }
-            OpCode::NUMEQUAL =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 == x2);
-                }
-            OpCode::NUMNOTEQUAL =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(x1 != x2);
-                }
-            OpCode::LT =>
-                {
-                    let
-                        x2 = Pop();
-                    let
-                        x1 = Pop();
-                    if (x1.IsNull || x2.IsNull)
-                    Push(false);
-                    else
-                    Push(x1.integer() < x2.integer());
-                }
-            OpCode::LE =>
-                {
-                    let
-                        x2 = Pop();
-                    let
-                        x1 = Pop();
-                    if (x1.IsNull || x2.IsNull)
-                    Push(false);
-                    else
-                    Push(x1.integer() < = x2.integer());
-                }
-            OpCode::GT =>
-                {
-                    let
-                        x2 = Pop();
-                    let
-                        x1 = Pop();
-                    if (x1.IsNull || x2.IsNull)
-                    Push(false);
-                    else
-                    Push(x1.integer() > x2.integer());
-                }
-            OpCode::GE =>
-                {
-                    let
-                        x2 = Pop();
-                    let
-                        x1 = Pop();
-                    if (x1.IsNull || x2.IsNull)
-                    Push(false);
-                    else
-                    Push(x1.integer() > = x2.integer());
-                }
-            OpCode::MIN =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(BigInt.Min(x1, x2));
+            OpCode::SIGN => {
+                let x = self.pop_integer()?;
+                self.push_integer(x.signum());
+            }
+            OpCode::ABS => {
+                let x = self.pop_integer()?;
+                self.push_integer(x.abs()?);
+            }
+            OpCode::NEGATE => {
+                let x = self.pop_integer()?;
+                self.push_integer(x.neg()?);
+            }
+            OpCode::INC => {
+                let x = self.pop_integer()?;
+                self.push_integer(x.add(&Integer::Small(1))?);
+            }
+            OpCode::DEC => {
+                let x = self.pop_integer()?;
+                self.push_integer(x.sub(&Integer::Small(1))?);
+            }
+            OpCode::ADD | OpCode::SUB | OpCode::MUL | OpCode::DIV | OpCode::MOD | OpCode::MIN | OpCode::MAX => {
+                let x2 = self.pop_integer()?;
+                let x1 = self.pop_integer()?;
+                self.push_integer(match opcode {
+                    OpCode::ADD => x1.add(&x2)?,
+                    OpCode::SUB => x1.sub(&x2)?,
+                    OpCode::MUL => x1.mul(&x2)?,
+                    OpCode::DIV => x1.div(&x2)?,
+                    OpCode::MOD => x1.rem(&x2)?,
+                    OpCode::MIN => x1.min(x2),
+                    _ => x1.max(x2),
+                });
+            }
+            OpCode::POW => {
+                let exponent = self.pop_i32()?;
+                self.limits.assert_shift(exponent)?;
+                let value = self.pop()?.integer()?;
+                self.push_integer(Integer::new(num::pow(value, exponent as usize))?);
+            }
+            OpCode::SQRT => {
+                let x = self.pop()?.integer()?;
+                if x.is_negative() {
+                    return Err(VmError::invalid_operation("value can not be negative"));
                 }
-            OpCode::MAX =>
-                {
-                    let
-                        x2 = Pop().integer();
-                    let
-                        x1 = Pop().integer();
-                    Push(BigInt.Max(x1, x2));
+                self.push_integer(Integer::new(x.sqrt())?);
+            }
+            OpCode::SHL | OpCode::SHR => {
+                let shift = self.pop_i32()?;
+                self.limits.assert_shift(shift)?;
+                if shift != 0 {
+                    let x = self.pop()?.integer()?;
+                    self.push_integer(Integer::new(match opcode {
+                        OpCode::SHL => x << shift as usize,
+                        _ => x >> shift as usize,
+                    })?);
                 }
-            OpCode::WITHIN =>
-                {
-                    BigInt
-                    b = Pop().integer();
-                    BigInt
-                    a = Pop().integer();
-                    let
-                        x = Pop().integer();
-                    Push(a < = x && x < b);
+            }
+            OpCode::NOT => {
+                let x = self.pop()?.boolean()?;
+                self.push_boolean(!x);
+            }
+            OpCode::BOOLAND | OpCode::BOOLOR => {
+                let x2 = self.pop()?.boolean()?;
+                let x1 = self.pop()?.boolean()?;
+                self.push_boolean(if opcode == OpCode::BOOLAND { x1 && x2 } else { x1 || x2 });
+            }
+            OpCode::NZ => {
+                let x = self.pop_integer()?;
+                self.push_boolean(!x.is_zero());
+            }
+            OpCode::NUMEQUAL | OpCode::NUMNOTEQUAL => {
+                let x2 = self.pop_integer()?;
+                let x1 = self.pop_integer()?;
+                self.push_boolean((x1 == x2) == (opcode == OpCode::NUMEQUAL));
+            }
+            OpCode::LT | OpCode::LE | OpCode::GT | OpCode::GE => {
+                let x2 = self.pop()?;
+                let x1 = self.pop()?;
+                if x1.is_null() || x2.is_null() {
+                    self.push_boolean(false);
+                } else {
+                    let (x1, x2) = (x1.to_integer()?, x2.to_integer()?);
+                    self.push_boolean(match opcode {
+                        OpCode::LT => x1 < x2,
+                        OpCode::LE => x1 <= x2,
+                        OpCode::GT => x1 > x2,
+                        _ => x1 >= x2,
+                    });
                 }
+            }
+            OpCode::WITHIN => {
+                let b = self.pop_integer()?;
+                let a = self.pop_integer()?;
+                let x = self.pop_integer()?;
+                self.push_boolean(a <= x && x < b);
+            }
 
             // Compound-type
-            OpCode::PACK =>
-                {
-                    int
-                    size = (int)
-                    Pop().integer();
-                    if (size < 0 || size > CurrentContext.EvaluationStack.Count)
-                    throw
-                    new
-                    InvalidOperationException($ "The value {size} is out of range.");
-                    VMArray
-                    array = new(ReferenceCounter);
-                    for (int i = 0; i < size; i + +)
-                    {
-                        StackItem
-                        item = Pop();
-                        array.Add(item);
-                    }
-                    Push(array);
-                }
-            OpCode::UNPACK =>
-                {
-                    VMArray
-                    array = Pop < VMArray > ();
-                    for (int i = array.Count -1; i > = 0; i - -)
-                    Push(array[i]);
-                    Push(array.Count);
-                }
-            OpCode::NEWARRAY0 =>
-                {
-                    Push(new VMArray(ReferenceCounter));
+            OpCode::PACK => {
+                let size = self.pop_index()?;
+                if size > self.context().evaluation_stack().count() {
+                    return Err(VmError::out_of_range(size));
                 }
-            OpCode::NEWARRAY |
-            OpCode::NEWARRAY_T =>
-                {
-                    int
-                    n = (int)
-                    Pop().integer();
-                    if (n < 0 || n > Limits.MaxStackSize)
-                    throw
-                    new
-                    InvalidOperationException($ "MaxStackSize exceed: {n}");
-                    StackItem
-                    item;
-                    if (instruction.OpCode == OpCode.NEWARRAY_T)
-                    {
-                        StackItemType type = (StackItemType)
-                        instruction.TokenU8;
-                        if (!Enum.IsDefined(typeof(StackItemType), type ))
-                        throw
-                        new
-                        InvalidOperationException($ "Invalid type for {instruction.OpCode}: {instruction.TokenU8}");
-                        item = instruction.TokenU8
-                        switch
-                        {
-                            (byte)StackItemType.Boolean => StackItem.False,
-                            (byte)StackItemType.Integer => Integer.Zero,
-                            (byte)StackItemType.ByteString => ByteString.Empty,
-                            _ => StackItem.Null,
-                        };
-                    } else {
-                        item = StackItem.Null;
+                let items = (0..size).map(|_| self.pop()).collect::<Result<_, _>>()?;
+                self.push(Array::new(Some(self.reference_counter.clone()), items).into());
+            }
+            OpCode::UNPACK => {
+                let x = self.pop()?;
+                let count = if let Some(map) = x.as_map() {
+                    for (key, value) in map.keys().into_iter().zip(map.values()).rev() {
+                        self.push(value);
+                        self.push(key);
                     }
-                    Push(new VMArray(ReferenceCounter, Enumerable.Repeat(item, n)));
-                }
-            OpCode::NEWSTRUCT0 =>
-                {
-                    Push(new Struct(ReferenceCounter));
-                }
-            OpCode::NEWSTRUCT =>
-                {
-                    int
-                    n = (int)
-                    Pop().integer();
-                    if (n < 0 || n > Limits.MaxStackSize)
-                    throw
-                    new
-                    InvalidOperationException($ "MaxStackSize exceed: {n}");
-                    Struct
-                    result = new(ReferenceCounter);
-                    for (let i = 0; i < n; i + +)
-                    result.Add(StackItem.Null);
-                    Push(result);
-                }
-            OpCode::NEWMAP =>
-                {
-                    Push(new Map(ReferenceCounter));
-                }
-            OpCode::SIZE =>
-                {
-                    let
-                        x = Pop();
-                    switch(x)
-                    {
-                        case
-                        CompoundType
-                        compound =>
-                        Push(compound.Count);
-                        |
-                        case
-                        PrimitiveType
-                        primitive =>
-                        Push(primitive.Size);
-                        |
-                        case
-                        Buffer
-                        buffer =>
-                        Push(buffer.Size);
-                        |
-                        default =>
-                        throw
-                        new
-                        InvalidOperationException($ "Invalid type for {instruction.OpCode}=> {x.Type}");
+                    map.keys().len()
+                } else if let Some(array) = x.as_array() {
+                    let items = array.items().clone();
+                    for item in items.iter().rev() {
+                        self.push(item.clone());
                     }
-                    |
-                }
-            OpCode::HASKEY =>
-                {
-                    PrimitiveType
-                    key = Pop < PrimitiveType > ();
-                    let
-                        x = Pop();
-                    switch(x)
-                    {
-                        case
-                        VMArray
-                        array =>
-                        {
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0)
-                            throw
-                            new
-                            InvalidOperationException($ "The negative value {index} is invalid for OpCode.{instruction.OpCode}.");
-                            Push(index < array.Count);
-                            |
-                        }
-                        case
-                        Map
-                        map =>
-                        {
-                            Push(map.ContainsKey(key));
-                            |
-                        }
-                        case
-                        Buffer
-                        buffer =>
-                        {
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0)
-                            throw
-                            new
-                            InvalidOperationException($ "The negative value {index} is invalid for OpCode.{instruction.OpCode}.");
-                            Push(index < buffer.Size);
-                            |
-                        }
-                        case
-                        ByteString
-                        array =>
-                        {
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0)
-                            throw
-                            new
-                            InvalidOperationException($ "The negative value {index} is invalid for OpCode.{instruction.OpCode}.");
-                            Push(index < array.Size);
-                            |
+                    items.len()
+                } else {
+                    return Err(VmError::invalid_type(opcode, &x));
+                };
+                self.push_integer(count);
+            }
+            OpCode::NEWARRAY0 => {
+                self.push(Array::new(Some(self.reference_counter.clone()), Vec::new()).into());
+            }
+            OpCode::NEWARRAY | OpCode::NEWARRAY_T => {
+                let n = self.pop_index()?;
+                VmError::check_limit(Limit::MaxStackSize, n, self.limits.max_stack_size() as usize)?;
+                let item = if opcode != OpCode::NEWARRAY_T {
+                    StackItem::Null
+                } else {
+                    match StackItemType::from_u8(instruction.token_u8()) {
+                        Some(StackItemType::Boolean) => StackItem::Boolean(false),
+                        Some(StackItemType::Integer) => StackItem::Integer(Integer::Small(0)),
+                        Some(StackItemType::ByteString) => StackItem::ByteString(ByteString::default()),
+                        Some(_) => StackItem::Null,
+                        None => {
+                            return Err(VmError::invalid_operation(format!(
+                                "Invalid type for NEWARRAY_T: {}",
+                                instruction.token_u8()
+                            )))
                         }
-                        default =>
-                        throw
-                        new
-                        InvalidOperationException($ "Invalid type for {instruction.OpCode}=> {x.Type}");
                     }
-                    |
-                }
-            OpCode::KEYS =>
-                {
-                    Map
-                    map = Pop < Map > ();
-                    Push(new VMArray(ReferenceCounter, map.Keys));
-                    |
-                }
-            OpCode::VALUES =>
-                {
-                    let
-                        x = Pop();
-                    IEnumerable < StackItem > values = x
-                    switch
-                    {
-                        VMArray array => array,
-                        Map map => map.Values,
-                        _ => throw new InvalidOperationException( $ "Invalid type for {instruction.OpCode}=> {x.Type}"),
-                    };
-                    VMArray
-                    newArray = new(ReferenceCounter);
-                    foreach(StackItem item in values)
-                    if (item
-                    is
-                    Struct
-                    s)
-                    newArray.Add(s.Clone());
-                    else
-                    newArray.Add(item);
-                    Push(newArray);
-                    |
-                }
-            OpCode::PICKITEM =>
-                {
-                    PrimitiveType
-                    key = Pop < PrimitiveType > ();
-                    let
-                        x = Pop();
-                    switch(x)
-                    {
-                        case
-                        VMArray
-                        array =>
-                        {
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0 | | index > = array.Count)
-                            throw
-                            new
-                            InvalidOperationException($ "The value {index} is out of range.");
-                            Push(array[index]);
-                            |
-                        }
-                        case
-                        Map
-                        map =>
-                        {
-                            if (!map.TryGetValue(key, out StackItem? value))
-                            throw
-                            new
-                            InvalidOperationException($ "Key not found in {nameof(Map)}");
-                            Push(value);
-                            |
-                        }
-                        case
-                        PrimitiveType
-                        primitive =>
-                        {
-                            ReadOnlySpan < byte > byteArray = primitive.GetSpan();
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0 || index > = byteArray.Length)
-                            throw
-                            new
-                            InvalidOperationException($ "The value {index} is out of range.");
-                            Push((BigInt)byteArray[index]);
-                            |
-                        }
-                        case
-                        Buffer
-                        buffer =>
-                        {
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0 | | index > = buffer.Size)
-                            throw
-                            new
-                            InvalidOperationException($ "The value {index} is out of range.");
-                            Push((BigInt)buffer.InnerBuffer[index]);
-                            |
-                        }
-                        default =>
-                        throw
-                        new
-                        InvalidOperationException($ "Invalid type for {instruction.OpCode}=> {x.Type}");
+                };
+                self.push(Array::new(Some(self.reference_counter.clone()), vec![item; n]).into());
+            }
+            OpCode::NEWSTRUCT0 => {
+                self.push(Struct::new(Some(self.reference_counter.clone()), Vec::new()).into());
+            }
+            OpCode::NEWSTRUCT => {
+                let n = self.pop_index()?;
+                VmError::check_limit(Limit::MaxStackSize, n, self.limits.max_stack_size() as usize)?;
+                self.push(Struct::new(Some(self.reference_counter.clone()), vec![StackItem::Null; n]).into());
+            }
+            OpCode::NEWMAP => {
+                self.push(Map::new(Some(self.reference_counter.clone())).into());
+            }
+            OpCode::SIZE => {
+                let x = self.pop()?;
+                let size = match &x {
+                    StackItem::Array(_) | StackItem::Struct(_) | StackItem::Map(_) => x.count(),
+                    StackItem::Boolean(_) | StackItem::Integer(_) | StackItem::ByteString(_) => x.size()?,
+                    StackItem::Buffer(buffer) => buffer.size(),
+                    _ => return Err(VmError::invalid_type(opcode, &x)),
+                };
+                self.push_integer(size);
+            }
+            OpCode::HASKEY => {
+                let key = self.pop_key()?;
+                let x = self.pop()?;
+                let result = match &x {
+                    StackItem::Map(map) => {
+                        Map::check_key(&key)?;
+                        map.contains_key(&key)
+                    }
+                    StackItem::Array(array) => index_of(&key)? < array.count(),
+                    StackItem::Struct(s) => index_of(&key)? < s.count(),
+                    StackItem::Buffer(buffer) => index_of(&key)? < buffer.size(),
+                    StackItem::ByteString(byte_string) => index_of(&key)? < byte_string.memory().len(),
+                    _ => return Err(VmError::invalid_type(opcode, &x)),
+                };
+                self.push_boolean(result);
+            }
+            OpCode::KEYS => {
+                let x = self.pop()?;
+                let keys = match x.as_map() {
+                    Some(map) => map.keys(),
+                    None => return Err(VmError::invalid_type(opcode, &x)),
+                };
+                self.push(Array::new(Some(self.reference_counter.clone()), keys).into());
+            }
+            OpCode::VALUES => {
+                let x = self.pop()?;
+                let values = if let Some(array) = x.as_array() {
+                    array.items().clone()
+                } else if let Some(map) = x.as_map() {
+                    map.values()
+                } else {
+                    return Err(VmError::invalid_type(opcode, &x));
+                };
+                let values = values
+                    .into_iter()
+                    .map(|item| self.clone_if_struct(item))
+                    .collect::<Result<_, _>>()?;
+                self.push(Array::new(Some(self.reference_counter.clone()), values).into());
+            }
+            OpCode::PICKITEM => {
+                let key = self.pop_key()?;
+                let x = self.pop()?;
+                if let Some(array) = x.as_array() {
+                    let index = index_of(&key)?;
+                    if index >= array.count() {
+                        return Err(VmError::out_of_range(index));
+                    }
+                    self.push(array.get(index));
+                } else if let Some(map) = x.as_map() {
+                    Map::check_key(&key)?;
+                    match map.get(&key) {
+                        Some(value) => self.push(value),
+                        None => return Err(VmError::invalid_operation("Key not found in Map")),
                     }
-                    |
+                } else if x.is_primitive() || x.as_buffer().is_some() {
+                    let span = x.span()?;
+                    let index = index_of(&key)?;
+                    if index >= span.len() {
+                        return Err(VmError::out_of_range(index));
+                    }
+                    self.push_integer(span[index]);
+                } else {
+                    return Err(VmError::invalid_type(opcode, &x));
                 }
-            OpCode::APPEND =>
-                {
-                    StackItem
-                    newItem = Pop();
-                    VMArray
-                    array = Pop < VMArray > ();
-                    if (newItem
-                    is
-                    Struct
-                    s) newItem = s.Clone();
-                    array.Add(newItem);
-                    |
+            }
+            OpCode::APPEND => {
+                let new_item = self.pop()?;
+                let new_item = self.clone_if_struct(new_item)?;
+                let x = self.pop()?;
+                match x.as_array() {
+                    Some(array) => array.add(new_item),
+                    None => return Err(VmError::invalid_type(opcode, &x)),
                 }
-            OpCode::SETITEM =>
-                {
-                    StackItem
-                    value = Pop();
-                    if (value
-                    is
-                    Struct
-                    s) value = s.Clone();
-                    PrimitiveType
-                    key = Pop < PrimitiveType > ();
-                    let
-                        x = Pop();
-                    switch(x)
-                    {
-                        case
-                        VMArray
-                        array =>
-                        {
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0 | | index > = array.Count)
-                            throw
-                            new
-                            InvalidOperationException($ "The value {index} is out of range.");
-                            array[index] = value;
-                            |
-                        }
-                        case
-                        Map
-                        map =>
-                        {
-                            map[key] = value;
-                            |
+            }
+            OpCode::SETITEM => {
+                let value = self.pop()?;
+                let value = self.clone_if_struct(value)?;
+                let key = self.pop_key()?;
+                let x = self.pop()?;
+                if let Some(array) = x.as_array() {
+                    let index = index_of(&key)?;
+                    if index >= array.count() {
+                        return Err(VmError::out_of_range(index));
+                    }
+                    array.set(index, value);
+                } else if let Some(map) = x.as_map() {
+                    Map::check_key(&key)?;
+                    map.set(key, value);
+                } else if let Some(buffer) = x.as_buffer() {
+                    let index = index_of(&key)?;
+                    if index >= buffer.size() {
+                        return Err(VmError::out_of_range(index));
+                    }
+                    if !value.is_primitive() {
+                        return Err(VmError::invalid_operation("Value must be a primitive type in SETITEM"));
+                    }
+                    let b = value.to_integer()?;
+                    match b.to_i64() {
+                        Some(b) if b >= i8::MIN as i64 && b <= u8::MAX as i64 => {
+                            buffer.inner_buffer_mut()[index] = b as u8;
                         }
-                        case
-                        Buffer
-                        buffer =>
-                        {
-                            int
-                            index = (int)
-                            key.integer();
-                            if (index < 0 | | index > = buffer.Size)
-                            throw
-                            new
-                            InvalidOperationException($ "The value {index} is out of range.");
-                            if (value
-                            is
-                            not
-                            PrimitiveType
-                            p)
-                            throw
-                            new
-                            InvalidOperationException($ "Value must be a primitive type in {instruction.OpCode}");
-                            int
-                            b = (int)
-                            p.integer();
-                            if (b < sbyte.MinValue || b > byte.MaxValue)
-                            throw
-                            new
-                            InvalidOperationException($ "Overflow in {instruction.OpCode}, {b} is not a byte type.");
-                            buffer.InnerBuffer[index] = (byte)
-                            b;
+                        _ => {
+                            return Err(VmError::invalid_operation(format!(
+                                "Overflow in SETITEM, {} is not a byte type.",
+                                b
+                            )))
                         }
-                        default =>
-                        // throw
-                        // new
-                        // InvalidOperationException($ "Invalid type for {instruction.OpCode}=> {x.Type}");
                     }
+                } else {
+                    return Err(VmError::invalid_type(opcode, &x));
                 }
-            OpCode::REVERSEITEMS =>
-                {
-                    let
-                        x = Pop();
-                    switch(x)
-                    {
-                        case
-                        VMArray
-                        array =>
-                        array.Reverse();
-                        |
-                        case
-                        Buffer
-                        buffer =>
-                        Array.Reverse(buffer.InnerBuffer);
-                        |
-                        default =>
-                        throw
-                        new
-                        InvalidOperationException($ "Invalid type for {instruction.OpCode}=> {x.Type}");
-                    }
-                    |
+            }
+            OpCode::REVERSEITEMS => {
+                let x = self.pop()?;
+                if let Some(array) = x.as_array() {
+                    array.reverse();
+                } else if let Some(buffer) = x.as_buffer() {
+                    buffer.inner_buffer_mut().reverse();
+                } else {
+                    return Err(VmError::invalid_type(opcode, &x));
                 }
-            OpCode::REMOVE =>
-                {
-                    PrimitiveType
-                    key = Pop < PrimitiveType > ();
-                    let
-                        x = Pop();
-                    switch(x)
-                    {
-                        case
-                        VMArray
-                        array =>
-                        int
-                        index = (int)
-                        key.integer();
-                        if (index < 0 | | index > = array.Count)
-                        throw
-                        new
-                        InvalidOperationException($ "The value {index} is out of range.");
-                        array.RemoveAt(index);
-                        |
-                        case
-                        Map
-                        map =>
-                        map.Remove(key);
-                        |
-                        default =>
-                        throw
-                        new
-                        InvalidOperationException($ "Invalid type for {instruction.OpCode}=> {x.Type}");
+            }
+            OpCode::REMOVE => {
+                let key = self.pop_key()?;
+                let x = self.pop()?;
+                if let Some(array) = x.as_array() {
+                    let index = index_of(&key)?;
+                    if index >= array.count() {
+                        return Err(VmError::out_of_range(index));
                     }
+                    array.remove_at(index);
+                } else if let Some(map) = x.as_map() {
+                    Map::check_key(&key)?;
+                    map.remove(&key);
+                } else {
+                    return Err(VmError::invalid_type(opcode, &x));
                 }
-            OpCode::CLEARITEMS =>
-                {
-                    CompoundType
-                    x = Pop < CompoundType > ();
-                    x.Clear();
-                }
-            OpCode::POPITEM =>
-                {
-                    VMArray
-                    x = Pop < VMArray > ();
-                    int
-                    index = x.Count - 1;
-                    Push(x[index]);
-                    x.RemoveAt(index);
-                    |
+            }
+            OpCode::CLEARITEMS => {
+                let x = self.pop()?;
+                if !x.is_compound() {
+                    return Err(VmError::invalid_type(opcode, &x));
                 }
+                x.clear();
+            }
+            OpCode::POPITEM => {
+                let x = self.pop()?;
+                let array = match x.as_array() {
+                    Some(array) if array.count() > 0 => array,
+                    Some(_) => return Err(VmError::out_of_range(-1)),
+                    None => return Err(VmError::invalid_type(opcode, &x)),
+                };
+                let index = array.count() - 1;
+                self.push(array.get(index));
+                array.remove_at(index);
+            }
 
             //Types
-            OpCode::ISNULL =>
-                {
-                    let x = Pop();
-                    Push(x.IsNull);
-                    |
-                }
-            OpCode::ISTYPE =>
-                {
-                    let x = Pop();
-                    StackItemType type = (StackItemType)
-                    instruction.TokenU8;
-                    if ( type == StackItemType.Any || !Enum.IsDefined(typeof(StackItemType), type ))
-                    throw
-                    new
-                    InvalidOperationException($ "Invalid type: {type}");
-                    Push(x.Type == type );
-                    |
-                }
-            OpCode::CONVERT =>
-                {
-                    let x = Pop();
-                    Push(x.ConvertTo((StackItemType)instruction.TokenU8));
-                    |
-                }
-
-            _ => unreachable!();
+            OpCode::ISNULL => {
+                let x = self.pop()?;
+                self.push_boolean(x.is_null());
+            }
+            OpCode::ISTYPE => {
+                let x = self.pop()?;
+                let typ = match StackItemType::from_u8(instruction.token_u8()) {
+                    Some(typ) if typ != StackItemType::Any => typ,
+                    _ => {
+                        return Err(VmError::invalid_operation(format!(
+                            "Invalid type: {}",
+                            instruction.token_u8()
+                        )))
+                    }
+                };
+                self.push_boolean(x.get_type() == typ);
+            }
+            OpCode::CONVERT => {
+                let x = self.pop()?;
+                let typ = match StackItemType::from_u8(instruction.token_u8()) {
+                    Some(typ) => typ,
+                    None => {
+                        return Err(VmError::invalid_operation(format!(
+                            "Invalid type: {}",
+                            instruction.token_u8()
+                        )))
+                    }
+                };
+                self.push(x.convert_to(typ)?);
+            }
 
+            _ => {
+                return Err(VmError::BadScript {
+                    ip: self.context().instruction_pointer(),
+                    opcode,
+                    message: "undefined opcode".to_string(),
+                })
+            }
         }
+        Ok(())
     }
 
-    fn execute_end_try(&self,endOffset:i32)
-    {
-    if self.current_context?.try_stack() {
-        panic!()
-    };
-    if !self.current_context.try_stack.TryPeek(out ExceptionHandlingContext ? currentTry) {
-        panic!();
-    }
-
-    if self.current_try.state == ExceptionHandlingState.Finally {
-        panic!();
+    /// The offset of a jump, call or ENDTRY instruction, from its short or long form.
+    fn jump_offset(instruction: &Instruction) -> i32 {
+        if instruction.operand().len() == 1 {
+            instruction.token_i8() as i32
+        } else {
+            instruction.token_i32()
+        }
     }
 
-
-    let endPointer = checked(CurrentContext.InstructionPointer + endOffset);
-    if (currentTry.HasFinally)
-    {
-    currentTry.State = ExceptionHandlingState.Finally;
-    currentTry.EndPointer = endPointer;
-    CurrentContext.InstructionPointer = currentTry.FinallyPointer;
-    }
-    else
-    {
-    CurrentContext.try_stack.Pop();
-    CurrentContext.InstructionPointer = endPointer;
-    }
-    self.is_jumping = true;
+    fn execute_end_try(&mut self, end_offset: i32) -> Result<(), VmError> {
+        let end_pointer = self.context().instruction_pointer() as i64 + end_offset as i64;
+        let end_pointer = usize::try_from(end_pointer).map_err(|_| VmError::out_of_range(end_pointer))?;
+        let context = self.context_mut();
+        let current_try = match context.try_stack_mut().as_mut().and_then(|stack| stack.last_mut()) {
+            Some(current_try) => current_try,
+            None => {
+                return Err(VmError::invalid_operation(
+                    "The corresponding TRY block cannot be found.",
+                ))
+            }
+        };
+        if current_try.state() == ExceptionHandlingState::Finally {
+            return Err(VmError::invalid_operation(
+                "The opcode ENDTRY can't be executed in a FINALLY block.",
+            ));
+        }
+        match current_try.finally_pointer() {
+            Some(finally_pointer) => {
+                current_try.set_state(ExceptionHandlingState::Finally);
+                current_try.set_end_pointer(Some(end_pointer));
+                context.set_instruction_pointer(finally_pointer)?;
+            }
+            None => {
+                context.try_stack_mut().as_mut().unwrap().pop();
+                context.set_instruction_pointer(end_pointer)?;
+            }
+        }
+        self.is_jumping = true;
+        Ok(())
     }
 
     /// <summary>
@@ -1496,12 +1073,16 @@ impl ExecutionEngine {
     /// </summary>
     /// <param name="position">The position to jump to.</param>
     #[inline]
-    fn execute_jump(&mut self, position: i32)
-    {
-        if position < 0 || position > self.current_context?.script().length() as i32 { panic!(); }
-
-        self.current_context?.InstructionPointer = position;
+    fn execute_jump(&mut self, position: i64) -> Result<(), VmError> {
+        if position < 0 || position as usize > self.context().script().length() {
+            return Err(VmError::invalid_operation(format!(
+                "Jump out of range for position: {}",
+                position
+            )));
+        }
+        self.context_mut().set_instruction_pointer(position as usize)?;
         self.is_jumping = true;
+        Ok(())
     }
 
     /// <summary>
@@ -1509,130 +1090,151 @@ impl ExecutionEngine {
     /// </summary>
     /// <param name="offset">The offset from the current position to jump to.</param>
     #[inline]
-    fn execute_jump_offset(&mut self, offset: i32)
-    {
-        self.execute_jump(checked(self.current_context?.InstructionPointer + offset));
+    fn execute_jump_offset(&mut self, offset: i32) -> Result<(), VmError> {
+        self.execute_jump(self.context().instruction_pointer() as i64 + offset as i64)
     }
 
-    fn execute_load_from_slot(&mut self, slot: Option<Slot>, index: i32)
-    {
-        if slot.is_none() { panic!(); }
-
-        if index < 0 || index >= slot.unwrap().Count { panic!(); }
+    fn with_slot<R>(&mut self, kind: SlotKind, index: usize, f: impl FnOnce(&mut Slot) -> R) -> Result<R, VmError> {
+        let context = self.context_mut();
+        let mut static_fields;
+        let slot = match kind {
+            SlotKind::Static => {
+                static_fields = context.static_fields_mut();
+                static_fields.as_mut()
+            }
+            SlotKind::Local => context.local_variables_mut().as_mut(),
+            SlotKind::Argument => context.arguments_mut().as_mut(),
+        };
+        match slot {
+            None => Err(VmError::invalid_operation("Slot has not been initialized.")),
+            Some(slot) if index >= slot.count() => Err(VmError::invalid_operation(format!(
+                "Index out of range when accessing slot: {}",
+                index
+            ))),
+            Some(slot) => Ok(f(slot)),
+        }
+    }
 
-        self.push(slot[index]);
+    fn execute_load_from_slot(&mut self, kind: SlotKind, index: usize) -> Result<(), VmError> {
+        let item = self.with_slot(kind, index, |slot| slot.get(index))?;
+        self.push(item);
+        Ok(())
     }
 
     /// <summary>
     /// Execute the next instruction.
     /// </summary>
-    fn execute_next(&mut self)
-    {
-        if (InvocationStack.Count == 0)
-        {
-            State = VMState.HALT;
-        } else {
-            try
-                {
-                    ExecutionContext
-                    context = CurrentContext!;
-                    PreExecuteInstruction();
-                    ExecuteInstruction();
-                    PostExecuteInstruction();
-                    if (!self.is_jumping)
-                    context.MoveNext();
-                    self.is_jumping = false;
-                }
-            catch(Exception e)
-            {
-                OnFault(e);
-            }
+    pub(crate) fn execute_next(&mut self) {
+        if self.invocation_stack.is_empty() {
+            self.set_state(VMState::HALT);
+            return;
+        }
+        if let Err(error) = self.try_execute_next() {
+            self.on_fault(error);
         }
     }
 
-    fn execute_store_to_slot(&mut self, slot: Option<Slot>, index: i32)
-    {
-        if slot.is_none() { panic!(); }
-
-        if index < 0 || index >= slot.Count { panic!(); }
+    fn try_execute_next(&mut self) -> Result<(), VmError> {
+        let index = self.invocation_stack.len() - 1;
+        let instruction = self.context().current_instruction()?;
+        self.pre_execute_instruction(&instruction)?;
+        self.execute_instruction(&instruction)?;
+        self.post_execute_instruction(&instruction)?;
+        if !self.is_jumping {
+            self.invocation_stack[index].move_next()?;
+        }
+        self.is_jumping = false;
+        Ok(())
+    }
 
-        slot[index] = Pop();
+    fn execute_store_to_slot(&mut self, kind: SlotKind, index: usize) -> Result<(), VmError> {
+        self.with_slot(kind, index, |_| ())?;
+        let value = self.pop()?;
+        self.with_slot(kind, index, |slot| slot.set(index, value))
     }
 
     /// <summary>
     /// Throws a specified exception in the VM.
     /// </summary>
     /// <param name="ex">The exception to be thrown.</param>
-    fn execute_throw(&mut self, ex: &StackItem)
-    {
-        self.uncaught_exception = ex;
-        self.handle_exception();
+    fn execute_throw(&mut self, ex: StackItem) -> Result<(), VmError> {
+        self.notify(|observer, engine| observer.exception_thrown(engine, &ex));
+        self.uncaught_exception = Some(ex);
+        self.handle_exception()
     }
 
     #[inline]
-    fn execute_try(&mut self, catchOffset: i32, finallyOffset: i32)
-    {
-        if catchOffset == 0 && finallyOffset == 0 { panic!(); }
-
-        if self.current_context.unwrap().try_stack().is_none() {
-            CurrentContext.try_stack = new Stack < ExceptionHandlingContext > ();
-        } else if CurrentContext.try_stack.Count > = Limits.MaxTryNestingDepth { panic!(); }
-
-        int catchPointer = catchOffset == 0? - 1: checked(CurrentContext.InstructionPointer + catchOffset);
-        int finallyPointer = finallyOffset == 0? - 1: checked(CurrentContext.InstructionPointer + finallyOffset);
-        CurrentContext.try_stack.Push(new ExceptionHandlingContext(catchPointer, finallyPointer));
+    fn execute_try(&mut self, catch_offset: i32, finally_offset: i32) -> Result<(), VmError> {
+        if catch_offset == 0 && finally_offset == 0 {
+            return Err(VmError::invalid_operation(
+                "catchOffset and finallyOffset can't be 0 in a TRY block",
+            ));
+        }
+        let max_try_nesting_depth = self.limits.max_try_nesting_depth() as usize;
+        let instruction_pointer = self.context().instruction_pointer() as i64;
+        // A pointer before the script start means the block is missing, like a zero offset.
+        let pointer = |offset: i32| match offset {
+            0 => None,
+            _ => usize::try_from(instruction_pointer + offset as i64).ok(),
+        };
+        let try_context = ExceptionHandlingContext::new(pointer(catch_offset), pointer(finally_offset));
+        let try_stack = self.context_mut().try_stack_mut().get_or_insert_with(Vec::new);
+        VmError::check_limit(Limit::MaxTryNestingDepth, try_stack.len() + 1, max_try_nesting_depth)?;
+        try_stack.push(try_context);
+        Ok(())
     }
 
-    fn handle_exception(&mut self)
-    {
-        let mut pop = 0;
-        for (executionContext in self.invocation_stack)
-        {
-            if executionContext.try_stack.is_some()
-            {
-                while executionContext.try_stack.TryPeek(out var tryContext)
-                {
-                    if tryContext.State == ExceptionHandlingState.Finally || (tryContext.State == ExceptionHandlingState.Catch && !tryContext.HasFinally)
-                    {
-                        executionContext.try_stack.Pop();
-                        continue;
-                    }
-                    for i in 0..pop
-                    {
-                        ContextUnloaded(InvocationStack.Pop());
-                    }
-                    if tryContext.State == ExceptionHandlingState.Try && tryContext.HasCatch
-                    {
-                        tryContext.State = ExceptionHandlingState.Catch;
-                        Push(uncaught_exception!);
-                        executionContext.InstructionPointer = tryContext.CatchPointer;
-                        uncaught_exception = null;
-                    } else {
-                        tryContext.State = ExceptionHandlingState.Finally;
-                        executionContext.InstructionPointer = tryContext.FinallyPointer;
-                    }
-                    self.is_jumping = true;
-                    return;
-                }
+    fn handle_exception(&mut self) -> Result<(), VmError> {
+        for depth in (0..self.invocation_stack.len()).rev() {
+            let try_stack = match self.invocation_stack[depth].try_stack_mut() {
+                Some(try_stack) => try_stack,
+                None => continue,
+            };
+            let handler = loop {
+                let try_context = match try_stack.last_mut() {
+                    Some(try_context) => try_context,
+                    None => break None,
+                };
+                if try_context.state() == ExceptionHandlingState::Finally
+                    || (try_context.state() == ExceptionHandlingState::Catch && !try_context.has_finally())
+                {
+                    try_stack.pop();
+                } else if try_context.state() == ExceptionHandlingState::Try && try_context.has_catch() {
+                    try_context.set_state(ExceptionHandlingState::Catch);
+                    break try_context.catch_pointer().map(|pointer| (pointer, true));
+                } else {
+                    try_context.set_state(ExceptionHandlingState::Finally);
+                    break try_context.finally_pointer().map(|pointer| (pointer, false));
+                }
+            };
+            if let Some((pointer, caught)) = handler {
+                while self.invocation_stack.len() > depth + 1 {
+                    let context = self.invocation_stack.pop().unwrap();
+                    self.context_unloaded(context);
+                }
+                if caught {
+                    let ex = self.uncaught_exception.take().unwrap();
+                    self.notify(|observer, engine| observer.exception_caught(engine, &ex));
+                    self.push(ex);
+                }
+                self.context_mut().set_instruction_pointer(pointer)?;
+                self.is_jumping = true;
+                return Ok(());
             }
-            pop += 1;
         }
 
-        panic!()
+        Err(VmError::Unhandled(self.uncaught_exception.clone().unwrap()))
     }
 
     /// <summary>
     /// Loads the specified context into the invocation stack.
     /// </summary>
     /// <param name="context">The context to load.</param>
-    fn LoadContext(&mut self, context: &ExecutionContext)
-    {
-        if self.invocation_stack.Count >= self.limits.max_invocation_stack_size() {panic!();}
-
-        InvocationStack.Push(context);
-        if (self.EntryContext.is_none()) {EntryContext = context;}
-
-        self.current_context = Some(context.clone());
+    /// The <see cref="ExecutionEngineLimits.max_invocation_stack_size"/> is checked once the instruction loading it is executed.
+    pub fn load_context(&mut self, context: ExecutionContext) -> &mut ExecutionContext {
+        self.invocation_stack.push(context);
+        self.notify(|observer, engine| observer.context_loaded(engine));
+        self.context_mut()
     }
 
     /// <summary>
@@ -1642,64 +1244,50 @@ impl ExecutionEngine {
     /// <param name="rvcount">The number of values that the context should return when it is unloaded.</param>
     /// <param name="initialPosition">The pointer indicating the current instruction.</param>
     /// <returns>The created context.</returns>
-    // [MethodImpl(MethodImplOptions.AggressiveInlining)]
-    fn  CreateContext( script:&Script, rvcount:i32, initialPosition:i32)->ExecutionContext
-    {
-      let mut ctx = ExecutionContext::new(script, rvcount, ReferenceCounter);
-        ctx.set_instructionPointer(initialPosition);
-        ctx
+    /// <exception cref="VmError::InvalidOperation">The position is out of the script.</exception>
+    pub fn create_context(
+        &self,
+        script: Rc<Script>,
+        rv_count: i32,
+        initial_position: usize,
+    ) -> Result<ExecutionContext, VmError> {
+        let mut context = ExecutionContext::new(script, rv_count, self.reference_counter.clone());
+        context.set_instruction_pointer(initial_position)?;
+        Ok(context)
     }
 
     /// <summary>
     /// Create a new context with the specified script and load it.
     /// </summary>
     /// <param name="script">The script used to create the context.</param>
-    /// <param name="rvcount">The number of values that the context should return when it is unloaded.</param>
+    /// <param name="rvcount">The number of values that the context should return when it is unloaded, -1 for any.</param>
     /// <param name="initialPosition">The pointer indicating the current instruction.</param>
     /// <returns>The created context.</returns>
-    pub fn LoadScript(script: &Script, rvcount: i32,  initialPosition: i32) -> ExecutionContext
-    {
-        let mut context = CreateContext { script, rvcount, initialPosition };
-        LoadContext(context);
-        context
-    }
-
-    /// <summary>
-    /// When overridden in a derived class, loads the specified method token.
-    /// Called when <see cref="OpCode.CALLT"/> is executed.
-    /// </summary>
-    /// <param name="token">The method token to be loaded.</param>
-    /// <returns>The created context.</returns>
-    fn LoadToken(token: u16) -> ExecutionContext
-    {
-        panic!();
-        // throw new InvalidOperationException( $ "Token not found: {token}");
+    /// <exception cref="VmError::InvalidOperation">The position is out of the script.</exception>
+    pub fn load_script(
+        &mut self,
+        script: impl Into<Rc<Script>>,
+        rv_count: i32,
+        initial_position: usize,
+    ) -> Result<&mut ExecutionContext, VmError> {
+        let context = self.create_context(script.into(), rv_count, initial_position)?;
+        Ok(self.load_context(context))
     }
 
     /// <summary>
     /// Called when an exception that cannot be caught by the VM is thrown.
     /// </summary>
-    /// <param name="ex">The exception that caused the <see cref="VMState.FAULT"/> state.</param>
-    fn OnFault(ex: Exception)
-    {
-        State = VMState.FAULT;
+    fn on_fault(&mut self, error: VmError) {
+        self.fault_exception = Some(error);
+        self.set_state(VMState::FAULT);
     }
 
     /// <summary>
     /// Called when the state of the VM changed.
     /// </summary>
-    fn OnStateChanged()
-    {}
-
-    /// <summary>
-    /// When overridden in a derived class, invokes the specified system call.
-    /// Called when <see cref="OpCode.SYSCALL"/> is executed.
-    /// </summary>
-    /// <param name="method">The system call to be invoked.</param>
-    fn OnSysCall(method: u32)
-    {
-        panic!();
-        // throw new InvalidOperationException( $ "Syscall not found: {method}");
+    /// <param name="previous">The state before the change.</param>
+    fn on_state_changed(&mut self, previous: VMState) {
+        self.notify(|observer, engine| observer.state_changed(engine, previous));
     }
 
     /// <summary>
@@ -1707,54 +1295,105 @@ impl ExecutionEngine {
     /// </summary>
     /// <param name="index">The index of the object from the top of the stack.</param>
     /// <returns>The item at the specified index.</returns>
-    pub fn Peek(&self, index: Option<i32>) -> Box<StackItem>
-    {
-        match index {
-            Some(val) => self.current_context.unwrap().EvaluationStack.Peek(index),
-            None => { self.current_context.unwrap().EvaluationStack.Peek(0) }
-        }
+    pub fn peek(&self, index: usize) -> Result<StackItem, VmError> {
+        self.context().evaluation_stack().peek(index)
     }
 
     /// <summary>
     /// Removes and returns the item at the top of the current stack.
     /// </summary>
     /// <returns>The item removed from the top of the stack.</returns>
-    pub fn Pop(&mut self) -> Box<StackItem>
-    {
-        self.current_context.unwrap().EvaluationStack.Pop()
+    pub fn pop(&mut self) -> Result<StackItem, VmError> {
+        self.context().evaluation_stack_mut().pop()
+    }
+
+    /// Pops an integer that must fit in an <see cref="int"/>.
+    fn pop_i32(&mut self) -> Result<i32, VmError> {
+        let x = self.pop_integer()?;
+        x.to_i64().and_then(|x| i32::try_from(x).ok()).ok_or_else(|| {
+            VmError::invalid_operation(format!("Value was either too large or too small for an Int32: {}", x))
+        })
+    }
+
+    /// Pops an item converted to an integer.
+    fn pop_integer(&mut self) -> Result<Integer, VmError> {
+        self.pop()?.to_integer()
+    }
+
+    /// Pops an integer used as a count or an index, which can't be negative.
+    fn pop_index(&mut self) -> Result<usize, VmError> {
+        let x = self.pop_i32()?;
+        usize::try_from(x).map_err(|_| VmError::out_of_range(x))
+    }
+
+    /// Pops the key of a compound-type instruction, which must be a primitive type.
+    fn pop_key(&mut self) -> Result<StackItem, VmError> {
+        let key = self.pop()?;
+        if !key.is_primitive() {
+            return Err(VmError::invalid_operation(format!(
+                "Invalid key type: {:?}",
+                key.get_type()
+            )));
+        }
+        Ok(key)
+    }
+
+    fn clone_if_struct(&self, item: StackItem) -> Result<StackItem, VmError> {
+        match item {
+            StackItem::Struct(s) => Ok(s.clone_struct(&self.limits)?.into()),
+            item => Ok(item),
+        }
     }
 
     /// <summary>
-    /// Removes and returns the item at the top of the current stack and convert it to the specified type.
+    /// Called after an instruction is executed.
     /// </summary>
-    /// <typeparam name="T">The type to convert to.</typeparam>
-    /// <returns>The item removed from the top of the stack.</returns>
-    // pub fn T Pop<T>(&mut self) where T : StackItem
-    // {
-    //     self.current_context!.EvaluationStack.Pop < T >();
-    // }
+    fn post_execute_instruction(&mut self, instruction: &Instruction) -> Result<(), VmError> {
+        VmError::check_limit(
+            Limit::MaxInvocationStackSize,
+            self.invocation_stack.len(),
+            self.limits.max_invocation_stack_size() as usize,
+        )?;
+        VmError::check_limit(
+            Limit::MaxStackSize,
+            self.reference_counter.check_zero_referred(),
+            self.limits.max_stack_size() as usize,
+        )?;
+        self.notify(|observer, engine| observer.post_execute_instruction(engine, instruction));
+        Ok(())
+    }
 
     /// <summary>
-    /// Called after an instruction is executed.
+    /// Called before an instruction is executed.
     /// </summary>
-    // protected virtual void PostExecuteInstruction()
-    // {
-    // if (ReferenceCounter.CheckZeroReferred() > Limits.MaxStackSize)
-    // throw new InvalidOperationException( $ "MaxStackSize exceed: {ReferenceCounter.count}");
-    // }
-    //
-    // /// <summary>
-    // /// Called before an instruction is executed.
-    // /// </summary>
-    // protected virtual void PreExecuteInstruction() {}
+    fn pre_execute_instruction(&mut self, instruction: &Instruction) -> Result<(), VmError> {
+        self.notify(|observer, engine| observer.pre_execute_instruction(engine, instruction));
+        H::pre_execute_instruction(self, instruction)
+    }
 
     /// <summary>
     /// Pushes an item onto the top of the current stack.
     /// </summary>
     /// <param name="item">The item to be pushed.</param>
-    pub fn Push(&mut self, item: Box<StackItem>)
-    {
-        self.current_context?.EvaluationStack().Push(item);
+    pub fn push(&mut self, item: StackItem) {
+        self.context().evaluation_stack_mut().push(item);
+    }
+
+    pub fn push_integer<T: Into<Integer>>(&mut self, value: T) {
+        self.push(StackItem::Integer(value.into()));
     }
+
+    pub fn push_boolean(&mut self, value: bool) {
+        self.push(StackItem::Boolean(value));
+    }
+}
+
+/// An index given as a primitive item, which can't be negative.
+fn index_of(key: &StackItem) -> Result<usize, VmError> {
+    let index = key.to_integer()?;
+    index
+        .to_i64()
+        .filter(|index| *index <= i32::MAX as i64)
+        .and_then(|index| usize::try_from(index).ok())
+        .ok_or_else(|| VmError::out_of_range(index))
 }
-// }
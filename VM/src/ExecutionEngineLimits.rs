@@ -1,35 +1,40 @@
-use getset::{CopyGetters, Getters, MutGetters, Setters};
+use getset::{CopyGetters, Setters};
 
-#[derive(Getters, Setters, MutGetters, CopyGetters,Debug, Clone, Default, Eq, PartialEq)]
+use crate::VmError::{Limit, VmError};
+
+/// <summary>
+/// Represents the restrictions on the VM.
+/// </summary>
+#[derive(CopyGetters, Setters, Debug, Clone, Eq, PartialEq)]
 pub struct ExecutionEngineLimits {
     /// <summary>
     /// The maximum number of bits that <see cref="OpCode.SHL"/> and <see cref="OpCode.SHR"/> can shift.
     /// </summary>
-    #[getset(get="pub", set)]
+    #[getset(get_copy = "pub", set = "pub")]
     max_shift: i32,
 
     /// <summary>
     /// The maximum number of items that can be contained in the VM's evaluation stacks and slots.
     /// </summary>
-    #[getset(get="pub", set)]
+    #[getset(get_copy = "pub", set = "pub")]
     max_stack_size: u32,
 
     /// <summary>
     /// The maximum size of an item in the VM.
     /// </summary>
-    #[getset(get="pub", set)]
+    #[getset(get_copy = "pub", set = "pub")]
     max_item_size: u32,
 
     /// <summary>
     /// The maximum number of frames in the invocation stack of the VM.
     /// </summary>
-    #[getset(get="pub", set)]
+    #[getset(get_copy = "pub", set = "pub")]
     max_invocation_stack_size: u32,
 
     /// <summary>
     /// The maximum nesting depth of <see langword="try"/>-<see langword="catch"/>-<see langword="finally"/> blocks.
     /// </summary>
-    #[getset(get="pub", set)]
+    #[getset(get_copy = "pub", set = "pub")]
     max_try_nesting_depth: u32,
 }
 
@@ -45,34 +50,23 @@ impl Default for ExecutionEngineLimits {
     }
 }
 
-/// <summary>
-/// Represents the restrictions on the VM.
-/// </summary>
-impl ExecutionEngineLimits
-{
+impl ExecutionEngineLimits {
     /// <summary>
     /// Assert that the size of the item meets the limit.
     /// </summary>
     /// <param name="size">The size to be checked.</param>
-    fn assert_max_item_size(&self, size: u32)
-    {
-        if size < 0 || size > self.max_item_size
-        {
-            panic!();
-            // throw new InvalidOperationException( $ "MaxItemSize exceed: {size}");
-        }
+    pub fn assert_max_item_size(&self, size: usize) -> Result<(), VmError> {
+        VmError::check_limit(Limit::MaxItemSize, size, self.max_item_size as usize)
     }
 
     /// <summary>
     /// Assert that the number of bits shifted meets the limit.
     /// </summary>
     /// <param name="shift">The number of bits shifted.</param>
-    fn assert_shift(&self, shift: i32)
-    {
-        if shift > self.max_shift || shift < 0
-        {
-            panic!();
-            // throw new InvalidOperationException( $ "Invalid shift value: {shift}");
+    pub fn assert_shift(&self, shift: i32) -> Result<(), VmError> {
+        if shift < 0 {
+            return Err(VmError::invalid_operation(format!("Invalid shift value: {}", shift)));
         }
+        VmError::check_limit(Limit::MaxShift, shift as usize, self.max_shift.max(0) as usize)
     }
 }
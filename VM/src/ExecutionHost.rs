@@ -0,0 +1,54 @@
+use crate::ExecutionContext::ExecutionContext;
+use crate::ExecutionEngine::ExecutionEngine;
+use crate::Instruction::Instruction;
+use crate::VmError::VmError;
+
+/// <summary>
+/// The environment an <see cref="ExecutionEngine"/> runs in.
+/// </summary>
+///
+/// The hooks take the whole engine, so that the host can reach its own data
+/// through <see cref="ExecutionEngine.host_mut"/> while using the stacks.
+/// An error returned by a hook faults the VM like any other instruction error.
+pub trait ExecutionHost: Sized {
+    /// <summary>
+    /// Invokes the specified system call, called when <see cref="OpCode.SYSCALL"/> is executed.
+    /// </summary>
+    /// <param name="method">The system call to be invoked.</param>
+    fn on_syscall(_engine: &mut ExecutionEngine<Self>, method: u32) -> Result<(), VmError> {
+        Err(VmError::invalid_operation(format!("Syscall not found: {}", method)))
+    }
+
+    /// <summary>
+    /// Loads the specified method token, called when <see cref="OpCode.CALLT"/> is executed.
+    /// </summary>
+    /// <param name="token">The method token to be loaded.</param>
+    /// <returns>The created context.</returns>
+    fn load_token(_engine: &mut ExecutionEngine<Self>, token: u16) -> Result<ExecutionContext, VmError> {
+        Err(VmError::invalid_operation(format!("Token not found: {}", token)))
+    }
+
+    /// <summary>
+    /// Called before an instruction is executed, an error faults the VM without executing it.
+    /// </summary>
+    /// <param name="instruction">The instruction about to be executed.</param>
+    fn pre_execute_instruction(_engine: &mut ExecutionEngine<Self>, _instruction: &Instruction) -> Result<(), VmError> {
+        Ok(())
+    }
+
+    /// <summary>
+    /// Called when a context is unloaded, after its return values were moved to the new current context.
+    /// </summary>
+    /// <param name="context">The context being unloaded.</param>
+    fn context_unloaded(_engine: &mut ExecutionEngine<Self>, _context: &ExecutionContext) {}
+
+    /// <summary>
+    /// The GAS consumed so far, reported by <see cref="JsonTracer"/>, 0 for a host that doesn't charge for execution.
+    /// </summary>
+    fn gas_consumed(&self) -> i64 {
+        0
+    }
+}
+
+/// A bare VM, without system calls nor method tokens.
+impl ExecutionHost for () {}
@@ -0,0 +1,137 @@
+use crate::ExecutionContext::ExecutionContext;
+use crate::ExecutionEngine::ExecutionEngine;
+use crate::ExecutionHost::ExecutionHost;
+use crate::Instruction::Instruction;
+use crate::Types::StackItem::StackItem;
+use crate::VMState::VMState;
+
+/// <summary>
+/// Watches an <see cref="ExecutionEngine"/> run, set with <see cref="ExecutionEngine.set_observer"/>.
+/// </summary>
+///
+/// Unlike the hooks of <see cref="ExecutionHost"/>, the callbacks only read the engine and can't fault it.
+/// An engine without observer only checks that none is set at each callback.
+pub trait ExecutionObserver<H: ExecutionHost> {
+    /// <summary>
+    /// Called before an instruction is executed, and before <see cref="ExecutionHost.pre_execute_instruction"/>.
+    /// </summary>
+    /// <param name="instruction">The instruction about to be executed.</param>
+    fn pre_execute_instruction(&mut self, _engine: &ExecutionEngine<H>, _instruction: &Instruction) {}
+
+    /// <summary>
+    /// Called after an instruction was executed without faulting, before the instruction pointer moves on.
+    /// </summary>
+    /// <param name="instruction">The instruction just executed.</param>
+    fn post_execute_instruction(&mut self, _engine: &ExecutionEngine<H>, _instruction: &Instruction) {}
+
+    /// <summary>
+    /// Called when a context is loaded, it is then the current context.
+    /// </summary>
+    fn context_loaded(&mut self, _engine: &ExecutionEngine<H>) {}
+
+    /// <summary>
+    /// Called when a context is unloaded, after <see cref="ExecutionHost.context_unloaded"/>.
+    /// </summary>
+    /// <param name="context">The context being unloaded.</param>
+    fn context_unloaded(&mut self, _engine: &ExecutionEngine<H>, _context: &ExecutionContext) {}
+
+    /// <summary>
+    /// Called when <see cref="OpCode.THROW"/> throws an exception, before a handler is looked for.
+    /// </summary>
+    /// <param name="exception">The exception thrown.</param>
+    fn exception_thrown(&mut self, _engine: &ExecutionEngine<H>, _exception: &StackItem) {}
+
+    /// <summary>
+    /// Called when an exception is caught, before it is pushed for the catch block.
+    /// </summary>
+    /// <param name="exception">The exception caught.</param>
+    fn exception_caught(&mut self, _engine: &ExecutionEngine<H>, _exception: &StackItem) {}
+
+    /// <summary>
+    /// Called when the state of the VM changed.
+    /// </summary>
+    /// <param name="previous">The state before the change, the new one is <see cref="ExecutionEngine.state"/>.</param>
+    fn state_changed(&mut self, _engine: &ExecutionEngine<H>, _previous: VMState) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::ExecutionEngineLimits::ExecutionEngineLimits;
+    use crate::OpCode::OpCode;
+    use crate::Script::Script;
+
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl ExecutionObserver<()> for Recorder {
+        fn context_loaded(&mut self, engine: &ExecutionEngine) {
+            self.0
+                .borrow_mut()
+                .push(format!("load {}", engine.invocation_stack().len()));
+        }
+
+        fn context_unloaded(&mut self, engine: &ExecutionEngine, _context: &ExecutionContext) {
+            self.0
+                .borrow_mut()
+                .push(format!("unload {}", engine.invocation_stack().len()));
+        }
+
+        fn exception_thrown(&mut self, _engine: &ExecutionEngine, exception: &StackItem) {
+            self.0
+                .borrow_mut()
+                .push(format!("throw {}", exception.string().unwrap()));
+        }
+
+        fn exception_caught(&mut self, _engine: &ExecutionEngine, exception: &StackItem) {
+            self.0
+                .borrow_mut()
+                .push(format!("catch {}", exception.string().unwrap()));
+        }
+
+        fn state_changed(&mut self, engine: &ExecutionEngine, previous: VMState) {
+            self.0
+                .borrow_mut()
+                .push(format!("{:?} -> {:?}", previous, engine.state()));
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut engine = ExecutionEngine::new(ExecutionEngineLimits::default());
+        assert!(engine.set_observer(Some(Box::new(Recorder(events.clone())))).is_none());
+        // TRY, with a catch at 6, then CALL a method throwing "e". RET halts before unloading the entry context.
+        let script = vec![
+            OpCode::TRY.0,
+            6,
+            0,
+            OpCode::CALL.0,
+            5,
+            OpCode::RET.0,
+            OpCode::DROP.0,
+            OpCode::RET.0,
+            OpCode::PUSHDATA1.0,
+            1,
+            b'e',
+            OpCode::THROW.0,
+        ];
+        engine.load_script(Script::from(script), -1, 0).unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+        assert_eq!(
+            *events.borrow(),
+            [
+                "load 1",
+                "BREAK -> NONE",
+                "load 2",
+                "throw e",
+                "unload 1",
+                "catch e",
+                "NONE -> HALT",
+                "unload 0"
+            ]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use serde_json::{json, Map as JsonMap, Value};
+
+use crate::ExecutionEngine::ExecutionEngine;
+use crate::ExecutionHost::ExecutionHost;
+use crate::ExecutionObserver::ExecutionObserver;
+use crate::Instruction::Instruction;
+use crate::Types::StackItem::StackItem;
+
+/// <summary>
+/// An <see cref="ExecutionObserver"/> writing a JSON Lines trace, one line per instruction executed.
+/// </summary>
+///
+/// Each line is taken before the instruction runs, with its offset <c>ip</c>, <c>opcode</c>, the <c>gas</c>
+/// consumed so far from <see cref="ExecutionHost.gas_consumed"/>, the <c>depth</c> of the invocation stack and
+/// the evaluation <c>stack</c> of the current context from the top. Items are <c>{"type", "value"}</c> objects,
+/// integers as strings and bytes as <c>0x</c> hex, like in the neo-vm JSON tests.
+/// A write error stops the trace.
+pub struct JsonTracer<W: Write> {
+    writer: Option<W>,
+}
+
+impl<W: Write> JsonTracer<W> {
+    /// <summary>
+    /// Create a tracer writing to the specified writer.
+    /// </summary>
+    pub fn new(writer: W) -> Self {
+        Self { writer: Some(writer) }
+    }
+}
+
+impl<H: ExecutionHost, W: Write> ExecutionObserver<H> for JsonTracer<W> {
+    fn pre_execute_instruction(&mut self, engine: &ExecutionEngine<H>, instruction: &Instruction) {
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => return,
+        };
+        let context = engine.current_context().unwrap();
+        let stack = context.evaluation_stack();
+        let line = json!({
+            "ip": context.instruction_pointer(),
+            "opcode": instruction.opcode().name().unwrap_or("?"),
+            "gas": engine.host().gas_consumed(),
+            "depth": engine.invocation_stack().len(),
+            "stack": (0..stack.count())
+                .map(|index| item_to_json(&stack.peek(index).unwrap()))
+                .collect::<Vec<_>>(),
+        });
+        if writeln!(writer, "{}", line).is_err() {
+            self.writer = None;
+        }
+    }
+}
+
+/// Converts an item to JSON, in the form of the trace lines. A compound item found inside itself, a cycle,
+/// is written without its value. Used by the JSON test runner of the crate.
+#[doc(hidden)]
+pub fn item_to_json(item: &StackItem) -> Value {
+    to_json(item, &mut HashSet::new())
+}
+
+fn to_json(item: &StackItem, ancestors: &mut HashSet<*const u8>) -> Value {
+    let typ = format!("{:?}", item.get_type());
+    if item.is_compound() && !ancestors.insert(item.address()) {
+        return json!({ "type": typ });
+    }
+    let value = match item {
+        StackItem::Null | StackItem::InteropInterface(_) => return json!({ "type": typ }),
+        StackItem::Boolean(value) => json!(value),
+        StackItem::Integer(value) => json!(value.to_string()),
+        StackItem::ByteString(value) => json!(to_hex(value.memory())),
+        StackItem::Buffer(buffer) => json!(to_hex(buffer.inner_buffer().as_slice())),
+        StackItem::Pointer(pointer) => json!(pointer.position()),
+        StackItem::Array(_) | StackItem::Struct(_) => {
+            Value::Array(item.sub_items().iter().map(|item| to_json(item, ancestors)).collect())
+        }
+        StackItem::Map(map) => {
+            let mut object = JsonMap::new();
+            for (key, value) in map.keys().iter().zip(map.values().iter()) {
+                object.insert(to_hex(&key.span().unwrap()), to_json(value, ancestors));
+            }
+            Value::Object(object)
+        }
+    };
+    ancestors.remove(&item.address());
+    json!({ "type": typ, "value": value })
+}
+
+fn to_hex(data: &[u8]) -> String {
+    let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::ExecutionEngineLimits::ExecutionEngineLimits;
+    use crate::OpCode::OpCode;
+    use crate::Script::Script;
+    use crate::VMState::VMState;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let buffer = SharedBuffer::default();
+        let mut engine = ExecutionEngine::new(ExecutionEngineLimits::default());
+        engine.set_observer(Some(Box::new(JsonTracer::new(buffer.clone()))));
+        let script = vec![
+            OpCode::PUSH1.0,
+            OpCode::PUSHDATA1.0,
+            1,
+            0xab,
+            OpCode::CALL.0,
+            3,
+            OpCode::RET.0,
+            OpCode::NEWARRAY0.0,
+            OpCode::DUP.0,
+            OpCode::DUP.0,
+            OpCode::APPEND.0,
+            OpCode::RET.0,
+        ];
+        engine.load_script(Script::from(script), -1, 0).unwrap();
+        assert_eq!(engine.execute(), VMState::HALT);
+
+        let trace = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        let lines: Vec<Value> = trace.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let opcodes: Vec<&str> = lines.iter().map(|line| line["opcode"].as_str().unwrap()).collect();
+        assert_eq!(
+            opcodes,
+            [
+                "PUSH1",
+                "PUSHDATA1",
+                "CALL",
+                "NEWARRAY0",
+                "DUP",
+                "DUP",
+                "APPEND",
+                "RET",
+                "RET"
+            ]
+        );
+        assert_eq!(
+            lines[2],
+            json!({
+                "ip": 4,
+                "opcode": "CALL",
+                "gas": 0,
+                "depth": 1,
+                "stack": [{ "type": "ByteString", "value": "0xab" }, { "type": "Integer", "value": "1" }],
+            })
+        );
+        assert_eq!(lines[3]["depth"], 2);
+        assert_eq!(lines[3]["ip"], 7);
+        // The array holds itself once APPEND ran.
+        assert_eq!(
+            lines[7]["stack"][0],
+            json!({ "type": "Array", "value": [{ "type": "Array" }] })
+        );
+    }
+}
@@ -1,6 +1,6 @@
 
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct OpCode(pub u8);
 
 /// <summary>
@@ -854,10 +854,239 @@ impl OpCode
 
     //// // #endregion
 }
-// }
-// pub fn toOpCode(tp: usize) -> Result<OpCode); Error> {
-//    match OpCode::try_from(te) {
-//         Ok(tp) => Ok(tp));
-//         Err(_) => Err(()));
-//     }
-// }
\ No newline at end of file
+
+impl OpCode {
+    /// Name of the opcode, `None` if it is not defined.
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            OpCode::PUSHINT8 => Some("PUSHINT8"),
+            OpCode::PUSHINT16 => Some("PUSHINT16"),
+            OpCode::PUSHINT32 => Some("PUSHINT32"),
+            OpCode::PUSHINT64 => Some("PUSHINT64"),
+            OpCode::PUSHINT128 => Some("PUSHINT128"),
+            OpCode::PUSHINT256 => Some("PUSHINT256"),
+            OpCode::PUSHA => Some("PUSHA"),
+            OpCode::PUSHNULL => Some("PUSHNULL"),
+            OpCode::PUSHDATA1 => Some("PUSHDATA1"),
+            OpCode::PUSHDATA2 => Some("PUSHDATA2"),
+            OpCode::PUSHDATA4 => Some("PUSHDATA4"),
+            OpCode::PUSHM1 => Some("PUSHM1"),
+            OpCode::PUSH0 => Some("PUSH0"),
+            OpCode::PUSH1 => Some("PUSH1"),
+            OpCode::PUSH2 => Some("PUSH2"),
+            OpCode::PUSH3 => Some("PUSH3"),
+            OpCode::PUSH4 => Some("PUSH4"),
+            OpCode::PUSH5 => Some("PUSH5"),
+            OpCode::PUSH6 => Some("PUSH6"),
+            OpCode::PUSH7 => Some("PUSH7"),
+            OpCode::PUSH8 => Some("PUSH8"),
+            OpCode::PUSH9 => Some("PUSH9"),
+            OpCode::PUSH10 => Some("PUSH10"),
+            OpCode::PUSH11 => Some("PUSH11"),
+            OpCode::PUSH12 => Some("PUSH12"),
+            OpCode::PUSH13 => Some("PUSH13"),
+            OpCode::PUSH14 => Some("PUSH14"),
+            OpCode::PUSH15 => Some("PUSH15"),
+            OpCode::PUSH16 => Some("PUSH16"),
+            OpCode::NOP => Some("NOP"),
+            OpCode::JMP => Some("JMP"),
+            OpCode::JMP_L => Some("JMP_L"),
+            OpCode::JMPIF => Some("JMPIF"),
+            OpCode::JMPIF_L => Some("JMPIF_L"),
+            OpCode::JMPIFNOT => Some("JMPIFNOT"),
+            OpCode::JMPIFNOT_L => Some("JMPIFNOT_L"),
+            OpCode::JMPEQ => Some("JMPEQ"),
+            OpCode::JMPEQ_L => Some("JMPEQ_L"),
+            OpCode::JMPNE => Some("JMPNE"),
+            OpCode::JMPNE_L => Some("JMPNE_L"),
+            OpCode::JMPGT => Some("JMPGT"),
+            OpCode::JMPGT_L => Some("JMPGT_L"),
+            OpCode::JMPGE => Some("JMPGE"),
+            OpCode::JMPGE_L => Some("JMPGE_L"),
+            OpCode::JMPLT => Some("JMPLT"),
+            OpCode::JMPLT_L => Some("JMPLT_L"),
+            OpCode::JMPLE => Some("JMPLE"),
+            OpCode::JMPLE_L => Some("JMPLE_L"),
+            OpCode::CALL => Some("CALL"),
+            OpCode::CALL_L => Some("CALL_L"),
+            OpCode::CALLA => Some("CALLA"),
+            OpCode::CALLT => Some("CALLT"),
+            OpCode::ABORT => Some("ABORT"),
+            OpCode::ASSERT => Some("ASSERT"),
+            OpCode::THROW => Some("THROW"),
+            OpCode::TRY => Some("TRY"),
+            OpCode::TRY_L => Some("TRY_L"),
+            OpCode::ENDTRY => Some("ENDTRY"),
+            OpCode::ENDTRY_L => Some("ENDTRY_L"),
+            OpCode::ENDFINALLY => Some("ENDFINALLY"),
+            OpCode::RET => Some("RET"),
+            OpCode::SYSCALL => Some("SYSCALL"),
+            OpCode::DEPTH => Some("DEPTH"),
+            OpCode::DROP => Some("DROP"),
+            OpCode::NIP => Some("NIP"),
+            OpCode::XDROP => Some("XDROP"),
+            OpCode::CLEAR => Some("CLEAR"),
+            OpCode::DUP => Some("DUP"),
+            OpCode::OVER => Some("OVER"),
+            OpCode::PICK => Some("PICK"),
+            OpCode::TUCK => Some("TUCK"),
+            OpCode::SWAP => Some("SWAP"),
+            OpCode::ROT => Some("ROT"),
+            OpCode::ROLL => Some("ROLL"),
+            OpCode::REVERSE3 => Some("REVERSE3"),
+            OpCode::REVERSE4 => Some("REVERSE4"),
+            OpCode::REVERSEN => Some("REVERSEN"),
+            OpCode::INITSSLOT => Some("INITSSLOT"),
+            OpCode::INITSLOT => Some("INITSLOT"),
+            OpCode::LDSFLD0 => Some("LDSFLD0"),
+            OpCode::LDSFLD1 => Some("LDSFLD1"),
+            OpCode::LDSFLD2 => Some("LDSFLD2"),
+            OpCode::LDSFLD3 => Some("LDSFLD3"),
+            OpCode::LDSFLD4 => Some("LDSFLD4"),
+            OpCode::LDSFLD5 => Some("LDSFLD5"),
+            OpCode::LDSFLD6 => Some("LDSFLD6"),
+            OpCode::LDSFLD => Some("LDSFLD"),
+            OpCode::STSFLD0 => Some("STSFLD0"),
+            OpCode::STSFLD1 => Some("STSFLD1"),
+            OpCode::STSFLD2 => Some("STSFLD2"),
+            OpCode::STSFLD3 => Some("STSFLD3"),
+            OpCode::STSFLD4 => Some("STSFLD4"),
+            OpCode::STSFLD5 => Some("STSFLD5"),
+            OpCode::STSFLD6 => Some("STSFLD6"),
+            OpCode::STSFLD => Some("STSFLD"),
+            OpCode::LDLOC0 => Some("LDLOC0"),
+            OpCode::LDLOC1 => Some("LDLOC1"),
+            OpCode::LDLOC2 => Some("LDLOC2"),
+            OpCode::LDLOC3 => Some("LDLOC3"),
+            OpCode::LDLOC4 => Some("LDLOC4"),
+            OpCode::LDLOC5 => Some("LDLOC5"),
+            OpCode::LDLOC6 => Some("LDLOC6"),
+            OpCode::LDLOC => Some("LDLOC"),
+            OpCode::STLOC0 => Some("STLOC0"),
+            OpCode::STLOC1 => Some("STLOC1"),
+            OpCode::STLOC2 => Some("STLOC2"),
+            OpCode::STLOC3 => Some("STLOC3"),
+            OpCode::STLOC4 => Some("STLOC4"),
+            OpCode::STLOC5 => Some("STLOC5"),
+            OpCode::STLOC6 => Some("STLOC6"),
+            OpCode::STLOC => Some("STLOC"),
+            OpCode::LDARG0 => Some("LDARG0"),
+            OpCode::LDARG1 => Some("LDARG1"),
+            OpCode::LDARG2 => Some("LDARG2"),
+            OpCode::LDARG3 => Some("LDARG3"),
+            OpCode::LDARG4 => Some("LDARG4"),
+            OpCode::LDARG5 => Some("LDARG5"),
+            OpCode::LDARG6 => Some("LDARG6"),
+            OpCode::LDARG => Some("LDARG"),
+            OpCode::STARG0 => Some("STARG0"),
+            OpCode::STARG1 => Some("STARG1"),
+            OpCode::STARG2 => Some("STARG2"),
+            OpCode::STARG3 => Some("STARG3"),
+            OpCode::STARG4 => Some("STARG4"),
+            OpCode::STARG5 => Some("STARG5"),
+            OpCode::STARG6 => Some("STARG6"),
+            OpCode::STARG => Some("STARG"),
+            OpCode::NEWBUFFER => Some("NEWBUFFER"),
+            OpCode::MEMCPY => Some("MEMCPY"),
+            OpCode::CAT => Some("CAT"),
+            OpCode::SUBSTR => Some("SUBSTR"),
+            OpCode::LEFT => Some("LEFT"),
+            OpCode::RIGHT => Some("RIGHT"),
+            OpCode::INVERT => Some("INVERT"),
+            OpCode::AND => Some("AND"),
+            OpCode::OR => Some("OR"),
+            OpCode::XOR => Some("XOR"),
+            OpCode::EQUAL => Some("EQUAL"),
+            OpCode::NOTEQUAL => Some("NOTEQUAL"),
+            OpCode::SIGN => Some("SIGN"),
+            OpCode::ABS => Some("ABS"),
+            OpCode::NEGATE => Some("NEGATE"),
+            OpCode::INC => Some("INC"),
+            OpCode::DEC => Some("DEC"),
+            OpCode::ADD => Some("ADD"),
+            OpCode::SUB => Some("SUB"),
+            OpCode::MUL => Some("MUL"),
+            OpCode::DIV => Some("DIV"),
+            OpCode::MOD => Some("MOD"),
+            OpCode::POW => Some("POW"),
+            OpCode::SQRT => Some("SQRT"),
+            OpCode::SHL => Some("SHL"),
+            OpCode::SHR => Some("SHR"),
+            OpCode::NOT => Some("NOT"),
+            OpCode::BOOLAND => Some("BOOLAND"),
+            OpCode::BOOLOR => Some("BOOLOR"),
+            OpCode::NZ => Some("NZ"),
+            OpCode::NUMEQUAL => Some("NUMEQUAL"),
+            OpCode::NUMNOTEQUAL => Some("NUMNOTEQUAL"),
+            OpCode::LT => Some("LT"),
+            OpCode::LE => Some("LE"),
+            OpCode::GT => Some("GT"),
+            OpCode::GE => Some("GE"),
+            OpCode::MIN => Some("MIN"),
+            OpCode::MAX => Some("MAX"),
+            OpCode::WITHIN => Some("WITHIN"),
+            OpCode::PACK => Some("PACK"),
+            OpCode::UNPACK => Some("UNPACK"),
+            OpCode::NEWARRAY0 => Some("NEWARRAY0"),
+            OpCode::NEWARRAY => Some("NEWARRAY"),
+            OpCode::NEWARRAY_T => Some("NEWARRAY_T"),
+            OpCode::NEWSTRUCT0 => Some("NEWSTRUCT0"),
+            OpCode::NEWSTRUCT => Some("NEWSTRUCT"),
+            OpCode::NEWMAP => Some("NEWMAP"),
+            OpCode::SIZE => Some("SIZE"),
+            OpCode::HASKEY => Some("HASKEY"),
+            OpCode::KEYS => Some("KEYS"),
+            OpCode::VALUES => Some("VALUES"),
+            OpCode::PICKITEM => Some("PICKITEM"),
+            OpCode::APPEND => Some("APPEND"),
+            OpCode::SETITEM => Some("SETITEM"),
+            OpCode::REVERSEITEMS => Some("REVERSEITEMS"),
+            OpCode::REMOVE => Some("REMOVE"),
+            OpCode::CLEARITEMS => Some("CLEARITEMS"),
+            OpCode::POPITEM => Some("POPITEM"),
+            OpCode::ISNULL => Some("ISNULL"),
+            OpCode::ISTYPE => Some("ISTYPE"),
+            OpCode::CONVERT => Some("CONVERT"),
+            _ => None,
+        }
+    }
+
+    /// Looks up a defined opcode by name.
+    pub fn from_name(name: &str) -> Option<OpCode> {
+        (0..=u8::MAX).map(OpCode).find(|opcode| opcode.name() == Some(name))
+    }
+
+    /// Size in bytes of the length prefix of the operand, 0 when the operand has a fixed size.
+    pub fn operand_size_prefix(self) -> usize {
+        match self {
+            OpCode::PUSHDATA1 => 1,
+            OpCode::PUSHDATA2 => 2,
+            OpCode::PUSHDATA4 => 4,
+            _ => 0,
+        }
+    }
+
+    /// Number of offsets, relative to the instruction, held by the operand: the jump and call targets,
+    /// the catch and finally blocks of a `TRY`.
+    pub fn offset_count(self) -> usize {
+        match self {
+            OpCode::TRY | OpCode::TRY_L => 2,
+            OpCode::PUSHA | OpCode::CALL | OpCode::CALL_L | OpCode::ENDTRY | OpCode::ENDTRY_L => 1,
+            _ if self >= OpCode::JMP && self <= OpCode::JMPLE_L => 1,
+            _ => 0,
+        }
+    }
+
+    /// Size in bytes of a fixed-size operand.
+    pub fn operand_size(self) -> usize {
+        match self {
+            OpCode::PUSHINT8 | OpCode::JMP | OpCode::JMPIF | OpCode::JMPIFNOT | OpCode::JMPEQ | OpCode::JMPNE | OpCode::JMPGT | OpCode::JMPGE | OpCode::JMPLT | OpCode::JMPLE | OpCode::CALL | OpCode::ENDTRY | OpCode::INITSSLOT | OpCode::LDSFLD | OpCode::STSFLD | OpCode::LDLOC | OpCode::STLOC | OpCode::LDARG | OpCode::STARG | OpCode::NEWARRAY_T | OpCode::ISTYPE | OpCode::CONVERT => 1,
+            OpCode::PUSHINT16 | OpCode::CALLT | OpCode::TRY | OpCode::INITSLOT => 2,
+            OpCode::PUSHINT32 | OpCode::PUSHA | OpCode::JMP_L | OpCode::JMPIF_L | OpCode::JMPIFNOT_L | OpCode::JMPEQ_L | OpCode::JMPNE_L | OpCode::JMPGT_L | OpCode::JMPGE_L | OpCode::JMPLT_L | OpCode::JMPLE_L | OpCode::CALL_L | OpCode::ENDTRY_L | OpCode::SYSCALL => 4,
+            OpCode::PUSHINT64 | OpCode::TRY_L => 8,
+            OpCode::PUSHINT128 => 16,
+            OpCode::PUSHINT256 => 32,
+            _ => 0,
+        }
+    }
+}
@@ -1,156 +1,131 @@
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
-use crate::CompoundType::CompoundType;
-use crate::StackItem::StackItem;
 
-pub struct Entry
-{
-    StackReferences: i32,
-    ObjectReferences: Option<HashMap<CompoundType, usize>>,
-}
+use crate::Types::StackItem::StackItem;
 
-#[derive(Debug, Copy, Clone)]
-pub struct ReferenceCounter {
-    counter: HashMap<CompoundType, Entry>,
-    // = new(ReferenceEqualityComparer.Instance);
-    zero_referred: HashSet<CompoundType>,
-    // = new(ReferenceEqualityComparer.Instance);
-    references_count: usize,// = 0;
-}
-
-impl Default for ReferenceCounter {
-    fn default()->Self{
-        Self{
-            counter: HashMap::default(),
-            zero_referred: HashSet::default(),
-            references_count: 0
-        }
-    }
+/// A compound item known to the counter and the number of stack slots referencing it.
+#[derive(Debug)]
+struct Entry {
+    item: StackItem,
+    stack_references: usize,
 }
 
 /// <summary>
 /// Used for reference counting of objects in the VM.
 /// </summary>
-impl ReferenceCounter
-{
+///
+/// The count is the number of items on the evaluation stacks and in the slots,
+/// plus the items contained in every compound item still reachable from them.
+/// Compound items are tracked by identity, those that are no longer reachable,
+/// cycles included, are dropped from the count by <see cref="check_zero_referred"/>.
+#[derive(Debug, Default)]
+pub struct ReferenceCounter {
+    tracked_items: RefCell<HashMap<*const u8, Entry>>,
+    zero_referred: Cell<bool>,
+    references_count: Cell<usize>,
+}
+
+impl ReferenceCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
     /// <summary>
     /// Indicates the number of this counter.
     /// </summary>
-    pub fn count(&self) -> usize { self.references_count }
-
-    pub fn add_reference(&mut self, referred: &StackItem, parent: &CompoundType)
-    {
-        self.references_count += 1;
-
-        let mut tracing = self.counter.get(compound);
-        if tracing.is_none()
-        {
-            tracing = Some(Entry::new());
-            self.counter.Add(compound, &tracing);
-        }
+    pub fn count(&self) -> usize {
+        self.references_count.get()
+    }
 
-        let mut count: usize = 0;
+    fn track(&self, item: &StackItem, stack_references: usize) {
+        self.tracked_items
+            .borrow_mut()
+            .entry(item.address())
+            .or_insert_with(|| Entry {
+                item: item.clone(),
+                stack_references: 0,
+            })
+            .stack_references += stack_references;
+    }
 
-        if tracing.unwrap().ObjectReferences.is_none()
-        {
-            tracing = Some(&Entry { ObjectReferences: Some(HashMap::new()), ..*tracing.unwrap() });
-            count = 1;
-        } else {
-            if tracing.unwrap().ObjectReferences.unwrap().contains_key(parent)
-            { count += 1; } else { count = 1; }
+    /// <summary>
+    /// Adds a reference from a compound item to <paramref name="item"/>.
+    /// </summary>
+    pub(crate) fn add_reference(&self, item: &StackItem) {
+        self.references_count.set(self.references_count.get() + 1);
+        if item.is_compound() {
+            self.track(item, 0);
         }
-        tracing.unwrap().ObjectReferences.unwrap()[parent] = count;
     }
 
-    pub fn add_references(&mut self, count: usize)
-    {
-        self.references_count += count;
+    /// <summary>
+    /// Adds <paramref name="count"/> references from evaluation stacks or slots to <paramref name="item"/>.
+    /// </summary>
+    pub(crate) fn add_stack_reference(&self, item: &StackItem, count: usize) {
+        self.references_count.set(self.references_count.get() + count);
+        if item.is_compound() {
+            self.track(item, count);
+        }
     }
 
-    pub fn add_stack_reference(&mut self, referred: Box<dyn StackItem>)
-    {
-        self.references_count += 1;
-
-        let mut entry = self.counter.get(referred);
-        if entry.is_some()
-        {
-            entry.unwrap().StackReferences.unwrap() += 1;
-        } else { self.counter.Add(referred, Entry { StackReferences: 1, ObjectReferences: None }); }
-
-        self.zero_referred.Remove(referred);
+    /// <summary>
+    /// Registers a new compound item, collected on the next check unless it gets referenced.
+    /// </summary>
+    pub(crate) fn add_zero_referred(&self, item: StackItem) {
+        self.track(&item, 0);
+        self.zero_referred.set(true);
     }
 
-    pub fn add_zero_referred(&mut self, item: CompoundType)
-    {
-        self.zero_referred.Add(item);
+    pub(crate) fn remove_reference(&self, item: &StackItem) {
+        self.references_count.set(self.references_count.get() - 1);
+        if item.is_compound() {
+            self.zero_referred.set(true);
+        }
     }
 
-    pub fn check_zero_referred(&mut self) -> usize
-    {
-        while self.zero_referred.len() > 0
-        {
-            let mut toBeDestroyed: HashSet<CompoundType> = new(ReferenceEqualityComparer.Instance);
-            for compound in self.zero_referred
-            {
-                toBeDestroyedInLoop: HashSet<CompoundType> = new(ReferenceEqualityComparer.Instance);
-                toBeChecked: Queue<CompoundType> = new();
-                toBeChecked.Enqueue(compound);
-                while toBeChecked.Count > 0
-                {
-                    let c = toBeChecked.Dequeue();
-                    let mut entry = self.counter.get(c);
-                    if entry?.StackReferences > 0
-                    {
-                        toBeDestroyedInLoop.clear();
-                        break;
-                    }
-                    toBeDestroyedInLoop.Add(c);
-
-                    if entry.unwrap().ObjectReferences.is_none() { continue; }
-
-                    for (pair in entry.unwrap().ObjectReferences){
-                        if (pair.Value > 0 && !toBeDestroyed.Contains(pair.Key) && !toBeDestroyedInLoop.Contains(pair.Key))
-                        toBeChecked.Enqueue(pair.Key);
-                    }
-                }
-                if toBeDestroyedInLoop.Count > 0
-                { toBeDestroyed.UnionWith(toBeDestroyedInLoop); }
-            }
-
-            self.zero_referred.clear();
-
-            for compound in toBeDestroyed.iter()
-            {
-                counter.Remove(compound);
-                references_count -= compound.SubItemsCount;
-                for subitem in compound.SubItems.OfType<CompoundType>().iter()
-                {
-                    if toBeDestroyed.Contains(subitem) continue;
-                    let entry = counter[subitem];
-                    entry.ObjectReferences!.Remove(compound);
-                    if entry.StackReferences == 0
-                        self.zero_referred.Add(subitem);
+    pub(crate) fn remove_stack_reference(&self, item: &StackItem) {
+        self.references_count.set(self.references_count.get() - 1);
+        if item.is_compound() {
+            if let Some(entry) = self.tracked_items.borrow_mut().get_mut(&item.address()) {
+                entry.stack_references -= 1;
+                if entry.stack_references == 0 {
+                    self.zero_referred.set(true);
                 }
             }
         }
-        return references_count;
     }
 
-    pub fn remove_reference(&mut self, referred: &StackItem, parent: &CompoundType)
-    {
-        self.references_count = self.references_count - 1;
+    /// <summary>
+    /// Drops the compound items no longer reachable from a stack and returns the updated count.
+    /// </summary>
+    pub fn check_zero_referred(&self) -> usize {
+        if !self.zero_referred.replace(false) {
+            return self.count();
+        }
 
-        let mut entry: Entry = counter[referred];
-        entry.ObjectReferences.unwrap()[parent] -= 1;
-        if entry.StackReferences == 0
-        { self.zero_referred.insert(referred); }
-    }
+        let mut tracked_items = self.tracked_items.borrow_mut();
+        let mut reachable: HashSet<*const u8> = HashSet::new();
+        let mut to_visit: Vec<StackItem> = tracked_items
+            .values()
+            .filter(|entry| entry.stack_references > 0)
+            .map(|entry| entry.item.clone())
+            .collect();
+        while let Some(item) = to_visit.pop() {
+            if !reachable.insert(item.address()) {
+                continue;
+            }
+            to_visit.extend(item.sub_items().into_iter().filter(StackItem::is_compound));
+        }
 
-    pub fn remove_stack_reference(&mut self, referred: &StackItem)
-    {
-        self.references_count -= 1;
-        let mut re = self.counter.get(referred).unwrap().StackReferences;
-        re -= 1;
-        if re == 0 { self.zero_referred.insert(referred) }
+        let mut references_count = self.references_count.get();
+        tracked_items.retain(|address, entry| {
+            if reachable.contains(address) {
+                return true;
+            }
+            references_count = references_count.saturating_sub(entry.item.sub_items_count());
+            false
+        });
+        self.references_count.set(references_count);
+        references_count
     }
 }
@@ -1,47 +1,23 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::Instruction::Instruction;
-use crate::OpCode;
-use crate::StackItemType::StackItemType;
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub struct Script {
-    _value: [u8],
-    strictMode: bool,
-    _instructions: HashMap<usize, Instruction>,
-}
+use crate::OpCode::OpCode;
+use crate::Types::StackItemType::StackItemType;
+use crate::VmError::VmError;
 
 /// <summary>
 /// Represents the script executed in the VM.
 /// </summary>
-// [DebuggerDisplay("Length={Length}")]
-impl Script
-{
-    /// <summary>
-    /// The length of the script.
-    /// </summary>
-    pub fn length(&self) -> usize
-    {
-        self._value.len() as usize
-    }
-
-    /// <summary>
-    /// Gets the <see cref="OpCode"/> at the specified index.
-    /// </summary>
-    /// <param name="index">The index to locate.</param>
-    /// <returns>The <see cref="OpCode"/> at the specified index.</returns>
-    pub fn at(&self, index: usize) -> OpCode
-    {
-        toOpCode(self._value[index]).unwrap()
-    }
-
-    /// <summary>
-    /// Initializes a new instance of the <see cref="Script"/> class.
-    /// </summary>
-    /// <param name="script">The bytecodes of the script.</param>
-    // fn Script(byte[] script): this(script, false)
-    // {}
+#[derive(Debug, Default)]
+pub struct Script {
+    value: Vec<u8>,
+    strict_mode: bool,
+    instructions: RefCell<HashMap<usize, Rc<Instruction>>>,
+}
 
+impl Script {
     /// <summary>
     /// Initializes a new instance of the <see cref="Script"/> class.
     /// </summary>
@@ -50,111 +26,191 @@ impl Script
     /// Indicates whether strict mode is enabled.
     /// In strict mode, the script will be checked, but the loading speed will be slower.
     /// </param>
-    /// <exception cref="BadScriptException">In strict mode, the script was found to contain bad instructions.</exception>
-    pub fn from_bytes(&mut self, script: &[u8], trictMode: bool)
-    {
-        self._value = *script.clone();
-        if strictMode
-        {
-            for mut ip in 0..self.script.len() { ip += GetInstruction(ip).Size; }
+    /// <exception cref="VmError::BadScript">In strict mode, the script was found to contain bad instructions.</exception>
+    pub fn from_bytes(script: &[u8], strict_mode: bool) -> Result<Self, VmError> {
+        let script = Self {
+            value: script.to_vec(),
+            strict_mode: false,
+            instructions: RefCell::new(HashMap::new()),
+        };
+        if !strict_mode {
+            return Ok(script);
+        }
+
+        let mut ip = 0;
+        while ip < script.length() {
+            ip += script.instruction(ip)?.size();
+        }
 
-            for (ip, instruction) in self._instructions
-            {
-                match instruction.OpCode {
-                    OpCode::JMP |
-                    OpCode::JMPIF |
-                    OpCode::JMPIFNOT |
-                    OpCode::JMPEQ |
-                    OpCode::JMPNE |
-                    OpCode::JMPGT |
-                    OpCode::JMPGE |
-                    OpCode::JMPLT |
-                    OpCode::JMPLE |
-                    OpCode::CALL |
-                    OpCode::ENDTRY => {
-                        if !self._instructions.contains_key(checked(ip + instruction.TokenI8)) {
-                            panic!();
-                            //      throw
-                            // new
-                            // BadScriptException($ "ip: {ip}, opcode: {instruction.OpCode}");
+        let bad_script = |ip: usize, opcode: OpCode, message: &str| {
+            Err(VmError::BadScript {
+                ip,
+                opcode,
+                message: message.to_string(),
+            })
+        };
+        let instructions = script.instructions.borrow();
+        let is_target = |ip: usize, offset: i32| {
+            let target = ip as i64 + offset as i64;
+            target >= 0 && instructions.contains_key(&(target as usize))
+        };
+        for (&ip, instruction) in instructions.iter() {
+            let opcode = instruction.opcode();
+            match opcode {
+                OpCode::JMP
+                | OpCode::JMPIF
+                | OpCode::JMPIFNOT
+                | OpCode::JMPEQ
+                | OpCode::JMPNE
+                | OpCode::JMPGT
+                | OpCode::JMPGE
+                | OpCode::JMPLT
+                | OpCode::JMPLE
+                | OpCode::CALL
+                | OpCode::ENDTRY
+                    if !is_target(ip, instruction.token_i8() as i32) =>
+                {
+                    return bad_script(ip, opcode, "invalid jump target");
+                }
+                OpCode::PUSHA
+                | OpCode::JMP_L
+                | OpCode::JMPIF_L
+                | OpCode::JMPIFNOT_L
+                | OpCode::JMPEQ_L
+                | OpCode::JMPNE_L
+                | OpCode::JMPGT_L
+                | OpCode::JMPGE_L
+                | OpCode::JMPLT_L
+                | OpCode::JMPLE_L
+                | OpCode::CALL_L
+                | OpCode::ENDTRY_L
+                    if !is_target(ip, instruction.token_i32()) =>
+                {
+                    return bad_script(ip, opcode, "invalid jump target");
+                }
+                OpCode::TRY
+                    if !is_target(ip, instruction.token_i8() as i32)
+                        || !is_target(ip, instruction.token_i8_1() as i32) =>
+                {
+                    return bad_script(ip, opcode, "invalid jump target");
+                }
+                OpCode::TRY_L
+                    if !is_target(ip, instruction.token_i32()) || !is_target(ip, instruction.token_i32_1()) =>
+                {
+                    return bad_script(ip, opcode, "invalid jump target");
+                }
+                OpCode::NEWARRAY_T | OpCode::ISTYPE | OpCode::CONVERT => {
+                    match StackItemType::from_u8(instruction.token_u8()) {
+                        None => return bad_script(ip, opcode, "invalid item type"),
+                        Some(StackItemType::Any) if opcode != OpCode::NEWARRAY_T => {
+                            return bad_script(ip, opcode, "invalid item type")
                         }
-                        break;
-                    }
-                    OpCode::PUSHA |
-                    OpCode::JMP_L |
-                    OpCode::JMPIF_L |
-                    OpCode::JMPIFNOT_L |
-                    OpCode::JMPEQ_L |
-                    OpCode::JMPNE_L |
-                    OpCode::JMPGT_L |
-                    OpCode::JMPGE_L |
-                    OpCode::JMPLT_L |
-                    OpCode::JMPLE_L |
-                    OpCode::CALL_L |
-                    OpCode::ENDTRY_L => {
-                        if !self._instructions.contains_key(checked(ip + instruction.TokenI32)) { panic!(); }
-                        // throw
-                        // new
-                        // BadScriptException($ "ip: {ip}, opcode: {instruction.OpCode}");
-                        break;
-                    }
-                    OpCode::TRY => {
-                        if !self._instructions.contains_key(checked(ip + instruction.ØTokenI8)) { panic!(); }
-                        // throw
-                        // new
-                        // BadScriptException($ "ip: {ip}, opcode: {instruction.OpCode}");
-                        if !self._instructions.contains_key(checked(ip + instruction.TokenI8_1)) { panic!(); }
-                        // throw
-                        // new
-                        // BadScriptException($ "ip: {ip}, opcode: {instruction.OpCode}");
-                        break;
+                        _ => {}
                     }
-                    OpCode::TRY_L => {
-                        if !self._instructions.ContainsKey(checked(ip + instruction.TokenI32)) { panic!(); }
-                        // throw
-                        // new
-                        // BadScriptException($ "ip: {ip}, opcode: {instruction.OpCode}");
-                        if !self._instructions.ContainsKey(checked(ip + instruction.TokenI32_1)) { panic!(); }
-                        // throw
-                        // new
-                        // BadScriptException($ "ip: {ip}, opcode: {instruction.OpCode}");
-                        break;
-                    }
-                    OpCode::NEWARRAY_T |
-                    OpCode::ISTYPE |
-                    OpCode::CONVERT => {
-                        let Type: StackItemType = instruction.TokenU8() as StackItemType;
-                        if !Enum.IsDefined(typeof(StackItemType), Type) { panic!(); }
-                        if instruction.OpCode != OpCode.NEWARRAY_T && Type == StackItemType.Any { panic!(); }
-                        break;
-                    }
-                    _ => { unreachable!() }
                 }
+                _ => {}
             }
         }
-        this.strictMode = strictMode;
+        drop(instructions);
+
+        Ok(Self {
+            strict_mode: true,
+            ..script
+        })
+    }
+
+    /// <summary>
+    /// The length of the script.
+    /// </summary>
+    pub fn length(&self) -> usize {
+        self.value.len()
+    }
+
+    /// <summary>
+    /// The bytecodes of the script.
+    /// </summary>
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// <summary>
+    /// Gets the <see cref="OpCode"/> at the specified index.
+    /// </summary>
+    pub fn at(&self, index: usize) -> OpCode {
+        OpCode(self.value[index])
     }
 
     /// <summary>
-    /// Get the <see cref="Instruction"/> at the specified position.
+    /// Get the <see cref="Instruction"/> at the specified position, <see cref="OpCode.RET"/> past the end.
     /// </summary>
-    /// <param name="ip">The position to get the <see cref="Instruction"/>.</param>
-    /// <returns>The <see cref="Instruction"/> at the specified position.</returns>
-    /// <exception cref="ArgumentException">In strict mode, the <see cref="Instruction"/> was not found at the specified position.</exception>
-    pub fn instruction(&self, ip: usize) -> Instruction
-    {
-        if ip >= self.Length() { return Instruction.RET; }
+    /// <exception cref="VmError::BadScript">The <see cref="Instruction"/> can't be decoded, or in strict mode was not found at the specified position.</exception>
+    pub fn instruction(&self, ip: usize) -> Result<Rc<Instruction>, VmError> {
+        if ip >= self.length() {
+            return Ok(Rc::new(Instruction::ret()));
+        }
+        if let Some(instruction) = self.instructions.borrow().get(&ip) {
+            return Ok(instruction.clone());
+        }
+        if self.strict_mode {
+            return Err(VmError::BadScript {
+                ip,
+                opcode: self.at(ip),
+                message: "ip not found with strict mode".to_string(),
+            });
+        }
+        let instruction = Rc::new(Instruction::from_script(&self.value, ip)?);
+        self.instructions.borrow_mut().insert(ip, instruction.clone());
+        Ok(instruction)
+    }
+}
 
-        if !self._instructions.TryGetValue(ip, out Instruction? instruction)
-        {
-            if self.strictMode { panic!(); }
-            // throw new ArgumentException( $ "ip not found with strict mode", nameof(ip));
-            instruction =  Instruction{_value, ip};
-            _instructions.Add(ip, instruction);
+impl From<Vec<u8>> for Script {
+    fn from(script: Vec<u8>) -> Self {
+        Self {
+            value: script,
+            strict_mode: false,
+            instructions: RefCell::new(HashMap::new()),
         }
-         instruction
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strict_mode() {
+        // JMP +2 lands on PUSH1, JMP +1 would land inside the JMP operand.
+        let script = Script::from_bytes(&[OpCode::JMP.0, 0x02, OpCode::PUSH1.0], true).unwrap();
+        assert_eq!(script.instruction(2).unwrap().opcode(), OpCode::PUSH1);
+        assert_eq!(script.instruction(3).unwrap().opcode(), OpCode::RET);
+        assert!(script.instruction(1).is_err());
 
-    // public static implicit operator byte[](Script script) => script._value;
-    // public static implicit operator Script(byte[] script) => new(script);
-}
\ No newline at end of file
+        let bad_jump = Script::from_bytes(&[OpCode::JMP.0, 0x01, OpCode::PUSH1.0], true);
+        assert!(matches!(
+            bad_jump,
+            Err(VmError::BadScript {
+                ip: 0,
+                opcode: OpCode::JMP,
+                ..
+            })
+        ));
+        let bad_type = Script::from_bytes(&[OpCode::ISTYPE.0, 0x00], true);
+        assert!(matches!(
+            bad_type,
+            Err(VmError::BadScript {
+                ip: 0,
+                opcode: OpCode::ISTYPE,
+                ..
+            })
+        ));
+        let truncated = Script::from_bytes(&[OpCode::PUSHDATA1.0, 0x02, 0x00], true);
+        assert!(matches!(truncated, Err(VmError::BadScript { ip: 0, .. })));
+        let undefined = Script::from_bytes(&[OpCode::NOP.0, 0xff], true);
+        assert!(matches!(undefined, Err(VmError::BadScript { ip: 1, .. })));
+
+        // Without strict mode only the executed instructions are decoded.
+        let script = Script::from(vec![OpCode::JMP.0, 0x01, OpCode::PUSH1.0]);
+        assert_eq!(script.instruction(0).unwrap().size(), 2);
+    }
+}
@@ -1,59 +1,39 @@
-use num::BigInt;
-use num::bigint::Sign;
-use crate::Memory::Memory;
+use num::{BigInt, ToPrimitive};
 
 use crate::OpCode::OpCode;
 
+/// <summary>
+/// A helper class for building scripts.
+/// </summary>
 #[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct ScriptBuilder {
-    ms: Memory,
-    writer: BinaryWriter,
+    script: Vec<u8>,
 }
 
-// <summary>
-/// A helper class for building scripts.
-/// </summary>
-impl ScriptBuilder
-{
+impl ScriptBuilder {
     /// <summary>
     /// Initializes a new instance of the <see cref="ScriptBuilder"/> class.
     /// </summary>
-    pub fn New() -> Self {
-        let mut writer = BinaryWriter(ms);
-        let mut ms = Memory();
-        Self {
-            ms,
-            writer,
-        }
+    pub fn new() -> Self {
+        Self::default()
     }
+
     /// <summary>
     /// The length of the script.
     /// </summary>
-    pub fn Length(&self) -> i32 {
-        self.ms.Position
+    pub fn length(&self) -> usize {
+        self.script.len()
     }
 
-    // public void Dispose()
-    // {
-    // writer.Dispose();
-    // ms.Dispose();
-    // }
-
     /// <summary>
     /// Emits an <see cref="Instruction"/> with the specified <see cref="OpCode"/> and operand.
     /// </summary>
     /// <param name="opcode">The <see cref="OpCode"/> to be emitted.</param>
     /// <param name="operand">The operand to be emitted.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn Emit(&mut self, opcode: OpCode, operand: Option<byte>) -> &mut ScriptBuilder
-    {
-        self.writer.Write(opcode);
-        match operand {
-            // The division was valid
-            Some(x) => writer.Write(x),
-            // The division was invalid
-            None => {},
-        }
+    pub fn emit(&mut self, opcode: OpCode, operand: &[u8]) -> &mut ScriptBuilder {
+        self.script.push(opcode.0);
+        self.script.extend_from_slice(operand);
         self
     }
 
@@ -62,14 +42,12 @@ impl ScriptBuilder
     /// </summary>
     /// <param name="offset">The offset to be called.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitCall(&mut self, offset: i32) -> &mut ScriptBuilder
-    {
-        // if (offset < sbyte.MinValue | | offset > sbyte.MaxValue)
-
-        self.Emit(OpCode::CALL_L, BitConverter.GetBytes(offset));
-        self
-        // else
-        // return Emit(OpCode.CALL, new[] { (byte)offset });
+    pub fn emit_call(&mut self, offset: i32) -> &mut ScriptBuilder {
+        if offset < i8::MIN as i32 || offset > i8::MAX as i32 {
+            self.emit(OpCode::CALL_L, &offset.to_le_bytes())
+        } else {
+            self.emit(OpCode::CALL, &[offset as u8])
+        }
     }
 
     /// <summary>
@@ -78,22 +56,19 @@ impl ScriptBuilder
     /// <param name="opcode">The <see cref="OpCode"/> to be emitted. It must be a jump <see cref="OpCode"/></param>
     /// <param name="offset">The offset to jump.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitJump(&mut self, mut opcode: OpCode, offset: i32) -> &mut ScriptBuilder
-    {
+    pub fn emit_jump(&mut self, mut opcode: OpCode, offset: i32) -> &mut ScriptBuilder {
         if opcode < OpCode::JMP || opcode > OpCode::JMPLE_L {
-            panic!();
+            panic!("Invalid jump opcode: {:?}", opcode.name());
         }
-
-        if opcode % 2 == 0 && (offset < sbyte.MinValue || offset > sbyte.MaxValue) {
-            opcode.0 = opcode.0 + 1; /// TODO: Check this
+        // The short forms have even opcodes, the long form follows each of them.
+        if opcode.0.is_multiple_of(2) && (offset < i8::MIN as i32 || offset > i8::MAX as i32) {
+            opcode = OpCode(opcode.0 + 1);
         }
-
-        if opcode % 2 == 0 {
-            self.Emit(opcode, StackItem { offset })
+        if opcode.0.is_multiple_of(2) {
+            self.emit(opcode, &[offset as u8])
         } else {
-            self.Emit(opcode, BitConverter.GetBytes(offset))
+            self.emit(opcode, &offset.to_le_bytes())
         }
-        self
     }
 
     /// <summary>
@@ -101,24 +76,30 @@ impl ScriptBuilder
     /// </summary>
     /// <param name="value">The number to be pushed.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitPush(&mut self, value: BigInt) -> &mut ScriptBuilder
-    {
-        if value >= BigInt::from(-1) && value <= BigInt::from(16) {
-            self.Emit(OpCode::PUSH0 + (byte)(int)value)
-        };
-
-        let data = value.ToByteArray(false, false);
-        if data.Length == 1 {
-            Emit(OpCode::PUSHINT8, data);
+    pub fn emit_push(&mut self, value: &BigInt) -> &mut ScriptBuilder {
+        if let Some(small) = value.to_i8() {
+            if (-1..=16).contains(&small) {
+                return self.emit(OpCode((OpCode::PUSH0.0 as i8 + small) as u8), &[]);
+            }
         }
-
-        if data.Length == 2 { self.Emit(OpCode::PUSHINT16, data); }
-        if data.Length <= 4 { self.Emit(OpCode::PUSHINT32, PadRight(data, 4)); }
-        if data.Length <= 8 { self.Emit(OpCode::PUSHINT64, PadRight(data, 8)); }
-        if data.Length <= 16 { self.Emit(OpCode::PUSHINT128, PadRight(data, 16)); }
-        if data.Length <= 32 { self.Emit(OpCode::PUSHINT256, PadRight(data, 32)); }
-        // panic!();
-        self
+        let data = value.to_signed_bytes_le();
+        let padding = if value.sign() == num::bigint::Sign::Minus {
+            0xff
+        } else {
+            0
+        };
+        let (opcode, length) = match data.len() {
+            1 => (OpCode::PUSHINT8, 1),
+            2 => (OpCode::PUSHINT16, 2),
+            3..=4 => (OpCode::PUSHINT32, 4),
+            5..=8 => (OpCode::PUSHINT64, 8),
+            9..=16 => (OpCode::PUSHINT128, 16),
+            17..=32 => (OpCode::PUSHINT256, 32),
+            _ => panic!("The value {} is out of range.", value),
+        };
+        let mut operand = data;
+        operand.resize(length, padding);
+        self.emit(opcode, &operand)
     }
 
     /// <summary>
@@ -126,10 +107,8 @@ impl ScriptBuilder
     /// </summary>
     /// <param name="value">The value to be pushed.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitPushBool(&mut self, value:bool) -> &mut ScriptBuilder
-    {
-        self.Emit(if value { OpCode::PUSH1 } else { OpCode::PUSH0 }, None);
-        self
+    pub fn emit_push_bool(&mut self, value: bool) -> &mut ScriptBuilder {
+        self.emit(if value { OpCode::PUSH1 } else { OpCode::PUSH0 }, &[])
     }
 
     /// <summary>
@@ -137,27 +116,15 @@ impl ScriptBuilder
     /// </summary>
     /// <param name="data">The data to be pushed.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitPushData(&mut self, data: &[u8]) -> &mut ScriptBuilder
-    {
-        // if (data == null)
-        // throw new ArgumentNullException(nameof(data));
-        if data.len() < 0x100
-        {
-            self.Emit(OpCode.PUSHDATA1);
-            self.writer.Write((byte)data.Length);
-            self.writer.Write(data);
-        } else if data.Length < 0x10000
-        {
-            self.Emit(OpCode.PUSHDATA2);
-            self.writer.Write((ushort)data.Length);
-            self.writer.Write(data);
-        } else// if (data.Length < 0x100000000L)
-        {
-            self.Emit(OpCode.PUSHDATA4);
-            self.writer.Write(data.Length);
-            self.writer.Write(data);
+    pub fn emit_push_data(&mut self, data: &[u8]) -> &mut ScriptBuilder {
+        if data.len() < 0x100 {
+            self.emit(OpCode::PUSHDATA1, &[data.len() as u8]);
+        } else if data.len() < 0x10000 {
+            self.emit(OpCode::PUSHDATA2, &(data.len() as u16).to_le_bytes());
+        } else {
+            self.emit(OpCode::PUSHDATA4, &(data.len() as u32).to_le_bytes());
         }
-        self
+        self.emit_raw(data)
     }
 
     /// <summary>
@@ -165,10 +132,8 @@ impl ScriptBuilder
     /// </summary>
     /// <param name="data">The <see cref="string"/> to be pushed.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitPushString(&mut self, data: &str) -> &mut ScriptBuilder
-    {
-        self.EmitPush(Utility.StrictUTF8.GetBytes(data));
-        self
+    pub fn emit_push_string(&mut self, data: &str) -> &mut ScriptBuilder {
+        self.emit_push_data(data.as_bytes())
     }
 
     /// <summary>
@@ -176,12 +141,8 @@ impl ScriptBuilder
     /// </summary>
     /// <param name="script">The raw script to be emitted.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitRaw(&mut self, script:Option<byte>) -> &mut ScriptBuilder
-    {
-        match script{
-            Some(v)=>self.writer.Write(script),
-            None=>()
-        }
+    pub fn emit_raw(&mut self, script: &[u8]) -> &mut ScriptBuilder {
+        self.script.extend_from_slice(script);
         self
     }
 
@@ -190,27 +151,59 @@ impl ScriptBuilder
     /// </summary>
     /// <param name="api">The operand of <see cref="OpCode.SYSCALL"/>.</param>
     /// <returns>A reference to this instance after the emit operation has completed.</returns>
-    pub fn EmitSysCall(&mut self, api: u32) -> &mut ScriptBuilder
-    {
-        self.Emit(OpCode.SYSCALL, api.to_be_bytes());
-        self
+    pub fn emit_syscall(&mut self, api: u32) -> &mut ScriptBuilder {
+        self.emit(OpCode::SYSCALL, &api.to_le_bytes())
     }
 
     /// <summary>
     /// Converts the value of this instance to a byte array.
     /// </summary>
     /// <returns>A byte array contains the script.</returns>
-    pub fn ToArray(&mut self)->
-    {
-    self.writer.Flush();
-        self.ms.ToArray()
+    pub fn to_array(&self) -> Vec<u8> {
+        self.script.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_push() {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_push(&BigInt::from(-1))
+            .emit_push(&BigInt::from(16))
+            .emit_push(&BigInt::from(17))
+            .emit_push(&BigInt::from(-129))
+            .emit_push(&BigInt::from(0x10000));
+        assert_eq!(
+            sb.to_array(),
+            vec![
+                OpCode::PUSHM1.0,
+                OpCode::PUSH16.0,
+                OpCode::PUSHINT8.0,
+                0x11,
+                OpCode::PUSHINT16.0,
+                0x7f,
+                0xff,
+                OpCode::PUSHINT32.0,
+                0x00,
+                0x00,
+                0x01,
+                0x00
+            ]
+        );
     }
 
-    fn PadRight(data:&[u8], length:i32) -> &[u8]
-    {
-    if data.Length >= length{ return data;}
-    let mut buffer = new byte[length];
-    Buffer.BlockCopy(data, 0, buffer, 0, data.Length);
-    return buffer;
+    #[test]
+    fn test_emit_jump() {
+        let mut sb = ScriptBuilder::new();
+        sb.emit_jump(OpCode::JMP, 2)
+            .emit_jump(OpCode::JMPIF, 200)
+            .emit_push_bool(true);
+        assert_eq!(
+            sb.to_array(),
+            vec![OpCode::JMP.0, 0x02, OpCode::JMPIF_L.0, 200, 0, 0, 0, OpCode::PUSH1.0]
+        );
     }
-}
\ No newline at end of file
+}
@@ -1,91 +1,73 @@
-use crate::ReferenceCounter::ReferenceCounter;
-use crate::StackItem::StackItem;
+use std::rc::Rc;
 
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
-pub struct Slot {
-    referenceCounter: ReferenceCounter,
-    items: Vec<StackItem>,
-}
+use crate::ReferenceCounter::ReferenceCounter;
+use crate::Types::StackItem::StackItem;
 
 /// <summary>
 /// Used to store local variables, arguments and static fields in the VM.
 /// </summary>
-impl Slot
-{
-    /// <summary>
-    /// Gets the item at the specified index in the slot.
-    /// </summary>
-    /// <param name="index">The zero-based index of the item to get.</param>
-    /// <returns>The item at the specified index in the slot.</returns>
-    pub fn Get(&self, index: i32) -> &StackItem
-    {
-        self.items[index]
-    }
-
-    pub fn Set(&mut self, index:i32, value: Box<StackItem>)
-    {
-        let oldValue =  self.items[index];
-        self.referenceCounter.RemoveStackReference(oldValue);
-        oldValue = value;
-        self.referenceCounter.AddStackReference(value);
-    }
-
-    /// <summary>
-    /// Gets the number of items in the slot.
-    /// </summary>
-    pub fn Count(&self) -> usize { self.items.len() }
+#[derive(Debug)]
+pub struct Slot {
+    reference_counter: Rc<ReferenceCounter>,
+    items: Vec<StackItem>,
+}
 
+impl Slot {
     /// <summary>
     /// Creates a slot containing the specified items.
     /// </summary>
     /// <param name="items">The items to be contained.</param>
     /// <param name="referenceCounter">The reference counter to be used.</param>
-    pub fn fromStackItems(items: Vec<StackItem>, referenceCounter: ReferenceCounter) -> Self
-    {
-        let mut slot = Self {
-            referenceCounter,
-            items: items.clone(),
-        };
-        for item in items.iter()
-        {
-            slot.referenceCounter.AddStackReference(item);
+    pub fn from_items(items: Vec<StackItem>, reference_counter: Rc<ReferenceCounter>) -> Self {
+        for item in items.iter() {
+            reference_counter.add_stack_reference(item, 1);
+        }
+        Self {
+            reference_counter,
+            items,
         }
-        slot
     }
 
     /// <summary>
-    /// Create a slot of the specified size.
+    /// Create a slot of the specified size, filled with <see langword="null"/>.
     /// </summary>
     /// <param name="count">Indicates the number of items contained in the slot.</param>
     /// <param name="referenceCounter">The reference counter to be used.</param>
-    pub fn New(count:int, mut referenceCounter:ReferenceCounter) ->Self
-    {
-    // this.referenceCounter = referenceCounter;
-    // this.items = new StackItem[count];
-    // Array.Fill(items, StackItem.Null);
-    referenceCounter.AddReferences(count);
-
-        Self{
-            referenceCounter,
-            // new StackItem[count,
-            // ]
-            items: vec![]
+    pub fn new(count: usize, reference_counter: Rc<ReferenceCounter>) -> Self {
+        reference_counter.add_stack_reference(&StackItem::Null, count);
+        Self {
+            reference_counter,
+            items: vec![StackItem::Null; count],
         }
     }
 
-    pub(crate) fn ClearReferences(&mut self)
-    {
-        for item in self.items.iter()
-        { self.referenceCounter.RemoveStackReference(item); }
+    /// <summary>
+    /// Gets the item at the specified index in the slot.
+    /// </summary>
+    pub fn get(&self, index: usize) -> StackItem {
+        self.items[index].clone()
     }
 
-    // IEnumerator < StackItem > IEnumerable < StackItem >.GetEnumerator()
-    // {
-    // foreach (StackItem item in items) yield return item;
-    // }
+    pub fn set(&mut self, index: usize, value: StackItem) {
+        self.reference_counter.remove_stack_reference(&self.items[index]);
+        self.reference_counter.add_stack_reference(&value, 1);
+        self.items[index] = value;
+    }
 
-// IEnumerator IEnumerable.GetEnumerator()
-// {
-// return items.GetEnumerator();
-// }
-}
\ No newline at end of file
+    /// <summary>
+    /// Gets the number of items in the slot.
+    /// </summary>
+    pub fn count(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, StackItem> {
+        self.items.iter()
+    }
+
+    pub(crate) fn clear_references(&self) {
+        for item in self.items.iter() {
+            self.reference_counter.remove_stack_reference(item);
+        }
+    }
+}
@@ -1,141 +1,122 @@
-use std::collections::HashMap;
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
 
 use crate::ReferenceCounter::ReferenceCounter;
-use crate::Types::CompoundType::CompoundType;
-use crate::Types::StackItem::StackItem;
-use crate::Types::StackItemType::StackItemType;
-use std::ptr::null;
-use std::slice::Iter;
+use crate::Types::StackItem::{track, StackItem};
 
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
+/// <summary>
+/// Represents an array or a complex object in the VM.
+/// </summary>
+#[derive(Debug)]
 pub struct Array {
-    _array: Vec<dyn StackItem>,
-    reference_counter: Option<ReferenceCounter>,
+    array: RefCell<Vec<StackItem>>,
+    reference_counter: Option<Rc<ReferenceCounter>>,
 }
 
-
-impl StackItem for Array {
-    fn Type() -> StackItemType {
-        StackItemType::Array
+impl Array {
+    /// <summary>
+    /// Create an array containing the specified items.
+    /// </summary>
+    /// <param name="referenceCounter">The <see cref="ReferenceCounter"/> to be used by this array.</param>
+    /// <param name="items">The items to be included in the array.</param>
+    pub fn new(reference_counter: Option<Rc<ReferenceCounter>>, items: Vec<StackItem>) -> Rc<Array> {
+        let array = Rc::new(Array::untracked(reference_counter, items));
+        track(StackItem::Array(array.clone()));
+        array
     }
 
-    fn ConvertTo(&self, typ: StackItemType) -> Box<StackItem>
-    {
-        if Type == StackItemType::Array && Type == StackItemType::Struct {
-            return Struct { ReferenceCounter: self.ReferenceCounter, new List < StackItem > (_array) };
-        }
-        // return base.ConvertTo( type );
-
-        if typ == Type { return Array { _array: self._array.clone(), ReferenceCounter: self.reference_counter }; }
-
-        if typ == StackItemType::Boolean
-        {
-            return GetBoolean();
+    /// Creates the array without registering it, for types built on top of it.
+    pub(crate) fn untracked(reference_counter: Option<Rc<ReferenceCounter>>, items: Vec<StackItem>) -> Array {
+        Self {
+            array: RefCell::new(items),
+            reference_counter,
         }
-        panic!();
     }
-}
 
-impl CompoundType for Array {
-    fn reference_counter(&self) -> Option<ReferenceCounter> {
-        todo!()
+    pub fn items(&self) -> Ref<'_, Vec<StackItem>> {
+        self.array.borrow()
     }
 
-    fn count(&self) -> i32 {
-        self._array.len() as i32
-    }
-
-    fn At(&self, index: i32) -> &StackItem
-    {
-        self._array[index]
-    }
-
-    fn Set(&mut self, index: i32, value: &StackItem) {
-        ReferenceCounter..RemoveReference(self._array[index], self);
-        self._array[index] = value;
-        ReferenceCounter?.AddReference(value, this);
+    /// <summary>
+    /// Gets the item at the specified index.
+    /// </summary>
+    pub fn get(&self, index: usize) -> StackItem {
+        match self.array.borrow().get(index) {
+            Some(item) => item.clone(),
+            None => panic!("The value {} is out of range.", index),
+        }
     }
 
-    fn SubItems(&self) -> Iter<'_, dyn StackItem> { self._array.iter() }
-
-
-    fn SubItemsCount(&self) -> i32 { self._array.len() as i32 }
-
-    fn Clear(&mut self) {
-        if self.ReferenceCounter != null {
-            for item in self._array {
-                self.ReferenceCounter.RemoveReference(item, this);
-                self._array.clear();
-            }
+    /// <summary>
+    /// Replaces the item at the specified index.
+    /// </summary>
+    pub fn set(&self, index: usize, value: StackItem) {
+        let mut array = self.array.borrow_mut();
+        if index >= array.len() {
+            panic!("The value {} is out of range.", index);
         }
+        if let Some(reference_counter) = &self.reference_counter {
+            reference_counter.remove_reference(&array[index]);
+            reference_counter.add_reference(&value);
+        }
+        array[index] = value;
     }
 
-    fn DeepCopy(&self, refMap: &HashMap<dyn StackItem, dyn StackItem>) -> Box<dyn StackItem> {
-        if refMap.TryGetValue(this, out StackItem? mappedItem) { return mappedItem; }
-
-        let mut result = this
-        is
-        Struct?
-        new
-        Struct(ReferenceCounter): new
-        Array(ReferenceCounter);
-        refMap.Add(this, result);
-        for (item in self._array){
-            result.Add(item.DeepCopy(refMap));
+    /// <summary>
+    /// Add a new item at the end of the array.
+    /// </summary>
+    pub fn add(&self, item: StackItem) {
+        if let Some(reference_counter) = &self.reference_counter {
+            reference_counter.add_reference(&item);
         }
-        return result;
+        self.array.borrow_mut().push(item);
     }
-}
 
-/// <summary>
-/// Represents an array or a complex object in the VM.
-/// </summary>
-impl Array
-{
     /// <summary>
-    /// Create an array containing the specified items. And make the array use the specified <see cref="ReferenceCounter"/>.
+    /// Remove the item at the specified index.
     /// </summary>
-    /// <param name="referenceCounter">The <see cref="ReferenceCounter"/> to be used by this array.</param>
-    /// <param name="items">The items to be included in the array.</param>
-    // pub fn Array(ReferenceCounter? referenceCounter, IEnumerable<StackItem> ? items = null)
-    // : base(referenceCounter)
-    // {
-    // _array = items switch
-    // {
-    // null => new List < StackItem > (),
-    // List < StackItem > list => list,
-    // _ => new List < StackItem > (items)
-    // };
-    // if (referenceCounter != null)
-    // foreach (StackItem item in _array)
-    // referenceCounter.AddReference(item, this);
-    // }
+    pub fn remove_at(&self, index: usize) {
+        let mut array = self.array.borrow_mut();
+        if index >= array.len() {
+            panic!("The value {} is out of range.", index);
+        }
+        let item = array.remove(index);
+        if let Some(reference_counter) = &self.reference_counter {
+            reference_counter.remove_reference(&item);
+        }
+    }
 
     /// <summary>
-    /// Add a new item at the end of the array.
+    /// Reverse all items in the array.
     /// </summary>
-    /// <param name="item">The item to be added.</param>
-    pub fn Add(&mut self, item: &StackItem)
-    {
-        self._array.push(item);
-        self.ReferenceCounter?.AddReference(item, this);
+    pub fn reverse(&self) {
+        self.array.borrow_mut().reverse();
     }
 
-
-    pub fn GetEnumerator(&self) -> Iter<'_, dyn StackItem>
-    {
-        self._array.iter()
+    /// <summary>
+    /// The reference counter used to count the items in the array.
+    /// </summary>
+    pub fn reference_counter(&self) -> Option<&Rc<ReferenceCounter>> {
+        self.reference_counter.as_ref()
     }
 
-    pub fn RemoveAt(&mut self, index: i32)
-    {
-        self.ReferenceCounter?.RemoveReference(_array[index], this);
-        _array.RemoveAt(index);
+    /// <summary>
+    /// The number of items in the array.
+    /// </summary>
+    pub fn count(&self) -> usize {
+        self.array.borrow().len()
     }
 
-    pub fn Reverse(&mut self)
-    {
-        self._array.reserve(0);
+    /// <summary>
+    /// Remove all items from the array.
+    /// </summary>
+    pub fn clear(&self) {
+        let mut array = self.array.borrow_mut();
+        if let Some(reference_counter) = &self.reference_counter {
+            for item in array.iter() {
+                reference_counter.remove_reference(item);
+            }
+        }
+        array.clear();
     }
 }
-
@@ -1,84 +1,44 @@
-use std::collections::HashMap;
-
-use crate::Types::StackItem::StackItem;
-use crate::Types::StackItemType::StackItemType;
-use crate::Types::Integer::Integer;
+use std::cell::{Ref, RefCell, RefMut};
 
+/// <summary>
+/// Represents a memory block that can be used for reading and writing in the VM.
+/// </summary>
+#[derive(Debug, Default)]
 pub struct Buffer {
-    InnerBuffer: Vec<u8>,
+    inner_buffer: RefCell<Vec<u8>>,
 }
 
-
-impl StackItem for Buffer {
-    fn Type() -> StackItemType {
-        StackItemType::Buffer
-    }
-
-    fn convertTo(&self, typ: StackItemType) -> Self {
-        match typ {
-            StackItemType::Integer => {
-                if self.InnerBuffer.len() > Integer.MaxSize { panic!() }
-
-                return new;
-                BigInt(InnerBuffer);
-            }
-            StackItemType::ByteString => {
-                byte
-                []
-                clone = new
-                byte[InnerBuffer.Length];
-                InnerBuffer.CopyTo(clone.AsSpan());
-                return clone;
-            }
-            _ => {
-                return base.ConvertTo(; type );
-            }
+impl Buffer {
+    /// <summary>
+    /// Create a buffer of the specified size, filled with zeros.
+    /// </summary>
+    pub fn new(size: usize) -> Self {
+        Self {
+            inner_buffer: RefCell::new(vec![0; size]),
         }
     }
-}
 
-/// <summary>
-/// Represents a memory block that can be used for reading and writing in the VM.
-/// </summary>
-// [DebuggerDisplay("Type={GetType().Name}, Value={System.BitConverter.ToString(InnerBuffer).Replace(\"-\", string.Empty)}")]
-impl Buffer
-{
     /// <summary>
-    /// The size of the buffer.
+    /// Create a buffer with the specified data.
     /// </summary>
-    // public int Size => ;
-    pub fn size(&self) -> i32 { self.InnerBuffer.len() as i32 }
-
+    pub fn from_slice(data: &[u8]) -> Self {
+        Self {
+            inner_buffer: RefCell::new(data.to_vec()),
+        }
+    }
 
     /// <summary>
-    /// Create a buffer with the specified data.
+    /// The size of the buffer.
     /// </summary>
-    /// <param name="data">The data to be contained in this buffer.</param>
-    // public Buffer(ReadOnlySpan<byte> data)
-    // : this(data.Length)
-    // {
-    // if ( ! data.IsEmpty) data.CopyTo(InnerBuffer);
-    // }
-
-
-    pub fn deep_copy(&self, refMap: &HashMap<StackItem, StackItem>) -> Box<StackItem>
-    {
-        if (refMap.TryGetValue(this, out StackItem? mappedItem))
-        return mappedItem;
-
-        Buffer
-        result = new(InnerBuffer);
-        refMap.Add(this, result);
-        return result;
+    pub fn size(&self) -> usize {
+        self.inner_buffer.borrow().len()
     }
 
-    pub fn boolean(&self) -> bool
-    {
-        true
+    pub fn inner_buffer(&self) -> Ref<'_, Vec<u8>> {
+        self.inner_buffer.borrow()
     }
 
-    pub fn span(&self) -> Vec<u8>
-    {
-        self.InnerBuffer.clone()
+    pub fn inner_buffer_mut(&self) -> RefMut<'_, Vec<u8>> {
+        self.inner_buffer.borrow_mut()
     }
 }
@@ -1,146 +1,322 @@
-use crate::Types::PrimitiveType::PrimitiveType;
-use crate::Types::StackItem::StackItem;
-use crate::Types::StackItemType::StackItemType;
-use num::traits::{Zero, One};
-use std::mem::replace;
-use num::bigint::{BigUint, BigInt, Sign};
-use crate::Types::ReadOnlyMemory::ReadOnlyMemory;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
+
+use num::{BigInt, Signed, ToPrimitive};
+
+use crate::VmError::{Limit, VmError};
 
 /// <summary>
 /// Represents an integer value in the VM.
 /// </summary>
-// [DebuggerDisplay("Type={GetType().Name}, Value={value}")]
-
-pub struct Integer {
-    value: BigInt,
+///
+/// Values fitting in an <see cref="i64"/> are kept inline and computed without
+/// allocating, larger ones fall back to a shared <see cref="BigInt"/>. A value
+/// has a single representation, so the derived equality compares values.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Integer {
+    Small(i64),
+    Big(Rc<BigInt>),
 }
 
-impl StackItem for Integer {
-    fn Type() -> StackItemType {
-        todo!()
+impl Integer {
+    /// <summary>
+    /// The maximum size of an integer in bytes.
+    /// </summary>
+    pub const MAX_SIZE: usize = 32;
+
+    /// <summary>
+    /// Create an integer with the specified value.
+    /// </summary>
+    /// <exception cref="VmError::LimitExceeded">The value is larger than <see cref="MAX_SIZE"/>.</exception>
+    pub fn new(value: BigInt) -> Result<Self, VmError> {
+        if let Some(small) = value.to_i64() {
+            return Ok(Integer::Small(small));
+        }
+        let size = value.to_signed_bytes_le().len();
+        VmError::check_limit(Limit::MaxIntegerSize, size, Integer::MAX_SIZE)?;
+        Ok(Integer::Big(Rc::new(value)))
     }
 
-    fn convertTo(&self, typ: StackItemType) -> Self {
-        todo!()
+    /// Reads the two's complement little-endian encoding of an integer, at most <see cref="MAX_SIZE"/> bytes.
+    pub fn from_signed_bytes_le(data: &[u8]) -> Result<Self, VmError> {
+        VmError::check_limit(Limit::MaxIntegerSize, data.len(), Integer::MAX_SIZE)?;
+        if data.len() <= 8 {
+            let padding = if data.last().is_some_and(|last| last & 0x80 != 0) {
+                0xff
+            } else {
+                0
+            };
+            let mut bytes = [padding; 8];
+            bytes[..data.len()].copy_from_slice(data);
+            return Ok(Integer::Small(i64::from_le_bytes(bytes)));
+        }
+        Integer::new(BigInt::from_signed_bytes_le(data))
     }
-}
 
-impl PrimitiveType for Integer {
-    fn Memory(&self) -> Vec<u8> {
-        self.value.is_zero()?
-        ReadOnlyMemory < byte >.Empty: value.ToByteArray();
+    pub fn to_big_int(&self) -> BigInt {
+        match self {
+            Integer::Small(value) => BigInt::from(*value),
+            Integer::Big(value) => (**value).clone(),
+        }
     }
 
-    fn Size(&self) -> i32 {
-        todo!()
+    pub fn to_i64(&self) -> Option<i64> {
+        match self {
+            Integer::Small(value) => Some(*value),
+            Integer::Big(_) => None,
+        }
     }
-}
 
-impl Integer
-{
-    /// <summary>
-    /// The maximum size of an integer in bytes.
-    /// </summary>
-    const MaxSize: i32 = 32;
-    const Zero: i32 = 0;
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Integer::Small(0))
+    }
 
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Integer::Small(value) => *value < 0,
+            Integer::Big(value) => value.is_negative(),
+        }
+    }
 
-    // internal override ReadOnlyMemory<byte> Memory =>
-    public override int Size { get;
-}
-public override StackItemType Type => StackItemType.Integer;
+    /// Size of the two's complement little-endian encoding, zero is empty.
+    pub fn size(&self) -> usize {
+        match self {
+            Integer::Small(0) => 0,
+            Integer::Small(value) => {
+                let significant_bits = if *value < 0 {
+                    64 - value.leading_ones() as usize
+                } else {
+                    64 - value.leading_zeros() as usize
+                };
+                significant_bits / 8 + 1
+            }
+            Integer::Big(value) => value.to_signed_bytes_le().len(),
+        }
+    }
 
-/// <summary>
-/// Create an integer with the specified value.
-/// </summary>
-/// <param name="value">The value of the integer.</param>
-public Integer(BigInt value)
-{
-if (value.IsZero)
-{
-Size = 0;
-}
-else
-{
-Size = value.GetByteCount();
-if (Size > MaxSize) throw new ArgumentException( $ "MaxSize exceed: {Size}");
-}
-this.value = value;
-}
+    /// The two's complement little-endian encoding, empty for zero.
+    pub fn to_signed_bytes_le(&self) -> Vec<u8> {
+        match self {
+            Integer::Small(value) => value.to_le_bytes()[..self.size()].to_vec(),
+            Integer::Big(value) => value.to_signed_bytes_le(),
+        }
+    }
 
-public override bool Equals(StackItem? other)
-{
-if (ReferenceEquals(this, other)) return true;
-if (other is Integer i) return value == i.value;
-return false;
-}
+    /// Applies `small` to values that fit in an <see cref="i64"/> and `big` when it overflows or they don't.
+    fn apply(
+        &self,
+        other: &Integer,
+        small: impl FnOnce(i64, i64) -> Option<i64>,
+        big: impl FnOnce(BigInt, BigInt) -> BigInt,
+    ) -> Result<Integer, VmError> {
+        if let (Integer::Small(a), Integer::Small(b)) = (self, other) {
+            if let Some(result) = small(*a, *b) {
+                return Ok(Integer::Small(result));
+            }
+        }
+        Integer::new(big(self.to_big_int(), other.to_big_int()))
+    }
 
-public override bool GetBoolean()
-{
-return ! value.IsZero;
-}
+    pub fn add(&self, other: &Integer) -> Result<Integer, VmError> {
+        self.apply(other, i64::checked_add, |a, b| a + b)
+    }
 
-public override int GetHashCode()
-{
-return HashCode.Combine(value);
-}
+    pub fn sub(&self, other: &Integer) -> Result<Integer, VmError> {
+        self.apply(other, i64::checked_sub, |a, b| a - b)
+    }
 
-public override BigInt GetInteger()
-{
-return value;
-}
+    pub fn mul(&self, other: &Integer) -> Result<Integer, VmError> {
+        self.apply(other, i64::checked_mul, |a, b| a * b)
+    }
 
+    /// Division truncated toward zero, fails when `other` is zero.
+    pub fn div(&self, other: &Integer) -> Result<Integer, VmError> {
+        if other.is_zero() {
+            return Err(VmError::invalid_operation("Attempted to divide by zero."));
+        }
+        self.apply(other, i64::checked_div, |a, b| a / b)
+    }
 
-public static implicit operator Integer(sbyte value)
-{
-return (BigInt)value;
-}
+    /// Remainder with the sign of the dividend, fails when `other` is zero.
+    pub fn rem(&self, other: &Integer) -> Result<Integer, VmError> {
+        if other.is_zero() {
+            return Err(VmError::invalid_operation("Attempted to divide by zero."));
+        }
+        self.apply(other, i64::checked_rem, |a, b| a % b)
+    }
 
+    pub fn bitand(&self, other: &Integer) -> Result<Integer, VmError> {
+        self.apply(other, |a, b| Some(a & b), |a, b| a & b)
+    }
 
-public static implicit operator Integer(byte value)
-{
-return (BigInt)value;
-}
+    pub fn bitor(&self, other: &Integer) -> Result<Integer, VmError> {
+        self.apply(other, |a, b| Some(a | b), |a, b| a | b)
+    }
 
+    pub fn bitxor(&self, other: &Integer) -> Result<Integer, VmError> {
+        self.apply(other, |a, b| Some(a ^ b), |a, b| a ^ b)
+    }
 
-public static implicit operator Integer(short value)
-{
-return (BigInt)value;
-}
+    pub fn not(&self) -> Result<Integer, VmError> {
+        match self {
+            Integer::Small(value) => Ok(Integer::Small(!value)),
+            Integer::Big(value) => Integer::new(!&**value),
+        }
+    }
 
+    pub fn neg(&self) -> Result<Integer, VmError> {
+        match self {
+            Integer::Small(value) if *value != i64::MIN => Ok(Integer::Small(-value)),
+            _ => Integer::new(-self.to_big_int()),
+        }
+    }
+
+    pub fn abs(&self) -> Result<Integer, VmError> {
+        match self {
+            Integer::Small(value) if *value != i64::MIN => Ok(Integer::Small(value.abs())),
+            _ => Integer::new(self.to_big_int().abs()),
+        }
+    }
 
-public static implicit operator Integer(ushort value)
-{
-return (BigInt)value;
+    pub fn signum(&self) -> i64 {
+        match self {
+            Integer::Small(value) => value.signum(),
+            Integer::Big(value) => value.signum().to_i64().unwrap(),
+        }
+    }
 }
 
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Integer::Small(a), Integer::Small(b)) => a.cmp(b),
+            _ => self.to_big_int().cmp(&other.to_big_int()),
+        }
+    }
+}
 
-public static implicit operator Integer(int value)
-{
-return (BigInt)value;
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
+impl Default for Integer {
+    fn default() -> Self {
+        Integer::Small(0)
+    }
+}
 
-public static implicit operator Integer(uint value)
-{
-return (BigInt)value;
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Integer::Small(value) => write!(f, "{}", value),
+            Integer::Big(value) => write!(f, "{}", value),
+        }
+    }
 }
 
+impl TryFrom<BigInt> for Integer {
+    type Error = VmError;
 
-public static implicit operator Integer(long value)
-{
-return (BigInt)value;
+    fn try_from(value: BigInt) -> Result<Self, VmError> {
+        Integer::new(value)
+    }
 }
 
+macro_rules! integer_from_small {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Integer {
+                fn from(value: $t) -> Self {
+                    Integer::Small(value as i64)
+                }
+            }
+        )*
+    };
+}
 
-public static implicit operator Integer(ulong value)
-{
-return (BigInt)value;
+integer_from_small!(i8, i16, i32, i64, u8, u16, u32);
+
+macro_rules! integer_from_large {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Integer {
+                fn from(value: $t) -> Self {
+                    // At most 17 bytes, within MAX_SIZE.
+                    match i64::try_from(value) {
+                        Ok(small) => Integer::Small(small),
+                        Err(_) => Integer::Big(Rc::new(BigInt::from(value))),
+                    }
+                }
+            }
+        )*
+    };
 }
 
+integer_from_large!(u64, usize, i128, u128);
+
+#[cfg(test)]
+mod tests {
+    use num::Zero;
+
+    use super::*;
+
+    #[test]
+    fn test_representation() {
+        for value in [0i64, 1, -1, 127, 128, -128, -129, 255, 256, i64::MAX, i64::MIN].iter() {
+            let integer = Integer::from(*value);
+            let big = BigInt::from(*value);
+            let expected = if big.is_zero() { Vec::new() } else { big.to_signed_bytes_le() };
+            assert_eq!(integer.to_signed_bytes_le(), expected, "{}", value);
+            assert_eq!(integer.size(), expected.len(), "{}", value);
+            assert_eq!(Integer::from_signed_bytes_le(&expected).unwrap(), integer, "{}", value);
+        }
+
+        let big = Integer::from(i64::MAX).add(&Integer::from(1)).unwrap();
+        assert!(matches!(big, Integer::Big(_)));
+        assert_eq!(big.sub(&Integer::from(1)).unwrap(), Integer::Small(i64::MAX));
+        assert_eq!(
+            Integer::from(i64::MIN).neg().unwrap().to_big_int(),
+            -BigInt::from(i64::MIN)
+        );
+        assert_eq!(Integer::from_signed_bytes_le(&[0; 9]).unwrap(), Integer::Small(0));
+        assert!(Integer::from(-3) < Integer::from(u64::MAX));
+    }
 
-public static implicit operator Integer(BigInt value)
-{
-return new Integer(value);
-}
+    #[test]
+    fn test_arithmetic() {
+        let (a, b) = (Integer::from(-7), Integer::from(2));
+        assert_eq!(a.div(&b).unwrap(), Integer::from(-3));
+        assert_eq!(a.rem(&b).unwrap(), Integer::from(-1));
+        assert_eq!(
+            Integer::from(i64::MIN).div(&Integer::from(-1)).unwrap().to_big_int(),
+            -BigInt::from(i64::MIN)
+        );
+        assert_eq!(
+            Integer::from(i64::MIN).rem(&Integer::from(-1)).unwrap(),
+            Integer::Small(0)
+        );
+        assert_eq!(a.bitand(&b).unwrap(), Integer::from(0));
+        assert_eq!(a.not().unwrap(), Integer::from(6));
+        assert_eq!(a.abs().unwrap().signum(), 1);
+        assert!(matches!(a.div(&Integer::from(0)), Err(VmError::InvalidOperation(_))));
+    }
+
+    #[test]
+    fn test_max_size() {
+        let max = Integer::new((BigInt::from(1) << 255usize) - 1).unwrap();
+        assert_eq!(max.size(), Integer::MAX_SIZE);
+        assert!(matches!(
+            Integer::new(BigInt::from(1) << 255usize),
+            Err(VmError::LimitExceeded {
+                limit: Limit::MaxIntegerSize,
+                value: 33
+            })
+        ));
+        assert!(max.add(&max).is_err());
+        assert!(Integer::from_signed_bytes_le(&[0xff; 33]).is_err());
+    }
 }
@@ -1,45 +1,40 @@
-
-
-
+use std::any::Any;
+use std::fmt;
+use std::rc::Rc;
+
+/// <summary>
+/// Represents an interface used to interoperate with the outside of the the VM.
+/// </summary>
+#[derive(Clone)]
+pub struct InteropInterface {
+    object: Rc<dyn Any>,
+}
+
+impl InteropInterface {
     /// <summary>
-    /// Represents an interface used to interoperate with the outside of the the VM.
+    /// Create an interoperability interface that wraps the specified object.
     /// </summary>
-    [DebuggerDisplay("Type={GetType().Name}, Value={_object}")]
-    public class InteropInterface : StackItem
-    {
-        private readonly object _object;
-
-        public override StackItemType Type => StackItemType.InteropInterface;
-
-        /// <summary>
-        /// Create an interoperability interface that wraps the specified <see cref="object"/>.
-        /// </summary>
-        /// <param name="value">The wrapped <see cref="object"/>.</param>
-        public InteropInterface(object value)
-        {
-            _object = value ?? throw new ArgumentNullException(nameof(value));
-        }
+    pub fn new(value: Rc<dyn Any>) -> Self {
+        Self { object: value }
+    }
 
-        public override bool Equals(StackItem? other)
-        {
-            if (ReferenceEquals(this, other)) return true;
-            if (other is InteropInterface i) return _object.Equals(i._object);
-            return false;
-        }
-
-        public override bool GetBoolean()
-        {
-            return true;
-        }
-
-        public override int GetHashCode()
-        {
-            return HashCode.Combine(_object);
-        }
-
-        public override T GetInterface<T>()
-        {
-            if (_object is T t) return t;
-            throw new InvalidCastException($"The item can't be casted to type {typeof(T)}");
-        }
-    }
\ No newline at end of file
+    /// <summary>
+    /// Gets the wrapped object if it has the type <typeparamref name="T"/>.
+    /// </summary>
+    pub fn interface<T: 'static>(&self) -> Option<Rc<T>> {
+        self.object.clone().downcast::<T>().ok()
+    }
+}
+
+impl fmt::Debug for InteropInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InteropInterface").finish()
+    }
+}
+
+/// Interfaces are equal when they wrap the same object.
+impl PartialEq for InteropInterface {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.object, &other.object)
+    }
+}
@@ -1,201 +1,190 @@
-use crate::Types::CompoundType::CompoundType;
-use indexmap::map::IndexMap;
-use crate::Types::StackItem::StackItem;
-use crate::Types::StackItemType::StackItemType;
-use std::collections::HashMap;
-use std::slice::Iter;
-use crate::Types::PrimitiveType::PrimitiveType;
-
-pub struct Map {
-    dictionary: IndexMap<dyn PrimitiveType, dyn StackItem>,
-}
-
-impl StackItem for Map {
-    fn Type() -> StackItemType {
-        StackItemType::Map
-    }
+use std::cell::{Ref, RefCell};
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
 
-    fn convertTo(&self, typ: StackItemType) -> Self {
-        todo!()
-    }
+use indexmap::map::IndexMap;
 
-    // const implicit: _ = ();
-}
+use crate::ReferenceCounter::ReferenceCounter;
+use crate::Types::StackItem::{track, StackItem};
+use crate::VmError::{Limit, VmError};
 
-impl CompoundType for Map {
-    fn count(&self) -> i32 {
-        todo!()
-    }
+/// A primitive item used as a map key, compared by type and value.
+#[derive(Debug, Clone)]
+pub struct MapKey(StackItem);
 
-    fn at(&self, index: i32) -> &dyn StackItem {
-        if (key.Size > MaxKeySize)
-        throw
-        new
-        ArgumentException($ "MaxKeySize exceed: {key.Size}");
-        return dictionary[key];
+impl MapKey {
+    fn new(key: &StackItem) -> Self {
+        if let Err(error) = Map::check_key(key) {
+            panic!("{}", error);
+        }
+        MapKey(key.clone())
     }
 
-    fn set(&mut self, index: i32, value: &dyn StackItem) {
-        todo!()
-    }
-
-    fn sub_items(&self) -> Iter<'_, dyn StackItem> {
-        todo!()
+    pub fn item(&self) -> &StackItem {
+        &self.0
     }
+}
 
-    fn sub_items_count(&self) -> i32 {
-        todo!()
+impl PartialEq for MapKey {
+    fn eq(&self, other: &Self) -> bool {
+        // Keys are checked, they are comparable.
+        matches!(self.0.equals(&other.0), Ok(true))
     }
+}
 
-    fn clear() {
-        todo!()
-    }
+impl Eq for MapKey {}
 
-    fn deep_copy(refMap: &HashMap<dyn StackItem, dyn StackItem>) -> dyn StackItem {
-        todo!()
+impl Hash for MapKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.get_type().hash(state);
+        if let Ok(span) = self.0.span() {
+            span.hash(state);
+        }
     }
 }
 
 /// <summary>
 /// Represents an ordered collection of key-value pairs in the VM.
 /// </summary>
-impl Map
-{
-    pub const MaxKeySize: i32 = 64;
+#[derive(Debug)]
+pub struct Map {
+    dictionary: RefCell<IndexMap<MapKey, StackItem>>,
+    reference_counter: Option<Rc<ReferenceCounter>>,
+}
 
+impl Map {
     /// <summary>
-    /// Gets or sets the element that has the specified key in the map.
+    /// Indicates the maximum size of keys in bytes.
     /// </summary>
-    /// <param name="key">The key to locate.</param>
-    /// <returns>The element that has the specified key in the map.</returns>
-    public StackItem this[PrimitiveType key]
-    {
-    get
-    {}
-    set
-    {
-    if (key.Size > MaxKeySize)
-    throw new ArgumentException( $ "MaxKeySize exceed: {key.Size}");
-    if (ReferenceCounter != null)
-    {
-    if (dictionary.TryGetValue(key, out StackItem? old_value))
-    ReferenceCounter.RemoveReference(old_value, this);
-    else
-    ReferenceCounter.AddReference(key, this);
-    ReferenceCounter.AddReference(value, this);
-    }
-    dictionary[key] = value;
-    }
-    }
-
-    public override int Count => dictionary.Count;
+    pub const MAX_KEY_SIZE: usize = 64;
 
     /// <summary>
-    /// Gets an enumerable collection that contains the keys in the map.
+    /// Create a new map with the specified reference counter.
     /// </summary>
-    public IEnumerable<PrimitiveType> Keys => dictionary.Keys;
-
-    internal override IEnumerable<StackItem> SubItems => Keys.Concat(Values);
+    pub fn new(reference_counter: Option<Rc<ReferenceCounter>>) -> Rc<Map> {
+        let map = Rc::new(Map {
+            dictionary: RefCell::new(IndexMap::new()),
+            reference_counter,
+        });
+        track(StackItem::Map(map.clone()));
+        map
+    }
 
-    internal override int SubItemsCount => dictionary.Count * 2;
+    /// <summary>
+    /// Checks that the item can be used as a key, a primitive of at most <see cref="MAX_KEY_SIZE"/> bytes.
+    /// The other methods panic on invalid keys.
+    /// </summary>
+    pub fn check_key(key: &StackItem) -> Result<(), VmError> {
+        if !key.is_primitive() {
+            return Err(VmError::invalid_operation(format!(
+                "{:?} can't be used as a map key",
+                key.get_type()
+            )));
+        }
+        VmError::check_limit(Limit::MaxKeySize, key.size()?, Map::MAX_KEY_SIZE)
+    }
 
-    public override StackItemType Type => StackItemType.Map;
+    pub fn dictionary(&self) -> Ref<'_, IndexMap<MapKey, StackItem>> {
+        self.dictionary.borrow()
+    }
 
     /// <summary>
-    /// Gets an enumerable collection that contains the values in the map.
+    /// Gets the element that has the specified key in the map.
     /// </summary>
-    public IEnumerable<StackItem> Values => dictionary.Values;
+    pub fn get(&self, key: &StackItem) -> Option<StackItem> {
+        self.dictionary.borrow().get(&MapKey::new(key)).cloned()
+    }
 
     /// <summary>
-    /// Create a new map with the specified reference counter.
+    /// Sets the element that has the specified key in the map.
     /// </summary>
-    /// <param name="referenceCounter">The reference counter to be used.</param>
-    public Map(ReferenceCounter? referenceCounter = null)
-    : base(referenceCounter)
-    {}
+    pub fn set(&self, key: StackItem, value: StackItem) {
+        let key = MapKey::new(&key);
+        let mut dictionary = self.dictionary.borrow_mut();
+        if let Some(reference_counter) = &self.reference_counter {
+            match dictionary.get(&key) {
+                Some(old_value) => reference_counter.remove_reference(old_value),
+                None => reference_counter.add_reference(&key.0),
+            }
+            reference_counter.add_reference(&value);
+        }
+        dictionary.insert(key, value);
+    }
 
-    public override void Clear()
-    {
-    if (ReferenceCounter != null)
-    foreach (var pair in dictionary)
-    {
-    ReferenceCounter.RemoveReference(pair.Key, this);
-    ReferenceCounter.RemoveReference(pair.Value, this);
+    /// <summary>
+    /// Determines whether the map contains an element that has the specified key.
+    /// </summary>
+    pub fn contains_key(&self, key: &StackItem) -> bool {
+        self.dictionary.borrow().contains_key(&MapKey::new(key))
     }
-    dictionary.Clear();
+
+    /// <summary>
+    /// Removes the element with the specified key from the map, returns whether it was found.
+    /// </summary>
+    pub fn remove(&self, key: &StackItem) -> bool {
+        match self.dictionary.borrow_mut().shift_remove_entry(&MapKey::new(key)) {
+            Some((old_key, old_value)) => {
+                if let Some(reference_counter) = &self.reference_counter {
+                    reference_counter.remove_reference(&old_key.0);
+                    reference_counter.remove_reference(&old_value);
+                }
+                true
+            }
+            None => false,
+        }
     }
 
     /// <summary>
-    /// Determines whether the map contains an element that has the specified key.
+    /// Gets the keys in the map.
     /// </summary>
-    /// <param name="key">The key to locate.</param>
-    /// <returns>
-    /// <see langword="true" /> if the map contains an element that has the specified key;
-    /// otherwise, <see langword="false" />.
-    /// </returns>
-    public bool ContainsKey(PrimitiveType key)
-    {
-    if (key.Size > MaxKeySize)
-    throw new ArgumentException( $ "MaxKeySize exceed: {key.Size}");
-    return dictionary.ContainsKey(key);
+    pub fn keys(&self) -> Vec<StackItem> {
+        self.dictionary.borrow().keys().map(|key| key.0.clone()).collect()
     }
 
-    internal override StackItem DeepCopy(Dictionary<StackItem, StackItem> refMap)
-    {
-    if (refMap.TryGetValue(this, out StackItem? mappedItem)) return mappedItem;
-    Map result = new(ReferenceCounter);
-    refMap.Add(this, result);
-    foreach (var pair in dictionary)
-    result[pair.Key] = pair.Value.DeepCopy(refMap);
-    return result;
+    /// <summary>
+    /// Gets the values in the map.
+    /// </summary>
+    pub fn values(&self) -> Vec<StackItem> {
+        self.dictionary.borrow().values().cloned().collect()
     }
 
-    IEnumerator<KeyValuePair<PrimitiveType, StackItem> > IEnumerable<KeyValuePair<PrimitiveType, StackItem> >.GetEnumerator()
-    {
-    return ((IDictionary < PrimitiveType, StackItem > )dictionary).GetEnumerator();
+    /// <summary>
+    /// The reference counter used to count the items in the map.
+    /// </summary>
+    pub fn reference_counter(&self) -> Option<&Rc<ReferenceCounter>> {
+        self.reference_counter.as_ref()
     }
 
-    IEnumerator IEnumerable.GetEnumerator()
-    {
-    return ((IDictionary < PrimitiveType, StackItem > )dictionary).GetEnumerator();
+    /// <summary>
+    /// The number of elements in the map.
+    /// </summary>
+    pub fn count(&self) -> usize {
+        self.dictionary.borrow().len()
     }
 
     /// <summary>
-    /// Removes the element with the specified key from the map.
+    /// The keys and the values of the map.
     /// </summary>
-    /// <param name="key">The key of the element to remove.</param>
-    /// <returns>
-    /// <see langword="true" /> if the element is successfully removed;
-    /// otherwise, <see langword="false"/>.
-    /// This method also returns <see langword="false"/> if <paramref name="key"/> was not found in the original map.
-    /// </returns>
-    public bool Remove(PrimitiveType key)
-    {
-    if (key.Size > MaxKeySize)
-    throw new ArgumentException( $ "MaxKeySize exceed: {key.Size}");
-    if ( ! dictionary.Remove(key, out StackItem ? old_value))
-    return false;
-    ReferenceCounter ?.RemoveReference(key, this);
-    ReferenceCounter ?.RemoveReference(old_value, this);
-    return true;
+    pub fn sub_items(&self) -> Vec<StackItem> {
+        let dictionary = self.dictionary.borrow();
+        dictionary
+            .keys()
+            .map(|key| key.0.clone())
+            .chain(dictionary.values().cloned())
+            .collect()
     }
 
     /// <summary>
-    /// Gets the value that is associated with the specified key.
+    /// Remove all elements from the map.
     /// </summary>
-    /// <param name="key">The key to locate.</param>
-    /// <param name="value">
-    /// When this method returns, the value associated with the specified key, if the key is found;
-    /// otherwise, <see langword="null"/>.
-    /// </param>
-    /// <returns>
-    /// <see langword="true" /> if the map contains an element that has the specified key;
-    /// otherwise, <see langword="false"/>.
-    /// </returns>
-    public bool TryGetValue(PrimitiveType key, [MaybeNullWhen(false)] out StackItem value)
-    {
-    if (key.Size > MaxKeySize)
-    throw new ArgumentException( $ "MaxKeySize exceed: {key.Size}");
-    return dictionary.TryGetValue(key, out value);
-    }
-}
\ No newline at end of file
+    pub fn clear(&self) {
+        let mut dictionary = self.dictionary.borrow_mut();
+        if let Some(reference_counter) = &self.reference_counter {
+            for (key, value) in dictionary.iter() {
+                reference_counter.remove_reference(&key.0);
+                reference_counter.remove_reference(value);
+            }
+        }
+        dictionary.clear();
+    }
+}
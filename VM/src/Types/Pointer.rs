@@ -1,58 +1,42 @@
+use std::rc::Rc;
+
 use crate::Script::Script;
-use crate::Types::StackItem::StackItem;
-use crate::Types::StackItemType::StackItemType;
 
 /// <summary>
 /// Represents the instruction pointer in the VM, used as the target of jump instructions.
 /// </summary>
-#[derive(Clone, Copy, Debug)]
+#[derive(Debug, Clone)]
 pub struct Pointer {
-    script: Script,
+    script: Rc<Script>,
     position: usize,
 }
 
 impl Pointer {
-    pub fn Script(&self) -> Script {
-        self.script
-    }
-    pub fn Position(&self) -> usize {
-        self.position
-    }
-}
-
-impl Pointer
-{
-    fn Type() -> StackItemType => StackItemType.Pointer;
-
     /// <summary>
     /// Create a code pointer with the specified script and position.
     /// </summary>
-    /// <param name="script">The <see cref="VM.Script"/> object containing this pointer.</param>
-    /// <param name="position">The position of the pointer in the script.</param>
-    // fn Pointer(script, position:usize)
-    // {
-    //     this.Script = script;
-    //     this.Position = position;
-    // }
-
-    fn equals(other:&dyn StackItem) -> bool
-    {
-        if other == this { return true; }
+    pub fn new(script: Rc<Script>, position: usize) -> Self {
+        Self { script, position }
+    }
 
-        if (other
-        is
-        Pointer
-        p) return Position == p.Position & &Script == p.Script;
-        return false;
+    /// <summary>
+    /// The <see cref="VM.Script"/> object containing this pointer.
+    /// </summary>
+    pub fn script(&self) -> &Rc<Script> {
+        &self.script
     }
 
-    fn boolean() -> bool
-    {
-        return true;
+    /// <summary>
+    /// The position of the pointer in the script.
+    /// </summary>
+    pub fn position(&self) -> usize {
+        self.position
     }
+}
 
-    fn hash_code() -> i32
-    {
-        return HashCode.Combine(Script, Position);
+/// Pointers are equal when they point to the same position of the same script.
+impl PartialEq for Pointer {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position && Rc::ptr_eq(&self.script, &other.script)
     }
 }
@@ -1,16 +0,0 @@
-pub struct ReadOnlyMemory([u8]);
-
-impl ReadOnlyMemory {
-
-    // fn Empty
-
-    fn is_empty(&self) -> bool {
-        self.0.is_empty()
-    }
-
-
-    fn length(&self) -> usize { self.0.len() }
-
-
-    fn span(&self) ->
-}
\ No newline at end of file
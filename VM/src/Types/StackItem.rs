@@ -1,135 +1,449 @@
-use crate::Types::StackItemType::StackItemType;
-use std::any::Any;
-use std::error::Error;
-use crate::Types::StackItemType::StackItemType::InteropInterface;
+use std::rc::Rc;
+
 use num::BigInt;
 
+use crate::ReferenceCounter::ReferenceCounter;
+use crate::Types::Array::Array;
+use crate::Types::Buffer::Buffer;
+use crate::Types::ByteString::ByteString;
+use crate::Types::Integer::Integer;
+use crate::Types::InteropInterface::InteropInterface;
+use crate::Types::Map::Map;
+use crate::Types::Pointer::Pointer;
+use crate::Types::StackItemType::StackItemType;
+use crate::Types::Struct::Struct;
+use crate::Unsafe::not_zero;
+use crate::VmError::{Limit, VmError};
+
 /// <summary>
 /// The base class for all types in the VM.
 /// </summary>
-pub trait StackItem
-{
-    fn False() -> bool { false }
-
-    fn is_null() -> bool { false }
-
-    fn True() -> bool { true }
-
-    fn Type() -> StackItemType;
-
-    fn convertTo(&self, typ: StackItemType) -> Self;
-
-    fn deep_copy(&self) -> &Self
-    {
-        self.clone()
-    }
-
-    fn equals(&self, other: &StackItem) -> bool
-    {
-        self == other
-    }
-
-    fn from_interface(&self, value: Option<dyn Any>) -> Result<StackItem, Error>
-    {
-        if value.is_none() { return Err(()); }
-        InteropInterface::new(value)
-    }
-
-    fn boolean(&self) -> bool { false }
-
-    fn integer(&self) -> BigInt { panic!() }
-
-    fn interface<T>(&self) -> T
-        where T: Self
-    {
-        panic!()
-    }
-
-    fn span(&self) -> ReadOnlySpan<u8>
-    {
-        panic!();
-    }
-
-    fn string(&self) -> String
-    {
-        return Utility.StrictUTF8.GetString(GetSpan());
-    }
-
-
-    // fn  from_sbyte(value:sbyte)-> Box<Self>
-    // {
-    //     Self{value}
-    // }
-
-
-// fn from_byte(value:byte)-> Box<Self>
-// {
-//     Self{value}
-// }
-//
-//
-// fn static implicit operator StackItem(short value)
-// {
-// return (Integer)value;
-// }
-//
-//
-// fn static implicit operator StackItem(ushort value)
-// {
-// return (Integer)value;
-// }
-//
-//
-// fn static implicit operator StackItem(int value)
-// {
-// return (Integer)value;
-// }
-//
-//
-// fn static implicit operator StackItem(uint value)
-// {
-// return (Integer)value;
-// }
-//
-//
-// fn static implicit operator StackItem(long value)
-// {
-// return (Integer)value;
-// }
-//
-//
-// fn static implicit operator StackItem(ulong value)
-// {
-// return (Integer)value;
-// }
-//
-//
-// fn static implicit operator StackItem(BigInt value)
-// {
-// return (Integer)value;
-// }
-//
-//
-// fn static implicit operator StackItem(bool value)
-// {
-// return value ? True: False;
-// }
-//
-//
-// fn static implicit operator StackItem(byte[] value)
-// {
-// return (ByteString)value;
-// }
-//
-//
-// fn static implicit operator StackItem(ReadOnlyMemory<byte> value)
-// {
-// return (ByteString)value;
-// }
-//
-//
-// fn static implicit operator StackItem(string value)
-// {
-// return (ByteString)value;
-// }
+///
+/// Cloning an item is cheap: primitive values are immutable and shared, while
+/// buffers and compound items are shared handles compared by identity, mutated
+/// through any of their clones.
+#[derive(Debug, Clone, Default)]
+pub enum StackItem {
+    /// <summary>
+    /// Represents <see langword="null"/> in the VM.
+    /// </summary>
+    #[default]
+    Null,
+    Pointer(Pointer),
+    /// <summary>
+    /// Represents a boolean (<see langword="true" /> or <see langword="false" />) value in the VM.
+    /// </summary>
+    Boolean(bool),
+    Integer(Integer),
+    ByteString(ByteString),
+    Buffer(Rc<Buffer>),
+    Array(Rc<Array>),
+    Struct(Rc<Struct>),
+    Map(Rc<Map>),
+    InteropInterface(InteropInterface),
+}
+
+impl StackItem {
+    /// <summary>
+    /// The type of this VM object.
+    /// </summary>
+    pub fn get_type(&self) -> StackItemType {
+        match self {
+            StackItem::Null => StackItemType::Any,
+            StackItem::Pointer(_) => StackItemType::Pointer,
+            StackItem::Boolean(_) => StackItemType::Boolean,
+            StackItem::Integer(_) => StackItemType::Integer,
+            StackItem::ByteString(_) => StackItemType::ByteString,
+            StackItem::Buffer(_) => StackItemType::Buffer,
+            StackItem::Array(_) => StackItemType::Array,
+            StackItem::Struct(_) => StackItemType::Struct,
+            StackItem::Map(_) => StackItemType::Map,
+            StackItem::InteropInterface(_) => StackItemType::InteropInterface,
+        }
+    }
+
+    /// <summary>
+    /// Indicates whether the object is <see cref="Null"/>.
+    /// </summary>
+    pub fn is_null(&self) -> bool {
+        matches!(self, StackItem::Null)
+    }
+
+    /// <summary>
+    /// Indicates whether the object is a <see cref="Boolean"/>, an <see cref="Integer"/> or a <see cref="ByteString"/>.
+    /// </summary>
+    pub fn is_primitive(&self) -> bool {
+        matches!(self, StackItem::Boolean(_) | StackItem::Integer(_) | StackItem::ByteString(_))
+    }
+
+    /// <summary>
+    /// Indicates whether the object is an <see cref="Array"/>, a <see cref="Struct"/> or a <see cref="Map"/>.
+    /// </summary>
+    pub fn is_compound(&self) -> bool {
+        matches!(self, StackItem::Array(_) | StackItem::Struct(_) | StackItem::Map(_))
+    }
+
+    /// <summary>
+    /// Converts the VM object to a <see cref="bool"/> value.
+    /// </summary>
+    pub fn boolean(&self) -> Result<bool, VmError> {
+        match self {
+            StackItem::Null => Ok(false),
+            StackItem::Boolean(value) => Ok(*value),
+            StackItem::Integer(value) => Ok(!value.is_zero()),
+            StackItem::ByteString(value) => {
+                if value.memory().len() > Integer::MAX_SIZE {
+                    return Err(self.invalid_cast(StackItemType::Boolean));
+                }
+                Ok(not_zero(value.memory()))
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// <summary>
+    /// Converts the VM object to an <see cref="Integer"/>, only primitive types can be converted.
+    /// </summary>
+    pub fn to_integer(&self) -> Result<Integer, VmError> {
+        match self {
+            StackItem::Integer(value) => Ok(value.clone()),
+            StackItem::Boolean(value) => Ok(Integer::Small(*value as i64)),
+            StackItem::ByteString(value) if value.memory().len() <= Integer::MAX_SIZE => {
+                Integer::from_signed_bytes_le(value.memory())
+            }
+            _ => Err(self.invalid_cast(StackItemType::Integer)),
+        }
+    }
+
+    /// <summary>
+    /// Converts the VM object to an integer.
+    /// </summary>
+    pub fn integer(&self) -> Result<BigInt, VmError> {
+        Ok(self.to_integer()?.to_big_int())
+    }
+
+    /// <summary>
+    /// Gets the memory of the VM object, only primitive types and buffers have one.
+    /// </summary>
+    pub fn span(&self) -> Result<Vec<u8>, VmError> {
+        match self {
+            StackItem::Boolean(value) => Ok(vec![*value as u8]),
+            StackItem::Integer(value) => Ok(value.to_signed_bytes_le()),
+            StackItem::ByteString(value) => Ok(value.memory().to_vec()),
+            StackItem::Buffer(buffer) => Ok(buffer.inner_buffer().clone()),
+            _ => Err(self.invalid_cast(StackItemType::ByteString)),
+        }
+    }
+
+    /// <summary>
+    /// Converts the VM object to a strict UTF-8 <see cref="string"/>.
+    /// </summary>
+    pub fn string(&self) -> Result<String, VmError> {
+        String::from_utf8(self.span()?).map_err(|_| VmError::invalid_operation("invalid UTF-8 string"))
+    }
+
+    /// <summary>
+    /// The size of a primitive VM object in bytes.
+    /// </summary>
+    pub fn size(&self) -> Result<usize, VmError> {
+        match self {
+            StackItem::Boolean(_) => Ok(std::mem::size_of::<bool>()),
+            StackItem::Integer(value) => Ok(value.size()),
+            StackItem::ByteString(value) => Ok(value.memory().len()),
+            _ => Err(VmError::invalid_operation(format!(
+                "{:?} is not a primitive type",
+                self.get_type()
+            ))),
+        }
+    }
+
+    /// <summary>
+    /// Determines whether the specified object is equal to this one. Buffers and compound items
+    /// are compared by identity, structures by value.
+    /// </summary>
+    /// <exception cref="VmError::LimitExceeded">A <see cref="ByteString"/> exceeds <see cref="ByteString.MAX_COMPARABLE_SIZE"/>.</exception>
+    pub fn equals(&self, other: &StackItem) -> Result<bool, VmError> {
+        match (self, other) {
+            (StackItem::Null, _) => Ok(other.is_null()),
+            (StackItem::Boolean(a), StackItem::Boolean(b)) => Ok(a == b),
+            (StackItem::Integer(a), StackItem::Integer(b)) => Ok(a == b),
+            (StackItem::ByteString(a), _) => {
+                let max = ByteString::MAX_COMPARABLE_SIZE;
+                VmError::check_limit(Limit::MaxComparableSize, a.memory().len(), max)?;
+                match other {
+                    StackItem::ByteString(b) => {
+                        VmError::check_limit(Limit::MaxComparableSize, b.memory().len(), max)?;
+                        Ok(a.memory() == b.memory())
+                    }
+                    _ => Ok(false),
+                }
+            }
+            (StackItem::Buffer(a), StackItem::Buffer(b)) => Ok(Rc::ptr_eq(a, b)),
+            (StackItem::Array(a), StackItem::Array(b)) => Ok(Rc::ptr_eq(a, b)),
+            (StackItem::Struct(a), StackItem::Struct(b)) => a.equals(b),
+            (StackItem::Map(a), StackItem::Map(b)) => Ok(Rc::ptr_eq(a, b)),
+            (StackItem::Pointer(a), StackItem::Pointer(b)) => Ok(a == b),
+            (StackItem::InteropInterface(a), StackItem::InteropInterface(b)) => Ok(a == b),
+            _ => Ok(false),
+        }
+    }
+
+    /// <summary>
+    /// Converts the VM object to the specified type, returning the object itself if it already has it.
+    /// </summary>
+    pub fn convert_to(&self, typ: StackItemType) -> Result<StackItem, VmError> {
+        if self.get_type() == typ && !self.is_null() {
+            return Ok(self.clone());
+        }
+        match self {
+            // null converts to any type but Any.
+            StackItem::Null if typ == StackItemType::Any => Err(self.invalid_cast(typ)),
+            StackItem::Null => Ok(StackItem::Null),
+            StackItem::Boolean(_) | StackItem::Integer(_) | StackItem::ByteString(_) => match typ {
+                StackItemType::Integer => Ok(StackItem::Integer(self.to_integer()?)),
+                StackItemType::ByteString => Ok(StackItem::ByteString(ByteString::new(self.span()?))),
+                StackItemType::Buffer => Ok(Buffer::from_slice(&self.span()?).into()),
+                StackItemType::Boolean => Ok(StackItem::Boolean(self.boolean()?)),
+                _ => Err(self.invalid_cast(typ)),
+            },
+            StackItem::Buffer(buffer) => match typ {
+                StackItemType::Integer => {
+                    let inner_buffer = buffer.inner_buffer();
+                    if inner_buffer.len() > Integer::MAX_SIZE {
+                        return Err(self.invalid_cast(typ));
+                    }
+                    Ok(StackItem::Integer(Integer::from_signed_bytes_le(&inner_buffer)?))
+                }
+                StackItemType::ByteString => Ok(StackItem::ByteString(ByteString::new(self.span()?))),
+                StackItemType::Boolean => Ok(StackItem::Boolean(true)),
+                _ => Err(self.invalid_cast(typ)),
+            },
+            StackItem::Array(array) if typ == StackItemType::Struct => {
+                Ok(Struct::new(array.reference_counter().cloned(), array.items().clone()).into())
+            }
+            StackItem::Struct(s) if typ == StackItemType::Array => {
+                Ok(Array::new(s.reference_counter().cloned(), s.items().clone()).into())
+            }
+            // The conversion every type supports.
+            _ if typ == StackItemType::Boolean => Ok(StackItem::Boolean(self.boolean()?)),
+            _ => Err(self.invalid_cast(typ)),
+        }
+    }
+
+    fn invalid_cast(&self, to: StackItemType) -> VmError {
+        VmError::InvalidCast {
+            from: self.get_type(),
+            to,
+        }
+    }
+
+    /// <summary>
+    /// Gets the items of an <see cref="Array"/> or a <see cref="Struct"/>.
+    /// </summary>
+    pub fn as_array(&self) -> Option<&Array> {
+        match self {
+            StackItem::Array(array) => Some(array),
+            StackItem::Struct(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_struct(&self) -> Option<&Rc<Struct>> {
+        match self {
+            StackItem::Struct(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&Map> {
+        match self {
+            StackItem::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_buffer(&self) -> Option<&Buffer> {
+        match self {
+            StackItem::Buffer(buffer) => Some(buffer),
+            _ => None,
+        }
+    }
+
+    pub fn as_pointer(&self) -> Option<&Pointer> {
+        match self {
+            StackItem::Pointer(pointer) => Some(pointer),
+            _ => None,
+        }
+    }
+
+    pub fn as_interop_interface(&self) -> Option<&InteropInterface> {
+        match self {
+            StackItem::InteropInterface(interface) => Some(interface),
+            _ => None,
+        }
+    }
+
+    /// <summary>
+    /// The reference counter used to count the items in a compound VM object.
+    /// </summary>
+    pub fn reference_counter(&self) -> Option<&Rc<ReferenceCounter>> {
+        match self {
+            StackItem::Array(array) => array.reference_counter(),
+            StackItem::Struct(s) => s.reference_counter(),
+            StackItem::Map(map) => map.reference_counter(),
+            _ => None,
+        }
+    }
+
+    /// <summary>
+    /// The number of items in a compound VM object, zero for other types.
+    /// </summary>
+    pub fn count(&self) -> usize {
+        match self {
+            StackItem::Array(array) => array.count(),
+            StackItem::Struct(s) => s.count(),
+            StackItem::Map(map) => map.count(),
+            _ => 0,
+        }
+    }
+
+    /// <summary>
+    /// The items referenced by a compound VM object, map keys included, none for other types.
+    /// </summary>
+    pub fn sub_items(&self) -> Vec<StackItem> {
+        match self {
+            StackItem::Array(array) => array.items().clone(),
+            StackItem::Struct(s) => s.items().clone(),
+            StackItem::Map(map) => map.sub_items(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn sub_items_count(&self) -> usize {
+        match self {
+            StackItem::Array(array) => array.count(),
+            StackItem::Struct(s) => s.count(),
+            StackItem::Map(map) => map.count() * 2,
+            _ => 0,
+        }
+    }
+
+    /// <summary>
+    /// Remove all items from a compound VM object, other types are left unchanged.
+    /// </summary>
+    pub fn clear(&self) {
+        match self {
+            StackItem::Array(array) => array.clear(),
+            StackItem::Struct(s) => s.clear(),
+            StackItem::Map(map) => map.clear(),
+            _ => {}
+        }
+    }
+
+    /// Address of a buffer or a compound item, identifying the shared object, null for other types.
+    pub fn address(&self) -> *const u8 {
+        match self {
+            StackItem::Buffer(buffer) => Rc::as_ptr(buffer) as *const u8,
+            StackItem::Array(array) => Rc::as_ptr(array) as *const u8,
+            StackItem::Struct(s) => Rc::as_ptr(s) as *const u8,
+            StackItem::Map(map) => Rc::as_ptr(map) as *const u8,
+            _ => std::ptr::null(),
+        }
+    }
+}
+
+/// Registers a new compound item and its items with its reference counter.
+pub(crate) fn track(item: StackItem) {
+    if let Some(reference_counter) = item.reference_counter() {
+        for sub_item in item.sub_items() {
+            reference_counter.add_reference(&sub_item);
+        }
+        reference_counter.add_zero_referred(item.clone());
+    }
+}
+
+impl From<bool> for StackItem {
+    fn from(value: bool) -> Self {
+        StackItem::Boolean(value)
+    }
+}
+
+impl From<Integer> for StackItem {
+    fn from(value: Integer) -> Self {
+        StackItem::Integer(value)
+    }
+}
+
+macro_rules! stack_item_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for StackItem {
+                fn from(value: $t) -> Self {
+                    StackItem::Integer(Integer::from(value))
+                }
+            }
+        )*
+    };
+}
+
+stack_item_from_integer!(i8, i16, i32, i64, u8, u16, u32, u64, usize, i128, u128);
+
+impl From<ByteString> for StackItem {
+    fn from(value: ByteString) -> Self {
+        StackItem::ByteString(value)
+    }
+}
+
+impl From<Vec<u8>> for StackItem {
+    fn from(value: Vec<u8>) -> Self {
+        StackItem::ByteString(ByteString::new(value))
+    }
+}
+
+impl From<&[u8]> for StackItem {
+    fn from(value: &[u8]) -> Self {
+        StackItem::ByteString(ByteString::from(value))
+    }
+}
+
+impl From<&str> for StackItem {
+    fn from(value: &str) -> Self {
+        StackItem::ByteString(ByteString::from(value))
+    }
+}
+
+impl From<Buffer> for StackItem {
+    fn from(value: Buffer) -> Self {
+        StackItem::Buffer(Rc::new(value))
+    }
+}
+
+impl From<Rc<Array>> for StackItem {
+    fn from(value: Rc<Array>) -> Self {
+        StackItem::Array(value)
+    }
+}
+
+impl From<Rc<Struct>> for StackItem {
+    fn from(value: Rc<Struct>) -> Self {
+        StackItem::Struct(value)
+    }
+}
+
+impl From<Rc<Map>> for StackItem {
+    fn from(value: Rc<Map>) -> Self {
+        StackItem::Map(value)
+    }
+}
+
+impl From<Pointer> for StackItem {
+    fn from(value: Pointer) -> Self {
+        StackItem::Pointer(value)
+    }
+}
+
+impl From<InteropInterface> for StackItem {
+    fn from(value: InteropInterface) -> Self {
+        StackItem::InteropInterface(value)
+    }
 }
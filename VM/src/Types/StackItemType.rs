@@ -1,7 +1,8 @@
 /// <summary>
 /// An enumeration representing the types in the VM.
 /// </summary>
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[repr(u8)]
 pub enum StackItemType
 {
     /// <summary>
@@ -54,3 +55,22 @@ pub enum StackItemType
     /// </summary>
     InteropInterface = 0x60,
 }
+
+impl StackItemType {
+    /// Returns the type with the specified byte value, `None` if it is not defined.
+    pub fn from_u8(value: u8) -> Option<StackItemType> {
+        match value {
+            0x00 => Some(StackItemType::Any),
+            0x10 => Some(StackItemType::Pointer),
+            0x20 => Some(StackItemType::Boolean),
+            0x21 => Some(StackItemType::Integer),
+            0x28 => Some(StackItemType::ByteString),
+            0x30 => Some(StackItemType::Buffer),
+            0x40 => Some(StackItemType::Array),
+            0x41 => Some(StackItemType::Struct),
+            0x48 => Some(StackItemType::Map),
+            0x60 => Some(StackItemType::InteropInterface),
+            _ => None,
+        }
+    }
+}
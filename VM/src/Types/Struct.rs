@@ -1,102 +1,102 @@
-use std::any::type_name;
 use std::collections::VecDeque;
+use std::ops::Deref;
+use std::rc::Rc;
 
-use crate::Types::StackItem::StackItem;
-use crate::Types::StackItemType::StackItemType;
-
-#[derive[Debug, Copy, Clone, Eq, PartialEq]]
-pub struct Struct<T>(pub(crate) [T]);
+use crate::ExecutionEngineLimits::ExecutionEngineLimits;
+use crate::ReferenceCounter::ReferenceCounter;
+use crate::Types::Array::Array;
+use crate::Types::StackItem::{track, StackItem};
+use crate::VmError::{Limit, VmError};
 
 /// <summary>
-/// Represents a structure in the VM.
+/// Represents a structure in the VM, an array compared and copied by value.
 /// </summary>
-impl Struct<T>
-{
-    const Type: StackItemType = StackItemType.Struct;
+#[derive(Debug)]
+pub struct Struct {
+    array: Array,
+}
 
+impl Struct {
     /// <summary>
     /// Create a structure with the specified fields.
     /// </summary>
-    /// <param name="fields">The fields to be included in the structure.</param>
-    fn Struct(IEnumerable<StackItem> ? fields = null) : this(null, fields)
-    {}
-
-    /// <summary>
-    /// Create a structure with the specified fields. And make the structure use the specified <see cref="ReferenceCounter"/>.
-    /// </summary>
-    /// <param name="referenceCounter">The <see cref="ReferenceCounter"/> to be used by this structure.</param>
-    /// <param name="fields">The fields to be included in the structure.</param>
-    fn Struct(ReferenceCounter? referenceCounter, IEnumerable<StackItem> ? fields = null)
-    : base(referenceCounter, fields)
-    {}
+    pub fn new(reference_counter: Option<Rc<ReferenceCounter>>, fields: Vec<StackItem>) -> Rc<Struct> {
+        let s = Rc::new(Struct {
+            array: Array::untracked(reference_counter, fields),
+        });
+        track(StackItem::Struct(s.clone()));
+        s
+    }
 
     /// <summary>
     /// Create a new structure with the same content as this structure. All nested structures will be copied by value.
     /// </summary>
-    /// <returns>The copied structure.</returns>
-    fn Clone(&self) -> Self
-    {
-        Struct result = new(ReferenceCounter);
-        let mut queue = VecDeque::new();
-        queue.push_back(result);
-        queue.push_back(self);
-
-        while queue.len() > 0
-        {
-            let mut a = queue.pop_front().unwrap() as Struct<T>;
-            let mut b = queue.pop_front().unwrap() as Struct<T>;
-            for (item in b)
-            {
-                if (item
-                is
-                Struct
-                sb)
-                {
-                    Struct
-                    sa = new(ReferenceCounter);
-                    a.Add(sa);
-                    queue.Enqueue(sa);
-                    queue.Enqueue(sb);
+    /// <param name="limits">Execution engine limits</param>
+    /// <exception cref="VmError::LimitExceeded">The copy has more than <see cref="ExecutionEngineLimits.max_stack_size"/> items.</exception>
+    pub fn clone_struct(&self, limits: &ExecutionEngineLimits) -> Result<Rc<Struct>, VmError> {
+        let mut count = limits.max_stack_size() as usize - 1;
+        let result = Struct::new(self.array.reference_counter().cloned(), Vec::new());
+        let mut queue: VecDeque<(Rc<Struct>, Vec<StackItem>)> = VecDeque::new();
+        queue.push_back((result.clone(), self.items().clone()));
+        while let Some((a, items)) = queue.pop_front() {
+            for item in items {
+                if count == 0 {
+                    return Err(VmError::LimitExceeded {
+                        limit: Limit::MaxStackSize,
+                        value: limits.max_stack_size() as usize + 1,
+                    });
                 }
-                else
-                {
-                    a.Add(item);
+                count -= 1;
+                match item {
+                    StackItem::Struct(sb) => {
+                        let sa = Struct::new(self.array.reference_counter().cloned(), Vec::new());
+                        a.add(StackItem::Struct(sa.clone()));
+                        queue.push_back((sa, sb.items().clone()));
+                    }
+                    item => a.add(item),
                 }
             }
         }
-        return result;
+        Ok(result)
     }
 
-    fn ConvertTo(Type: StackItemType) -> Box<dyn StackItem>
-    {
-        if (Type == StackItemType.Array)
-        return new;
-        Array(ReferenceCounter, new List < StackItem > (_array));
-        return base.ConvertTo(Type);
+    /// <summary>
+    /// Structures are equal when their fields are, nested structures are compared by value.
+    /// </summary>
+    pub fn equals(&self, other: &Struct) -> Result<bool, VmError> {
+        let mut stack1: Vec<StackItem> = self.items().clone();
+        let mut stack2: Vec<StackItem> = other.items().clone();
+        if stack1.len() != stack2.len() {
+            return Ok(false);
+        }
+        while let (Some(a), Some(b)) = (stack1.pop(), stack2.pop()) {
+            match (&a, &b) {
+                (StackItem::Struct(sa), StackItem::Struct(sb)) => {
+                    if Rc::ptr_eq(sa, sb) {
+                        continue;
+                    }
+                    if sa.count() != sb.count() {
+                        return Ok(false);
+                    }
+                    stack1.extend(sa.items().iter().cloned());
+                    stack2.extend(sb.items().iter().cloned());
+                }
+                (StackItem::Struct(_), _) => return Ok(false),
+                _ => {
+                    if !a.equals(&b)? {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Ok(true)
     }
+}
 
-    fn Equals(&self, other: &Struct<T>) -> bool
-    {
-        let mut stack1: VecDeque<dyn StackItem> = VecDeque::new();
-        let mut stack2: VecDeque<dyn StackItem> = VecDeque::new();
-
-        stack1.push_back(self.clone());
-        stack2.Push(other.clone());
-        while stack1.len() > 0
-        {
-            let mut a = stack1.pop_back().unwrap() as Struct<T>;
-            let mut b = stack2.pop_back().unwrap() as Struct<T>;
+impl Deref for Struct {
+    type Target = Array;
 
-            if ReferenceEquals(a, b) { continue; }
-            if type_name::<b>() == type_name::<Self>() { return false; }
-            if a.0.len() != b.0.len() { return false; }
-            for ia in a {
-                stack1.push_back(ia);
-            }
-            for ib in b {
-                stack2.push_back(ib);
-            }
-        }
-        return true;
+    fn deref(&self) -> &Array {
+        &self.array
     }
 }
@@ -1,19 +1,12 @@
 pub mod StackItem;
-pub mod Map;
-pub mod InteropInterface;
+pub mod StackItemType;
 pub mod Integer;
-pub mod CompoundType;
 pub mod ByteString;
 pub mod Buffer;
-pub mod Boolean;
+pub mod Pointer;
+pub mod InteropInterface;
 pub mod Array;
 pub mod Struct;
-pub mod StackItemType;
-pub mod PrimitiveType;
-pub mod Pointer;
-pub mod Null;
-pub mod ReadOnlyMemory;
-pub mod OrderedDictionary;
-pub mod Memory;
+pub mod Map;
 
-pub use num;
\ No newline at end of file
+pub use num;
@@ -1,26 +1,4 @@
-pub fn NotZero(x: ReadOnlySpan<byte>) -> bool
-{
-    let len = x.Length;
-    if len == 0
-    return false;
-    fixed(byte * xp = x)
-    {
-        long * xlp = (long *)
-        xp;
-        for (; len > = 8; len -= 8)
-        {
-            if (*xlp != 0)
-            return true;
-            xlp + +;
-        }
-        byte * xbp = (byte *)
-        xlp;
-        for (; len > 0; len - -)
-        {
-            if (*xbp != 0)
-            return true;
-            xbp + +;
-        }
-    }
-     false
+/// Whether any byte of `x` is not zero.
+pub fn not_zero(x: &[u8]) -> bool {
+    x.iter().any(|b| *b != 0)
 }
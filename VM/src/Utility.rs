@@ -1,35 +0,0 @@
-using System;
-using System.Numerics;
-using System.Text;
-
-namespace Neo.VM
-{
-    internal static class Utility
-    {
-        public static Encoding StrictUTF8 { get; }
-
-        static Utility()
-        {
-            StrictUTF8 = (Encoding)Encoding.UTF8.Clone();
-            StrictUTF8.DecoderFallback = DecoderFallback.ExceptionFallback;
-            StrictUTF8.EncoderFallback = EncoderFallback.ExceptionFallback;
-        }
-
-        public static BigInt Sqrt(this BigInt value)
-        {
-            if (value < 0) throw new InvalidOperationException("value can not be negative");
-            if (value.IsZero) return BigInt.Zero;
-            if (value < 4) return BigInt.One;
-
-            var z = value;
-            var x = value / 2 + 1;
-            while (x < z)
-            {
-                z = x;
-                x = (value / x + x) / 2;
-            }
-
-            return z;
-        }
-    }
-}
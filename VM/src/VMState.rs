@@ -1,12 +1,12 @@
 /// <summary>
 /// Indicates the status of the VM.
 /// </summary>
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
-pub enum VMState
-{
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum VMState {
     /// <summary>
     /// Indicates that the execution is in progress or has not yet begun.
     /// </summary>
+    #[default]
     NONE = 0,
     /// <summary>
     /// Indicates that the execution has been completed successfully.
@@ -21,4 +21,3 @@ pub enum VMState
     /// </summary>
     BREAK = 1 << 2,
 }
-
@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::OpCode::OpCode;
+use crate::Types::StackItem::StackItem;
+use crate::Types::StackItemType::StackItemType;
+
+/// <summary>
+/// A restriction on the VM, from <see cref="ExecutionEngineLimits"/> or the item types.
+/// </summary>
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Limit {
+    MaxShift,
+    MaxStackSize,
+    MaxItemSize,
+    MaxInvocationStackSize,
+    MaxTryNestingDepth,
+    /// <summary>
+    /// The maximum size of an <see cref="Integer"/> in bytes.
+    /// </summary>
+    MaxIntegerSize,
+    /// <summary>
+    /// The maximum size of a <see cref="ByteString"/> compared with another item.
+    /// </summary>
+    MaxComparableSize,
+    /// <summary>
+    /// The maximum size of a <see cref="Map"/> key.
+    /// </summary>
+    MaxKeySize,
+}
+
+/// <summary>
+/// The error that faults the VM, where the C# VM throws an exception.
+/// </summary>
+#[derive(Debug, Clone)]
+pub enum VmError {
+    /// <summary>
+    /// The script contains an instruction that can't be decoded or executed, like <see cref="BadScriptException"/>.
+    /// </summary>
+    BadScript { ip: usize, opcode: OpCode, message: String },
+
+    /// <summary>
+    /// A restriction on the VM is exceeded by the value.
+    /// </summary>
+    LimitExceeded { limit: Limit, value: usize },
+
+    /// <summary>
+    /// An item can't be converted to the type the operation requires.
+    /// </summary>
+    InvalidCast { from: StackItemType, to: StackItemType },
+
+    /// <summary>
+    /// The item at the index from the top of a stack of <c>count</c> items is missing.
+    /// </summary>
+    StackUnderflow { index: usize, count: usize },
+
+    /// <summary>
+    /// The operation is invalid in the current state, like <see cref="InvalidOperationException"/>.
+    /// </summary>
+    InvalidOperation(String),
+
+    /// <summary>
+    /// An exception thrown by the script was not caught, like <see cref="VMUnhandledException"/>.
+    /// </summary>
+    Unhandled(StackItem),
+}
+
+impl VmError {
+    pub fn invalid_operation(message: impl Into<String>) -> Self {
+        VmError::InvalidOperation(message.into())
+    }
+
+    pub(crate) fn out_of_range(value: impl fmt::Display) -> Self {
+        VmError::InvalidOperation(format!("The value {} is out of range.", value))
+    }
+
+    pub(crate) fn invalid_type(opcode: OpCode, item: &StackItem) -> Self {
+        VmError::InvalidOperation(format!(
+            "Invalid type for {}: {:?}",
+            opcode.name().unwrap_or("?"),
+            item.get_type()
+        ))
+    }
+
+    /// <summary>
+    /// Fails with <see cref="Limit"/> when <paramref name="value"/> is greater than <paramref name="max"/>.
+    /// </summary>
+    pub fn check_limit(limit: Limit, value: usize, max: usize) -> Result<(), VmError> {
+        if value > max {
+            return Err(VmError::LimitExceeded { limit, value });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::BadScript { ip, opcode, message } => match opcode.name() {
+                Some(name) => write!(f, "bad script at {} ({}): {}", ip, name, message),
+                None => write!(f, "bad script at {} ({:#04x}): {}", ip, opcode.0, message),
+            },
+            VmError::LimitExceeded { limit, value } => write!(f, "{:?} exceed: {}", limit, value),
+            VmError::InvalidCast { from, to } => write!(f, "can't convert {:?} to {:?}", from, to),
+            VmError::StackUnderflow { index, count } => {
+                write!(f, "stack underflow: item {} of {}", index, count)
+            }
+            VmError::InvalidOperation(message) => write!(f, "{}", message),
+            VmError::Unhandled(exception) => {
+                write!(f, "An unhandled exception was thrown.")?;
+                let message = match exception.as_array() {
+                    Some(array) if !array.items().is_empty() => array.get(0),
+                    _ => exception.clone(),
+                };
+                if let StackItem::ByteString(s) = message {
+                    write!(f, " {}", String::from_utf8_lossy(s.memory()))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for VmError {}
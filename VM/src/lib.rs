@@ -1,28 +1,28 @@
-extern crate core;
-
-use self::Types::*;
-extern crate alloc;
+#![allow(non_snake_case)]
 
 pub mod OpCode;
 pub mod VMState;
-// pub mod Debugger;
+pub mod Debugger;
 
 pub mod Types;
 pub mod Instruction;
 pub mod Script;
 pub mod ExecutionEngineLimits;
 pub mod ExecutionEngine;
+pub mod ExecutionHost;
+pub mod ExecutionObserver;
+pub mod JsonTracer;
 pub mod ExecutionContext;
 pub mod ExceptionHandlingState;
 pub mod ExceptionHandlingContext;
 pub mod EvaluationStack;
-pub mod BadScriptException;
 pub mod ReferenceCounter;
 pub mod Slot;
 pub mod ScriptBuilder;
 pub mod Unsafe;
-mod VMUnhandledException;
-mod Macros;
+pub mod VmError;
+pub mod Disassembler;
+pub mod Assembler;
 
 #[cfg(test)]
 mod tests {
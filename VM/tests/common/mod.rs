@@ -0,0 +1,64 @@
+//! Reading of the JSON tests, shared by tests/vm_tests.rs and benches/vm.rs.
+
+// Each of them uses a part of it.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use neo_vm::OpCode::OpCode;
+use serde_json::Value;
+
+/// Tests written for this crate.
+pub const CASES: &str = "tests/cases";
+
+/// The neo-vm tests, see `tests/fetch-neo-vm-tests.sh`.
+pub const NEO_VM_TESTS: &str = "tests/neo-vm.Tests/Tests";
+
+fn test_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            test_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "json") {
+            files.push(path);
+        }
+    }
+}
+
+/// The test files under `dir`, relative to the crate, with their content, none when `dir` doesn't exist.
+pub fn load_tests(dir: &str) -> Vec<(PathBuf, Value)> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+    let mut files = Vec::new();
+    if root.is_dir() {
+        test_files(&root, &mut files);
+    }
+    files.sort();
+    files
+        .into_iter()
+        .map(|file| {
+            let text = fs::read_to_string(&file).unwrap();
+            // Some of the neo-vm files start with a byte order mark.
+            let content = serde_json::from_str(text.trim_start_matches('\u{feff}'))
+                .unwrap_or_else(|e| panic!("{}: {}", file.display(), e));
+            (file.strip_prefix(&root).unwrap().to_path_buf(), content)
+        })
+        .collect()
+}
+
+/// Assembles a test script, a list of opcode names and `0x` hex operands.
+pub fn parse_script(script: &Value) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for token in script.as_array().unwrap() {
+        let token = token.as_str().unwrap();
+        match token.strip_prefix("0x") {
+            Some(hex) => bytes.extend(hex::decode(hex).unwrap()),
+            None => bytes.push(
+                OpCode::from_name(token)
+                    .unwrap_or_else(|| panic!("unknown opcode {}", token))
+                    .0,
+            ),
+        }
+    }
+    bytes
+}
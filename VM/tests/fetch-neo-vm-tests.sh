@@ -0,0 +1,18 @@
+#!/bin/sh
+# Copies the JSON tests of neo-vm, at the release this VM is a port of, to tests/neo-vm.Tests/Tests.
+#
+# tests/vm_tests.rs runs them with `cargo test -p neo_vm`, it fails until they are copied.
+# The upstream commit the copy was taken from is written to tests/neo-vm.Tests/COMMIT.
+set -e
+
+NEO_VM_TAG=v3.0.0
+
+dir=$(cd "$(dirname "$0")" && pwd)
+checkout=$(mktemp -d)
+trap 'rm -rf "$checkout"' EXIT
+
+git clone --quiet --depth 1 --branch "$NEO_VM_TAG" https://github.com/neo-project/neo-vm.git "$checkout"
+rm -rf "$dir/neo-vm.Tests"
+mkdir -p "$dir/neo-vm.Tests"
+cp -R "$checkout/tests/neo-vm.Tests/Tests" "$dir/neo-vm.Tests/Tests"
+echo "$NEO_VM_TAG $(git -C "$checkout" rev-parse HEAD)" > "$dir/neo-vm.Tests/COMMIT"
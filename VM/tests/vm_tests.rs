@@ -0,0 +1,280 @@
+//! Runs JSON unit tests in the format of the neo-vm ones.
+//!
+//! Every file holds tests made of a script and steps, each step runs actions on
+//! the engine (`execute`, `stepInto`, `stepOver`, `stepOut`) then compares the
+//! state and, when given, the invocation stack and the result stack. Stacks are
+//! listed from the top, items are `{ "type": ..., "value": ... }` objects.
+//!
+//! `tests/cases` holds tests written for this crate. The neo-vm tests themselves
+//! are copied to `tests/neo-vm.Tests/Tests` by `tests/fetch-neo-vm-tests.sh`,
+//! at the release this VM is a port of, and run by `neo_vm_json_tests`, except
+//! for the cases listed in `UNSUPPORTED`. It fails until they are copied.
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use neo_vm::Assembler::assemble;
+use neo_vm::Debugger::Debugger;
+use neo_vm::Disassembler::disassemble;
+use neo_vm::EvaluationStack::EvaluationStack;
+use neo_vm::ExecutionContext::ExecutionContext;
+use neo_vm::ExecutionEngine::ExecutionEngine;
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+use neo_vm::JsonTracer::item_to_json;
+use neo_vm::OpCode::OpCode;
+use neo_vm::Script::Script;
+use neo_vm::Slot::Slot;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::Types::StackItemType::StackItemType;
+use neo_vm::VMState::VMState;
+use neo_vm::VmError::{Limit, VmError};
+use serde_json::{json, Value};
+
+use common::{load_tests, parse_script, CASES, NEO_VM_TESTS};
+
+/// neo-vm tests this VM doesn't pass, as `<file under NEO_VM_TESTS> / <test name>`, with the reason.
+const UNSUPPORTED: &[(&str, &str)] = &[];
+
+/// Brings hex to the lowercase, `0x` prefixed form `item_to_json` produces.
+fn normalize_hex(hex: &str) -> String {
+    format!("0x{}", hex.strip_prefix("0x").unwrap_or(hex).to_lowercase())
+}
+
+/// Brings an expected item to the form `item_to_json` produces. The neo-vm tests name
+/// the types `Null` and `Interop`, and also give byte strings as `String` text.
+fn normalize_item(item: &Value) -> Value {
+    let typ = item["type"].as_str().unwrap();
+    let value = &item["value"];
+    let (typ, value) = match typ {
+        "Any" | "Null" => return json!({ "type": "Any" }),
+        "InteropInterface" | "Interop" => return json!({ "type": "InteropInterface" }),
+        "Integer" => match value {
+            Value::Number(number) => (typ, json!(number.to_string())),
+            _ => (typ, value.clone()),
+        },
+        "ByteString" | "Buffer" => (typ, json!(normalize_hex(value.as_str().unwrap()))),
+        "String" => (
+            "ByteString",
+            json!(normalize_hex(&hex::encode(value.as_str().unwrap()))),
+        ),
+        "Array" | "Struct" => (
+            typ,
+            Value::Array(value.as_array().unwrap().iter().map(normalize_item).collect()),
+        ),
+        "Map" => (
+            typ,
+            Value::Object(
+                value
+                    .as_object()
+                    .unwrap()
+                    .iter()
+                    .map(|(key, value)| (normalize_hex(key), normalize_item(value)))
+                    .collect(),
+            ),
+        ),
+        _ => (typ, value.clone()),
+    };
+    json!({ "type": typ, "value": value })
+}
+
+fn stack_to_json(stack: &EvaluationStack) -> Value {
+    Value::Array(
+        (0..stack.count())
+            .map(|index| item_to_json(&stack.peek(index).unwrap()))
+            .collect(),
+    )
+}
+
+fn slot_to_json(slot: Option<&Slot>) -> Value {
+    Value::Array(slot.map_or(Vec::new(), |slot| slot.iter().map(item_to_json).collect()))
+}
+
+fn context_to_json(context: &ExecutionContext) -> Value {
+    json!({
+        "instructionPointer": context.instruction_pointer(),
+        "nextInstruction": context.current_instruction().unwrap().opcode().name().unwrap(),
+        "evaluationStack": stack_to_json(&context.evaluation_stack()),
+        "staticFields": slot_to_json(context.static_fields().as_ref()),
+        "localVariables": slot_to_json(context.local_variables().as_ref()),
+        "arguments": slot_to_json(context.arguments().as_ref()),
+    })
+}
+
+fn normalize_items(items: &Value) -> Value {
+    match items {
+        Value::Null => json!([]),
+        _ => Value::Array(items.as_array().unwrap().iter().map(normalize_item).collect()),
+    }
+}
+
+fn normalize_context(context: &Value) -> Value {
+    json!({
+        "instructionPointer": context["instructionPointer"],
+        "nextInstruction": context["nextInstruction"],
+        "evaluationStack": normalize_items(&context["evaluationStack"]),
+        "staticFields": normalize_items(&context["staticFields"]),
+        "localVariables": normalize_items(&context["localVariables"]),
+        "arguments": normalize_items(&context["arguments"]),
+    })
+}
+
+fn state_name(state: VMState) -> String {
+    format!("{:?}", state)
+}
+
+fn run_action(engine: &mut ExecutionEngine, action: &str) {
+    let mut debugger = Debugger::new(engine);
+    match action {
+        "execute" => debugger.execute(),
+        "stepInto" => debugger.step_into(),
+        "stepOver" => debugger.step_over(),
+        "stepOut" => debugger.step_out(),
+        _ => panic!("unknown action {}", action),
+    };
+}
+
+fn run_test(file: &Path, test: &Value) {
+    let name = format!("{} / {}", file.display(), test["name"].as_str().unwrap());
+    let script = parse_script(&test["script"]);
+    let mut engine = ExecutionEngine::new(ExecutionEngineLimits::default());
+    engine.load_script(Script::from(script), -1, 0).unwrap();
+
+    for (index, step) in test["steps"].as_array().unwrap().iter().enumerate() {
+        for action in step["actions"].as_array().map_or(&[][..], |actions| actions.as_slice()) {
+            run_action(&mut engine, action.as_str().unwrap());
+        }
+        let result = &step["result"];
+        assert_eq!(
+            result["state"].as_str().unwrap(),
+            state_name(engine.state()),
+            "{}, step {}: state",
+            name,
+            index
+        );
+
+        if let Some(contexts) = result["invocationStack"].as_array() {
+            let expected: Vec<Value> = contexts.iter().map(normalize_context).collect();
+            let actual: Vec<Value> = engine.invocation_stack().iter().rev().map(context_to_json).collect();
+            assert_eq!(
+                Value::Array(expected),
+                Value::Array(actual),
+                "{}, step {}: invocation stack",
+                name,
+                index
+            );
+        }
+        if !result["resultStack"].is_null() {
+            let expected = normalize_items(&result["resultStack"]);
+            assert_eq!(
+                expected,
+                stack_to_json(engine.result_stack()),
+                "{}, step {}: result stack",
+                name,
+                index
+            );
+        }
+    }
+}
+
+#[test]
+fn json_tests() {
+    let files = load_tests(CASES);
+    assert!(!files.is_empty(), "no test files under {}", CASES);
+    for (file, content) in files {
+        for test in content["tests"].as_array().unwrap() {
+            run_test(&file, test);
+        }
+    }
+}
+
+#[test]
+fn neo_vm_json_tests() {
+    let files = load_tests(NEO_VM_TESTS);
+    assert!(
+        !files.is_empty(),
+        "no test files under {}, run tests/fetch-neo-vm-tests.sh",
+        NEO_VM_TESTS
+    );
+    let mut skipped = Vec::new();
+    for (file, content) in files {
+        for test in content["tests"].as_array().unwrap() {
+            let name = format!("{} / {}", file.display(), test["name"].as_str().unwrap());
+            match UNSUPPORTED.iter().find(|(case, _)| *case == name) {
+                Some((case, _)) => skipped.push(*case),
+                None => run_test(&file, test),
+            }
+        }
+    }
+    // Keeps the list to cases that still exist.
+    for (case, _) in UNSUPPORTED {
+        assert!(skipped.contains(case), "unsupported case {} not found", case);
+    }
+}
+
+/// Every script of the JSON tests that decodes assembles back to the same bytes.
+#[test]
+fn disassemble_round_trip() {
+    let syscalls = HashMap::new();
+    let mut count = 0;
+    for (file, content) in load_tests(CASES).into_iter().chain(load_tests(NEO_VM_TESTS)) {
+        for test in content["tests"].as_array().unwrap() {
+            let script = parse_script(&test["script"]);
+            if let Ok(text) = disassemble(&script, &syscalls) {
+                let assembled =
+                    assemble(&text, &syscalls).unwrap_or_else(|e| panic!("{}: {}\n{}", file.display(), e, text));
+                assert_eq!(assembled, script, "{}:\n{}", file.display(), text);
+                count += 1;
+            }
+        }
+    }
+    assert!(count > 0);
+}
+
+fn fault(script: Vec<u8>) -> VmError {
+    let mut engine = ExecutionEngine::new(ExecutionEngineLimits::default());
+    engine.load_script(Script::from(script), -1, 0).unwrap();
+    assert_eq!(engine.execute(), VMState::FAULT);
+    engine.fault_exception().clone().unwrap()
+}
+
+#[test]
+fn fault_exception() {
+    assert!(matches!(
+        fault(vec![OpCode::PUSHDATA1.0, 0x05, 0x00]),
+        VmError::BadScript {
+            ip: 0,
+            opcode: OpCode::PUSHDATA1,
+            ..
+        }
+    ));
+    assert!(matches!(
+        fault(vec![OpCode::NOP.0, 0xff]),
+        VmError::BadScript { ip: 1, .. }
+    ));
+    assert!(matches!(
+        fault(vec![OpCode::DROP.0]),
+        VmError::StackUnderflow { index: 0, count: 0 }
+    ));
+    assert!(matches!(
+        fault(vec![OpCode::NEWMAP.0, OpCode::PUSH1.0, OpCode::ADD.0]),
+        VmError::InvalidCast {
+            from: StackItemType::Map,
+            to: StackItemType::Integer
+        }
+    ));
+    // A CALL to itself recurses until the invocation stack is full.
+    assert!(matches!(
+        fault(vec![OpCode::CALL.0, 0x00]),
+        VmError::LimitExceeded {
+            limit: Limit::MaxInvocationStackSize,
+            value: 1025
+        }
+    ));
+    assert!(matches!(fault(vec![OpCode::ABORT.0]), VmError::InvalidOperation(_)));
+
+    let error = fault(vec![OpCode::PUSHDATA1.0, 0x02, b'h', b'i', OpCode::THROW.0]);
+    assert!(matches!(&error, VmError::Unhandled(StackItem::ByteString(_))));
+    assert_eq!(error.to_string(), "An unhandled exception was thrown. hi");
+}
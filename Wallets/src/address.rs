@@ -1,25 +1,19 @@
 use std::{convert::TryFrom, fmt, str::FromStr};
 
 use failure::Fail;
-use openssl::sha::sha224;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use neo_core::consts::ADDR_VERSION;
 use neo_core::convert::ab2hexstring;
-use neo_core::crypto::{checksum, hash160};
 use neo_core::misc::reverse_hex;
-use neo_core::to_hex_string;
-use neo_crypto::{base58, FromBase58, hex, sha2, ToBase58};
-use neo_crypto::sha2::Digest;
+use neo_core::neo_type::SCRIPT_HASH_BIN_LEN;
+use neo_core::ProtocolSettings;
+use neo_crypto::{base58, hex, FromBase58};
 
 use crate::private_key::{PrivateKey, PrivateKeyError};
 use crate::public_key::{PublicKey, PublicKeyError};
-use crate::key_trait::KeyTrait;
-use std::error::Error;
 
 /// Represents an  address
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub struct Address(pub String);
 
 impl Address {
@@ -33,17 +27,21 @@ impl Address {
         Ok(Self::checksum_address(public_key))
     }
 
+    /// Returns the address of a script hash, given as big-endian hex, on the default network.
     pub fn from_script_hash(script_hash: &str) -> Result<Self, AddressError> {
-        let script_hash = hex::decode(reverse_hex(script_hash)).unwrap();
-
-        let mut addr = [0u8; 25];
-        addr[0] = 23;
-        addr[1..21].copy_from_slice(&script_hash);
+        Self::from_script_hash_with(script_hash, &ProtocolSettings::default())
+    }
 
-        let sum = &checksum(&addr[0..21])[0..4];
-        addr[21..25].copy_from_slice(sum);
+    /// Returns the address of a script hash, given as big-endian hex, with the address version of `settings`.
+    pub fn from_script_hash_with(script_hash: &str, settings: &ProtocolSettings) -> Result<Self, AddressError> {
+        let script_hash = hex::decode(reverse_hex(script_hash))?;
+        if script_hash.len() != SCRIPT_HASH_BIN_LEN {
+            return Err(AddressError::InvalidByteLength(script_hash.len()));
+        }
 
-        Ok(Self(addr.to_base58()))
+        let mut hash = [0u8; SCRIPT_HASH_BIN_LEN];
+        hash.copy_from_slice(&script_hash);
+        Ok(Self(settings.address_from_script_hash(&hash)))
     }
 
     pub fn to_script_hash(&self) -> String {
@@ -52,52 +50,19 @@ impl Address {
         reverse_hex(&hash[2..42])
     }
 
-    /// Returns the checksum address given a public key.
+    /// Returns the checksum address given a public key, on the default network.
     pub fn checksum_address(public_key: &PublicKey) -> Self {
-        let mut script: Vec<u8> = Vec::new();
-        script.push(33);
-        script.extend(&public_key);
-        script.push(172);
-
-        let hs = hash160(&script);
-
-        let mut addr = [0u8; 25];
-        addr[0] = 23;
-        addr[1..21].copy_from_slice(&hs);
-
-        let sum = &checksum(&addr[0..21])[0..4];
-
-        addr[21..25].copy_from_slice(sum);
-        let mut pubk = [0u8; 33];
-        pubk.clone_from_slice(pub_key.as_slice());
-
-        Address(addr.to_base58())
+        Self::checksum_address_with(public_key, &ProtocolSettings::default())
     }
-}
 
-
-impl KeyTrait for Address{
-    fn deserialize(&self, hex: &str) -> Result<_, dyn Error> {
-        unimplemented!()
-    }
-
-    fn serialize(&self) -> Result<String, dyn Error> {
-        unimplemented!()
-    }
-
-    fn to_hex(&self) -> String {
-        unimplemented!()
-    }
-
-    fn to_slice(&self) -> &[u8] {
-        unimplemented!()
-    }
-
-    fn equals(&self, other: &_) -> bool {
-        unimplemented!()
+    /// Returns the address of the standard account of a public key, whose verification script and
+    /// address version are the ones of `settings`.
+    pub fn checksum_address_with(public_key: &PublicKey, settings: &ProtocolSettings) -> Self {
+        Address(settings.address_from_public_key(&public_key.0))
     }
 }
 
+
 impl<'a> TryFrom<&'a str> for Address {
     type Error = AddressError;
 
@@ -109,7 +74,13 @@ impl<'a> TryFrom<&'a str> for Address {
 impl FromStr for Address {
     type Err = AddressError;
 
+    /// Parses a Neo2 or N3 address, checking its length, address version and checksum.
     fn from_str(addr: &str) -> Result<Self, Self::Err> {
+        let settings = ProtocolSettings::from_address(addr).ok_or_else(|| AddressError::InvalidAddress(addr.into()))?;
+        settings
+            .address_to_script_hash(addr)
+            .map_err(|error| AddressError::Crate("neo_core::protocol_settings", error.to_string()))?;
+
         Ok(Address(addr.to_string()))
     }
 }
@@ -215,118 +186,77 @@ impl From<hex::FromHexError> for AddressError {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::test_vectors::*;
 
-    fn test_from_private_key(expected_address: &str, private_key: &PrivateKey) {
-        let address = Address::from_private_key(private_key).unwrap();
-        assert_eq!(expected_address, address.to_string());
-    }
-
-    fn test_from_public_key(expected_address: &str, public_key: &PublicKey) {
-        let address = Address::from_public_key(public_key).unwrap();
-        assert_eq!(expected_address, address.to_string());
-    }
+    use super::*;
 
-    fn test_from_str(expected_address: &str) {
-        let address = Address::from_str(expected_address).unwrap();
-        assert_eq!(expected_address, address.to_string());
+    #[test]
+    fn test_from_private_key() {
+        let private_key = PrivateKey::from_str(PRIVATE_KEY).unwrap();
+        let address = Address::from_private_key(&private_key).unwrap();
+        assert_eq!(address.to_string(), N3_ADDRESS);
     }
 
-    fn test_to_str(expected_address: &str, address: &Address) {
-        assert_eq!(expected_address, address.to_string());
+    #[test]
+    fn test_from_public_key() {
+        let public_key = PublicKey::from_str(PUBLIC_KEY).unwrap();
+        assert_eq!(Address::from_public_key(&public_key).unwrap().to_string(), N3_ADDRESS);
+        assert_eq!(Address::checksum_address_with(&public_key, &ProtocolSettings::N2_MAINNET).to_string(), N2_ADDRESS);
     }
 
-    mod checksum_address {
-        use super::*;
-
-        const KEYPAIRS: [(&str, &str); 5] = [
-            (
-                "f89f23eaeac18252fedf81bb8318d3c111d48c19b0680dcf6e0a8d5136caf287",
-                "0x9141B7539E7902872095C408BfA294435e2b8c8a",
-            ),
-            (
-                "a93701ea343247db13466f6448ffbca658726e2b4a77530db3eca3c9250b4f0d",
-                "0xa0967B1F698DC497A694FE955666D1dDd398145C",
-            ),
-            (
-                "de61e35e2e5eb9504d52f5042126591d80144d49f74b8ced68f4959a3e8edffd",
-                "0xD5d13d1dD277BB9041e560A63ee29c086D370b0A",
-            ),
-            (
-                "56f01d5e01b6fd1cc123d8d1eae0d148e00c025b5be2ef624775f7a1b802e9c1",
-                "0xc4488ebbE882fa2aF1D466CB2C8ecafE316c067a",
-            ),
-            (
-                "363af8b4d3ff22bb0e4ffc2ff198b4b5be0316f8a507ad5fe32f021c3d1ae8ad",
-                "0xF9001e6AEE6EA439D713fBbF960EbA76f4770E2B",
-            ),
-        ];
-
-        #[test]
-        fn from_private_key() {
-            KEYPAIRS.iter().for_each(|(private_key, address)| {
-                let private_key = PrivateKey::from_str(private_key).unwrap();
-                test_from_private_key(address, &private_key);
-            });
-        }
-
-        #[test]
-        fn from_public_key() {
-            KEYPAIRS.iter().for_each(|(private_key, address)| {
-                let private_key = PrivateKey::from_str(private_key).unwrap();
-                let public_key = PublicKey::from_private_key(&private_key);
-                test_from_public_key(address, &public_key);
-            });
-        }
-
-        #[test]
-        fn from_str() {
-            KEYPAIRS.iter().for_each(|(_, address)| {
-                test_from_str(address);
-            });
+    #[test]
+    fn test_from_str() {
+        for address in &[N2_ADDRESS, N3_ADDRESS] {
+            assert_eq!(Address::from_str(address).unwrap().to_string(), *address);
         }
+    }
 
-        #[test]
-        fn to_str() {
-            KEYPAIRS.iter().for_each(|(_, expected_address)| {
-                let address = Address::from_str(expected_address).unwrap();
-                test_to_str(expected_address, &address);
-            });
-        }
+    #[test]
+    fn test_network_addresses() {
+        let public_key = hex::decode("02208aea0068c429a03316e37be0e3e8e21e6cda5442df4c5914a19b3a9b6de375").unwrap();
+        let public_key = PublicKey(<[u8; 33]>::try_from(public_key.as_slice()).unwrap());
+
+        let address = Address::checksum_address_with(&public_key, &ProtocolSettings::N2_MAINNET);
+        assert_eq!(address.to_string(), "ANTQRzAxtjp7wyvWc2Y9SLkfxTkyRKuioR");
+        assert_eq!(address.to_script_hash(), "d070b967c7058db990a83579193674deb4ac4a49");
+
+        let address = Address::checksum_address(&public_key);
+        assert_eq!(address.to_string(), "Nari61wgSjpn2b8958ticxYqMWJ3k1EB6o");
+        assert_eq!(address.to_script_hash(), "1ad0b8400ff864ecb709d8c804184095ef08eca3");
+
+        let address = Address::from_script_hash("1ad0b8400ff864ecb709d8c804184095ef08eca3").unwrap();
+        assert_eq!(address.to_string(), "Nari61wgSjpn2b8958ticxYqMWJ3k1EB6o");
+        let address =
+            Address::from_script_hash_with("d070b967c7058db990a83579193674deb4ac4a49", &ProtocolSettings::N2_MAINNET)
+                .unwrap();
+        assert_eq!(address.to_string(), "ANTQRzAxtjp7wyvWc2Y9SLkfxTkyRKuioR");
     }
 
     #[test]
     fn test_checksum_address_invalid() {
         // Mismatched keypair
 
-        let private_key = "f89f23eaeac18252fedf81bb8318d3c111d48c19b0680dcf6e0a8d5136caf287";
-        let expected_address = "0xF9001e6AEE6EA439D713fBbF960EbA76f4770E2B";
-
-        let private_key = PrivateKey::from_str(private_key).unwrap();
+        let private_key = PrivateKey::from_str(PRIVATE_KEY).unwrap();
         let address = Address::from_private_key(&private_key).unwrap();
-        assert_ne!(expected_address, address.to_string());
-
-        let public_key = PublicKey::from_private_key(&private_key);
-        let address = Address::from_public_key(&public_key).unwrap();
-        assert_ne!(expected_address, address.to_string());
+        assert_ne!(N2_ADDRESS, address.to_string());
 
         // Invalid address length
 
         let address = "9";
         assert!(Address::from_str(address).is_err());
 
-        let address = "0x9";
+        let address = "NS5F1Mth64bgJW4LgmEMNdEk7pVeAp3jr";
         assert!(Address::from_str(address).is_err());
 
-        let address = "0x9141B7539E7902872095C408BfA294435e2b8c8";
+        let address = "NS5F1Mth64bgJW4LgmEMNdEk7pVeAp3jrFNS5F1Mth64bgJW4Lg";
         assert!(Address::from_str(address).is_err());
 
-        let address = "0x9141B7539E7902872095C408BfA294435e2b8c8a0x9141B7539E7902872095";
+        // Invalid checksum and characters
+
+        let address = "NS5F1Mth64bgJW4LgmEMNdEk7pVeAp3jrG";
         assert!(Address::from_str(address).is_err());
 
-        let address = "0x9141B7539E7902872095C408BfA294435e2b8c8a0x9141B7539E7902872095C408BfA294435e2b8c8a";
+        let address = "0x9141B7539E7902872095C408BfA294435e2b8c8a";
         assert!(Address::from_str(address).is_err());
     }
 }
-
-
@@ -0,0 +1,737 @@
+use std::{fmt, str::FromStr};
+
+use failure::Fail;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcPoint, PointConversionForm};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+use neo_core::crypto::{checksum, hash160};
+use neo_core::neo_type::{PrivateKeyBin, PublicKeyBin, ScriptHashBin, PRIVATE_KEY_BIN_LEN, PUBLIC_KEY_BIN_LEN};
+use neo_core::protocol_settings::ProtocolVersion;
+use neo_core::ProtocolSettings;
+use neo_crypto::{hex, FromBase58, ToBase58};
+
+use crate::account::{AccountJSON, Contract, ContractParameterJSON};
+use crate::mnemonic::Mnemonic;
+use crate::nep2::{Nep2, Nep2Error, ScryptParams};
+use crate::private_key::PrivateKey;
+
+/// Coin type of Neo registered in SLIP-44, the second level of `m/44'/888'/account'/change/index`.
+pub const NEO_COIN_TYPE: u32 = 888;
+
+/// Child numbers from this one on are hardened.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// HMAC key deriving the master key from a seed, as specified by SLIP-10 for NIST P-256.
+const CURVE_SEED_KEY: &[u8] = b"Nist256p1 seed";
+
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+/// Length of a base58check decoded extended key, without its checksum.
+const EXTENDED_KEY_BIN_LEN: usize = 78;
+
+/// Index of a key among the children of its parent.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ChildNumber(pub u32);
+
+impl ChildNumber {
+    pub fn normal(index: u32) -> Result<Self, HdKeyError> {
+        if index >= HARDENED_OFFSET {
+            return Err(HdKeyError::InvalidChildNumber(index.to_string()));
+        }
+        Ok(ChildNumber(index))
+    }
+
+    pub fn hardened(index: u32) -> Result<Self, HdKeyError> {
+        Ok(ChildNumber(ChildNumber::normal(index)?.0 | HARDENED_OFFSET))
+    }
+
+    pub fn is_hardened(&self) -> bool {
+        self.0 >= HARDENED_OFFSET
+    }
+
+    /// The index without the hardened bit.
+    pub fn index(&self) -> u32 {
+        self.0 & !HARDENED_OFFSET
+    }
+}
+
+impl FromStr for ChildNumber {
+    type Err = HdKeyError;
+
+    /// Parses `0`, or `0'` and `0h` for hardened children.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, hardened) = match s.strip_suffix(|c| c == '\'' || c == 'h' || c == 'H') {
+            Some(index) => (index, true),
+            None => (s, false),
+        };
+        let index = index
+            .parse::<u32>()
+            .map_err(|_| HdKeyError::InvalidChildNumber(s.to_string()))?;
+
+        if hardened {
+            ChildNumber::hardened(index)
+        } else {
+            ChildNumber::normal(index)
+        }
+    }
+}
+
+impl fmt::Display for ChildNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_hardened() {
+            write!(f, "{}'", self.index())
+        } else {
+            write!(f, "{}", self.index())
+        }
+    }
+}
+
+/// A path from the master key, such as `m/44'/888'/0'/0/0`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct DerivationPath(pub Vec<ChildNumber>);
+
+impl DerivationPath {
+    /**
+     * The BIP-44 path of a Neo account, `m/44'/888'/account'/change/index`.
+     * @param account Hardened account index.
+     * @param change 0 for receiving keys, 1 for change keys.
+     * @param index Index of the key in the chain.
+     */
+    pub fn neo(account: u32, change: u32, index: u32) -> Result<Self, HdKeyError> {
+        Ok(DerivationPath(vec![
+            ChildNumber::hardened(44)?,
+            ChildNumber::hardened(NEO_COIN_TYPE)?,
+            ChildNumber::hardened(account)?,
+            ChildNumber::normal(change)?,
+            ChildNumber::normal(index)?,
+        ]))
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = HdKeyError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(HdKeyError::InvalidPath(path.to_string()));
+        }
+
+        Ok(DerivationPath(parts.map(str::parse).collect::<Result<_, _>>()?))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for child in &self.0 {
+            write!(f, "/{}", child)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * A SLIP-10 extended private key on NIST P-256, the curve of Neo keys.
+ *
+ * Derivation follows BIP-32 except that an intermediate value outside the
+ * curve order is retried instead of skipped, so keys derived from a mnemonic
+ * match the ones of other Neo wallets using `m/44'/888'/account'/change/index`.
+ */
+#[derive(Clone, Eq, PartialEq)]
+pub struct ExtendedPrivateKey {
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: ChildNumber,
+    pub chain_code: [u8; 32],
+    private_key: PrivateKeyBin,
+}
+
+impl ExtendedPrivateKey {
+    /// Derives the master key of a BIP-39 seed.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, HdKeyError> {
+        let curve = Curve::new()?;
+        let mut data = seed.to_vec();
+        loop {
+            let i = hmac_sha512(CURVE_SEED_KEY, &data)?;
+            let (il, ir) = i.split_at(32);
+            if curve.is_valid_private_key(il)? {
+                return Ok(Self {
+                    depth: 0,
+                    parent_fingerprint: [0u8; 4],
+                    child_number: ChildNumber(0),
+                    chain_code: to_array(ir),
+                    private_key: to_array(il),
+                });
+            }
+            data = i.to_vec();
+        }
+    }
+
+    /// Derives the master key of a mnemonic, `passphrase` being empty if there is none.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Result<Self, HdKeyError> {
+        ExtendedPrivateKey::from_seed(&mnemonic.to_seed(passphrase))
+    }
+
+    pub fn derive_child(&self, child_number: ChildNumber) -> Result<Self, HdKeyError> {
+        let mut curve = Curve::new()?;
+        let mut data = Vec::with_capacity(1 + PUBLIC_KEY_BIN_LEN + 4);
+        if child_number.is_hardened() {
+            data.push(0);
+            data.extend_from_slice(&self.private_key);
+        } else {
+            data.extend_from_slice(&curve.public_key(&self.private_key)?);
+        }
+        data.extend_from_slice(&child_number.0.to_be_bytes());
+
+        loop {
+            let i = hmac_sha512(&self.chain_code, &data)?;
+            let (il, ir) = i.split_at(32);
+            if let Some(private_key) = curve.add_private_keys(il, &self.private_key)? {
+                return Ok(Self {
+                    depth: self.depth.checked_add(1).ok_or(HdKeyError::MaxDepthExceeded)?,
+                    parent_fingerprint: self.fingerprint()?,
+                    child_number,
+                    chain_code: to_array(ir),
+                    private_key,
+                });
+            }
+            data = retry_data(ir, child_number);
+        }
+    }
+
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, HdKeyError> {
+        path.0
+            .iter()
+            .try_fold(self.clone(), |key, child_number| key.derive_child(*child_number))
+    }
+
+    pub fn private_key(&self) -> &PrivateKeyBin {
+        &self.private_key
+    }
+
+    pub fn to_private_key(&self) -> PrivateKey {
+        PrivateKey(self.private_key)
+    }
+
+    pub fn public_key(&self) -> Result<PublicKeyBin, HdKeyError> {
+        Curve::new()?.public_key(&self.private_key)
+    }
+
+    pub fn fingerprint(&self) -> Result<[u8; 4], HdKeyError> {
+        Ok(fingerprint(&self.public_key()?))
+    }
+
+    /// The extended public key deriving the same non-hardened children, for watch-only wallets.
+    pub fn to_extended_public_key(&self) -> Result<ExtendedPublicKey, HdKeyError> {
+        Ok(ExtendedPublicKey {
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            chain_code: self.chain_code,
+            public_key: self.public_key()?,
+        })
+    }
+
+    /// Encrypts the private key according to NEP-2, for the address on the network of `settings`.
+    pub fn to_nep2(
+        &self,
+        passphrase: &str,
+        params: &ScryptParams,
+        settings: &ProtocolSettings,
+    ) -> Result<String, HdKeyError> {
+        Ok(Nep2::encrypt_with(&self.private_key, passphrase, params, settings)?)
+    }
+
+    /// A NEP-6 account holding the NEP-2 encrypted key and the standard verification contract.
+    pub fn to_account(
+        &self,
+        passphrase: &str,
+        params: &ScryptParams,
+        settings: &ProtocolSettings,
+    ) -> Result<AccountJSON, HdKeyError> {
+        let mut account = self.to_extended_public_key()?.to_account(settings);
+        account.key = Some(self.to_nep2(passphrase, params, settings)?);
+        Ok(account)
+    }
+}
+
+impl FromStr for ExtendedPrivateKey {
+    type Err = HdKeyError;
+
+    /// Parses a base58check `xprv` key.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (depth, parent_fingerprint, child_number, chain_code, key) = decode(s, &XPRV_VERSION)?;
+        if key[0] != 0 || !Curve::new()?.is_valid_private_key(&key[1..])? {
+            return Err(HdKeyError::InvalidKey);
+        }
+
+        Ok(Self {
+            depth,
+            parent_fingerprint,
+            child_number,
+            chain_code,
+            private_key: to_array(&key[1..]),
+        })
+    }
+}
+
+impl fmt::Display for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut key = [0u8; PUBLIC_KEY_BIN_LEN];
+        key[1..].copy_from_slice(&self.private_key);
+        let encoded = encode(
+            &XPRV_VERSION,
+            self.depth,
+            &self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &key,
+        );
+        write!(f, "{}", encoded)
+    }
+}
+
+/// The private key is a secret, it is left out of debug output.
+impl fmt::Debug for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExtendedPrivateKey")
+            .field("depth", &self.depth)
+            .field("parent_fingerprint", &hex::encode(self.parent_fingerprint))
+            .field("child_number", &self.child_number)
+            .finish()
+    }
+}
+
+/// An extended public key, deriving the public keys of the non-hardened children of its private key.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ExtendedPublicKey {
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: ChildNumber,
+    pub chain_code: [u8; 32],
+    pub public_key: PublicKeyBin,
+}
+
+impl ExtendedPublicKey {
+    pub fn derive_child(&self, child_number: ChildNumber) -> Result<Self, HdKeyError> {
+        if child_number.is_hardened() {
+            return Err(HdKeyError::HardenedPublicDerivation(child_number));
+        }
+
+        let mut curve = Curve::new()?;
+        let mut data = Vec::with_capacity(1 + PUBLIC_KEY_BIN_LEN + 4);
+        data.extend_from_slice(&self.public_key);
+        data.extend_from_slice(&child_number.0.to_be_bytes());
+
+        loop {
+            let i = hmac_sha512(&self.chain_code, &data)?;
+            let (il, ir) = i.split_at(32);
+            if let Some(public_key) = curve.add_public_key(il, &self.public_key)? {
+                return Ok(Self {
+                    depth: self.depth.checked_add(1).ok_or(HdKeyError::MaxDepthExceeded)?,
+                    parent_fingerprint: fingerprint(&self.public_key),
+                    child_number,
+                    chain_code: to_array(ir),
+                    public_key,
+                });
+            }
+            data = retry_data(ir, child_number);
+        }
+    }
+
+    /// Derives a path relative to this key, such as `m/0/1` from an account key.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, HdKeyError> {
+        path.0
+            .iter()
+            .try_fold(self.clone(), |key, child_number| key.derive_child(*child_number))
+    }
+
+    pub fn script_hash(&self, settings: &ProtocolSettings) -> ScriptHashBin {
+        settings.script_hash(&self.public_key)
+    }
+
+    pub fn address(&self, settings: &ProtocolSettings) -> String {
+        settings.address_from_public_key(&self.public_key)
+    }
+
+    /// A watch-only NEP-6 account with the standard verification contract of the key.
+    pub fn to_account(&self, settings: &ProtocolSettings) -> AccountJSON {
+        let script = settings.verification_script(&self.public_key);
+        let script = match settings.version {
+            ProtocolVersion::N2 => hex::encode(script),
+            ProtocolVersion::N3 => base64::encode(script),
+        };
+
+        AccountJSON {
+            address: self.address(settings),
+            label: None,
+            is_default: false,
+            lock: false,
+            key: None,
+            contract: Some(Contract {
+                script,
+                parameters: vec![ContractParameterJSON {
+                    name: "signature".to_string(),
+                    parameter_type: "Signature".to_string(),
+                    unknown: Default::default(),
+                }],
+                deployed: false,
+                unknown: Default::default(),
+            }),
+            extra: None,
+            unknown: Default::default(),
+        }
+    }
+}
+
+impl FromStr for ExtendedPublicKey {
+    type Err = HdKeyError;
+
+    /// Parses a base58check `xpub` key.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (depth, parent_fingerprint, child_number, chain_code, key) = decode(s, &XPUB_VERSION)?;
+        Curve::new()?.point(&key)?;
+
+        Ok(Self {
+            depth,
+            parent_fingerprint,
+            child_number,
+            chain_code,
+            public_key: key,
+        })
+    }
+}
+
+impl fmt::Display for ExtendedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let encoded = encode(
+            &XPUB_VERSION,
+            self.depth,
+            &self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &self.public_key,
+        );
+        write!(f, "{}", encoded)
+    }
+}
+
+/// NIST P-256 and its order, with the operations derivation needs.
+struct Curve {
+    group: EcGroup,
+    order: BigNum,
+    ctx: BigNumContext,
+}
+
+impl Curve {
+    fn new() -> Result<Self, HdKeyError> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        let mut ctx = BigNumContext::new()?;
+        let mut order = BigNum::new()?;
+        group.order(&mut order, &mut ctx)?;
+        Ok(Self { group, order, ctx })
+    }
+
+    /// Whether `key` is in `[1, n)`.
+    fn is_valid_private_key(&self, key: &[u8]) -> Result<bool, HdKeyError> {
+        let key = BigNum::from_slice(key)?;
+        Ok(key.num_bits() > 0 && key < self.order)
+    }
+
+    fn public_key(&mut self, private_key: &[u8]) -> Result<PublicKeyBin, HdKeyError> {
+        let private_key = BigNum::from_slice(private_key)?;
+        let mut point = EcPoint::new(&self.group)?;
+        point.mul_generator2(&self.group, &private_key, &mut self.ctx)?;
+        self.serialize(&point)
+    }
+
+    /// `il + key mod n`, `None` if `il` is not below `n` or the sum is zero.
+    fn add_private_keys(&mut self, il: &[u8], key: &[u8]) -> Result<Option<PrivateKeyBin>, HdKeyError> {
+        let il = BigNum::from_slice(il)?;
+        if il >= self.order {
+            return Ok(None);
+        }
+
+        let key = BigNum::from_slice(key)?;
+        let mut sum = BigNum::new()?;
+        sum.mod_add(&il, &key, &self.order, &mut self.ctx)?;
+        if sum.num_bits() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(to_array(&sum.to_vec_padded(PRIVATE_KEY_BIN_LEN as i32)?)))
+    }
+
+    /// `il * G + key`, `None` if `il` is not below `n` or the sum is the point at infinity.
+    fn add_public_key(&mut self, il: &[u8], key: &PublicKeyBin) -> Result<Option<PublicKeyBin>, HdKeyError> {
+        let il = BigNum::from_slice(il)?;
+        if il >= self.order {
+            return Ok(None);
+        }
+
+        let mut tweak = EcPoint::new(&self.group)?;
+        tweak.mul_generator2(&self.group, &il, &mut self.ctx)?;
+        let key = self.point(key)?;
+        let mut sum = EcPoint::new(&self.group)?;
+        sum.add(&self.group, &tweak, &key, &mut self.ctx)?;
+        if sum.is_infinity(&self.group) {
+            return Ok(None);
+        }
+        Ok(Some(self.serialize(&sum)?))
+    }
+
+    fn point(&mut self, key: &[u8]) -> Result<EcPoint, HdKeyError> {
+        EcPoint::from_bytes(&self.group, key, &mut self.ctx).map_err(|_| HdKeyError::InvalidKey)
+    }
+
+    fn serialize(&mut self, point: &EcPoint) -> Result<PublicKeyBin, HdKeyError> {
+        let bytes = point.to_bytes(&self.group, PointConversionForm::COMPRESSED, &mut self.ctx)?;
+        Ok(to_array(&bytes))
+    }
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> Result<[u8; 64], HdKeyError> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(MessageDigest::sha512(), &key)?;
+    signer.update(data)?;
+
+    let mut mac = [0u8; 64];
+    signer.sign(&mut mac)?;
+    Ok(mac)
+}
+
+/// SLIP-10 derives again from `0x01 || IR || i` when a child key is invalid.
+fn retry_data(ir: &[u8], child_number: ChildNumber) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + 32 + 4);
+    data.push(1);
+    data.extend_from_slice(ir);
+    data.extend_from_slice(&child_number.0.to_be_bytes());
+    data
+}
+
+fn fingerprint(public_key: &[u8]) -> [u8; 4] {
+    to_array(&hash160(public_key)[0..4])
+}
+
+fn to_array<const N: usize>(slice: &[u8]) -> [u8; N] {
+    let mut array = [0u8; N];
+    array.copy_from_slice(slice);
+    array
+}
+
+fn encode(
+    version: &[u8; 4],
+    depth: u8,
+    parent_fingerprint: &[u8; 4],
+    child_number: ChildNumber,
+    chain_code: &[u8; 32],
+    key: &[u8; PUBLIC_KEY_BIN_LEN],
+) -> String {
+    let mut data = Vec::with_capacity(EXTENDED_KEY_BIN_LEN + 4);
+    data.extend_from_slice(version);
+    data.push(depth);
+    data.extend_from_slice(parent_fingerprint);
+    data.extend_from_slice(&child_number.0.to_be_bytes());
+    data.extend_from_slice(chain_code);
+    data.extend_from_slice(key);
+
+    let sum = checksum(&data);
+    data.extend_from_slice(&sum[0..4]);
+    data.to_base58()
+}
+
+type DecodedKey = (u8, [u8; 4], ChildNumber, [u8; 32], PublicKeyBin);
+
+fn decode(s: &str, version: &[u8; 4]) -> Result<DecodedKey, HdKeyError> {
+    let data = s.from_base58().map_err(|_| HdKeyError::InvalidExtendedKey)?;
+    if data.len() != EXTENDED_KEY_BIN_LEN + 4 {
+        return Err(HdKeyError::InvalidExtendedKey);
+    }
+
+    let (data, sum) = data.split_at(EXTENDED_KEY_BIN_LEN);
+    if checksum(data)[0..4] != *sum {
+        return Err(HdKeyError::InvalidChecksum);
+    }
+    if data[0..4] != version[..] {
+        return Err(HdKeyError::InvalidExtendedKey);
+    }
+
+    let child_number = u32::from_be_bytes(to_array(&data[9..13]));
+    Ok((
+        data[4],
+        to_array(&data[5..9]),
+        ChildNumber(child_number),
+        to_array(&data[13..45]),
+        to_array(&data[45..78]),
+    ))
+}
+
+#[derive(Debug, Fail)]
+pub enum HdKeyError {
+    #[fail(display = "{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[fail(display = "hardened child {} can't be derived from a public key", _0)]
+    HardenedPublicDerivation(ChildNumber),
+
+    #[fail(display = "invalid child number: {}", _0)]
+    InvalidChildNumber(String),
+
+    #[fail(display = "invalid derivation path: {}", _0)]
+    InvalidPath(String),
+
+    #[fail(display = "invalid extended key")]
+    InvalidExtendedKey,
+
+    #[fail(display = "invalid extended key checksum")]
+    InvalidChecksum,
+
+    #[fail(display = "invalid key")]
+    InvalidKey,
+
+    #[fail(display = "maximum depth exceeded")]
+    MaxDepthExceeded,
+
+    #[fail(display = "{}", _0)]
+    Nep2(Nep2Error),
+}
+
+impl From<openssl::error::ErrorStack> for HdKeyError {
+    fn from(error: openssl::error::ErrorStack) -> Self {
+        HdKeyError::Crate("openssl", format!("{:?}", error))
+    }
+}
+
+impl From<Nep2Error> for HdKeyError {
+    fn from(error: Nep2Error) -> Self {
+        HdKeyError::Nep2(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+    const ABANDON: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    /// Key of `m/44'/888'/0'/0/0` of the mnemonic above, and its N3 address.
+    const NEO_PRIVATE_KEY: &str = "38bcb1943801333aecdb9099b368ca8ea5b13a2c22862f8e1be77a46ed88738b";
+    const NEO_ADDRESS: &str = "NYqCjmV8g8PFCYpyD3K4kSCkQxZff1UNMV";
+    /// `m/0'/1` of the SLIP-10 seed.
+    const CHILD_XPRV: &str =
+        "xprv9wvN2XR2jhXFtoRvikiU4HhtMgFanjvmmMhRHj5KMKtHi2PN9aZPjAVWDLrjUbi5qejuMeQ3jH4ysGCVjVMMgERS3zCpv9DgbSEeHBnmR5k";
+    const CHILD_XPUB: &str =
+        "xpub6AuiS2wva55Z7HWPpnFURRecui65CCed8ad267UvufRGapiWh7seGxoz4e9nu9G1aBYqGsEV5RjhqLAjNWm294RZTgU8UgQ821iaPY5tazr";
+
+    #[test]
+    fn test_slip10_vector() {
+        // SLIP-10 test vector 1 for nist256p1
+        let master = ExtendedPrivateKey::from_seed(&hex::decode(SEED).unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(master.chain_code),
+            "beeb672fe4621673f722f38529c07392fecaa61015c80c34f29ce8b41b3cb6ea"
+        );
+        assert_eq!(
+            hex::encode(master.private_key()),
+            "612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2"
+        );
+        assert_eq!(
+            hex::encode(&master.public_key().unwrap()[..]),
+            "0266874dc6ade47b3ecd096745ca09bcd29638dd52c2c12117b11ed3e458cfa9e8"
+        );
+
+        let child = master.derive_path(&"m/0'".parse().unwrap()).unwrap();
+        assert_eq!(hex::encode(child.parent_fingerprint), "be6105b5");
+        assert_eq!(
+            hex::encode(child.chain_code),
+            "3460cea53e6a6bb5fb391eeef3237ffd8724bf0a40e94943c98b83825342ee11"
+        );
+        assert_eq!(
+            hex::encode(child.private_key()),
+            "6939694369114c67917a182c59ddb8cafc3004e63ca5d3b84403ba8613debc0c"
+        );
+        assert_eq!(
+            hex::encode(&child.public_key().unwrap()[..]),
+            "0384610f5ecffe8fda089363a41f56a5c7ffc1d81b59a612d0d649b2d22355590c"
+        );
+    }
+
+    #[test]
+    fn test_neo_path() {
+        let path = DerivationPath::neo(0, 0, 0).unwrap();
+        assert_eq!(path.to_string(), "m/44'/888'/0'/0/0");
+        assert_eq!("m/44h/888h/0h/0/0".parse::<DerivationPath>().unwrap(), path);
+
+        let master = ExtendedPrivateKey::from_mnemonic(&ABANDON.parse().unwrap(), "").unwrap();
+        let key = master.derive_path(&path).unwrap();
+        assert_eq!(key.depth, 5);
+        assert_eq!(hex::encode(key.private_key()), NEO_PRIVATE_KEY);
+        assert_eq!(
+            key.to_extended_public_key()
+                .unwrap()
+                .address(&ProtocolSettings::default()),
+            NEO_ADDRESS
+        );
+        assert_eq!(key.to_private_key().0, *key.private_key());
+    }
+
+    #[test]
+    fn test_public_derivation() {
+        let master = ExtendedPrivateKey::from_mnemonic(&ABANDON.parse().unwrap(), "").unwrap();
+        let account = master.derive_path(&"m/44'/888'/0'".parse().unwrap()).unwrap();
+        let watch_only = account.to_extended_public_key().unwrap();
+
+        let path = "m/0/7".parse().unwrap();
+        assert_eq!(
+            watch_only.derive_path(&path).unwrap(),
+            account.derive_path(&path).unwrap().to_extended_public_key().unwrap()
+        );
+        assert!(matches!(
+            watch_only.derive_child(ChildNumber::hardened(0).unwrap()),
+            Err(HdKeyError::HardenedPublicDerivation(_))
+        ));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let master = ExtendedPrivateKey::from_seed(&hex::decode(SEED).unwrap()).unwrap();
+        let child = master.derive_path(&"m/0'/1".parse().unwrap()).unwrap();
+        assert_eq!(child.to_string(), CHILD_XPRV);
+        assert_eq!(child.to_extended_public_key().unwrap().to_string(), CHILD_XPUB);
+
+        assert_eq!(CHILD_XPRV.parse::<ExtendedPrivateKey>().unwrap(), child);
+        assert_eq!(
+            CHILD_XPUB.parse::<ExtendedPublicKey>().unwrap(),
+            child.to_extended_public_key().unwrap()
+        );
+        assert!(matches!(
+            CHILD_XPUB.parse::<ExtendedPrivateKey>(),
+            Err(HdKeyError::InvalidExtendedKey)
+        ));
+    }
+
+    #[test]
+    fn test_account() {
+        let master = ExtendedPrivateKey::from_mnemonic(&ABANDON.parse().unwrap(), "").unwrap();
+        let key = master.derive_path(&DerivationPath::neo(0, 0, 0).unwrap()).unwrap();
+        let params = ScryptParams { n: 2, r: 1, p: 1 };
+        let settings = ProtocolSettings::default();
+
+        let account = key.to_account("neo", &params, &settings).unwrap();
+        assert_eq!(account.address, NEO_ADDRESS);
+        let contract = account.contract.unwrap();
+        assert_eq!(
+            base64::decode(&contract.script).unwrap(),
+            settings.verification_script(&key.public_key().unwrap())
+        );
+
+        let private_key = Nep2::decrypt(&account.key.unwrap(), "neo", &params).unwrap();
+        assert_eq!(private_key, *key.private_key());
+    }
+}
@@ -2,18 +2,18 @@ pub mod verify;
 pub mod nep2;
 pub mod nep6;
 pub mod signing;
-pub mod core;
-pub mod coin;
-pub mod claim_item;
-pub mod asset_balance;
+pub mod signer;
 pub mod private_key;
 pub mod public_key;
 pub mod address;
 pub mod wif;
 pub mod multisig;
 pub mod account;
-pub mod key_trait;
+pub mod mnemonic;
+pub mod hd_key;
 
+#[cfg(test)]
+mod test_vectors;
 
 #[cfg(test)]
 mod tests {
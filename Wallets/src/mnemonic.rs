@@ -0,0 +1,259 @@
+use std::{fmt, str::FromStr};
+
+use failure::Fail;
+use openssl::hash::MessageDigest;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::rand::rand_bytes;
+use unicode_normalization::UnicodeNormalization;
+
+use neo_crypto::sha2::{Digest, Sha256};
+
+/// The BIP-39 English word list, one word per line in index order.
+const ENGLISH: &str = include_str!("wordlist/english.txt");
+
+/// Number of PBKDF2 rounds deriving the seed.
+const PBKDF2_ROUNDS: usize = 2048;
+
+/// Length of the seed derived from a mnemonic.
+pub const SEED_LEN: usize = 64;
+
+/**
+ * A BIP-39 mnemonic, 12 to 24 English words encoding 128 to 256 bits of
+ * entropy and a checksum.
+ *
+ * The phrase is kept as the wallet secret and turned into the seed of an
+ * HD key tree with [`Mnemonic::to_seed`], the same way other Neo wallets do.
+ */
+#[derive(Clone, Eq, PartialEq)]
+pub struct Mnemonic {
+    entropy: Vec<u8>,
+    phrase: String,
+}
+
+impl Mnemonic {
+    /// Generates a random mnemonic of `word_count` words, one of 12, 15, 18, 21 or 24.
+    pub fn generate(word_count: usize) -> Result<Self, MnemonicError> {
+        if !is_valid_word_count(word_count) {
+            return Err(MnemonicError::InvalidWordCount(word_count));
+        }
+
+        let mut entropy = vec![0u8; word_count / 3 * 4];
+        rand_bytes(&mut entropy)?;
+        Mnemonic::from_entropy(&entropy)
+    }
+
+    /// Encodes `entropy`, 16 to 32 bytes in steps of 4, as a mnemonic.
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, MnemonicError> {
+        if !matches!(entropy.len(), 16 | 20 | 24 | 28 | 32) {
+            return Err(MnemonicError::InvalidEntropyLength(entropy.len()));
+        }
+
+        // The entropy is followed by the first len / 4 bits of its sha256, read in groups of 11 bits.
+        let mut bits = entropy.to_vec();
+        bits.push(Sha256::digest(entropy)[0]);
+        let word_count = entropy.len() * 8 / 32 * 3;
+
+        let words = (0..word_count)
+            .map(|i| {
+                let index = (0..11).fold(0, |index, j| (index << 1) | bit(&bits, i * 11 + j) as usize);
+                word(index)
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            entropy: entropy.to_vec(),
+            phrase: words.join(" "),
+        })
+    }
+
+    /// Parses a phrase, checking that every word is in the word list and that the checksum matches.
+    pub fn from_phrase(phrase: &str) -> Result<Self, MnemonicError> {
+        let words = phrase.split_whitespace().collect::<Vec<_>>();
+        if !is_valid_word_count(words.len()) {
+            return Err(MnemonicError::InvalidWordCount(words.len()));
+        }
+
+        let mut bits = vec![0u8; words.len() * 11 / 8 + 1];
+        for (i, word) in words.iter().enumerate() {
+            let index = index_of(word).ok_or_else(|| MnemonicError::UnknownWord(word.to_string()))?;
+            for j in 0..11 {
+                if index & (1 << (10 - j)) != 0 {
+                    let position = i * 11 + j;
+                    bits[position / 8] |= 0x80 >> (position % 8);
+                }
+            }
+        }
+
+        let entropy_len = words.len() / 3 * 4;
+        let mnemonic = Mnemonic::from_entropy(&bits[..entropy_len])?;
+        if mnemonic.phrase.split(' ').ne(words.iter().copied()) {
+            return Err(MnemonicError::InvalidChecksum);
+        }
+
+        Ok(mnemonic)
+    }
+
+    pub fn phrase(&self) -> &str {
+        &self.phrase
+    }
+
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.entropy.len() / 4 * 3
+    }
+
+    /**
+     * Derives the 64 byte seed of the mnemonic.
+     * @param passphrase Optional extra secret, an empty string if there is none.
+     */
+    pub fn to_seed(&self, passphrase: &str) -> [u8; SEED_LEN] {
+        let phrase = self.phrase.nfkd().collect::<String>();
+        let salt = format!("mnemonic{}", passphrase).nfkd().collect::<String>();
+
+        let mut seed = [0u8; SEED_LEN];
+        pbkdf2_hmac(
+            phrase.as_bytes(),
+            salt.as_bytes(),
+            PBKDF2_ROUNDS,
+            MessageDigest::sha512(),
+            &mut seed,
+        )
+        .expect("pbkdf2 with sha512 never fails");
+        seed
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = MnemonicError;
+
+    fn from_str(phrase: &str) -> Result<Self, Self::Err> {
+        Mnemonic::from_phrase(phrase)
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.phrase)
+    }
+}
+
+/// The phrase is a secret, it is left out of debug output.
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mnemonic({} words)", self.word_count())
+    }
+}
+
+fn is_valid_word_count(word_count: usize) -> bool {
+    matches!(word_count, 12 | 15 | 18 | 21 | 24)
+}
+
+fn bit(data: &[u8], position: usize) -> u8 {
+    (data[position / 8] >> (7 - position % 8)) & 1
+}
+
+fn word(index: usize) -> &'static str {
+    ENGLISH.lines().nth(index).expect("the word list has 2048 words")
+}
+
+fn index_of(word: &str) -> Option<usize> {
+    ENGLISH.lines().position(|w| w == word)
+}
+
+#[derive(Debug, Fail)]
+pub enum MnemonicError {
+    #[fail(display = "{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[fail(display = "invalid entropy length: {}", _0)]
+    InvalidEntropyLength(usize),
+
+    #[fail(display = "invalid word count: {}", _0)]
+    InvalidWordCount(usize),
+
+    #[fail(display = "unknown word: {}", _0)]
+    UnknownWord(String),
+
+    #[fail(display = "invalid mnemonic checksum")]
+    InvalidChecksum,
+}
+
+impl From<openssl::error::ErrorStack> for MnemonicError {
+    fn from(error: openssl::error::ErrorStack) -> Self {
+        MnemonicError::Crate("openssl", format!("{:?}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_crypto::hex;
+
+    use super::*;
+
+    /// (entropy, phrase, seed with the passphrase "TREZOR"), from the BIP-39 reference vectors.
+    const VECTORS: [(&str, &str, &str); 3] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+        ),
+    ];
+
+    #[test]
+    fn test_vectors() {
+        for (entropy, phrase, seed) in VECTORS.iter() {
+            let mnemonic = Mnemonic::from_entropy(&hex::decode(entropy).unwrap()).unwrap();
+            assert_eq!(mnemonic.phrase(), *phrase);
+            assert_eq!(hex::encode(&mnemonic.to_seed("TREZOR")[..]), *seed);
+
+            let parsed = Mnemonic::from_phrase(phrase).unwrap();
+            assert_eq!(hex::encode(parsed.entropy()), *entropy);
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        for word_count in (12..=24).step_by(3) {
+            let mnemonic = Mnemonic::generate(word_count).unwrap();
+            assert_eq!(mnemonic.phrase().split(' ').count(), word_count);
+            assert_eq!(mnemonic.phrase().parse::<Mnemonic>().unwrap(), mnemonic);
+        }
+        assert!(matches!(
+            Mnemonic::generate(13),
+            Err(MnemonicError::InvalidWordCount(13))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_phrase() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert!(matches!(
+            Mnemonic::from_phrase(phrase),
+            Err(MnemonicError::InvalidChecksum)
+        ));
+
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon neo";
+        assert!(matches!(
+            Mnemonic::from_phrase(phrase),
+            Err(MnemonicError::UnknownWord(_))
+        ));
+
+        assert!(matches!(
+            Mnemonic::from_phrase("abandon about"),
+            Err(MnemonicError::InvalidWordCount(2))
+        ));
+    }
+}
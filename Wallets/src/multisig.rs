@@ -1,105 +1,51 @@
-use neo_core::convert::hex2int;
-use neo_core::fixed8::Fixed8;
-use neo_core::misc::reverse_hex;
-use neo_core::stringstream::StringStream;
+//! Multi-signature accounts.
+//!
+//! The verification script is built by `neo_core::ProtocolSettings`, the
+//! same builder the contract parameters context and the ledger check
+//! witnesses against, so every co-signer derives the same account.
 
-pub fn construct_multi_sig_verification_script(signingThreshold: usize, keys: &[str]) -> String {
-    if signingThreshold > keys.len() {
-        panic!("signingThreshold must be smaller than or equal to number of keys");
-    }
+use neo_core::neo_type::PublicKeyBin;
+use neo_core::ProtocolSettings;
 
-    let ss = ScriptBuilder.new();
-    ss.emitPush(signingThreshold);
-    for k in keys {
-        if !is_public_keylicKey(k, true) {
-            panic!("{} is not a valid encoded public ke", k);
-        }
-        ss.emitPush(k);
-    }
-    ss.emitPush(keys.len());
-    ss.emit(OpCode.CHECKMULTISIG);
-    return ss.str;
-}
+pub use neo_core::protocol_settings::{MultisigError, MAX_MULTISIG_KEYS};
 
 /**
- * Returns the list of public keys found in the verification script.
- * @param verification_script Verification Script of an Account.
+ * The N3 verification script of the `signing_threshold` out of `public_keys.len()` multi-signature account:
+ * `PUSH m, PUSHDATA1 <key>..., PUSH n, SYSCALL System.Crypto.CheckMultisig`, with the keys sorted.
  */
-pub fn getPublicKeysFromVerificationScript(
-    verification_script: &str
-) -> Vec<String> {
-    let mut ss = StringStream::new(verification_script);
-    let mut keys: Vec<String> = Vec::new();
-    while !ss.isEmpty() {
-        let byte = ss.read(1);
-        if byte == "21" {
-            keys.push(ss.read(33));
-        }
-    }
-
-    keys
+pub fn construct_multi_sig_verification_script(
+    signing_threshold: usize,
+    public_keys: &[PublicKeyBin],
+) -> Result<Vec<u8>, MultisigError> {
+    ProtocolSettings::default().multisig_verification_script(signing_threshold, public_keys)
 }
 
-/**
- * Returns the number of signatures required for signing for a verification Script.
- * @param verificationScript Verification Script of a multi-sig Account.
- */
-pub fn getSigningThresholdFromVerificationScript(
-    verificationScript: &str
-) -> Fixed8 {
-    let check_sig_op_code = &verificationScript[
-        verificationScript.len() - 2..verificationScript.len()];
-    match check_sig_op_code {
-        "ac" => Fixed8(1),
-        "ae" => {
-            let mut ss = StringStream::new(verificationScript);
-            let byte = hex2int(&ss.peek(1).as_str()).unwrap();
+#[cfg(test)]
+mod tests {
+    use neo_crypto::hex;
 
-            return if byte < 80 {
-                let hex_num = reverse_hex(ss.readVarBytes());
-                Fixed8(hex2int(hex_num.as_str()).unwrap())
-            } else {
-                Fixed8(ss.read()) - 80
-            };
-        }
-        _ => panic!("VerificationScript does not call CHECKSIG or CHECKMULTISIG.");
-    }
-}
+    use super::*;
 
-/**
- * Extract signatures from invocationScript
- * @param invocationScript InvocationScript of a Witness.
- */
-pub fn getSignaturesFromInvocationScript(
-    invocationScript: &str
-) -> Vec<String> {
-    let ss = StringStream::new(invocationScript);
-    let mut sigs = Vec::new();
-    while !ss.isEmpty() {
-        let byte = hex2int(ss.peek(1).as_str()).unwrap();
-        if byte > 80 {
-            continue;
-        } else if byte == 4 * 16 {
-            sigs.push(ss.readVarBytes());
-        }
+    fn public_key(s: &str) -> PublicKeyBin {
+        let mut key = [0u8; 33];
+        key.copy_from_slice(&hex::decode(s).unwrap());
+        key
     }
-    return sigs;
-}
-
-#[derive(Debug, Fail)]
-pub enum MultiSigError {
-    #[fail(display = "{}: {}", _0, _1)]
-    Crate(&'static str, String),
 
-    #[fail(display = "invalid byte length: {}", _0)]
-    InvalidByteLength(usize),
-
-    #[fail(display = "invalid character length: {}", _0)]
-    InvalidCharacterLength(usize),
-
-    #[fail(display = "{}", _0)]
-    Message(String),
-
-    #[fail(display = "unsupported format")]
-    UnsupportedFormat,
+    #[test]
+    fn test_construct_multi_sig_verification_script() {
+        let keys = [
+            public_key("02028a99826edc0c97d18e22b6932373d908d323aa7f92656a77ec26e8861699ef"),
+            public_key("031d8e1630ce640966967bc6d95223d21f44304133003140c3b52004dc981349c9"),
+            public_key("02232ce8d2e2063dce0451131851d47421bfc4fc1da4db116fca5302c0756462fa"),
+        ];
+
+        let script = construct_multi_sig_verification_script(2, &keys).unwrap();
+        assert_eq!(script, ProtocolSettings::N3_MAINNET.multisig_verification_script(2, &keys).unwrap());
+        assert_eq!(script[0], 0x12);
+        assert_eq!(&script[script.len() - 6..], &[0x13, 0x41, 0x9e, 0xd0, 0xdc, 0x3a][..]);
+
+        assert!(construct_multi_sig_verification_script(0, &keys).is_err());
+        assert!(construct_multi_sig_verification_script(4, &keys).is_err());
+    }
 }
@@ -1,122 +1,311 @@
-use neo_core::neo_type::{PrivateKeyHex, PrivateKeyBin};
-use std::error::Error;
-use neo_core::KeyPair;
-use neo_core::consts::{SCRYPT_DK_LEN, SCRYPT_R, SCRYPT_P, SCRYPT_LOG_N, NEP_HEADER_1, NEP_HEADER_2, NEP_FLAG};
-use scrypt::Params;
-use neo_crypto::aes::Aes;
-use std::convert::{TryFrom, TryInto};
-use neo_crypto::{ToBase58, FromBase58, hex};
+use std::convert::TryFrom;
 
-pub struct Nep2 {}
+use failure::Fail;
+use openssl::symm::{Cipher, Crypter, Mode};
+use scrypt::{scrypt, Params};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
-impl Nep2 {
+use neo_core::consts::{NEP_FLAG, NEP_HEADER_1, NEP_HEADER_2, SCRYPT_DK_LEN, SCRYPT_N, SCRYPT_P, SCRYPT_R};
+use neo_core::crypto::checksum;
+use neo_core::neo_type::{PrivateKeyBin, PrivateKeyHex, PRIVATE_KEY_BIN_LEN};
+use neo_core::{KeyPair, ProtocolSettings};
+use neo_crypto::{hex, FromBase58, ToBase58};
 
-    pub fn get_nep2_from_key_pair() {}
+/// Length of a base58check decoded NEP-2 key.
+const NEP2_BIN_LEN: usize = 39;
 
-    pub fn get_nep2_from_private_key(pri_key: &PrivateKeyHex, passphrase: &str) -> Result<&str, Error> {
+/// Scrypt parameters used to derive the NEP-2 encryption key.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub n: u64,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl ScryptParams {
+    fn to_params(&self) -> Result<Params, Nep2Error> {
+        if self.n < 2 || !self.n.is_power_of_two() {
+            return Err(Nep2Error::InvalidScryptParams(*self));
+        }
 
-        let private_key: &PrivateKeyBin = pri_key.as_bytes() as &PrivateKeyBin;
+        let log_n = self.n.trailing_zeros() as u8;
+        Params::new(log_n, self.r, self.p).map_err(|_| Nep2Error::InvalidScryptParams(*self))
+    }
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        Self {
+            n: SCRYPT_N as u64,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+        }
+    }
+}
 
-        let key_pair = KeyPair::get_key_pair_from_private_key(private_key);
+pub struct Nep2 {}
 
-        let mut addresshash: [u8; 4] = key_pair.get_addr_hash_from_address();
+impl Nep2 {
+    /**
+     * Encrypts a hex private key with the default scrypt parameters.
+     */
+    pub fn get_nep2_from_private_key(pri_key: &PrivateKeyHex, passphrase: &str) -> Result<String, Nep2Error> {
+        let private_key = <PrivateKeyBin>::try_from(hex::decode(pri_key)?.as_slice())
+            .map_err(|_| Nep2Error::InvalidByteLength(pri_key.len() / 2))?;
 
-        let mut result = vec![0u8; SCRYPT_DK_LEN];
-        let params = Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P).unwrap();
+        Nep2::encrypt(&private_key, passphrase, &ScryptParams::default())
+    }
 
-        scrypt(
-            passphrase.as_bytes(),
-            addresshash.to_vec().as_slice(),
-            &params,
-            &mut result,
-        ).unwrap();
+    /**
+     * Decrypts a NEP-2 key with the default scrypt parameters into a hex private key.
+     */
+    pub fn get_private_key_from_nep2(nep2: &str, passphrase: &str) -> Result<PrivateKeyHex, Nep2Error> {
+        let private_key = Nep2::decrypt(nep2, passphrase, &ScryptParams::default())?;
 
-        let half_1 = &result[0..32];
-        let half_2 = &result[32..64];
-        let mut u8xor = [0u8; 32];
+        Ok(hex::encode(private_key))
+    }
 
-        for i in 0..32 {
-            u8xor[i] = &private_key[i] ^ half_1[i];
-        }
+    /**
+     * Encrypts a private key according to the NEP-2 standard, for the default network.
+     * @param private_key Private key to encrypt.
+     * @param passphrase Passphrase used to derive the encryption key.
+     * @param params Scrypt parameters, neo-cli and Neon use 16384/8/8.
+     */
+    pub fn encrypt(private_key: &PrivateKeyBin, passphrase: &str, params: &ScryptParams) -> Result<String, Nep2Error> {
+        Nep2::encrypt_with(private_key, passphrase, params, &ProtocolSettings::default())
+    }
 
-        let cipher = Aes::from_key(<[u8; 32]>::try_from(half_2).unwrap());
-        let encrypted = cipher.encrypt(Vec::from(u8xor));
+    /**
+     * Encrypts a private key according to the NEP-2 standard.
+     * The address hash is the one of the account address on the network of `settings`.
+     */
+    pub fn encrypt_with(
+        private_key: &PrivateKeyBin,
+        passphrase: &str,
+        params: &ScryptParams,
+        settings: &ProtocolSettings,
+    ) -> Result<String, Nep2Error> {
+        let key_pair = KeyPair::get_key_pair_from_private_key_with(private_key, settings);
+        let address_hash = Nep2::address_hash(&key_pair.address);
 
-        // # Assemble the final result
-        let mut assembled = Vec::new();
+        let derived = Nep2::derive(passphrase, &address_hash, params)?;
 
+        let mut xor = [0u8; PRIVATE_KEY_BIN_LEN];
+        for i in 0..PRIVATE_KEY_BIN_LEN {
+            xor[i] = private_key[i] ^ derived[i];
+        }
+        let encrypted = Nep2::aes(Mode::Encrypt, &derived[32..64], &xor)?;
+
+        // Assemble the final result
+        let mut assembled = Vec::with_capacity(NEP2_BIN_LEN + 4);
         assembled.push(NEP_HEADER_1);
         assembled.push(NEP_HEADER_2);
         assembled.push(NEP_FLAG);
-        assembled.extend(addresshash.to_vec());
-        assembled.extend(encrypted);
+        assembled.extend_from_slice(&address_hash);
+        assembled.extend_from_slice(&encrypted);
 
-        // # Finally, encode with Base58Check
-        OK(assembled.to_base58())
+        // Finally, encode with Base58Check
+        let sum = checksum(&assembled);
+        assembled.extend_from_slice(&sum[0..4]);
+        Ok(assembled.to_base58())
     }
 
+    /**
+     * Decrypts a NEP-2 key of the default network. Fails if the passphrase does not match the address hash.
+     * @param nep2 Base58check encoded NEP-2 key.
+     * @param passphrase Passphrase used to derive the encryption key.
+     * @param params Scrypt parameters the key was encrypted with.
+     */
+    pub fn decrypt(nep2: &str, passphrase: &str, params: &ScryptParams) -> Result<PrivateKeyBin, Nep2Error> {
+        Nep2::decrypt_with(nep2, passphrase, params, &ProtocolSettings::default())
+    }
 
-    pub fn get_private_key_from_nep2(nep2: & str, passphrase: &str) -> Result<&str, Error> {
-        if nep2.len() != 58 {
-            println!("Wrong Nep2");
-            ()
+    /**
+     * Decrypts a NEP-2 key whose address hash is the one of an address on the network of `settings`.
+     */
+    pub fn decrypt_with(
+        nep2: &str,
+        passphrase: &str,
+        params: &ScryptParams,
+        settings: &ProtocolSettings,
+    ) -> Result<PrivateKeyBin, Nep2Error> {
+        let data = nep2.from_base58().map_err(|_| Nep2Error::InvalidFormat)?;
+        if data.len() != NEP2_BIN_LEN + 4 {
+            return Err(Nep2Error::InvalidByteLength(data.len()));
         }
-        let decoded_key: [u8; 39] = nep2.from_base58().unwrap().try_into().unwrap();
-        // ADDRESS_HASH_SIZE = 4
-        // ADDRESS_HASH_OFFSET = 3
 
-        let mut address_hash: &[u8] = &decoded_key[3..7];
-        let encrypted: &[u8] = &decoded_key[7..39];
+        let (decoded, sum) = data.split_at(NEP2_BIN_LEN);
+        if checksum(decoded)[0..4] != *sum {
+            return Err(Nep2Error::InvalidChecksum);
+        }
+        if decoded[0] != NEP_HEADER_1 || decoded[1] != NEP_HEADER_2 || decoded[2] != NEP_FLAG {
+            return Err(Nep2Error::InvalidFormat);
+        }
+
+        let address_hash = &decoded[3..7];
+        let encrypted = &decoded[7..39];
+
+        let derived = Nep2::derive(passphrase, address_hash, params)?;
+        let decrypted = Nep2::aes(Mode::Decrypt, &derived[32..64], encrypted)?;
+
+        let mut private_key = [0u8; PRIVATE_KEY_BIN_LEN];
+        for i in 0..PRIVATE_KEY_BIN_LEN {
+            private_key[i] = decrypted[i] ^ derived[i];
+        }
+
+        // Now check that the address hashes match. If they don't, the password was wrong.
+        let key_pair = KeyPair::get_key_pair_from_private_key_with(&private_key, settings);
+        if Nep2::address_hash(&key_pair.address) != address_hash {
+            return Err(Nep2Error::WrongPassphrase);
+        }
+
+        Ok(private_key)
+    }
+
+    fn address_hash(address: &str) -> [u8; 4] {
+        let mut hash = [0u8; 4];
+        hash.copy_from_slice(&checksum(address.as_bytes())[0..4]);
+        hash
+    }
+
+    /// Derives the key from the UTF-8 of the NFC normalized passphrase, as NEP-2 requires.
+    fn derive(passphrase: &str, salt: &[u8], params: &ScryptParams) -> Result<Vec<u8>, Nep2Error> {
+        let passphrase: String = passphrase.nfc().collect();
+        let mut derived = vec![0u8; SCRYPT_DK_LEN];
+        scrypt(passphrase.as_bytes(), salt, &params.to_params()?, &mut derived)
+            .map_err(|_| Nep2Error::InvalidScryptParams(*params))?;
+
+        Ok(derived)
+    }
+
+    fn aes(mode: Mode, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Nep2Error> {
+        let cipher = Cipher::aes_256_ecb();
+        let mut crypter = Crypter::new(cipher, mode, key, None)?;
+        crypter.pad(false);
+
+        let mut out = vec![0u8; data.len() + cipher.block_size()];
+        let mut count = crypter.update(data, &mut out)?;
+        count += crypter.finalize(&mut out[count..])?;
+        out.truncate(count);
 
-        // pwd_normalized = bytes(unicodedata.normalize('NFC', passphrase), 'utf-8')
-        let mut result = vec![0u8; SCRYPT_DK_LEN];
-        let params = Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P).unwrap();
+        Ok(out)
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum Nep2Error {
+    #[fail(display = "{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[fail(display = "invalid byte length: {}", _0)]
+    InvalidByteLength(usize),
+
+    #[fail(display = "invalid NEP-2 checksum")]
+    InvalidChecksum,
 
-        scrypt(
-            passphrase.as_bytes(),
-            &address_hash,
-            &params,
-            &mut result,
-        ).unwrap();
+    #[fail(display = "invalid NEP-2 format")]
+    InvalidFormat,
 
+    #[fail(display = "invalid scrypt parameters: {:?}", _0)]
+    InvalidScryptParams(ScryptParams),
 
-        // derived = scrypt.hash(pwd_normalized, address_hash,
-        //                       N=SCRYPT_ITERATIONS,
-        //                       r=SCRYPT_BLOCKSIZE,
-        //                       p=SCRYPT_PARALLEL_FACTOR,
-        //                       buflen=SCRYPT_KEY_LEN_BYTES)
+    #[fail(display = "wrong passphrase")]
+    WrongPassphrase,
+}
 
-        let half_1 = &result[0..32];
-        let half_2 = &result[32..64];
+impl From<hex::FromHexError> for Nep2Error {
+    fn from(error: hex::FromHexError) -> Self {
+        Nep2Error::Crate("hex", format!("{:?}", error))
+    }
+}
+
+impl From<openssl::error::ErrorStack> for Nep2Error {
+    fn from(error: openssl::error::ErrorStack) -> Self {
+        Nep2Error::Crate("openssl", format!("{:?}", error))
+    }
+}
 
-        // derived1 = derived[:32]
-        // derived2 = derived[32:]
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let cipher = Aes::from_key(<[u8; 32]>::try_from(half_2).unwrap());
-        let decrypted = cipher.encrypt(Vec::from(encrypted));
+    const PRIVATE_KEY: &str = "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5";
+    const PASSPHRASE: &str = "TestingOneTwoThree";
 
-        let mut pri_key = [0u8; 32];
+    #[test]
+    fn test_encrypt() {
+        // NEP-2 specification test vector, for the Neo2 address AStZHy8E6StCqYQbzMqi4poH7YNDHQKxvt
+        let private_key = <PrivateKeyBin>::try_from(hex::decode(PRIVATE_KEY).unwrap().as_slice()).unwrap();
+        let params = ScryptParams::default();
+        let nep2 = Nep2::encrypt_with(&private_key, PASSPHRASE, &params, &ProtocolSettings::N2_MAINNET).unwrap();
+        assert_eq!(nep2, "6PYVPVe1fQznphjbUxXP9KZJqPMVnVwCx5s5pr5axRJ8uHkMtZg97eT5kL");
 
-        for i in 0..32 {
-            pri_key[i] = decrypted[i] ^ half_1[i];
+        // The N3 address NS5F1Mth64bgJW4LgmEMNdEk7pVeAp3jrF
+        let nep2 = Nep2::get_nep2_from_private_key(&PRIVATE_KEY.to_string(), PASSPHRASE).unwrap();
+        assert_eq!(nep2, "6PYP4G8nszhSeYCpSHPSHdTsghgKXCWLu61B8hSrqsUR2VtV21D2r536af");
+    }
+
+    #[test]
+    fn test_decrypt() {
+        let params = ScryptParams { n: 2, r: 1, p: 1 };
+        let nep2 = "6PYVPVe1fEoXVHG1wZRWEFnPUYeZ2doid9G6P2TDZYjEunU5yasrv8vQEn";
+
+        let private_key = Nep2::decrypt_with(nep2, PASSPHRASE, &params, &ProtocolSettings::N2_MAINNET).unwrap();
+        assert_eq!(hex::encode(private_key), PRIVATE_KEY);
+
+        match Nep2::decrypt_with(nep2, "wrong", &params, &ProtocolSettings::N2_MAINNET) {
+            Err(Nep2Error::WrongPassphrase) => {}
+            other => panic!("unexpected result: {:?}", other),
         }
-        // cipher = Aes.new(derived2, Aes.MODE_ECB)
-        // decrypted = cipher.decrypt(encrypted)
-        // private_key = xor_bytes(decrypted, derived1)
-
-        let KeyPair = KeyPair::KeyPair::get_KeyPair_from_private_key(pri_key.as_ref());
-        let mut kp_addresshash: [u8; 4] = KeyPair.get_addr_hash_from_address();
-
-        // # Now check that the address hashes match. If they don't, the password was wrong.
-        // kp_new = KeyPair(priv_key=private_key)
-        // kp_new_address = kp_new.get_address()
-        // kp_new_address_hash_tmp = hashlib.sha256(kp_new_address.encode("utf-8")).digest()
-        // kp_new_address_hash_tmp2 = hashlib.sha256(kp_new_address_hash_tmp).digest()
-        // kp_new_address_hash = kp_new_address_hash_tmp2[:4]
-        if kp_addresshash != address_hash {
-            println!("Wrong Passphrase");
+
+        let nep2 = "6PYP4G8nse4oRZfAk1KESj5c8EtFrEEahGo3hz5XRwW2EAQguFKv3AGWdk";
+        let private_key = Nep2::decrypt(nep2, PASSPHRASE, &params).unwrap();
+        assert_eq!(hex::encode(private_key), PRIVATE_KEY);
+    }
+
+    #[test]
+    fn test_unicode_passphrase() {
+        // The BIP-38 passphrase GREEK UPSILON WITH HOOK, COMBINING ACUTE ACCENT, NULL,
+        // DESERET CAPITAL LETTER LONG I, PILE OF POO, whose first two characters NFC composes.
+        let decomposed = "\u{3d2}\u{301}\u{0}\u{10400}\u{1f4a9}";
+        let composed = "\u{3d3}\u{0}\u{10400}\u{1f4a9}";
+        let private_key = <PrivateKeyBin>::try_from(hex::decode(PRIVATE_KEY).unwrap().as_slice()).unwrap();
+
+        let nep2 = Nep2::encrypt(&private_key, decomposed, &ScryptParams::default()).unwrap();
+        assert_eq!(nep2, "6PYP4G8nrfYZ3m9ozQjFyFigavZ51renAmibjZkR3kg2cw1WEUQVyA2MEu");
+
+        let params = ScryptParams { n: 2, r: 1, p: 1 };
+        let nep2 = "6PYP4G8nsjJ68YsYHhYiSCPg7d5Cc1worskdNPr7La8BJDQLdmf5ALAyA6";
+        for passphrase in &[decomposed, composed] {
+            let private_key = Nep2::decrypt(nep2, passphrase, &params).unwrap();
+            assert_eq!(hex::encode(private_key), PRIVATE_KEY);
         }
+    }
 
-        Ok(hex::encode(pri_key).as_str())
+    /// The vectors the enclave of SecureWallet is tested against.
+    #[test]
+    fn test_shared_vectors() {
+        use crate::test_vectors::*;
+
+        let private_key = <PrivateKeyBin>::try_from(hex::decode(PRIVATE_KEY).unwrap().as_slice()).unwrap();
+        let public_key = KeyPair::get_public_key_from_private_key(&private_key).unwrap();
+        assert_eq!(hex::encode(&public_key[..]), PUBLIC_KEY);
+        assert_eq!(KeyPair::get_wif_from_private_key(&private_key).unwrap(), WIF);
+        assert_eq!(ProtocolSettings::N2_MAINNET.address_from_public_key(&public_key), N2_ADDRESS);
+        assert_eq!(ProtocolSettings::N3_MAINNET.address_from_public_key(&public_key), N3_ADDRESS);
+
+        for (passphrase, n, r, p, address, nep2) in NEP2.iter() {
+            let params = ScryptParams { n: *n, r: *r, p: *p };
+            let settings = ProtocolSettings::from_address(address).unwrap();
+            assert_eq!(
+                Nep2::encrypt_with(&private_key, passphrase, &params, &settings).unwrap(),
+                *nep2
+            );
+            assert_eq!(
+                Nep2::decrypt_with(nep2, passphrase, &params, &settings).unwrap(),
+                private_key
+            );
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,350 @@
+use std::fs;
+use std::path::Path;
+
+use failure::Fail;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use neo_core::ProtocolSettings;
+
+use crate::account::AccountJSON;
+use crate::nep2::{Nep2, Nep2Error, ScryptParams};
+
+pub const DEFAULT_WALLET_VERSION: &str = "1.0";
+
+/**
+ * NEP-6 wallet file, as written by neo-cli and Neon.
+ *
+ * Fields are kept in the same order as the reference implementation so that
+ * loading and saving a wallet does not reorder its JSON. Fields other clients
+ * add, at any level, are kept and written after the NEP-6 ones.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Nep6Wallet {
+    pub name: Option<String>,
+    pub version: String,
+    pub scrypt: ScryptParams,
+    pub accounts: Vec<AccountJSON>,
+    pub extra: Option<Value>,
+    /// Fields NEP-6 does not define.
+    #[serde(flatten)]
+    pub unknown: Map<String, Value>,
+}
+
+impl Nep6Wallet {
+    /// Creates an empty wallet with the default scrypt parameters.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            version: DEFAULT_WALLET_VERSION.to_string(),
+            scrypt: ScryptParams::default(),
+            accounts: Vec::new(),
+            extra: None,
+            unknown: Map::new(),
+        }
+    }
+
+    /// Parses a wallet from its NEP-6 JSON.
+    pub fn from_json(json: &str) -> Result<Self, Nep6Error> {
+        let wallet: Nep6Wallet = serde_json::from_str(json)?;
+        if wallet.accounts.iter().filter(|account| account.is_default).count() > 1 {
+            return Err(Nep6Error::MultipleDefaultAccounts);
+        }
+
+        Ok(wallet)
+    }
+
+    /// Serializes the wallet into NEP-6 JSON.
+    pub fn to_json(&self) -> Result<String, Nep6Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Loads a wallet from a NEP-6 file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Nep6Error> {
+        let json = fs::read_to_string(path)?;
+        Nep6Wallet::from_json(&json)
+    }
+
+    /// Saves the wallet to a NEP-6 file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Nep6Error> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn accounts(&self) -> &[AccountJSON] {
+        &self.accounts
+    }
+
+    pub fn get_account(&self, address: &str) -> Option<&AccountJSON> {
+        self.accounts.iter().find(|account| account.address == address)
+    }
+
+    fn get_account_mut(&mut self, address: &str) -> Result<&mut AccountJSON, Nep6Error> {
+        self.accounts
+            .iter_mut()
+            .find(|account| account.address == address)
+            .ok_or_else(|| Nep6Error::AccountNotFound(address.to_string()))
+    }
+
+    /// Returns the default account, or the first account if none is marked as default.
+    pub fn default_account(&self) -> Option<&AccountJSON> {
+        self.accounts
+            .iter()
+            .find(|account| account.is_default)
+            .or_else(|| self.accounts.first())
+    }
+
+    /// Adds an account. If it is marked as default, it replaces the current default.
+    pub fn add_account(&mut self, account: AccountJSON) -> Result<(), Nep6Error> {
+        if self.get_account(&account.address).is_some() {
+            return Err(Nep6Error::DuplicateAccount(account.address));
+        }
+
+        if account.is_default {
+            self.accounts.iter_mut().for_each(|account| account.is_default = false);
+        }
+        self.accounts.push(account);
+        Ok(())
+    }
+
+    /// Removes an account, returning it if it was present.
+    pub fn remove_account(&mut self, address: &str) -> Option<AccountJSON> {
+        let index = self.accounts.iter().position(|account| account.address == address)?;
+        Some(self.accounts.remove(index))
+    }
+
+    /// Sets the label of an account.
+    pub fn rename_account(&mut self, address: &str, label: Option<String>) -> Result<(), Nep6Error> {
+        self.get_account_mut(address)?.label = label;
+        Ok(())
+    }
+
+    /// Marks an account as default, clearing the flag on every other account.
+    pub fn set_default(&mut self, address: &str) -> Result<(), Nep6Error> {
+        self.get_account_mut(address)?;
+        self.accounts
+            .iter_mut()
+            .for_each(|account| account.is_default = account.address == address);
+        Ok(())
+    }
+
+    /// Sets the lock flag of an account.
+    pub fn set_lock(&mut self, address: &str, lock: bool) -> Result<(), Nep6Error> {
+        self.get_account_mut(address)?.lock = lock;
+        Ok(())
+    }
+
+    /// Decrypts the NEP-2 key of an account.
+    pub fn decrypt_account(&self, address: &str, passphrase: &str) -> Result<[u8; 32], Nep6Error> {
+        let account = self
+            .get_account(address)
+            .ok_or_else(|| Nep6Error::AccountNotFound(address.to_string()))?;
+        let key = account.key.as_ref().ok_or_else(|| Nep6Error::WatchOnlyAccount(address.to_string()))?;
+
+        Ok(Nep2::decrypt_with(key, passphrase, &self.scrypt, &settings(address))?)
+    }
+
+    /// Checks the passphrase against the first account that holds a key.
+    pub fn verify_password(&self, passphrase: &str) -> bool {
+        match self.accounts.iter().find(|account| account.key.is_some()) {
+            Some(account) => self.decrypt_account(&account.address, passphrase).is_ok(),
+            None => true,
+        }
+    }
+
+    /**
+     * Re-encrypts every NEP-2 key with a new passphrase.
+     * The wallet is left untouched if any key fails to decrypt.
+     */
+    pub fn change_password(&mut self, old_passphrase: &str, new_passphrase: &str) -> Result<(), Nep6Error> {
+        let mut keys = Vec::with_capacity(self.accounts.len());
+        for account in &self.accounts {
+            let key = match &account.key {
+                Some(key) => {
+                    let settings = settings(&account.address);
+                    let private_key = Nep2::decrypt_with(key, old_passphrase, &self.scrypt, &settings)?;
+                    Some(Nep2::encrypt_with(&private_key, new_passphrase, &self.scrypt, &settings)?)
+                }
+                None => None,
+            };
+            keys.push(key);
+        }
+
+        for (account, key) in self.accounts.iter_mut().zip(keys) {
+            account.key = key;
+        }
+        Ok(())
+    }
+}
+
+/// NEP-2 keys are bound to the address of their account, on the network the address belongs to.
+fn settings(address: &str) -> ProtocolSettings {
+    ProtocolSettings::from_address(address).unwrap_or_default()
+}
+
+#[derive(Debug, Fail)]
+pub enum Nep6Error {
+    #[fail(display = "{}: {}", _0, _1)]
+    Crate(&'static str, String),
+
+    #[fail(display = "account not found: {}", _0)]
+    AccountNotFound(String),
+
+    #[fail(display = "duplicate account: {}", _0)]
+    DuplicateAccount(String),
+
+    #[fail(display = "more than one default account")]
+    MultipleDefaultAccounts,
+
+    #[fail(display = "{}", _0)]
+    Nep2(Nep2Error),
+
+    #[fail(display = "account has no key: {}", _0)]
+    WatchOnlyAccount(String),
+}
+
+impl From<Nep2Error> for Nep6Error {
+    fn from(error: Nep2Error) -> Self {
+        Nep6Error::Nep2(error)
+    }
+}
+
+impl From<serde_json::Error> for Nep6Error {
+    fn from(error: serde_json::Error) -> Self {
+        Nep6Error::Crate("serde_json", format!("{:?}", error))
+    }
+}
+
+impl From<std::io::Error> for Nep6Error {
+    fn from(error: std::io::Error) -> Self {
+        Nep6Error::Crate("std::io", format!("{:?}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = r#"{
+  "name": "MyWallet",
+  "version": "1.0",
+  "scrypt": {
+    "n": 2,
+    "r": 1,
+    "p": 1
+  },
+  "accounts": [
+    {
+      "address": "AStZHy8E6StCqYQbzMqi4poH7YNDHQKxvt",
+      "label": null,
+      "isDefault": true,
+      "lock": false,
+      "key": "6PYVPVe1fEoXVHG1wZRWEFnPUYeZ2doid9G6P2TDZYjEunU5yasrv8vQEn",
+      "contract": {
+        "script": "2102028a99826edc0c97d18e22b6932373d908d323aa7f92656a77ec26e8861699efac",
+        "parameters": [
+          {
+            "name": "signature",
+            "type": "Signature"
+          }
+        ],
+        "deployed": false
+      },
+      "extra": null
+    },
+    {
+      "address": "ALq7AWrhAueN6mJNqk6FHJjnsEoPRytLdW",
+      "label": "watch",
+      "isDefault": false,
+      "lock": true,
+      "key": null,
+      "contract": null,
+      "extra": {
+        "source": "Neon"
+      }
+    }
+  ],
+  "extra": null
+}"#;
+
+    #[test]
+    fn test_round_trip() {
+        let wallet = Nep6Wallet::from_json(WALLET).unwrap();
+        assert_eq!(wallet.to_json().unwrap(), WALLET);
+    }
+
+    /// An N3 wallet with fields other clients add to the wallet, an account, a contract and a parameter.
+    const THIRD_PARTY_WALLET: &str = r#"{
+  "name": "neo-cli",
+  "version": "1.0",
+  "scrypt": { "n": 16384, "r": 8, "p": 8 },
+  "accounts": [
+    {
+      "address": "NS5F1Mth64bgJW4LgmEMNdEk7pVeAp3jrF",
+      "label": "main",
+      "isDefault": true,
+      "lock": false,
+      "key": "6PYP4G8nszhSeYCpSHPSHdTsghgKXCWLu61B8hSrqsUR2VtV21D2r536af",
+      "contract": {
+        "script": "DCECYkHn4ms4u3FUuK1JRYuX+xxHl0Q9ySHFyld09RGiu/xBVuezJw==",
+        "parameters": [{ "name": "signature", "type": "Signature", "description": "owner" }],
+        "deployed": false,
+        "hash": "0x4e3da2f51c7c6db6ede3a0a9b6041da3b4a1c5f7"
+      },
+      "extra": null,
+      "publicKey": "026241e7e26b38bb7154b8ad49458b97fb1c4797443dc921c5ca5774f511a2bbfc",
+      "watchOnly": false
+    }
+  ],
+  "extra": { "network": 860833102 },
+  "tokens": [{ "symbol": "GAS", "hash": "0xd2a4cff31913016155e38e474a2c06d08be276cf" }]
+}"#;
+
+    #[test]
+    fn test_round_trip_unknown_fields() {
+        let wallet = Nep6Wallet::from_json(THIRD_PARTY_WALLET).unwrap();
+        let account = &wallet.accounts[0];
+        assert_eq!(account.label, Some("main".to_string()));
+        assert!(account.unknown.contains_key("publicKey"));
+        assert!(account.contract.as_ref().unwrap().unknown.contains_key("hash"));
+        assert!(wallet.unknown.contains_key("tokens"));
+
+        let saved: Value = serde_json::from_str(&wallet.to_json().unwrap()).unwrap();
+        let original: Value = serde_json::from_str(THIRD_PARTY_WALLET).unwrap();
+        assert_eq!(saved, original);
+    }
+
+    #[test]
+    fn test_accounts() {
+        let mut wallet = Nep6Wallet::from_json(WALLET).unwrap();
+        assert_eq!(wallet.default_account().unwrap().address, "AStZHy8E6StCqYQbzMqi4poH7YNDHQKxvt");
+
+        wallet.set_default("ALq7AWrhAueN6mJNqk6FHJjnsEoPRytLdW").unwrap();
+        wallet.rename_account("ALq7AWrhAueN6mJNqk6FHJjnsEoPRytLdW", Some("cold".to_string())).unwrap();
+        let account = wallet.default_account().unwrap();
+        assert_eq!(account.address, "ALq7AWrhAueN6mJNqk6FHJjnsEoPRytLdW");
+        assert_eq!(account.label, Some("cold".to_string()));
+
+        let removed = wallet.remove_account("ALq7AWrhAueN6mJNqk6FHJjnsEoPRytLdW").unwrap();
+        assert!(wallet.add_account(removed.clone()).is_ok());
+        assert!(wallet.add_account(removed).is_err());
+        assert!(wallet.set_default("AUnknown").is_err());
+    }
+
+    #[test]
+    fn test_change_password() {
+        let mut wallet = Nep6Wallet::from_json(WALLET).unwrap();
+        let address = "AStZHy8E6StCqYQbzMqi4poH7YNDHQKxvt";
+
+        assert!(wallet.change_password("wrong", "NewPassword").is_err());
+        assert!(wallet.verify_password("TestingOneTwoThree"));
+
+        wallet.change_password("TestingOneTwoThree", "NewPassword").unwrap();
+        assert_eq!(
+            wallet.get_account(address).unwrap().key,
+            Some("6PYVPVe1fkqm4tH8FR9WBLCpCAuTfFDERPqeYUNdpk4BuWKgY3qCKXdiAr".to_string())
+        );
+        assert!(!wallet.verify_password("TestingOneTwoThree"));
+        assert!(wallet.decrypt_account(address, "NewPassword").is_ok());
+    }
+}
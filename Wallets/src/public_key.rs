@@ -1,84 +1,71 @@
-use neo_core::neo_type::PUBLIC_KEY_BIN_LEN;
-use neo_core::utilities::*;
-use crate::private_key::PrivateKey;
-use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
-use crate::address::{AddressError, Address};
-use neo_crypto::{hex, base58};
-use std::str::FromStr;
-use std::fmt::Display;
 use std::fmt;
-use serde::{Serialize,Deserialize};
+use std::fmt::Display;
+use std::str::FromStr;
+
 use failure::Fail;
-use crate::key_trait::KeyTrait;
-use std::error::Error;
 
-/// Represents an  public key
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Serialize, Deserialize)]
+use neo_core::neo_type::PUBLIC_KEY_BIN_LEN;
+use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
+use neo_crypto::{base58, hex};
+
+use crate::address::{Address, AddressError};
+use crate::private_key::PrivateKey;
+
+/// Represents an  public key, in its compressed form.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub struct PublicKey(pub [u8; PUBLIC_KEY_BIN_LEN]);
 
 impl PublicKey {
-    /// Returns the address corresponding to the given public key.
+    /// Returns the public key corresponding to the given private key.
     pub fn from_private_key(private_key: &PrivateKey) -> Self {
         let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
-        let mut pub_key = ecdsa.derive_public_key(&private_key.0).unwrap();
+        let pub_key = ecdsa.derive_public_key(&private_key.0).unwrap();
 
-        Self(pub_key.as_slice())
+        let mut key = [0u8; PUBLIC_KEY_BIN_LEN];
+        key.copy_from_slice(&pub_key);
+        Self(key)
     }
 
     /// Returns the address of the corresponding private key.
-    pub fn to_address(&self) -> Result<Self::Address, AddressError> {
+    pub fn to_address(&self) -> Result<Address, AddressError> {
         Address::from_public_key(self)
     }
 
     /// Returns the hex string of the public key
     pub fn to_hex_string(&self) -> String {
-        hex::encode(&self.0)
-    }
-}
-
-impl KeyTrait for PublicKey{
-    fn deserialize(&self, hex: &str) -> Result<_, dyn Error> {
-        unimplemented!()
-    }
-
-    fn serialize(&self) -> Result<String, dyn Error> {
-        unimplemented!()
-    }
-
-    fn to_hex(&self) -> String {
-        unimplemented!()
-    }
-
-    fn to_slice(&self) -> &[u8] {
-        unimplemented!()
-    }
-
-    fn equals(&self, other: &PublicKey) -> bool {
-        unimplemented!()
+        hex::encode(self.0)
     }
 }
 
 impl FromStr for PublicKey {
-
     type Err = PublicKeyError;
+
+    /// Parses a compressed public key given as 66 hex characters.
     fn from_str(public_key: &str) -> Result<Self, Self::Err> {
-        Ok(Self(
-            hex::decode(public_key).unwrap().as_slice()
-        )
-        )
+        if public_key.len() != PUBLIC_KEY_BIN_LEN * 2 {
+            return Err(PublicKeyError::InvalidCharacterLength(public_key.len()));
+        }
+
+        let data = hex::decode(public_key)?;
+        if data.len() != PUBLIC_KEY_BIN_LEN {
+            return Err(PublicKeyError::InvalidByteLength(data.len()));
+        }
+        if data[0] != 0x02 && data[0] != 0x03 {
+            return Err(PublicKeyError::InvalidPrefix(public_key[0..2].to_string()));
+        }
+
+        let mut key = [0u8; PUBLIC_KEY_BIN_LEN];
+        key.copy_from_slice(&data);
+        Ok(Self(key))
     }
 }
 
 impl Display for PublicKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for s in &self.0.serialize()[1..] {
-            write!(f, "{:02x}", s)?;
-        }
-        Ok(())
+        write!(f, "{}", self.to_hex_string())
     }
 }
 
-
 #[derive(Debug, Fail)]
 pub enum PublicKeyError {
     #[fail(display = "{}: {}", _0, _1)]
@@ -100,19 +87,12 @@ pub enum PublicKeyError {
     NoViewingKey,
 }
 
-impl From<neo_core::no_std::io::Error> for PublicKeyError {
-    fn from(error: neo_core::no_std::io::Error) -> Self {
-        PublicKeyError::Crate("crate::no_std::io", format!("{:?}", error))
-    }
-}
-
 impl From<base58::FromBase58Error> for PublicKeyError {
     fn from(error: base58::FromBase58Error) -> Self {
         PublicKeyError::Crate("base58", format!("{:?}", error))
     }
 }
 
-
 impl From<hex::FromHexError> for PublicKeyError {
     fn from(error: hex::FromHexError) -> Self {
         PublicKeyError::Crate("hex", format!("{:?}", error))
@@ -122,96 +102,30 @@ impl From<hex::FromHexError> for PublicKeyError {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::test_vectors::*;
 
-    fn test_from_private_key(expected_public_key: &PublicKey, private_key: &PrivateKey) {
-        let public_key = PublicKey::from_private_key(private_key);
-        assert_eq!(*expected_public_key, public_key);
-    }
-
-    fn test_to_address(expected_address: &Address, public_key: &PublicKey) {
-        let address = public_key.to_address().unwrap();
-        assert_eq!(*expected_address, address);
-    }
+    use super::*;
 
-    fn test_from_str(expected_public_key: &str, expected_address: &str) {
-        let public_key = PublicKey::from_str(expected_public_key).unwrap();
-        let address = public_key.to_address().unwrap();
-        assert_eq!(expected_public_key, public_key.to_string());
-        assert_eq!(expected_address, address.to_string());
+    #[test]
+    fn test_from_private_key() {
+        let private_key = PrivateKey::from_str(PRIVATE_KEY).unwrap();
+        assert_eq!(PublicKey::from_private_key(&private_key), PublicKey::from_str(PUBLIC_KEY).unwrap());
     }
 
-    fn test_to_str(expected_public_key: &str, public_key: &PublicKey) {
-        assert_eq!(expected_public_key, public_key.to_string());
+    #[test]
+    fn test_to_address() {
+        let public_key = PublicKey::from_str(PUBLIC_KEY).unwrap();
+        assert_eq!(public_key.to_address().unwrap().to_string(), N3_ADDRESS);
     }
 
-    mod checksum_address {
-        use super::*;
-
-        const KEYPAIRS: [(&str, &str, &str); 5] = [
-            (
-                "2f46188bd601ece2a4446fa31de9419ee9baabf5305d65a5a7aea8badee27a5a",
-                "06d68e391c6961fceb5d8c5ad8ee5c6346db24df9dae61c9c0b0142409760451d982c0f35931f33e57adfc4f11bdf1946be2d75d6ecc925e8d22f319c71a721c",
-                "0x9Ed0C5817aE96Cb886BF74EB02B238De682e9B07"
-            ),
-            (
-                "d96c4c30bbabde58653e4fb4f4d97d064c70e300a37ab8780a8ecc15220423fb",
-                "bfe0746c85802c3ca1c2d5e4f4d23fb8321b8b1009af67855cc9a4aed8285567d7045bb700e27d5e33572ae5d84a8d1e11bb134f6f14f37ffcb2fa73f7c6b0ac",
-                "0xBc90633A78dA594ace8e25AAA3517F924C76099d"
-            ),
-            (
-                "c677a1215eebd35d20337d8896ee6579c78f41f93946b17c8d4ccb772c25cde4",
-                "ff3e50efb509efd0d18ff9074bc8b253419d2437e0c1e81661c1ba419f877162eed685d80bdd3b33adde4ff2a0946dd97460f126992064059a129e2a7172d566",
-                "0xA99E404A60ab8561F7c844529F735A88D7A61C5A"
-            ),
-            (
-                "b681e5bd4ddffefe1a691fe7c6375775c11992b9a25e4f9e3f235eb054d49343",
-                "d9ed72afa68a9732df005df2dbbfb2abcad050579bd8dfeb32389d0f1e492d130ca33f9e71345d558da5859026fee86c03be685f95a4c8ddc55e048c5ff8b398",
-                "0x28826C9f713c96ee63e59Ed9220c77b021FAfC3e"
-            ),
-            (
-                "da5d359af6827e76e0a1b71c75c375f0d33f63bae4fd551d81ee10faa34e33e9",
-                "0b752d5e89126b62a99edfe40a4cbd9122cfb04257a28d225858d38bc92a0e1517e797e9029e810b329afa32a1d46268e84eb10c700314b0059f506130d1e9e6",
-                "0x9eC59170674DbEfeF40efE2ED03175b39fCA921a"
-            )
-        ];
-
-        #[test]
-        fn from_private_key() {
-            KEYPAIRS.iter().for_each(|(private_key, public_key, _)| {
-                let public_key = PublicKey::from_str(public_key).unwrap();
-                let private_key = PrivateKey::from_str(&private_key).unwrap();
-                test_from_private_key(&public_key, &private_key);
-            });
-        }
-
-        #[test]
-        fn to_address() {
-            KEYPAIRS.iter().for_each(|(_, public_key, address)| {
-                let address = Address::from_str(address).unwrap();
-                let public_key = PublicKey::from_str(&public_key).unwrap();
-                test_to_address(&address, &public_key);
-            });
-        }
-
-        #[test]
-        fn from_str() {
-            KEYPAIRS.iter().for_each(|(_, expected_public_key, expected_address)| {
-                test_from_str(expected_public_key, expected_address);
-            });
-        }
-
-        #[test]
-        fn to_str() {
-            KEYPAIRS.iter().for_each(|(_, expected_public_key, _)| {
-                let public_key = PublicKey::from_str(expected_public_key).unwrap();
-                test_to_str(expected_public_key, &public_key);
-            });
-        }
+    #[test]
+    fn test_to_str() {
+        let public_key = PublicKey::from_str(PUBLIC_KEY).unwrap();
+        assert_eq!(public_key.to_string(), PUBLIC_KEY);
     }
 
     #[test]
-    fn test_checksum_address_invalid() {
+    fn test_public_key_invalid() {
         // Invalid public key length
 
         let public_key = "0";
@@ -226,7 +140,12 @@ mod tests {
         let public_key = "06d68e391c6961fceb5d8c5ad8ee5c6346db24df9dae61c9c0b0142409760451d982c0f35931f33e57adfc4f11bdf1946be2d75d6ecc925e8d22f319c71a721c06d68e391c6961fceb5d8c5ad8ee5c6346db24df9dae61c9c0b0142409760451d982c0f3593";
         assert!(PublicKey::from_str(public_key).is_err());
 
-        let public_key = "06d68e391c6961fceb5d8c5ad8ee5c6346db24df9dae61c9c0b0142409760451d982c0f35931f33e57adfc4f11bdf1946be2d75d6ecc925e8d22f319c71a721c06d68e391c6961fceb5d8c5ad8ee5c6346db24df9dae61c9c0b0142409760451d982c0f35931f33e57adfc4f11bdf1946be2d75d6ecc925e8d22f319c71a721c";
+        // Invalid prefix and hex
+
+        let public_key = "066241e7e26b38bb7154b8ad49458b97fb1c4797443dc921c5ca5774f511a2bbfc";
+        assert!(PublicKey::from_str(public_key).is_err());
+
+        let public_key = "02zz41e7e26b38bb7154b8ad49458b97fb1c4797443dc921c5ca5774f511a2bbfc";
         assert!(PublicKey::from_str(public_key).is_err());
     }
 }
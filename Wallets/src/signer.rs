@@ -0,0 +1,323 @@
+use std::fmt;
+
+use neo_core::neo_type::{PrivateKeyBin, PublicKeyBin, ScriptHashBin, PUBLIC_KEY_BIN_LEN};
+use neo_core::protocol_settings::to_script_hash;
+use neo_core::ProtocolSettings;
+use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
+
+use crate::nep6::Nep6Wallet;
+use crate::private_key::PrivateKey;
+use crate::signing::{sign_message, SigningError, SIGNATURE_LEN};
+
+/// A `r || s` secp256r1 signature.
+pub type Signature = [u8; SIGNATURE_LEN];
+
+/**
+ * Something that signs for an account without handing out its private key.
+ *
+ * The key may be in memory, in an unlocked NEP-6 account or sealed in the
+ * SGX enclave; callers only see the public key and the signatures, so
+ * transaction building takes a `&dyn Signer` rather than key material.
+ */
+pub trait Signer {
+    fn public_key(&self) -> PublicKeyBin;
+
+    /// Settings of the network the account belongs to, they decide its verification script.
+    fn settings(&self) -> ProtocolSettings {
+        ProtocolSettings::default()
+    }
+
+    /// The verification script of the standard account of the key.
+    fn verification_script(&self) -> Vec<u8> {
+        self.settings().verification_script(&self.public_key())
+    }
+
+    fn script_hash(&self) -> ScriptHashBin {
+        to_script_hash(&self.verification_script())
+    }
+
+    fn address(&self) -> String {
+        self.settings().address_from_script_hash(&self.script_hash())
+    }
+
+    /// Signs the SHA-256 of `message`.
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError>;
+
+    /**
+     * Signs a transaction, given by its hash, for the network with magic `network`.
+     * The signed data is the little-endian magic followed by the hash.
+     */
+    fn sign_transaction(&self, hash: &[u8; 32], network: u32) -> Result<Signature, SigningError> {
+        let mut data = Vec::with_capacity(4 + hash.len());
+        data.extend_from_slice(&network.to_le_bytes());
+        data.extend_from_slice(hash);
+        self.sign(&data)
+    }
+}
+
+/// A signer holding its private key in memory, the key is wiped when it is dropped.
+pub struct KeySigner {
+    private_key: PrivateKey,
+    public_key: PublicKeyBin,
+    settings: ProtocolSettings,
+}
+
+impl KeySigner {
+    /// A signer for the account of `private_key` on the default network.
+    pub fn new(private_key: &PrivateKeyBin) -> Result<Self, SigningError> {
+        KeySigner::with_settings(private_key, &ProtocolSettings::default())
+    }
+
+    pub fn with_settings(private_key: &PrivateKeyBin, settings: &ProtocolSettings) -> Result<Self, SigningError> {
+        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI)?;
+        let mut public_key = [0u8; PUBLIC_KEY_BIN_LEN];
+        public_key.copy_from_slice(&ecdsa.derive_public_key(private_key)?);
+
+        Ok(Self {
+            private_key: PrivateKey(*private_key),
+            public_key,
+            settings: *settings,
+        })
+    }
+}
+
+impl Signer for KeySigner {
+    fn public_key(&self) -> PublicKeyBin {
+        self.public_key
+    }
+
+    fn settings(&self) -> ProtocolSettings {
+        self.settings
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        to_signature(&sign_message(message, &self.private_key)?)
+    }
+}
+
+impl Drop for KeySigner {
+    fn drop(&mut self) {
+        for byte in self.private_key.0.iter_mut() {
+            // Volatile so that the wipe of a value about to be freed is not optimized away.
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+/// The private key is a secret, it is left out of debug output.
+impl fmt::Debug for KeySigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeySigner").field("address", &self.address()).finish()
+    }
+}
+
+/// A signer for a NEP-6 account, whose key is decrypted once when the account is unlocked.
+#[derive(Debug)]
+pub struct Nep6Signer {
+    address: String,
+    signer: KeySigner,
+}
+
+impl Nep6Signer {
+    /// Decrypts the key of the account `address` of `wallet` with `passphrase`.
+    pub fn unlock(wallet: &Nep6Wallet, address: &str, passphrase: &str) -> Result<Self, SigningError> {
+        let private_key = wallet.decrypt_account(address, passphrase)?;
+        let settings = ProtocolSettings::from_address(address).unwrap_or_default();
+
+        Ok(Self {
+            address: address.to_string(),
+            signer: KeySigner::with_settings(&private_key, &settings)?,
+        })
+    }
+}
+
+impl Signer for Nep6Signer {
+    fn public_key(&self) -> PublicKeyBin {
+        self.signer.public_key()
+    }
+
+    fn settings(&self) -> ProtocolSettings {
+        self.signer.settings()
+    }
+
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        self.signer.sign(message)
+    }
+}
+
+/**
+ * The operations the SGX enclave exposes to the untrusted side: the key is
+ * loaded and used inside the enclave and only the public key and
+ * signatures cross the boundary.
+ */
+pub trait Enclave {
+    fn get_public_key(&self) -> Result<PublicKeyBin, SigningError>;
+
+    /// Signs the SHA-256 of `message` with the key loaded in the enclave.
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError>;
+}
+
+/// A signer whose key never leaves the enclave.
+pub struct EnclaveSigner<E: Enclave> {
+    enclave: E,
+    public_key: PublicKeyBin,
+    settings: ProtocolSettings,
+}
+
+impl<E: Enclave> EnclaveSigner<E> {
+    /// A signer for the key currently loaded in `enclave`, on the network of `settings`.
+    pub fn new(enclave: E, settings: &ProtocolSettings) -> Result<Self, SigningError> {
+        let public_key = enclave.get_public_key()?;
+
+        Ok(Self {
+            enclave,
+            public_key,
+            settings: *settings,
+        })
+    }
+
+    pub fn enclave(&self) -> &E {
+        &self.enclave
+    }
+}
+
+impl<E: Enclave> Signer for EnclaveSigner<E> {
+    fn public_key(&self) -> PublicKeyBin {
+        self.public_key
+    }
+
+    fn settings(&self) -> ProtocolSettings {
+        self.settings
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        self.enclave.sign(message)
+    }
+}
+
+fn to_signature(data: &[u8]) -> Result<Signature, SigningError> {
+    if data.len() != SIGNATURE_LEN {
+        return Err(SigningError::InvalidSignatureLength(data.len()));
+    }
+
+    let mut signature = [0u8; SIGNATURE_LEN];
+    signature.copy_from_slice(data);
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use neo_crypto::hex;
+
+    use crate::account::AccountJSON;
+    use crate::nep2::{Nep2, ScryptParams};
+    use crate::signing::verify_message;
+
+    use super::*;
+
+    const PRIVATE_KEY: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+    const PUBLIC_KEY: &str = "0360fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6";
+
+    fn private_key() -> PrivateKeyBin {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&hex::decode(PRIVATE_KEY).unwrap());
+        key
+    }
+
+    #[test]
+    fn test_key_signer() {
+        let signer = KeySigner::new(&private_key()).unwrap();
+        assert_eq!(hex::encode(&signer.public_key()[..]), PUBLIC_KEY);
+        assert_eq!(
+            signer.address(),
+            ProtocolSettings::default().address_from_public_key(&signer.public_key())
+        );
+
+        // ASCII "sample", the same signature as `signing::sign`
+        let signature = signer.sign(b"sample").unwrap();
+        assert_eq!(
+            hex::encode(&signature[..]),
+            "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf37160834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9"
+        );
+
+        let hash = [7u8; 32];
+        let signature = signer.sign_transaction(&hash, 860833102).unwrap();
+        let mut data = 860833102u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&hash);
+        assert!(verify_message(&data, &signature, &signer.public_key()));
+        assert!(!format!("{:?}", signer).contains(PRIVATE_KEY));
+    }
+
+    #[test]
+    fn test_nep6_signer() {
+        let mut wallet = Nep6Wallet::new("test");
+        wallet.scrypt = ScryptParams { n: 2, r: 1, p: 1 };
+        let settings = ProtocolSettings::N2_MAINNET;
+        let key = Nep2::encrypt_with(&private_key(), "neo", &wallet.scrypt, &settings).unwrap();
+        let address = settings.address_from_public_key(&hex::decode(PUBLIC_KEY).unwrap());
+        wallet
+            .add_account(AccountJSON {
+                address: address.clone(),
+                label: None,
+                is_default: true,
+                lock: false,
+                key: Some(key),
+                contract: None,
+                extra: None,
+                unknown: Default::default(),
+            })
+            .unwrap();
+
+        assert!(Nep6Signer::unlock(&wallet, &address, "wrong").is_err());
+        let signer = Nep6Signer::unlock(&wallet, &address, "neo").unwrap();
+        assert_eq!(signer.address(), address);
+        assert_eq!(
+            signer.verification_script(),
+            settings.verification_script(&signer.public_key())
+        );
+        assert!(verify_message(
+            b"neo",
+            &signer.sign(b"neo").unwrap(),
+            &signer.public_key()
+        ));
+    }
+
+    /// An enclave standing in for the SGX one, recording what crosses the boundary.
+    struct MockEnclave {
+        signer: KeySigner,
+        messages: RefCell<Vec<Vec<u8>>>,
+    }
+
+    impl Enclave for MockEnclave {
+        fn get_public_key(&self) -> Result<PublicKeyBin, SigningError> {
+            Ok(self.signer.public_key())
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+            self.messages.borrow_mut().push(message.to_vec());
+            self.signer.sign(message)
+        }
+    }
+
+    #[test]
+    fn test_enclave_signer() {
+        let enclave = MockEnclave {
+            signer: KeySigner::new(&private_key()).unwrap(),
+            messages: RefCell::new(Vec::new()),
+        };
+        let enclave_signer = EnclaveSigner::new(enclave, &ProtocolSettings::default()).unwrap();
+        let signer: &dyn Signer = &enclave_signer;
+        assert_eq!(hex::encode(&signer.public_key()[..]), PUBLIC_KEY);
+
+        let signature = signer.sign_transaction(&[1u8; 32], 1).unwrap();
+        let data = [&[1, 0, 0, 0][..], &[1u8; 32][..]].concat();
+        assert!(verify_message(&data, &signature, &signer.public_key()));
+        assert_eq!(*enclave_signer.enclave().messages.borrow(), vec![data]);
+    }
+}
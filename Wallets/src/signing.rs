@@ -1,65 +1,153 @@
-use neo_core::neo_type::{SignatureHex, PublicKeyHex};
-use openssl::bn::BigNum;
+use std::str::FromStr;
+
+use failure::Fail;
+
+use neo_core::neo_type::{PublicKeyHex, SignatureHex};
+use neo_crypto::ecdsa::{self, CipherSuite, ECECDSA};
 use neo_crypto::hex;
-use neo_crypto::ecdsa::{ECECDSA, CipherSuite, Ecdsa};
-use std::error::Error;
 
-#[derive(Debug)]
-pub struct signing {}
+use crate::nep6::Nep6Error;
+use crate::private_key::{PrivateKey, PrivateKeyError};
+
+/// Length of a Neo `r || s` secp256r1 signature in bytes.
+pub const SIGNATURE_LEN: usize = 64;
+
+/**
+ * Generates a deterministic (RFC 6979) secp256r1 signature of a message.
+ * The message is hashed with SHA-256 before signing and the result is the
+ * 64 byte `r || s` form, with `s` normalized to low-S.
+ * @param message Raw message bytes, eg. the signed data of a transaction.
+ * @param private_key Private key to sign with.
+ */
+pub fn sign_message(message: &[u8], private_key: &PrivateKey) -> Result<Vec<u8>, SigningError> {
+    let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI)?;
+
+    Ok(ecdsa.sign(&private_key.0, message)?)
+}
+
+/**
+ * Verifies a 64 byte `r || s` signature of a message.
+ * @param message Raw message bytes that were signed.
+ * @param signature ECDSA signature.
+ * @param public_key encoded/unencoded public key of the signing key.
+ */
+pub fn verify_message(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    if signature.len() != SIGNATURE_LEN {
+        return false;
+    }
+
+    match ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI) {
+        Ok(mut ecdsa) => ecdsa.verify_signature(public_key, signature, message).unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/**
+ * Generates a ECDSA signature from a hex string using the given private key.
+ * @param hex Hex string to hash and sign.
+ * @param private_key Hex string or WIF format.
+ */
+pub fn sign(hex: &str, private_key: &str) -> Result<SignatureHex, SigningError> {
+    let private_key = PrivateKey::from_str(private_key)?;
+    let msg = hex::decode(hex)?;
+
+    let signature = sign_message(&msg, &private_key)?;
+    Ok(hex::encode(signature))
+}
+
+/**
+ * Verifies that the message, signature and signing key matches.
+ * @param hex Message that was signed.
+ * @param sig ECDSA signature.
+ * @param public_key encoded/unencoded public key of the signing key.
+ */
+pub fn verify(hex: &str, sig: &SignatureHex, public_key: &PublicKeyHex) -> bool {
+    let (msg, sig, public_key) = match (hex::decode(hex), hex::decode(sig), hex::decode(public_key)) {
+        (Ok(msg), Ok(sig), Ok(public_key)) => (msg, sig, public_key),
+        _ => return false,
+    };
+
+    verify_message(&msg, &sig, &public_key)
+}
+
+#[derive(Debug, Fail)]
+pub enum SigningError {
+    #[fail(display = "{}: {}", _0, _1)]
+    Crate(&'static str, String),
 
-impl signing {
-    /**
-     * Converts signatureHex to a signature object with r & s.
-     */
-    fn get_signature_from_hex(&self, signatureHex: SignatureHex) -> (BigNum, BigNum) {
-        let signatureBuffer = hex::decode(signatureHex)?;
+    #[fail(display = "{}", _0)]
+    PrivateKey(PrivateKeyError),
 
-        let r = BigNum::from_slice(&signatureBuffer[0..32])?;
-        // new BN(signatureBuffer.slice(0, 32).to & str("hex"), 16, "be");
-        let s = BigNum::from_slice(&signatureBuffer[32..signatureBuffer.len()])?;
+    #[fail(display = "{}", _0)]
+    Nep6(Nep6Error),
 
-        (r, s)
+    #[fail(display = "invalid signature length: {}", _0)]
+    InvalidSignatureLength(usize),
+
+    #[fail(display = "no signer for account {}", _0)]
+    MissingSigner(String),
+}
+
+impl From<PrivateKeyError> for SigningError {
+    fn from(error: PrivateKeyError) -> Self {
+        SigningError::PrivateKey(error)
+    }
+}
+
+impl From<Nep6Error> for SigningError {
+    fn from(error: Nep6Error) -> Self {
+        SigningError::Nep6(error)
+    }
+}
+
+impl From<ecdsa::Error> for SigningError {
+    fn from(error: ecdsa::Error) -> Self {
+        SigningError::Crate("ecdsa", format!("{:?}", error))
+    }
+}
+
+impl From<hex::FromHexError> for SigningError {
+    fn from(error: hex::FromHexError) -> Self {
+        SigningError::Crate("hex", format!("{:?}", error))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /**
-     * Generates a ECDSA signature from a hex&str using the given private key.
-     * @param hex Hex&str to hash.
-     * @param privateKey Hex&str or WIF format.
-     */
-    pub fn sign(&self, hex: &str, private_key: &str) -> Result<SignatureHex, dyn Error> {
-        let mut pri_key = Vec::new();
-        match isWIF(private_key) {
-            true => pri_key = KeyPair::KeyPair::get_private_key_from_wif(private_key)?.to_vec(),
-            false => pri_key = hex::decode(private_key)?,
-        }
-
-        let msg = hex::decode(hex)?;
-        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
-        Ok(hex::encode(ecdsa.prove(&pri_key, &msg).unwrap()).as_str().parse().unwrap())
+    const PRIVATE_KEY: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+    const PUBLIC_KEY: &str = "0360fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6";
+
+    #[test]
+    fn test_sign() {
+        // ASCII "sample", RFC 6979 A.2.5 with low-S normalization
+        let signature = sign("73616d706c65", PRIVATE_KEY).unwrap();
+        assert_eq!(
+            signature,
+            "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf37160834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9"
+        );
     }
 
-    /**
-     * Verifies that the message, signature and signing key matches.
-     * @param hex Message that was signed.
-     * @param sig ECDSA signature.
-     * @param publicKey encoded/unencoded public key of the signing key.
-     */
-    pub fn verify(&self, hex: &str, sig: SignatureHex, publicKey: PublicKeyHex) -> bool {
-        let pubkey = hex::decode(publicKey).unwrap();
-        let msg = hex::decode(&hex)?;
-        let pi = hex::decode(&sig)?;
-        let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
-
-        let beta = ecdsa.verify(&pubkey, &pi, &msg);
-
-        match beta {
-            Ok(beta) => {
-                println!("VRF proof is valid!\nHash output: {}", hex::encode(&beta));
-                true
-            }
-            Err(e) => {
-                false
-            }
-        }
+    #[test]
+    fn test_sign_is_deterministic() {
+        let private_key = "1d9d6b11b9570e50a8511de539be9d125dda022b7d65452acc03de3aa3e87d6c";
+        let first = sign("48656c6c6f20576f726c64", private_key).unwrap();
+        let second = sign("48656c6c6f20576f726c64", private_key).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            "eb8214acd776268172d792423e3af2f5fd8529bbb67bcedd8fb9a3be3ff390e50ffb1d155d4f7098aaab6a3ea4faf6c177d4bbbcc0259a3388baf892489e355d"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let signature = sign("74657374", PRIVATE_KEY).unwrap();
+
+        assert!(verify("74657374", &signature, &PUBLIC_KEY.to_string()));
+        assert!(!verify("73616d706c65", &signature, &PUBLIC_KEY.to_string()));
+        assert!(!verify("74657374", &signature[0..126].to_string(), &PUBLIC_KEY.to_string()));
     }
-}
\ No newline at end of file
+}
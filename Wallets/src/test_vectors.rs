@@ -0,0 +1,65 @@
+//! One key in the formats a wallet loads keys from, with its addresses.
+//!
+//! The SGX wallet enclave decodes WIF and NEP-2 keys and derives addresses with
+//! its own code, its tests include this file so both give the same results.
+
+/// Key of the NEP-2 specification test vector.
+pub const PRIVATE_KEY: &str = "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5";
+pub const PUBLIC_KEY: &str = "026241e7e26b38bb7154b8ad49458b97fb1c4797443dc921c5ca5774f511a2bbfc";
+/// `PRIVATE_KEY` as a compressed-key WIF.
+pub const WIF: &str = "L44B5gGEpqEDRS9vVPz7QT35jcBG2r3CZwSwQ4fCewXAhAhqGVpP";
+pub const N2_ADDRESS: &str = "AStZHy8E6StCqYQbzMqi4poH7YNDHQKxvt";
+pub const N3_ADDRESS: &str = "NS5F1Mth64bgJW4LgmEMNdEk7pVeAp3jrF";
+
+/// `PRIVATE_KEY` encrypted for the account at an address, as (passphrase, scrypt n, r, p, address, NEP-2 key).
+/// The last two passphrases are the NFD and NFC forms of the same BIP-38 passphrase.
+pub const NEP2: [(&str, u64, u32, u32, &str, &str); 6] = [
+    (
+        "TestingOneTwoThree",
+        16384,
+        8,
+        8,
+        N2_ADDRESS,
+        "6PYVPVe1fQznphjbUxXP9KZJqPMVnVwCx5s5pr5axRJ8uHkMtZg97eT5kL",
+    ),
+    (
+        "TestingOneTwoThree",
+        16384,
+        8,
+        8,
+        N3_ADDRESS,
+        "6PYP4G8nszhSeYCpSHPSHdTsghgKXCWLu61B8hSrqsUR2VtV21D2r536af",
+    ),
+    (
+        "TestingOneTwoThree",
+        2,
+        1,
+        1,
+        N2_ADDRESS,
+        "6PYVPVe1fEoXVHG1wZRWEFnPUYeZ2doid9G6P2TDZYjEunU5yasrv8vQEn",
+    ),
+    (
+        "TestingOneTwoThree",
+        2,
+        1,
+        1,
+        N3_ADDRESS,
+        "6PYP4G8nse4oRZfAk1KESj5c8EtFrEEahGo3hz5XRwW2EAQguFKv3AGWdk",
+    ),
+    (
+        "\u{3d2}\u{301}\u{0}\u{10400}\u{1f4a9}",
+        2,
+        1,
+        1,
+        N3_ADDRESS,
+        "6PYP4G8nsjJ68YsYHhYiSCPg7d5Cc1worskdNPr7La8BJDQLdmf5ALAyA6",
+    ),
+    (
+        "\u{3d3}\u{0}\u{10400}\u{1f4a9}",
+        2,
+        1,
+        1,
+        N3_ADDRESS,
+        "6PYP4G8nsjJ68YsYHhYiSCPg7d5Cc1worskdNPr7La8BJDQLdmf5ALAyA6",
+    ),
+];
@@ -1,10 +1,15 @@
-use std::error::Error;
-use std::str::FromStr;
+use std::fmt;
 
+use failure::Fail;
+
+use neo_core::crypto::checksum;
+use neo_core::neo_type::PRIVATE_KEY_BIN_LEN;
 use neo_crypto::{FromBase58, ToBase58};
 
 use crate::private_key::PrivateKey;
-use crate::key_trait::KeyTrait;
+
+/// Version byte of the wallet import format.
+pub const WIF_VERSION: u8 = 0x80;
 
 // WIF represents a wallet import format.
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
@@ -17,21 +22,33 @@ pub struct WIF {
 
     // A reference to the private_key which this WIF is created from.
     private_key: PrivateKey,
-
-    // The string representation of the WIF.
-    s: String,
 }
 
 impl WIF {
-    const WIFVersion: u8 = 0x80;
+    /// The compressed-key WIF of `private_key`, the form Neo wallets use.
+    pub fn new(private_key: PrivateKey) -> Self {
+        WIF { version: WIF_VERSION, compressed: true, private_key }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    pub fn private_key(&self) -> &PrivateKey {
+        &self.private_key
+    }
 
     // wif encode encodes the given private key into a WIF string.
-    pub fn wif_encode(key: &[u8], mut version: u8, compressed: bool) -> Result<String, dyn Error> {
+    pub fn wif_encode(key: &[u8], mut version: u8, compressed: bool) -> Result<String, WifKeyError> {
         if version == 0x00 {
-            version = WIFVersion
+            version = WIF_VERSION
         }
 
-        if key.len() != 32 {
+        if key.len() != PRIVATE_KEY_BIN_LEN {
             return Err(WifKeyError::InvalidByteLength(key.len()));
         }
 
@@ -43,67 +60,55 @@ impl WIF {
             buf.push(0x01);
         }
 
+        let sum = checksum(&buf);
+        buf.extend_from_slice(&sum[0..4]);
         Ok(buf.to_base58())
     }
 
     // wif decode decodes the given WIF string into a WIF struct.
-    pub fn wif_decode(wif: &str, mut version: u8) -> Result<Self, dyn Error> {
-        let mut b = wif.from_base58().unwrap();
+    pub fn wif_decode(wif: &str, mut version: u8) -> Result<Self, WifKeyError> {
+        let b = wif
+            .from_base58()
+            .map_err(|error| WifKeyError::Crate("base58", format!("{:?}", error)))?;
 
         if version == 0x00 {
-            version = WIFVersion
+            version = WIF_VERSION
         }
 
-        if b[0] != version {
-            return Err(Error::new(ErrorKind::Invalid, "invalid WIF version got {}, expected {}", b[0], version));
+        // version, key, optional compression flag and checksum.
+        if b.len() != 37 && b.len() != 38 {
+            return Err(WifKeyError::InvalidByteLength(b.len()));
         }
 
-        let mut w = WIF {
-            version,
-            compressed: false,
-            private_key: PrivateKey::from_str(wif).unwrap(),
-            s: wif.to_string(),
-        };
+        let (data, sum) = b.split_at(b.len() - 4);
+        if checksum(data)[0..4] != *sum {
+            return Err(WifKeyError::InvalidChecksum);
+        }
 
-        // This is an uncompressed WIF.
-        if b.len() == 33 {
-            w.compressed = false;
-            return Ok(w);
+        if data[0] != version {
+            return Err(WifKeyError::InvalidVersion(version, data[0]));
         }
 
-        if b.len() != 34 {
-            return Err(WifKeyError::InvalidByteLength(b.len()));
+        let mut private_key = [0u8; PRIVATE_KEY_BIN_LEN];
+        private_key.copy_from_slice(&data[1..33]);
+
+        // This is an uncompressed WIF.
+        if data.len() == 33 {
+            return Ok(WIF { version, compressed: false, private_key: PrivateKey(private_key) });
         }
 
         // Check the compression flag.
-        if b[33] != 0x01 {
+        if data[33] != 0x01 {
             return Err(WifKeyError::UnsupportedFormat);
         }
-        w.compressed = true;
-        Ok(w)
+        Ok(WIF { version, compressed: true, private_key: PrivateKey(private_key) })
     }
 }
 
-
-impl KeyTrait for WIF{
-    fn deserialize(&self, hex: &str) -> Result<_, dyn Error> {
-        unimplemented!()
-    }
-
-    fn serialize(&self) -> Result<String, dyn Error> {
-        unimplemented!()
-    }
-
-    fn to_hex(&self) -> String {
-        unimplemented!()
-    }
-
-    fn to_slice(&self) -> &[u8] {
-        unimplemented!()
-    }
-
-    fn equals(&self, other: &_) -> bool {
-        unimplemented!()
+impl fmt::Display for WIF {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let wif = WIF::wif_encode(&self.private_key.0, self.version, self.compressed).map_err(|_| fmt::Error)?;
+        write!(f, "{}", wif)
     }
 }
 
@@ -118,9 +123,49 @@ pub enum WifKeyError {
     #[fail(display = "invalid character length: {}", _0)]
     InvalidCharacterLength(usize),
 
+    #[fail(display = "invalid WIF checksum")]
+    InvalidChecksum,
+
+    #[fail(display = "invalid WIF version: {{ expected: {:#04x}, found: {:#04x} }}", _0, _1)]
+    InvalidVersion(u8, u8),
+
     #[fail(display = "{}", _0)]
     Message(String),
 
     #[fail(display = "unsupported format")]
     UnsupportedFormat,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::test_vectors::*;
+
+    use super::*;
+
+    #[test]
+    fn test_wif_encode() {
+        let private_key = PrivateKey::from_str(PRIVATE_KEY).unwrap();
+        assert_eq!(WIF::wif_encode(&private_key.0, 0, true).unwrap(), WIF);
+        assert_eq!(WIF::new(private_key).to_string(), WIF);
+        assert!(WIF::wif_encode(&private_key.0[1..], 0, true).is_err());
+    }
+
+    #[test]
+    fn test_wif_decode() {
+        let wif = WIF::wif_decode(WIF, 0).unwrap();
+        assert_eq!(wif.private_key().to_string(), PRIVATE_KEY);
+        assert_eq!(wif.version(), WIF_VERSION);
+        assert!(wif.is_compressed());
+
+        let uncompressed = WIF::wif_encode(&wif.private_key().0, 0, false).unwrap();
+        let decoded = WIF::wif_decode(&uncompressed, 0).unwrap();
+        assert!(!decoded.is_compressed());
+        assert_eq!(decoded.to_string(), uncompressed);
+
+        assert!(WIF::wif_decode(WIF, 0x81).is_err());
+        assert!(WIF::wif_decode(&WIF[1..], 0).is_err());
+        assert!(WIF::wif_decode(N3_ADDRESS, 0).is_err());
+    }
+}
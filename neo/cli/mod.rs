@@ -1,4 +1,5 @@
 pub mod neo;
+pub mod vm;
 
 pub mod parameters;
 pub use self::parameters::*;
@@ -42,4 +42,20 @@ pub const EXTENDED_PUBLIC: OptionType = (
     &["account", "count", "extended private", "index", "mnemonic", "password"],
     &[],
     &[],
-);
\ No newline at end of file
+);
+
+// Debug
+
+pub const SCRIPT: OptionType = (
+    "<script> 'The script to debug, in hex or base64, or the path of a file holding it'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const BREAK: OptionType = (
+    "[break] -b --break=[break]... 'Adds a breakpoint, <offset> in the entry script or <hash>:<offset>'",
+    &[],
+    &[],
+    &[],
+);
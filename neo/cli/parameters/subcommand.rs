@@ -1,3 +1,14 @@
 use crate::cli::{option, types::*};
 
-use clap::AppSettings;
\ No newline at end of file
+use clap::AppSettings;
+
+pub const DEBUG: SubCommandType = (
+    "debug",
+    "Runs a script in the VM debugger, with the core interop services (include -h for more options)",
+    &[option::SCRIPT, option::BREAK],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
@@ -0,0 +1,282 @@
+use crate::cli::{subcommand, types::*, CLIError, CLI};
+
+use neo_io::UInt160;
+use neo_persistence::{DataCache, MemoryStore, Store};
+use neo_sc::application_engine::{script_hash, ApplicationEngine, ApplicationHost, TEST_MODE_GAS};
+use neo_sc::interop::InteropService;
+use neo_sc::json_serializer;
+use neo_sc::trigger_type::TriggerType;
+use neo_vm::Debugger::Debugger;
+use neo_vm::Disassembler::{disassemble, format_instruction};
+use neo_vm::ExecutionEngineLimits::ExecutionEngineLimits;
+use neo_vm::Script::Script;
+use neo_vm::Types::StackItem::StackItem;
+use neo_vm::VMState::VMState;
+
+use clap::{ArgMatches, Values};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::{fs, path::Path, str::FromStr};
+
+const HELP: &str = "\
+  c, continue          Runs to the next breakpoint or the end of the script
+  s, step              Steps into the next instruction
+  n, next              Steps over the next instruction
+  o, out               Steps out of the current method
+  b, break <bp>        Adds a breakpoint, <offset> in the entry script or <hash>:<offset>
+  d, delete <bp>       Removes a breakpoint
+  stack [depth]        Prints the evaluation stack of a context, 0 is the current one
+  locals [depth]       Prints the local variables of a context
+  args [depth]         Prints the arguments of a context
+  statics [depth]      Prints the static fields of a context
+  bt                   Prints the invocation stack
+  l, list              Disassembles the current script
+  r, result            Prints the result stack
+  q, quit              Exits the debugger";
+
+/// Represents options for the VM commands
+#[derive(Clone, Debug, Default)]
+pub struct VmOptions {
+    subcommand: Option<String>,
+    script: Option<String>,
+    break_points: Vec<String>,
+}
+
+impl VmOptions {
+    fn parse(&mut self, arguments: &ArgMatches, options: &[&str]) {
+        options.iter().for_each(|option| match *option {
+            "break" => self.break_points(arguments.values_of(option)),
+            "script" => self.script(arguments.value_of(option)),
+            _ => (),
+        });
+    }
+
+    fn break_points(&mut self, argument: Option<Values>) {
+        if let Some(break_points) = argument {
+            self.break_points = break_points.map(String::from).collect();
+        }
+    }
+
+    fn script(&mut self, argument: Option<&str>) {
+        if let Some(script) = argument {
+            self.script = Some(script.to_string());
+        }
+    }
+}
+
+/// Reads a script from a file, or from hex or base64 text.
+fn read_script(script: &str) -> Result<Vec<u8>, CLIError> {
+    if Path::new(script).is_file() {
+        return fs::read(script).map_err(|error| CLIError::Crate("fs", error.to_string()));
+    }
+    let hex = script.strip_prefix("0x").unwrap_or(script);
+    hex::decode(hex)
+        .or_else(|_| base64::decode(script))
+        .map_err(|_| CLIError::Crate("vm", format!("{} is neither a file, hex nor base64", script)))
+}
+
+type VmDebugger<'a> = Debugger<'a, ApplicationHost, UInt160>;
+
+/// Parses `<offset>`, in the entry script, or `<hash>:<offset>`.
+fn parse_break_point(debugger: &VmDebugger, break_point: &str) -> Result<(UInt160, usize), CLIError> {
+    let (hash, offset) = match break_point.split_once(':') {
+        Some((hash, offset)) => {
+            let hash = UInt160::from_str(hash).map_err(|_| CLIError::Crate("vm", format!("invalid hash {}", hash)))?;
+            (hash, offset)
+        }
+        None => {
+            let depth = debugger.engine().invocation_stack().len().saturating_sub(1);
+            let hash = debugger
+                .script_hash(depth)
+                .ok_or_else(|| CLIError::Crate("vm", "no script is loaded".into()))?;
+            (hash, break_point)
+        }
+    };
+    Ok((hash, offset.parse()?))
+}
+
+fn print_items(items: Option<Vec<StackItem>>) {
+    match items {
+        Some(items) => {
+            for (index, item) in items.iter().enumerate() {
+                match json_serializer::to_json(item, usize::MAX) {
+                    Ok(json) => println!("  {}: {}", index, json),
+                    Err(_) => println!("  {}: {:?}", index, item.get_type()),
+                }
+            }
+        }
+        None => println!("  none"),
+    }
+}
+
+/// Prints the state of the engine and the next instruction when it is paused.
+fn print_position(debugger: &VmDebugger, names: &HashMap<u32, String>) {
+    let engine = debugger.engine();
+    match engine.state() {
+        VMState::FAULT => match engine.fault_exception() {
+            Some(error) => println!("FAULT: {}", error),
+            None => println!("FAULT"),
+        },
+        VMState::HALT => println!("HALT, {} result(s)", engine.result_stack().count()),
+        state => {
+            let context = match debugger.context(0) {
+                Some(context) => context,
+                None => return println!("{:?}", state),
+            };
+            let hash = debugger.script_hash(0).unwrap();
+            match context.current_instruction() {
+                Ok(instruction) => println!(
+                    "{} {}",
+                    hash,
+                    format_instruction(&instruction, context.instruction_pointer(), names).trim_start()
+                ),
+                Err(error) => println!("{} {}: {}", hash, context.instruction_pointer(), error),
+            }
+        }
+    }
+}
+
+fn run_command(debugger: &mut VmDebugger, names: &HashMap<u32, String>, line: &str) -> Result<bool, CLIError> {
+    let mut words = line.split_whitespace();
+    let command = match words.next() {
+        Some(command) => command,
+        None => return Ok(true),
+    };
+    let argument = words.next();
+    let depth = || -> Result<usize, CLIError> { Ok(argument.map(str::parse).transpose()?.unwrap_or(0)) };
+    match command {
+        "c" | "continue" => {
+            debugger.execute();
+            print_position(debugger, names);
+        }
+        "s" | "step" => {
+            debugger.step_into();
+            print_position(debugger, names);
+        }
+        "n" | "next" => {
+            debugger.step_over();
+            print_position(debugger, names);
+        }
+        "o" | "out" => {
+            debugger.step_out();
+            print_position(debugger, names);
+        }
+        "b" | "break" | "d" | "delete" => {
+            let break_point = argument.ok_or_else(|| CLIError::Crate("vm", "missing breakpoint".into()))?;
+            let (hash, offset) = parse_break_point(debugger, break_point)?;
+            if command.starts_with('b') {
+                debugger.add_break_point(hash, offset);
+            } else if !debugger.remove_break_point(&hash, offset) {
+                println!("no breakpoint at {}:{}", hash, offset);
+            }
+        }
+        "stack" => print_items(debugger.evaluation_stack(depth()?)),
+        "locals" => print_items(debugger.local_variables(depth()?)),
+        "args" => print_items(debugger.arguments(depth()?)),
+        "statics" => print_items(debugger.static_fields(depth()?)),
+        "bt" => {
+            for depth in 0..debugger.engine().invocation_stack().len() {
+                let context = debugger.context(depth).unwrap();
+                println!(
+                    "  {}: {} {}",
+                    depth,
+                    debugger.script_hash(depth).unwrap(),
+                    context.instruction_pointer()
+                );
+            }
+        }
+        "l" | "list" => {
+            if let Some(context) = debugger.context(depth()?) {
+                let text = disassemble(context.script().as_bytes(), names)
+                    .map_err(|error| CLIError::Crate("vm", error.to_string()))?;
+                let ip = context.instruction_pointer();
+                for line in text.lines() {
+                    let offset: usize = line.split_whitespace().next().unwrap().parse()?;
+                    println!("{} {}", if offset == ip { "=>" } else { "  " }, line);
+                }
+            }
+        }
+        "r" | "result" => print_items(Some(debugger.engine().result_stack().iter().rev().cloned().collect())),
+        "h" | "help" => println!("{}", HELP),
+        "q" | "quit" => return Ok(false),
+        _ => println!("unknown command {}, h for help", command),
+    }
+    Ok(true)
+}
+
+/// Runs `script` in an `ApplicationEngine` with the core interop services, paused before the first instruction.
+fn debug(script: Vec<u8>, break_points: &[String]) -> Result<(), CLIError> {
+    let snapshot = DataCache::new(MemoryStore::new().get_snapshot());
+    let host = ApplicationHost::new(TriggerType::APPLICATION, None, snapshot, None, TEST_MODE_GAS);
+    let mut engine = ApplicationEngine::with_host(ExecutionEngineLimits::default(), host);
+    engine
+        .load_script(Script::from(script), -1, 0)
+        .map_err(|error| CLIError::Crate("vm", error.to_string()))?;
+    let names = InteropService::new().names();
+    let mut debugger = Debugger::with_script_hash(&mut engine, script_hash);
+    for break_point in break_points {
+        let (hash, offset) = parse_break_point(&debugger, break_point)?;
+        debugger.add_break_point(hash, offset);
+    }
+
+    print_position(&debugger, &names);
+    let stdin = io::stdin();
+    loop {
+        print!("(neovm) ");
+        io::stdout()
+            .flush()
+            .map_err(|error| CLIError::Crate("io", error.to_string()))?;
+        let mut line = String::new();
+        if stdin
+            .lock()
+            .read_line(&mut line)
+            .map_err(|error| CLIError::Crate("io", error.to_string()))?
+            == 0
+        {
+            return Ok(());
+        }
+        match run_command(&mut debugger, &names, &line) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(error) => println!("{}", error),
+        }
+    }
+}
+
+pub struct VmCLI;
+
+impl CLI for VmCLI {
+    type Options = VmOptions;
+
+    const NAME: NameType = "vm";
+    const ABOUT: AboutType = "Runs NeoVM scripts (include -h for more options)";
+    const FLAGS: &'static [FlagType] = &[];
+    const OPTIONS: &'static [OptionType] = &[];
+    const SUBCOMMANDS: &'static [SubCommandType] = &[subcommand::DEBUG];
+
+    #[cfg_attr(tarpaulin, skip)]
+    fn parse(arguments: &ArgMatches) -> Self::Options {
+        let mut options = VmOptions::default();
+
+        if let ("debug", Some(arguments)) = arguments.subcommand() {
+            options.subcommand = Some("debug".into());
+            options.parse(arguments, &["break", "script"]);
+        }
+
+        options
+    }
+
+    #[cfg_attr(tarpaulin, skip)]
+    fn print(options: Self::Options) {
+        fn output(options: VmOptions) -> Result<(), CLIError> {
+            match (options.subcommand.as_deref(), options.script) {
+                (Some("debug"), Some(script)) => debug(read_script(&script)?, &options.break_points),
+                _ => Ok(()),
+            }
+        }
+
+        if let Err(error) = output(options) {
+            eprintln!("{}", error);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use neo::cli::neo::NeoCLI;
+use neo::cli::vm::VmCLI;
 use neo::cli::CLI;
 
 use clap::{App, AppSettings};
@@ -17,9 +18,13 @@ fn main() {
         ])
         .subcommands(vec![
             NeoCLI::new(),
+            VmCLI::new(),
         ])
         .set_term_width(0)
         .get_matches();
 
-        NeoCLI::print(NeoCLI::parse(&arguments));
+        match arguments.subcommand_name() {
+            Some("vm") => VmCLI::print(VmCLI::parse(arguments.subcommand_matches("vm").unwrap())),
+            _ => NeoCLI::print(NeoCLI::parse(&arguments)),
+        }
 }
@@ -17,12 +17,6 @@ pub enum AssetType {
     Token = 0x60, // (= CreditFlag | 0x20)
 }
 
-pub const CONTRACTS_RPX: &str = "ecc6b20d3ccac1ee9ef109af5a7cdb85706b1df9";
-pub const CONTRACTS_TEST_RPX: &str = "5b7074e873973a6ed3708862f219a6fbf4d1c411";
-pub const CONTRACTS_TEST_LWTF: &str = "d7678dd97c000be3f33e9362e673101bac4ca654";
-pub const CONTRACTS_TEST_NXT: &str = "0b6c1f919e95fe61c17a7612aebfaf4fda3a2214";
-pub const CONTRACTS_TEST_RHTT4: &str = "f9572c5b119a6b5775a6af07f1cef5d310038f55";
-
 pub const DEFAULT_RPC_MAIN: &str = "https://seed11.ngd.network:10331";
 pub const DEFAULT_RPC_TEST: &str = "https://seed11.ngd.network:20331";
 
@@ -14,6 +14,7 @@ use neo_crypto::{base58::{FromBase58, ToBase58},
 
 use crate::neo_type::{AddressHex, PRIVATE_KEY_BIN_LEN, PrivateKeyBin, PUBLIC_KEY_BIN_LEN, PublicKeyBin, WIF_KEY_BIN_LEN};
 use crate::no_std::*;
+use crate::protocol_settings::ProtocolSettings;
 use crate::utilities::crypto::{checksum, hash160};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -26,6 +27,11 @@ pub struct KeyPair {
 
 impl KeyPair {
     pub fn new() -> KeyPair {
+        KeyPair::generate(&ProtocolSettings::default())
+    }
+
+    /// Generates a random key pair, its address is the one of `settings`.
+    pub fn generate(settings: &ProtocolSettings) -> KeyPair {
         let mut ecdsa = ECECDSA::from_suite(CipherSuite::P256_SHA256_TAI).unwrap();
         let mut rng = rand::thread_rng();
         let mut sec_key = [0u8; 32];
@@ -36,33 +42,13 @@ impl KeyPair {
 
         let mut pub_key = ecdsa.derive_public_key(&sec_key.as_slice()).unwrap();
 
-        let mut script: Vec<u8> = Vec::new();
-        script.push(33);
-        script.extend(&pub_key);
-        script.push(172);
-
-        let hs = hash160(&script);
-
-        let mut addr = [0u8; 25];
-        addr[0] = 23;
-        addr[1..21].copy_from_slice(&hs);
-
-        let sum = &checksum(&addr[0..21])[0..4];
-
-        addr[21..25].copy_from_slice(sum);
-        // println!("{}",&pub_key.len());
-
-        // let pubkk = pub_key.clone();
         let mut pubk = [0u8; 33];
         pubk.clone_from_slice(pub_key.as_slice());
 
-
-        let add = addr.to_base58();
-
         KeyPair {
             private_key: sec_key.clone(),
             public_key: pubk.clone(),
-            address: String::from(add),
+            address: settings.address_from_public_key(&pubk),
         }
     }
 
@@ -77,8 +63,12 @@ impl KeyPair {
     }
 
     pub fn get_key_pair_from_private_key(pri_key: &[u8]) -> KeyPair {
+        KeyPair::get_key_pair_from_private_key_with(pri_key, &ProtocolSettings::default())
+    }
+
+    pub fn get_key_pair_from_private_key_with(pri_key: &[u8], settings: &ProtocolSettings) -> KeyPair {
         let pub_key = KeyPair::get_public_key_from_private_key(&pri_key).unwrap().clone();
-        let mut addr = KeyPair::get_address_from_public_key(&pub_key).unwrap();
+        let mut addr = settings.address_from_public_key(&pub_key);
 
         Self {
             private_key: <[u8; 32]>::try_from(pri_key).unwrap(),
@@ -161,20 +151,11 @@ impl KeyPair {
     }
 
     pub fn get_address_from_public_key(pub_key: &[u8]) -> Result<String, Error> {
-        let mut script: Vec<u8> = Vec::new();
-        script.push(33);
-        script.extend(pub_key);
-        script.push(172);
-
-        let hs = hash160(&script);
-        let mut addr = [0u8; 25];
-        addr[0] = 23;
-        addr[1..21].copy_from_slice(&hs);
-
-        let sum = &checksum(&addr[0..21])[0..4];
-        addr[21..25].copy_from_slice(sum);
+        KeyPair::get_address_from_public_key_with(pub_key, &ProtocolSettings::default())
+    }
 
-        Ok(addr.to_base58())
+    pub fn get_address_from_public_key_with(pub_key: &[u8], settings: &ProtocolSettings) -> Result<String, Error> {
+        Ok(settings.address_from_public_key(pub_key))
     }
 
     pub fn get_private_key_from_key_pair(key_pair_key: Vec<u8>, passphrase: Vec<u8>) {}
@@ -273,7 +254,7 @@ mod tests {
     use neo_crypto::ecdsa::{CipherSuite, ECECDSA};
     use neo_crypto::hex;
 
-    use crate::KeyPair;
+    use crate::{KeyPair, ProtocolSettings};
 
     #[test]
     pub fn test_get_pub_key_from_private_key() {
@@ -292,7 +273,10 @@ mod tests {
         let public_key = "03f9e9a50af13ccec64feedb45d558815ba6d3a3e8c3a727be7f97bb9eeca80f52";
 
         let pub_key = hex::decode(public_key).unwrap();
-        let addr = KeyPair::get_address_from_public_key(&pub_key).unwrap();
+        let addr = KeyPair::get_address_from_public_key_with(&pub_key, &ProtocolSettings::N2_MAINNET).unwrap();
         assert_eq!(addr, "AHV5J1bVXAvM3eVDrCXx34U1QQnNKeKX1F");
+
+        let addr = KeyPair::get_address_from_public_key(&pub_key).unwrap();
+        assert_eq!(addr, "NR49ZNznVEspQ9zcozU6NRnaMx4EChSJkp");
     }
 }
\ No newline at end of file
@@ -1,4 +1,3 @@
-#![feature(array_methods)]
 //! # Model
 //!
 //! A neo_core for cryptocurrency wallets.
@@ -26,6 +25,10 @@ pub mod neo_type;
 pub mod key_pair;
 pub use self::key_pair::*;
 
+pub mod protocol_version;
+pub mod protocol_settings;
+pub use self::protocol_settings::ProtocolSettings;
+
 pub mod misc;
 pub mod fixed8;
 pub mod stringstream;
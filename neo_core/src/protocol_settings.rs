@@ -0,0 +1,376 @@
+use neo_crypto::base58::{FromBase58, ToBase58};
+use neo_crypto::sha2::{Digest, Sha256};
+
+use crate::neo_type::{PublicKeyBin, ScriptHashBin, ADDRESS_BIN_LEN, SCRIPT_HASH_BIN_LEN};
+pub use crate::protocol_version::ProtocolVersion;
+use crate::utilities::crypto::{checksum, hash160};
+
+/// Address version of Neo2 networks, addresses start with `A`.
+pub const ADDRESS_VERSION_N2: u8 = 0x17;
+/// Address version of Neo N3 networks, addresses start with `N`.
+pub const ADDRESS_VERSION_N3: u8 = 0x35;
+
+/// Maximum number of public keys of a multi-signature account.
+pub const MAX_MULTISIG_KEYS: usize = 1024;
+
+/**
+ * The settings of a network that addresses and scripts depend on.
+ *
+ * The same key has a different verification script, script hash and
+ * address on Neo2 and Neo N3, everything deriving them takes these
+ * settings. The default is N3 main net.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProtocolSettings {
+    pub version: ProtocolVersion,
+    /// Magic number of the network.
+    pub network: u32,
+    /// First byte of the base58 encoded addresses.
+    pub address_version: u8,
+}
+
+impl ProtocolSettings {
+    pub const N2_MAINNET: ProtocolSettings = ProtocolSettings {
+        version: ProtocolVersion::N2,
+        network: 7630401,
+        address_version: ADDRESS_VERSION_N2,
+    };
+
+    pub const N3_MAINNET: ProtocolSettings = ProtocolSettings {
+        version: ProtocolVersion::N3,
+        network: 860833102,
+        address_version: ADDRESS_VERSION_N3,
+    };
+
+    pub const N3_TESTNET: ProtocolSettings = ProtocolSettings {
+        version: ProtocolVersion::N3,
+        network: 894710606,
+        address_version: ADDRESS_VERSION_N3,
+    };
+
+    /// The main net settings of the protocol `address` belongs to, judging by its address version.
+    pub fn from_address(address: &str) -> Option<ProtocolSettings> {
+        match *address.from_base58().ok()?.first()? {
+            ADDRESS_VERSION_N2 => Some(ProtocolSettings::N2_MAINNET),
+            ADDRESS_VERSION_N3 => Some(ProtocolSettings::N3_MAINNET),
+            _ => None,
+        }
+    }
+
+    /// The verification script of the standard account of `public_key`, a compressed key.
+    pub fn verification_script(&self, public_key: &[u8]) -> Vec<u8> {
+        self.version.verification_script(public_key)
+    }
+
+    /**
+     * The verification script of the `m` out of `public_keys.len()` multi-signature account.
+     *
+     * Keys are sorted like the reference implementation does, so every
+     * co-signer gets the same script whatever order they list the keys in.
+     */
+    pub fn multisig_verification_script(
+        &self,
+        m: usize,
+        public_keys: &[PublicKeyBin],
+    ) -> Result<Vec<u8>, MultisigError> {
+        let n = public_keys.len();
+        if m == 0 || m > n || n > MAX_MULTISIG_KEYS {
+            return Err(MultisigError::InvalidThreshold(m, n));
+        }
+
+        let mut keys = public_keys.to_vec();
+        keys.sort_by(|a, b| (&a[1..], a[0]).cmp(&(&b[1..], b[0])));
+        if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(MultisigError::DuplicateKey);
+        }
+
+        let mut script = Vec::with_capacity(n * 35 + 12);
+        self.push_count(&mut script, m);
+        for key in &keys {
+            match self.version {
+                ProtocolVersion::N2 => script.push(key.len() as u8),
+                ProtocolVersion::N3 => script.extend_from_slice(&[0x0c, key.len() as u8]),
+            }
+            script.extend_from_slice(key);
+        }
+        self.push_count(&mut script, n);
+        match self.version {
+            ProtocolVersion::N2 => script.push(0xae),
+            ProtocolVersion::N3 => {
+                script.push(0x41);
+                script.extend_from_slice(&syscall_hash("System.Crypto.CheckMultisig"));
+            }
+        }
+        Ok(script)
+    }
+
+    /// The script hash of the `m` out of `public_keys.len()` multi-signature account.
+    pub fn multisig_script_hash(&self, m: usize, public_keys: &[PublicKeyBin]) -> Result<ScriptHashBin, MultisigError> {
+        Ok(to_script_hash(&self.multisig_verification_script(m, public_keys)?))
+    }
+
+    /// The address of the `m` out of `public_keys.len()` multi-signature account.
+    pub fn multisig_address(&self, m: usize, public_keys: &[PublicKeyBin]) -> Result<String, MultisigError> {
+        Ok(self.address_from_script_hash(&self.multisig_script_hash(m, public_keys)?))
+    }
+
+    /// Pushes a count of at most `MAX_MULTISIG_KEYS` with the smallest opcode.
+    fn push_count(&self, script: &mut Vec<u8>, count: usize) {
+        match (self.version, count) {
+            (ProtocolVersion::N2, 1..=16) => script.push(0x50 + count as u8),
+            (ProtocolVersion::N3, 0..=16) => script.push(0x10 + count as u8),
+            (ProtocolVersion::N2, 17..=127) => script.extend_from_slice(&[0x01, count as u8]),
+            (ProtocolVersion::N3, 17..=127) => script.extend_from_slice(&[0x00, count as u8]),
+            (ProtocolVersion::N2, _) => {
+                script.push(0x02);
+                script.extend_from_slice(&(count as u16).to_le_bytes());
+            }
+            (ProtocolVersion::N3, _) => {
+                script.push(0x01);
+                script.extend_from_slice(&(count as u16).to_le_bytes());
+            }
+        }
+    }
+
+    /// The script hash of the standard account of `public_key`.
+    pub fn script_hash(&self, public_key: &[u8]) -> ScriptHashBin {
+        to_script_hash(&self.verification_script(public_key))
+    }
+
+    /// The address of `script_hash`, in its little-endian byte order.
+    pub fn address_from_script_hash(&self, script_hash: &ScriptHashBin) -> String {
+        let mut addr = [0u8; ADDRESS_BIN_LEN];
+        addr[0] = self.address_version;
+        addr[1..21].copy_from_slice(script_hash);
+
+        let sum = &checksum(&addr[0..21])[0..4];
+        addr[21..25].copy_from_slice(sum);
+
+        addr.to_base58()
+    }
+
+    /// The address of the standard account of `public_key`.
+    pub fn address_from_public_key(&self, public_key: &[u8]) -> String {
+        self.address_from_script_hash(&self.script_hash(public_key))
+    }
+
+    /// The script hash of `address`, which must have the address version of this network.
+    pub fn address_to_script_hash(&self, address: &str) -> Result<ScriptHashBin, AddressFormatError> {
+        let data = address.from_base58().map_err(|_| AddressFormatError::InvalidBase58)?;
+        if data.len() != ADDRESS_BIN_LEN {
+            return Err(AddressFormatError::InvalidByteLength(data.len()));
+        }
+        if data[0] != self.address_version {
+            return Err(AddressFormatError::InvalidVersion(self.address_version, data[0]));
+        }
+        if checksum(&data[0..21])[0..4] != data[21..25] {
+            return Err(AddressFormatError::InvalidChecksum);
+        }
+
+        let mut script_hash = [0u8; SCRIPT_HASH_BIN_LEN];
+        script_hash.copy_from_slice(&data[1..21]);
+        Ok(script_hash)
+    }
+}
+
+impl Default for ProtocolSettings {
+    fn default() -> Self {
+        ProtocolSettings::N3_MAINNET
+    }
+}
+
+/// The hash160 of `script`.
+pub fn to_script_hash(script: &[u8]) -> ScriptHashBin {
+    let mut script_hash = [0u8; SCRIPT_HASH_BIN_LEN];
+    script_hash.copy_from_slice(&hash160(script));
+    script_hash
+}
+
+/// The operand of `SYSCALL` for the interop service `name`.
+pub fn syscall_hash(name: &str) -> [u8; 4] {
+    let mut hash = [0u8; 4];
+    hash.copy_from_slice(&Sha256::digest(name.as_bytes())[0..4]);
+    hash
+}
+
+#[derive(Debug, Fail)]
+pub enum AddressFormatError {
+    #[fail(display = "invalid base58 string")]
+    InvalidBase58,
+
+    #[fail(display = "invalid byte length: {}", _0)]
+    InvalidByteLength(usize),
+
+    #[fail(display = "invalid address version: {{ expected: {:?}, found: {:?} }}", _0, _1)]
+    InvalidVersion(u8, u8),
+
+    #[fail(display = "invalid address checksum")]
+    InvalidChecksum,
+}
+
+#[derive(Debug, Fail)]
+pub enum MultisigError {
+    #[fail(display = "invalid multi-signature threshold: {} out of {} keys", _0, _1)]
+    InvalidThreshold(usize, usize),
+
+    #[fail(display = "duplicate public key")]
+    DuplicateKey,
+}
+
+#[cfg(test)]
+mod tests {
+    use neo_crypto::hex;
+
+    use super::*;
+
+    /// (public key, N2 address, N3 address, N3 script hash in big-endian order)
+    const ACCOUNTS: [(&str, &str, &str, &str); 2] = [
+        (
+            "03f9e9a50af13ccec64feedb45d558815ba6d3a3e8c3a727be7f97bb9eeca80f52",
+            "AHV5J1bVXAvM3eVDrCXx34U1QQnNKeKX1F",
+            "NR49ZNznVEspQ9zcozU6NRnaMx4EChSJkp",
+            "557dc46cda69721e23ace6b709e45eda8b9d6438",
+        ),
+        (
+            "02208aea0068c429a03316e37be0e3e8e21e6cda5442df4c5914a19b3a9b6de375",
+            "ANTQRzAxtjp7wyvWc2Y9SLkfxTkyRKuioR",
+            "Nari61wgSjpn2b8958ticxYqMWJ3k1EB6o",
+            "1ad0b8400ff864ecb709d8c804184095ef08eca3",
+        ),
+    ];
+
+    /// 1 out of 2 multi-signature account of the keys above.
+    const MULTISIG_ADDRESS: &str = "NcUEjS6FSZKEp83RK8gE8iM8ytFbAc6TCb";
+
+    #[test]
+    fn test_verification_script() {
+        let public_key = hex::decode(ACCOUNTS[1].0).unwrap();
+        assert_eq!(
+            hex::encode(ProtocolSettings::N3_MAINNET.verification_script(&public_key)),
+            "0c2102208aea0068c429a03316e37be0e3e8e21e6cda5442df4c5914a19b3a9b6de3754156e7b327"
+        );
+        assert_eq!(
+            hex::encode(ProtocolSettings::N2_MAINNET.verification_script(&public_key)),
+            "2102208aea0068c429a03316e37be0e3e8e21e6cda5442df4c5914a19b3a9b6de375ac"
+        );
+    }
+
+    #[test]
+    fn test_check_sig() {
+        assert_eq!(
+            crate::protocol_version::CHECK_SIG,
+            syscall_hash("System.Crypto.CheckSig")
+        );
+        for version in [ProtocolVersion::N2, ProtocolVersion::N3].iter() {
+            assert_eq!(ProtocolVersion::from_u8(*version as u8), Some(*version));
+        }
+        assert_eq!(ProtocolVersion::from_u8(2), None);
+    }
+
+    #[test]
+    fn test_addresses() {
+        for (public_key, n2_address, n3_address, n3_script_hash) in ACCOUNTS.iter() {
+            let public_key = hex::decode(public_key).unwrap();
+            assert_eq!(
+                ProtocolSettings::N2_MAINNET.address_from_public_key(&public_key),
+                *n2_address
+            );
+            assert_eq!(
+                ProtocolSettings::default().address_from_public_key(&public_key),
+                *n3_address
+            );
+            assert_eq!(
+                ProtocolSettings::N3_TESTNET.address_from_public_key(&public_key),
+                *n3_address
+            );
+
+            let mut script_hash = ProtocolSettings::N3_MAINNET.script_hash(&public_key);
+            assert_eq!(
+                ProtocolSettings::N3_MAINNET.address_to_script_hash(n3_address).unwrap(),
+                script_hash
+            );
+            script_hash.reverse();
+            assert_eq!(hex::encode(script_hash), *n3_script_hash);
+        }
+    }
+
+    #[test]
+    fn test_from_address() {
+        assert_eq!(
+            ProtocolSettings::from_address(ACCOUNTS[0].1),
+            Some(ProtocolSettings::N2_MAINNET)
+        );
+        assert_eq!(
+            ProtocolSettings::from_address(ACCOUNTS[0].2),
+            Some(ProtocolSettings::N3_MAINNET)
+        );
+        assert_eq!(ProtocolSettings::from_address("0"), None);
+    }
+
+    #[test]
+    fn test_address_to_script_hash_errors() {
+        let n2_address = ACCOUNTS[0].1;
+        assert!(matches!(
+            ProtocolSettings::N3_MAINNET.address_to_script_hash(n2_address),
+            Err(AddressFormatError::InvalidVersion(0x35, 0x17))
+        ));
+        assert!(matches!(
+            ProtocolSettings::N3_MAINNET.address_to_script_hash("NR49ZNznVEspQ9zcozU6NRnaMx4EChSJkq"),
+            Err(AddressFormatError::InvalidChecksum)
+        ));
+        assert!(ProtocolSettings::N3_MAINNET.address_to_script_hash("N0").is_err());
+    }
+
+    #[test]
+    fn test_multisig() {
+        let keys = ACCOUNTS
+            .iter()
+            .map(|(public_key, ..)| to_array(&hex::decode(public_key).unwrap()))
+            .collect::<Vec<PublicKeyBin>>();
+        let reversed = keys.iter().rev().cloned().collect::<Vec<_>>();
+
+        let script = ProtocolSettings::N3_MAINNET
+            .multisig_verification_script(1, &keys)
+            .unwrap();
+        assert_eq!(
+            hex::encode(&script),
+            "110c2102208aea0068c429a03316e37be0e3e8e21e6cda5442df4c5914a19b3a9b6de375\
+             0c2103f9e9a50af13ccec64feedb45d558815ba6d3a3e8c3a727be7f97bb9eeca80f52\
+             12419ed0dc3a"
+        );
+        assert_eq!(
+            ProtocolSettings::N3_MAINNET
+                .multisig_verification_script(1, &reversed)
+                .unwrap(),
+            script
+        );
+        assert_eq!(
+            ProtocolSettings::N3_MAINNET.multisig_address(1, &keys).unwrap(),
+            MULTISIG_ADDRESS
+        );
+        assert_eq!(
+            hex::encode(
+                ProtocolSettings::N2_MAINNET
+                    .multisig_verification_script(2, &keys)
+                    .unwrap()
+            ),
+            "522102208aea0068c429a03316e37be0e3e8e21e6cda5442df4c5914a19b3a9b6de375\
+             2103f9e9a50af13ccec64feedb45d558815ba6d3a3e8c3a727be7f97bb9eeca80f5252ae"
+        );
+
+        assert!(matches!(
+            ProtocolSettings::N3_MAINNET.multisig_verification_script(3, &keys),
+            Err(MultisigError::InvalidThreshold(3, 2))
+        ));
+        assert!(matches!(
+            ProtocolSettings::N3_MAINNET.multisig_verification_script(1, &[keys[0], keys[0]]),
+            Err(MultisigError::DuplicateKey)
+        ));
+    }
+
+    fn to_array(data: &[u8]) -> PublicKeyBin {
+        let mut key = [0u8; 33];
+        key.copy_from_slice(data);
+        key
+    }
+}
@@ -0,0 +1,49 @@
+// The generations of the protocol and the verification script of their standard accounts.
+//
+// This file only needs `alloc` and has no inner attributes: the SGX wallet
+// enclave includes it as it is, so both derive the same addresses.
+
+/// `SYSCALL` operand of `System.Crypto.CheckSig`, the first 4 bytes of the SHA-256 of its name.
+pub const CHECK_SIG: [u8; 4] = [0x56, 0xe7, 0xb3, 0x27];
+
+/// Generation of the protocol, it decides how standard accounts are verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ProtocolVersion {
+    /// `PUSHBYTES33 <key> CHECKSIG`.
+    N2 = 0,
+    /// `PUSHDATA1 <key> SYSCALL System.Crypto.CheckSig`.
+    /// Multi-signature accounts call `System.Crypto.CheckMultisig` instead of `CHECKMULTISIG`.
+    N3 = 1,
+}
+
+impl ProtocolVersion {
+    /// The version whose `as u8` value is `value`.
+    pub fn from_u8(value: u8) -> Option<ProtocolVersion> {
+        match value {
+            0 => Some(ProtocolVersion::N2),
+            1 => Some(ProtocolVersion::N3),
+            _ => None,
+        }
+    }
+
+    /// The verification script of the standard account of `public_key`, a compressed key.
+    pub fn verification_script(self, public_key: &[u8]) -> Vec<u8> {
+        let mut script = Vec::with_capacity(public_key.len() + 8);
+        match self {
+            ProtocolVersion::N2 => {
+                script.push(public_key.len() as u8);
+                script.extend_from_slice(public_key);
+                script.push(0xac);
+            }
+            ProtocolVersion::N3 => {
+                script.push(0x0c);
+                script.push(public_key.len() as u8);
+                script.extend_from_slice(public_key);
+                script.push(0x41);
+                script.extend_from_slice(&CHECK_SIG);
+            }
+        }
+        script
+    }
+}